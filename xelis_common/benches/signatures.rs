@@ -0,0 +1,53 @@
+// Compare the signatures verified one by one and in batch
+// Run it using `cargo bench -p xelis_common --bench signatures`
+use std::{hint::black_box, time::{Duration, Instant}};
+use xelis_common::crypto::{
+    elgamal::SignatureBatchCollector,
+    KeyPair,
+    Signature
+};
+
+const ROUNDS: u32 = 20;
+
+// Average time of a verification over all the rounds
+fn measure<F: FnMut() -> bool>(mut verify: F) -> Duration {
+    // warm up the lazy statics
+    assert!(verify());
+
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        assert!(black_box(verify()));
+    }
+    start.elapsed() / ROUNDS
+}
+
+fn main() {
+    for count in [1, 8, 64, 256, 1024] {
+        let signatures = (0..count).map(|i: u32| {
+            let keypair = KeyPair::new();
+            let message = i.to_be_bytes();
+            let signature = keypair.sign(&message);
+            (keypair, message, signature)
+        }).collect::<Vec<(KeyPair, [u8; 4], Signature)>>();
+
+        let single = measure(|| {
+            signatures.iter().all(|(keypair, message, signature)| signature.verify(message, keypair.get_public_key()))
+        });
+
+        let batch = measure(|| {
+            let mut collector = SignatureBatchCollector::new();
+            for (keypair, message, signature) in signatures.iter() {
+                collector.add(signature, message, keypair.get_public_key());
+            }
+            collector.verify()
+        });
+
+        println!(
+            "{:>5} signatures: one by one {:>10.3?}, batch {:>10.3?} ({:.2}x)",
+            count,
+            single,
+            batch,
+            single.as_secs_f64() / batch.as_secs_f64()
+        );
+    }
+}
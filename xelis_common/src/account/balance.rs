@@ -5,7 +5,7 @@ use crate::serializer::{Serializer, ReaderError, Reader, Writer};
 
 use super::CiphertextCache;
 
-#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Serializer)]
 #[serde(rename_all = "snake_case")]
 pub enum BalanceType {
     // Only incoming funds were added
@@ -17,29 +17,6 @@ pub enum BalanceType {
     Both
 }
 
-impl Serializer for BalanceType {
-    fn write(&self, writer: &mut Writer) {
-        match self {
-            BalanceType::Input => writer.write_u8(0),
-            BalanceType::Output => writer.write_u8(1),
-            BalanceType::Both => writer.write_u8(2)
-        }
-    }
-
-    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
-        match reader.read_u8()? {
-            0 => Ok(BalanceType::Input),
-            1 => Ok(BalanceType::Output),
-            2 => Ok(BalanceType::Both),
-            _ => Err(ReaderError::InvalidValue)
-        }
-    }
-
-    fn size(&self) -> usize {
-        1
-    }
-}
-
 #[derive(Clone, Deserialize, Serialize, PartialEq, Eq, Debug)]
 pub struct VersionedBalance {
     // Output balance is used in case of multi TXs not in same block
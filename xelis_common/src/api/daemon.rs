@@ -1,21 +1,25 @@
 use std::{
     borrow::Cow,
     collections::{HashSet, HashMap},
-    net::SocketAddr
+    net::{IpAddr, SocketAddr}
 };
 use indexmap::IndexSet;
 use serde::{Deserialize, Serialize, Serializer, Deserializer, de::Error};
 use crate::{
     account::{CiphertextCache, VersionedBalance, VersionedNonce},
+    asset::{AssetData, AssetWithData},
     block::EXTRA_NONCE_SIZE,
+    capabilities::Capabilities,
+    contract::Program,
     crypto::{Address, Hash},
     difficulty::{CumulativeDifficulty, Difficulty},
     network::Network,
-    time::{TimestampMillis, TimestampSeconds}
+    time::{TimestampMillis, TimestampSeconds},
+    transaction::lock::TransferLock
 };
 use super::RPCTransaction;
 
-#[derive(Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy, Debug)]
 pub enum BlockType {
     Sync,
     Side,
@@ -139,10 +143,40 @@ pub struct GetMinerWorkResult {
     // topoheight of the daemon
     // this is for visual purposes only
     pub topoheight: u64,
-    // difficulty required for valid block POW
+    // difficulty required for a valid share POW
+    // it is the share difficulty of the miner, capped to the block difficulty
     pub difficulty: Difficulty
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct GetWorkMinerEntry<'a> {
+    pub address: Cow<'a, Address>,
+    pub name: Cow<'a, String>,
+    pub first_seen: TimestampMillis,
+    // difficulty currently expected for each share
+    pub share_difficulty: Difficulty,
+    pub shares_accepted: usize,
+    pub shares_rejected: usize,
+    pub blocks_accepted: usize,
+    pub blocks_rejected: usize,
+    // estimated from the accepted shares since it is connected
+    pub hashrate: f64,
+    pub jobs_sent: usize,
+    // timestamp of the last job sent to the miner
+    pub last_job_timestamp: Option<TimestampMillis>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GetWorkMinersResult<'a> {
+    // sum of the estimated hashrate of all miners
+    pub total_hashrate: f64,
+    pub total_shares_accepted: usize,
+    pub total_shares_rejected: usize,
+    // timestamp of the last job sent to any miner
+    pub last_job_timestamp: Option<TimestampMillis>,
+    pub miners: Vec<GetWorkMinerEntry<'a>>
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct SubmitMinerWorkParams {
     // hex: represent block miner in hexadecimal format
@@ -185,6 +219,25 @@ pub struct GetBalanceAtTopoHeightParams<'a> {
     pub topoheight: u64
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct GetBalanceHistoryParams<'a> {
+    pub address: Cow<'a, Address>,
+    #[serde(default = "default_xelis_asset")]
+    pub asset: Hash,
+    // Start from the version at or below this topoheight, last version if not set
+    pub maximum_topoheight: Option<u64>,
+    // Maximum versions to return
+    pub maximum: Option<usize>
+}
+
+// A version of the balance with the block in which it was changed
+#[derive(Serialize, Deserialize)]
+pub struct BalanceHistoryEntry {
+    pub topoheight: u64,
+    pub block_hash: Hash,
+    pub version: VersionedBalance
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct GetNonceParams<'a> {
     pub address: Cow<'a, Address>
@@ -215,6 +268,97 @@ pub struct HasNonceResult {
     pub exist: bool
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct GetMultiSigParams<'a> {
+    pub address: Cow<'a, Address>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GetMultiSigResult {
+    // Topoheight at which the multisig was set
+    pub topoheight: u64,
+    pub threshold: u8,
+    // Participants ordered by their signature id
+    pub participants: Vec<Address>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct HasMultiSigParams<'a> {
+    pub address: Cow<'a, Address>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GetLockedTransferParams<'a> {
+    pub id: Cow<'a, Hash>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GetLockedTransferResult {
+    // Topoheight at which the transfer was locked
+    pub topoheight: u64,
+    pub source: Address,
+    pub destination: Address,
+    pub asset: Hash,
+    pub lock: TransferLock
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GetContractParams<'a> {
+    pub contract: Cow<'a, Hash>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GetContractResult<'a> {
+    // Topoheight at which the contract was deployed
+    pub topoheight: u64,
+    pub program: Cow<'a, Program>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GetContractDataParams<'a> {
+    pub contract: Cow<'a, Hash>,
+    pub key: u64
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GetContractDataResult {
+    // Topoheight at which the value was last changed
+    pub topoheight: u64,
+    // None if the key is not set
+    pub value: Option<u64>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ReserveNonceParams<'a> {
+    pub address: Cow<'a, Address>,
+    // duration in seconds
+    #[serde(default)]
+    pub duration: Option<u64>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RenewNonceReservationParams<'a> {
+    pub address: Cow<'a, Address>,
+    pub id: u64,
+    // duration in seconds
+    #[serde(default)]
+    pub duration: Option<u64>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ReleaseNonceReservationParams<'a> {
+    pub address: Cow<'a, Address>,
+    pub id: u64
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct NonceReservationResult {
+    // id required to renew or release the reservation
+    pub id: u64,
+    pub nonce: u64,
+    pub expire_at: TimestampMillis
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct GetBalanceResult {
     pub version: VersionedBalance,
@@ -244,7 +388,112 @@ pub struct GetInfoResult {
     // software version on which the daemon is running
     pub version: String,
     // Network state (mainnet, testnet, devnet)
-    pub network: Network
+    pub network: Network,
+    // Features and runtime modes enabled on the daemon
+    #[serde(default)]
+    pub capabilities: Capabilities,
+    // Block version required for the next block
+    #[serde(default)]
+    pub block_version: u8
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct MempoolStatus {
+    pub tx_count: usize,
+    // sum of the transactions sizes in bytes
+    pub total_size: usize,
+    pub total_fees: u64
+}
+
+// Transactions of the mempool in a range of fee rate
+#[derive(Serialize, Deserialize)]
+pub struct FeeRateBucket {
+    // inclusive lower bound of the fee paid per kB
+    pub min_fee_per_kb: u64,
+    // exclusive upper bound, None for the last bucket
+    pub max_fee_per_kb: Option<u64>,
+    pub tx_count: usize,
+    // sum of the transactions sizes in bytes
+    pub total_size: usize
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct MempoolTransactionSummary {
+    pub hash: Hash,
+    pub fee: u64,
+    pub size: usize,
+    // fee paid per kB (1024 bytes) in atomic units
+    pub fee_per_kb: u64,
+    pub owner: Address,
+    pub nonce: u64,
+    pub first_seen: TimestampSeconds
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GetMempoolSummaryResult {
+    pub tx_count: usize,
+    // sum of the transactions sizes in bytes
+    pub total_size: usize,
+    pub total_fees: u64,
+    pub histogram: Vec<FeeRateBucket>,
+    // sorted by fee per kB, highest first
+    pub transactions: Vec<MempoolTransactionSummary>
+}
+
+pub type GetMempoolTransactionParams<'a> = GetTransactionParams<'a>;
+
+#[derive(Serialize, Deserialize)]
+pub struct GetMempoolTransactionResult<'a> {
+    pub size: usize,
+    pub fee_per_kb: u64,
+    pub first_seen: TimestampSeconds,
+    // count of transactions in mempool paying a higher fee per kB
+    pub rank: usize,
+    #[serde(flatten)]
+    pub data: RPCTransaction<'a>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PruningStatus {
+    pub pruned_topoheight: Option<u64>,
+    // blocks kept by the auto prune mode, None if disabled
+    pub auto_prune_keep_n_blocks: Option<u64>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GetStatusResult<'a> {
+    pub version: Cow<'a, str>,
+    pub network: Network,
+    // uptime of the daemon in seconds
+    pub uptime: u64,
+    pub height: u64,
+    pub topoheight: u64,
+    pub stableheight: u64,
+    // best and median topoheight of the connected peers
+    // None if the P2P server is disabled
+    pub best_topoheight: Option<u64>,
+    pub median_topoheight: Option<u64>,
+    // estimated sync progress in percent against the best peer
+    pub sync_progress: f64,
+    // chain is being synced with a peer
+    pub syncing: bool,
+    // node is synced with its peers and can serve requests
+    pub ready: bool,
+    pub peer_count: usize,
+    // node is following its primary node in standby mode
+    pub standby: bool,
+    pub mempool: MempoolStatus,
+    pub pruning: PruningStatus
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GetCapabilitiesResult<'a> {
+    // Features and runtime modes enabled on the daemon
+    pub capabilities: Capabilities,
+    // Names of the capabilities enabled
+    pub names: Vec<Cow<'a, str>>,
+    // Features compiled in the daemon
+    pub features: Vec<Cow<'a, str>>
 }
 
 #[derive(Serialize, Deserialize)]
@@ -323,7 +572,41 @@ pub struct PeerEntry<'a> {
     pub pruned_topoheight: Option<u64>,
     pub peers: Cow<'a, HashMap<SocketAddr, Direction>>,
     pub cumulative_difficulty: Cow<'a, CumulativeDifficulty>,
-    pub connected_on: TimestampSeconds
+    pub connected_on: TimestampSeconds,
+    // Bandwidth and packets stats of the connection
+    #[serde(default)]
+    pub bytes_in: usize,
+    #[serde(default)]
+    pub bytes_out: usize,
+    #[serde(default)]
+    pub packets_in: usize,
+    #[serde(default)]
+    pub packets_out: usize,
+    // How many packets of the peer got dropped for exceeding the rate limits
+    #[serde(default)]
+    pub throttled: u64,
+    // P2P protocol version negotiated with the peer
+    #[serde(default)]
+    pub protocol_version: u16,
+    // Features and runtime modes enabled on the peer
+    #[serde(default)]
+    pub capabilities: Capabilities,
+    // Rolling round-trip time and its deviation in ms
+    // None if the peer doesn't support latency pings or no pong was received yet
+    #[serde(default)]
+    pub latency: Option<u64>,
+    #[serde(default)]
+    pub jitter: Option<u64>,
+    // Connection is wrapped in a TLS session
+    #[serde(default)]
+    pub tls: bool,
+    // TLS port advertised by the peer in its handshake
+    #[serde(default)]
+    pub tls_port: Option<u16>,
+    // Topoheight of the peer minus ours, negative if the peer is behind us
+    // Only set when requested through get_peers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub topoheight_diff: Option<i64>
 }
 
 #[derive(Serialize, Deserialize)]
@@ -337,6 +620,219 @@ pub struct P2pStatusResult<'a> {
     pub peer_id: u64
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct AnchorPeerEntry {
+    pub addr: SocketAddr,
+    pub connected: bool,
+    // connection attempts done since it got disconnected
+    pub failures: u32,
+    // time at which the next connection attempt can be done
+    pub next_attempt: TimestampMillis
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GetConnectionManagerResult {
+    pub max_outbound: usize,
+    pub max_inbound: usize,
+    pub outbound: usize,
+    pub inbound: usize,
+    pub anchors: Vec<AnchorPeerEntry>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SetConnectionTargetsParams {
+    #[serde(default)]
+    pub max_outbound: Option<usize>,
+    #[serde(default)]
+    pub max_inbound: Option<usize>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AnchorPeerParams {
+    pub address: SocketAddr
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AdminRewindChainParams {
+    pub count: u64,
+    #[serde(default)]
+    pub until_stable_height: bool
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AdminRewindChainResult {
+    // new topoheight of the chain
+    pub topoheight: u64
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AdminPruneChainParams {
+    pub topoheight: u64
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AdminPruneChainResult {
+    pub pruned_topoheight: u64
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AdminSetLogLevelParams<'a> {
+    pub level: Cow<'a, str>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AdminPeerAddressParams {
+    pub address: SocketAddr
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AdminBanPeerParams {
+    pub ip: IpAddr,
+    // temporary ban duration, blacklisted if not set
+    #[serde(default)]
+    pub seconds: Option<u64>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AdminUnbanPeerParams {
+    pub ip: IpAddr
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AdminValidateSupplyParams {
+    // first topoheight verified, default is the first one available
+    #[serde(default)]
+    pub start_topoheight: Option<u64>,
+    // last topoheight verified, default is the current topoheight
+    #[serde(default)]
+    pub end_topoheight: Option<u64>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AdminStorageMaintenanceResult {
+    // size on disk in bytes before and after the maintenance
+    pub size_before: u64,
+    pub size_after: u64,
+    // balance versions deleted by the balance pruning
+    pub pruned_balances: u64,
+    pub dropped_trees: usize,
+    pub flushed_bytes: usize,
+    pub duration_millis: u64
+}
+
+// Transaction submitted through the RPC of the node and still in its mempool
+#[derive(Serialize, Deserialize)]
+pub struct LocalTransactionEntry {
+    pub hash: Hash,
+    pub submitted_at: TimestampMillis,
+    // topoheight of its last broadcast
+    pub broadcast_topoheight: u64,
+    pub rebroadcasts: u32
+}
+
+// Hits and misses of a storage cache
+#[derive(Serialize, Deserialize)]
+pub struct CacheStats {
+    pub name: String,
+    pub capacity: usize,
+    pub size: usize,
+    pub hits: u64,
+    pub misses: u64
+}
+
+// Last block handled by the block processing task
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ProcessedBlockStats {
+    pub hash: Hash,
+    // false if the block was rejected
+    pub accepted: bool,
+    // durations in milliseconds
+    pub queue_time: u64,
+    pub validation_time: u64,
+    pub commit_time: u64
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BlockProcessingStats {
+    // blocks waiting to be processed
+    pub queue_size: usize,
+    pub queue_capacity: usize,
+    pub processed: u64,
+    pub rejected: u64,
+    // average durations in milliseconds
+    pub average_queue_time: u64,
+    pub average_processing_time: u64,
+    pub last_block: Option<ProcessedBlockStats>
+}
+
+// Block whose stored reward or supply doesn't match the recomputed one
+#[derive(Serialize, Deserialize)]
+pub struct SupplyDivergence {
+    pub topoheight: u64,
+    pub block_hash: Hash,
+    pub expected_block_reward: u64,
+    pub block_reward: u64,
+    pub expected_supply: u64,
+    pub supply: u64
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AdminValidateSupplyResult {
+    pub start_topoheight: u64,
+    pub end_topoheight: u64,
+    // supply stored at the end topoheight
+    pub supply: u64,
+    // coins burned between the start and the end topoheight
+    pub burned_supply: u64,
+    // supply minus the coins burned
+    pub circulating_supply: u64,
+    pub divergences: Vec<SupplyDivergence>
+}
+
+// Chain status of a peer compared to our chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PeerChainStatus {
+    // Peer has the same top block as us
+    Synced,
+    // Peer top block is in our chain at a lower topoheight
+    Behind,
+    // Peer is at a higher topoheight, its chain can't be verified yet
+    Ahead,
+    // Peer top block is not in our chain at its topoheight
+    Diverged,
+    // No chain view received yet or block not available anymore
+    Unknown
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PeerChainViewEntry<'a> {
+    pub id: u64,
+    pub addr: Cow<'a, SocketAddr>,
+    pub status: PeerChainStatus,
+    // since when the peer has this status
+    pub status_since: Option<TimestampMillis>,
+    pub topoheight: u64,
+    pub top_block_hash: Cow<'a, Hash>,
+    pub cumulative_difficulty: Cow<'a, CumulativeDifficulty>,
+    // peer topoheight minus our topoheight
+    pub topoheight_difference: i64
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GetNetworkViewResult<'a> {
+    pub our_topoheight: u64,
+    pub our_top_block_hash: Cow<'a, Hash>,
+    // count of peers for each status
+    // all peers are counted, even the hidden ones
+    pub synced: usize,
+    pub behind: usize,
+    pub ahead: usize,
+    pub diverged: usize,
+    pub unknown: usize,
+    pub peers: Vec<PeerChainViewEntry<'a>>
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct GetTopoHeightRangeParams {
     pub start_topoheight: Option<u64>,
@@ -349,6 +845,37 @@ pub struct GetHeightRangeParams {
     pub end_height: Option<u64>
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct GetDagInfoParams {
+    pub start_topoheight: Option<u64>,
+    pub end_topoheight: Option<u64>,
+    // Also build the graph in the Graphviz DOT format
+    #[serde(default)]
+    pub include_dot: bool
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DagBlockEntry<'a> {
+    pub hash: Cow<'a, Hash>,
+    pub tips: Cow<'a, IndexSet<Hash>>,
+    pub height: u64,
+    // None if the block is orphaned
+    pub topoheight: Option<u64>,
+    pub block_type: BlockType,
+    pub cumulative_difficulty: Cow<'a, CumulativeDifficulty>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GetDagInfoResult<'a> {
+    pub start_topoheight: u64,
+    pub end_topoheight: u64,
+    // Blocks ordered in the range, followed by the orphaned blocks at the same heights
+    pub blocks: Vec<DagBlockEntry<'a>>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dot: Option<String>
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct GetTransactionsParams {
     pub tx_hashes: Vec<Hash>
@@ -389,6 +916,14 @@ pub enum AccountHistoryType {
     DevFee { reward: u64 },
     Mining { reward: u64 },
     Burn { amount: u64 },
+    // Registration fee burned to create the asset
+    AssetCreation { amount: u64 },
+    // Units minted by the asset owner
+    Mint { amount: u64 },
+    // Locked transfer claimed or refunded
+    Unlock { id: Hash },
+    // Gas paid to invoke a contract
+    InvokeContract { contract: Hash, gas: u64 },
     Outgoing { to: Address },
     Incoming { from: Address },
 }
@@ -412,6 +947,18 @@ pub struct GetAssetParams<'a> {
     pub asset: Cow<'a, Hash>
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct GetAssetResult {
+    #[serde(flatten)]
+    pub data: AssetData,
+    // Current owner of an asset created by a transaction
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub owner: Option<Address>,
+    // Current minted supply of an asset created by a transaction
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub supply: Option<u64>
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct GetAssetsParams {
     pub skip: Option<usize>,
@@ -479,6 +1026,20 @@ pub struct GetMempoolCacheResult {
     balances: HashMap<Hash, CiphertextCache>
 }
 
+impl GetMempoolCacheResult {
+    pub fn get_min(&self) -> u64 {
+        self.min
+    }
+
+    pub fn get_max(&self) -> u64 {
+        self.max
+    }
+
+    pub fn get_txs(&self) -> &Vec<Hash> {
+        &self.txs
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct GetDifficultyResult {
     pub difficulty: Difficulty,
@@ -486,6 +1047,53 @@ pub struct GetDifficultyResult {
     pub hashrate_formatted: String
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct GetDifficultyHistoryParams {
+    pub count: u64
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DifficultySample {
+    pub topoheight: u64,
+    pub hash: Hash,
+    pub difficulty: Difficulty,
+    // time in milliseconds between the block and its most recent tip
+    pub solve_time: TimestampMillis
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GetDifficultyHistoryResult {
+    // current difficulty at tips
+    pub difficulty: Difficulty,
+    // current target in compact form
+    pub target: u32,
+    // samples in descending topoheight order
+    pub samples: Vec<DifficultySample>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GetDailyStatsParams {
+    // timestamps in milliseconds, days including them are returned
+    pub from: TimestampMillis,
+    pub to: TimestampMillis
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DailyStatsEntry {
+    // timestamp in milliseconds of the day start (UTC)
+    pub day: TimestampMillis,
+    // blocks ordered during this day
+    pub blocks: u64,
+    // transactions executed
+    pub txs: u64,
+    // fees paid by the executed transactions
+    pub fees: u64,
+    // accounts registered
+    pub new_accounts: u64,
+    // coins emitted by the block rewards
+    pub emission: u64
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct ValidateAddressParams<'a> {
     pub address: Cow<'a, Address>,
@@ -539,11 +1147,14 @@ pub enum NotifyEvent {
     // When a transaction has been included in a valid block & executed on chain
     // it contains TransactionExecutedEvent struct as value
     TransactionExecuted,
+    // When a transaction is in conflict with a transaction in mempool (same owner and nonce)
+    // It contains TransactionConflictDetectedEvent as value
+    TransactionConflictDetected,
     // When a registered TX SC Call hash has been executed by chain
     // TODO: Smart Contracts
     TransactionSCResult,
     // When a new asset has been registered
-    // TODO: Smart Contracts
+    // It contains NewAssetEvent as value
     NewAsset,
     // When a new peer has connected to us
     // It contains PeerConnectedEvent struct as value
@@ -561,6 +1172,9 @@ pub enum NotifyEvent {
     // and that he notified us
     // It contains PeerPeerDisconnectedEvent as value
     PeerPeerDisconnected,
+    // Sent periodically with the statistics of the GetWork miners
+    // It contains GetWorkMinersStatsEvent as value
+    GetWorkMinersStats,
 }
 
 // Value of NotifyEvent::NewBlock
@@ -604,6 +1218,32 @@ pub struct TransactionExecutedEvent<'a> {
     pub topoheight: u64,
 }
 
+// Transaction using the same owner and nonce as a transaction in mempool
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TransactionConflict {
+    pub owner: Address,
+    pub nonce: u64,
+    // transaction already in mempool
+    pub mempool_tx_hash: Hash,
+    // transaction received using the same nonce
+    pub conflicting_tx_hash: Hash,
+    // block including the conflicting transaction, None if it was submitted to the mempool
+    pub block_hash: Option<Hash>,
+    pub timestamp: TimestampMillis
+}
+
+// Value of NotifyEvent::TransactionConflictDetected
+pub type TransactionConflictDetectedEvent = TransactionConflict;
+
+#[derive(Serialize, Deserialize)]
+pub struct GetTransactionConflictsParams<'a> {
+    // only returns the conflicts involving this transaction
+    pub hash: Option<Cow<'a, Hash>>
+}
+
+// Value of NotifyEvent::NewAsset
+pub type NewAssetEvent = AssetWithData;
+
 // Value of NotifyEvent::PeerConnected
 pub type PeerConnectedEvent = PeerEntry<'static>;
 
@@ -622,6 +1262,9 @@ pub struct PeerPeerListUpdatedEvent {
 // Value of NotifyEvent::PeerStateUpdated
 pub type PeerStateUpdatedEvent = PeerEntry<'static>;
 
+// Value of NotifyEvent::GetWorkMinersStats
+pub type GetWorkMinersStatsEvent<'a> = GetWorkMinersResult<'a>;
+
 // Value of NotifyEvent::PeerPeerDisconnected
 #[derive(Serialize, Deserialize)]
 pub struct PeerPeerDisconnectedEvent {
@@ -629,4 +1272,29 @@ pub struct PeerPeerDisconnectedEvent {
     pub peer_id: u64,
     // address of the peer that disconnected from him
     pub peer_addr: SocketAddr
-}
\ No newline at end of file
+}
+// Event saved in the event journal of the daemon
+#[derive(Serialize, Deserialize)]
+pub struct JournalEvent {
+    // Sequence number of the event, it is also set in its notification
+    pub sequence: u64,
+    pub event: NotifyEvent,
+    pub value: serde_json::Value
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GetEventsSinceParams {
+    // Sequence number of the last event received
+    pub sequence: u64,
+    pub maximum: Option<usize>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GetEventsSinceResult {
+    // Sequence number of the oldest event still in the journal
+    // If it is above the requested sequence + 1, events were missed
+    pub oldest_sequence: Option<u64>,
+    // Sequence number of the last event saved in the journal
+    pub last_sequence: u64,
+    pub events: Vec<JournalEvent>
+}
@@ -17,12 +17,21 @@ use crate::{
     },
     transaction::{
         extra_data::UnknownExtraDataFormat,
+        lock::TransferLock,
+        multisig::MultiSig,
+        AssetCreationPayload,
+        AssetOwnershipPayload,
         BurnPayload,
+        MintAssetPayload,
+        MultiSigPayload,
         Reference,
         SourceCommitment,
         Transaction,
         TransactionType,
-        TransferPayload
+        TransferPayload,
+        UnlockPayload,
+        DeployContractPayload,
+        InvokeContractPayload
     }
 };
 pub use data::*;
@@ -55,6 +64,8 @@ pub struct RPCTransferPayload<'a> {
     pub sender_handle: Cow<'a, CompressedHandle>,
     pub receiver_handle: Cow<'a, CompressedHandle>,
     pub ct_validity_proof: Cow<'a, CiphertextValidityProof>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lock: Option<Cow<'a, TransferLock>>,
 }
 
 impl<'a> From<RPCTransferPayload<'a>> for TransferPayload {
@@ -66,16 +77,38 @@ impl<'a> From<RPCTransferPayload<'a>> for TransferPayload {
             transfer.commitment.into_owned(),
             transfer.sender_handle.into_owned(),
             transfer.receiver_handle.into_owned(),
-            transfer.ct_validity_proof.into_owned()
+            transfer.ct_validity_proof.into_owned(),
+            transfer.lock.map(Cow::into_owned)
         )
     }
 }
 
+// Same as AssetOwnershipPayload but with the new owner as an address
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RPCAssetOwnershipPayload<'a> {
+    pub asset: Cow<'a, Hash>,
+    pub new_owner: Address
+}
+
+// Same as MultiSigPayload but with the participants as addresses
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RPCMultiSigPayload {
+    pub threshold: u8,
+    pub participants: Vec<Address>
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(rename_all = "snake_case")]
 pub enum RPCTransactionType<'a> {
     Transfers(Vec<RPCTransferPayload<'a>>),
     Burn(Cow<'a, BurnPayload>),
+    CreateAsset(Cow<'a, AssetCreationPayload>),
+    MintAsset(Cow<'a, MintAssetPayload>),
+    TransferAssetOwnership(RPCAssetOwnershipPayload<'a>),
+    MultiSig(RPCMultiSigPayload),
+    Unlock(Cow<'a, UnlockPayload>),
+    DeployContract(Cow<'a, DeployContractPayload>),
+    InvokeContract(Cow<'a, InvokeContractPayload>),
 }
 
 impl<'a> RPCTransactionType<'a> {
@@ -92,11 +125,25 @@ impl<'a> RPCTransactionType<'a> {
                         sender_handle: Cow::Borrowed(transfer.get_sender_handle()),
                         receiver_handle: Cow::Borrowed(transfer.get_receiver_handle()),
                         ct_validity_proof: Cow::Borrowed(transfer.get_proof()),
+                        lock: transfer.get_lock().as_ref().map(Cow::Borrowed),
                     });
                 }
                 Self::Transfers(rpc_transfers)
             },
-            TransactionType::Burn(burn) => Self::Burn(Cow::Borrowed(burn))
+            TransactionType::Burn(burn) => Self::Burn(Cow::Borrowed(burn)),
+            TransactionType::CreateAsset(payload) => Self::CreateAsset(Cow::Borrowed(payload)),
+            TransactionType::MintAsset(payload) => Self::MintAsset(Cow::Borrowed(payload)),
+            TransactionType::TransferAssetOwnership(payload) => Self::TransferAssetOwnership(RPCAssetOwnershipPayload {
+                asset: Cow::Borrowed(&payload.asset),
+                new_owner: payload.new_owner.as_address(mainnet)
+            }),
+            TransactionType::MultiSig(payload) => Self::MultiSig(RPCMultiSigPayload {
+                threshold: payload.threshold,
+                participants: payload.participants.iter().map(|p| p.as_address(mainnet)).collect()
+            }),
+            TransactionType::Unlock(payload) => Self::Unlock(Cow::Borrowed(payload)),
+            TransactionType::DeployContract(payload) => Self::DeployContract(Cow::Borrowed(payload)),
+            TransactionType::InvokeContract(payload) => Self::InvokeContract(Cow::Borrowed(payload))
         }
     }
 }
@@ -107,7 +154,20 @@ impl From<RPCTransactionType<'_>> for TransactionType {
             RPCTransactionType::Transfers(transfers) => {
                 TransactionType::Transfers(transfers.into_iter().map(|transfer| transfer.into()).collect::<Vec<TransferPayload>>())
             },
-            RPCTransactionType::Burn(burn) => TransactionType::Burn(burn.into_owned())
+            RPCTransactionType::Burn(burn) => TransactionType::Burn(burn.into_owned()),
+            RPCTransactionType::CreateAsset(payload) => TransactionType::CreateAsset(payload.into_owned()),
+            RPCTransactionType::MintAsset(payload) => TransactionType::MintAsset(payload.into_owned()),
+            RPCTransactionType::TransferAssetOwnership(payload) => TransactionType::TransferAssetOwnership(AssetOwnershipPayload {
+                asset: payload.asset.into_owned(),
+                new_owner: payload.new_owner.to_public_key()
+            }),
+            RPCTransactionType::MultiSig(payload) => TransactionType::MultiSig(MultiSigPayload {
+                threshold: payload.threshold,
+                participants: payload.participants.into_iter().map(|p| p.to_public_key()).collect()
+            }),
+            RPCTransactionType::Unlock(payload) => TransactionType::Unlock(payload.into_owned()),
+            RPCTransactionType::DeployContract(payload) => TransactionType::DeployContract(payload.into_owned()),
+            RPCTransactionType::InvokeContract(payload) => TransactionType::InvokeContract(payload.into_owned())
         }
     }
 }
@@ -136,6 +196,9 @@ pub struct RPCTransaction<'a> {
     pub range_proof: Cow<'a, RangeProof>,
     /// Reference at which block the transaction was built
     pub reference: Cow<'a, Reference>,
+    /// Signatures of the multisig participants
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub multisig: Option<Cow<'a, MultiSig>>,
     /// Signature of the transaction
     pub signature: Cow<'a, Signature>,
 }
@@ -152,6 +215,7 @@ impl<'a> RPCTransaction<'a> {
             source_commitments: Cow::Borrowed(tx.get_source_commitments()),
             range_proof: Cow::Borrowed(tx.get_range_proof()),
             reference: Cow::Borrowed(tx.get_reference()),
+            multisig: tx.get_multisig().as_ref().map(Cow::Borrowed),
             signature: Cow::Borrowed(tx.get_signature()),
         }
     }
@@ -160,6 +224,7 @@ impl<'a> RPCTransaction<'a> {
 impl<'a> From<RPCTransaction<'a>> for Transaction {
     fn from(tx: RPCTransaction<'a>) -> Self {
         Transaction::new(
+            tx.version,
             tx.source.to_public_key(),
             tx.data.into(),
             tx.fee,
@@ -167,6 +232,7 @@ impl<'a> From<RPCTransaction<'a>> for Transaction {
             tx.source_commitments.into_owned(),
             tx.range_proof.into_owned(),
             tx.reference.into_owned(),
+            tx.multisig.map(Cow::into_owned),
             tx.signature.into_owned()
         )
     }
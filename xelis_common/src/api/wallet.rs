@@ -1,9 +1,14 @@
-use std::borrow::Cow;
+use std::{borrow::Cow, collections::HashMap};
 use serde::{Deserialize, Serialize};
 use crate::{
-    crypto::{Address, Hash},
+    account::CiphertextCache,
+    crypto::{Address, AddressType, Hash, PublicKey, Signature},
+    serializer::{Reader, ReaderError, Serializer, Writer},
+    time::{TimestampMillis, TimestampSeconds},
     transaction::{
         builder::{FeeBuilder, TransactionTypeBuilder},
+        multisig::SignatureId,
+        Reference,
         Transaction
     }
 };
@@ -21,6 +26,90 @@ pub struct BuildTransactionParams {
     pub tx_as_hex: bool
 }
 
+// Single transfer of the REST API, always broadcasted
+#[derive(Serialize, Deserialize)]
+pub struct TransferParams {
+    pub destination: Address,
+    pub amount: u64,
+    // XELIS by default
+    pub asset: Option<Hash>,
+    pub extra_data: Option<DataElement>,
+    pub fee: Option<FeeBuilder>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BuildUnsignedTransactionParams {
+    #[serde(flatten)]
+    pub tx_type: TransactionTypeBuilder,
+    pub fee: Option<FeeBuilder>,
+    // Signatures required by the multisig of the wallet
+    pub threshold: u8
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct UnsignedTransactionResponse {
+    // Hash to sign by the multisig participants
+    pub hash: Hash,
+    // Unsigned transaction in HEX format
+    pub tx_as_hex: String
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SignUnsignedTransactionParams {
+    // Hash of the unsigned transaction
+    pub hash: Hash,
+    // Index of the wallet in the multisig participants
+    pub signer_id: u8
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct FinalizeUnsignedTransactionParams {
+    // Unsigned transaction in HEX format
+    pub unsigned: String,
+    pub signatures: Vec<SignatureId>,
+    // Cannot be broadcasted if set to false
+    pub broadcast: bool,
+    // Returns the TX in HEX format also
+    #[serde(default = "default_false_value")]
+    pub tx_as_hex: bool
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BuildOfflineTransactionParams {
+    // Account of the air-gapped wallet
+    pub address: Address,
+    #[serde(flatten)]
+    pub tx_type: TransactionTypeBuilder,
+    pub fee: Option<FeeBuilder>
+}
+
+// Transaction to build and sign by an air-gapped wallet
+// Its proofs require the private key, so only the chain state needed to build it is provided
+#[derive(Serialize, Deserialize)]
+pub struct OfflineTransactionRequest {
+    // Account which must sign the transaction
+    pub source: Address,
+    pub tx_type: TransactionTypeBuilder,
+    pub fee: FeeBuilder,
+    pub nonce: u64,
+    pub reference: Reference,
+    // Encrypted balances of the source for each asset used
+    pub balances: HashMap<Hash, CiphertextCache>,
+    // Destinations already registered on chain, used to compute the fees
+    pub registered_keys: Vec<Address>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SignOfflineTransactionParams {
+    pub request: OfflineTransactionRequest
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SubmitTransactionParams {
+    // Signed transaction in HEX format
+    pub tx_as_hex: String
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct EstimateFeesParams {
     #[serde(flatten)]
@@ -54,6 +143,70 @@ pub struct ListTransactionsParams {
     pub query: Option<Query>
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    Csv,
+    Json
+}
+
+// Range of the history to export, all bounds are inclusive
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HistoryRange {
+    pub min_topoheight: Option<u64>,
+    pub max_topoheight: Option<u64>,
+    // Timestamps of the blocks executing the transactions
+    pub min_timestamp: Option<TimestampMillis>,
+    pub max_timestamp: Option<TimestampMillis>
+}
+
+// Price API used to add the fiat value of each record
+#[derive(Serialize, Deserialize)]
+pub struct FiatValuationParams {
+    // Currency requested such as "usd"
+    pub currency: String,
+    // Called with the asset, timestamp and currency as query parameters
+    // It must answer with the price of one unit of the asset as a JSON number
+    pub url: String
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ExportHistoryParams {
+    pub format: ExportFormat,
+    #[serde(flatten)]
+    pub range: HistoryRange,
+    pub fiat: Option<FiatValuationParams>
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryDirection {
+    Incoming,
+    Outgoing
+}
+
+// One asset movement of the wallet history, a transaction may have several
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryRecord {
+    pub hash: Hash,
+    pub topoheight: u64,
+    // Not set if the block couldn't be retrieved from the daemon
+    pub timestamp: Option<TimestampMillis>,
+    // Entry type of the transaction
+    pub kind: String,
+    pub direction: HistoryDirection,
+    pub asset: Option<Hash>,
+    // In atomic units
+    pub amount: u64,
+    pub decimals: u8,
+    // Fee paid in XELIS atomic units, only set on the first record of a transaction
+    pub fee: u64,
+    pub counterparty: Option<Address>,
+    // Price of one unit of the asset and value of the amount in the requested currency
+    pub fiat_price: Option<f64>,
+    pub fiat_value: Option<f64>
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct TransactionResponse<'a> {
     #[serde(flatten)]
@@ -98,12 +251,481 @@ pub struct GetTransactionParams {
     pub hash: Hash
 }
 
+// Account derived from the seed of the wallet
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AccountEntry {
+    pub index: u32,
+    pub address: Address
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SelectAccountParams {
+    pub index: u32,
+    #[serde(default = "default_false_value")]
+    pub auto_reconnect: bool
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct UnlockWalletParams {
+    pub password: String
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BackupToFileParams {
+    pub path: String,
+    // Password used to encrypt the backup
+    pub password: String
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SignMessageParams<'a> {
+    pub message: Cow<'a, str>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SignedMessage {
+    // Address of the account which signed the message
+    pub address: Address,
+    pub signature: Signature
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct VerifySignedMessageParams<'a> {
+    pub address: Address,
+    pub message: Cow<'a, str>,
+    pub signature: Signature
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CreateBalanceProofParams {
+    pub asset: Hash,
+    // Minimum amount proven, in atomic units
+    pub minimum: u64,
+    // Daemon top topoheight is used if not provided
+    pub topoheight: Option<u64>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BalanceProofResult {
+    // Address of the account which created the proof
+    pub address: Address,
+    pub asset: Hash,
+    pub topoheight: u64,
+    pub minimum: u64,
+    // Serialized proof in hex
+    pub proof: String
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct VerifyBalanceProofParams {
+    pub address: Address,
+    pub proof: String
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CreatePaymentRequestParams {
+    // Amount requested in atomic units, the payer chooses it if not set
+    pub amount: Option<u64>,
+    // XELIS is requested if not set
+    pub asset: Option<Hash>,
+    // Sent by the payer as extra data
+    pub message: Option<String>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ParsePaymentRequestParams {
+    pub uri: String
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PaymentRequestEntry {
+    pub uri: String,
+    pub address: Address,
+    pub amount: Option<u64>,
+    pub asset: Hash,
+    pub message: Option<String>,
+    // Address with the message integrated for wallets which can't read the URI
+    pub integrated_address: Address
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PayRequestParams {
+    pub uri: String,
+    // Amount to send if the request doesn't set it
+    pub amount: Option<u64>,
+    pub fee: Option<FeeBuilder>,
+    // Cannot be broadcasted if set to false
+    pub broadcast: bool,
+    // Returns the TX in HEX format also
+    #[serde(default = "default_false_value")]
+    pub tx_as_hex: bool
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct BalanceChanged {
     pub asset: Hash,
     pub balance: u64
 }
 
+// Condition evaluated by the wallet while syncing
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Serializer)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertRule {
+    // Balance of the asset went below the amount
+    BalanceBelow {
+        asset: Hash,
+        amount: u64
+    },
+    // An incoming transfer of the asset is above the amount
+    IncomingAbove {
+        asset: Hash,
+        amount: u64
+    },
+    // Nonce synced from the daemon isn't the one expected by the wallet
+    // This may happen if the same keys are used by another wallet
+    UnexpectedNonce
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertRuleEntry {
+    pub id: u64,
+    pub rule: AlertRule
+}
+
+// What triggered the alert
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    BalanceBelow {
+        asset: Hash,
+        threshold: u64,
+        balance: u64
+    },
+    IncomingAbove {
+        asset: Hash,
+        threshold: u64,
+        amount: u64,
+        // Transaction in which the transfer was received
+        hash: Hash
+    },
+    UnexpectedNonce {
+        expected: u64,
+        nonce: u64
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletAlert {
+    pub id: u64,
+    // Rule which triggered this alert
+    pub rule_id: u64,
+    pub timestamp: TimestampMillis,
+    pub alert: AlertKind
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AddAlertRuleParams {
+    pub rule: AlertRule
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RemoveAlertRuleParams {
+    pub id: u64
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SetAlertWebhooksParams {
+    pub webhooks: Vec<String>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GetWebhooksResult {
+    pub urls: Vec<String>,
+    // Notifications waiting to be delivered
+    pub pending: usize
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SetWebhooksParams {
+    pub urls: Vec<String>,
+    // Generated by the wallet if not set
+    #[serde(default)]
+    pub secret: Option<String>
+}
+
+// Body of a POST request sent to the webhooks
+#[derive(Serialize, Deserialize)]
+pub struct WebhookNotification<T> {
+    pub id: u64,
+    pub event: NotifyEvent,
+    pub timestamp: TimestampMillis,
+    pub data: T
+}
+
+// Limits applied by the wallet to the requests of an XSWD application
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, Serializer)]
+pub struct XSWDLimits {
+    // Requests allowed per minute, not limited if not set
+    #[serde(default)]
+    pub max_requests_per_minute: Option<u32>,
+    // Amount per asset a transaction can spend before asking the user again,
+    // even if the method is always allowed
+    #[serde(default)]
+    pub max_amount_per_transaction: HashMap<Hash, u64>,
+    // Amount per asset the application can spend per day
+    #[serde(default)]
+    pub daily_spend_cap: HashMap<Hash, u64>
+}
+
+// XSWD limits of all the applications
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct XSWDLimitsSettings {
+    // Used by the applications without their own limits
+    pub default: XSWDLimits,
+    // Limits by application ID
+    pub applications: HashMap<String, XSWDLimits>
+}
+
+impl XSWDLimitsSettings {
+    pub fn get_limits(&self, app_id: &str) -> &XSWDLimits {
+        self.applications.get(app_id).unwrap_or(&self.default)
+    }
+}
+
+impl Serializer for XSWDLimitsSettings {
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        let default = XSWDLimits::read(reader)?;
+        let count = reader.read_u16()?;
+        let mut applications = HashMap::with_capacity(count as usize);
+        for _ in 0..count {
+            let id = reader.read_string()?;
+            let limits = XSWDLimits::read(reader)?;
+            applications.insert(id, limits);
+        }
+
+        Ok(Self {
+            default,
+            applications
+        })
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        self.default.write(writer);
+        writer.write_u16(self.applications.len() as u16);
+        for (id, limits) in &self.applications {
+            writer.write_string(id);
+            limits.write(writer);
+        }
+    }
+}
+
+// When a scheduled payment is due
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Serializer)]
+#[serde(rename_all = "snake_case")]
+pub enum PaymentTrigger {
+    // Daemon topoheight reached, the interval is in blocks
+    Topoheight(u64),
+    // Time reached, the interval is in seconds
+    Timestamp(TimestampSeconds)
+}
+
+// Payment built and submitted by the wallet once due while online
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledPayment {
+    pub destination: Address,
+    pub asset: Hash,
+    pub amount: u64,
+    pub next_execution: PaymentTrigger,
+    // Delay between two executions, in the unit of the trigger
+    // The payment is deleted after its execution if not set
+    #[serde(default)]
+    pub interval: Option<u64>
+}
+
+impl Serializer for ScheduledPayment {
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        let mainnet = reader.read_bool()?;
+        let key = PublicKey::read(reader)?;
+        let addr_type = AddressType::read(reader)?;
+        Ok(Self {
+            destination: Address::new(mainnet, addr_type, key),
+            asset: Hash::read(reader)?,
+            amount: reader.read_u64()?,
+            next_execution: PaymentTrigger::read(reader)?,
+            interval: Option::read(reader)?
+        })
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        writer.write_bool(self.destination.is_mainnet());
+        self.destination.get_public_key().write(writer);
+        self.destination.get_type().write(writer);
+        self.asset.write(writer);
+        writer.write_u64(&self.amount);
+        self.next_execution.write(writer);
+        self.interval.write(writer);
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledPaymentEntry {
+    pub id: u64,
+    #[serde(flatten)]
+    pub payment: ScheduledPayment
+}
+
+// Result of a scheduled payment execution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledPaymentResult {
+    pub id: u64,
+    // Hash of the transaction submitted
+    pub hash: Option<Hash>,
+    // Reason of the failure
+    pub error: Option<String>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AddScheduledPaymentParams {
+    #[serde(flatten)]
+    pub payment: ScheduledPayment
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RemoveScheduledPaymentParams {
+    pub id: u64
+}
+
+// Contact saved in the address book of the wallet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Contact {
+    pub address: Address,
+    #[serde(default)]
+    pub note: Option<String>,
+    // Asset proposed by default when sending to this contact
+    #[serde(default)]
+    pub default_asset: Option<Hash>
+}
+
+impl Serializer for Contact {
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        let mainnet = reader.read_bool()?;
+        let key = PublicKey::read(reader)?;
+        let addr_type = AddressType::read(reader)?;
+        Ok(Self {
+            address: Address::new(mainnet, addr_type, key),
+            note: reader.read_optional_string()?,
+            default_asset: Option::read(reader)?
+        })
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        writer.write_bool(self.address.is_mainnet());
+        self.address.get_public_key().write(writer);
+        self.address.get_type().write(writer);
+        writer.write_optional_string(&self.note);
+        self.default_asset.write(writer);
+    }
+
+    fn size(&self) -> usize {
+        1 + self.address.get_public_key().size()
+            + self.address.get_type().size()
+            + 1 + self.note.as_ref().map(|note| note.len()).unwrap_or(0)
+            + self.default_asset.size()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContactEntry {
+    pub name: String,
+    #[serde(flatten)]
+    pub contact: Contact
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GetContactParams {
+    pub name: String
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RemoveContactParams {
+    pub name: String
+}
+
+// Label attached by the user to a transaction of its history
+#[derive(Debug, Clone, Serialize, Deserialize, Serializer)]
+pub struct TransactionLabel {
+    pub label: String,
+    // Free-text category to group the transactions
+    #[serde(default)]
+    #[serializer(optional_string)]
+    pub category: Option<String>
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SetTransactionLabelParams {
+    pub hash: Hash,
+    #[serde(flatten)]
+    pub label: TransactionLabel
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GetTransactionLabelParams {
+    pub hash: Hash
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RemoveTransactionLabelParams {
+    pub hash: Hash
+}
+
+// Direction of the funds moved by a transaction for the wallet
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionDirection {
+    Incoming,
+    Outgoing
+}
+
+// All the filters are optional and combined, amounts and topoheights bounds are inclusive
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchTransactionsParams {
+    pub asset: Option<Hash>,
+    pub direction: Option<TransactionDirection>,
+    pub min_amount: Option<u64>,
+    pub max_amount: Option<u64>,
+    // Case insensitive text searched in the label and the category
+    pub label: Option<String>,
+    pub min_topoheight: Option<u64>,
+    pub max_topoheight: Option<u64>
+}
+
+// Transaction built by the wallet and not executed in a block yet
+// Its topoheight is the one at which it was broadcasted the last time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingTransactionEntry {
+    #[serde(flatten)]
+    pub transaction: TransactionEntry,
+    pub rebroadcasts: u8
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BumpFeeParams {
+    pub hash: Hash,
+    // Previous fee is doubled if not set
+    pub fee: Option<FeeBuilder>,
+    // Returns the TX in HEX format also
+    #[serde(default = "default_false_value")]
+    pub tx_as_hex: bool
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LabeledTransactionEntry {
+    #[serde(flatten)]
+    pub transaction: TransactionEntry,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<TransactionLabel>
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct GetValueFromKeyParams {
     pub tree: String,
@@ -167,6 +789,27 @@ pub enum NotifyEvent {
     Online,
     // Same here
     Offline,
+    // When an alert rule is triggered during the sync
+    // Contains a WalletAlert as value
+    Alert,
+    // When another account is selected in the wallet
+    // Contains an AccountEntry as value
+    AccountSelected,
+    // When a scheduled payment is submitted
+    // Contains a ScheduledPaymentResult as value
+    ScheduledPaymentExecuted,
+    // When a scheduled payment couldn't be built or submitted
+    // Contains a ScheduledPaymentResult as value
+    ScheduledPaymentFailed,
+    // Progress of the blocks synced by the wallet
+    // Contains the current and target values
+    SyncProgress,
+    // When a transaction built by the wallet is executed in a block
+    // Contains its hash and the topoheight
+    TransactionConfirmed,
+    // When a transaction built by the wallet will never be executed
+    // Contains its hash
+    TransactionDropped,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -202,6 +845,43 @@ pub enum EntryType {
         asset: Hash,
         amount: u64
     },
+    // Asset registered by us, its hash is the transaction hash
+    AssetCreation {
+        asset: Hash,
+        name: String,
+        decimals: u8,
+        max_supply: u64
+    },
+    // Units minted by us as the asset owner
+    AssetMint {
+        asset: Hash,
+        amount: u64
+    },
+    // Ownership of the asset was transferred from/to us
+    AssetOwnershipTransfer {
+        asset: Hash,
+        from: Address,
+        to: Address
+    },
+    // Multisig of our account was set up or deleted
+    MultiSig {
+        participants: Vec<Address>,
+        threshold: u8
+    },
+    // Locked transfer claimed or refunded by us
+    Unlock {
+        id: Hash,
+        asset: Hash
+    },
+    // Contract deployed by us
+    DeployContract {
+        contract: Hash
+    },
+    // Contract invoked by us, max gas is always paid
+    InvokeContract {
+        contract: Hash,
+        max_gas: u64
+    },
     Incoming {
         from: Address,
         transfers: Vec<TransferIn>
@@ -1,7 +1,7 @@
 use std::hash::{Hash as StdHash, Hasher};
 use crate::{
     serializer::{Serializer, Writer, Reader, ReaderError},
-    crypto::Hash
+    crypto::{elgamal::CompressedPublicKey, Hash}
 };
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
@@ -10,13 +10,31 @@ pub struct AssetData {
     topoheight: u64,
     // How many atomic units is needed for a full coin
     decimals: u8,
+    // Name set at its creation, none for assets registered by the chain
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    name: Option<String>,
+    // Maximum supply in atomic units set at its creation
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    max_supply: Option<u64>
 }
 
 impl AssetData {
     pub fn new(topoheight: u64, decimals: u8) -> Self {
         Self {
             topoheight,
-            decimals
+            decimals,
+            name: None,
+            max_supply: None
+        }
+    }
+
+    // Asset created by a transaction
+    pub fn with_details(topoheight: u64, decimals: u8, name: String, max_supply: u64) -> Self {
+        Self {
+            topoheight,
+            decimals,
+            name: Some(name),
+            max_supply: Some(max_supply)
         }
     }
 
@@ -27,26 +45,113 @@ impl AssetData {
     pub fn get_decimals(&self) -> u8 {
         self.decimals
     }
+
+    pub fn get_name(&self) -> Option<&String> {
+        self.name.as_ref()
+    }
+
+    pub fn get_max_supply(&self) -> Option<u64> {
+        self.max_supply
+    }
 }
 
 impl Serializer for AssetData {
     fn write(&self, writer: &mut Writer) {
         writer.write_u64(&self.topoheight);
         writer.write_u8(self.decimals);
+        writer.write_optional_string(&self.name);
+        self.max_supply.write(writer);
     }
 
     fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
-        Ok(
-            Self::new(reader.read_u64()?, reader.read_u8()?)
-        )
+        let topoheight = reader.read_u64()?;
+        let decimals = reader.read_u8()?;
+        let name = reader.read_optional_string()?;
+        let max_supply = Option::read(reader)?;
+
+        Ok(Self {
+            topoheight,
+            decimals,
+            name,
+            max_supply
+        })
     }
 
     fn size(&self) -> usize {
-        self.topoheight.size() + self.decimals.size()
+        self.topoheight.size()
+        + self.decimals.size()
+        // 1 for the string length
+        + 1 + self.name.as_ref().map(|v| v.len()).unwrap_or(0)
+        + self.max_supply.size()
     }
 }
 
-#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+// Mintable state of an asset created by a transaction
+// It is versioned by topoheight as it changes with each mint or ownership transfer
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AssetState {
+    // Account allowed to mint and to transfer the ownership
+    owner: CompressedPublicKey,
+    // Atomic units minted until now
+    supply: u64,
+    // Atomic units that can be minted at most
+    max_supply: u64
+}
+
+impl AssetState {
+    pub fn new(owner: CompressedPublicKey, supply: u64, max_supply: u64) -> Self {
+        Self {
+            owner,
+            supply,
+            max_supply
+        }
+    }
+
+    pub fn get_owner(&self) -> &CompressedPublicKey {
+        &self.owner
+    }
+
+    pub fn set_owner(&mut self, owner: CompressedPublicKey) {
+        self.owner = owner;
+    }
+
+    pub fn get_supply(&self) -> u64 {
+        self.supply
+    }
+
+    pub fn set_supply(&mut self, supply: u64) {
+        self.supply = supply;
+    }
+
+    pub fn get_max_supply(&self) -> u64 {
+        self.max_supply
+    }
+}
+
+impl Serializer for AssetState {
+    fn write(&self, writer: &mut Writer) {
+        self.owner.write(writer);
+        writer.write_u64(&self.supply);
+        writer.write_u64(&self.max_supply);
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        let owner = CompressedPublicKey::read(reader)?;
+        let supply = reader.read_u64()?;
+        let max_supply = reader.read_u64()?;
+        if supply > max_supply {
+            return Err(ReaderError::InvalidValue)
+        }
+
+        Ok(Self::new(owner, supply, max_supply))
+    }
+
+    fn size(&self) -> usize {
+        self.owner.size() + self.supply.size() + self.max_supply.size()
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Serializer)]
 pub struct AssetWithData {
     asset: Hash,
     #[serde(flatten)]
@@ -78,23 +183,6 @@ impl AssetWithData {
     }
 }
 
-impl Serializer for AssetWithData {
-    fn write(&self, writer: &mut Writer) {
-        self.asset.write(writer);
-        self.data.write(writer);
-    }
-
-    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
-        Ok(
-            Self::new(reader.read_hash()?, AssetData::read(reader)?)
-        )
-    }
-
-    fn size(&self) -> usize {
-        self.asset.size() + self.data.size()
-    }
-}
-
 impl StdHash for AssetWithData {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.asset.hash(state);
@@ -107,4 +195,32 @@ impl PartialEq for AssetWithData {
     }
 }
 
-impl Eq for AssetWithData {}
\ No newline at end of file
+impl Eq for AssetWithData {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assets_in_sequence() {
+        // Assets are sent one after another in the bootstrap chain responses
+        let assets = [
+            AssetWithData::new(Hash::zero(), AssetData::new(0, 8)),
+            AssetWithData::new(Hash::max(), AssetData::with_details(5, 2, "Test".to_owned(), 1000))
+        ];
+
+        let mut writer = Writer::new();
+        for asset in &assets {
+            asset.write(&mut writer);
+        }
+        let bytes = writer.bytes();
+        assert_eq!(bytes.len(), assets.iter().map(|asset| asset.size()).sum::<usize>());
+
+        let mut reader = Reader::new(&bytes);
+        for asset in &assets {
+            let read = AssetWithData::read(&mut reader).unwrap();
+            assert_eq!(read.to_bytes(), asset.to_bytes());
+        }
+        assert_eq!(reader.size(), 0);
+    }
+}
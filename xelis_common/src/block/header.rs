@@ -3,7 +3,7 @@ use indexmap::IndexSet;
 use serde::Deserialize;
 use log::debug;
 use crate::{
-    block::{BLOCK_WORK_SIZE, HEADER_WORK_SIZE},
+    block::{BLOCK_WORK_SIZE, HEADER_WORK_SIZE, MAX_BLOCK_VERSION},
     config::TIPS_LIMIT,
     crypto::{
         elgamal::CompressedPublicKey,
@@ -217,10 +217,10 @@ impl Serializer for BlockHeader {
 
     fn read(reader: &mut Reader) -> Result<BlockHeader, ReaderError> {
         let version = reader.read_u8()?;
-        // At this moment we only support version 0, so we check it here directly
-        if version != 0 {
-            debug!("Expected version 0 got version {version}");
-            return Err(ReaderError::InvalidValue)
+        // Rules of the version are verified by the daemon based on the block height
+        if version > MAX_BLOCK_VERSION {
+            debug!("Expected version up to {MAX_BLOCK_VERSION} got version {version}");
+            return Err(ReaderError::UnsupportedVersion(version))
         }
 
         let height = reader.read_u64()?;
@@ -11,6 +11,8 @@ use crate::crypto::{Hash, HASH_SIZE};
 pub const EXTRA_NONCE_SIZE: usize = 32;
 pub const HEADER_WORK_SIZE: usize = 73;
 pub const BLOCK_WORK_SIZE: usize = 112; // 32 + 8 + 8 + 32 + 32 = 112
+// Highest block version known, each hard fork increases it
+pub const MAX_BLOCK_VERSION: u8 = 1;
 
 // Get combined hash for tips
 // This is used to get a hash that is unique for a set of tips
@@ -0,0 +1,168 @@
+use std::fmt::{Display, Formatter, self};
+use serde::{Serialize, Deserialize};
+
+use crate::serializer::{Serializer, Reader, ReaderError, Writer};
+
+// Bitfield describing the features and runtime modes enabled on a node
+// It is reported through the API and the P2P handshake
+// so tools and peers can adapt their behavior without probing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Capabilities(u64);
+
+impl Capabilities {
+    // RPC API server is enabled
+    pub const RPC_SERVER: Capabilities = Capabilities(1 << 0);
+    // GetWork server is enabled for miners
+    pub const GETWORK_SERVER: Capabilities = Capabilities(1 << 1);
+    // Chain history is pruned
+    pub const PRUNED: Capabilities = Capabilities(1 << 2);
+    // Chain is pruned automatically at each new block
+    pub const AUTO_PRUNE: Capabilities = Capabilities(1 << 3);
+    // Fast sync (bootstrapped chain) is allowed
+    pub const FAST_SYNC: Capabilities = Capabilities(1 << 4);
+    // Boost sync (parallel blocks requests) is allowed
+    pub const BOOST_SYNC: Capabilities = Capabilities(1 << 5);
+    // Peer exchange packets are supported
+    pub const PEER_EXCHANGE: Capabilities = Capabilities(1 << 6);
+    // Missing txs of a propagated block can be requested in one packet
+    pub const GET_TXS: Capabilities = Capabilities(1 << 7);
+    // Node is running in simulator mode
+    pub const SIMULATOR: Capabilities = Capabilities(1 << 8);
+    // PoW verification is skipped
+    pub const SKIP_POW_VERIFICATION: Capabilities = Capabilities(1 << 9);
+    // Several txs hashes can be propagated in one packet
+    pub const TXS_PROPAGATION: Capabilities = Capabilities(1 << 10);
+    // Latency ping/pong packets are supported
+    pub const LATENCY_PING: Capabilities = Capabilities(1 << 11);
+
+    // P2P protocol features that can only be used if both peers support them
+    pub const P2P_FEATURES: Capabilities = Capabilities(
+        Self::PEER_EXCHANGE.0 | Self::GET_TXS.0 | Self::TXS_PROPAGATION.0 | Self::LATENCY_PING.0
+    );
+
+    // All known capabilities with their names
+    const NAMES: [(Capabilities, &'static str); 12] = [
+        (Self::RPC_SERVER, "rpc_server"),
+        (Self::GETWORK_SERVER, "getwork_server"),
+        (Self::PRUNED, "pruned"),
+        (Self::AUTO_PRUNE, "auto_prune"),
+        (Self::FAST_SYNC, "fast_sync"),
+        (Self::BOOST_SYNC, "boost_sync"),
+        (Self::PEER_EXCHANGE, "peer_exchange"),
+        (Self::GET_TXS, "get_txs"),
+        (Self::SIMULATOR, "simulator"),
+        (Self::SKIP_POW_VERIFICATION, "skip_pow_verification"),
+        (Self::TXS_PROPAGATION, "txs_propagation"),
+        (Self::LATENCY_PING, "latency_ping"),
+    ];
+
+    pub fn new(bits: u64) -> Self {
+        Self(bits)
+    }
+
+    pub fn empty() -> Self {
+        Self(0)
+    }
+
+    pub fn bits(&self) -> u64 {
+        self.0
+    }
+
+    // Check if all the capabilities requested are enabled
+    pub fn contains(&self, other: Capabilities) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    // Enable the capabilities
+    pub fn insert(&mut self, other: Capabilities) {
+        self.0 |= other.0;
+    }
+
+    // Disable the capabilities
+    pub fn remove(&mut self, other: Capabilities) {
+        self.0 &= !other.0;
+    }
+
+    // Get the capabilities enabled in both
+    pub fn intersection(&self, other: Capabilities) -> Capabilities {
+        Self(self.0 & other.0)
+    }
+
+    // Enable or disable the capabilities
+    pub fn set(&mut self, other: Capabilities, value: bool) {
+        if value {
+            self.insert(other);
+        } else {
+            self.remove(other);
+        }
+    }
+
+    // Get the names of all known capabilities enabled
+    pub fn get_names(&self) -> Vec<&'static str> {
+        Self::NAMES.iter()
+            .filter(|(capability, _)| self.contains(*capability))
+            .map(|(_, name)| *name)
+            .collect()
+    }
+}
+
+impl Display for Capabilities {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.get_names().join(", "))
+    }
+}
+
+impl Serializer for Capabilities {
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        Ok(Self(reader.read_u64()?))
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        writer.write_u64(&self.0);
+    }
+
+    fn size(&self) -> usize {
+        self.0.size()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capabilities() {
+        let mut capabilities = Capabilities::empty();
+        capabilities.insert(Capabilities::RPC_SERVER);
+        capabilities.set(Capabilities::PRUNED, true);
+        assert!(capabilities.contains(Capabilities::RPC_SERVER));
+        assert!(capabilities.contains(Capabilities::PRUNED));
+        assert!(!capabilities.contains(Capabilities::GETWORK_SERVER));
+        assert_eq!(capabilities.get_names(), vec!["rpc_server", "pruned"]);
+
+        capabilities.set(Capabilities::PRUNED, false);
+        assert!(!capabilities.contains(Capabilities::PRUNED));
+
+        let bytes = capabilities.to_bytes();
+        assert_eq!(Capabilities::from_bytes(&bytes).unwrap(), capabilities);
+    }
+
+    #[test]
+    fn test_capabilities_intersection() {
+        let mut ours = Capabilities::empty();
+        ours.insert(Capabilities::GET_TXS);
+        ours.insert(Capabilities::PEER_EXCHANGE);
+        ours.insert(Capabilities::RPC_SERVER);
+        ours.insert(Capabilities::LATENCY_PING);
+
+        let mut theirs = Capabilities::empty();
+        theirs.insert(Capabilities::PEER_EXCHANGE);
+        theirs.insert(Capabilities::TXS_PROPAGATION);
+        theirs.insert(Capabilities::RPC_SERVER);
+        theirs.insert(Capabilities::LATENCY_PING);
+
+        let negotiated = ours.intersection(theirs).intersection(Capabilities::P2P_FEATURES);
+        assert_eq!(negotiated.get_names(), vec!["peer_exchange", "latency_ping"]);
+    }
+}
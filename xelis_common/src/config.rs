@@ -12,6 +12,12 @@ pub const FEE_PER_ACCOUNT_CREATION: u64 = 100000;
 // 0.00005000 XEL per KB
 // Each transfer has a overhead of 5000 atomic units
 pub const FEE_PER_TRANSFER: u64 = 5000;
+// Minimum fee increase in percent for a TX to replace one with the same nonce in mempool
+// The increase is at least the minimum fee of a TX (FEE_PER_KB)
+pub const TX_REPLACEMENT_FEE_INCREASE_PERCENT: u64 = 10;
+// 10 XEL burned per asset registration
+// It prevents to spam the chain with useless assets
+pub const ASSET_REGISTRATION_FEE: u64 = 10 * COIN_VALUE;
 
 // 8 decimals numbers
 pub const COIN_DECIMALS: u8 = 8;
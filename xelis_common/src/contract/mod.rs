@@ -0,0 +1,235 @@
+use serde::{de::Error as SerdeError, Deserialize, Deserializer, Serialize};
+use crate::serializer::{Reader, ReaderError, Serializer, Writer};
+
+mod vm;
+
+pub use vm::{Vm, VmError, VmState};
+
+// Maximum instructions in a contract program
+pub const MAX_PROGRAM_LENGTH: usize = 4096;
+// Maximum values on the stack during an execution
+pub const MAX_STACK_SIZE: usize = 256;
+// Maximum parameters given to a contract invocation
+pub const MAX_CONTRACT_PARAMETERS: usize = 32;
+// Maximum gas allowed for a contract invocation
+// Gas is paid in atomic units of XELIS
+pub const MAX_GAS_PER_INVOKE: u64 = 1_000_000;
+
+// Instructions of the contract virtual machine
+// All values are u64, booleans are represented as 0 or 1
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Opcode {
+    // Stop the execution successfully
+    Stop,
+    // Abort the execution, all changes are discarded
+    Revert,
+    Push(u64),
+    Pop,
+    // Duplicate the value at depth, 0 being the top of the stack
+    Dup(u8),
+    // Swap the top of the stack with the value at depth
+    Swap(u8),
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Eq,
+    Lt,
+    Gt,
+    Not,
+    And,
+    Or,
+    // Jump to the instruction index
+    Jump(u16),
+    // Pop a condition and jump to the instruction index if it is not zero
+    JumpIf(u16),
+    // Pop a key and push its value from the contract storage, 0 if not set
+    Load,
+    // Pop a value then a key and write it in the contract storage
+    // Writing 0 deletes the key
+    Store,
+    // Push the invocation parameter at index
+    Param(u8),
+    // Push the topoheight at which the contract is executed
+    Topoheight
+}
+
+impl Opcode {
+    // Gas paid for each execution of the instruction
+    pub fn get_gas_cost(&self) -> u64 {
+        match self {
+            Self::Stop | Self::Revert | Self::Push(_) | Self::Pop | Self::Dup(_) | Self::Swap(_) => 1,
+            Self::Add | Self::Sub | Self::Eq | Self::Lt | Self::Gt | Self::Not | Self::And | Self::Or => 2,
+            Self::Param(_) | Self::Topoheight => 2,
+            Self::Mul | Self::Div | Self::Mod => 4,
+            Self::Jump(_) | Self::JumpIf(_) => 4,
+            Self::Load => 50,
+            Self::Store => 200
+        }
+    }
+}
+
+impl Serializer for Opcode {
+    fn write(&self, writer: &mut Writer) {
+        match self {
+            Self::Stop => writer.write_u8(0),
+            Self::Revert => writer.write_u8(1),
+            Self::Push(value) => {
+                writer.write_u8(2);
+                writer.write_u64(value);
+            },
+            Self::Pop => writer.write_u8(3),
+            Self::Dup(depth) => {
+                writer.write_u8(4);
+                writer.write_u8(*depth);
+            },
+            Self::Swap(depth) => {
+                writer.write_u8(5);
+                writer.write_u8(*depth);
+            },
+            Self::Add => writer.write_u8(16),
+            Self::Sub => writer.write_u8(17),
+            Self::Mul => writer.write_u8(18),
+            Self::Div => writer.write_u8(19),
+            Self::Mod => writer.write_u8(20),
+            Self::Eq => writer.write_u8(32),
+            Self::Lt => writer.write_u8(33),
+            Self::Gt => writer.write_u8(34),
+            Self::Not => writer.write_u8(35),
+            Self::And => writer.write_u8(36),
+            Self::Or => writer.write_u8(37),
+            Self::Jump(index) => {
+                writer.write_u8(48);
+                writer.write_u16(*index);
+            },
+            Self::JumpIf(index) => {
+                writer.write_u8(49);
+                writer.write_u16(*index);
+            },
+            Self::Load => writer.write_u8(64),
+            Self::Store => writer.write_u8(65),
+            Self::Param(index) => {
+                writer.write_u8(80);
+                writer.write_u8(*index);
+            },
+            Self::Topoheight => writer.write_u8(81)
+        }
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        Ok(match reader.read_u8()? {
+            0 => Self::Stop,
+            1 => Self::Revert,
+            2 => Self::Push(reader.read_u64()?),
+            3 => Self::Pop,
+            4 => Self::Dup(reader.read_u8()?),
+            5 => Self::Swap(reader.read_u8()?),
+            16 => Self::Add,
+            17 => Self::Sub,
+            18 => Self::Mul,
+            19 => Self::Div,
+            20 => Self::Mod,
+            32 => Self::Eq,
+            33 => Self::Lt,
+            34 => Self::Gt,
+            35 => Self::Not,
+            36 => Self::And,
+            37 => Self::Or,
+            48 => Self::Jump(reader.read_u16()?),
+            49 => Self::JumpIf(reader.read_u16()?),
+            64 => Self::Load,
+            65 => Self::Store,
+            80 => Self::Param(reader.read_u8()?),
+            81 => Self::Topoheight,
+            _ => return Err(ReaderError::InvalidValue)
+        })
+    }
+
+    fn size(&self) -> usize {
+        1 + match self {
+            Self::Push(_) => 8,
+            Self::Dup(_) | Self::Swap(_) | Self::Param(_) => 1,
+            Self::Jump(_) | Self::JumpIf(_) => 2,
+            _ => 0
+        }
+    }
+}
+
+// Code of a contract deployed on chain
+// A program is always valid once deserialized: it is not empty and all its jumps are in bounds
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Program {
+    code: Vec<Opcode>
+}
+
+impl Program {
+    // Create a new program, None if it is invalid
+    pub fn new(code: Vec<Opcode>) -> Option<Self> {
+        let program = Self { code };
+        if program.is_valid() {
+            Some(program)
+        } else {
+            None
+        }
+    }
+
+    fn is_valid(&self) -> bool {
+        if self.code.is_empty() || self.code.len() > MAX_PROGRAM_LENGTH {
+            return false
+        }
+
+        self.code.iter().all(|opcode| match opcode {
+            Opcode::Jump(index) | Opcode::JumpIf(index) => (*index as usize) < self.code.len(),
+            _ => true
+        })
+    }
+
+    pub fn get_code(&self) -> &Vec<Opcode> {
+        &self.code
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Opcode> {
+        self.code.get(index)
+    }
+}
+
+impl Serialize for Program {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_hex())
+    }
+}
+
+impl<'a> Deserialize<'a> for Program {
+    fn deserialize<D: Deserializer<'a>>(deserializer: D) -> Result<Self, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        Program::from_hex(hex).map_err(SerdeError::custom)
+    }
+}
+
+impl Serializer for Program {
+    fn write(&self, writer: &mut Writer) {
+        writer.write_u16(self.code.len() as u16);
+        for opcode in &self.code {
+            opcode.write(writer);
+        }
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        let len = reader.read_u16()? as usize;
+        if len == 0 || len > MAX_PROGRAM_LENGTH {
+            return Err(ReaderError::InvalidSize)
+        }
+
+        let mut code = Vec::with_capacity(len);
+        for _ in 0..len {
+            code.push(Opcode::read(reader)?);
+        }
+
+        Self::new(code).ok_or(ReaderError::InvalidValue)
+    }
+
+    fn size(&self) -> usize {
+        2 + self.code.iter().map(|opcode| opcode.size()).sum::<usize>()
+    }
+}
@@ -0,0 +1,286 @@
+use std::collections::HashMap;
+use thiserror::Error;
+use super::{Opcode, Program, MAX_STACK_SIZE};
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum VmError {
+    #[error("Out of gas")]
+    OutOfGas,
+    #[error("Stack overflow")]
+    StackOverflow,
+    #[error("Stack underflow")]
+    StackUnderflow,
+    #[error("Arithmetic overflow")]
+    Overflow,
+    #[error("Division by zero")]
+    DivisionByZero,
+    #[error("Invalid parameter index {}", _0)]
+    InvalidParameter(u8),
+    #[error("Execution reverted at instruction {}", _0)]
+    Revert(usize)
+}
+
+// State returned when the execution is paused or done
+#[derive(Debug, PartialEq, Eq)]
+pub enum VmState {
+    // Execution stopped successfully
+    Done,
+    // The value of this key must be loaded from the contract storage
+    // using `set_loaded` before running again
+    Load(u64)
+}
+
+// Deterministic stack machine executing a contract program
+// It has no access to the chain state: storage values are requested through `VmState::Load`
+// and all writes are kept in memory until the execution is done
+pub struct Vm<'a> {
+    program: &'a Program,
+    parameters: &'a [u64],
+    topoheight: u64,
+    stack: Vec<u64>,
+    // Index of the next instruction
+    pc: usize,
+    gas_limit: u64,
+    gas_used: u64,
+    // Values loaded from the contract storage
+    loaded: HashMap<u64, u64>,
+    // Values written by the execution
+    changes: HashMap<u64, u64>
+}
+
+impl<'a> Vm<'a> {
+    pub fn new(program: &'a Program, parameters: &'a [u64], topoheight: u64, gas_limit: u64) -> Self {
+        Self {
+            program,
+            parameters,
+            topoheight,
+            stack: Vec::new(),
+            pc: 0,
+            gas_limit,
+            gas_used: 0,
+            loaded: HashMap::new(),
+            changes: HashMap::new()
+        }
+    }
+
+    pub fn get_gas_used(&self) -> u64 {
+        self.gas_used
+    }
+
+    // Provide the value of a key requested by `VmState::Load`
+    pub fn set_loaded(&mut self, key: u64, value: Option<u64>) {
+        self.loaded.insert(key, value.unwrap_or(0));
+    }
+
+    // Storage changes to apply once the execution is done
+    // None means the key is deleted
+    pub fn into_changes(self) -> impl Iterator<Item = (u64, Option<u64>)> {
+        self.changes.into_iter()
+            .map(|(key, value)| (key, if value == 0 { None } else { Some(value) }))
+    }
+
+    fn push(&mut self, value: u64) -> Result<(), VmError> {
+        if self.stack.len() >= MAX_STACK_SIZE {
+            return Err(VmError::StackOverflow)
+        }
+        self.stack.push(value);
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Result<u64, VmError> {
+        self.stack.pop().ok_or(VmError::StackUnderflow)
+    }
+
+    // Pop two values and push the result of the operation
+    fn binary_op<F: FnOnce(u64, u64) -> Result<u64, VmError>>(&mut self, f: F) -> Result<(), VmError> {
+        let right = self.pop()?;
+        let left = self.pop()?;
+        let value = f(left, right)?;
+        self.push(value)
+    }
+
+    fn get_value(&self, key: &u64) -> Option<u64> {
+        self.changes.get(key).or_else(|| self.loaded.get(key)).copied()
+    }
+
+    // Execute the program until it is done or until a storage value is required
+    pub fn run(&mut self) -> Result<VmState, VmError> {
+        let program = self.program;
+        // Reaching the end of the program is an implicit stop
+        while let Some(opcode) = program.get(self.pc) {
+            // Request the value before paying for the instruction
+            if let Opcode::Load = opcode {
+                let key = *self.stack.last().ok_or(VmError::StackUnderflow)?;
+                if self.get_value(&key).is_none() {
+                    return Ok(VmState::Load(key))
+                }
+            }
+
+            self.gas_used = self.gas_used.checked_add(opcode.get_gas_cost())
+                .filter(|gas| *gas <= self.gas_limit)
+                .ok_or(VmError::OutOfGas)?;
+
+            self.pc += 1;
+            match opcode {
+                Opcode::Stop => return Ok(VmState::Done),
+                Opcode::Revert => return Err(VmError::Revert(self.pc - 1)),
+                Opcode::Push(value) => self.push(*value)?,
+                Opcode::Pop => {
+                    self.pop()?;
+                },
+                Opcode::Dup(depth) => {
+                    let index = self.stack.len().checked_sub(*depth as usize + 1).ok_or(VmError::StackUnderflow)?;
+                    self.push(self.stack[index])?;
+                },
+                Opcode::Swap(depth) => {
+                    let top = self.stack.len().checked_sub(1).ok_or(VmError::StackUnderflow)?;
+                    let index = top.checked_sub(*depth as usize).ok_or(VmError::StackUnderflow)?;
+                    self.stack.swap(top, index);
+                },
+                Opcode::Add => self.binary_op(|a, b| a.checked_add(b).ok_or(VmError::Overflow))?,
+                Opcode::Sub => self.binary_op(|a, b| a.checked_sub(b).ok_or(VmError::Overflow))?,
+                Opcode::Mul => self.binary_op(|a, b| a.checked_mul(b).ok_or(VmError::Overflow))?,
+                Opcode::Div => self.binary_op(|a, b| a.checked_div(b).ok_or(VmError::DivisionByZero))?,
+                Opcode::Mod => self.binary_op(|a, b| a.checked_rem(b).ok_or(VmError::DivisionByZero))?,
+                Opcode::Eq => self.binary_op(|a, b| Ok((a == b) as u64))?,
+                Opcode::Lt => self.binary_op(|a, b| Ok((a < b) as u64))?,
+                Opcode::Gt => self.binary_op(|a, b| Ok((a > b) as u64))?,
+                Opcode::And => self.binary_op(|a, b| Ok((a != 0 && b != 0) as u64))?,
+                Opcode::Or => self.binary_op(|a, b| Ok((a != 0 || b != 0) as u64))?,
+                Opcode::Not => {
+                    let value = self.pop()?;
+                    self.push((value == 0) as u64)?;
+                },
+                // Jumps are verified to be in bounds when the program is created
+                Opcode::Jump(index) => self.pc = *index as usize,
+                Opcode::JumpIf(index) => {
+                    if self.pop()? != 0 {
+                        self.pc = *index as usize;
+                    }
+                },
+                Opcode::Load => {
+                    let key = self.pop()?;
+                    // Value was requested above
+                    let value = self.get_value(&key).unwrap_or(0);
+                    self.push(value)?;
+                },
+                Opcode::Store => {
+                    let value = self.pop()?;
+                    let key = self.pop()?;
+                    self.changes.insert(key, value);
+                },
+                Opcode::Param(index) => {
+                    let value = *self.parameters.get(*index as usize).ok_or(VmError::InvalidParameter(*index))?;
+                    self.push(value)?;
+                },
+                Opcode::Topoheight => self.push(self.topoheight)?
+            }
+        }
+
+        Ok(VmState::Done)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use super::*;
+
+    // Run the program until the end using the storage provided
+    fn execute(program: &Program, parameters: &[u64], storage: &mut HashMap<u64, u64>, gas_limit: u64) -> Result<u64, VmError> {
+        let mut vm = Vm::new(program, parameters, 10, gas_limit);
+        while let VmState::Load(key) = vm.run()? {
+            vm.set_loaded(key, storage.get(&key).copied());
+        }
+
+        let gas_used = vm.get_gas_used();
+        for (key, value) in vm.into_changes() {
+            match value {
+                Some(value) => storage.insert(key, value),
+                None => storage.remove(&key)
+            };
+        }
+        Ok(gas_used)
+    }
+
+    // storage[0] += param[0]
+    fn counter() -> Program {
+        Program::new(vec![
+            Opcode::Push(0),
+            Opcode::Dup(0),
+            Opcode::Load,
+            Opcode::Param(0),
+            Opcode::Add,
+            Opcode::Store
+        ]).unwrap()
+    }
+
+    #[test]
+    fn test_counter() {
+        let program = counter();
+        let mut storage = HashMap::new();
+        execute(&program, &[5], &mut storage, 1000).unwrap();
+        execute(&program, &[7], &mut storage, 1000).unwrap();
+        assert_eq!(storage.get(&0), Some(&12));
+    }
+
+    #[test]
+    fn test_store_zero_deletes() {
+        let program = Program::new(vec![Opcode::Push(1), Opcode::Param(0), Opcode::Store]).unwrap();
+        let mut storage = HashMap::new();
+        execute(&program, &[3], &mut storage, 1000).unwrap();
+        assert_eq!(storage.get(&1), Some(&3));
+
+        execute(&program, &[0], &mut storage, 1000).unwrap();
+        assert!(storage.is_empty());
+    }
+
+    #[test]
+    fn test_out_of_gas() {
+        // Infinite loop
+        let program = Program::new(vec![Opcode::Jump(0)]).unwrap();
+        let mut storage = HashMap::new();
+        assert_eq!(execute(&program, &[], &mut storage, 1000), Err(VmError::OutOfGas));
+
+        // Not enough gas to load the value
+        assert_eq!(execute(&counter(), &[1], &mut storage, 10), Err(VmError::OutOfGas));
+        assert!(storage.is_empty());
+    }
+
+    #[test]
+    fn test_revert() {
+        // Revert if the parameter is zero
+        let program = Program::new(vec![
+            Opcode::Param(0),
+            Opcode::JumpIf(3),
+            Opcode::Revert,
+            Opcode::Stop
+        ]).unwrap();
+        let mut storage = HashMap::new();
+        assert_eq!(execute(&program, &[0], &mut storage, 1000), Err(VmError::Revert(2)));
+        assert!(execute(&program, &[1], &mut storage, 1000).is_ok());
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut storage = HashMap::new();
+        let program = Program::new(vec![Opcode::Push(u64::MAX), Opcode::Push(1), Opcode::Add]).unwrap();
+        assert_eq!(execute(&program, &[], &mut storage, 1000), Err(VmError::Overflow));
+
+        let program = Program::new(vec![Opcode::Push(1), Opcode::Push(0), Opcode::Div]).unwrap();
+        assert_eq!(execute(&program, &[], &mut storage, 1000), Err(VmError::DivisionByZero));
+
+        let program = Program::new(vec![Opcode::Pop]).unwrap();
+        assert_eq!(execute(&program, &[], &mut storage, 1000), Err(VmError::StackUnderflow));
+
+        let program = Program::new(vec![Opcode::Param(1)]).unwrap();
+        assert_eq!(execute(&program, &[1], &mut storage, 1000), Err(VmError::InvalidParameter(1)));
+    }
+
+    #[test]
+    fn test_invalid_program() {
+        assert!(Program::new(Vec::new()).is_none());
+        assert!(Program::new(vec![Opcode::Jump(1)]).is_none());
+        assert!(Program::new(vec![Opcode::Push(0), Opcode::JumpIf(0)]).is_some());
+    }
+}
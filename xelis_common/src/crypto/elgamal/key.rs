@@ -1,9 +1,11 @@
+use std::thread::{self, available_parallelism};
 use curve25519_dalek::{
     ecdlp::{self, ECDLPArguments, ECDLPTablesFileView},
     ristretto::RistrettoPoint,
     Scalar
 };
 use rand::rngs::OsRng;
+use sha3::{Digest, Sha3_512};
 use zeroize::Zeroize;
 use crate::{
     api::DataElement,
@@ -19,6 +21,7 @@ use crate::{
 use super::{
     ciphertext::Ciphertext,
     hash_and_point_to_scalar,
+    signed_message_bytes,
     pedersen::{DecryptHandle, PedersenCommitment, PedersenOpening},
     CompressedPublicKey,
     Signature,
@@ -109,6 +112,30 @@ impl PrivateKey {
         &self.0
     }
 
+    // Derive the private key of the account at this index
+    // All the accounts can be recovered from the same private key
+    pub fn derive_account(&self, index: u32) -> Self {
+        let mut hasher = Sha3_512::new();
+        hasher.update(b"XELIS_ACCOUNT");
+        hasher.update(self.0.as_bytes());
+        hasher.update(index.to_be_bytes());
+
+        let hash = hasher.finalize();
+        Self::from_scalar(Scalar::from_bytes_mod_order_wide(&hash.try_into().unwrap()))
+    }
+
+    // Derive the private key protected by a passphrase
+    // The passphrase should be stretched first to slow down brute force attacks
+    pub fn derive_passphrase(&self, passphrase: &[u8]) -> Self {
+        let mut hasher = Sha3_512::new();
+        hasher.update(b"XELIS_PASSPHRASE");
+        hasher.update(self.0.as_bytes());
+        hasher.update(passphrase);
+
+        let hash = hasher.finalize();
+        Self::from_scalar(Scalar::from_bytes_mod_order_wide(&hash.try_into().unwrap()))
+    }
+
     // Decrypt a Ciphertext to a point
     pub fn decrypt_to_point(&self, ciphertext: &Ciphertext) -> RistrettoPoint {
         let commitment = ciphertext.commitment().as_point();
@@ -128,6 +155,37 @@ impl PrivateKey {
         let point = self.decrypt_to_point(ciphertext);
         self.decode_point(precomputed_tables, point)
     }
+
+    // Decrypt several Ciphertexts with precomputed tables
+    // Decoding is the slowest part, so points are decoded in parallel
+    // Results are in the same order as the ciphertexts
+    pub fn decrypt_batch<const L1: usize>(&self, precomputed_tables: &ECDLPTablesFileView<L1>, ciphertexts: &[Ciphertext]) -> Vec<Option<u64>> {
+        let threads = available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+            .min(ciphertexts.len());
+
+        if threads <= 1 {
+            return ciphertexts.iter()
+                .map(|ciphertext| self.decrypt(precomputed_tables, ciphertext))
+                .collect()
+        }
+
+        let chunk_size = ciphertexts.len().div_ceil(threads);
+        thread::scope(|scope| {
+            let handles: Vec<_> = ciphertexts.chunks(chunk_size)
+                .map(|chunk| scope.spawn(move || {
+                    chunk.iter()
+                        .map(|ciphertext| self.decrypt(precomputed_tables, ciphertext))
+                        .collect::<Vec<_>>()
+                }))
+                .collect();
+
+            handles.into_iter()
+                .flat_map(|handle| handle.join().expect("decryption thread panicked"))
+                .collect()
+        })
+    }
 }
 
 impl KeyPair {
@@ -161,6 +219,11 @@ impl KeyPair {
         self.private_key.decrypt(precomputed_tables, ciphertext)
     }
 
+    // Decrypt several Ciphertexts with precomputed tables
+    pub fn decrypt_batch<const L1: usize>(&self, precomputed_tables: &ECDLPTablesFileView<L1>, ciphertexts: &[Ciphertext]) -> Vec<Option<u64>> {
+        self.private_key.decrypt_batch(precomputed_tables, ciphertexts)
+    }
+
     pub fn decrypt_to_point(&self, ciphertext: &Ciphertext) -> RistrettoPoint {
         self.private_key.decrypt_to_point(ciphertext)
     }
@@ -174,6 +237,11 @@ impl KeyPair {
         Signature::new(s, e)
     }
 
+    // Sign a message to prove the ownership of the key
+    pub fn sign_message(&self, message: &[u8]) -> Signature {
+        self.sign(&signed_message_bytes(message))
+    }
+
     // Get the public key of the KeyPair
     pub fn get_public_key(&self) -> &PublicKey {
         &self.public_key
@@ -190,6 +258,13 @@ impl KeyPair {
     }
 }
 
+// Only the private key is secret
+impl Zeroize for KeyPair {
+    fn zeroize(&mut self) {
+        self.private_key.zeroize();
+    }
+}
+
 impl Serializer for PrivateKey {
     fn write(&self, writer: &mut Writer) {
         self.0.write(writer);
@@ -206,7 +281,7 @@ mod tests {
     use curve25519_dalek::traits::Identity;
 
     use super::*;
-    use super::super::G;
+    use super::super::{G, SignatureBatchCollector};
 
     #[test]
     fn test_signature() {
@@ -218,6 +293,54 @@ mod tests {
         assert!(signature.verify(message, public_key));
     }
 
+    #[test]
+    fn test_signature_batch() {
+        let mut batch = SignatureBatchCollector::new();
+        let keypairs = (0..4).map(|_| KeyPair::new()).collect::<Vec<_>>();
+        for (i, keypair) in keypairs.iter().enumerate() {
+            let message = [i as u8; 32];
+            batch.add(&keypair.sign(&message), &message, keypair.get_public_key());
+        }
+        assert!(batch.verify());
+
+        // One invalid signature fails the whole batch
+        let mut batch = SignatureBatchCollector::new();
+        for (i, keypair) in keypairs.iter().enumerate() {
+            let message = [i as u8; 32];
+            batch.add(&keypair.sign(&message), &message, keypair.get_public_key());
+        }
+        let signature = keypairs[0].sign(b"Hello, world!");
+        batch.add(&signature, b"Hello, world?", keypairs[0].get_public_key());
+        assert!(!batch.verify());
+    }
+
+    #[test]
+    fn test_signed_message() {
+        let keypair = KeyPair::new();
+        let public_key = keypair.get_public_key();
+
+        let message = b"Hello, world!";
+        let signature = keypair.sign_message(message);
+        assert!(signature.verify_message(message, public_key));
+        assert!(!signature.verify_message(b"Hello, world?", public_key));
+        // Can't be verified as a raw signature of the message
+        assert!(!signature.verify(message, public_key));
+
+        let other = KeyPair::new();
+        assert!(!signature.verify_message(message, other.get_public_key()));
+    }
+
+    #[test]
+    fn test_derive_account() {
+        let keypair = KeyPair::new();
+        let private_key = keypair.get_private_key();
+
+        let first = private_key.derive_account(1);
+        assert!(first.as_scalar() == private_key.derive_account(1).as_scalar());
+        assert!(first.as_scalar() != private_key.derive_account(2).as_scalar());
+        assert!(first.as_scalar() != private_key.as_scalar());
+    }
+
     #[test]
     fn test_encrypt_decrypt() {
         let keypair = KeyPair::new();
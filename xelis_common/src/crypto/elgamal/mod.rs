@@ -10,12 +10,14 @@ mod ciphertext;
 mod key;
 mod signature;
 mod pedersen;
+mod tables;
 
 pub use compressed::*;
 pub use ciphertext::Ciphertext;
 pub use key::*;
 pub use pedersen::*;
 pub use signature::*;
+pub use tables::*;
 
 pub use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT as G;
 
@@ -1,13 +1,22 @@
-use curve25519_dalek::{RistrettoPoint, Scalar};
+use curve25519_dalek::{traits::VartimeMultiscalarMul, RistrettoPoint, Scalar};
+use lazy_static::lazy_static;
 use serde::{de::Error, Serialize};
 use sha3::{Digest, Sha3_512};
-use crate::serializer::{Reader, ReaderError, Serializer, Writer};
+use crate::{
+    crypto::hash,
+    serializer::Serializer
+};
 
 use super::{CompressedPublicKey, PublicKey, H, SCALAR_SIZE};
 
 pub const SIGNATURE_SIZE: usize = SCALAR_SIZE * 2;
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+lazy_static! {
+    // Inverse of 2, used to halve the nonce points before their batch compression
+    static ref HALF: Scalar = Scalar::from(2u64).invert();
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serializer)]
 pub struct Signature {
     s: Scalar,
     e: Scalar,
@@ -24,6 +33,81 @@ impl Signature {
         let calculated = hash_and_point_to_scalar(&key.compress(), message, &r);
         self.e == calculated
     }
+
+    // Verify the signature of a message signed to prove the ownership of the key
+    pub fn verify_message(&self, message: &[u8], key: &PublicKey) -> bool {
+        self.verify(&signed_message_bytes(message), key)
+    }
+}
+
+// Collect signatures to verify them all at once
+// A signature is (s, e) and not (R, s): the nonce point R is not known before
+// it is computed from the signature, and it is needed to hash the challenge.
+// So there is no group equation left to combine with random weights in a single MSM,
+// each nonce point is computed on its own but all of them are compressed together
+// with a single field inversion
+#[derive(Default)]
+pub struct SignatureBatchCollector {
+    // Half of the nonce point, the challenge and the hasher fed with the key and the message
+    entries: Vec<(RistrettoPoint, Scalar, Sha3_512)>
+}
+
+impl SignatureBatchCollector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Add a signature to verify using the Public Key and the message
+    pub fn add(&mut self, signature: &Signature, message: &[u8], key: &PublicKey) {
+        // Halved so the batch compression (which doubles the points) returns the nonce point
+        let half = *HALF;
+        let half_r = RistrettoPoint::vartime_multiscalar_mul(
+            [signature.s * half, -signature.e * half],
+            [*H, *key.as_point()]
+        );
+
+        let mut hasher = Sha3_512::new();
+        hasher.update(key.compress().as_bytes());
+        hasher.update(message);
+
+        self.entries.push((half_r, signature.e, hasher));
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // Returns true only if every signature collected is valid
+    pub fn verify(self) -> bool {
+        if self.entries.is_empty() {
+            return true
+        }
+
+        let points = RistrettoPoint::double_and_compress_batch(self.entries.iter().map(|(half_r, _, _)| half_r));
+        self.entries.into_iter()
+            .zip(points)
+            .all(|((_, e, mut hasher), r)| {
+                hasher.update(r.as_bytes());
+                let hash = hasher.finalize();
+                e == Scalar::from_bytes_mod_order_wide(&hash.try_into().unwrap())
+            })
+    }
+}
+
+// Prefix of the messages signed to prove the ownership of a key
+// No transaction or data element starts with it,
+// so a message signature can't be reused as one of them
+const SIGNED_MESSAGE_PREFIX: &[u8] = b"XELIS Signed Message:\n";
+
+// Bytes signed for a message, the prefix followed by the hash of the message
+pub fn signed_message_bytes(message: &[u8]) -> Vec<u8> {
+    let mut bytes = SIGNED_MESSAGE_PREFIX.to_vec();
+    bytes.extend_from_slice(hash(message).as_bytes());
+    bytes
 }
 
 // Create a Scalar from Public Key, Hash of the message, and selected point
@@ -55,20 +139,3 @@ impl<'de> serde::Deserialize<'de> for Signature {
         Ok(Self::from_hex(s).map_err(D::Error::custom)?)
     }
 }
-
-impl Serializer for Signature {
-    fn write(&self, writer: &mut Writer) {
-        self.s.write(writer);
-        self.e.write(writer);
-    }
-
-    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
-        let s = Scalar::read(reader)?;
-        let e = Scalar::read(reader)?;
-        Ok(Signature::new(s, e))
-    }
-
-    fn size(&self) -> usize {
-        SIGNATURE_SIZE
-    }
-}
\ No newline at end of file
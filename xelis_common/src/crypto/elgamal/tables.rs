@@ -0,0 +1,83 @@
+use std::{
+    fs::{self, OpenOptions},
+    path::Path
+};
+use anyhow::{bail, Result};
+use curve25519_dalek::ecdlp::{
+    table_generation,
+    ProgressTableGenerationReportFunction
+};
+use log::{debug, info};
+use memmap2::{Mmap, MmapMut};
+
+// L1 used for the precomputed tables
+// A bigger value decodes faster but requires a bigger file
+pub const PRECOMPUTED_TABLES_L1: usize = 26;
+
+// Baby-step giant-step tables used to decode the points of the decrypted ciphertexts
+// The file is generated only once then memory-mapped, its pages are loaded on demand
+// They can be shared between several wallets
+pub struct PrecomputedTables {
+    mmap: Mmap
+}
+
+impl PrecomputedTables {
+    // Map the tables file from the directory, it is generated first if it doesn't exist
+    // The directory must end with a / or be empty for the current one
+    pub fn load_or_generate<P: ProgressTableGenerationReportFunction>(directory: &str, progress_report: P) -> Result<Self> {
+        const N: usize = PRECOMPUTED_TABLES_L1;
+
+        if !directory.is_empty() && !Path::new(directory).exists() {
+            fs::create_dir_all(directory)?;
+        }
+
+        let path = format!("{directory}precomputed_tables_{N}.bin");
+        let expected_size = table_generation::table_file_len(N);
+        if Path::new(&path).exists() {
+            info!("Mapping precomputed tables from file");
+            let file = OpenOptions::new().read(true).open(&path)?;
+            let size = file.metadata()?.len() as usize;
+            if size != expected_size {
+                bail!("precomputed tables file {} has {} bytes instead of {}, delete it to generate it again", path, size, expected_size);
+            }
+
+            // The file must not be modified while it is mapped
+            let mmap = unsafe { Mmap::map(&file)? };
+            return Ok(Self { mmap })
+        }
+
+        // Generate them in a temporary file so an interrupted generation is never used
+        info!("Generating precomputed tables");
+        debug!("Precomputed tables size: {} bytes", expected_size);
+        let tmp_path = format!("{path}.tmp");
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&tmp_path)?;
+        file.set_len(expected_size as u64)?;
+
+        let mut mmap = unsafe { MmapMut::map_mut(&file)? };
+        table_generation::create_table_file_with_progress_report(N, &mut mmap[..], progress_report)?;
+        mmap.flush()?;
+        fs::rename(&tmp_path, &path)?;
+
+        Ok(Self {
+            mmap: mmap.make_read_only()?
+        })
+    }
+
+    // Bytes of the tables, they are aligned to the page size
+    pub fn get(&self) -> &[u8] {
+        &self.mmap
+    }
+
+    pub fn l1(&self) -> usize {
+        PRECOMPUTED_TABLES_L1
+    }
+
+    pub fn bytes_count(&self) -> usize {
+        self.mmap.len()
+    }
+}
@@ -1,13 +1,16 @@
 mod hash;
 mod address;
+mod payment_request;
 mod transcript;
 
 pub mod elgamal;
 pub mod proofs;
 pub mod bech32;
+pub mod vanity;
 
 pub use hash::*;
 pub use address::*;
+pub use payment_request::*;
 pub use transcript::*;
 
 pub type PublicKey = elgamal::CompressedPublicKey;
@@ -0,0 +1,270 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr
+};
+use thiserror::Error;
+use crate::{
+    api::{DataElement, DataValue},
+    serializer::Serializer,
+    transaction::EXTRA_DATA_LIMIT_SIZE
+};
+use super::{Address, AddressType, Hash};
+
+// Scheme of the payment request URIs
+pub const PAYMENT_REQUEST_SCHEME: &str = "xelis";
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum PaymentRequestError {
+    #[error("URI must start with {}:", PAYMENT_REQUEST_SCHEME)]
+    InvalidScheme,
+    #[error("Invalid address: {}", _0)]
+    InvalidAddress(String),
+    #[error("Invalid amount, it must be a positive integer in atomic units")]
+    InvalidAmount,
+    #[error("Invalid asset hash")]
+    InvalidAsset,
+    #[error("Invalid percent encoding")]
+    InvalidEncoding,
+    #[error("Parameter {} is set more than once", _0)]
+    DuplicateParameter(String),
+    #[error("Required parameter {} is not supported", _0)]
+    UnsupportedParameter(String),
+    #[error("A message can't be set with an integrated address")]
+    MessageWithIntegratedAddress,
+    #[error("Message is too long, maximum is {} bytes", EXTRA_DATA_LIMIT_SIZE)]
+    MessageTooLong
+}
+
+// Payment requested to a payer, it is shared as a URI (in a QR code for example):
+// xelis:<address>?amount=<atomic units>&asset=<hash>&message=<percent encoded text>
+// All parameters are optional, XELIS is requested if no asset is set
+// Unknown parameters are ignored unless they are prefixed by req- like in BIP 21
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaymentRequest {
+    address: Address,
+    amount: Option<u64>,
+    asset: Option<Hash>,
+    // Sent as extra data of the transfer
+    message: Option<String>
+}
+
+impl PaymentRequest {
+    pub fn new(address: Address, amount: Option<u64>, asset: Option<Hash>, message: Option<String>) -> Result<Self, PaymentRequestError> {
+        if amount == Some(0) {
+            return Err(PaymentRequestError::InvalidAmount)
+        }
+
+        // The integrated data is already sent as extra data
+        let message = message.filter(|message| !message.is_empty());
+        if let Some(message) = message.as_ref() {
+            if !address.is_normal() {
+                return Err(PaymentRequestError::MessageWithIntegratedAddress)
+            }
+
+            if DataElement::Value(DataValue::String(message.clone())).size() > EXTRA_DATA_LIMIT_SIZE {
+                return Err(PaymentRequestError::MessageTooLong)
+            }
+        }
+
+        Ok(Self {
+            address,
+            amount,
+            asset,
+            message
+        })
+    }
+
+    pub fn get_address(&self) -> &Address {
+        &self.address
+    }
+
+    pub fn get_amount(&self) -> Option<u64> {
+        self.amount
+    }
+
+    pub fn get_asset(&self) -> Option<&Hash> {
+        self.asset.as_ref()
+    }
+
+    pub fn get_message(&self) -> Option<&String> {
+        self.message.as_ref()
+    }
+
+    // Data to send with the transfer, the message or the data of the integrated address
+    pub fn get_extra_data(&self) -> Option<DataElement> {
+        match &self.message {
+            Some(message) => Some(DataElement::Value(DataValue::String(message.clone()))),
+            None => self.address.get_extra_data().cloned()
+        }
+    }
+
+    // Address with the message integrated, for wallets which can't read the URI
+    pub fn to_integrated_address(&self) -> Address {
+        match self.get_extra_data() {
+            Some(data) => Address::new(self.address.is_mainnet(), AddressType::Data(data), self.address.get_public_key().clone()),
+            None => self.address.clone()
+        }
+    }
+
+    // Build the URI of the request
+    pub fn to_uri(&self) -> String {
+        let mut params = Vec::new();
+        if let Some(amount) = self.amount {
+            params.push(format!("amount={}", amount));
+        }
+
+        if let Some(asset) = self.asset.as_ref() {
+            params.push(format!("asset={}", asset));
+        }
+
+        if let Some(message) = self.message.as_ref() {
+            params.push(format!("message={}", percent_encode(message)));
+        }
+
+        let mut uri = format!("{}:{}", PAYMENT_REQUEST_SCHEME, self.address);
+        if !params.is_empty() {
+            uri.push('?');
+            uri.push_str(&params.join("&"));
+        }
+
+        uri
+    }
+
+    // Parse a request from its URI
+    pub fn from_uri(uri: &str) -> Result<Self, PaymentRequestError> {
+        let (scheme, rest) = uri.trim().split_once(':')
+            .ok_or(PaymentRequestError::InvalidScheme)?;
+        if !scheme.eq_ignore_ascii_case(PAYMENT_REQUEST_SCHEME) {
+            return Err(PaymentRequestError::InvalidScheme)
+        }
+
+        // Some QR code readers are adding the // of the URLs
+        let rest = rest.strip_prefix("//").unwrap_or(rest);
+        let (address, query) = match rest.split_once('?') {
+            Some((address, query)) => (address, Some(query)),
+            None => (rest, None)
+        };
+
+        let address = Address::from_str(address)
+            .map_err(|e| PaymentRequestError::InvalidAddress(e.to_string()))?;
+
+        let mut amount = None;
+        let mut asset = None;
+        let mut message = None;
+        for param in query.into_iter().flat_map(|query| query.split('&')).filter(|param| !param.is_empty()) {
+            let (key, value) = param.split_once('=').unwrap_or((param, ""));
+            match key {
+                "amount" => {
+                    let value = value.parse().map_err(|_| PaymentRequestError::InvalidAmount)?;
+                    if amount.replace(value).is_some() {
+                        return Err(PaymentRequestError::DuplicateParameter(key.to_owned()))
+                    }
+                },
+                "asset" => {
+                    let value = Hash::from_hex(value.to_owned()).map_err(|_| PaymentRequestError::InvalidAsset)?;
+                    if asset.replace(value).is_some() {
+                        return Err(PaymentRequestError::DuplicateParameter(key.to_owned()))
+                    }
+                },
+                "message" => {
+                    let value = percent_decode(value)?;
+                    if message.replace(value).is_some() {
+                        return Err(PaymentRequestError::DuplicateParameter(key.to_owned()))
+                    }
+                },
+                key if key.starts_with("req-") => return Err(PaymentRequestError::UnsupportedParameter(key.to_owned())),
+                _ => {}
+            }
+        }
+
+        Self::new(address, amount, asset, message)
+    }
+}
+
+impl Display for PaymentRequest {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_uri())
+    }
+}
+
+impl FromStr for PaymentRequest {
+    type Err = PaymentRequestError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_uri(s)
+    }
+}
+
+// Encode all the characters except the unreserved ones from RFC 3986
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte))
+        }
+    }
+    encoded
+}
+
+// Decode a percent encoded UTF-8 value, + is accepted as a space
+fn percent_decode(value: &str) -> Result<String, PaymentRequestError> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let hex = bytes.get(i + 1..i + 3).ok_or(PaymentRequestError::InvalidEncoding)?;
+                let hex = std::str::from_utf8(hex).map_err(|_| PaymentRequestError::InvalidEncoding)?;
+                decoded.push(u8::from_str_radix(hex, 16).map_err(|_| PaymentRequestError::InvalidEncoding)?);
+                i += 3;
+            },
+            b'+' => {
+                decoded.push(b' ');
+                i += 1;
+            },
+            byte => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8(decoded).map_err(|_| PaymentRequestError::InvalidEncoding)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::crypto::KeyPair;
+    use super::*;
+
+    #[test]
+    fn test_payment_request_uri() {
+        let address = KeyPair::new().get_public_key().to_address(true);
+        let request = PaymentRequest::new(address, Some(150_000_000), Some(Hash::max()), Some("Order #42 & co".to_owned())).unwrap();
+
+        let uri = request.to_uri();
+        assert!(uri.starts_with("xelis:xel:"));
+        assert!(uri.contains("message=Order%20%2342%20%26%20co"));
+        assert_eq!(PaymentRequest::from_uri(&uri).unwrap(), request);
+
+        // Integrated address carries the message
+        let integrated = request.to_integrated_address();
+        assert_eq!(integrated.get_extra_data(), request.get_extra_data().as_ref());
+    }
+
+    #[test]
+    fn test_payment_request_invalid() {
+        let address = KeyPair::new().get_public_key().to_address(false);
+        assert_eq!(PaymentRequest::from_uri(&format!("bitcoin:{}", address)), Err(PaymentRequestError::InvalidScheme));
+        assert_eq!(PaymentRequest::from_uri(&format!("xelis:{}?amount=-1", address)), Err(PaymentRequestError::InvalidAmount));
+        assert_eq!(PaymentRequest::from_uri(&format!("xelis:{}?amount=1&amount=2", address)), Err(PaymentRequestError::DuplicateParameter("amount".to_owned())));
+        assert_eq!(PaymentRequest::from_uri(&format!("xelis:{}?req-expiry=10", address)), Err(PaymentRequestError::UnsupportedParameter("req-expiry".to_owned())));
+        assert_eq!(PaymentRequest::from_uri(&format!("xelis:{}?message=%E9", address)), Err(PaymentRequestError::InvalidEncoding));
+
+        // Unknown parameters are ignored
+        let request = PaymentRequest::from_uri(&format!("xelis:{}?label=shop", address)).unwrap();
+        assert_eq!(request.get_amount(), None);
+    }
+}
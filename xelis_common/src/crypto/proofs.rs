@@ -14,6 +14,9 @@ use crate::{serializer::{Reader, ReaderError, Serializer, Writer}, transaction::
 use super::{
     elgamal::{
         Ciphertext,
+        CompressedCiphertext,
+        CompressedCommitment,
+        CompressedPublicKey,
         DecompressionError,
         DecryptHandle,
         KeyPair,
@@ -24,6 +27,7 @@ use super::{
         RISTRETTO_COMPRESSED_SIZE,
         SCALAR_SIZE
     },
+    Hash,
     ProtocolTranscript,
     TranscriptError
 };
@@ -71,7 +75,7 @@ pub enum ProofVerificationError {
 
 /// Proof that a commitment and ciphertext are equal.
 #[allow(non_snake_case)]
-#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Serializer)]
 pub struct CommitmentEqProof {
     Y_0: CompressedRistretto,
     Y_1: CompressedRistretto,
@@ -249,7 +253,7 @@ impl CommitmentEqProof {
 }
 
 #[allow(non_snake_case)]
-#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Serializer)]
 pub struct CiphertextValidityProof {
     Y_0: CompressedRistretto,
     Y_1: CompressedRistretto,
@@ -355,56 +359,6 @@ impl CiphertextValidityProof {
     }
 }
 
-#[allow(non_snake_case)]
-impl Serializer for CommitmentEqProof {
-    fn write(&self, writer: &mut Writer) {
-        self.Y_0.write(writer);
-        self.Y_1.write(writer);
-        self.Y_2.write(writer);
-        self.z_s.write(writer);
-        self.z_x.write(writer);
-        self.z_r.write(writer);
-    }
-
-    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
-        let Y_0 = CompressedRistretto::read(reader)?;
-        let Y_1 = CompressedRistretto::read(reader)?;
-        let Y_2 = CompressedRistretto::read(reader)?;
-        let z_s = Scalar::read(reader)?;
-        let z_x = Scalar::read(reader)?;
-        let z_r = Scalar::read(reader)?;
-
-        Ok(Self { Y_0, Y_1, Y_2, z_s, z_x, z_r })
-    }
-
-    fn size(&self) -> usize {
-        RISTRETTO_COMPRESSED_SIZE * 3 + SCALAR_SIZE * 3
-    }    
-}
-
-#[allow(non_snake_case)]
-impl Serializer for CiphertextValidityProof {
-    fn write(&self, writer: &mut Writer) {
-        self.Y_0.write(writer);
-        self.Y_1.write(writer);
-        self.z_r.write(writer);
-        self.z_x.write(writer);
-    }
-
-    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
-        let Y_0 = CompressedRistretto::read(reader)?;
-        let Y_1 = CompressedRistretto::read(reader)?;
-        let z_r = Scalar::read(reader)?;
-        let z_x = Scalar::read(reader)?;
-
-        Ok(Self { Y_0, Y_1, z_r, z_x })
-    }
-
-    fn size(&self) -> usize {
-        RISTRETTO_COMPRESSED_SIZE * 2 + SCALAR_SIZE * 2
-    }
-}
-
 #[allow(non_snake_case)]
 impl Serializer for RangeProof {
     fn write(&self, writer: &mut Writer) {
@@ -439,6 +393,105 @@ impl Serializer for RangeProof {
     }
 }
 
+// Proof that the balance of an account for an asset is at least a minimum amount at a topoheight
+// Neither the balance nor the private key are revealed
+// The balance ciphertext is not included, the verifier must retrieve it from the chain
+#[derive(Clone, Debug, Serializer)]
+pub struct BalanceProof {
+    asset: Hash,
+    topoheight: u64,
+    minimum: u64,
+    // Commitment of the balance minus the minimum
+    commitment: CompressedCommitment,
+    // The commitment and the balance minus the minimum are equal
+    eq_proof: CommitmentEqProof,
+    // The commitment is not negative
+    range_proof: RangeProof
+}
+
+impl BalanceProof {
+    // Statement shared by the prover and the verifier
+    fn transcript(public_key: &CompressedPublicKey, asset: &Hash, topoheight: u64, minimum: u64, ciphertext: &CompressedCiphertext, commitment: &CompressedCommitment) -> Transcript {
+        let mut transcript = Transcript::new(b"balance-proof");
+        transcript.balance_proof_domain_separator();
+        transcript.append_public_key(b"source_pubkey", public_key);
+        transcript.append_hash(b"asset", asset);
+        transcript.append_u64(b"topoheight", topoheight);
+        transcript.append_u64(b"minimum", minimum);
+        transcript.append_ciphertext(b"source_ct", ciphertext);
+        transcript.append_commitment(b"commitment", commitment);
+        transcript
+    }
+
+    // Prove that the balance ciphertext at the topoheight, which decrypts to balance, is at least minimum
+    pub fn new(keypair: &KeyPair, asset: Hash, topoheight: u64, ciphertext: &Ciphertext, balance: u64, minimum: u64) -> Result<Self, ProofGenerationError> {
+        let amount = balance.checked_sub(minimum)
+            .ok_or(ProofGenerationError::InsufficientFunds)?;
+
+        let opening = PedersenOpening::generate_new();
+        let commitment = PedersenCommitment::new_with_opening(amount, &opening).compress();
+
+        let mut transcript = Self::transcript(&keypair.get_public_key().compress(), &asset, topoheight, minimum, &ciphertext.compress(), &commitment);
+        let remaining = ciphertext.clone() - minimum;
+        let eq_proof = CommitmentEqProof::new(keypair, &remaining, &opening, amount, &mut transcript);
+
+        let (range_proof, _) = RangeProof::prove_multiple(
+            &BP_GENS,
+            &PC_GENS,
+            &mut transcript,
+            &[amount],
+            &[opening.as_scalar()],
+            BULLET_PROOF_SIZE,
+        )?;
+
+        Ok(Self {
+            asset,
+            topoheight,
+            minimum,
+            commitment,
+            eq_proof,
+            range_proof
+        })
+    }
+
+    pub fn get_asset(&self) -> &Hash {
+        &self.asset
+    }
+
+    pub fn get_topoheight(&self) -> u64 {
+        self.topoheight
+    }
+
+    pub fn get_minimum(&self) -> u64 {
+        self.minimum
+    }
+}
+
+// Verify a balance proof with the public key of the account and its balance ciphertext at the proof topoheight
+pub fn verify_balance_proof(proof: &BalanceProof, public_key: &CompressedPublicKey, ciphertext: &CompressedCiphertext) -> Result<(), ProofVerificationError> {
+    let mut transcript = BalanceProof::transcript(public_key, &proof.asset, proof.topoheight, proof.minimum, ciphertext, &proof.commitment);
+
+    let source_pubkey = public_key.decompress()?;
+    let remaining = ciphertext.decompress()? - proof.minimum;
+    let commitment = proof.commitment.decompress()?;
+
+    let mut batch_collector = BatchCollector::default();
+    proof.eq_proof.pre_verify(&source_pubkey, &remaining, &commitment, &mut transcript, &mut batch_collector)?;
+    batch_collector.verify()
+        .map_err(|_| ProofVerificationError::CommitmentEqProof)?;
+
+    RangeProof::verify_multiple(
+        &proof.range_proof,
+        &BP_GENS,
+        &PC_GENS,
+        &mut transcript,
+        &[(commitment.as_point().clone(), proof.commitment.as_point().clone())],
+        BULLET_PROOF_SIZE,
+    )?;
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -481,6 +534,26 @@ mod tests {
         assert!(batch_collector.verify().is_ok());
     }
 
+    #[test]
+    fn test_balance_proof() {
+        let keypair = KeyPair::new();
+        let asset = Hash::zero();
+        let balance = 100u64;
+        let ciphertext = keypair.get_public_key().encrypt(balance);
+        let public_key = keypair.get_public_key().compress();
+
+        let proof = BalanceProof::new(&keypair, asset.clone(), 10, &ciphertext, balance, 60).unwrap();
+        let proof = BalanceProof::from_bytes(&proof.to_bytes()).unwrap();
+        assert!(verify_balance_proof(&proof, &public_key, &ciphertext.compress()).is_ok());
+
+        // Another account can't use it
+        let other = KeyPair::new();
+        assert!(verify_balance_proof(&proof, &other.get_public_key().compress(), &ciphertext.compress()).is_err());
+
+        // Not enough funds
+        assert!(BalanceProof::new(&keypair, asset, 10, &ciphertext, balance, 101).is_err());
+    }
+
     #[test]
     fn test_ciphertext_validity_proof() {
         let mut transcript = Transcript::new(b"test");
@@ -27,6 +27,7 @@ pub trait ProtocolTranscript {
     fn new_commitment_eq_proof_domain_separator(&mut self);
     fn transfer_proof_domain_separator(&mut self);
     fn ciphertext_validity_proof_domain_separator(&mut self);
+    fn balance_proof_domain_separator(&mut self);
 }
 
 impl ProtocolTranscript for Transcript {
@@ -91,4 +92,8 @@ impl ProtocolTranscript for Transcript {
     fn ciphertext_validity_proof_domain_separator(&mut self) {
         self.append_message(b"dom-sep", b"validity-proof");
     }
+
+    fn balance_proof_domain_separator(&mut self) {
+        self.append_message(b"dom-sep", b"balance-proof");
+    }
 }
@@ -0,0 +1,231 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Mutex
+    },
+    thread,
+    time::Duration
+};
+use thiserror::Error;
+use super::{bech32::CHARSET, KeyPair};
+
+// Maximum characters allowed in the prefix
+// Each character multiply by 32 the expected attempts
+pub const VANITY_MAX_PREFIX_LEN: usize = 8;
+// Maximum characters allowed in the suffix
+// Only the checksum (6 chars) is random at the end of a normal address
+pub const VANITY_MAX_SUFFIX_LEN: usize = 6;
+// How many attempts a thread does before updating the shared counter
+const ATTEMPTS_BATCH: u64 = 64;
+
+#[derive(Error, Debug)]
+pub enum VanityError {
+    #[error("Prefix and suffix are empty")]
+    EmptyPattern,
+    #[error("Prefix is too long, maximum is {} chars", VANITY_MAX_PREFIX_LEN)]
+    PrefixTooLong,
+    #[error("Suffix is too long, maximum is {} chars", VANITY_MAX_SUFFIX_LEN)]
+    SuffixTooLong,
+    #[error("Invalid character '{}', only '{}' are allowed", _0, CHARSET)]
+    InvalidCharacter(char),
+    #[error("Character '{}' is impossible at position 2 of the prefix", _0)]
+    ImpossiblePrefix(char)
+}
+
+// Shared state of a vanity search
+// It can be read from another thread to report the progress
+// or to stop the search
+pub struct VanityProgress {
+    attempts: AtomicU64,
+    stopped: AtomicBool
+}
+
+impl VanityProgress {
+    pub fn new() -> Self {
+        Self {
+            attempts: AtomicU64::new(0),
+            stopped: AtomicBool::new(false)
+        }
+    }
+
+    // Total attempts done by all threads
+    pub fn get_attempts(&self) -> u64 {
+        self.attempts.load(Ordering::Relaxed)
+    }
+
+    // Request all threads to stop the search
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::Release);
+    }
+
+    // Is the search stopped (found or cancelled)
+    pub fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::Acquire)
+    }
+}
+
+// Search a key pair whose address starts and/or ends with the requested patterns
+// Patterns are matched against the bech32 encoded part of the address (after the "xel:" prefix)
+pub struct VanitySearch {
+    prefix: String,
+    suffix: String,
+    mainnet: bool
+}
+
+impl VanitySearch {
+    pub fn new(prefix: String, suffix: String, mainnet: bool) -> Result<Self, VanityError> {
+        let prefix = prefix.to_lowercase();
+        let suffix = suffix.to_lowercase();
+
+        if prefix.is_empty() && suffix.is_empty() {
+            return Err(VanityError::EmptyPattern)
+        }
+
+        if prefix.len() > VANITY_MAX_PREFIX_LEN {
+            return Err(VanityError::PrefixTooLong)
+        }
+
+        if suffix.len() > VANITY_MAX_SUFFIX_LEN {
+            return Err(VanityError::SuffixTooLong)
+        }
+
+        for c in prefix.chars().chain(suffix.chars()) {
+            if !CHARSET.contains(c) {
+                return Err(VanityError::InvalidCharacter(c))
+            }
+        }
+
+        // A compressed ristretto point is always "non-negative": its first bit is always zero
+        // This is the third bit of the second char, so half of the charset is impossible there
+        if let Some(c) = prefix.chars().nth(1) {
+            if Self::char_value(c) & 0b00100 != 0 {
+                return Err(VanityError::ImpossiblePrefix(c))
+            }
+        }
+
+        Ok(Self {
+            prefix,
+            suffix,
+            mainnet
+        })
+    }
+
+    fn char_value(c: char) -> usize {
+        CHARSET.find(c).unwrap_or(0)
+    }
+
+    // Check if the encoded address match our patterns
+    pub fn is_matching(&self, address: &str) -> bool {
+        let data = address.split_once(':')
+            .map(|(_, data)| data)
+            .unwrap_or(address);
+
+        data.starts_with(&self.prefix) && data.ends_with(&self.suffix)
+    }
+
+    // Expected attempts to find a matching address
+    // It is capped to u64::MAX for the longest patterns
+    pub fn expected_attempts(&self) -> u64 {
+        let chars = (self.prefix.len() + self.suffix.len()) as u32;
+        let mut attempts = 32u64.saturating_pow(chars);
+        // Second char of the prefix has only 16 possibilities
+        if self.prefix.len() >= 2 {
+            attempts /= 2;
+        }
+        attempts
+    }
+
+    // Estimate the time needed to find a matching address at the given rate (attempts per second)
+    pub fn estimated_time(&self, rate: f64) -> Option<Duration> {
+        if rate <= 0.0 {
+            return None
+        }
+
+        // Too long to be represented for the longest patterns at a low rate
+        Duration::try_from_secs_f64(self.expected_attempts() as f64 / rate).ok()
+    }
+
+    // Generate a random key pair and check if it matches
+    fn try_once(&self) -> Option<KeyPair> {
+        let keypair = KeyPair::new();
+        let address = keypair.get_public_key().to_address(self.mainnet);
+        match address.as_string() {
+            Ok(address) if self.is_matching(&address) => Some(keypair),
+            _ => None
+        }
+    }
+
+    // Search using N threads until a key pair is found or the search is stopped
+    // This is a blocking function, call VanityProgress::stop from another thread to cancel it
+    pub fn search(&self, threads: usize, progress: &VanityProgress) -> Option<KeyPair> {
+        let result = Mutex::new(None);
+        thread::scope(|s| {
+            for _ in 0..threads.max(1) {
+                s.spawn(|| {
+                    let mut attempts = 0;
+                    while !progress.is_stopped() {
+                        if let Some(keypair) = self.try_once() {
+                            if let Ok(mut result) = result.lock() {
+                                if result.is_none() {
+                                    *result = Some(keypair);
+                                }
+                            }
+                            progress.stop();
+                        }
+
+                        attempts += 1;
+                        if attempts == ATTEMPTS_BATCH {
+                            progress.attempts.fetch_add(attempts, Ordering::Relaxed);
+                            attempts = 0;
+                        }
+                    }
+                    progress.attempts.fetch_add(attempts, Ordering::Relaxed);
+                });
+            }
+        });
+
+        result.into_inner().ok().flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalid_patterns() {
+        assert!(matches!(VanitySearch::new(String::new(), String::new(), true), Err(VanityError::EmptyPattern)));
+        assert!(matches!(VanitySearch::new("b".into(), String::new(), true), Err(VanityError::InvalidCharacter('b'))));
+        assert!(matches!(VanitySearch::new("123456789".into(), String::new(), true), Err(VanityError::PrefixTooLong)));
+        // 'x' has value 6, its third bit is set
+        assert!(matches!(VanitySearch::new("qx".into(), String::new(), true), Err(VanityError::ImpossiblePrefix('x'))));
+    }
+
+    #[test]
+    fn test_expected_attempts() {
+        let search = VanitySearch::new("q".into(), "q".into(), true).unwrap();
+        assert_eq!(search.expected_attempts(), 32 * 32);
+
+        let search = VanitySearch::new("qq".into(), String::new(), true).unwrap();
+        assert_eq!(search.expected_attempts(), 32 * 16);
+
+        // 32^14 doesn't fit in a u64
+        let prefix = "q".repeat(VANITY_MAX_PREFIX_LEN);
+        let suffix = "q".repeat(VANITY_MAX_SUFFIX_LEN);
+        let search = VanitySearch::new(prefix, suffix, true).unwrap();
+        assert_eq!(search.expected_attempts(), u64::MAX / 2);
+        assert!(search.estimated_time(1.0).is_some());
+        assert!(search.estimated_time(0.1).is_none());
+    }
+
+    #[test]
+    fn test_search() {
+        let search = VanitySearch::new("x".into(), String::new(), false).unwrap();
+        let progress = VanityProgress::new();
+        let keypair = search.search(2, &progress).unwrap();
+        let address = keypair.get_public_key().to_address(false).as_string().unwrap();
+        assert!(address.starts_with("xet:x"));
+        assert!(progress.is_stopped());
+        assert!(progress.get_attempts() > 0);
+    }
+}
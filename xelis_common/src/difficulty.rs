@@ -37,6 +37,37 @@ pub fn compute_difficulty_target(difficulty: &Difficulty) -> Result<U256, Diffic
     Ok(U256::max_value() / diff)
 }
 
+// Compact representation of a target on 4 bytes, same format as the Bitcoin "bits"
+// The highest byte is the size of the target in bytes and the 3 others are its most significant bytes
+// Some precision is lost, it should only be used for display
+pub fn compact_from_target(target: &U256) -> u32 {
+    let mut size = (target.bits() + 7) / 8;
+    let mut mantissa = if size <= 3 {
+        target.low_u64() << (8 * (3 - size))
+    } else {
+        (*target >> (8 * (size - 3))).low_u64()
+    };
+
+    // the mantissa is signed, keep its highest bit unset
+    if mantissa & 0x00800000 != 0 {
+        mantissa >>= 8;
+        size += 1;
+    }
+
+    ((size as u32) << 24) | mantissa as u32
+}
+
+// Get back the target from its compact representation
+pub fn target_from_compact(compact: u32) -> U256 {
+    let size = (compact >> 24) as usize;
+    let mantissa = U256::from(compact & 0x007fffff);
+    if size <= 3 {
+        mantissa >> (8 * (3 - size))
+    } else {
+        mantissa << (8 * (size - 3))
+    }
+}
+
 // Check if the hash is below the target difficulty
 pub fn check_difficulty_against_target(hash: &Hash, target: &U256) -> bool {
     let hash_work = U256::from_big_endian(hash.as_bytes());
@@ -48,4 +79,33 @@ pub fn check_difficulty_against_target(hash: &Hash, target: &U256) -> bool {
 #[inline(always)]
 pub fn difficulty_from_hash(hash: &Hash) -> Difficulty {
     (U256::max_value() / U256::from_big_endian(hash.as_bytes())).into()
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use primitive_types::U256;
+    use super::*;
+
+    #[test]
+    fn test_compact_small_target() {
+        let target = U256::from(0x1234u64);
+        let compact = compact_from_target(&target);
+        assert_eq!(compact, 0x02123400);
+        assert_eq!(target_from_compact(compact), target);
+    }
+
+    #[test]
+    fn test_compact_max_target() {
+        let compact = compact_from_target(&U256::max_value());
+        assert_eq!(compact, 0x2100ffff);
+    }
+
+    #[test]
+    fn test_compact_difficulty_target() {
+        let target = compute_difficulty_target(&Difficulty::from_u64(100_000)).unwrap();
+        let decoded = target_from_compact(compact_from_target(&target));
+        // at least the 2 most significant bytes are kept
+        assert!(decoded <= target);
+        assert_eq!(decoded >> (target.bits() - 16), target >> (target.bits() - 16));
+    }
+}
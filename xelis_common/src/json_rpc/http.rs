@@ -1,81 +1,190 @@
-use reqwest::Client as HttpClient;
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration
+};
+use async_trait::async_trait;
+use log::{debug, trace};
+use reqwest::{
+    Client as HttpClient,
+    StatusCode
+};
 use serde::{
     Serialize,
     de::DeserializeOwned
 };
+use serde_json::{json, Value};
+use tokio::time::sleep;
 use super::{
-    JsonRPCResult, JsonRPCErrorResponse, JsonRPCError,
+    JsonRPCResult, JsonRPCErrorResponse, JsonRPCError, JsonRPCTransport,
     JSON_RPC_VERSION, PARSE_ERROR_CODE, INVALID_REQUEST_CODE,
     METHOD_NOT_FOUND_CODE, INVALID_PARAMS_CODE, INTERNAL_ERROR_CODE
 };
-use serde_json::{json, Value};
-use std::sync::atomic::{AtomicUsize, Ordering};
 
+// Options of the HTTP transport
+#[derive(Debug, Clone)]
+pub struct HttpClientConfig {
+    // Maximum time for a whole request, including the response body
+    pub timeout: Duration,
+    // Maximum time to open a new connection
+    pub connect_timeout: Duration,
+    // How many times a failed request is sent again
+    // Only requests which may not have reached the server or got a temporary error are retried
+    pub max_retries: u32,
+    // Delay before the first retry, doubled on each new retry
+    pub initial_backoff: Duration,
+    // Upper bound of the delay between two retries
+    pub max_backoff: Duration,
+    // Idle connections kept open per host to be reused
+    pub pool_max_idle_per_host: usize,
+    // How long an idle connection is kept in the pool
+    pub pool_idle_timeout: Duration
+}
+
+impl Default for HttpClientConfig {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(15),
+            connect_timeout: Duration::from_secs(5),
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(250),
+            max_backoff: Duration::from_secs(5),
+            pool_max_idle_per_host: 8,
+            pool_idle_timeout: Duration::from_secs(90)
+        }
+    }
+}
+
+impl HttpClientConfig {
+    // Delay to wait before the retry number `attempt`, starting at 0
+    fn backoff(&self, attempt: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_backoff)
+    }
+}
+
+// A JSON-RPC Client over HTTP
+// Connections are pooled and reused between requests, it can be shared between tasks
 pub struct JsonRPCClient {
     http: HttpClient,
     target: String,
     count: AtomicUsize,
+    config: HttpClientConfig
 }
 
 impl JsonRPCClient {
-    pub fn new(target: String) -> Self {
-        JsonRPCClient {
-            http: HttpClient::new(),
+    pub fn new(target: String) -> JsonRPCResult<Self> {
+        Self::with_config(target, HttpClientConfig::default())
+    }
+
+    pub fn with_config(target: String, config: HttpClientConfig) -> JsonRPCResult<Self> {
+        let http = HttpClient::builder()
+            .timeout(config.timeout)
+            .connect_timeout(config.connect_timeout)
+            .pool_max_idle_per_host(config.pool_max_idle_per_host)
+            .pool_idle_timeout(config.pool_idle_timeout)
+            .build()?;
+
+        Ok(JsonRPCClient {
+            http,
             target,
             count: AtomicUsize::new(0),
-        }
+            config
+        })
+    }
+
+    pub fn get_config(&self) -> &HttpClientConfig {
+        &self.config
+    }
+
+    // Generate a new ID for a JSON-RPC request
+    fn next_id(&self) -> usize {
+        self.count.fetch_add(1, Ordering::SeqCst)
     }
 
     pub async fn call<R: DeserializeOwned>(&self, method: &str) -> JsonRPCResult<R> {
-        let id = self.count.fetch_add(1, Ordering::SeqCst);
         self.send(json!({
             "jsonrpc": JSON_RPC_VERSION,
             "method": method,
-            "id": id
+            "id": self.next_id()
         })).await
     }
 
     pub async fn call_with<P, R>(&self, method: &str, params: &P) -> JsonRPCResult<R>
-        where P: Serialize + Sized, R: DeserializeOwned
+        where P: Serialize, R: DeserializeOwned
     {
-        let id = self.count.fetch_add(1, Ordering::SeqCst);
         self.send(json!({
             "jsonrpc": JSON_RPC_VERSION,
             "method": method,
-            "id": id,
+            "id": self.next_id(),
             "params": params
         })).await
     }
 
     pub async fn notify(&self, method: &str) -> JsonRPCResult<()> {
-        self.http.post(&self.target)
-            .json(&json!({
-                "jsonrpc": JSON_RPC_VERSION,
-                "method": method
-            }))
-            .send().await?;
+        self.post(&json!({
+            "jsonrpc": JSON_RPC_VERSION,
+            "method": method
+        })).await?;
         Ok(())
     }
 
-    pub async fn notify_with<P>(&self, method: &str, params: P) -> JsonRPCResult<()>
-        where P: Serialize + Sized
+    pub async fn notify_with<P>(&self, method: &str, params: &P) -> JsonRPCResult<()>
+        where P: Serialize
     {
-        self.http
-            .post(&self.target)
-            .json(&json!({
-                "jsonrpc": JSON_RPC_VERSION,
-                "method": method,
-                "params": &params
-            }))
-            .send().await?;
+        self.post(&json!({
+            "jsonrpc": JSON_RPC_VERSION,
+            "method": method,
+            "params": params
+        })).await?;
         Ok(())
     }
 
+    // Should the request be sent again after this error
+    // A request is retried only if it may not have been executed or if the server is temporarily unavailable
+    fn is_retryable(error: &reqwest::Error) -> bool {
+        if error.is_connect() || error.is_timeout() {
+            return true
+        }
+
+        matches!(
+            error.status(),
+            Some(StatusCode::TOO_MANY_REQUESTS | StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT)
+        )
+    }
+
+    // Post the request and read the response body, retrying with an exponential backoff
+    async fn post(&self, value: &Value) -> JsonRPCResult<Value> {
+        let mut attempt = 0;
+        loop {
+            let res = async {
+                self.http.post(&self.target)
+                    .json(value)
+                    .send().await?
+                    .error_for_status()?
+                    .bytes().await
+            }.await;
+
+            match res {
+                Ok(bytes) if bytes.is_empty() => return Ok(Value::Null),
+                Ok(bytes) => return Ok(serde_json::from_slice(&bytes)?),
+                Err(e) if attempt < self.config.max_retries && Self::is_retryable(&e) => {
+                    let delay = self.config.backoff(attempt);
+                    debug!("HTTP request to {} failed: {}, retrying in {:?}", self.target, e, delay);
+                    sleep(delay).await;
+                    attempt += 1;
+                },
+                Err(e) if e.is_timeout() => {
+                    trace!("HTTP request to {} timed out", self.target);
+                    return Err(JsonRPCError::TimedOut)
+                },
+                Err(e) => return Err(e.into())
+            }
+        }
+    }
+
     pub async fn send<R: DeserializeOwned>(&self, value: Value) -> JsonRPCResult<R> {
-        let mut response: Value = self.http.post(&self.target)
-            .json(&value)
-            .send().await?
-            .json().await?;
+        let mut response = self.post(&value).await?;
 
         if let Some(error) = response.get_mut("error") {
             let error: JsonRPCErrorResponse = serde_json::from_value(error.take())?;
@@ -108,4 +217,39 @@ impl JsonRPCClient {
                 .take(),
         )?)
     }
-}
\ No newline at end of file
+}
+
+#[async_trait]
+impl JsonRPCTransport for JsonRPCClient {
+    async fn call<R: DeserializeOwned + Send>(&self, method: &str) -> JsonRPCResult<R> {
+        JsonRPCClient::call(self, method).await
+    }
+
+    async fn call_with<P: Serialize + Sync, R: DeserializeOwned + Send>(&self, method: &str, params: &P) -> JsonRPCResult<R> {
+        JsonRPCClient::call_with(self, method, params).await
+    }
+
+    async fn notify_with<P: Serialize + Sync>(&self, method: &str, params: &P) -> JsonRPCResult<()> {
+        JsonRPCClient::notify_with(self, method, params).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_is_exponential_and_bounded() {
+        let config = HttpClientConfig {
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(1),
+            ..Default::default()
+        };
+
+        assert_eq!(config.backoff(0), Duration::from_millis(100));
+        assert_eq!(config.backoff(1), Duration::from_millis(200));
+        assert_eq!(config.backoff(3), Duration::from_millis(800));
+        assert_eq!(config.backoff(4), Duration::from_secs(1));
+        assert_eq!(config.backoff(u32::MAX), Duration::from_secs(1));
+    }
+}
@@ -1,13 +1,16 @@
-use serde::Deserialize;
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::Value;
 use thiserror::Error;
 use tokio_tungstenite::tungstenite::Error as TungsteniteError;
 
 mod http;
 mod websocket;
+mod tls;
 
-pub use http::JsonRPCClient;
-pub use websocket::{WebSocketJsonRPCClientImpl, WebSocketJsonRPCClient, EventReceiver};
+pub use http::{JsonRPCClient, HttpClientConfig};
+pub use tls::TlsConfig;
+pub use websocket::{WebSocketJsonRPCClientImpl, WebSocketJsonRPCClient, EventReceiver, EventMessage};
 
 const JSON_RPC_VERSION: &str = "2.0";
 
@@ -19,6 +22,20 @@ const INTERNAL_ERROR_CODE: i16 = -32603;
 
 pub type JsonRPCResult<T> = Result<T, JsonRPCError>;
 
+// Typed requests shared by the HTTP and WebSocket clients
+// Code using it can work with any transport
+#[async_trait]
+pub trait JsonRPCTransport: Send + Sync {
+    // Call a method without parameters
+    async fn call<R: DeserializeOwned + Send>(&self, method: &str) -> JsonRPCResult<R>;
+
+    // Call a method with parameters
+    async fn call_with<P: Serialize + Sync, R: DeserializeOwned + Send>(&self, method: &str, params: &P) -> JsonRPCResult<R>;
+
+    // Send a request without waiting for its response
+    async fn notify_with<P: Serialize + Sync>(&self, method: &str, params: &P) -> JsonRPCResult<()>;
+}
+
 #[derive(Debug, Deserialize)]
 struct JsonRPCResponse {
     id: Option<usize>,
@@ -59,6 +76,8 @@ pub enum JsonRPCError {
     NoResponse,
     #[error("No response in the given time")]
     TimedOut,
+    #[error("Connection lost before the response")]
+    ConnectionLost,
     #[error("Server returned a response without result")]
     MissingResult,
     #[error("Error while (de)serializing JSON data: {}", _0)]
@@ -72,5 +91,7 @@ pub enum JsonRPCError {
     #[error(transparent)]
     SocketError(#[from] TungsteniteError),
     #[error(transparent)]
-    Any(#[from] anyhow::Error)
+    Any(#[from] anyhow::Error),
+    #[error("Invalid TLS configuration: {}", _0)]
+    TlsConfig(String)
 }
\ No newline at end of file
@@ -0,0 +1,129 @@
+use std::{fs::File, io::BufReader, sync::Arc};
+use rustls::{
+    client::danger::{
+        HandshakeSignatureValid,
+        ServerCertVerified,
+        ServerCertVerifier
+    },
+    crypto::{
+        ring::default_provider,
+        verify_tls12_signature,
+        verify_tls13_signature,
+        WebPkiSupportedAlgorithms
+    },
+    pki_types::{CertificateDer, ServerName, UnixTime},
+    ClientConfig,
+    DigitallySignedStruct,
+    Error as TlsError,
+    RootCertStore,
+    SignatureScheme
+};
+use sha2::{Digest, Sha256};
+use tokio_tungstenite::Connector;
+use super::JsonRPCError;
+
+// TLS options of a connection to a wss:// server
+// Default authorities are used if nothing is set
+#[derive(Debug, Clone, Default)]
+pub struct TlsConfig {
+    // PEM file of the certificate authorities trusted instead of the default ones
+    pub ca_bundle: Option<String>,
+    // SHA-256 fingerprint in hex of the server certificate
+    // Only this certificate is accepted, even if self-signed
+    pub pinned_fingerprint: Option<String>
+}
+
+impl TlsConfig {
+    // No custom verification configured
+    pub fn is_default(&self) -> bool {
+        self.ca_bundle.is_none() && self.pinned_fingerprint.is_none()
+    }
+
+    // Build the connector verifying the server certificate
+    pub fn build_connector(&self) -> Result<Connector, JsonRPCError> {
+        let builder = ClientConfig::builder();
+        let config = match (&self.pinned_fingerprint, &self.ca_bundle) {
+            (Some(_), Some(_)) => return Err(JsonRPCError::TlsConfig("a pinned certificate and a CA bundle can't be used together".to_owned())),
+            (Some(fingerprint), None) => builder.dangerous()
+                .with_custom_certificate_verifier(Arc::new(PinnedCertificateVerifier::new(fingerprint)?))
+                .with_no_client_auth(),
+            (None, ca_bundle) => {
+                let roots = match ca_bundle {
+                    Some(path) => load_ca_bundle(path)?,
+                    None => {
+                        let mut roots = RootCertStore::empty();
+                        roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+                        roots
+                    }
+                };
+                builder.with_root_certificates(roots).with_no_client_auth()
+            }
+        };
+
+        Ok(Connector::Rustls(Arc::new(config)))
+    }
+}
+
+// Read all the certificates of a PEM file
+fn load_ca_bundle(path: &str) -> Result<RootCertStore, JsonRPCError> {
+    let file = File::open(path)
+        .map_err(|e| JsonRPCError::TlsConfig(format!("can't open CA bundle {}: {}", path, e)))?;
+
+    let mut roots = RootCertStore::empty();
+    for cert in rustls_pemfile::certs(&mut BufReader::new(file)) {
+        let cert = cert.map_err(|e| JsonRPCError::TlsConfig(format!("invalid certificate in CA bundle {}: {}", path, e)))?;
+        roots.add(cert).map_err(|e| JsonRPCError::TlsConfig(format!("invalid certificate in CA bundle {}: {}", path, e)))?;
+    }
+
+    if roots.is_empty() {
+        return Err(JsonRPCError::TlsConfig(format!("no certificate found in CA bundle {}", path)))
+    }
+
+    Ok(roots)
+}
+
+// Accept only the server certificate matching the fingerprint
+// Handshake signatures are still verified against it
+#[derive(Debug)]
+struct PinnedCertificateVerifier {
+    fingerprint: [u8; 32],
+    algorithms: WebPkiSupportedAlgorithms
+}
+
+impl PinnedCertificateVerifier {
+    // Colons are allowed between the bytes, as displayed by most tools
+    fn new(fingerprint: &str) -> Result<Self, JsonRPCError> {
+        let bytes = hex::decode(fingerprint.replace(':', ""))
+            .map_err(|_| JsonRPCError::TlsConfig("pinned fingerprint is not in hex".to_owned()))?;
+        let fingerprint = bytes.try_into()
+            .map_err(|_| JsonRPCError::TlsConfig("pinned fingerprint must be a SHA-256 of 32 bytes".to_owned()))?;
+
+        Ok(Self {
+            fingerprint,
+            algorithms: default_provider().signature_verification_algorithms
+        })
+    }
+}
+
+impl ServerCertVerifier for PinnedCertificateVerifier {
+    fn verify_server_cert(&self, end_entity: &CertificateDer<'_>, _: &[CertificateDer<'_>], _: &ServerName<'_>, _: &[u8], _: UnixTime) -> Result<ServerCertVerified, TlsError> {
+        let fingerprint: [u8; 32] = Sha256::digest(end_entity.as_ref()).into();
+        if fingerprint != self.fingerprint {
+            return Err(TlsError::General(format!("server certificate fingerprint {} doesn't match the pinned one", hex::encode(fingerprint))))
+        }
+
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(&self, message: &[u8], cert: &CertificateDer<'_>, dss: &DigitallySignedStruct) -> Result<HandshakeSignatureValid, TlsError> {
+        verify_tls12_signature(message, cert, dss, &self.algorithms)
+    }
+
+    fn verify_tls13_signature(&self, message: &[u8], cert: &CertificateDer<'_>, dss: &DigitallySignedStruct) -> Result<HandshakeSignatureValid, TlsError> {
+        verify_tls13_signature(message, cert, dss, &self.algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.algorithms.supported_schemes()
+    }
+}
@@ -10,6 +10,7 @@ use std::{
     time::Duration
 };
 use anyhow::Error;
+use async_trait::async_trait;
 use futures_util::{
     StreamExt,
     stream::{SplitSink, SplitStream},
@@ -19,14 +20,15 @@ use serde::{de::DeserializeOwned, Serialize};
 use serde_json::{Value, json};
 use tokio::{
     net::TcpStream,
-    sync::{broadcast, oneshot, Mutex},
+    sync::{broadcast, oneshot, Mutex, Semaphore},
     task::JoinHandle,
     time::{sleep, timeout}
 };
 use tokio_tungstenite::{
     WebSocketStream,
     MaybeTlsStream,
-    connect_async,
+    Connector,
+    connect_async_tls_with_config,
     tungstenite::Message
 };
 use log::{debug, error, trace, warn};
@@ -35,16 +37,26 @@ use crate::{
     utils::{sanitize_daemon_address, spawn_task}
 };
 
-use super::{JSON_RPC_VERSION, JsonRPCError, JsonRPCResponse, JsonRPCResult};
+use super::{JSON_RPC_VERSION, JsonRPCError, JsonRPCResponse, JsonRPCResult, JsonRPCTransport, TlsConfig};
+
+// Message propagated to the receivers of an event
+#[derive(Debug, Clone)]
+pub enum EventMessage<T> {
+    Event(T),
+    // Events may have been missed, the state must be fetched again from the server
+    // Sent once the connection is back and the events are subscribed again,
+    // or when the receiver lagged behind
+    ResyncRequired
+}
 
 // EventReceiver allows to get the event value parsed directly
 pub struct EventReceiver<T: DeserializeOwned> {
-    inner: broadcast::Receiver<Value>,
+    inner: broadcast::Receiver<EventMessage<Value>>,
     _phantom: PhantomData<T>
 }
 
 impl<T: DeserializeOwned> EventReceiver<T> {
-    pub fn new(inner: broadcast::Receiver<Value>) -> Self {
+    pub fn new(inner: broadcast::Receiver<EventMessage<Value>>) -> Self {
         Self {
             inner,
             _phantom: PhantomData
@@ -53,23 +65,28 @@ impl<T: DeserializeOwned> EventReceiver<T> {
 
     // Get the next event
     // if we lagged behind, we will catch up
+    // Resync markers are skipped, use `recv` to be notified of them
     // If you don't want to miss any event, you should create a queue to store them
     // or an unbounded channel
     pub async fn next(&mut self) -> Result<T, Error> {
-        let mut res = self.inner.recv().await;
-        // If we lagged behind, we need to catch up
-        while let Err(e) = res {
-            match e {
-                broadcast::error::RecvError::Lagged(_) => {
-                    trace!("EventReceiver lagged behind, catching up...");
-                    res = self.inner.recv().await;
-                }
-                e => return Err(e.into())
-            };
+        loop {
+            if let EventMessage::Event(value) = self.recv().await? {
+                return Ok(value)
+            }
+        }
+    }
+
+    // Get the next event or a marker if some events were missed
+    pub async fn recv(&mut self) -> Result<EventMessage<T>, Error> {
+        match self.inner.recv().await {
+            Ok(EventMessage::Event(value)) => Ok(EventMessage::Event(serde_json::from_value(value)?)),
+            Ok(EventMessage::ResyncRequired) => Ok(EventMessage::ResyncRequired),
+            Err(broadcast::error::RecvError::Lagged(_)) => {
+                trace!("EventReceiver lagged behind, resync required");
+                Ok(EventMessage::ResyncRequired)
+            },
+            Err(e) => Err(e.into())
         }
- 
-        let value = res?;
-        Ok(serde_json::from_value(value)?)
     }
 }
 
@@ -82,15 +99,21 @@ pub type WebSocketJsonRPCClient<E> = Arc<WebSocketJsonRPCClientImpl<E>>;
 pub struct WebSocketJsonRPCClientImpl<E: Serialize + Hash + Eq + Send + Sync + Clone + 'static> {
     ws: Mutex<SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>>,
     count: AtomicUsize,
-    requests: Mutex<HashMap<usize, oneshot::Sender<JsonRPCResponse>>>,
+    // Pending requests waiting for their response
+    requests: Mutex<HashMap<usize, oneshot::Sender<JsonRPCResult<JsonRPCResponse>>>>,
+    // Limit the requests waiting for a response at the same time
+    in_flight: Semaphore,
     // This contains all id sent to register to a event on daemon
     // It stores the sender channel to propagate the event to apps 
-    handler_by_id: Mutex<HashMap<usize, broadcast::Sender<Value>>>,
+    handler_by_id: Mutex<HashMap<usize, broadcast::Sender<EventMessage<Value>>>>,
     // This contains all events registered by the app with its usize
     // This allows us to subscribe to same channel if its already subscribed
     events_to_id: Mutex<HashMap<E, usize>>,
     // websocket server address
     target: String,
+    // Custom TLS verification of the server
+    // None uses the default authorities
+    connector: Option<Connector>,
     // auto reconnect duration
     auto_reconnect: Mutex<Option<Duration>>,
     // is the client online
@@ -99,17 +122,21 @@ pub struct WebSocketJsonRPCClientImpl<E: Serialize + Hash + Eq + Send + Sync + C
     offline_channel: Mutex<Option<broadcast::Sender<()>>>,
     // This channel is called each time we connect
     online_channel: Mutex<Option<broadcast::Sender<()>>>,
+    // This channel is called once reconnected and the events subscribed again
+    reconnected_channel: Mutex<Option<broadcast::Sender<()>>>,
     // Background task that keep alive WS connection
     background_task: Mutex<Option<JoinHandle<()>>>,
     // Timeout for a request
-    timeout_after: Duration,
+    timeout_after: Mutex<Duration>,
 }
 
 pub const DEFAULT_AUTO_RECONNECT: Duration = Duration::from_secs(5);
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+pub const MAX_IN_FLIGHT_REQUESTS: usize = 64;
 
 impl<E: Serialize + Hash + Eq + Send + Sync + Clone + 'static> WebSocketJsonRPCClientImpl<E> {
-    async fn connect_to(target: &String) -> Result<WebSocketStream<MaybeTlsStream<TcpStream>>, JsonRPCError> {
-        let (ws, response) = connect_async(target).await?;
+    async fn connect_to(target: &String, connector: Option<Connector>) -> Result<WebSocketStream<MaybeTlsStream<TcpStream>>, JsonRPCError> {
+        let (ws, response) = connect_async_tls_with_config(target, None, false, connector).await?;
         let status = response.status();
         if status.is_server_error() || status.is_client_error() {
             return Err(JsonRPCError::ConnectionError(status.to_string()));
@@ -118,25 +145,44 @@ impl<E: Serialize + Hash + Eq + Send + Sync + Clone + 'static> WebSocketJsonRPCC
         Ok(ws)
     }
 
-    pub async fn new(mut target: String) -> Result<WebSocketJsonRPCClient<E>, JsonRPCError> {
+    pub async fn new(target: String) -> Result<WebSocketJsonRPCClient<E>, JsonRPCError> {
+        Self::new_with_tls(target, &TlsConfig::default()).await
+    }
+
+    // Connect using a custom CA bundle or a pinned certificate
+    // The connection is refused if the server certificate doesn't match
+    pub async fn new_with_tls(mut target: String, tls: &TlsConfig) -> Result<WebSocketJsonRPCClient<E>, JsonRPCError> {
         target = sanitize_daemon_address(target.as_str());
 
-        let ws = Self::connect_to(&target).await?;
+        let connector = if tls.is_default() {
+            None
+        } else {
+            // Never fallback silently to a non verified connection
+            if !target.starts_with("wss://") {
+                return Err(JsonRPCError::TlsConfig(format!("TLS verification requires a wss:// address, got {}", target)));
+            }
+            Some(tls.build_connector()?)
+        };
+
+        let ws = Self::connect_to(&target, connector.clone()).await?;
         
         let (write, read) = ws.split();
         let client = Arc::new(WebSocketJsonRPCClientImpl {
             ws: Mutex::new(write),
             count: AtomicUsize::new(0),
             requests: Mutex::new(HashMap::new()),
+            in_flight: Semaphore::new(MAX_IN_FLIGHT_REQUESTS),
             handler_by_id: Mutex::new(HashMap::new()),
             events_to_id: Mutex::new(HashMap::new()),
             target,
+            connector,
             auto_reconnect: Mutex::new(Some(DEFAULT_AUTO_RECONNECT)),
             online: AtomicBool::new(true),
             offline_channel: Mutex::new(None),
             online_channel: Mutex::new(None),
+            reconnected_channel: Mutex::new(None),
             background_task: Mutex::new(None),
-            timeout_after: Duration::from_secs(5),
+            timeout_after: Mutex::new(DEFAULT_REQUEST_TIMEOUT),
         });
 
         {
@@ -198,6 +244,12 @@ impl<E: Serialize + Hash + Eq + Send + Sync + Clone + 'static> WebSocketJsonRPCC
     pub async fn on_connection(&self) -> broadcast::Receiver<()> {
         self.register_to_connection_channel(&self.online_channel).await
     }
+
+    // Call this function to be notified by a channel when we are reconnected to the server
+    // Events subscribed are received again from this point, those sent while offline are lost
+    pub async fn on_reconnected(&self) -> broadcast::Receiver<()> {
+        self.register_to_connection_channel(&self.reconnected_channel).await
+    }
     
     // Should the client try to reconnect to the server if the connection is lost
     pub async fn should_auto_reconnect(&self) -> bool {
@@ -210,6 +262,17 @@ impl<E: Serialize + Hash + Eq + Send + Sync + Clone + 'static> WebSocketJsonRPCC
         *reconnect = duration;
     }
 
+    // Maximum time to wait for the response of a request
+    pub async fn get_timeout(&self) -> Duration {
+        *self.timeout_after.lock().await
+    }
+
+    // Set the maximum time to wait for the response of a request
+    pub async fn set_timeout(&self, duration: Duration) {
+        let mut timeout_after = self.timeout_after.lock().await;
+        *timeout_after = duration;
+    }
+
     // Is the client online
     pub fn is_online(&self) -> bool {
         self.online.load(Ordering::SeqCst)
@@ -273,7 +336,7 @@ impl<E: Serialize + Hash + Eq + Send + Sync + Clone + 'static> WebSocketJsonRPCC
         }
 
         {
-            let ws = Self::connect_to(&self.target).await?;
+            let ws = Self::connect_to(&self.target, self.connector.clone()).await?;
             let (write, read) = ws.split();
             {
                 let mut lock = self.ws.lock().await;
@@ -313,7 +376,7 @@ impl<E: Serialize + Hash + Eq + Send + Sync + Clone + 'static> WebSocketJsonRPCC
             sleep(*duration).await;
             debug!("Trying to reconnect to the server...");
 
-            let ws = match Self::connect_to(&self.target).await {
+            let ws = match Self::connect_to(&self.target, self.connector.clone()).await {
                 Ok(ws) => ws,
                 Err(e) => {
                     debug!("Error while reconnecting to the server: {:?}", e);
@@ -336,9 +399,13 @@ impl<E: Serialize + Hash + Eq + Send + Sync + Clone + 'static> WebSocketJsonRPCC
             {
                 let client = self.clone();
                 spawn_task("ws-subscribe-events", async move {
-                    if let Err(e) = client.resubscribe_events().await {
-                        error!("Error while resubscribing to events: {:?}", e);
-                    }
+                    match client.resubscribe_events().await {
+                        Ok(()) => client.notify_connection_channel(&client.reconnected_channel).await,
+                        Err(e) => error!("Error while resubscribing to events: {:?}", e)
+                    };
+
+                    // Events sent while offline are lost
+                    client.notify_resync_required().await;
                 });
             }
 
@@ -354,10 +421,25 @@ impl<E: Serialize + Hash + Eq + Send + Sync + Clone + 'static> WebSocketJsonRPCC
         None
     }
 
-    // Clear all pending requests to notifier the caller that the connection is lost
+    // Notify every event receiver that some events may have been missed
+    async fn notify_resync_required(&self) {
+        let handlers = self.handler_by_id.lock().await;
+        for sender in handlers.values() {
+            if sender.receiver_count() > 0 {
+                if let Err(e) = sender.send(EventMessage::ResyncRequired) {
+                    error!("Error sending resync marker to the event receivers: {:?}", e);
+                }
+            }
+        }
+    }
+
+    // Clear all pending requests and notify the callers that the connection is lost
     async fn clear_requests(&self) {
         let mut requests = self.requests.lock().await;
-        requests.clear();
+        for (_, sender) in requests.drain() {
+            // Caller may have stopped waiting
+            let _ = sender.send(Err(JsonRPCError::ConnectionLost));
+        }
     }
 
     // Clear all events
@@ -402,7 +484,7 @@ impl<E: Serialize + Hash + Eq + Send + Sync + Clone + 'static> WebSocketJsonRPCC
                         {
                             let mut requests = self.requests.lock().await;
                             if let Some(sender) = requests.remove(&id) {
-                                if let Err(e) = sender.send(response) {
+                                if let Err(e) = sender.send(Ok(response)) {
                                     error!("Error sending response to the request: {:?}", e);
                                 }
                                 continue;
@@ -415,7 +497,7 @@ impl<E: Serialize + Hash + Eq + Send + Sync + Clone + 'static> WebSocketJsonRPCC
                             if let Some(sender) = handlers.get_mut(&id) {
                                 // Check that we still have someone who listen it
                                 if sender.receiver_count() > 0 {
-                                    if let Err(e) = sender.send(response.result.unwrap_or_default()) {
+                                    if let Err(e) = sender.send(EventMessage::Event(response.result.unwrap_or_default())) {
                                         error!("Error sending event to the request: {:?}", e);
                                     }
                                 }
@@ -430,6 +512,9 @@ impl<E: Serialize + Hash + Eq + Send + Sync + Clone + 'static> WebSocketJsonRPCC
             }
         }
 
+        // No response will come anymore
+        self.clear_requests().await;
+
         Ok(())
     }
 
@@ -519,19 +604,19 @@ impl<E: Serialize + Hash + Eq + Send + Sync + Clone + 'static> WebSocketJsonRPCC
     }
 
     // Send a request to the server and wait for the response
+    // Waiting for a free slot when too many requests are in flight counts in the timeout
     async fn send<P: Serialize, R: DeserializeOwned>(&self, method: &str, id: Option<usize>, params: &P) -> JsonRPCResult<R> {
         let id = id.unwrap_or_else(|| self.next_id());
-        let (sender, receiver) = oneshot::channel();
-        {
+        let timeout_after = self.get_timeout().await;
+        let res = timeout(timeout_after, self.send_and_wait(method, id, params)).await;
+
+        // Don't keep the request if we don't wait for it anymore
+        if !matches!(res, Ok(Ok(_))) {
             let mut requests = self.requests.lock().await;
-            requests.insert(id, sender);
+            requests.remove(&id);
         }
 
-        self.send_message_internal(Some(id), method, params).await?;
-
-        let response = timeout(self.timeout_after, receiver).await
-            .or(Err(JsonRPCError::TimedOut))?
-            .or(Err(JsonRPCError::NoResponse))?;
+        let response = res.or(Err(JsonRPCError::TimedOut))??;
 
         if let Some(error) = response.error {
             return Err(JsonRPCError::ServerError {
@@ -546,6 +631,21 @@ impl<E: Serialize + Hash + Eq + Send + Sync + Clone + 'static> WebSocketJsonRPCC
         Ok(serde_json::from_value(result)?)
     }
 
+    async fn send_and_wait<P: Serialize>(&self, method: &str, id: usize, params: &P) -> JsonRPCResult<JsonRPCResponse> {
+        let _permit = self.in_flight.acquire().await
+            .map_err(|e| JsonRPCError::Any(e.into()))?;
+
+        let (sender, receiver) = oneshot::channel();
+        {
+            let mut requests = self.requests.lock().await;
+            requests.insert(id, sender);
+        }
+
+        self.send_message_internal(Some(id), method, params).await?;
+
+        receiver.await.or(Err(JsonRPCError::NoResponse))?
+    }
+
     // Send a request to the server without waiting for the response
     pub async fn notify_with<P: Serialize>(&self, method: &str, params: &P) -> JsonRPCResult<()> {
         self.send_message_internal(None, method, params).await?;
@@ -556,4 +656,18 @@ impl<E: Serialize + Hash + Eq + Send + Sync + Clone + 'static> WebSocketJsonRPCC
     pub async fn notify<P: Serialize>(&self, method: &str) -> JsonRPCResult<()> {
         self.notify_with(method, &Value::Null).await
     }
-}
\ No newline at end of file
+}
+#[async_trait]
+impl<E: Serialize + Hash + Eq + Send + Sync + Clone + 'static> JsonRPCTransport for WebSocketJsonRPCClientImpl<E> {
+    async fn call<R: DeserializeOwned + Send>(&self, method: &str) -> JsonRPCResult<R> {
+        WebSocketJsonRPCClientImpl::call(self, method).await
+    }
+
+    async fn call_with<P: Serialize + Sync, R: DeserializeOwned + Send>(&self, method: &str, params: &P) -> JsonRPCResult<R> {
+        WebSocketJsonRPCClientImpl::call_with(self, method, params).await
+    }
+
+    async fn notify_with<P: Serialize + Sync>(&self, method: &str, params: &P) -> JsonRPCResult<()> {
+        WebSocketJsonRPCClientImpl::notify_with(self, method, params).await
+    }
+}
@@ -1,3 +1,6 @@
+// Derived Serializer implementations refer to this crate by its name
+extern crate self as xelis_common;
+
 pub mod crypto;
 pub mod serializer;
 pub mod transaction;
@@ -10,7 +13,9 @@ pub mod config;
 pub mod immutable;
 pub mod difficulty;
 pub mod network;
+pub mod capabilities;
 pub mod asset;
+pub mod contract;
 pub mod context;
 pub mod queue;
 pub mod varuint;
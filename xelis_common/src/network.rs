@@ -1,9 +1,9 @@
 use std::{fmt::{Display, Formatter, self}, str::FromStr};
 use serde::{Serialize, Deserialize};
 
-use crate::serializer::{Serializer, Reader, ReaderError, Writer};
+use crate::serializer::Serializer;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Serializer)]
 #[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
 pub enum Network {
     Mainnet,
@@ -43,27 +43,3 @@ impl FromStr for Network {
         })
     }
 }
-
-impl Serializer for Network {
-    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
-        Ok(match reader.read_u8()? {
-            0 => Self::Mainnet,
-            1 => Self::Testnet,
-            2 => Self::Dev,
-            _ => return Err(ReaderError::InvalidValue)
-        })
-    }
-
-    fn write(&self, writer: &mut Writer) {
-        let id = match &self {
-          Self::Mainnet => 0,
-          Self::Testnet => 1,
-          Self::Dev => 2 
-        };
-        writer.write_u8(id);
-    }
-
-    fn size(&self) -> usize {
-        1
-    }
-}
\ No newline at end of file
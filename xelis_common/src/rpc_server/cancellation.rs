@@ -0,0 +1,90 @@
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc
+    },
+    time::{Duration, Instant}
+};
+use super::InternalRpcError;
+
+tokio::task_local! {
+    // Token of the RPC request being executed in the task
+    static CANCELLATION_TOKEN: CancellationToken;
+}
+
+// Cooperative cancellation of a RPC request
+// A request is cancelled once its deadline is reached
+// Handlers doing long scans must check it regularly to stop as soon as possible
+#[derive(Clone)]
+pub struct CancellationToken {
+    deadline: Option<Instant>,
+    cancelled: Arc<AtomicBool>
+}
+
+impl CancellationToken {
+    // Create a token cancelled after the timeout, never cancelled if no timeout is set
+    pub fn new(timeout: Option<Duration>) -> Self {
+        Self {
+            deadline: timeout.map(|timeout| Instant::now() + timeout),
+            cancelled: Arc::new(AtomicBool::new(false))
+        }
+    }
+
+    // Token of the RPC request executed in the current task
+    // Returns a token never cancelled if called outside of a RPC request
+    pub fn current() -> Self {
+        CANCELLATION_TOKEN.try_with(Clone::clone)
+            .unwrap_or_else(|_| Self::new(None))
+    }
+
+    // Execute the future with this token as the current one
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        CANCELLATION_TOKEN.scope(self, future).await
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Release);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Acquire)
+            || self.deadline.map(|deadline| Instant::now() >= deadline).unwrap_or(false)
+    }
+
+    // Returns an error if the request has been cancelled
+    pub fn check(&self) -> Result<(), InternalRpcError> {
+        if self.is_cancelled() {
+            return Err(InternalRpcError::DeadlineExceeded)
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_current_token() {
+        // outside of a request, never cancelled
+        assert!(CancellationToken::current().check().is_ok());
+
+        let token = CancellationToken::new(Some(Duration::from_secs(60)));
+        token.clone().scope(async {
+            assert!(CancellationToken::current().check().is_ok());
+        }).await;
+
+        token.cancel();
+        token.scope(async {
+            assert!(matches!(CancellationToken::current().check(), Err(InternalRpcError::DeadlineExceeded)));
+        }).await;
+    }
+
+    #[test]
+    fn test_deadline() {
+        let token = CancellationToken::new(Some(Duration::ZERO));
+        assert!(token.is_cancelled());
+    }
+}
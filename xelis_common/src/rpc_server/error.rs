@@ -45,6 +45,8 @@ pub enum InternalRpcError {
     EventAlreadySubscribed,
     #[error(transparent)]
     SerializeResponse(SerdeError),
+    #[error("Request deadline exceeded")]
+    DeadlineExceeded,
     // Custom errors must have a code between -3 and -31999
     #[error("{}", _1)]
     CustomAny(i16, AnyError),
@@ -70,6 +72,7 @@ impl InternalRpcError {
             Self::ClientNotFound => -32002,
             InternalRpcError::SerializeResponse(_) => -32003,
             InternalRpcError::AnyError(_) => -32004,
+            Self::DeadlineExceeded => -32005,
             // Events invalid requests
             Self::EventNotSubscribed => -1,
             Self::EventAlreadySubscribed => -2,
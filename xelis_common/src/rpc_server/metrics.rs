@@ -0,0 +1,106 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::Mutex,
+    time::Duration
+};
+
+// Upper bounds in seconds of the latency histogram buckets
+const LATENCY_BUCKETS: [f64; 10] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0];
+
+#[derive(Default)]
+struct MethodMetrics {
+    // Requests count for each bucket, the last one is +Inf
+    buckets: [u64; LATENCY_BUCKETS.len() + 1],
+    // Total time spent in seconds
+    sum: f64,
+    count: u64,
+    errors: u64
+}
+
+// Latencies and errors of the RPC methods executed
+// Only the registered methods are recorded
+#[derive(Default)]
+pub struct RpcMetrics {
+    methods: Mutex<BTreeMap<String, MethodMetrics>>
+}
+
+impl RpcMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Record the execution of a method
+    pub fn record(&self, method: &str, duration: Duration, success: bool) {
+        let Ok(mut methods) = self.methods.lock() else {
+            return
+        };
+
+        if !methods.contains_key(method) {
+            methods.insert(method.to_owned(), MethodMetrics::default());
+        }
+
+        if let Some(metrics) = methods.get_mut(method) {
+            let seconds = duration.as_secs_f64();
+            let index = LATENCY_BUCKETS.iter()
+                .position(|bound| seconds <= *bound)
+                .unwrap_or(LATENCY_BUCKETS.len());
+            metrics.buckets[index] += 1;
+            metrics.sum += seconds;
+            metrics.count += 1;
+            if !success {
+                metrics.errors += 1;
+            }
+        }
+    }
+
+    // Write the metrics in the Prometheus text format
+    // Each metric name starts with the prefix
+    pub fn write_prometheus(&self, out: &mut String, prefix: &str) {
+        let Ok(methods) = self.methods.lock() else {
+            return
+        };
+
+        let _ = writeln!(out, "# HELP {}_rpc_request_duration_seconds Latency of the RPC requests", prefix);
+        let _ = writeln!(out, "# TYPE {}_rpc_request_duration_seconds histogram", prefix);
+        for (method, metrics) in methods.iter() {
+            // Buckets are cumulative
+            let mut count = 0;
+            for (bound, value) in LATENCY_BUCKETS.iter().zip(metrics.buckets.iter()) {
+                count += value;
+                let _ = writeln!(out, "{}_rpc_request_duration_seconds_bucket{{method=\"{}\",le=\"{}\"}} {}", prefix, method, bound, count);
+            }
+            let _ = writeln!(out, "{}_rpc_request_duration_seconds_bucket{{method=\"{}\",le=\"+Inf\"}} {}", prefix, method, metrics.count);
+            let _ = writeln!(out, "{}_rpc_request_duration_seconds_sum{{method=\"{}\"}} {}", prefix, method, metrics.sum);
+            let _ = writeln!(out, "{}_rpc_request_duration_seconds_count{{method=\"{}\"}} {}", prefix, method, metrics.count);
+        }
+
+        let _ = writeln!(out, "# HELP {}_rpc_request_errors_total RPC requests which returned an error", prefix);
+        let _ = writeln!(out, "# TYPE {}_rpc_request_errors_total counter", prefix);
+        for (method, metrics) in methods.iter() {
+            let _ = writeln!(out, "{}_rpc_request_errors_total{{method=\"{}\"}} {}", prefix, method, metrics.errors);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buckets_are_cumulative() {
+        let metrics = RpcMetrics::new();
+        metrics.record("get_balance", Duration::from_millis(1), true);
+        metrics.record("get_balance", Duration::from_millis(200), false);
+        metrics.record("get_balance", Duration::from_secs(10), true);
+
+        let mut out = String::new();
+        metrics.write_prometheus(&mut out, "xelis_wallet");
+        assert!(out.contains("xelis_wallet_rpc_request_duration_seconds_bucket{method=\"get_balance\",le=\"0.005\"} 1\n"));
+        assert!(out.contains("xelis_wallet_rpc_request_duration_seconds_bucket{method=\"get_balance\",le=\"0.25\"} 2\n"));
+        assert!(out.contains("xelis_wallet_rpc_request_duration_seconds_bucket{method=\"get_balance\",le=\"5\"} 2\n"));
+        assert!(out.contains("xelis_wallet_rpc_request_duration_seconds_bucket{method=\"get_balance\",le=\"+Inf\"} 3\n"));
+        assert!(out.contains("xelis_wallet_rpc_request_duration_seconds_count{method=\"get_balance\"} 3\n"));
+        assert!(out.contains("xelis_wallet_rpc_request_errors_total{method=\"get_balance\"} 1\n"));
+    }
+}
@@ -1,12 +1,16 @@
 pub mod websocket;
 mod error;
 mod rpc_handler;
+mod cancellation;
+mod metrics;
 
 use std::borrow::Cow;
 
 pub use error::{RpcResponseError, InternalRpcError};
 pub use rpc_handler::{RPCHandler, Handler};
 pub use rpc_handler::parse_params;
+pub use cancellation::CancellationToken;
+pub use metrics::RpcMetrics;
 
 use actix_web::{HttpResponse, web::{self, Data, Payload}, Responder, HttpRequest};
 use serde::{Deserialize, Serialize};
@@ -1,16 +1,20 @@
-use std::{collections::HashMap, pin::Pin, future::Future};
+use std::{collections::HashMap, pin::Pin, future::Future, time::{Duration, Instant}};
 use serde::de::DeserializeOwned;
 use serde_json::{json, Map, Value};
 use crate::context::Context;
 
-use super::{InternalRpcError, RpcResponseError, RpcRequest, JSON_RPC_VERSION};
-use log::{error, trace};
+use super::{CancellationToken, InternalRpcError, RpcMetrics, RpcResponseError, RpcRequest, JSON_RPC_VERSION};
+use log::{debug, error, trace};
 
 pub type Handler = fn(&'_ Context, Value) -> Pin<Box<dyn Future<Output = Result<Value, InternalRpcError>> + Send + '_>>;
 
 pub struct RPCHandler<T: Send + Clone + 'static> {
     methods: HashMap<String, Handler>, // all RPC methods registered
-    data: T
+    data: T,
+    // deadline of each request, no deadline if not set
+    request_timeout: Option<Duration>,
+    // latencies of the methods, only recorded if enabled
+    metrics: Option<RpcMetrics>
 }
 
 impl<T> RPCHandler<T>
@@ -20,7 +24,9 @@ where
     pub fn new(data: T) -> Self {
         Self {
             methods: HashMap::new(),
-            data
+            data,
+            request_timeout: None,
+            metrics: None
         }
     }
 
@@ -78,13 +84,9 @@ where
     }
 
     pub async fn execute_method<'a>(&'a self, context: &'a Context, mut request: RpcRequest) -> Result<Option<Value>, RpcResponseError> {
-        let handler = match self.methods.get(&request.method) {
-            Some(handler) => handler,
-            None => return Err(RpcResponseError::new(request.id, InternalRpcError::MethodNotFound(request.method)))
-        };
-        trace!("executing '{}' RPC method", request.method);
         let params = request.params.take().unwrap_or(Value::Null);
-        let result = handler(context, params).await.map_err(|err| RpcResponseError::new(request.id.clone(), err))?;
+        let result = self.call_method_with_context(context, &request.method, params).await
+            .map_err(|err| RpcResponseError::new(request.id.clone(), err))?;
         Ok(if request.id.is_some() {
             Some(json!({
                 "jsonrpc": JSON_RPC_VERSION,
@@ -96,6 +98,43 @@ where
         })
     }
 
+    // Execute a registered method with its params, without the JSON-RPC envelope
+    pub async fn call_method(&self, method: &str, params: Value) -> Result<Value, InternalRpcError> {
+        let mut context = Context::new();
+
+        // Add the data
+        context.store(self.get_data().clone());
+
+        self.call_method_with_context(&context, method, params).await
+    }
+
+    pub async fn call_method_with_context(&self, context: &Context, method: &str, params: Value) -> Result<Value, InternalRpcError> {
+        let handler = self.methods.get(method)
+            .ok_or_else(|| InternalRpcError::MethodNotFound(method.to_owned()))?;
+        trace!("executing '{}' RPC method", method);
+        let token = CancellationToken::new(self.request_timeout);
+        let future = token.clone().scope(handler(context, params));
+        let start = Instant::now();
+        let result = match self.request_timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, future).await {
+                Ok(result) => result,
+                Err(_) => {
+                    debug!("RPC method '{}' exceeded its deadline", method);
+                    // stop any work still referencing the token
+                    token.cancel();
+                    Err(InternalRpcError::DeadlineExceeded)
+                }
+            },
+            None => future.await
+        };
+
+        if let Some(metrics) = self.metrics.as_ref() {
+            metrics.record(method, start.elapsed(), result.is_ok());
+        }
+
+        result
+    }
+
     // register a new RPC method handler
     pub fn register_method(&mut self, name: &str, handler: Handler) {
         if self.methods.insert(name.into(), handler).is_some() {
@@ -106,6 +145,24 @@ where
     pub fn get_data(&self) -> &T {
         &self.data
     }
+
+    // Set the deadline applied to each request
+    pub fn set_request_timeout(&mut self, timeout: Option<Duration>) {
+        self.request_timeout = timeout;
+    }
+
+    pub fn get_request_timeout(&self) -> Option<Duration> {
+        self.request_timeout
+    }
+
+    // Record the latencies of the methods executed
+    pub fn enable_metrics(&mut self) {
+        self.metrics = Some(RpcMetrics::new());
+    }
+
+    pub fn get_metrics(&self) -> Option<&RpcMetrics> {
+        self.metrics.as_ref()
+    }
 }
 
 pub fn parse_params<P: DeserializeOwned>(mut value: Value) -> Result<P, InternalRpcError> {
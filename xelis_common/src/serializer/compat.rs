@@ -0,0 +1,221 @@
+// Helpers for the tests of the serialized formats
+// Golden byte vectors are the bytes written by a released version,
+// they must never change without a new format version
+use super::{Reader, Serializer};
+
+// The value must be serialized exactly as the golden bytes (hex encoded)
+// and reading them must consume all of them and give back the same bytes
+pub fn assert_golden<T: Serializer>(value: &T, golden: &str) {
+    let bytes = value.to_bytes();
+    assert_eq!(hex::encode(&bytes), golden, "serialized bytes differ from the golden ones");
+    assert_eq!(value.size(), bytes.len(), "size differs from the serialized bytes");
+
+    read_golden::<T>(golden);
+}
+
+// Read the golden bytes (hex encoded) written by a released version
+// They must be fully consumed and written back the same
+// Returns the value read so the caller can check its fields
+pub fn read_golden<T: Serializer>(golden: &str) -> T {
+    let bytes = hex::decode(golden).expect("golden bytes are not valid hex");
+
+    let mut reader = Reader::new(&bytes);
+    let read = T::read(&mut reader).expect("golden bytes can't be read");
+    assert_eq!(reader.size(), 0, "golden bytes are not fully read");
+    assert_eq!(read.to_bytes(), bytes, "golden bytes are not written back the same");
+    assert_eq!(read.size(), bytes.len(), "size differs from the golden bytes");
+
+    read
+}
+
+// Data written by a newer version, with fields appended at the end, must still be read
+// Returns the value read, fields unknown are ignored
+pub fn read_from_newer<T: Serializer>(value: &T, appended: &[u8]) -> T {
+    let mut bytes = value.to_bytes();
+    bytes.extend_from_slice(appended);
+
+    T::from_bytes(&bytes).expect("data from a newer version can't be read")
+}
+
+// Data written by an older version, without the last `removed` bytes of its fields, must still be read
+// Returns the value read so the caller can check the default values of the missing fields
+pub fn read_from_older<T: Serializer>(value: &T, removed: usize) -> T {
+    let bytes = value.to_bytes();
+    assert!(removed <= bytes.len(), "can't remove more bytes than written");
+
+    T::from_bytes(&bytes[..bytes.len() - removed]).expect("data from an older version can't be read")
+}
+
+#[cfg(test)]
+mod tests {
+    use curve25519_dalek::ristretto::CompressedRistretto;
+    use indexmap::IndexSet;
+    use crate::{
+        block::BlockHeader,
+        crypto::{elgamal::CompressedPublicKey, Hash},
+        serializer::ReaderError,
+        transaction::{Transaction, TransactionType}
+    };
+    use super::*;
+
+    // Hex of `count` zero bytes, used for the points, scalars and hashes
+    fn zeros(count: usize) -> String {
+        "00".repeat(count)
+    }
+
+    fn header() -> BlockHeader {
+        let mut tips = IndexSet::new();
+        tips.insert(Hash::zero());
+
+        let miner = CompressedPublicKey::new(CompressedRistretto([0u8; 32]));
+        BlockHeader::new(0, 1, 2, tips, [0u8; 32], miner, IndexSet::new())
+    }
+
+    // Block header version 0 as written by the first release
+    fn block_header_v0_golden() -> String {
+        format!(
+            "{}{}{}{}{}{}{}{}{}",
+            // version
+            "00",
+            // height
+            "0000000000000001",
+            // timestamp
+            "0000000000000002",
+            // nonce
+            "0000000000000000",
+            // extra nonce
+            zeros(32),
+            // one tip
+            "01",
+            zeros(32),
+            // no txs
+            "0000",
+            // miner
+            zeros(32)
+        )
+    }
+
+    // Source commitment, range proof, reference and signature of a transaction version 0
+    // as written by the first release, with all points and scalars set to zero
+    fn tx_v0_proofs_golden() -> String {
+        format!(
+            "{}{}{}{}{}{}{}{}{}",
+            // fee
+            "00000000000003e8",
+            // nonce
+            "0000000000000005",
+            // one source commitment: commitment, equality proof and asset
+            "01",
+            zeros(32),
+            zeros(192),
+            zeros(32),
+            // range proof: 9 elements of 32 bytes
+            format!("0120{}", zeros(288)),
+            // reference: hash and topoheight
+            format!("{}0000000000000007", zeros(32)),
+            // signature
+            zeros(64)
+        )
+    }
+
+    #[test]
+    fn test_block_header_golden() {
+        let golden = block_header_v0_golden();
+        assert_golden(&header(), &golden);
+
+        let header: BlockHeader = read_golden(&golden);
+        assert_eq!(header.get_version(), 0);
+        assert_eq!(header.get_height(), 1);
+        assert_eq!(header.get_timestamp(), 2);
+        assert_eq!(header.get_tips().len(), 1);
+        assert_eq!(header.get_txs_count(), 0);
+    }
+
+    #[test]
+    fn test_block_header_unsupported_version() {
+        let mut bytes = hex::decode(block_header_v0_golden()).unwrap();
+        bytes[0] = u8::MAX;
+        assert!(matches!(BlockHeader::from_bytes(&bytes), Err(ReaderError::UnsupportedVersion(u8::MAX))));
+    }
+
+    #[test]
+    fn test_burn_tx_v0_golden() {
+        let golden = format!(
+            "{}{}{}{}{}{}",
+            // version
+            "00",
+            // source
+            zeros(32),
+            // burn id, asset and amount
+            "00",
+            zeros(32),
+            "0000000000000064",
+            tx_v0_proofs_golden()
+        );
+
+        let tx: Transaction = read_golden(&golden);
+        assert_eq!(tx.get_version(), 0);
+        assert_eq!(tx.get_fee(), 1000);
+        assert_eq!(tx.get_nonce(), 5);
+        assert_eq!(tx.get_reference().topoheight, 7);
+        // no multisig field before version 1
+        assert!(tx.get_multisig().is_none());
+        match tx.get_data() {
+            TransactionType::Burn(payload) => {
+                assert_eq!(payload.asset, Hash::zero());
+                assert_eq!(payload.amount, 100);
+            },
+            _ => panic!("expected a burn transaction")
+        }
+    }
+
+    #[test]
+    fn test_transfer_tx_v0_golden() {
+        let golden = format!(
+            "{}{}{}{}{}{}{}",
+            // version
+            "00",
+            // source
+            zeros(32),
+            // transfers id and one transfer
+            "0101",
+            // asset and destination
+            zeros(64),
+            // no extra data
+            "00",
+            // commitment, sender and receiver handles, ciphertext validity proof
+            zeros(32 * 3 + 128),
+            tx_v0_proofs_golden()
+        );
+
+        let tx: Transaction = read_golden(&golden);
+        assert_eq!(tx.get_version(), 0);
+        match tx.get_data() {
+            TransactionType::Transfers(transfers) => {
+                assert_eq!(transfers.len(), 1);
+                assert!(transfers[0].get_extra_data().is_none());
+            },
+            _ => panic!("expected a transfers transaction")
+        }
+    }
+
+    #[test]
+    fn test_tx_unsupported_version() {
+        let golden = format!("00{}00{}0000000000000064{}", zeros(32), zeros(32), tx_v0_proofs_golden());
+        let mut bytes = hex::decode(golden).unwrap();
+        bytes[0] = u8::MAX;
+        assert!(matches!(Transaction::from_bytes(&bytes), Err(ReaderError::UnsupportedVersion(u8::MAX))));
+    }
+
+    #[test]
+    fn test_trailing_field() {
+        let value = (1u16, 2u16);
+        let read = read_from_newer(&value, &[0xFF]);
+        assert_eq!(read, value);
+
+        let bytes = value.to_bytes();
+        let mut reader = Reader::new(&bytes[..2]);
+        assert_eq!(reader.read_u16().unwrap(), 1);
+        assert!(reader.read_trailing::<u16>().unwrap().is_none());
+    }
+}
@@ -1,9 +1,11 @@
 mod defaults;
 mod reader;
 mod writer;
+pub mod compat;
 
 pub use reader::{Reader, ReaderError};
 pub use writer::Writer;
+pub use xelis_derive::Serializer;
 use std::marker::Sized;
 
 pub trait Serializer {
@@ -46,4 +48,67 @@ pub trait Serializer {
         let mut reader = Reader::new(bytes);
         Self::read(&mut reader)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serializer)]
+    struct Item {
+        id: u64,
+        name: String,
+        #[serializer(optional_string)]
+        note: Option<String>,
+        values: Vec<u8>,
+        #[serializer(skip)]
+        cached: bool
+    }
+
+    #[derive(Debug, PartialEq, Serializer)]
+    enum Kind {
+        Empty,
+        Value(u16),
+        #[serializer(id = 5)]
+        Named { value: Option<u32> }
+    }
+
+    #[test]
+    fn test_derive_struct() {
+        let item = Item {
+            id: 1,
+            name: "xelis".to_owned(),
+            note: None,
+            values: vec![1, 2],
+            cached: true
+        };
+
+        let mut writer = Writer::new();
+        writer.write_u64(&1);
+        writer.write_string(&"xelis".to_owned());
+        writer.write_optional_string(&None);
+        writer.write_u16(2);
+        writer.write_u8(1);
+        writer.write_u8(2);
+        let bytes = item.to_bytes();
+        assert_eq!(bytes, writer.bytes());
+        assert_eq!(bytes.len(), item.size());
+
+        let read = Item::from_bytes(&bytes).unwrap();
+        assert_eq!(read, Item { cached: false, ..item });
+    }
+
+    #[test]
+    fn test_derive_enum() {
+        assert_eq!(Kind::Empty.to_bytes(), vec![0]);
+        assert_eq!(Kind::Value(3).to_bytes(), vec![1, 0, 3]);
+
+        let named = Kind::Named { value: Some(7) };
+        let bytes = named.to_bytes();
+        assert_eq!(bytes, vec![5, 1, 0, 0, 0, 7]);
+        assert_eq!(bytes.len(), named.size());
+        assert_eq!(Kind::from_bytes(&bytes).unwrap(), named);
+
+        assert!(Kind::from_bytes(&[2]).is_err());
+    }
+}
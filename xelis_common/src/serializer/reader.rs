@@ -15,6 +15,8 @@ pub enum ReaderError {
     InvalidHex,
     #[error("Error on try into")]
     ErrorTryInto,
+    #[error("Unsupported format version {0}")]
+    UnsupportedVersion(u8),
     #[error(transparent)]
     TryFromSliceError(#[from] TryFromSliceError),
     #[error(transparent)]
@@ -142,6 +144,17 @@ impl<'a> Reader<'a> {
         Ok(Some(byte))
     }
 
+    // Read a field appended in a newer version of the format
+    // None if the data was written by an older version, without it
+    // It only works for the last fields of data read until the end
+    pub fn read_trailing<T: Serializer>(&mut self) -> Result<Option<T>, ReaderError> {
+        if self.size() == 0 {
+            return Ok(None)
+        }
+
+        T::read(self).map(Some)
+    }
+
     pub fn total_size(&self) -> usize {
         self.bytes.len()
     }
@@ -4,15 +4,17 @@
 
 use bulletproofs::RangeProof;
 use curve25519_dalek::Scalar;
+use indexmap::IndexSet;
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     iter,
 };
 use crate::{
     account::CiphertextCache,
     api::DataElement,
-    config::XELIS_ASSET,
+    config::{ASSET_REGISTRATION_FEE, XELIS_ASSET},
+    contract::{MAX_CONTRACT_PARAMETERS, MAX_GAS_PER_INVOKE},
     crypto::{
         elgamal::{
             Ciphertext,
@@ -33,9 +35,11 @@ use crate::{
             PC_GENS,
             BULLET_PROOF_SIZE,
         },
+        hash,
         Address,
         Hash,
         ProtocolTranscript,
+        Signature,
         HASH_SIZE,
         SIGNATURE_SIZE
     },
@@ -44,16 +48,30 @@ use crate::{
 };
 use thiserror::Error;
 use super::{
-    extra_data::{ExtraData, PlaintextData},
+    extra_data::{AuthenticatedExtraData, ExtraData, PlaintextData, UnknownExtraDataFormat},
+    lock::TransferLock,
+    multisig::{MultiSig, SignatureId, MAX_MULTISIG_PARTICIPANTS},
+    AssetCreationPayload,
+    AssetOwnershipPayload,
     BurnPayload,
+    MintAssetPayload,
+    MultiSigPayload,
     Reference,
     Role,
     SourceCommitment,
     Transaction,
     TransactionType,
     TransferPayload,
+    UnlockPayload,
+    DeployContractPayload,
+    InvokeContractPayload,
     EXTRA_DATA_LIMIT_SIZE,
-    MAX_TRANSFER_COUNT
+    MAX_ASSET_DECIMALS,
+    MAX_ASSET_NAME_LENGTH,
+    MAX_TRANSFER_COUNT,
+    TX_VERSION,
+    TX_VERSION_CONTRACTS,
+    TX_VERSION_MULTISIG
 };
 
 #[derive(Error, Debug, Clone)]
@@ -74,10 +92,36 @@ pub enum GenerationError<T> {
     InvalidNetwork,
     #[error("Extra data was provied with an integrated address")]
     ExtraDataAndIntegratedAddress,
+    #[error("Invalid asset creation payload")]
+    InvalidAssetCreation,
+    #[error("Mint amount can't be zero")]
+    InvalidMintAmount,
+    #[error("Invalid multisig participants or threshold")]
+    InvalidMultiSig,
+    #[error("Multisig requires transaction version {}", TX_VERSION_MULTISIG)]
+    MultiSigVersion,
+    #[error("Transaction version {} is not supported", _0)]
+    InvalidVersion(u8),
+    #[error("Transfer lock must be above the current topoheight")]
+    InvalidLock,
+    #[error("Contracts require transaction version {}", TX_VERSION_CONTRACTS)]
+    ContractVersion,
+    #[error("Transaction type requires transaction version {}", _0)]
+    TypeVersion(u8),
+    #[error("Invalid contract invocation parameters or gas")]
+    InvalidContractInvoke,
     #[error("Proof generation error: {0}")]
     Proof(#[from] ProofGenerationError),
 }
 
+#[derive(Error, Debug, Clone)]
+pub enum MultiSigError {
+    #[error("Multisig requires transaction version {}", TX_VERSION_MULTISIG)]
+    Version,
+    #[error("Participant {} already signed", _0)]
+    AlreadySigned(u8),
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(rename_all = "snake_case")]
 pub enum FeeBuilder {
@@ -135,7 +179,31 @@ pub trait AccountState: FeeHelper {
 pub enum TransactionTypeBuilder {
     Transfers(Vec<TransferBuilder>),
     // We can use the same as final transaction
-    Burn(BurnPayload)
+    Burn(BurnPayload),
+    // Same as final transaction
+    CreateAsset(AssetCreationPayload),
+    // Same as final transaction
+    MintAsset(MintAssetPayload),
+    TransferAssetOwnership(AssetOwnershipBuilder),
+    MultiSig(MultiSigBuilder),
+    // Same as final transaction
+    Unlock(UnlockPayload),
+    // Same as final transaction
+    DeployContract(DeployContractPayload),
+    // Same as final transaction
+    InvokeContract(InvokeContractPayload)
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AssetOwnershipBuilder {
+    pub asset: Hash,
+    pub new_owner: Address
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MultiSigBuilder {
+    pub threshold: u8,
+    pub participants: Vec<Address>
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -145,12 +213,23 @@ pub struct TransferBuilder {
     pub destination: Address,
     // we can put whatever we want up to EXTRA_DATA_LIMIT_SIZE bytes
     pub extra_data: Option<DataElement>,
+    // funds are kept on chain until unlocked
+    #[serde(default)]
+    pub lock: Option<TransferLock>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct TransactionBuilder {
     version: u8,
     source: CompressedPublicKey,
+    // Signatures required by the multisig of the source account
+    // Used to estimate the final size
+    #[serde(default)]
+    multisig_threshold: Option<u8>,
+    // Encrypt the extra data of the transfers using the authenticated (V3) format
+    // Disabled by default: wallets not upgraded can only decrypt the V2 format
+    #[serde(default)]
+    authenticated_extra_data: bool,
     data: TransactionTypeBuilder,
     fee_builder: FeeBuilder
 }
@@ -193,12 +272,47 @@ impl TransactionTypeBuilder {
             }
             TransactionTypeBuilder::Burn(payload) => {
                 consumed.insert(payload.asset.clone());
-            }
+            },
+            // Only the native asset is used
+            TransactionTypeBuilder::CreateAsset(_)
+            | TransactionTypeBuilder::MintAsset(_)
+            | TransactionTypeBuilder::TransferAssetOwnership(_)
+            | TransactionTypeBuilder::MultiSig(_)
+            | TransactionTypeBuilder::Unlock(_)
+            | TransactionTypeBuilder::DeployContract(_)
+            | TransactionTypeBuilder::InvokeContract(_) => {}
         }
 
         consumed
     }
 
+    // Get the amount spent for each asset, fees excluded
+    pub fn get_spent_amounts(&self) -> HashMap<Hash, u64> {
+        let mut spent = HashMap::new();
+        let mut add = |asset: &Hash, amount: u64| {
+            let total: &mut u64 = spent.entry(asset.clone()).or_default();
+            *total = total.saturating_add(amount);
+        };
+
+        match &self {
+            TransactionTypeBuilder::Transfers(transfers) => {
+                for transfer in transfers {
+                    add(&transfer.asset, transfer.amount);
+                }
+            },
+            TransactionTypeBuilder::Burn(payload) => add(&payload.asset, payload.amount),
+            TransactionTypeBuilder::CreateAsset(_) => add(&XELIS_ASSET, ASSET_REGISTRATION_FEE),
+            TransactionTypeBuilder::InvokeContract(payload) => add(&XELIS_ASSET, payload.max_gas),
+            TransactionTypeBuilder::MintAsset(_)
+            | TransactionTypeBuilder::TransferAssetOwnership(_)
+            | TransactionTypeBuilder::MultiSig(_)
+            | TransactionTypeBuilder::Unlock(_)
+            | TransactionTypeBuilder::DeployContract(_) => {}
+        }
+
+        spent
+    }
+
     // Get the destination keys used in the transaction
     pub fn used_keys(&self) -> Vec<CompressedPublicKey> {
         let mut used_keys = Vec::new();
@@ -209,16 +323,50 @@ impl TransactionTypeBuilder {
                     used_keys.push(transfer.destination.get_public_key().clone());
                 }
             }
-            TransactionTypeBuilder::Burn(_) => {}
+            TransactionTypeBuilder::TransferAssetOwnership(payload) => {
+                used_keys.push(payload.new_owner.get_public_key().clone());
+            }
+            TransactionTypeBuilder::MultiSig(payload) => {
+                for participant in &payload.participants {
+                    used_keys.push(participant.get_public_key().clone());
+                }
+            }
+            TransactionTypeBuilder::Burn(_)
+            | TransactionTypeBuilder::CreateAsset(_)
+            | TransactionTypeBuilder::MintAsset(_)
+            | TransactionTypeBuilder::Unlock(_)
+            | TransactionTypeBuilder::DeployContract(_)
+            | TransactionTypeBuilder::InvokeContract(_) => {}
         }
 
         used_keys
     }
+
+    // Contracts require TX_VERSION_CONTRACTS
+    pub fn is_contract(&self) -> bool {
+        matches!(self, Self::DeployContract(_) | Self::InvokeContract(_))
+    }
+
+    // Lowest transaction version supporting this type
+    // Same rules as the final transaction type
+    pub fn get_min_tx_version(&self) -> u8 {
+        match self {
+            Self::Transfers(transfers) if transfers.iter().any(|transfer| transfer.lock.is_some()) => TX_VERSION_MULTISIG,
+            Self::Transfers(_) | Self::Burn(_) => 0,
+            Self::CreateAsset(_)
+            | Self::MintAsset(_)
+            | Self::TransferAssetOwnership(_)
+            | Self::MultiSig(_)
+            | Self::Unlock(_) => TX_VERSION_MULTISIG,
+            Self::DeployContract(_) | Self::InvokeContract(_) => TX_VERSION_CONTRACTS
+        }
+    }
 }
 
-// Used to build the final transaction
-// by signing it
-struct TransactionSigner {
+// Transaction with all its proofs but not signed yet
+// Multisig participants sign it before the final signature of the source
+#[derive(Clone, Debug)]
+pub struct UnsignedTransaction {
     version: u8,
     source: CompressedPublicKey,
     data: TransactionType,
@@ -227,13 +375,84 @@ struct TransactionSigner {
     source_commitments: Vec<SourceCommitment>,
     reference: Reference,
     range_proof: RangeProof,
+    multisig: Option<MultiSig>,
 }
 
-impl TransactionSigner {
-    pub fn sign(self, keypair: &KeyPair) -> Transaction {
-        let bytes = self.to_bytes();
-        let signature = keypair.sign(&bytes);
+impl UnsignedTransaction {
+    // Get the transaction version
+    pub fn get_version(&self) -> u8 {
+        self.version
+    }
+
+    // Get the source key
+    pub fn get_source(&self) -> &CompressedPublicKey {
+        &self.source
+    }
 
+    // Get the nonce used
+    pub fn get_nonce(&self) -> u64 {
+        self.nonce
+    }
+
+    // Get the signatures of the multisig participants
+    pub fn get_multisig(&self) -> &Option<MultiSig> {
+        &self.multisig
+    }
+
+    // Write everything except the signatures
+    fn write_no_signature(&self, writer: &mut Writer) {
+        self.version.write(writer);
+        self.source.write(writer);
+        self.data.write(writer);
+        self.fee.write(writer);
+        self.nonce.write(writer);
+
+        writer.write_u8(self.source_commitments.len() as u8);
+        for commitment in &self.source_commitments {
+            commitment.write(writer);
+        }
+
+        self.range_proof.write(writer);
+        self.reference.write(writer);
+    }
+
+    // Get the hash to sign by the multisig participants
+    // Same as Transaction::get_multisig_hash
+    pub fn get_hash_for_multisig(&self) -> Hash {
+        let mut writer = Writer::new();
+        self.write_no_signature(&mut writer);
+        hash(&writer.bytes())
+    }
+
+    // Sign the transaction as a multisig participant
+    pub fn sign_multisig(&mut self, keypair: &KeyPair, id: u8) -> Result<(), MultiSigError> {
+        let signature = keypair.sign(self.get_hash_for_multisig().as_bytes());
+        self.add_multisig_signature(SignatureId { id, signature })
+    }
+
+    // Add the signature of a multisig participant
+    pub fn add_multisig_signature(&mut self, signature: SignatureId) -> Result<(), MultiSigError> {
+        if self.version < TX_VERSION_MULTISIG {
+            return Err(MultiSigError::Version);
+        }
+
+        let id = signature.id;
+        if !self.multisig.get_or_insert_with(MultiSig::new).add_signature(signature) {
+            return Err(MultiSigError::AlreadySigned(id));
+        }
+
+        Ok(())
+    }
+
+    // Sign the transaction with the source key
+    pub fn finalize(self, keypair: &KeyPair) -> Transaction {
+        let signature = keypair.sign(&self.to_bytes());
+        self.finalize_with_signature(signature)
+    }
+
+    // Add the signature of the source made over the bytes of this unsigned transaction
+    // Used when the source key is held by an external signer
+    pub fn finalize_with_signature(self, signature: Signature) -> Transaction {
         Transaction {
             version: self.version,
             source: self.source,
@@ -243,6 +462,7 @@ impl TransactionSigner {
             source_commitments: self.source_commitments,
             range_proof: self.range_proof,
             reference: self.reference,
+            multisig: self.multisig,
             signature,
         }
     }
@@ -253,11 +473,26 @@ impl TransactionBuilder {
         Self {
             version,
             source,
+            multisig_threshold: None,
+            authenticated_extra_data: false,
             data,
             fee_builder,
         }
     }
 
+    // Encrypt the extra data with the authenticated (V3) format instead of the V2 one
+    // Receivers must be able to decrypt it, so it should only be enabled once their wallets support it
+    pub fn with_authenticated_extra_data(mut self, enabled: bool) -> Self {
+        self.authenticated_extra_data = enabled;
+        self
+    }
+
+    // Set the signatures count required by the multisig of the source account
+    pub fn with_multisig_threshold(mut self, threshold: u8) -> Self {
+        self.multisig_threshold = Some(threshold);
+        self
+    }
+
     /// Estimate by hand the bytes size of a final TX
     // Returns bytes size and transfers count
     pub fn estimate_size(&self) -> usize {
@@ -283,11 +518,26 @@ impl TransactionBuilder {
         + SIGNATURE_SIZE
         ;
 
+        if self.version >= TX_VERSION_MULTISIG {
+            // Multisig option byte
+            size += 1;
+            if let Some(threshold) = self.multisig_threshold {
+                // Signatures count byte and (id, signature) for each participant required
+                size += 1 + threshold as usize * (1 + SIGNATURE_SIZE);
+            }
+        }
+
         let transfers_count = match &self.data {
             TransactionTypeBuilder::Transfers(transfers) => {
                 // Transfers count byte
                 size += 1;
+                let locked = transfers.iter().any(|transfer| transfer.lock.is_some());
                 for transfer in transfers {
+                    if locked {
+                        // Lock option byte and the lock
+                        size += transfer.lock.size();
+                    }
+
                     size += transfer.asset.size()
                     + transfer.destination.get_public_key().size()
                     // Commitment, sender handle, receiver handle
@@ -298,11 +548,16 @@ impl TransactionBuilder {
                     + 1;
 
                     if let Some(extra_data) = transfer.extra_data.as_ref().or(transfer.destination.get_extra_data()) {
-                        // 2 represents u16 length of AEADCipher in extra data
                         // 2 represents u16 length of UnknownExtraDataFormat
-                        // We have both length has we move one in the other
-                        // This mean new ExtraData version has 2 + 2 + 32 (sender) + 32 (receiver) bytes of overhead.
-                        size += 2 + 2 + (RISTRETTO_COMPRESSED_SIZE * 2) + extra_data.size();
+                        size += 2 + extra_data.size();
+                        if self.authenticated_extra_data {
+                            // Authenticated extra data has a version byte, both handles, the nonce,
+                            // the u16 length of the AEADCipher and the tag as overhead.
+                            size += AuthenticatedExtraData::OVERHEAD;
+                        } else {
+                            // V2 has the u16 length of the Cipher and both handles as overhead.
+                            size += 2 + (RISTRETTO_COMPRESSED_SIZE * 2);
+                        }
                     }
                 }
                 transfers.len()
@@ -311,6 +566,41 @@ impl TransactionBuilder {
                 // Payload size
                 size += payload.size();
                 0
+            },
+            TransactionTypeBuilder::CreateAsset(payload) => {
+                // Payload size
+                size += payload.size();
+                0
+            },
+            TransactionTypeBuilder::MintAsset(payload) => {
+                // Payload size
+                size += payload.size();
+                0
+            },
+            TransactionTypeBuilder::TransferAssetOwnership(payload) => {
+                // Asset hash and new owner key
+                size += payload.asset.size() + payload.new_owner.get_public_key().size();
+                0
+            },
+            TransactionTypeBuilder::MultiSig(payload) => {
+                // Threshold byte, participants count byte and their keys
+                size += 1 + 1 + payload.participants.iter().map(|p| p.get_public_key().size()).sum::<usize>();
+                0
+            },
+            TransactionTypeBuilder::Unlock(payload) => {
+                // Payload size
+                size += payload.size();
+                0
+            },
+            TransactionTypeBuilder::DeployContract(payload) => {
+                // Payload size
+                size += payload.size();
+                0
+            },
+            TransactionTypeBuilder::InvokeContract(payload) => {
+                // Payload size
+                size += payload.size();
+                0
             }
         };
 
@@ -376,7 +666,22 @@ impl TransactionBuilder {
                 if *asset == payload.asset {
                     ct -= Scalar::from(payload.amount)
                 }
-            }
+            },
+            TransactionTypeBuilder::CreateAsset(_) => {
+                if *asset == XELIS_ASSET {
+                    ct -= Scalar::from(ASSET_REGISTRATION_FEE)
+                }
+            },
+            TransactionTypeBuilder::InvokeContract(payload) => {
+                if *asset == XELIS_ASSET {
+                    ct -= Scalar::from(payload.max_gas)
+                }
+            },
+            TransactionTypeBuilder::MintAsset(_)
+            | TransactionTypeBuilder::TransferAssetOwnership(_)
+            | TransactionTypeBuilder::MultiSig(_)
+            | TransactionTypeBuilder::Unlock(_)
+            | TransactionTypeBuilder::DeployContract(_) => {}
         }
 
         ct
@@ -392,28 +697,127 @@ impl TransactionBuilder {
         }
 
         match &self.data {
+            // Saturate so an overflowing total is reported as insufficient funds
             TransactionTypeBuilder::Transfers(transfers) => {
                 for transfer in transfers {
                     if &transfer.asset == asset {
-                        cost += transfer.amount;
+                        cost = cost.saturating_add(transfer.amount);
                     }
                 }
             }
             TransactionTypeBuilder::Burn(payload) => {
                 if *asset == payload.asset {
-                    cost += payload.amount
+                    cost = cost.saturating_add(payload.amount)
                 }
-            }
+            },
+            TransactionTypeBuilder::CreateAsset(_) => {
+                if *asset == XELIS_ASSET {
+                    cost += ASSET_REGISTRATION_FEE
+                }
+            },
+            TransactionTypeBuilder::InvokeContract(payload) => {
+                if *asset == XELIS_ASSET {
+                    cost += payload.max_gas
+                }
+            },
+            TransactionTypeBuilder::MintAsset(_)
+            | TransactionTypeBuilder::TransferAssetOwnership(_)
+            | TransactionTypeBuilder::MultiSig(_)
+            | TransactionTypeBuilder::Unlock(_)
+            | TransactionTypeBuilder::DeployContract(_) => {}
         }
 
         cost
     }
 
+    // Build and sign the transaction
     pub fn build<B: AccountState>(
-        mut self,
+        self,
         state: &mut B,
         source_keypair: &KeyPair,
     ) -> Result<Transaction, GenerationError<B::Error>> {
+        let unsigned = self.build_unsigned(state, source_keypair)?;
+        Ok(unsigned.finalize(source_keypair))
+    }
+
+    // Build the transaction with all its proofs without signing it
+    // The source keypair is still required to generate the proofs
+    pub fn build_unsigned<B: AccountState>(
+        mut self,
+        state: &mut B,
+        source_keypair: &KeyPair,
+    ) -> Result<UnsignedTransaction, GenerationError<B::Error>> {
+        if self.version > TX_VERSION {
+            return Err(GenerationError::InvalidVersion(self.version));
+        }
+
+        if self.multisig_threshold.is_some() && self.version < TX_VERSION_MULTISIG {
+            return Err(GenerationError::MultiSigVersion);
+        }
+
+        if self.data.is_contract() && self.version < TX_VERSION_CONTRACTS {
+            return Err(GenerationError::ContractVersion);
+        }
+
+        if self.version < self.data.get_min_tx_version() {
+            return Err(GenerationError::TypeVersion(self.data.get_min_tx_version()));
+        }
+
+        // Same rules as the ones applied when reading the payload
+        match &self.data {
+            TransactionTypeBuilder::CreateAsset(payload) => {
+                if payload.name.is_empty() || payload.name.len() > MAX_ASSET_NAME_LENGTH || payload.decimals > MAX_ASSET_DECIMALS || payload.max_supply == 0 {
+                    return Err(GenerationError::InvalidAssetCreation);
+                }
+            },
+            TransactionTypeBuilder::MintAsset(payload) => {
+                if payload.amount == 0 {
+                    return Err(GenerationError::InvalidMintAmount);
+                }
+            },
+            TransactionTypeBuilder::TransferAssetOwnership(payload) => {
+                if state.is_mainnet() != payload.new_owner.is_mainnet() {
+                    return Err(GenerationError::InvalidNetwork);
+                }
+
+                if *payload.new_owner.get_public_key() == source_keypair.get_public_key().compress() {
+                    return Err(GenerationError::SenderIsReceiver);
+                }
+            },
+            TransactionTypeBuilder::MultiSig(payload) => {
+                let len = payload.participants.len();
+                if len > MAX_MULTISIG_PARTICIPANTS || payload.threshold as usize > len || (payload.threshold == 0 && len != 0) {
+                    return Err(GenerationError::InvalidMultiSig);
+                }
+
+                // A participant can't be set twice
+                let keys = payload.participants.iter().map(|p| p.get_public_key()).collect::<IndexSet<_>>();
+                if keys.len() != len {
+                    return Err(GenerationError::InvalidMultiSig);
+                }
+
+                let source = source_keypair.get_public_key().compress();
+                for participant in &payload.participants {
+                    if state.is_mainnet() != participant.is_mainnet() {
+                        return Err(GenerationError::InvalidNetwork);
+                    }
+
+                    if *participant.get_public_key() == source {
+                        return Err(GenerationError::SenderIsReceiver);
+                    }
+                }
+            },
+            TransactionTypeBuilder::InvokeContract(payload) => {
+                if payload.parameters.len() > MAX_CONTRACT_PARAMETERS || payload.max_gas == 0 || payload.max_gas > MAX_GAS_PER_INVOKE {
+                    return Err(GenerationError::InvalidContractInvoke);
+                }
+            },
+            TransactionTypeBuilder::Transfers(_)
+            | TransactionTypeBuilder::Burn(_)
+            | TransactionTypeBuilder::Unlock(_)
+            | TransactionTypeBuilder::DeployContract(_) => {}
+        };
+
         // Compute the fees
         let fee = self.estimate_fees(state)?;
 
@@ -458,6 +862,11 @@ impl TransactionBuilder {
                 if let Some(extra_data) = &transfer.extra_data {
                     extra_data_size += extra_data.size();
                 }
+
+                // A lock already reached can't be verified anymore
+                if transfer.lock.as_ref().is_some_and(|lock| lock.topoheight <= state.get_reference().topoheight) {
+                    return Err(GenerationError::InvalidLock);
+                }
             }
 
             if extra_data_size > EXTRA_DATA_LIMIT_SIZE {
@@ -593,16 +1002,21 @@ impl TransactionBuilder {
 
                     // Encrypt the extra data if it exists
                     let extra_data = if let Some(extra_data) = transfer.inner.extra_data {
-                        let bytes = extra_data.to_bytes();
-                        let cipher = ExtraData::new(PlaintextData(bytes), source_keypair.get_public_key(), &transfer.destination);
-                        let cipher_size = cipher.size();
+                        let bytes = PlaintextData(extra_data.to_bytes());
+                        let cipher: UnknownExtraDataFormat = if self.authenticated_extra_data {
+                            AuthenticatedExtraData::new(bytes, source_keypair.get_public_key(), &transfer.destination).into()
+                        } else {
+                            ExtraData::new(bytes, source_keypair.get_public_key(), &transfer.destination).into()
+                        };
+                        // Size of the encrypted payload, without its length prefix
+                        let cipher_size = cipher.0.len();
                         if cipher_size > EXTRA_DATA_LIMIT_SIZE {
                             return Err(GenerationError::EncryptedExtraDataTooLarge);
                         }
 
                         total_cipher_size += cipher_size;
 
-                        Some(cipher.into())
+                        Some(cipher)
                     } else {
                         None
                     };
@@ -615,6 +1029,7 @@ impl TransactionBuilder {
                         asset: transfer.inner.asset,
                         ct_validity_proof,
                         extra_data,
+                        lock: transfer.inner.lock,
                     })
                 })
                 .collect::<Result<Vec<_>, GenerationError<B::Error>>>()?;
@@ -641,7 +1056,20 @@ impl TransactionBuilder {
 
         let data = match self.data {
             TransactionTypeBuilder::Transfers(_) => TransactionType::Transfers(transfers),
-            TransactionTypeBuilder::Burn(payload) => TransactionType::Burn(payload)
+            TransactionTypeBuilder::Burn(payload) => TransactionType::Burn(payload),
+            TransactionTypeBuilder::CreateAsset(payload) => TransactionType::CreateAsset(payload),
+            TransactionTypeBuilder::MintAsset(payload) => TransactionType::MintAsset(payload),
+            TransactionTypeBuilder::TransferAssetOwnership(payload) => TransactionType::TransferAssetOwnership(AssetOwnershipPayload {
+                asset: payload.asset,
+                new_owner: payload.new_owner.to_public_key()
+            }),
+            TransactionTypeBuilder::MultiSig(payload) => TransactionType::MultiSig(MultiSigPayload {
+                threshold: payload.threshold,
+                participants: payload.participants.into_iter().map(|p| p.to_public_key()).collect()
+            }),
+            TransactionTypeBuilder::Unlock(payload) => TransactionType::Unlock(payload),
+            TransactionTypeBuilder::DeployContract(payload) => TransactionType::DeployContract(payload),
+            TransactionTypeBuilder::InvokeContract(payload) => TransactionType::InvokeContract(payload)
         };
 
         // 3. Create the RangeProof
@@ -656,7 +1084,7 @@ impl TransactionBuilder {
         )
         .map_err(ProofGenerationError::from)?;
 
-        let transaction = TransactionSigner {
+        Ok(UnsignedTransaction {
             version: self.version,
             source: self.source,
             data,
@@ -665,32 +1093,64 @@ impl TransactionBuilder {
             source_commitments,
             reference,
             range_proof,
-        }.sign(source_keypair);
-
-        Ok(transaction)
+            multisig: None,
+        })
     }
 }
 
-impl Serializer for TransactionSigner {
+// Same format as the final transaction without its signature
+impl Serializer for UnsignedTransaction {
     fn write(&self, writer: &mut Writer) {
-        self.version.write(writer);
-        self.source.write(writer);
-        self.data.write(writer);
-        self.fee.write(writer);
-        self.nonce.write(writer);
+        self.write_no_signature(writer);
+        if self.version >= TX_VERSION_MULTISIG {
+            self.multisig.write(writer);
+        }
+    }
 
-        writer.write_u8(self.source_commitments.len() as u8);
-        for commitment in &self.source_commitments {
-            commitment.write(writer);
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        let version = reader.read_u8()?;
+        if version > TX_VERSION {
+            return Err(ReaderError::InvalidValue)
         }
 
-        self.range_proof.write(writer);
-        self.reference.write(writer);
-    }
+        let source = CompressedPublicKey::read(reader)?;
+        let data = TransactionType::read(reader)?;
+        if version < data.get_min_tx_version() {
+            return Err(ReaderError::InvalidValue)
+        }
+
+        let fee = reader.read_u64()?;
+        let nonce = reader.read_u64()?;
+
+        let commitments_len = reader.read_u8()?;
+        if commitments_len == 0 || commitments_len > MAX_TRANSFER_COUNT as u8 {
+            return Err(ReaderError::InvalidSize)
+        }
+
+        let mut source_commitments = Vec::with_capacity(commitments_len as usize);
+        for _ in 0..commitments_len {
+            source_commitments.push(SourceCommitment::read(reader)?);
+        }
 
-    // Should never be called
-    fn read(_: &mut Reader) -> Result<Self, ReaderError> {
-        Err(ReaderError::InvalidValue)
+        let range_proof = RangeProof::read(reader)?;
+        let reference = Reference::read(reader)?;
+        let multisig = if version >= TX_VERSION_MULTISIG {
+            Option::read(reader)?
+        } else {
+            None
+        };
+
+        Ok(Self {
+            version,
+            source,
+            data,
+            fee,
+            nonce,
+            source_commitments,
+            reference,
+            range_proof,
+            multisig
+        })
     }
 }
 
@@ -2,13 +2,14 @@ use std::borrow::Cow;
 
 use chacha20poly1305::{
     aead::{Aead, Payload},
-    AeadInPlace, ChaCha20Poly1305, KeyInit,
+    AeadInPlace, ChaCha20Poly1305, KeyInit, XChaCha20Poly1305, XNonce,
 };
 use chacha20::{
     cipher::{KeyIvInit, StreamCipher},
     ChaCha20,
 };
 use curve25519_dalek::ristretto::CompressedRistretto;
+use rand::{rngs::OsRng, RngCore};
 use sha3::Digest;
 use zeroize::Zeroize;
 use thiserror::Error;
@@ -41,6 +42,16 @@ pub type KDF = sha3::Sha3_256;
 // The size of the tag in bytes.
 pub const TAG_SIZE: usize = 16;
 
+// The size of the XChaCha20-Poly1305 nonce in bytes.
+pub const XNONCE_SIZE: usize = 24;
+
+// Version byte prefixed to the authenticated extra data.
+// V1 and V2 have no version byte.
+pub const EXTRA_DATA_VERSION: u8 = 3;
+
+// Domain separator of the key derived for the authenticated extra data.
+const EXTRA_DATA_KDF_DOMAIN: &[u8] = b"xelis-extra-data-v3";
+
 // This error is thrown when the ciphertext is not in the expected format.
 #[derive(Error, Clone, Debug, Eq, PartialEq)]
 #[error("malformated ciphertext")]
@@ -86,7 +97,25 @@ pub struct ExtraData {
     receiver_handle: CompressedHandle,
 }
 
+// Authenticated version of the Extra Data.
+// The V2 cipher has no tag, so a modified payload is decrypted to garbage without being detected.
+// The shared key is derived the same way (ECDH between a random opening and the keys of both parties)
+// but with its own domain, and the payload is encrypted with XChaCha20-Poly1305 using a random nonce.
+// The version byte and both handles are authenticated as associated data.
+pub struct AuthenticatedExtraData {
+    sender_handle: CompressedHandle,
+    receiver_handle: CompressedHandle,
+    nonce: [u8; XNONCE_SIZE],
+    cipher: AEADCipher,
+}
+
 impl UnknownExtraDataFormat {
+    pub fn decrypt_v3(&self, private_key: &PrivateKey, role: Role) -> Result<DataElement, CipherFormatError> {
+        let e = AuthenticatedExtraData::from_bytes(&self.0).map_err(|_| CipherFormatError)?;
+        let plaintext = e.decrypt(private_key, role)?;
+        DataElement::from_bytes(&plaintext.0).map_err(|_| CipherFormatError)
+    }
+
     pub fn decrypt_v2(&self, private_key: &PrivateKey, role: Role) -> Result<DataElement, CipherFormatError> {
         let e = ExtraData::from_bytes(&self.0).map_err(|_| CipherFormatError)?;
         let plaintext = e.decrypt(private_key, role)?;
@@ -100,7 +129,15 @@ impl UnknownExtraDataFormat {
     }
 
     pub fn decrypt(&self, private_key: &PrivateKey, handle: &DecryptHandle, role: Role) -> Result<DataElement, CipherFormatError> {
-        // Try the new version
+        // Try the authenticated version, it is the only one with a version byte
+        // A V1 or V2 payload starting with the same byte fails the tag verification
+        if self.0.first() == Some(&EXTRA_DATA_VERSION) && self.0.len() >= AuthenticatedExtraData::OVERHEAD {
+            if let Ok(e) = self.decrypt_v3(private_key, role) {
+                return Ok(e)
+            }
+        }
+
+        // Try the V2 version
         // If it has 64 + 2 bytes of overhead at least, it may be a V2 
         if self.0.len() >= (RISTRETTO_COMPRESSED_SIZE * 2) + 2 {
             if let Ok(e) = self.decrypt_v2(private_key, role) {
@@ -151,10 +188,73 @@ impl ExtraData {
     }
 }
 
+impl AuthenticatedExtraData {
+    // Version byte, both handles, nonce, cipher length and tag
+    pub const OVERHEAD: usize = 1 + (RISTRETTO_COMPRESSED_SIZE * 2) + XNONCE_SIZE + 2 + TAG_SIZE;
+
+    // Encrypt the message for the receiver & sender keys.
+    // Both will be able to decrypt it.
+    pub fn new(data: PlaintextData, sender: &PublicKey, receiver: &PublicKey) -> Self {
+        let opening = PedersenOpening::generate_new();
+        let sender_handle = sender.decrypt_handle(&opening).compress();
+        let receiver_handle = receiver.decrypt_handle(&opening).compress();
+
+        // Key is never reused as the opening is random, the nonce is random too for defense in depth
+        let mut nonce = [0u8; XNONCE_SIZE];
+        OsRng.fill_bytes(&mut nonce);
+
+        let key = derive_authenticated_key(&(opening.as_scalar() * &*H).compress());
+        let aad = Self::associated_data(&sender_handle, &receiver_handle);
+        let mut buffer = data.0;
+        XChaCha20Poly1305::new(&key)
+            .encrypt_in_place(XNonce::from_slice(&nonce), &aad, &mut buffer)
+            .expect("unreachable (unsufficient capacity on a vec)");
+
+        Self {
+            sender_handle,
+            receiver_handle,
+            nonce,
+            cipher: AEADCipher(buffer),
+        }
+    }
+
+    // Version byte and both handles are authenticated with the payload
+    fn associated_data(sender_handle: &CompressedHandle, receiver_handle: &CompressedHandle) -> Vec<u8> {
+        let mut aad = Vec::with_capacity(1 + RISTRETTO_COMPRESSED_SIZE * 2);
+        aad.push(EXTRA_DATA_VERSION);
+        aad.extend_from_slice(sender_handle.as_bytes());
+        aad.extend_from_slice(receiver_handle.as_bytes());
+        aad
+    }
+
+    // Get the compressed handle based on its role
+    fn get_handle(&self, role: Role) -> &CompressedHandle {
+        match role {
+            Role::Sender => &self.sender_handle,
+            Role::Receiver => &self.receiver_handle,
+        }
+    }
+
+    // Decrypt the message using the private key and the role to determine the correct handle to use.
+    // Fails if the payload, the handles or the nonce were modified.
+    pub fn decrypt(&self, private_key: &PrivateKey, role: Role) -> Result<PlaintextData, CipherFormatError> {
+        let handle = self.get_handle(role).decompress().map_err(|_| CipherFormatError)?;
+        let key = derive_authenticated_key(&(private_key.as_scalar() * handle.as_point()).compress());
+        let aad = Self::associated_data(&self.sender_handle, &self.receiver_handle);
+        let res = XChaCha20Poly1305::new(&key)
+            .decrypt(XNonce::from_slice(&self.nonce), Payload { msg: &self.cipher.0, aad: &aad })
+            .map_err(|_| CipherFormatError)?;
+
+        Ok(PlaintextData(res))
+    }
+}
+
 pub enum ExtraDataVariant {
     // Warning: should not be used anymore as cryptographically broken
     V1(AEADCipher),
-    V2(ExtraData)
+    // Warning: not authenticated, replaced by V3
+    V2(ExtraData),
+    V3(AuthenticatedExtraData)
 }
 
 impl Serializer for ExtraData {
@@ -177,6 +277,41 @@ impl Serializer for ExtraData {
     }
 }
 
+impl Serializer for AuthenticatedExtraData {
+    fn write(&self, writer: &mut Writer) {
+        writer.write_u8(EXTRA_DATA_VERSION);
+        self.sender_handle.write(writer);
+        self.receiver_handle.write(writer);
+        writer.write_bytes(&self.nonce);
+        self.cipher.write(writer);
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        if reader.read_u8()? != EXTRA_DATA_VERSION {
+            return Err(ReaderError::InvalidValue)
+        }
+
+        Ok(Self {
+            sender_handle: CompressedHandle::read(reader)?,
+            receiver_handle: CompressedHandle::read(reader)?,
+            nonce: reader.read_bytes(XNONCE_SIZE)?,
+            cipher: AEADCipher::read(reader)?,
+        })
+    }
+
+    fn size(&self) -> usize {
+        1 + self.sender_handle.size() + self.receiver_handle.size() + XNONCE_SIZE + self.cipher.size()
+    }
+}
+
+/// Same as [`derive_shared_key`] but domain separated for the authenticated extra data.
+pub fn derive_authenticated_key(point: &CompressedRistretto) -> SharedKey {
+    let mut hash = KDF::new();
+    hash.update(EXTRA_DATA_KDF_DOMAIN);
+    hash.update(point.as_bytes());
+    hash.finalize()
+}
+
 /// See [`derive_shared_key`].
 pub fn derive_shared_key_from_opening(opening: &PedersenOpening) -> SharedKey {
     derive_shared_key(&(opening.as_scalar() * &*H).compress())
@@ -250,6 +385,12 @@ impl From<ExtraData> for UnknownExtraDataFormat {
     }
 }
 
+impl From<AuthenticatedExtraData> for UnknownExtraDataFormat {
+    fn from(value: AuthenticatedExtraData) -> Self {
+        Self(value.to_bytes())
+    }
+}
+
 impl<'a> AEADCipherInner<'a> {
     /// Warning: keys should not be reused
     pub fn decrypt(&self, key: &SharedKey) -> Result<PlaintextData, CipherFormatError> {
@@ -350,4 +491,27 @@ mod tests {
         let decrypted = extra_data.decrypt(bob.get_private_key(), Role::Receiver).unwrap();
         assert_eq!(decrypted.0, bytes);
     }
+
+    #[test]
+    fn test_encrypt_decrypt_authenticated_extra_data() {
+        let alice = KeyPair::new();
+        let bob = KeyPair::new();
+
+        let bytes = vec![1, 2, 3, 4, 5];
+        let extra_data = AuthenticatedExtraData::new(PlaintextData(bytes.clone()), alice.get_public_key(), bob.get_public_key());
+        let mut raw = extra_data.to_bytes();
+        assert_eq!(raw[0], EXTRA_DATA_VERSION);
+        assert_eq!(raw.len(), AuthenticatedExtraData::OVERHEAD + bytes.len());
+
+        let extra_data = AuthenticatedExtraData::from_bytes(&raw).unwrap();
+        assert_eq!(extra_data.decrypt(alice.get_private_key(), Role::Sender).unwrap().0, bytes);
+        assert_eq!(extra_data.decrypt(bob.get_private_key(), Role::Receiver).unwrap().0, bytes);
+        assert!(extra_data.decrypt(alice.get_private_key(), Role::Receiver).is_err());
+
+        // Any modification is detected
+        let last = raw.len() - 1;
+        raw[last] ^= 1;
+        let extra_data = AuthenticatedExtraData::from_bytes(&raw).unwrap();
+        assert!(extra_data.decrypt(bob.get_private_key(), Role::Receiver).is_err());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,130 @@
+use rand::{rngs::OsRng, RngCore};
+use serde::{de::Error as SerdeError, Deserialize, Deserializer, Serialize};
+use sha2::{Digest, Sha256};
+use crate::{
+    crypto::{
+        elgamal::{CompressedCiphertext, CompressedCommitment, CompressedHandle, CompressedPublicKey},
+        hash,
+        Hash,
+        HASH_SIZE
+    },
+    serializer::{Reader, ReaderError, Serializer, Writer}
+};
+use super::Role;
+
+// Size of the secret revealed to claim a hash locked transfer
+pub const PREIMAGE_SIZE: usize = 32;
+
+// Secret revealed by the receiver to claim a hash locked transfer
+// It is hashed using SHA-256 to be compatible with the hash locks of other chains
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Preimage([u8; PREIMAGE_SIZE]);
+
+impl Preimage {
+    pub fn new(bytes: [u8; PREIMAGE_SIZE]) -> Self {
+        Self(bytes)
+    }
+
+    // Generate a new random secret
+    pub fn random() -> Self {
+        let mut bytes = [0; PREIMAGE_SIZE];
+        OsRng.fill_bytes(&mut bytes);
+        Self(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; PREIMAGE_SIZE] {
+        &self.0
+    }
+
+    // Hash to set in the lock of the transfer
+    pub fn get_hash(&self) -> Hash {
+        let mut bytes = [0; HASH_SIZE];
+        bytes.copy_from_slice(&Sha256::digest(&self.0));
+        Hash::new(bytes)
+    }
+}
+
+impl Serialize for Preimage {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_hex())
+    }
+}
+
+impl<'a> Deserialize<'a> for Preimage {
+    fn deserialize<D: Deserializer<'a>>(deserializer: D) -> Result<Self, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        Preimage::from_hex(hex).map_err(SerdeError::custom)
+    }
+}
+
+impl Serializer for Preimage {
+    fn write(&self, writer: &mut Writer) {
+        writer.write_bytes(&self.0);
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        Ok(Self(reader.read_bytes(PREIMAGE_SIZE)?))
+    }
+
+    fn size(&self) -> usize {
+        PREIMAGE_SIZE
+    }
+}
+
+// Condition to unlock a transfer
+// Without hash, the receiver can claim it once the chain reached the topoheight
+// With a hash, the receiver can claim it before the topoheight by revealing the preimage
+// and once the topoheight is reached, only the sender can get refunded
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Serializer)]
+pub struct TransferLock {
+    pub topoheight: u64,
+    pub hash: Option<Hash>
+}
+
+impl TransferLock {
+    // Can the receiver claim the transfer at this topoheight
+    pub fn can_claim(&self, topoheight: u64, preimage: Option<&Preimage>) -> bool {
+        match (&self.hash, preimage) {
+            (None, None) => topoheight >= self.topoheight,
+            (Some(hash), Some(preimage)) => topoheight < self.topoheight && preimage.get_hash() == *hash,
+            _ => false
+        }
+    }
+
+    // Can the sender get refunded at this topoheight
+    pub fn can_refund(&self, topoheight: u64) -> bool {
+        self.hash.is_some() && topoheight >= self.topoheight
+    }
+}
+
+// Transfer kept on chain until it is claimed by the receiver or refunded to the sender
+// The ciphertext is credited as is to the account unlocking it
+#[derive(Serialize, Deserialize, Clone, Debug, Serializer)]
+pub struct LockedTransfer {
+    pub source: CompressedPublicKey,
+    pub destination: CompressedPublicKey,
+    pub asset: Hash,
+    pub commitment: CompressedCommitment,
+    pub sender_handle: CompressedHandle,
+    pub receiver_handle: CompressedHandle,
+    pub lock: TransferLock
+}
+
+impl LockedTransfer {
+    pub fn get_ciphertext(&self, role: Role) -> CompressedCiphertext {
+        let handle = match role {
+            Role::Receiver => self.receiver_handle.clone(),
+            Role::Sender => self.sender_handle.clone(),
+        };
+
+        CompressedCiphertext::new(self.commitment.clone(), handle)
+    }
+}
+
+// Id of a locked transfer, based on the transaction hash and the index of the transfer in it
+pub fn get_lock_id(tx_hash: &Hash, index: u8) -> Hash {
+    let mut bytes = [0; HASH_SIZE + 1];
+    bytes[..HASH_SIZE].copy_from_slice(tx_hash.as_bytes());
+    bytes[HASH_SIZE] = index;
+    hash(&bytes)
+}
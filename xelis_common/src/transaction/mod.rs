@@ -1,22 +1,32 @@
 use std::fmt;
 use crate::{
+    contract::{Program, MAX_CONTRACT_PARAMETERS, MAX_GAS_PER_INVOKE},
     crypto::{
         elgamal::{CompressedCiphertext, CompressedCommitment, CompressedHandle, CompressedPublicKey},
         proofs::{CiphertextValidityProof, CommitmentEqProof},
+        hash,
         Hash,
         Hashable,
         Signature,
+        SIGNATURE_SIZE
     },
     serializer::{Reader, ReaderError, Serializer, Writer}
 };
 use bulletproofs::RangeProof;
+use indexmap::IndexSet;
 use log::debug;
 use serde::{Deserialize, Serialize};
-use self::extra_data::UnknownExtraDataFormat;
+use self::{
+    extra_data::UnknownExtraDataFormat,
+    lock::{LockedTransfer, Preimage, TransferLock},
+    multisig::{MultiSig, MAX_MULTISIG_PARTICIPANTS}
+};
 
 pub mod builder;
 pub mod verify;
 pub mod extra_data;
+pub mod multisig;
+pub mod lock;
 
 #[cfg(test)]
 mod tests;
@@ -24,8 +34,19 @@ mod tests;
 // Maximum total size of payload across all transfers per transaction
 pub const EXTRA_DATA_LIMIT_SIZE: usize = 1024;
 pub const MAX_TRANSFER_COUNT: usize = 255;
-
-#[derive(Serialize, Deserialize, Clone, Debug)]
+// Maximum length of an asset name in bytes
+pub const MAX_ASSET_NAME_LENGTH: usize = 32;
+// Maximum decimals allowed for an asset
+pub const MAX_ASSET_DECIMALS: u8 = 18;
+// Transactions from this version contain the multisig signatures
+// and can use the asset, multisig, locked transfer and unlock types
+pub const TX_VERSION_MULTISIG: u8 = 1;
+// Transactions from this version can deploy and invoke contracts
+pub const TX_VERSION_CONTRACTS: u8 = 2;
+// Latest transaction version supported
+pub const TX_VERSION: u8 = TX_VERSION_CONTRACTS;
+
+#[derive(Serialize, Deserialize, Clone, Debug, Serializer)]
 pub struct Reference {
     pub hash: Hash,
     pub topoheight: u64,
@@ -48,14 +69,14 @@ pub enum Role {
     Receiver,
 }
 
-#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Serializer)]
 pub struct SourceCommitment {
     commitment: CompressedCommitment,
     proof: CommitmentEqProof,
     asset: Hash,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, Serializer)]
 pub struct TransferPayload {
     asset: Hash,
     destination: CompressedPublicKey,
@@ -67,21 +88,120 @@ pub struct TransferPayload {
     sender_handle: CompressedHandle,
     receiver_handle: CompressedHandle,
     ct_validity_proof: CiphertextValidityProof,
+    // Funds are kept on chain until the lock is unlocked
+    // The lock is serialized by the transaction type to stay compatible with transfers without locks
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serializer(skip)]
+    lock: Option<TransferLock>,
 }
 
 // Burn is a public payload allowing to use it as a proof of burn
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, Serializer)]
 pub struct BurnPayload {
     pub asset: Hash,
     pub amount: u64
 }
 
+// Register a new asset on chain
+// The asset hash is the hash of the transaction creating it
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AssetCreationPayload {
+    pub name: String,
+    pub decimals: u8,
+    pub max_supply: u64
+}
+
+// Mint new atomic units of an asset to its owner
+// Only the owner can mint, up to the maximum supply of the asset
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MintAssetPayload {
+    pub asset: Hash,
+    pub amount: u64
+}
+
+// Give the ownership of an asset to another account
+#[derive(Serialize, Deserialize, Clone, Debug, Serializer)]
+pub struct AssetOwnershipPayload {
+    pub asset: Hash,
+    pub new_owner: CompressedPublicKey
+}
+
+// Setup the multisig of the account
+// Each transaction of the account will then require `threshold` signatures of its participants
+// A threshold of 0 without participants deletes the multisig
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct MultiSigPayload {
+    pub threshold: u8,
+    pub participants: IndexSet<CompressedPublicKey>
+}
+
+impl MultiSigPayload {
+    // Is this payload deleting the multisig
+    pub fn is_delete(&self) -> bool {
+        self.threshold == 0 && self.participants.is_empty()
+    }
+}
+
+// Claim or refund a locked transfer
+// The receiver claims it, the sender can only get refunded once its hash lock expired
+// The asset must be the one of the locked transfer
+#[derive(Serialize, Deserialize, Clone, Debug, Serializer)]
+pub struct UnlockPayload {
+    pub id: Hash,
+    pub asset: Hash,
+    pub preimage: Option<Preimage>
+}
+
+// Deploy a new contract on chain
+// The contract hash is the hash of the transaction deploying it
+#[derive(Serialize, Deserialize, Clone, Debug, Serializer)]
+pub struct DeployContractPayload {
+    pub program: Program
+}
+
+// Execute a contract with the parameters
+// The maximum gas is always paid in XELIS, even if the execution used less
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct InvokeContractPayload {
+    pub contract: Hash,
+    pub parameters: Vec<u64>,
+    pub max_gas: u64
+}
+
 // this enum represent all types of transaction available on XELIS Network
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(rename_all = "snake_case")]
 pub enum TransactionType {
     Transfers(Vec<TransferPayload>),
     Burn(BurnPayload),
+    CreateAsset(AssetCreationPayload),
+    MintAsset(MintAssetPayload),
+    TransferAssetOwnership(AssetOwnershipPayload),
+    MultiSig(MultiSigPayload),
+    Unlock(UnlockPayload),
+    DeployContract(DeployContractPayload),
+    InvokeContract(InvokeContractPayload),
+}
+
+impl TransactionType {
+    // Contracts are only available from TX_VERSION_CONTRACTS
+    pub fn is_contract(&self) -> bool {
+        matches!(self, Self::DeployContract(_) | Self::InvokeContract(_))
+    }
+
+    // Lowest transaction version supporting this type
+    pub fn get_min_tx_version(&self) -> u8 {
+        match self {
+            Self::Transfers(txs) if txs.iter().any(|tx| tx.lock.is_some()) => TX_VERSION_MULTISIG,
+            Self::Transfers(_) | Self::Burn(_) => 0,
+            Self::CreateAsset(_)
+            | Self::MintAsset(_)
+            | Self::TransferAssetOwnership(_)
+            | Self::MultiSig(_)
+            | Self::Unlock(_) => TX_VERSION_MULTISIG,
+            Self::DeployContract(_) | Self::InvokeContract(_) => TX_VERSION_CONTRACTS
+        }
+    }
 }
 
 // Transaction to be sent over the network
@@ -104,13 +224,16 @@ pub struct Transaction {
     range_proof: RangeProof,
     /// At which block the TX is built
     reference: Reference,
+    /// Signatures of the multisig participants
+    /// Only serialized from version 1
+    multisig: Option<MultiSig>,
     /// The signature of the source key
     signature: Signature,
 }
 
 impl TransferPayload {
     // Create a new transfer payload
-    pub fn new(asset: Hash, destination: CompressedPublicKey, extra_data: Option<UnknownExtraDataFormat>, commitment: CompressedCommitment, sender_handle: CompressedHandle, receiver_handle: CompressedHandle, ct_validity_proof: CiphertextValidityProof) -> Self {
+    pub fn new(asset: Hash, destination: CompressedPublicKey, extra_data: Option<UnknownExtraDataFormat>, commitment: CompressedCommitment, sender_handle: CompressedHandle, receiver_handle: CompressedHandle, ct_validity_proof: CiphertextValidityProof, lock: Option<TransferLock>) -> Self {
         TransferPayload {
             asset,
             destination,
@@ -118,7 +241,8 @@ impl TransferPayload {
             commitment,
             sender_handle,
             receiver_handle,
-            ct_validity_proof
+            ct_validity_proof,
+            lock
         }
     }
 
@@ -157,6 +281,24 @@ impl TransferPayload {
         &self.ct_validity_proof
     }
 
+    // Get the lock of the transfer if any
+    pub fn get_lock(&self) -> &Option<TransferLock> {
+        &self.lock
+    }
+
+    // Get the locked transfer to keep on chain if the transfer has a lock
+    pub fn get_locked_transfer(&self, source: &CompressedPublicKey) -> Option<LockedTransfer> {
+        self.lock.as_ref().map(|lock| LockedTransfer {
+            source: source.clone(),
+            destination: self.destination.clone(),
+            asset: self.asset.clone(),
+            commitment: self.commitment.clone(),
+            sender_handle: self.sender_handle.clone(),
+            receiver_handle: self.receiver_handle.clone(),
+            lock: lock.clone()
+        })
+    }
+
     pub fn get_ciphertext(&self, role: Role) -> CompressedCiphertext {
         let handle = match role {
             Role::Receiver => self.receiver_handle.clone(),
@@ -173,9 +315,9 @@ impl TransferPayload {
 }
 
 impl Transaction {
-    pub fn new(source: CompressedPublicKey, data: TransactionType, fee: u64, nonce: u64, source_commitments: Vec<SourceCommitment>, range_proof: RangeProof, reference: Reference, signature: Signature) -> Self {
+    pub fn new(version: u8, source: CompressedPublicKey, data: TransactionType, fee: u64, nonce: u64, source_commitments: Vec<SourceCommitment>, range_proof: RangeProof, reference: Reference, multisig: Option<MultiSig>, signature: Signature) -> Self {
         Transaction {
-            version: 0,
+            version,
             source,
             data,
             fee,
@@ -183,6 +325,7 @@ impl Transaction {
             source_commitments,
             range_proof,
             reference,
+            multisig,
             signature
         }
     }
@@ -222,11 +365,28 @@ impl Transaction {
         &self.range_proof
     }
 
+    // Get the signatures of the multisig participants
+    pub fn get_multisig(&self) -> &Option<MultiSig> {
+        &self.multisig
+    }
+
     // Get the signature of source key
     pub fn get_signature(&self) -> &Signature {
         &self.signature
     }
 
+    // Get the hash signed by the multisig participants
+    // It covers the whole transaction except the signatures
+    pub fn get_multisig_hash(&self) -> Hash {
+        let bytes = self.to_bytes();
+        let mut size = bytes.len() - SIGNATURE_SIZE;
+        if self.version >= TX_VERSION_MULTISIG {
+            size -= self.multisig.size();
+        }
+
+        hash(&bytes[..size])
+    }
+
     // Get the block reference to determine which block the transaction is built
     pub fn get_reference(&self) -> &Reference {
         &self.reference
@@ -237,90 +397,141 @@ impl Transaction {
     }
 }
 
-impl Serializer for SourceCommitment {
+impl Serializer for AssetCreationPayload {
     fn write(&self, writer: &mut Writer) {
-        self.commitment.write(writer);
-        self.proof.write(writer);
-        self.asset.write(writer);
+        self.name.write(writer);
+        writer.write_u8(self.decimals);
+        self.max_supply.write(writer);
     }
 
-    fn read(reader: &mut Reader) -> Result<SourceCommitment, ReaderError> {
-        let commitment = CompressedCommitment::read(reader)?;
-        let proof = CommitmentEqProof::read(reader)?;
-        let asset = Hash::read(reader)?;
+    fn read(reader: &mut Reader) -> Result<AssetCreationPayload, ReaderError> {
+        let name = reader.read_string()?;
+        if name.is_empty() || name.len() > MAX_ASSET_NAME_LENGTH {
+            return Err(ReaderError::InvalidSize)
+        }
 
-        Ok(SourceCommitment {
-            commitment,
-            proof,
-            asset
+        let decimals = reader.read_u8()?;
+        if decimals > MAX_ASSET_DECIMALS {
+            return Err(ReaderError::InvalidValue)
+        }
+
+        let max_supply = reader.read_u64()?;
+        if max_supply == 0 {
+            return Err(ReaderError::InvalidValue)
+        }
+
+        Ok(AssetCreationPayload {
+            name,
+            decimals,
+            max_supply
         })
     }
 
     fn size(&self) -> usize {
-        self.commitment.size() + self.proof.size() + self.asset.size()
+        self.name.size() + self.decimals.size() + self.max_supply.size()
     }
 }
 
-impl Serializer for TransferPayload {
+impl Serializer for MintAssetPayload {
     fn write(&self, writer: &mut Writer) {
         self.asset.write(writer);
-        self.destination.write(writer);
-        self.extra_data.write(writer);
-        self.commitment.write(writer);
-        self.sender_handle.write(writer);
-        self.receiver_handle.write(writer);
-        self.ct_validity_proof.write(writer);
+        self.amount.write(writer);
     }
 
-    fn read(reader: &mut Reader) -> Result<TransferPayload, ReaderError> {
+    fn read(reader: &mut Reader) -> Result<MintAssetPayload, ReaderError> {
         let asset = Hash::read(reader)?;
-        let destination = CompressedPublicKey::read(reader)?;
-        let extra_data = Option::read(reader)?;
-
-        let commitment = CompressedCommitment::read(reader)?;
-        let sender_handle = CompressedHandle::read(reader)?;
-        let receiver_handle = CompressedHandle::read(reader)?;
-        let ct_validity_proof = CiphertextValidityProof::read(reader)?;
+        let amount = reader.read_u64()?;
+        if amount == 0 {
+            return Err(ReaderError::InvalidValue)
+        }
 
-        Ok(TransferPayload {
+        Ok(MintAssetPayload {
             asset,
-            destination,
-            extra_data,
-            commitment,
-            sender_handle,
-            receiver_handle,
-            ct_validity_proof
+            amount
         })
     }
 
     fn size(&self) -> usize {
-        self.asset.size()
-        + self.destination.size()
-        + self.extra_data.size()
-        + self.commitment.size()
-        + self.sender_handle.size()
-        + self.receiver_handle.size()
-        + self.ct_validity_proof.size()
+        self.asset.size() + self.amount.size()
     }
 }
 
-impl Serializer for BurnPayload {
+impl Serializer for MultiSigPayload {
     fn write(&self, writer: &mut Writer) {
-        self.asset.write(writer);
-        self.amount.write(writer);
+        writer.write_u8(self.threshold);
+        writer.write_u8(self.participants.len() as u8);
+        for participant in &self.participants {
+            participant.write(writer);
+        }
     }
 
-    fn read(reader: &mut Reader) -> Result<BurnPayload, ReaderError> {
-        let asset = Hash::read(reader)?;
-        let amount = reader.read_u64()?;
-        Ok(BurnPayload {
-            asset,
-            amount
+    fn read(reader: &mut Reader) -> Result<MultiSigPayload, ReaderError> {
+        let threshold = reader.read_u8()?;
+        let len = reader.read_u8()? as usize;
+        if len > MAX_MULTISIG_PARTICIPANTS {
+            return Err(ReaderError::InvalidSize)
+        }
+
+        let mut participants = IndexSet::with_capacity(len);
+        for _ in 0..len {
+            if !participants.insert(CompressedPublicKey::read(reader)?) {
+                return Err(ReaderError::InvalidValue)
+            }
+        }
+
+        // Threshold can't be above the participants count
+        // And only a deletion can have a threshold of 0
+        if threshold as usize > len || (threshold == 0 && len != 0) {
+            return Err(ReaderError::InvalidValue)
+        }
+
+        Ok(MultiSigPayload {
+            threshold,
+            participants
         })
     }
 
     fn size(&self) -> usize {
-        self.asset.size() + self.amount.size()
+        1 + 1 + self.participants.iter().map(|p| p.size()).sum::<usize>()
+    }
+}
+
+impl Serializer for InvokeContractPayload {
+    fn write(&self, writer: &mut Writer) {
+        self.contract.write(writer);
+        writer.write_u8(self.parameters.len() as u8);
+        for parameter in &self.parameters {
+            parameter.write(writer);
+        }
+        self.max_gas.write(writer);
+    }
+
+    fn read(reader: &mut Reader) -> Result<InvokeContractPayload, ReaderError> {
+        let contract = Hash::read(reader)?;
+        let len = reader.read_u8()? as usize;
+        if len > MAX_CONTRACT_PARAMETERS {
+            return Err(ReaderError::InvalidSize)
+        }
+
+        let mut parameters = Vec::with_capacity(len);
+        for _ in 0..len {
+            parameters.push(reader.read_u64()?);
+        }
+
+        let max_gas = reader.read_u64()?;
+        if max_gas == 0 || max_gas > MAX_GAS_PER_INVOKE {
+            return Err(ReaderError::InvalidValue)
+        }
+
+        Ok(InvokeContractPayload {
+            contract,
+            parameters,
+            max_gas
+        })
+    }
+
+    fn size(&self) -> usize {
+        self.contract.size() + 1 + self.parameters.len() * 8 + self.max_gas.size()
     }
 }
 
@@ -332,13 +543,46 @@ impl Serializer for TransactionType {
                 payload.write(writer);
             }
             TransactionType::Transfers(txs) => {
-                writer.write_u8(1);
+                // Transfers with locks have their own id
+                let locked = txs.iter().any(|tx| tx.lock.is_some());
+                writer.write_u8(if locked { 7 } else { 1 });
                 // max 255 txs per transaction
                 let len: u8 = txs.len() as u8;
                 writer.write_u8(len);
                 for tx in txs {
                     tx.write(writer);
+                    if locked {
+                        tx.lock.write(writer);
+                    }
                 }
+            },
+            TransactionType::CreateAsset(payload) => {
+                writer.write_u8(2);
+                payload.write(writer);
+            },
+            TransactionType::MintAsset(payload) => {
+                writer.write_u8(3);
+                payload.write(writer);
+            },
+            TransactionType::TransferAssetOwnership(payload) => {
+                writer.write_u8(4);
+                payload.write(writer);
+            },
+            TransactionType::MultiSig(payload) => {
+                writer.write_u8(5);
+                payload.write(writer);
+            },
+            TransactionType::Unlock(payload) => {
+                writer.write_u8(6);
+                payload.write(writer);
+            },
+            TransactionType::DeployContract(payload) => {
+                writer.write_u8(8);
+                payload.write(writer);
+            },
+            TransactionType::InvokeContract(payload) => {
+                writer.write_u8(9);
+                payload.write(writer);
             }
         };
     }
@@ -349,7 +593,7 @@ impl Serializer for TransactionType {
                 let payload = BurnPayload::read(reader)?;
                 TransactionType::Burn(payload)
             },
-            1 => {
+            id @ (1 | 7) => {
                 let txs_count = reader.read_u8()?;
                 if txs_count == 0 || txs_count > MAX_TRANSFER_COUNT as u8 {
                     return Err(ReaderError::InvalidSize)
@@ -357,10 +601,47 @@ impl Serializer for TransactionType {
 
                 let mut txs = Vec::with_capacity(txs_count as usize);
                 for _ in 0..txs_count {
-                    txs.push(TransferPayload::read(reader)?);
+                    let mut tx = TransferPayload::read(reader)?;
+                    if id == 7 {
+                        tx.lock = Option::read(reader)?;
+                    }
+                    txs.push(tx);
+                }
+
+                // Transfers with locks must have at least one lock
+                if id == 7 && txs.iter().all(|tx| tx.lock.is_none()) {
+                    return Err(ReaderError::InvalidValue)
                 }
                 TransactionType::Transfers(txs)
             },
+            2 => {
+                let payload = AssetCreationPayload::read(reader)?;
+                TransactionType::CreateAsset(payload)
+            },
+            3 => {
+                let payload = MintAssetPayload::read(reader)?;
+                TransactionType::MintAsset(payload)
+            },
+            4 => {
+                let payload = AssetOwnershipPayload::read(reader)?;
+                TransactionType::TransferAssetOwnership(payload)
+            },
+            5 => {
+                let payload = MultiSigPayload::read(reader)?;
+                TransactionType::MultiSig(payload)
+            },
+            6 => {
+                let payload = UnlockPayload::read(reader)?;
+                TransactionType::Unlock(payload)
+            },
+            8 => {
+                let payload = DeployContractPayload::read(reader)?;
+                TransactionType::DeployContract(payload)
+            },
+            9 => {
+                let payload = InvokeContractPayload::read(reader)?;
+                TransactionType::InvokeContract(payload)
+            },
             _ => {
                 return Err(ReaderError::InvalidValue)
             }
@@ -375,10 +656,35 @@ impl Serializer for TransactionType {
             TransactionType::Transfers(txs) => {
                 // 1 byte for variant, 1 byte for count of transfers
                 let mut size = 1 + 1;
+                let locked = txs.iter().any(|tx| tx.lock.is_some());
                 for tx in txs {
                     size += tx.size();
+                    if locked {
+                        size += tx.lock.size();
+                    }
                 }
                 size
+            },
+            TransactionType::CreateAsset(payload) => {
+                1 + payload.size()
+            },
+            TransactionType::MintAsset(payload) => {
+                1 + payload.size()
+            },
+            TransactionType::TransferAssetOwnership(payload) => {
+                1 + payload.size()
+            },
+            TransactionType::MultiSig(payload) => {
+                1 + payload.size()
+            },
+            TransactionType::Unlock(payload) => {
+                1 + payload.size()
+            },
+            TransactionType::DeployContract(payload) => {
+                1 + payload.size()
+            },
+            TransactionType::InvokeContract(payload) => {
+                1 + payload.size()
             }
         }
     }
@@ -399,19 +705,26 @@ impl Serializer for Transaction {
 
         self.range_proof.write(writer);
         self.reference.write(writer);
+        if self.version >= TX_VERSION_MULTISIG {
+            self.multisig.write(writer);
+        }
         self.signature.write(writer);
     }
 
     fn read(reader: &mut Reader) -> Result<Transaction, ReaderError> {
         let version = reader.read_u8()?;
-        // At this moment we only support version 0, so we check it here directly
-        if version != 0 {
-            debug!("Expected version 0 got version {version}");
-            return Err(ReaderError::InvalidValue)
+        if version > TX_VERSION {
+            debug!("Expected version up to {TX_VERSION} got version {version}");
+            return Err(ReaderError::UnsupportedVersion(version))
         }
 
         let source = CompressedPublicKey::read(reader)?;
         let data = TransactionType::read(reader)?;
+        if version < data.get_min_tx_version() {
+            debug!("Transaction type requires version {} got version {version}", data.get_min_tx_version());
+            return Err(ReaderError::InvalidValue)
+        }
+
         let fee = reader.read_u64()?;
         let nonce = reader.read_u64()?;
 
@@ -427,6 +740,11 @@ impl Serializer for Transaction {
 
         let range_proof = RangeProof::read(reader)?;
         let reference = Reference::read(reader)?;
+        let multisig = if version >= TX_VERSION_MULTISIG {
+            Option::read(reader)?
+        } else {
+            None
+        };
         let signature = Signature::read(reader)?;
 
         Ok(Transaction {
@@ -438,6 +756,7 @@ impl Serializer for Transaction {
             source_commitments,
             range_proof,
             reference,
+            multisig,
             signature,
         })
     }
@@ -455,6 +774,8 @@ impl Serializer for Transaction {
         + self.range_proof.size()
         + self.reference.size()
         + self.signature.size()
+        // Multisig is only serialized from version 1
+        + if self.version >= TX_VERSION_MULTISIG { self.multisig.size() } else { 0 }
     }
 }
 
@@ -465,23 +786,3 @@ impl AsRef<Transaction> for Transaction {
         self
     }
 }
-
-impl Serializer for Reference {
-    fn write(&self, writer: &mut Writer) {
-        self.hash.write(writer);
-        writer.write_u64(&self.topoheight);
-    }
-
-    fn read(reader: &mut Reader) -> Result<Reference, ReaderError> {
-        let hash = Hash::read(reader)?;
-        let topoheight = reader.read_u64()?;
-        Ok(Reference {
-            hash,
-            topoheight
-        })
-    }
-
-    fn size(&self) -> usize {
-        self.hash.size() + self.topoheight.size()
-    }
-}
\ No newline at end of file
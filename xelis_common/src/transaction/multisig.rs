@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+use crate::{
+    crypto::Signature,
+    serializer::{Reader, ReaderError, Serializer, Writer}
+};
+
+// Maximum participants allowed in a multisig
+pub const MAX_MULTISIG_PARTICIPANTS: usize = 255;
+
+// Signature of a multisig participant
+// The id is the index of the participant in the multisig configuration
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Serializer)]
+pub struct SignatureId {
+    pub id: u8,
+    pub signature: Signature
+}
+
+// All the participants signatures of a transaction
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct MultiSig {
+    signatures: Vec<SignatureId>
+}
+
+impl MultiSig {
+    pub fn new() -> Self {
+        Self {
+            signatures: Vec::new()
+        }
+    }
+
+    // Add a signature, returns false if the participant already signed
+    pub fn add_signature(&mut self, signature: SignatureId) -> bool {
+        if self.signatures.iter().any(|s| s.id == signature.id) {
+            return false
+        }
+
+        self.signatures.push(signature);
+        true
+    }
+
+    // Get all the signatures
+    pub fn get_signatures(&self) -> &Vec<SignatureId> {
+        &self.signatures
+    }
+
+    // Count of signatures
+    pub fn len(&self) -> usize {
+        self.signatures.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.signatures.is_empty()
+    }
+}
+
+impl Serializer for MultiSig {
+    fn write(&self, writer: &mut Writer) {
+        writer.write_u8(self.signatures.len() as u8);
+        for signature in &self.signatures {
+            signature.write(writer);
+        }
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        let len = reader.read_u8()?;
+        let mut multisig = MultiSig::new();
+        for _ in 0..len {
+            // A participant can't sign twice
+            if !multisig.add_signature(SignatureId::read(reader)?) {
+                return Err(ReaderError::InvalidValue)
+            }
+        }
+
+        Ok(multisig)
+    }
+
+    fn size(&self) -> usize {
+        1 + self.signatures.iter().map(|s| s.size()).sum::<usize>()
+    }
+}
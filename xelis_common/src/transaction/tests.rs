@@ -2,45 +2,68 @@ use std::collections::HashMap;
 use async_trait::async_trait;
 use crate::{
     account::CiphertextCache,
+    asset::AssetState,
     api::{DataElement, DataValue},
+    contract::{Opcode, Program, VmError},
     config::{COIN_VALUE, XELIS_ASSET},
     crypto::{
         elgamal::{Ciphertext, PedersenOpening},
         Address,
         Hash,
+        Hashable,
         KeyPair,
         PublicKey
     },
     serializer::Serializer,
-    transaction::{TransactionType, MAX_TRANSFER_COUNT}
+    transaction::{TransactionType, MAX_ASSET_NAME_LENGTH, MAX_TRANSFER_COUNT}
 };
 use super::{
     extra_data::{
         derive_shared_key_from_opening,
         PlaintextData
     },
+    lock::{get_lock_id, LockedTransfer, Preimage, TransferLock},
     builder::{
         AccountState,
         FeeBuilder,
         FeeHelper,
+        GenerationError,
+        MultiSigBuilder,
         TransactionBuilder,
         TransactionTypeBuilder,
-        TransferBuilder
+        TransferBuilder,
+        UnsignedTransaction
     },
-    verify::BlockchainVerificationState,
+    verify::{BlockchainVerificationState, VerificationError},
+    AssetCreationPayload,
     BurnPayload,
+    DeployContractPayload,
+    InvokeContractPayload,
+    MintAssetPayload,
+    MultiSigPayload,
     Reference,
     Role,
-    Transaction
+    Transaction,
+    UnlockPayload,
+    TX_VERSION_CONTRACTS,
+    TX_VERSION_MULTISIG
 };
 
+#[derive(Clone)]
 struct AccountChainState {
     balances: HashMap<Hash, Ciphertext>,
     nonce: u64,
 }
 
+#[derive(Clone)]
 struct ChainState {
     accounts: HashMap<PublicKey, AccountChainState>,
+    assets: HashMap<Hash, AssetState>,
+    multisig: HashMap<PublicKey, MultiSigPayload>,
+    locks: HashMap<Hash, LockedTransfer>,
+    contracts: HashMap<Hash, Program>,
+    contract_data: HashMap<(Hash, u64), u64>,
+    topoheight: u64,
 }
 
 #[derive(Clone)]
@@ -85,6 +108,10 @@ struct AccountStateImpl {
 }
 
 fn create_tx_for(account: Account, destination: Address, amount: u64, extra_data: Option<DataElement>) -> Transaction {
+    create_tx_with_extra_data_format(account, destination, amount, extra_data, false)
+}
+
+fn create_tx_with_extra_data_format(account: Account, destination: Address, amount: u64, extra_data: Option<DataElement>, authenticated_extra_data: bool) -> Transaction {
     let mut state = AccountStateImpl {
         balances: account.balances,
         nonce: account.nonce,
@@ -99,10 +126,12 @@ fn create_tx_for(account: Account, destination: Address, amount: u64, extra_data
         destination,
         asset: XELIS_ASSET,
         extra_data,
+        lock: None,
     }]);
 
 
-    let builder = TransactionBuilder::new(0, account.keypair.get_public_key().compress(), data, FeeBuilder::Multiplier(1f64));
+    let builder = TransactionBuilder::new(0, account.keypair.get_public_key().compress(), data, FeeBuilder::Multiplier(1f64))
+        .with_authenticated_extra_data(authenticated_extra_data);
     let estimated_size = builder.estimate_size();
     let tx = builder.build(&mut state, &account.keypair).unwrap();
     assert!(estimated_size == tx.size(), "expected {} bytes got {} bytes", tx.size(), estimated_size);
@@ -135,6 +164,7 @@ fn test_encrypt_decrypt_two_parties() {
     let bob = Account::new();
 
     let payload = DataElement::Value(DataValue::String("Hello, World!".to_string()));
+    // V2 is written by default
     let tx = create_tx_for(alice.clone(), bob.address(), 50, Some(payload.clone()));
     let TransactionType::Transfers(transfers) = tx.get_data() else {
         unreachable!()
@@ -161,6 +191,28 @@ fn test_encrypt_decrypt_two_parties() {
     }
 }
 
+#[test]
+fn test_encrypt_decrypt_two_parties_authenticated() {
+    let mut alice = Account::new();
+    alice.balances.insert(XELIS_ASSET, Balance {
+        balance: 100 * COIN_VALUE,
+        ciphertext: CiphertextCache::Decompressed(alice.keypair.get_public_key().encrypt(100 * COIN_VALUE)),
+    });
+
+    let bob = Account::new();
+
+    let payload = DataElement::Value(DataValue::String("Hello, World!".to_string()));
+    let tx = create_tx_with_extra_data_format(alice.clone(), bob.address(), 50, Some(payload.clone()), true);
+    let TransactionType::Transfers(transfers) = tx.get_data() else {
+        unreachable!()
+    };
+
+    let cipher = transfers[0].extra_data.clone().unwrap();
+    assert_eq!(cipher.decrypt_v3(&alice.keypair.get_private_key(), Role::Sender).unwrap(), payload);
+    assert_eq!(cipher.decrypt_v3(&bob.keypair.get_private_key(), Role::Receiver).unwrap(), payload);
+    assert!(cipher.decrypt_v3(&bob.keypair.get_private_key(), Role::Sender).is_err());
+}
+
 
 #[tokio::test]
 async fn test_tx_verify() {
@@ -175,6 +227,12 @@ async fn test_tx_verify() {
 
     let mut state = ChainState {
         accounts: HashMap::new(),
+        assets: HashMap::new(),
+        multisig: HashMap::new(),
+        locks: HashMap::new(),
+        contracts: HashMap::new(),
+        contract_data: HashMap::new(),
+        topoheight: 0,
     };
 
     // Create the chain state
@@ -236,6 +294,12 @@ async fn test_burn_tx_verify() {
 
     let mut state = ChainState {
         accounts: HashMap::new(),
+        assets: HashMap::new(),
+        multisig: HashMap::new(),
+        locks: HashMap::new(),
+        contracts: HashMap::new(),
+        contract_data: HashMap::new(),
+        topoheight: 0,
     };
 
     // Create the chain state
@@ -264,6 +328,505 @@ async fn test_burn_tx_verify() {
     tx.verify(&mut state).await.unwrap();
 }
 
+#[tokio::test]
+async fn test_create_asset_tx_verify() {
+    let mut alice = Account::new();
+    alice.set_balance(XELIS_ASSET, 100 * COIN_VALUE);
+
+    let mut state = AccountStateImpl {
+        balances: alice.balances.clone(),
+        nonce: alice.nonce,
+        reference: Reference {
+            topoheight: 0,
+            hash: Hash::zero(),
+        },
+    };
+
+    // Name is too long
+    let data = TransactionTypeBuilder::CreateAsset(AssetCreationPayload {
+        name: "a".repeat(MAX_ASSET_NAME_LENGTH + 1),
+        decimals: 8,
+        max_supply: 1000 * COIN_VALUE,
+    });
+    let builder = TransactionBuilder::new(TX_VERSION_MULTISIG, alice.keypair.get_public_key().compress(), data, FeeBuilder::Multiplier(1f64));
+    assert!(builder.build(&mut state, &alice.keypair).is_err());
+
+    let data = TransactionTypeBuilder::CreateAsset(AssetCreationPayload {
+        name: "Test Asset".to_owned(),
+        decimals: 8,
+        max_supply: 1000 * COIN_VALUE,
+    });
+    // Assets are not supported by the first transaction version
+    let builder = TransactionBuilder::new(0, alice.keypair.get_public_key().compress(), data.clone(), FeeBuilder::Multiplier(1f64));
+    assert!(matches!(builder.build(&mut state, &alice.keypair), Err(GenerationError::TypeVersion(TX_VERSION_MULTISIG))));
+
+    let builder = TransactionBuilder::new(TX_VERSION_MULTISIG, alice.keypair.get_public_key().compress(), data, FeeBuilder::Multiplier(1f64));
+    let estimated_size = builder.estimate_size();
+    let tx = builder.build(&mut state, &alice.keypair).unwrap();
+    assert!(estimated_size == tx.size());
+
+    let tx = Transaction::from_bytes(&tx.to_bytes()).unwrap();
+    let TransactionType::CreateAsset(payload) = tx.get_data() else {
+        unreachable!()
+    };
+    assert_eq!(payload.name, "Test Asset");
+
+    let mut state = ChainState {
+        accounts: HashMap::new(),
+        assets: HashMap::new(),
+        multisig: HashMap::new(),
+        locks: HashMap::new(),
+        contracts: HashMap::new(),
+        contract_data: HashMap::new(),
+        topoheight: 0,
+    };
+
+    let mut balances = HashMap::new();
+    for (asset, balance) in &alice.balances {
+        balances.insert(asset.clone(), balance.ciphertext.clone().take_ciphertext().unwrap());
+    }
+    state.accounts.insert(alice.keypair.get_public_key().compress(), AccountChainState {
+        balances,
+        nonce: alice.nonce,
+    });
+
+    tx.verify(&mut state).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_mint_asset_tx_verify() {
+    let mut alice = Account::new();
+    let mut bob = Account::new();
+    alice.set_balance(XELIS_ASSET, 100 * COIN_VALUE);
+    bob.set_balance(XELIS_ASSET, 100 * COIN_VALUE);
+
+    let mut state = ChainState {
+        accounts: HashMap::new(),
+        assets: HashMap::new(),
+        multisig: HashMap::new(),
+        locks: HashMap::new(),
+        contracts: HashMap::new(),
+        contract_data: HashMap::new(),
+        topoheight: 0,
+    };
+
+    for account in [&alice, &bob] {
+        let mut balances = HashMap::new();
+        for (asset, balance) in &account.balances {
+            balances.insert(asset.clone(), balance.ciphertext.clone().take_ciphertext().unwrap());
+        }
+        state.accounts.insert(account.keypair.get_public_key().compress(), AccountChainState {
+            balances,
+            nonce: account.nonce,
+        });
+    }
+
+    let build = |account: &Account, account_state: &mut AccountStateImpl, data: TransactionTypeBuilder| {
+        TransactionBuilder::new(TX_VERSION_MULTISIG, account.keypair.get_public_key().compress(), data, FeeBuilder::Multiplier(1f64))
+            .build(account_state, &account.keypair)
+            .unwrap()
+    };
+
+    let mut alice_state = AccountStateImpl {
+        balances: alice.balances.clone(),
+        nonce: alice.nonce,
+        reference: Reference {
+            topoheight: 0,
+            hash: Hash::zero(),
+        },
+    };
+
+    // Alice creates the asset and is its owner
+    let tx = build(&alice, &mut alice_state, TransactionTypeBuilder::CreateAsset(AssetCreationPayload {
+        name: "Test Asset".to_owned(),
+        decimals: 8,
+        max_supply: 1000,
+    }));
+    tx.verify(&mut state).await.unwrap();
+
+    let asset = tx.hash();
+    assert_eq!(state.assets.get(&asset).map(|v| v.get_supply()), Some(0));
+
+    // Minted units are credited to the owner
+    state.accounts.get_mut(&alice.keypair.get_public_key().compress()).unwrap()
+        .balances.insert(asset.clone(), alice.keypair.get_public_key().encrypt(0u64));
+
+    let tx = build(&alice, &mut alice_state, TransactionTypeBuilder::MintAsset(MintAssetPayload {
+        asset: asset.clone(),
+        amount: 600,
+    }));
+    tx.verify(&mut state).await.unwrap();
+    assert_eq!(state.assets.get(&asset).map(|v| v.get_supply()), Some(600));
+
+    // Can't mint above the maximum supply
+    let tx = build(&alice, &mut alice_state, TransactionTypeBuilder::MintAsset(MintAssetPayload {
+        asset: asset.clone(),
+        amount: 401,
+    }));
+    assert!(matches!(tx.verify(&mut state).await, Err(VerificationError::MaxSupplyExceeded(401, _))));
+
+    // Only the owner can mint
+    let mut bob_state = AccountStateImpl {
+        balances: bob.balances.clone(),
+        nonce: bob.nonce,
+        reference: Reference {
+            topoheight: 0,
+            hash: Hash::zero(),
+        },
+    };
+    let tx = build(&bob, &mut bob_state, TransactionTypeBuilder::MintAsset(MintAssetPayload {
+        asset: asset.clone(),
+        amount: 1,
+    }));
+    assert!(matches!(tx.verify(&mut state).await, Err(VerificationError::NotAssetOwner(_))));
+    assert_eq!(state.assets.get(&asset).map(|v| v.get_supply()), Some(600));
+}
+
+#[tokio::test]
+async fn test_multisig_tx_verify() {
+    let mut alice = Account::new();
+    let bob = Account::new();
+    let charlie = Account::new();
+    alice.set_balance(XELIS_ASSET, 100 * COIN_VALUE);
+
+    let mut state = ChainState {
+        accounts: HashMap::new(),
+        assets: HashMap::new(),
+        multisig: HashMap::new(),
+        locks: HashMap::new(),
+        contracts: HashMap::new(),
+        contract_data: HashMap::new(),
+        topoheight: 0,
+    };
+
+    {
+        let mut balances = HashMap::new();
+        for (asset, balance) in &alice.balances {
+            balances.insert(asset.clone(), balance.ciphertext.clone().take_ciphertext().unwrap());
+        }
+        state.accounts.insert(alice.keypair.get_public_key().compress(), AccountChainState {
+            balances,
+            nonce: alice.nonce,
+        });
+    }
+
+    let mut alice_state = AccountStateImpl {
+        balances: alice.balances.clone(),
+        nonce: alice.nonce,
+        reference: Reference {
+            topoheight: 0,
+            hash: Hash::zero(),
+        },
+    };
+
+    // Alice requires 2 signatures out of bob and charlie
+    let data = TransactionTypeBuilder::MultiSig(MultiSigBuilder {
+        threshold: 2,
+        participants: vec![bob.address(), charlie.address()],
+    });
+    let builder = TransactionBuilder::new(TX_VERSION_MULTISIG, alice.keypair.get_public_key().compress(), data, FeeBuilder::Multiplier(1f64));
+    let estimated_size = builder.estimate_size();
+    let tx = builder.build(&mut alice_state, &alice.keypair).unwrap();
+    assert_eq!(estimated_size, tx.size());
+    tx.verify(&mut state).await.unwrap();
+    assert!(state.multisig.contains_key(&alice.keypair.get_public_key().compress()));
+
+    // Every following transaction is built from the same state
+    // Failing ones are verified against a copy of the chain state
+    let balances = alice_state.balances.clone();
+    let nonce = alice_state.nonce;
+    let build_unsigned = || {
+        let mut alice_state = AccountStateImpl {
+            balances: balances.clone(),
+            nonce,
+            reference: Reference {
+                topoheight: 0,
+                hash: Hash::zero(),
+            },
+        };
+
+        let data = TransactionTypeBuilder::Burn(BurnPayload {
+            asset: XELIS_ASSET,
+            amount: 50,
+        });
+        let builder = TransactionBuilder::new(TX_VERSION_MULTISIG, alice.keypair.get_public_key().compress(), data, FeeBuilder::Multiplier(1f64))
+            .with_multisig_threshold(2);
+        let estimated_size = builder.estimate_size();
+        (estimated_size, builder.build_unsigned(&mut alice_state, &alice.keypair).unwrap())
+    };
+
+    // Signatures of the participants are required
+    let (_, unsigned) = build_unsigned();
+    let tx = unsigned.finalize(&alice.keypair);
+    assert!(matches!(tx.verify(&mut state.clone()).await, Err(VerificationError::MultiSigNotFound)));
+
+    // Not enough signatures
+    let (_, mut unsigned) = build_unsigned();
+    unsigned.sign_multisig(&bob.keypair, 0).unwrap();
+    let tx = unsigned.finalize(&alice.keypair);
+    assert!(matches!(tx.verify(&mut state.clone()).await, Err(VerificationError::MultiSigThreshold(2, 1))));
+
+    // Signatures don't match the participants ids
+    let (_, mut unsigned) = build_unsigned();
+    unsigned.sign_multisig(&bob.keypair, 1).unwrap();
+    unsigned.sign_multisig(&charlie.keypair, 0).unwrap();
+    let tx = unsigned.finalize(&alice.keypair);
+    assert!(matches!(tx.verify(&mut state.clone()).await, Err(VerificationError::InvalidMultiSigSignature(_))));
+
+    // Both participants signed
+    let (estimated_size, mut unsigned) = build_unsigned();
+    unsigned.sign_multisig(&bob.keypair, 0).unwrap();
+    unsigned.sign_multisig(&charlie.keypair, 1).unwrap();
+    // A participant can't sign twice
+    assert!(unsigned.sign_multisig(&bob.keypair, 0).is_err());
+
+    // Unsigned transaction is shared between the participants as bytes
+    let unsigned = UnsignedTransaction::from_bytes(&unsigned.to_bytes()).unwrap();
+    let tx = unsigned.finalize(&alice.keypair);
+    assert_eq!(estimated_size, tx.size());
+    tx.verify(&mut state).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_locked_transfer_tx_verify() {
+    let mut alice = Account::new();
+    let mut bob = Account::new();
+    alice.set_balance(XELIS_ASSET, 100 * COIN_VALUE);
+    bob.set_balance(XELIS_ASSET, 10 * COIN_VALUE);
+
+    let mut state = ChainState {
+        accounts: HashMap::new(),
+        assets: HashMap::new(),
+        multisig: HashMap::new(),
+        locks: HashMap::new(),
+        contracts: HashMap::new(),
+        contract_data: HashMap::new(),
+        topoheight: 0,
+    };
+
+    for account in [&alice, &bob] {
+        let mut balances = HashMap::new();
+        for (asset, balance) in &account.balances {
+            balances.insert(asset.clone(), balance.ciphertext.clone().take_ciphertext().unwrap());
+        }
+        state.accounts.insert(account.keypair.get_public_key().compress(), AccountChainState {
+            balances,
+            nonce: account.nonce,
+        });
+    }
+
+    let mut alice_state = AccountStateImpl {
+        balances: alice.balances.clone(),
+        nonce: alice.nonce,
+        reference: Reference {
+            topoheight: 0,
+            hash: Hash::zero(),
+        },
+    };
+
+    let preimage = Preimage::random();
+    let destination = bob.address();
+    let lock_transfer = |alice_state: &mut AccountStateImpl, lock: TransferLock| {
+        let data = TransactionTypeBuilder::Transfers(vec![TransferBuilder {
+            amount: 50,
+            destination: destination.clone(),
+            asset: XELIS_ASSET,
+            extra_data: None,
+            lock: Some(lock.clone()),
+        }]);
+        let builder = TransactionBuilder::new(TX_VERSION_MULTISIG, alice.keypair.get_public_key().compress(), data, FeeBuilder::Multiplier(1f64));
+        let estimated_size = builder.estimate_size();
+        let tx = builder.build(alice_state, &alice.keypair).unwrap();
+        assert_eq!(estimated_size, tx.size());
+
+        // Lock is kept when shared over the network
+        let tx = Transaction::from_bytes(&tx.to_bytes()).unwrap();
+        let TransactionType::Transfers(transfers) = tx.get_data() else {
+            unreachable!()
+        };
+        assert_eq!(transfers[0].get_lock().as_ref(), Some(&lock));
+        tx
+    };
+
+    // Bob can claim it by revealing the preimage before topoheight 10
+    let tx = lock_transfer(&mut alice_state, TransferLock {
+        topoheight: 10,
+        hash: Some(preimage.get_hash()),
+    });
+    let bob_balance = state.accounts[&bob.keypair.get_public_key().compress()].balances[&XELIS_ASSET].clone();
+    tx.verify(&mut state).await.unwrap();
+
+    // Funds are locked and not credited to bob
+    let id = get_lock_id(&tx.hash(), 0);
+    assert!(state.locks.contains_key(&id));
+    assert_eq!(state.accounts[&bob.keypair.get_public_key().compress()].balances[&XELIS_ASSET], bob_balance);
+
+    // Every unlock is built from the same state
+    // Failing ones are verified against a copy of the chain state
+    let unlock = |account: &Account, balances: &HashMap<Hash, Balance>, nonce: u64, id: &Hash, preimage: Option<Preimage>| {
+        let mut account_state = AccountStateImpl {
+            balances: balances.clone(),
+            nonce,
+            reference: Reference {
+                topoheight: 0,
+                hash: Hash::zero(),
+            },
+        };
+
+        let data = TransactionTypeBuilder::Unlock(UnlockPayload {
+            id: id.clone(),
+            asset: XELIS_ASSET,
+            preimage,
+        });
+        let builder = TransactionBuilder::new(TX_VERSION_MULTISIG, account.keypair.get_public_key().compress(), data, FeeBuilder::Multiplier(1f64));
+        let estimated_size = builder.estimate_size();
+        let tx = builder.build(&mut account_state, &account.keypair).unwrap();
+        assert_eq!(estimated_size, tx.size());
+        tx
+    };
+
+    // Preimage is required
+    let tx = unlock(&bob, &bob.balances, bob.nonce, &id, None);
+    assert!(matches!(tx.verify(&mut state.clone()).await, Err(VerificationError::InvalidUnlock(_))));
+
+    // Invalid preimage
+    let tx = unlock(&bob, &bob.balances, bob.nonce, &id, Some(Preimage::random()));
+    assert!(matches!(tx.verify(&mut state.clone()).await, Err(VerificationError::InvalidUnlock(_))));
+
+    // Alice can't get refunded before the lock expired
+    let tx = unlock(&alice, &alice_state.balances, alice_state.nonce, &id, None);
+    assert!(matches!(tx.verify(&mut state.clone()).await, Err(VerificationError::InvalidUnlock(_))));
+
+    let tx = unlock(&bob, &bob.balances, bob.nonce, &id, Some(preimage.clone()));
+    tx.verify(&mut state).await.unwrap();
+    assert!(!state.locks.contains_key(&id));
+
+    // Can't be unlocked twice
+    bob.nonce += 1;
+    let tx = unlock(&bob, &bob.balances, bob.nonce, &id, Some(preimage.clone()));
+    assert!(matches!(tx.verify(&mut state.clone()).await, Err(VerificationError::LockNotFound(_))));
+
+    // Alice gets refunded once the lock expired
+    let tx = lock_transfer(&mut alice_state, TransferLock {
+        topoheight: 10,
+        hash: Some(preimage.get_hash()),
+    });
+    tx.verify(&mut state).await.unwrap();
+    let id = get_lock_id(&tx.hash(), 0);
+
+    state.topoheight = 10;
+    let tx = unlock(&bob, &bob.balances, bob.nonce, &id, Some(preimage.clone()));
+    assert!(matches!(tx.verify(&mut state.clone()).await, Err(VerificationError::InvalidUnlock(_))));
+
+    let tx = unlock(&alice, &alice_state.balances, alice_state.nonce, &id, None);
+    tx.verify(&mut state).await.unwrap();
+    assert!(state.locks.is_empty());
+    alice_state.nonce += 1;
+
+    // A lock already reached is rejected
+    let tx = lock_transfer(&mut alice_state, TransferLock {
+        topoheight: 10,
+        hash: None,
+    });
+    assert!(matches!(tx.verify(&mut state.clone()).await, Err(VerificationError::InvalidLock)));
+}
+
+#[tokio::test]
+async fn test_contract_tx_verify() {
+    let mut alice = Account::new();
+    alice.set_balance(XELIS_ASSET, 100 * COIN_VALUE);
+
+    let mut state = ChainState {
+        accounts: HashMap::new(),
+        assets: HashMap::new(),
+        multisig: HashMap::new(),
+        locks: HashMap::new(),
+        contracts: HashMap::new(),
+        contract_data: HashMap::new(),
+        topoheight: 0,
+    };
+
+    {
+        let mut balances = HashMap::new();
+        for (asset, balance) in &alice.balances {
+            balances.insert(asset.clone(), balance.ciphertext.clone().take_ciphertext().unwrap());
+        }
+        state.accounts.insert(alice.keypair.get_public_key().compress(), AccountChainState {
+            balances,
+            nonce: alice.nonce,
+        });
+    }
+
+    let mut alice_state = AccountStateImpl {
+        balances: alice.balances.clone(),
+        nonce: alice.nonce,
+        reference: Reference {
+            topoheight: 0,
+            hash: Hash::zero(),
+        },
+    };
+
+    // Build a transaction without updating the state of Alice
+    // Failing ones are verified against a copy of the chain state
+    let build = |alice_state: &AccountStateImpl, version: u8, data: TransactionTypeBuilder| {
+        let mut alice_state = AccountStateImpl {
+            balances: alice_state.balances.clone(),
+            nonce: alice_state.nonce,
+            reference: alice_state.reference.clone(),
+        };
+        TransactionBuilder::new(version, alice.keypair.get_public_key().compress(), data, FeeBuilder::Multiplier(1f64))
+            .build(&mut alice_state, &alice.keypair)
+    };
+
+    // Counter adding the first parameter to the value at key 0
+    let program = Program::new(vec![
+        Opcode::Push(0),
+        Opcode::Dup(0),
+        Opcode::Load,
+        Opcode::Param(0),
+        Opcode::Add,
+        Opcode::Store
+    ]).unwrap();
+    let data = TransactionTypeBuilder::DeployContract(DeployContractPayload { program: program.clone() });
+
+    // Contracts require their transaction version
+    assert!(matches!(build(&alice_state, 0, data.clone()), Err(GenerationError::ContractVersion)));
+
+    let builder = TransactionBuilder::new(TX_VERSION_CONTRACTS, alice.keypair.get_public_key().compress(), data, FeeBuilder::Multiplier(1f64));
+    let estimated_size = builder.estimate_size();
+    let tx = builder.build(&mut alice_state, &alice.keypair).unwrap();
+    assert_eq!(estimated_size, tx.size());
+    let tx = Transaction::from_bytes(&tx.to_bytes()).unwrap();
+    tx.verify(&mut state).await.unwrap();
+
+    let contract = tx.hash();
+    assert_eq!(state.contracts.get(&contract), Some(&program));
+
+    let invoke = |contract: &Hash, parameters: Vec<u64>| TransactionTypeBuilder::InvokeContract(InvokeContractPayload {
+        contract: contract.clone(),
+        parameters,
+        max_gas: 1000,
+    });
+
+    // Contract doesn't exist
+    let tx = build(&alice_state, TX_VERSION_CONTRACTS, invoke(&Hash::zero(), vec![1])).unwrap();
+    assert!(matches!(tx.verify(&mut state.clone()).await, Err(VerificationError::ContractNotFound(_))));
+
+    // Execution fails without parameter
+    let tx = build(&alice_state, TX_VERSION_CONTRACTS, invoke(&contract, Vec::new())).unwrap();
+    assert!(matches!(tx.verify(&mut state.clone()).await, Err(VerificationError::ContractExecution(VmError::InvalidParameter(0)))));
+
+    for value in [5, 7] {
+        let builder = TransactionBuilder::new(TX_VERSION_CONTRACTS, alice.keypair.get_public_key().compress(), invoke(&contract, vec![value]), FeeBuilder::Multiplier(1f64));
+        let estimated_size = builder.estimate_size();
+        let tx = builder.build(&mut alice_state, &alice.keypair).unwrap();
+        assert_eq!(estimated_size, tx.size());
+        tx.verify(&mut state).await.unwrap();
+    }
+
+    assert_eq!(state.contract_data.get(&(contract, 0)), Some(&12));
+}
+
 #[tokio::test]
 async fn test_max_transfers() {
     let mut alice = Account::new();
@@ -280,6 +843,7 @@ async fn test_max_transfers() {
                 destination: bob.address(),
                 asset: XELIS_ASSET,
                 extra_data: None,
+                lock: None,
             });
         }
 
@@ -305,6 +869,12 @@ async fn test_max_transfers() {
     // Create the chain state
     let mut state = ChainState {
         accounts: HashMap::new(),
+        assets: HashMap::new(),
+        multisig: HashMap::new(),
+        locks: HashMap::new(),
+        contracts: HashMap::new(),
+        contract_data: HashMap::new(),
+        topoheight: 0,
     };
 
     // Alice
@@ -390,6 +960,109 @@ impl<'a> BlockchainVerificationState<'a, ()> for ChainState {
     ) -> Result<(), ()> {
         self.accounts.get_mut(account).map(|account| account.nonce = new_nonce).ok_or(())
     }
+
+    /// Get the state of an asset created by a transaction
+    async fn get_asset_state<'b>(
+        &'b mut self,
+        asset: &Hash
+    ) -> Result<Option<&'b mut AssetState>, ()> {
+        Ok(self.assets.get_mut(asset))
+    }
+
+    /// Set the initial state of an asset created by a transaction
+    async fn set_asset_state(
+        &mut self,
+        asset: Hash,
+        state: AssetState
+    ) -> Result<(), ()> {
+        self.assets.insert(asset, state);
+        Ok(())
+    }
+
+    /// Get the multisig configuration of an account
+    async fn get_multisig_state<'b>(
+        &'b mut self,
+        account: &'a PublicKey
+    ) -> Result<Option<&'b MultiSigPayload>, ()> {
+        Ok(self.multisig.get(account).filter(|config| !config.is_delete()))
+    }
+
+    /// Set the multisig configuration of an account
+    async fn set_multisig_state(
+        &mut self,
+        account: &'a PublicKey,
+        config: &MultiSigPayload
+    ) -> Result<(), ()> {
+        self.multisig.insert(account.clone(), config.clone());
+        Ok(())
+    }
+
+    /// Topoheight at which the transactions are executed
+    fn get_verification_topoheight(&self) -> u64 {
+        self.topoheight
+    }
+
+    /// Get a locked transfer
+    async fn get_locked_transfer(
+        &mut self,
+        id: &Hash
+    ) -> Result<Option<LockedTransfer>, ()> {
+        Ok(self.locks.get(id).cloned())
+    }
+
+    /// Set a locked transfer, None once it is unlocked
+    async fn set_locked_transfer(
+        &mut self,
+        id: Hash,
+        locked: Option<LockedTransfer>
+    ) -> Result<(), ()> {
+        match locked {
+            Some(locked) => self.locks.insert(id, locked),
+            None => self.locks.remove(&id)
+        };
+        Ok(())
+    }
+
+    /// Get the program of a deployed contract
+    async fn get_contract(
+        &mut self,
+        contract: &Hash
+    ) -> Result<Option<Program>, ()> {
+        Ok(self.contracts.get(contract).cloned())
+    }
+
+    /// Set the program of a contract deployed by a transaction
+    async fn set_contract(
+        &mut self,
+        contract: Hash,
+        program: Program
+    ) -> Result<(), ()> {
+        self.contracts.insert(contract, program);
+        Ok(())
+    }
+
+    /// Get a value from the storage of a contract
+    async fn get_contract_data(
+        &mut self,
+        contract: &Hash,
+        key: u64
+    ) -> Result<Option<u64>, ()> {
+        Ok(self.contract_data.get(&(contract.clone(), key)).copied())
+    }
+
+    /// Set a value in the storage of a contract, None to delete it
+    async fn set_contract_data(
+        &mut self,
+        contract: &Hash,
+        key: u64,
+        value: Option<u64>
+    ) -> Result<(), ()> {
+        match value {
+            Some(value) => self.contract_data.insert((contract.clone(), key), value),
+            None => self.contract_data.remove(&(contract.clone(), key))
+        };
+        Ok(())
+    }
 }
 
 impl FeeHelper for AccountStateImpl {
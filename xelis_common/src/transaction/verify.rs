@@ -2,8 +2,8 @@ use bulletproofs::RangeProof;
 use curve25519_dalek::{ristretto::CompressedRistretto, traits::Identity, RistrettoPoint, Scalar};
 use log::{debug, trace};
 use merlin::Transcript;
-use crate::{config::XELIS_ASSET, crypto::{elgamal::{Ciphertext, CompressedPublicKey, DecompressionError, DecryptHandle, PedersenCommitment}, proofs::{BatchCollector, ProofVerificationError, BP_GENS, BULLET_PROOF_SIZE, PC_GENS}, Hash, ProtocolTranscript, SIGNATURE_SIZE}, serializer::Serializer, transaction::{EXTRA_DATA_LIMIT_SIZE, MAX_TRANSFER_COUNT}};
-use super::{Reference, Role, Transaction, TransactionType, TransferPayload};
+use crate::{asset::AssetState, config::{ASSET_REGISTRATION_FEE, XELIS_ASSET}, contract::{Program, Vm, VmError, VmState}, crypto::{elgamal::{Ciphertext, CompressedPublicKey, DecompressionError, DecryptHandle, PedersenCommitment, SignatureBatchCollector}, proofs::{BatchCollector, ProofVerificationError, BP_GENS, BULLET_PROOF_SIZE, PC_GENS}, Hash, Hashable, ProtocolTranscript, SIGNATURE_SIZE}, serializer::Serializer, transaction::{EXTRA_DATA_LIMIT_SIZE, MAX_TRANSFER_COUNT}};
+use super::{lock::{get_lock_id, LockedTransfer}, InvokeContractPayload, MultiSigPayload, Reference, Role, Transaction, TransactionType, TransferPayload, UnlockPayload};
 use thiserror::Error;
 use std::iter;
 use async_trait::async_trait;
@@ -59,6 +59,81 @@ pub trait BlockchainVerificationState<'a, E> {
         account: &'a CompressedPublicKey,
         new_nonce: u64
     ) -> Result<(), E>;
+
+    /// Get the state of an asset created by a transaction
+    /// None if the asset doesn't exist or can't be minted
+    async fn get_asset_state<'b>(
+        &'b mut self,
+        asset: &Hash
+    ) -> Result<Option<&'b mut AssetState>, E>;
+
+    /// Set the initial state of an asset created by a transaction
+    async fn set_asset_state(
+        &mut self,
+        asset: Hash,
+        state: AssetState
+    ) -> Result<(), E>;
+
+    /// Get the multisig configuration of an account
+    /// None if the account has no multisig or if it was deleted
+    async fn get_multisig_state<'b>(
+        &'b mut self,
+        account: &'a CompressedPublicKey
+    ) -> Result<Option<&'b MultiSigPayload>, E>;
+
+    /// Set the multisig configuration of an account
+    async fn set_multisig_state(
+        &mut self,
+        account: &'a CompressedPublicKey,
+        config: &MultiSigPayload
+    ) -> Result<(), E>;
+
+    /// Topoheight at which the transactions are executed
+    /// Used to verify the transfer locks
+    fn get_verification_topoheight(&self) -> u64;
+
+    /// Get a locked transfer
+    /// None if it doesn't exist or if it was already unlocked
+    async fn get_locked_transfer(
+        &mut self,
+        id: &Hash
+    ) -> Result<Option<LockedTransfer>, E>;
+
+    /// Set a locked transfer, None once it is unlocked
+    async fn set_locked_transfer(
+        &mut self,
+        id: Hash,
+        locked: Option<LockedTransfer>
+    ) -> Result<(), E>;
+
+    /// Get the program of a deployed contract
+    /// None if the contract doesn't exist
+    async fn get_contract(
+        &mut self,
+        contract: &Hash
+    ) -> Result<Option<Program>, E>;
+
+    /// Set the program of a contract deployed by a transaction
+    async fn set_contract(
+        &mut self,
+        contract: Hash,
+        program: Program
+    ) -> Result<(), E>;
+
+    /// Get a value from the storage of a contract
+    async fn get_contract_data(
+        &mut self,
+        contract: &Hash,
+        key: u64
+    ) -> Result<Option<u64>, E>;
+
+    /// Set a value in the storage of a contract, None to delete it
+    async fn set_contract_data(
+        &mut self,
+        contract: &Hash,
+        key: u64,
+        value: Option<u64>
+    ) -> Result<(), E>;
 }
 
 #[derive(Error, Debug, Clone)]
@@ -71,6 +146,30 @@ pub enum VerificationError<T> {
     SenderIsReceiver,
     #[error("Invalid signature")]
     InvalidSignature,
+    #[error("Account is not the owner of asset {}", _0)]
+    NotAssetOwner(Hash),
+    #[error("Minting {} of asset {} exceeds its maximum supply", _0, _1)]
+    MaxSupplyExceeded(u64, Hash),
+    #[error("Account has no multisig configured")]
+    MultiSigNotConfigured,
+    #[error("Multisig signatures are required")]
+    MultiSigNotFound,
+    #[error("Invalid multisig signatures count, expected {} got {}", _0, _1)]
+    MultiSigThreshold(u8, usize),
+    #[error("Invalid multisig signature for participant {}", _0)]
+    InvalidMultiSigSignature(u8),
+    #[error("Invalid multisig participants")]
+    MultiSigParticipants,
+    #[error("Transfer lock must be above the current topoheight")]
+    InvalidLock,
+    #[error("Locked transfer {} not found", _0)]
+    LockNotFound(Hash),
+    #[error("Locked transfer {} can't be unlocked", _0)]
+    InvalidUnlock(Hash),
+    #[error("Contract {} not found", _0)]
+    ContractNotFound(Hash),
+    #[error("Contract execution error: {0}")]
+    ContractExecution(#[from] VmError),
     #[error("Proof verification error: {0}")]
     Proof(#[from] ProofVerificationError),
 }
@@ -100,6 +199,30 @@ impl DecompressedTransferCt {
     }
 }
 
+// Commitment eq proofs of a transaction to verify before applying it
+// Built by `prepare_partial_verify`, it doesn't borrow the state
+pub struct PartialVerification<'a> {
+    // None once taken to be verified on another thread
+    sigma_batch_collector: Option<BatchCollector>,
+    // Sender balance updated, output ciphertext, asset of each commitment
+    commitments_changes: Vec<(Ciphertext, Ciphertext, &'a Hash)>
+}
+
+impl<'a> PartialVerification<'a> {
+    pub fn verify(&self) -> Result<(), ProofVerificationError> {
+        self.sigma_batch_collector
+            .as_ref()
+            .ok_or(ProofVerificationError::GenericProof)?
+            .verify()
+            .map_err(|_| ProofVerificationError::GenericProof)
+    }
+
+    // Take the proofs to verify them without borrowing the transaction
+    pub fn take_proofs(&mut self) -> Option<BatchCollector> {
+        self.sigma_batch_collector.take()
+    }
+}
+
 impl Transaction {
     /// Get the new output ciphertext
     // This is used to substract the amount from the sender's balance
@@ -127,7 +250,25 @@ impl Transaction {
                 if *asset == payload.asset {
                     output += Scalar::from(payload.amount)
                 }
-            }
+            },
+            TransactionType::CreateAsset(_) => {
+                // Registration fee is burned in the native asset
+                if *asset == XELIS_ASSET {
+                    output += Scalar::from(ASSET_REGISTRATION_FEE)
+                }
+            },
+            TransactionType::InvokeContract(payload) => {
+                // Maximum gas is burned in the native asset
+                if *asset == XELIS_ASSET {
+                    output += Scalar::from(payload.max_gas)
+                }
+            },
+            // Only fees are paid
+            TransactionType::MintAsset(_)
+            | TransactionType::TransferAssetOwnership(_)
+            | TransactionType::MultiSig(_)
+            | TransactionType::Unlock(_)
+            | TransactionType::DeployContract(_) => {}
         }
 
         Ok(output)
@@ -181,15 +322,187 @@ impl Transaction {
                 .iter()
                 .all(|transfer| has_commitment_for_asset(&transfer.asset)),
             TransactionType::Burn(payload) => has_commitment_for_asset(&payload.asset),
+            // Only XELIS_ASSET is used, which is already checked above
+            TransactionType::CreateAsset(_)
+            | TransactionType::MintAsset(_)
+            | TransactionType::TransferAssetOwnership(_)
+            | TransactionType::MultiSig(_)
+            | TransactionType::Unlock(_)
+            | TransactionType::DeployContract(_)
+            | TransactionType::InvokeContract(_) => true,
+        }
+    }
+
+    // Verify the multisig signatures against the configuration of the source account
+    async fn verify_multisig<'a, E, B: BlockchainVerificationState<'a, E>>(
+        &'a self,
+        state: &mut B
+    ) -> Result<(), VerificationError<E>> {
+        let config = state.get_multisig_state(&self.source).await
+            .map_err(VerificationError::State)?;
+
+        match (config, &self.multisig) {
+            (None, None) => {},
+            (None, Some(_)) => return Err(VerificationError::MultiSigNotConfigured),
+            (Some(_), None) => return Err(VerificationError::MultiSigNotFound),
+            (Some(config), Some(multisig)) => {
+                if multisig.len() != config.threshold as usize {
+                    return Err(VerificationError::MultiSigThreshold(config.threshold, multisig.len()));
+                }
+
+                let hash = self.get_multisig_hash();
+                for signature in multisig.get_signatures() {
+                    let participant = config.participants.get_index(signature.id as usize)
+                        .ok_or(VerificationError::InvalidMultiSigSignature(signature.id))?
+                        .decompress()
+                        .map_err(|err| VerificationError::Proof(err.into()))?;
+
+                    if !signature.signature.verify(hash.as_bytes(), &participant) {
+                        debug!("multisig signature of participant {} is invalid", signature.id);
+                        return Err(VerificationError::InvalidMultiSigSignature(signature.id));
+                    }
+                }
+            }
+        };
+
+        Ok(())
+    }
+
+    // Move the funds of a locked transfer to the account unlocking it
+    // The unlock must have been verified before
+    async fn apply_unlock<'a, E, B: BlockchainVerificationState<'a, E>>(
+        &'a self,
+        payload: &'a UnlockPayload,
+        state: &mut B
+    ) -> Result<(), E> {
+        if let Some(locked) = state.get_locked_transfer(&payload.id).await? {
+            let role = if locked.destination == self.source { Role::Receiver } else { Role::Sender };
+            // Ciphertext was verified when the transfer was locked
+            let ct = locked.get_ciphertext(role)
+                .decompress()
+                .expect("ill-formed ciphertext");
+
+            state.set_locked_transfer(payload.id.clone(), None).await?;
+            let balance = state.get_receiver_balance(&self.source, &payload.asset).await?;
+            *balance += ct;
         }
+
+        Ok(())
+    }
+
+    // Execute the contract and write its storage changes
+    // Nothing is written if the execution fails
+    async fn execute_contract<'a, E, B: BlockchainVerificationState<'a, E>>(
+        payload: &InvokeContractPayload,
+        state: &mut B
+    ) -> Result<(), VerificationError<E>> {
+        let program = state.get_contract(&payload.contract).await
+            .map_err(VerificationError::State)?
+            .ok_or_else(|| VerificationError::ContractNotFound(payload.contract.clone()))?;
+
+        let mut vm = Vm::new(&program, &payload.parameters, state.get_verification_topoheight(), payload.max_gas);
+        while let VmState::Load(key) = vm.run()? {
+            let value = state.get_contract_data(&payload.contract, key).await
+                .map_err(VerificationError::State)?;
+            vm.set_loaded(key, value);
+        }
+
+        trace!("contract {} executed using {} gas", payload.contract, vm.get_gas_used());
+        for (key, value) in vm.into_changes() {
+            state.set_contract_data(&payload.contract, key, value).await
+                .map_err(VerificationError::State)?;
+        }
+
+        Ok(())
+    }
+
+    // Verify the state changes requested by the transaction (assets, multisig, locks, contracts) and apply them
+    // This must be done before applying any balance change as it may fail
+    async fn verify_and_apply_state_changes<'a, E, B: BlockchainVerificationState<'a, E>>(
+        &'a self,
+        state: &mut B
+    ) -> Result<(), VerificationError<E>> {
+        match &self.data {
+            TransactionType::CreateAsset(payload) => {
+                // Creator is the first owner
+                let asset_state = AssetState::new(self.source.clone(), 0, payload.max_supply);
+                state.set_asset_state(self.hash(), asset_state).await
+                    .map_err(VerificationError::State)?;
+            },
+            TransactionType::MintAsset(payload) => {
+                let asset_state = state.get_asset_state(&payload.asset).await
+                    .map_err(VerificationError::State)?
+                    .filter(|asset_state| *asset_state.get_owner() == self.source)
+                    .ok_or_else(|| VerificationError::NotAssetOwner(payload.asset.clone()))?;
+
+                let supply = asset_state.get_supply()
+                    .checked_add(payload.amount)
+                    .filter(|supply| *supply <= asset_state.get_max_supply())
+                    .ok_or_else(|| VerificationError::MaxSupplyExceeded(payload.amount, payload.asset.clone()))?;
+
+                asset_state.set_supply(supply);
+            },
+            TransactionType::TransferAssetOwnership(payload) => {
+                if payload.new_owner == self.source {
+                    return Err(VerificationError::SenderIsReceiver);
+                }
+
+                let asset_state = state.get_asset_state(&payload.asset).await
+                    .map_err(VerificationError::State)?
+                    .filter(|asset_state| *asset_state.get_owner() == self.source)
+                    .ok_or_else(|| VerificationError::NotAssetOwner(payload.asset.clone()))?;
+
+                asset_state.set_owner(payload.new_owner.clone());
+            },
+            TransactionType::MultiSig(payload) => {
+                if payload.participants.contains(&self.source) {
+                    return Err(VerificationError::MultiSigParticipants);
+                }
+
+                // Without signatures, the account has no multisig to delete
+                if payload.is_delete() && self.multisig.is_none() {
+                    return Err(VerificationError::MultiSigNotConfigured);
+                }
+
+                state.set_multisig_state(&self.source, payload).await
+                    .map_err(VerificationError::State)?;
+            },
+            TransactionType::Unlock(payload) => {
+                let locked = state.get_locked_transfer(&payload.id).await
+                    .map_err(VerificationError::State)?
+                    .filter(|locked| locked.asset == payload.asset)
+                    .ok_or_else(|| VerificationError::LockNotFound(payload.id.clone()))?;
+
+                // Receiver claims it, sender can only get refunded
+                // Funds are moved once the proofs are verified
+                let topoheight = state.get_verification_topoheight();
+                let claim = locked.destination == self.source && locked.lock.can_claim(topoheight, payload.preimage.as_ref());
+                let refund = locked.source == self.source && payload.preimage.is_none() && locked.lock.can_refund(topoheight);
+                if !claim && !refund {
+                    return Err(VerificationError::InvalidUnlock(payload.id.clone()));
+                }
+            },
+            TransactionType::DeployContract(payload) => {
+                state.set_contract(self.hash(), payload.program.clone()).await
+                    .map_err(VerificationError::State)?;
+            },
+            TransactionType::InvokeContract(payload) => {
+                // A failed execution invalidates the transaction
+                Self::execute_contract(payload, state).await?;
+            },
+            TransactionType::Transfers(_) | TransactionType::Burn(_) => {}
+        };
+
+        Ok(())
     }
 
-    // internal, does not verify the range proof
+    // internal, does not verify the range proof nor the signature
     // returns (transcript, commitments for range proof)
     async fn pre_verify<'a, E, B: BlockchainVerificationState<'a, E>>(
         &'a self,
         state: &mut B,
         sigma_batch_collector: &mut BatchCollector,
+        signature_batch_collector: &mut SignatureBatchCollector,
     ) -> Result<(Transcript, Vec<(RistrettoPoint, CompressedRistretto)>), VerificationError<E>>
     {
         trace!("Pre-verifying transaction");
@@ -214,6 +527,10 @@ impl Transaction {
             return Err(VerificationError::Proof(ProofVerificationError::Format));
         }
 
+        // Multisig must be verified before any change to its configuration
+        self.verify_multisig(state).await?;
+        self.verify_and_apply_state_changes(state).await?;
+
         let transfers_decompressed = if let TransactionType::Transfers(transfers) = &self.data {
             if transfers.len() > MAX_TRANSFER_COUNT || transfers.is_empty() {
                 debug!("incorrect transfers size: {}", transfers.len());
@@ -231,6 +548,12 @@ impl Transaction {
                 if let Some(extra_data) = transfer.extra_data.as_ref() {
                     extra_data_size += extra_data.size();
                 }
+
+                // Lock must not be already reached
+                if transfer.lock.as_ref().is_some_and(|lock| lock.topoheight <= state.get_verification_topoheight()) {
+                    debug!("transfer lock is already reached");
+                    return Err(VerificationError::InvalidLock);
+                }
             }
 
             if extra_data_size > EXTRA_DATA_LIMIT_SIZE {
@@ -261,12 +584,9 @@ impl Transaction {
 
         let mut transcript = Self::prepare_transcript(self.version, &self.source, self.fee, self.nonce);
 
-        // 0. Verify Signature
+        // 0. Signature will be verified in batch by caller
         let bytes = self.to_bytes();
-        if !self.signature.verify(&bytes[..bytes.len() - SIGNATURE_SIZE], &owner) {
-            debug!("transaction signature is invalid");
-            return Err(VerificationError::InvalidSignature);
-        }
+        signature_batch_collector.add(&self.signature, &bytes[..bytes.len() - SIGNATURE_SIZE], &owner);
 
         // 1. Verify CommitmentEqProofs
         trace!("verifying commitments eq proofs");
@@ -314,23 +634,28 @@ impl Transaction {
         trace!("verifying transfers ciphertext validity proofs");
 
         if let TransactionType::Transfers(transfers) = &self.data {
-            for (transfer, decompressed) in transfers.iter().zip(&transfers_decompressed) {
+            for (i, (transfer, decompressed)) in transfers.iter().zip(&transfers_decompressed).enumerate() {
                 let receiver = transfer
                     .destination
                     .decompress()
                     .map_err(ProofVerificationError::from)?;
 
                 // Update receiver balance
-
-                let current_balance = state
-                    .get_receiver_balance(
-                        &transfer.destination,
-                        &transfer.asset
-                    ).await
-                    .map_err(VerificationError::State)?;
-
-                let receiver_ct = decompressed.get_ciphertext(Role::Receiver);
-                *current_balance += receiver_ct;
+                // Locked funds are kept aside until unlocked
+                if let Some(locked) = transfer.get_locked_transfer(&self.source) {
+                    state.set_locked_transfer(get_lock_id(&self.hash(), i as u8), Some(locked)).await
+                        .map_err(VerificationError::State)?;
+                } else {
+                    let current_balance = state
+                        .get_receiver_balance(
+                            &transfer.destination,
+                            &transfer.asset
+                        ).await
+                        .map_err(VerificationError::State)?;
+
+                    let receiver_ct = decompressed.get_ciphertext(Role::Receiver);
+                    *current_balance += receiver_ct;
+                }
 
                 // Validity proof
 
@@ -351,6 +676,21 @@ impl Transaction {
             }
         }
 
+        // Credit the minted units to the owner
+        if let TransactionType::MintAsset(payload) = &self.data {
+            let owner_balance = state
+                .get_receiver_balance(&self.source, &payload.asset).await
+                .map_err(VerificationError::State)?;
+
+            *owner_balance += payload.amount;
+        }
+
+        // Credit the unlocked funds
+        if let TransactionType::Unlock(payload) = &self.data {
+            self.apply_unlock(payload, state).await
+                .map_err(VerificationError::State)?;
+        }
+
         // Prepare the new source commitments
 
         let new_source_commitments = self
@@ -413,12 +753,19 @@ impl Transaction {
     ) -> Result<(), VerificationError<E>> {
         trace!("Verifying batch of {} transactions", txs.len());
         let mut sigma_batch_collector = BatchCollector::default();
+        let mut signature_batch_collector = SignatureBatchCollector::new();
         let mut prepared = Vec::with_capacity(txs.len());
         for tx in txs {
-            let (transcript, commitments) = tx.as_ref().pre_verify(state, &mut sigma_batch_collector).await?;
+            let (transcript, commitments) = tx.as_ref().pre_verify(state, &mut sigma_batch_collector, &mut signature_batch_collector).await?;
             prepared.push((transcript, commitments));
         }
 
+        // Signatures of all the transactions at once
+        if !signature_batch_collector.verify() {
+            debug!("a transaction signature in the batch is invalid");
+            return Err(VerificationError::InvalidSignature);
+        }
+
         sigma_batch_collector
             .verify()
             .map_err(|_| ProofVerificationError::GenericProof)?;
@@ -444,7 +791,13 @@ impl Transaction {
         state: &mut B,
     ) -> Result<(), VerificationError<E>> {
         let mut sigma_batch_collector = BatchCollector::default();
-        let (mut transcript, commitments) = self.pre_verify(state, &mut sigma_batch_collector).await?;
+        let mut signature_batch_collector = SignatureBatchCollector::new();
+        let (mut transcript, commitments) = self.pre_verify(state, &mut sigma_batch_collector, &mut signature_batch_collector).await?;
+
+        if !signature_batch_collector.verify() {
+            debug!("transaction signature is invalid");
+            return Err(VerificationError::InvalidSignature);
+        }
 
         trace!("Verifying sigma proofs");
         sigma_batch_collector
@@ -507,7 +860,12 @@ impl Transaction {
         }
 
         if let TransactionType::Transfers(transfers) = &self.data {
-            for transfer in transfers {
+            for (i, transfer) in transfers.iter().enumerate() {
+                if let Some(locked) = transfer.get_locked_transfer(&self.source) {
+                    state.set_locked_transfer(get_lock_id(&self.hash(), i as u8), Some(locked)).await?;
+                    continue;
+                }
+
                 // Update receiver balance
                 let current_bal = state
                     .get_receiver_balance(
@@ -523,6 +881,45 @@ impl Transaction {
                 *current_bal += receiver_ct;
             }
         }
+
+        // Apply the asset changes, they are assumed valid
+        match &self.data {
+            TransactionType::CreateAsset(payload) => {
+                let asset_state = AssetState::new(self.source.clone(), 0, payload.max_supply);
+                state.set_asset_state(self.hash(), asset_state).await?;
+            },
+            TransactionType::MintAsset(payload) => {
+                if let Some(asset_state) = state.get_asset_state(&payload.asset).await? {
+                    asset_state.set_supply(asset_state.get_supply() + payload.amount);
+                }
+
+                let owner_balance = state.get_receiver_balance(&self.source, &payload.asset).await?;
+                *owner_balance += payload.amount;
+            },
+            TransactionType::TransferAssetOwnership(payload) => {
+                if let Some(asset_state) = state.get_asset_state(&payload.asset).await? {
+                    asset_state.set_owner(payload.new_owner.clone());
+                }
+            },
+            TransactionType::MultiSig(payload) => {
+                state.set_multisig_state(&self.source, payload).await?;
+            },
+            TransactionType::Unlock(payload) => {
+                self.apply_unlock(payload, state).await?;
+            },
+            TransactionType::DeployContract(payload) => {
+                state.set_contract(self.hash(), payload.program.clone()).await?;
+            },
+            TransactionType::InvokeContract(payload) => {
+                match Self::execute_contract(payload, state).await {
+                    Ok(()) => {},
+                    Err(VerificationError::State(e)) => return Err(e),
+                    // Already verified, a failed execution has no effect
+                    Err(_) => debug!("execution of contract {} failed", payload.contract)
+                }
+            },
+            TransactionType::Transfers(_) | TransactionType::Burn(_) => {}
+        };
     
         Ok(())
     }
@@ -532,6 +929,17 @@ impl Transaction {
     /// Checks done are: commitment eq proofs only
     pub async fn apply_with_partial_verify<'a, E, B: BlockchainVerificationState<'a, E>>(&'a self, state: &mut B) -> Result<(), VerificationError<E>> {
         trace!("apply with partial verify");
+        let verification = self.prepare_partial_verify(state).await?;
+
+        trace!("Verifying sigma proofs");
+        verification.verify()?;
+
+        self.apply_partial_verified(state, verification).await
+    }
+
+    /// Prepare the commitment eq proofs checked by `apply_with_partial_verify` without verifying them
+    /// The state is only read, so the proofs of independent transactions can be verified in parallel
+    pub async fn prepare_partial_verify<'a, E, B: BlockchainVerificationState<'a, E>>(&'a self, state: &mut B) -> Result<PartialVerification<'a>, VerificationError<E>> {
         let mut sigma_batch_collector = BatchCollector::default();
 
         let transfers_decompressed = if let TransactionType::Transfers(transfers) = &self.data {
@@ -596,12 +1004,22 @@ impl Transaction {
             commitments_changes.push((source_verification_ciphertext, output, &commitment.asset));
         }
 
-        trace!("Verifying sigma proofs");
-        sigma_batch_collector
-            .verify()
-            .map_err(|_| ProofVerificationError::GenericProof)?;
+        Ok(PartialVerification {
+            sigma_batch_collector: Some(sigma_batch_collector),
+            commitments_changes
+        })
+    }
+
+    /// Apply a transaction prepared with `prepare_partial_verify`
+    /// Its proofs must have been verified before
+    pub async fn apply_partial_verified<'a, E, B: BlockchainVerificationState<'a, E>>(&'a self, state: &mut B, verification: PartialVerification<'a>) -> Result<(), VerificationError<E>> {
+        let commitments_changes = verification.commitments_changes;
+
+        // Proofs are correct, verify the multisig and apply the state changes
+        self.verify_multisig(state).await?;
+        self.verify_and_apply_state_changes(state).await?;
 
-        // Proofs are correct, apply
+        // Apply
         for (source_verification_ciphertext, output, asset) in commitments_changes {
             // Update sender final balance for asset
             let current_ciphertext = state
@@ -622,7 +1040,13 @@ impl Transaction {
 
         // Apply receiver balances
         if let TransactionType::Transfers(transfers) = &self.data {
-            for transfer in transfers {
+            for (i, transfer) in transfers.iter().enumerate() {
+                if let Some(locked) = transfer.get_locked_transfer(&self.source) {
+                    state.set_locked_transfer(get_lock_id(&self.hash(), i as u8), Some(locked)).await
+                        .map_err(VerificationError::State)?;
+                    continue;
+                }
+
                 // Update receiver balance
                 let current_bal = state
                     .get_receiver_balance(
@@ -640,6 +1064,21 @@ impl Transaction {
             }
         }
 
+        // Credit the minted units to the owner
+        if let TransactionType::MintAsset(payload) = &self.data {
+            let owner_balance = state
+                .get_receiver_balance(&self.source, &payload.asset).await
+                .map_err(VerificationError::State)?;
+
+            *owner_balance += payload.amount;
+        }
+
+        // Credit the unlocked funds
+        if let TransactionType::Unlock(payload) = &self.data {
+            self.apply_unlock(payload, state).await
+                .map_err(VerificationError::State)?;
+        }
+
         Ok(())
     }
 }
\ No newline at end of file
@@ -11,7 +11,8 @@ use crate::{
         COIN_DECIMALS,
         FEE_PER_ACCOUNT_CREATION,
         FEE_PER_KB,
-        FEE_PER_TRANSFER
+        FEE_PER_TRANSFER,
+        TX_REPLACEMENT_FEE_INCREASE_PERCENT
     },
     difficulty::Difficulty,
     varuint::VarUint
@@ -68,6 +69,14 @@ pub fn calculate_tx_fee(tx_size: usize, output_count: usize, new_addresses: usiz
     + new_addresses as u64 * FEE_PER_ACCOUNT_CREATION
 }
 
+// Returns the minimum fee for a TX to replace a TX with the same nonce in mempool
+// Fee must be increased by TX_REPLACEMENT_FEE_INCREASE_PERCENT and at least by FEE_PER_KB
+// so the network can't be spammed with replacements paying one more atomic unit
+pub fn get_replacement_min_fee(fee: u64) -> u64 {
+    let increase = (fee / 100).saturating_mul(TX_REPLACEMENT_FEE_INCREASE_PERCENT).max(FEE_PER_KB);
+    fee.saturating_add(increase)
+}
+
 const HASHRATE_FORMATS: [&str; 7] = ["H/s", "KH/s", "MH/s", "GH/s", "TH/s", "PH/s", "EH/s"];
 
 // Format a hashrate in human-readable format
@@ -187,4 +196,22 @@ mod tests {
         let value = from_xelis("100.123");
         assert_eq!(value, Some(100_123_00000));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_replacement_min_fee() {
+        // Low fees must be increased at least by the minimum fee
+        assert_eq!(get_replacement_min_fee(0), FEE_PER_KB);
+        assert_eq!(get_replacement_min_fee(FEE_PER_KB), 2 * FEE_PER_KB);
+
+        // Increase of 10% above the minimum fee
+        let fee = 100 * FEE_PER_KB;
+        let required = get_replacement_min_fee(fee);
+        assert_eq!(required, fee + 10 * FEE_PER_KB);
+
+        // Threshold between both rules
+        assert_eq!(get_replacement_min_fee(10 * FEE_PER_KB), 11 * FEE_PER_KB);
+        assert_eq!(get_replacement_min_fee(10 * FEE_PER_KB + 100), 11 * FEE_PER_KB + 110);
+
+        assert_eq!(get_replacement_min_fee(u64::MAX), u64::MAX);
+    }
+}
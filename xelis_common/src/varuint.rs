@@ -47,6 +47,11 @@ impl VarUint {
     pub const fn one() -> Self {
         Self(U256::one())
     }
+
+    // Addition returning None on overflow
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        self.0.checked_add(rhs.0).map(Self)
+    }
 }
 
 impl Serializer for VarUint {
@@ -124,21 +129,34 @@ impl From<VarUint> for U256 {
     }
 }
 
+// Conversions to smaller types are saturating
+// a cumulative difficulty can go above u64::MAX on a mature chain
 impl From<VarUint> for u128 {
     fn from(c: VarUint) -> u128 {
-        c.0.as_u128()
+        if c.0.bits() > 128 {
+            u128::MAX
+        } else {
+            c.0.low_u128()
+        }
     }
 }
 
 impl From<VarUint> for u64 {
     fn from(c: VarUint) -> u64 {
-        c.0.as_u64()
+        if c.0.bits() > 64 {
+            u64::MAX
+        } else {
+            c.0.low_u64()
+        }
     }
 }
 
+// Approximation using all the 256 bits
 impl From<VarUint> for f64 {
     fn from(c: VarUint) -> f64 {
-        c.0.as_u128() as f64
+        c.0.0.iter()
+            .rev()
+            .fold(0f64, |acc, limb| acc * 2f64.powi(64) + *limb as f64)
     }
 }
 
@@ -297,4 +315,18 @@ mod tests {
         assert_eq!(compact.as_ref(), compact2.as_ref());
         assert!(json.contains("123456"));
     }
+
+    #[test]
+    fn test_saturating_conversions() {
+        let value = VarUint::from_u64(u64::MAX) + VarUint::one();
+        assert_eq!(u64::from(value), u64::MAX);
+        assert_eq!(u128::from(value), u64::MAX as u128 + 1);
+        assert_eq!(u64::from(VarUint::from_u64(42)), 42);
+
+        let max = VarUint::new(U256::max_value());
+        assert_eq!(u128::from(max), u128::MAX);
+        assert_eq!(f64::from(max), 2f64.powi(256));
+        assert_eq!(f64::from(value), 2f64.powi(64));
+        assert!(max.checked_add(VarUint::one()).is_none());
+    }
 }
\ No newline at end of file
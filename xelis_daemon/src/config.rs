@@ -8,7 +8,7 @@ use xelis_common::{
     },
     difficulty::Difficulty,
     network::Network,
-    time::TimestampSeconds,
+    time::TimestampMillis,
 };
 
 // In case of potential forks, have a unique network id to not connect to others compatible chains
@@ -18,9 +18,73 @@ pub const NETWORK_ID: [u8; NETWORK_ID_SIZE] = [0x73, 0x6c, 0x69, 0x78, 0x65, 0x5
 // bind addresses
 pub const DEFAULT_P2P_BIND_ADDRESS: &str = "0.0.0.0:2125";
 pub const DEFAULT_RPC_BIND_ADDRESS: &str = "0.0.0.0:8080";
+// Name sent in the SNI extension of the P2P TLS connections
+pub const DEFAULT_P2P_TLS_SERVER_NAME: &str = "www.cloudflare.com";
+// Username of the admin RPC methods
+pub const DEFAULT_RPC_ADMIN_USERNAME: &str = "admin";
+// Deadline in seconds of each RPC request
+pub const DEFAULT_RPC_REQUEST_TIMEOUT: u64 = 30;
+// Default and minimum difficulty of the shares sent to the Stratum server
+pub const DEFAULT_STRATUM_SHARE_DIFFICULTY: u64 = 10_000;
+
+// GetWork variable difficulty
+// Initial share difficulty of a miner
+pub const GETWORK_VARDIFF_INITIAL_DIFFICULTY: Difficulty = Difficulty::from_u64(10_000);
+// Share difficulty can't go below it so low hashrate miners still submit shares
+pub const GETWORK_VARDIFF_MIN_DIFFICULTY: Difficulty = Difficulty::from_u64(1_000);
+// Expected time between two shares of a miner
+pub const GETWORK_VARDIFF_SHARE_TIME_MILLIS: u64 = 5 * MILLIS_PER_SECOND;
+// Minimum time between two adjustments of the share difficulty
+pub const GETWORK_VARDIFF_RETARGET_MILLIS: u64 = 30 * MILLIS_PER_SECOND;
+// Maximum factor applied to the share difficulty at each adjustment
+pub const GETWORK_VARDIFF_MAX_FACTOR: u64 = 4;
+// Interval between two getwork_miners_stats events sent to the websocket clients
+pub const GETWORK_MINERS_STATS_INTERVAL_MILLIS: u64 = 10 * MILLIS_PER_SECOND;
+
+// Webhooks
+// Maximum events waiting to be delivered to a webhook, new events are dropped above it
+pub const WEBHOOK_QUEUE_SIZE: usize = 1024;
+// Maximum attempts to deliver an event to a webhook
+pub const WEBHOOK_MAX_ATTEMPTS: u32 = 5;
+// Delay before retrying to deliver an event, doubled at each failed attempt
+pub const WEBHOOK_RETRY_BASE_DELAY_MILLIS: u64 = 500;
+// Timeout of a webhook request
+pub const WEBHOOK_REQUEST_TIMEOUT_MILLIS: u64 = 10 * MILLIS_PER_SECOND;
+
+// Default duration in seconds after which a tx is deleted from mempool
+pub const DEFAULT_MEMPOOL_TX_TTL: u64 = 3 * 60 * 60;
+// Interval in seconds between two cleanups of the mempool
+pub const MEMPOOL_CLEANUP_INTERVAL: u64 = 60;
+// Default count of topoheights after which a local tx still in mempool is broadcasted again
+pub const DEFAULT_TX_REBROADCAST_DELAY: u64 = 10;
+// Default duration in seconds during which a local tx is broadcasted again
+pub const DEFAULT_TX_REBROADCAST_TTL: u64 = 60 * 60;
+// Interval between two checks of the local txs to broadcast again
+pub const TX_REBROADCAST_CHECK_INTERVAL_MILLIS: u64 = BLOCK_TIME_MILLIS;
+// Maximum count of blocks waiting to be processed
+// Submitters wait once the queue is full
+pub const BLOCK_QUEUE_SIZE: usize = 64;
+// Maximum count of transaction conflicts recorded, the oldest ones are deleted
+pub const MAX_TX_CONFLICTS: usize = 1024;
+// Default count of events kept in the event journal
+pub const DEFAULT_EVENT_JOURNAL_SIZE: u64 = 10_000;
+
+// Optional cargo features compiled in the daemon, reported by the get_capabilities RPC method
+// Runtime modes are reported by the capabilities bitfield instead
+pub fn get_compiled_features() -> Vec<&'static str> {
+    let mut features = Vec::new();
+    if cfg!(feature = "e2e_tests") {
+        features.push("e2e_tests");
+    }
+    features
+}
 
 // Default cache size for storage DB
 pub const DEFAULT_CACHE_SIZE: usize = 1024;
+// Default interval in seconds between two storage maintenances
+pub const DEFAULT_STORAGE_MAINTENANCE_INTERVAL: u64 = 60 * 60;
+// Default count of the last versions of each balance kept by the balance pruning
+pub const DEFAULT_BALANCE_PRUNING_KEEP_VERSIONS: usize = 8;
 
 // Block rules
 // Millis per second, it is used to prevent having random 1000 values anywhere
@@ -39,12 +103,41 @@ pub const GENESIS_BLOCK_DIFFICULTY: Difficulty = Difficulty::from_u64(1);
 // 1024 * 1024 + (256 * 1024) bytes = 1.25 MB maximum size per block with txs
 pub const MAX_BLOCK_SIZE: usize = (1024 * 1024) + (256 * 1024);
 // 2 seconds maximum in future (prevent any attack on reducing difficulty but keep margin for unsynced devices)
-pub const TIMESTAMP_IN_FUTURE_LIMIT: TimestampSeconds = 2 * 1000;
+pub const TIMESTAMP_IN_FUTURE_LIMIT: TimestampMillis = 2 * 1000;
+// Count of past blocks used to compute the median timestamp
+// A block timestamp can't be lower than the median timestamp of its past blocks
+pub const TIMESTAMP_MEDIAN_BLOCKS: u64 = 11;
+// Maximum count of past blocks allowed to compute the median timestamp
+pub const MAX_TIMESTAMP_MEDIAN_BLOCKS: u64 = 64;
 
 // keep at least last N blocks until top topoheight when pruning the chain
 // WARNING: This must be at least 50 blocks for difficulty adjustement
 pub const PRUNE_SAFETY_LIMIT: u64 = STABLE_LIMIT * 10;
 
+// Show the reindex progress every N blocks replayed
+pub const REINDEX_PROGRESS_INTERVAL: u64 = 1000;
+// Directory name in the dir path where the chain is rebuilt during a reindex
+pub const REINDEX_DIR_NAME: &str = "reindex/";
+// Directory name in the dir path where the chain is replayed during a verification
+pub const VERIFY_CHAIN_DIR_NAME: &str = "verify-chain/";
+// Accounts compared at once during a chain verification
+pub const VERIFY_CHAIN_ACCOUNTS_BATCH: usize = 1000;
+// Directory name in the dir path where a snapshot is imported before replacing the chain
+pub const SNAPSHOT_IMPORT_DIR_NAME: &str = "snapshot-import/";
+
+// Chain statistics are aggregated per day (UTC)
+pub const STATS_DAY_DURATION_MILLIS: u64 = 24 * 60 * 60 * MILLIS_PER_SECOND;
+// Maximum days returned at once by the daily stats API
+pub const MAX_DAILY_STATS_DAYS: u64 = 366;
+
+// Nonce reservations for external signers
+// default duration of a reservation in seconds
+pub const NONCE_RESERVATION_DEFAULT_DURATION: u64 = 60;
+// maximum duration of a reservation in seconds
+pub const NONCE_RESERVATION_MAX_DURATION: u64 = 10 * 60;
+// maximum reservations at same time per account
+pub const NONCE_RESERVATION_MAX_PER_ACCOUNT: usize = 64;
+
 // BlockDAG rules
 pub const STABLE_LIMIT: u64 = 8; // in how many height we consider the block stable
 
@@ -109,6 +202,15 @@ pub const CHAIN_SYNC_DEFAULT_RESPONSE_BLOCKS: usize = 4096;
 pub const CHAIN_SYNC_RESPONSE_MAX_BLOCKS: usize = 16384;
 // send last 10 heights
 pub const CHAIN_SYNC_TOP_BLOCKS: usize = 10;
+// Blocks requested to the same peer in a row when syncing from several peers
+pub const CHAIN_SYNC_CHUNK_SIZE: usize = 32;
+// Maximum time in seconds for a peer to send us a whole chunk
+// After it, the chunk is re-assigned to another peer
+pub const CHAIN_SYNC_CHUNK_TIMEOUT_SECS: u64 = 60;
+// Chunks that can be downloaded ahead of the chunks applied per peer
+pub const CHAIN_SYNC_CHUNKS_AHEAD: usize = 2;
+// Maximum peers used at same time to sync the chain
+pub const CHAIN_SYNC_MAX_PEERS: usize = 8;
 
 // P2p rules
 // time between each ping
@@ -119,6 +221,11 @@ pub const P2P_PING_PEER_LIST_DELAY: u64 = 60 * 5;
 pub const P2P_PING_PEER_LIST_LIMIT: usize = 16;
 // default number of maximum peers
 pub const P2P_DEFAULT_MAX_PEERS: usize = 32;
+// P2P protocol versions supported, negotiated during the handshake
+// The highest version supported by both peers is used, and a peer is refused if there is none
+// Version 0 is used by nodes not sending their protocol versions in the handshake
+pub const P2P_MIN_PROTOCOL_VERSION: u16 = 0;
+pub const P2P_MAX_PROTOCOL_VERSION: u16 = 1;
 // time in seconds between each time we try to connect to a new peer
 pub const P2P_EXTEND_PEERLIST_DELAY: u64 = 60;
 // Peer wait on error accept new p2p connections in seconds
@@ -132,6 +239,49 @@ pub const P2P_HEARTBEAT_INTERVAL: u64 = P2P_PING_DELAY / 2;
 // Timeout in seconds
 // If we didn't receive any packet from a peer during this time, we disconnect it
 pub const P2P_PING_TIMEOUT: u64 = P2P_PING_DELAY * 6;
+// time in seconds between each peer exchange request
+pub const P2P_PEER_EXCHANGE_DELAY: u64 = 60 * 10;
+// time in seconds to answer a peer exchange request before another one can be sent
+pub const P2P_PEER_EXCHANGE_TIMEOUT: u64 = 60;
+// time in seconds between each check of the anchors connections
+pub const P2P_ANCHOR_CHECK_DELAY: u64 = 1;
+// delay before retrying to connect to a disconnected anchor
+// doubled at each failed attempt up to the max delay
+pub const P2P_ANCHOR_RETRY_BASE_DELAY_MILLIS: u64 = 5 * MILLIS_PER_SECOND;
+pub const P2P_ANCHOR_RETRY_MAX_DELAY_MILLIS: u64 = 10 * 60 * MILLIS_PER_SECOND;
+// time in seconds between each latency ping sent to a peer
+pub const P2P_LATENCY_PING_DELAY: u64 = 15;
+// Weight of the new RTT sample in the rolling latency (1/N)
+pub const P2P_LATENCY_SMOOTHING: u64 = 8;
+// Weight of the new deviation sample in the rolling jitter (1/N)
+pub const P2P_JITTER_SMOOTHING: u64 = 4;
+// maximum number of addresses to be sent in a peer exchange response
+pub const P2P_PEER_EXCHANGE_LIMIT: usize = 32;
+// a stored peer is shared only if we saw it during this time (in seconds)
+// Set to 1 day
+pub const P2P_PEER_EXCHANGE_MAX_AGE: u64 = 24 * 60 * 60;
+// maximum number of stored peers before we stop accepting addresses from peer exchange
+pub const P2P_MAX_STORED_PEERS: usize = 4096;
+// UPnP port mapping lease duration in seconds
+// it is renewed at half of its duration
+pub const P2P_UPNP_LEASE_DURATION: u32 = 60 * 60;
+// timeout in milliseconds for each request to the UPnP gateway
+pub const P2P_UPNP_TIMEOUT_MILLIS: u64 = 5_000;
+// time in milliseconds between each flush of the txs hashes to broadcast
+pub const P2P_TXS_BATCH_INTERVAL_MILLIS: u64 = 100;
+// maximum number of txs hashes sent in one propagation packet
+// the batch is flushed immediately when reached
+pub const P2P_TXS_BATCH_MAX_SIZE: usize = 256;
+// Default maximum blocks propagated per second by a peer
+pub const P2P_DEFAULT_MAX_BLOCKS_PER_SECOND: u64 = 10;
+// Default maximum txs hashes propagated per second by a peer
+pub const P2P_DEFAULT_MAX_TXS_PER_SECOND: u64 = 1000;
+// Default maximum bytes received per second from a peer
+// Set to 32 MiB
+pub const P2P_DEFAULT_MAX_BYTES_PER_SECOND: u64 = 32 * 1024 * 1024;
+// Seconds a peer can exceed the blocks/txs limits before being banned
+// Each second without exceeding them removes one
+pub const P2P_RATE_LIMIT_MAX_VIOLATIONS: u8 = 10;
 
 // Peer rules
 // number of seconds to reset the counter
@@ -164,6 +314,8 @@ pub const PEER_MAX_PACKET_SIZE: u32 = MAX_BLOCK_SIZE as u32 + 16;
 pub const PEER_TX_CACHE_SIZE: usize = 10240;
 // Peer Block cache size
 pub const PEER_BLOCK_CACHE_SIZE: usize = 1024;
+// Chain views kept per peer to track its chain over time
+pub const PEER_CHAIN_VIEW_HISTORY_SIZE: usize = 32;
 // Peer packet channel size
 pub const PEER_PACKET_CHANNEL_SIZE: usize = 1024;
 // Peer timeout for packet channel
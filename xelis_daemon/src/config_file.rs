@@ -0,0 +1,182 @@
+use std::{ffi::OsString, fmt::Write, fs};
+use anyhow::{anyhow, Context, Result};
+use clap::{ArgAction, Command};
+use toml_edit::{Document, Item, Value};
+
+// Options that can only be set from the command line
+const CLI_ONLY_OPTIONS: [&str; 4] = ["help", "version", "config_file", "generate_config"];
+
+// Convert the keys of a TOML configuration file into command line arguments
+// Each key is the name of an option with '_' instead of '-'
+// The returned arguments must be placed before the command line ones so these have priority
+pub fn load_args(command: &Command, path: &str) -> Result<Vec<OsString>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Error while reading config file {}", path))?;
+    to_args(command, &content)
+        .with_context(|| format!("Invalid config file {}", path))
+}
+
+fn to_args(command: &Command, content: &str) -> Result<Vec<OsString>> {
+    let document: Document = content.parse()?;
+    let mut args = Vec::new();
+    for (key, item) in document.iter() {
+        // Unknown keys are rejected to detect typos
+        let arg = command.get_arguments()
+            .find(|arg| arg.get_id().as_str() == key && arg.get_long().is_some())
+            .filter(|arg| !CLI_ONLY_OPTIONS.contains(&key))
+            .ok_or_else(|| anyhow!("unknown key {}", key))?;
+        let long = arg.get_long().expect("argument must have a long name");
+
+        let value = match item {
+            Item::Value(value) => value,
+            _ => return Err(anyhow!("{} must be a value", key))
+        };
+
+        if matches!(arg.get_action(), ArgAction::SetTrue) {
+            // a flag can only be enabled
+            if value.as_bool().ok_or_else(|| anyhow!("{} must be a boolean", key))? {
+                args.push(format!("--{}", long).into());
+            }
+            continue;
+        }
+
+        let values: Vec<&Value> = match value {
+            Value::Array(array) => array.iter().collect(),
+            value => vec![value]
+        };
+
+        for value in values {
+            let value = match value {
+                Value::String(v) => v.value().clone(),
+                Value::Integer(v) => v.value().to_string(),
+                Value::Float(v) => v.value().to_string(),
+                Value::Boolean(v) => v.value().to_string(),
+                _ => return Err(anyhow!("{} has an unsupported value type", key))
+            };
+            args.push(format!("--{}={}", long, value).into());
+        }
+    }
+
+    Ok(args)
+}
+
+// Generate a configuration file with all the options commented
+// and set to their default value
+pub fn generate_template(command: &Command) -> String {
+    let mut template = String::new();
+    let _ = writeln!(template, "# {} configuration file", command.get_name());
+    let _ = writeln!(template, "# Uncomment an option to set it, command line arguments have priority over this file");
+
+    for arg in command.get_arguments() {
+        let key = arg.get_id().as_str();
+        if arg.get_long().is_none() || arg.is_hide_set() || CLI_ONLY_OPTIONS.contains(&key) {
+            continue;
+        }
+
+        let _ = writeln!(template);
+        if let Some(help) = arg.get_long_help().or(arg.get_help()) {
+            for line in help.to_string().lines() {
+                let _ = writeln!(template, "# {}", line.trim_end());
+            }
+        }
+
+        let value = if matches!(arg.get_action(), ArgAction::SetTrue) {
+            "false".to_owned()
+        } else {
+            let defaults: Vec<String> = arg.get_default_values()
+                .iter()
+                .map(|v| format_value(&v.to_string_lossy()))
+                .collect();
+
+            match (arg.get_action(), defaults.len()) {
+                (ArgAction::Append, _) => format!("[{}]", defaults.join(", ")),
+                (_, 0) => "\"\"".to_owned(),
+                _ => defaults.join(", ")
+            }
+        };
+        let _ = writeln!(template, "# {} = {}", key, value);
+    }
+
+    template
+}
+
+// Numbers and booleans are written as is, others as strings
+fn format_value(value: &str) -> String {
+    if value.parse::<i64>().is_ok() || value.parse::<f64>().is_ok() || value.parse::<bool>().is_ok() {
+        value.to_owned()
+    } else {
+        format!("{:?}", value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::{CommandFactory, FromArgMatches, Parser};
+    use super::*;
+
+    #[derive(Parser)]
+    struct TestConfig {
+        /// Port to listen on
+        #[clap(long, default_value_t = 8080)]
+        port: u16,
+        #[clap(long)]
+        name: Option<String>,
+        #[clap(long)]
+        verbose: bool,
+        #[clap(long)]
+        peers: Vec<String>,
+        #[clap(long)]
+        config_file: Option<String>
+    }
+
+    fn parse(file_args: Vec<OsString>, cli_args: &[&str]) -> TestConfig {
+        let mut args: Vec<OsString> = vec!["test".into()];
+        args.extend(file_args);
+        args.extend(cli_args.iter().map(OsString::from));
+        let matches = TestConfig::command().args_override_self(true).get_matches_from(args);
+        TestConfig::from_arg_matches(&matches).unwrap()
+    }
+
+    #[test]
+    fn test_cli_has_priority() {
+        let command = TestConfig::command();
+        let args = to_args(&command, "port = 9000\nname = \"node\"\nverbose = true\npeers = [\"a\", \"b\"]").unwrap();
+        let config = parse(args.clone(), &[]);
+        assert_eq!(config.port, 9000);
+        assert_eq!(config.name.as_deref(), Some("node"));
+        assert!(config.verbose);
+        assert_eq!(config.peers, vec!["a", "b"]);
+
+        let config = parse(args, &["--port", "9001", "--name", "cli"]);
+        assert_eq!(config.port, 9001);
+        assert_eq!(config.name.as_deref(), Some("cli"));
+    }
+
+    #[test]
+    fn test_invalid_keys() {
+        let command = TestConfig::command();
+        assert!(to_args(&command, "unknown = 1").is_err());
+        assert!(to_args(&command, "config_file = \"other.toml\"").is_err());
+        assert!(to_args(&command, "verbose = 1").is_err());
+        assert!(to_args(&command, "[section]\nport = 1").is_err());
+    }
+
+    #[test]
+    fn test_template_is_valid() {
+        let command = TestConfig::command();
+        let template = generate_template(&command);
+        assert!(template.contains("# Port to listen on\n# port = 8080"));
+        assert!(!template.contains("config_file"));
+
+        // uncommenting the options with a default value must give a valid file
+        let content: String = template.lines()
+            .filter_map(|line| line.strip_prefix("# "))
+            .filter(|line| line.starts_with("port =") || line.starts_with("verbose =") || line.starts_with("peers ="))
+            .map(|line| format!("{}\n", line))
+            .collect();
+        let config = parse(to_args(&command, &content).unwrap(), &[]);
+        assert_eq!(config.port, 8080);
+        assert!(!config.verbose);
+        assert!(config.peers.is_empty());
+    }
+}
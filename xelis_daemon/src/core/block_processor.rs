@@ -0,0 +1,119 @@
+use std::time::{Duration, Instant};
+use log::trace;
+use tokio::sync::oneshot;
+use xelis_common::{
+    api::daemon::{BlockProcessingStats, ProcessedBlockStats},
+    block::Block,
+    crypto::Hash
+};
+use super::error::BlockchainError;
+
+// A block waiting in the queue of the block processing task
+pub struct BlockSubmission {
+    pub block: Block,
+    pub broadcast: bool,
+    pub mining: bool,
+    // when it was added in the queue
+    pub queued_at: Instant,
+    // result of its processing sent back to the submitter
+    pub response: oneshot::Sender<Result<(), BlockchainError>>
+}
+
+impl BlockSubmission {
+    pub fn new(block: Block, broadcast: bool, mining: bool) -> (Self, oneshot::Receiver<Result<(), BlockchainError>>) {
+        let (sender, receiver) = oneshot::channel();
+        let submission = Self {
+            block,
+            broadcast,
+            mining,
+            queued_at: Instant::now(),
+            response: sender
+        };
+        (submission, receiver)
+    }
+}
+
+// Metrics of the blocks handled by the block processing task
+pub struct BlockProcessingMetrics {
+    processed: u64,
+    rejected: u64,
+    // total time spent by the blocks in the queue
+    total_queue_time: Duration,
+    // total time spent to validate and commit the blocks
+    total_processing_time: Duration,
+    last_block: Option<ProcessedBlockStats>
+}
+
+impl BlockProcessingMetrics {
+    pub fn new() -> Self {
+        Self {
+            processed: 0,
+            rejected: 0,
+            total_queue_time: Duration::ZERO,
+            total_processing_time: Duration::ZERO,
+            last_block: None
+        }
+    }
+
+    // Record a block handled by the block processing task
+    pub fn record(&mut self, hash: Hash, accepted: bool, queue_time: Duration, validation_time: Duration, commit_time: Duration) {
+        trace!("block {} accepted: {}, queue: {:?}, validation: {:?}, commit: {:?}", hash, accepted, queue_time, validation_time, commit_time);
+        if accepted {
+            self.processed += 1;
+        } else {
+            self.rejected += 1;
+        }
+
+        self.total_queue_time += queue_time;
+        self.total_processing_time += validation_time + commit_time;
+        self.last_block = Some(ProcessedBlockStats {
+            hash,
+            accepted,
+            queue_time: queue_time.as_millis() as u64,
+            validation_time: validation_time.as_millis() as u64,
+            commit_time: commit_time.as_millis() as u64
+        });
+    }
+
+    pub fn to_stats(&self, queue_size: usize, queue_capacity: usize) -> BlockProcessingStats {
+        let count = (self.processed + self.rejected).max(1) as u128;
+        BlockProcessingStats {
+            queue_size,
+            queue_capacity,
+            processed: self.processed,
+            rejected: self.rejected,
+            average_queue_time: (self.total_queue_time.as_millis() / count) as u64,
+            average_processing_time: (self.total_processing_time.as_millis() / count) as u64,
+            last_block: self.last_block.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics_averages() {
+        let mut metrics = BlockProcessingMetrics::new();
+        let stats = metrics.to_stats(0, 8);
+        assert_eq!(stats.processed, 0);
+        assert_eq!(stats.average_processing_time, 0);
+        assert!(stats.last_block.is_none());
+
+        metrics.record(Hash::zero(), true, Duration::from_millis(10), Duration::from_millis(20), Duration::from_millis(30));
+        metrics.record(Hash::max(), false, Duration::from_millis(30), Duration::from_millis(20), Duration::ZERO);
+        let stats = metrics.to_stats(1, 8);
+        assert_eq!(stats.queue_size, 1);
+        assert_eq!(stats.processed, 1);
+        assert_eq!(stats.rejected, 1);
+        assert_eq!(stats.average_queue_time, 20);
+        assert_eq!(stats.average_processing_time, 35);
+
+        let last = stats.last_block.unwrap();
+        assert_eq!(last.hash, Hash::max());
+        assert!(!last.accepted);
+        assert_eq!(last.validation_time, 20);
+        assert_eq!(last.commit_time, 0);
+    }
+}
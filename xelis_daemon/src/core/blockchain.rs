@@ -5,23 +5,33 @@ use serde_json::{Value, json};
 use xelis_common::{
     api::{
         daemon::{
+            AdminStorageMaintenanceResult,
+            AdminValidateSupplyResult,
+            LocalTransactionEntry,
             BlockOrderedEvent,
+            BlockProcessingStats,
             BlockOrphanedEvent,
             BlockType,
+            NewAssetEvent,
             NotifyEvent,
             StableHeightChangedEvent,
+            SupplyDivergence,
+            TransactionConflict,
             TransactionExecutedEvent,
             TransactionResponse
         },
         RPCTransaction
     },
+    account::{VersionedBalance, VersionedNonce},
     asset::AssetData,
     block::{
         Block,
         BlockHeader,
         EXTRA_NONCE_SIZE
     },
+    capabilities::Capabilities,
     config::{
+        ASSET_REGISTRATION_FEE,
         COIN_DECIMALS,
         MAXIMUM_SUPPLY,
         MAX_TRANSACTION_SIZE,
@@ -29,6 +39,7 @@ use xelis_common::{
         XELIS_ASSET
     },
     crypto::{
+        Address,
         Hash,
         Hashable,
         PublicKey,
@@ -44,36 +55,45 @@ use xelis_common::{
         TimestampMillis
     },
     transaction::{verify::BlockchainVerificationState, Transaction, TransactionType},
-    utils::{calculate_tx_fee, format_xelis, spawn_task},
+    utils::{calculate_tx_fee, format_xelis, from_xelis, get_replacement_min_fee, spawn_task},
     varuint::VarUint
 };
 use crate::{
     config::{
-        get_genesis_block_hash, get_hex_genesis_block, get_minimum_difficulty,
-        BLOCK_TIME_MILLIS, CHAIN_SYNC_RESPONSE_MAX_BLOCKS, CHAIN_SYNC_RESPONSE_MIN_BLOCKS,
-        DEFAULT_CACHE_SIZE, DEFAULT_P2P_BIND_ADDRESS, DEFAULT_RPC_BIND_ADDRESS, DEV_FEES,
-        DEV_PUBLIC_KEY, EMISSION_SPEED_FACTOR, GENESIS_BLOCK_DIFFICULTY, MAX_BLOCK_SIZE,
-        MILLIS_PER_SECOND, P2P_DEFAULT_MAX_PEERS, SIDE_BLOCK_REWARD_MAX_BLOCKS, PRUNE_SAFETY_LIMIT,
-        SIDE_BLOCK_REWARD_PERCENT, SIDE_BLOCK_REWARD_MIN_PERCENT, STABLE_LIMIT, TIMESTAMP_IN_FUTURE_LIMIT,
-        P2P_DEFAULT_CONCURRENCY_TASK_COUNT_LIMIT
+        get_genesis_block_hash, get_hex_genesis_block,
+        CHAIN_SYNC_RESPONSE_MAX_BLOCKS, CHAIN_SYNC_RESPONSE_MIN_BLOCKS,
+        DEFAULT_BALANCE_PRUNING_KEEP_VERSIONS, DEFAULT_CACHE_SIZE, DEFAULT_EVENT_JOURNAL_SIZE, DEFAULT_MEMPOOL_TX_TTL, DEFAULT_STORAGE_MAINTENANCE_INTERVAL, DEFAULT_P2P_BIND_ADDRESS, DEFAULT_P2P_TLS_SERVER_NAME, DEFAULT_RPC_ADMIN_USERNAME, DEFAULT_RPC_BIND_ADDRESS, DEFAULT_RPC_REQUEST_TIMEOUT, DEFAULT_STRATUM_SHARE_DIFFICULTY, DEFAULT_TX_REBROADCAST_DELAY, DEFAULT_TX_REBROADCAST_TTL, DEV_FEES,
+        DEV_PUBLIC_KEY, GENESIS_BLOCK_DIFFICULTY,
+        BLOCK_QUEUE_SIZE, MAX_TX_CONFLICTS, MEMPOOL_CLEANUP_INTERVAL, MILLIS_PER_SECOND, TX_REBROADCAST_CHECK_INTERVAL_MILLIS, NONCE_RESERVATION_MAX_DURATION, P2P_DEFAULT_MAX_PEERS, REINDEX_PROGRESS_INTERVAL, VERIFY_CHAIN_ACCOUNTS_BATCH, SIDE_BLOCK_REWARD_MAX_BLOCKS, PRUNE_SAFETY_LIMIT,
+        SIDE_BLOCK_REWARD_PERCENT, SIDE_BLOCK_REWARD_MIN_PERCENT,
+        P2P_DEFAULT_CONCURRENCY_TASK_COUNT_LIMIT, P2P_DEFAULT_MAX_BLOCKS_PER_SECOND,
+        P2P_DEFAULT_MAX_TXS_PER_SECOND, P2P_DEFAULT_MAX_BYTES_PER_SECOND
     },
     core::{
         blockdag,
+        chain_params::ChainParams,
         difficulty,
         error::BlockchainError,
+        hard_fork,
         mempool::Mempool,
         nonce_checker::NonceChecker,
-        simulator::Simulator,
-        storage::{DagOrderProvider, DifficultyProvider, Storage},
+        nonce_reservation::{NonceReservation, NonceReservations},
+        local_txs::LocalTransactions,
+        block_processor::{BlockProcessingMetrics, BlockSubmission},
+        committed_view::{CommittedView, StorageRewrites},
+        simulator::{Simulator, SimulatorConfig},
+        storage::{DagOrderProvider, DifficultyProvider, Storage, WorkScoreProvider},
         tx_selector::{TxSelector, TxSelectorEntry},
-        state::{ChainState, ApplicableChainState},
+        state::{ChainState, ApplicableChainState, ConflictDetector, verify_in_parallel},
     },
-    p2p::P2pServer,
+    p2p::{P2pServer, rate_limiter::RateLimits, tls::P2pTls},
     rpc::{
         rpc::{
             get_block_type_for_block,
             get_block_response
         },
+        stratum_server::StratumConfig,
+        AdminAuthConfig,
         DaemonRpcServer,
         SharedDaemonRpcServer
     }
@@ -86,26 +106,32 @@ use std::{
         HashSet,
         VecDeque
     },
+    iter,
     net::SocketAddr,
     num::NonZeroUsize,
     sync::{
-        atomic::{AtomicU64, Ordering},
-        Arc
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+        Weak
     },
-    time::Instant
+    time::{Duration, Instant}
 };
-use tokio::{sync::{Mutex, RwLock}, net::lookup_host};
+use tokio::{sync::{mpsc, Mutex, RwLock}, net::lookup_host, time::interval};
 use log::{info, error, debug, warn, trace};
 use rand::Rng;
+use human_bytes::human_bytes;
 
 use super::storage::{
     BlocksAtHeightProvider,
     ClientProtocolProvider,
     PrunedTopoheightProvider,
-    AccountProvider
+    AccountProvider,
+    CacheSizes,
+    ChainStats,
+    Tips
 };
 
-#[derive(Debug, clap::Args)]
+#[derive(Debug, Clone, clap::Args)]
 pub struct Config {
     /// Optional node tag
     #[clap(long)]
@@ -119,8 +145,37 @@ pub struct Config {
     /// Rpc bind address to listen for HTTP requests
     #[clap(long, default_value_t = String::from(DEFAULT_RPC_BIND_ADDRESS))]
     pub rpc_bind_address: String,
+    /// Deadline in seconds of each RPC request (0 = disabled).
+    /// 
+    /// Requests exceeding it return a timeout error instead of tying up the RPC server.
+    #[clap(long, default_value_t = DEFAULT_RPC_REQUEST_TIMEOUT)]
+    pub rpc_request_timeout: u64,
+    /// Count of events kept in the event journal (0 = disabled).
+    /// 
+    /// Websocket clients can catch up the events they missed using `get_events_since`.
+    #[clap(long, default_value_t = DEFAULT_EVENT_JOURNAL_SIZE)]
+    pub event_journal_size: u64,
+    /// Push the new_block, block_ordered, transaction_executed and transaction_conflict_detected events to this HTTP webhook URL.
+    /// 
+    /// Each event is sent as JSON in a POST request and retried with an exponential backoff on failure.
+    /// It is loaded on RPC server.
+    #[clap(long)]
+    pub webhook_urls: Vec<String>,
+    /// Broadcast again the transactions submitted through RPC
+    /// still in mempool after N topoheights (0 = disabled).
+    #[clap(long, default_value_t = DEFAULT_TX_REBROADCAST_DELAY)]
+    pub tx_rebroadcast_delay: u64,
+    /// Duration in seconds during which a transaction submitted through RPC
+    /// is broadcasted again while it is in mempool.
+    #[clap(long, default_value_t = DEFAULT_TX_REBROADCAST_TTL)]
+    pub tx_rebroadcast_ttl: u64,
+    /// Duration in seconds after which a transaction is deleted from mempool (0 = disabled).
+    /// 
+    /// Transactions of the same account with a higher nonce are also deleted.
+    #[clap(long, default_value_t = DEFAULT_MEMPOOL_TX_TTL)]
+    pub mempool_tx_ttl: u64,
     /// Add a priority node to connect when P2p is started.
-    /// A priority node is connected only one time.
+    /// A priority node is an anchor: its connection is retried with an exponential backoff when dropped.
     #[clap(long)]
     pub priority_nodes: Vec<String>,
     /// An exclusive node is connected and its connection is maintained in case of disconnect
@@ -132,19 +187,67 @@ pub struct Config {
     /// It must ends with a slash.
     #[clap(long)]
     pub dir_path: Option<String>,
-    /// Set LRUCache size (0 = disabled).
+    /// Set the size of all the LRU caches (0 = disabled).
+    /// 
+    /// Each cache can be sized independently with its own option.
     #[clap(long, default_value_t = DEFAULT_CACHE_SIZE)]
     pub cache_size: usize,
+    /// Size of the blocks cache (block headers and their tips), default is the cache size.
+    #[clap(long)]
+    pub blocks_cache_size: Option<usize>,
+    /// Size of the transactions cache, default is the cache size.
+    #[clap(long)]
+    pub transactions_cache_size: Option<usize>,
+    /// Size of the versioned balances cache, default is the cache size.
+    #[clap(long)]
+    pub balances_cache_size: Option<usize>,
+    /// Size of the topoheight index cache (topoheight by hash and hash at topoheight), default is the cache size.
+    #[clap(long)]
+    pub topoheights_cache_size: Option<usize>,
+    /// Interval in seconds between two storage maintenances (0 = disabled).
+    /// 
+    /// The database is flushed on disk and the data not used anymore is deleted.
+    #[clap(long, default_value_t = DEFAULT_STORAGE_MAINTENANCE_INTERVAL)]
+    pub storage_maintenance_interval: u64,
+    /// Prune the balance versions set more than N topoheights below the stable topoheight
+    /// during the storage maintenance.
+    /// 
+    /// Old versions of each balance are collapsed into a single base version.
+    /// Balances can't be queried anymore below it.
+    #[clap(long)]
+    pub balance_pruning_depth: Option<u64>,
+    /// Count of the last versions of each balance always kept by the balance pruning.
+    #[clap(long, default_value_t = DEFAULT_BALANCE_PRUNING_KEEP_VERSIONS)]
+    pub balance_pruning_keep_versions: usize,
     /// Disable GetWork Server (WebSocket for miners).
     #[clap(long)]
     pub disable_getwork_server: bool,
+    /// Enable the Stratum server (TCP for mining pools) on this address.
+    /// It is loaded on RPC server.
+    #[clap(long)]
+    pub stratum_bind_address: Option<String>,
+    /// Default and minimum difficulty of the shares sent to the Stratum server.
+    /// Miners can request a higher one using `mining.suggest_difficulty`.
+    #[clap(long, default_value_t = DEFAULT_STRATUM_SHARE_DIFFICULTY)]
+    pub stratum_share_difficulty: u64,
     /// Disable RPC Server
-    /// This will also disable the GetWork Server as it is loaded on RPC server.
+    /// This will also disable the GetWork and Stratum Servers as they are loaded on RPC server.
     #[clap(long)]
     pub disable_rpc_server: bool,
     /// Enable the simulator (skip PoW verification, generate a new block for every BLOCK_TIME).
     #[clap(long)]
     pub simulator: Option<Simulator>,
+    /// Maximum blocks generated per interval by the simulator.
+    /// By default it depends on the simulator mode.
+    #[clap(long)]
+    pub simulator_blocks_per_interval: Option<usize>,
+    /// Number of keys used by the simulator to mine blocks and send transactions.
+    #[clap(long, default_value_t = 100)]
+    pub simulator_keys: usize,
+    /// Maximum transactions generated per interval by the simulator.
+    /// By default it depends on the simulator mode, set it to 0 to disable them.
+    #[clap(long)]
+    pub simulator_txs_per_interval: Option<usize>,
     /// Skip PoW verification.
     /// Warning: This is dangerous and should not be used in production.
     #[clap(long)]
@@ -192,9 +295,191 @@ pub struct Config {
     /// This is useful for seed nodes under heavy load or for nodes that don't want to connect to others.
     #[clap(long)]
     pub disable_p2p_outgoing_connections: bool,
+    /// SOCKS5 proxy used for all outgoing P2P connections.
+    /// 
+    /// Format is socks5://[user:password@]host:port, for example socks5://127.0.0.1:9050 to use Tor.
+    #[clap(long)]
+    pub p2p_proxy: Option<String>,
+    /// Disable P2P inbound connections.
+    /// 
+    /// No port is listened and the node is never shared to others peers.
+    /// This should be enabled with a P2P proxy to not leak your IP address.
+    #[clap(long)]
+    pub disable_p2p_inbound_connections: bool,
+    /// Enable UPnP to request a port mapping of the P2P port on the local gateway.
+    /// 
+    /// This allows nodes behind a NAT to receive inbound connections.
+    /// The mapping is removed when the node is stopped.
+    #[clap(long)]
+    pub enable_upnp: bool,
     /// Limit of concurrent tasks accepting new incoming connections.
     #[clap(long, default_value_t = P2P_DEFAULT_CONCURRENCY_TASK_COUNT_LIMIT)]
-    pub p2p_concurrency_task_count_limit: usize
+    pub p2p_concurrency_task_count_limit: usize,
+    /// Maximum blocks propagated per second accepted from a peer (0 = unlimited).
+    /// 
+    /// Blocks over it are dropped, and the peer is banned temporarily if it continues.
+    #[clap(long, default_value_t = P2P_DEFAULT_MAX_BLOCKS_PER_SECOND)]
+    pub p2p_max_blocks_per_second: u64,
+    /// Maximum txs hashes propagated per second accepted from a peer (0 = unlimited).
+    /// 
+    /// Txs over it are dropped, and the peer is banned temporarily if it continues.
+    #[clap(long, default_value_t = P2P_DEFAULT_MAX_TXS_PER_SECOND)]
+    pub p2p_max_txs_per_second: u64,
+    /// Maximum bytes per second read from a peer (0 = unlimited).
+    /// 
+    /// Blocks and txs propagated over it are dropped.
+    #[clap(long, default_value_t = P2P_DEFAULT_MAX_BYTES_PER_SECOND)]
+    pub p2p_max_bytes_per_second: u64,
+    /// Maximum outgoing P2P connections, up to max peers (default = max peers).
+    /// 
+    /// Anchors (priority nodes) are always reconnected even if this limit is reached.
+    #[clap(long)]
+    pub p2p_max_outbound_peers: Option<usize>,
+    /// Maximum incoming P2P connections accepted, up to max peers (default = max peers).
+    #[clap(long)]
+    pub p2p_max_inbound_peers: Option<usize>,
+    /// Enable the RPC methods to manage the P2P connections at runtime.
+    /// 
+    /// This allows to add or remove anchors and to change the connections limits.
+    /// Only enable it if the RPC server is not reachable publicly.
+    #[clap(long)]
+    pub enable_p2p_rpc_management: bool,
+    /// Bind address of an additional P2P listener wrapped in TLS.
+    /// 
+    /// Its port is advertised to peers in the handshake so they can reconnect through it.
+    /// This is useful in networks where plain P2P traffic is throttled or blocked.
+    #[clap(long)]
+    pub p2p_tls_bind_address: Option<String>,
+    /// PEM file of the TLS certificate chain to use.
+    /// 
+    /// If not provided, an ephemeral self-signed certificate is generated at each start.
+    #[clap(long)]
+    pub p2p_tls_certificate: Option<String>,
+    /// PEM file of the private key of the TLS certificate.
+    #[clap(long)]
+    pub p2p_tls_private_key: Option<String>,
+    /// Server name sent in the SNI extension and used in the generated certificate.
+    #[clap(long, default_value_t = String::from(DEFAULT_P2P_TLS_SERVER_NAME))]
+    pub p2p_tls_server_name: String,
+    /// Use TLS for outgoing connections to the peers advertising a TLS port.
+    #[clap(long)]
+    pub p2p_tls_outgoing: bool,
+    /// Rebuild at startup all the data derived from the stored blocks.
+    /// 
+    /// DAG order, balances, nonces, supply and accounts registrations are rebuilt by replaying all blocks.
+    /// This is useful in case of index corruption without having to resync the whole chain from the network.
+    /// The chain is rebuilt in a temporary directory and replaces the current one only once done.
+    /// The chain must not be pruned.
+    #[clap(long)]
+    pub reindex: bool,
+    /// Verify the whole chain at startup before starting the P2P and RPC servers.
+    /// 
+    /// All blocks are replayed in a temporary directory through the complete validation path (PoW, tips, balances, nonces, supply)
+    /// and the result is compared to the current chain, the first inconsistency found is reported.
+    /// The current chain is never modified. The chain must not be pruned.
+    #[clap(long)]
+    pub verify_chain: bool,
+    /// Import the chain from a snapshot directory before starting.
+    /// 
+    /// The snapshot manifest must be signed by one of the trusted snapshot publishers.
+    /// The current chain is replaced only once the snapshot data has been verified.
+    #[clap(long)]
+    pub import_snapshot: Option<String>,
+    /// Hex encoded ed25519 public key of a trusted snapshot publisher.
+    #[clap(long)]
+    pub trusted_snapshot_publishers: Vec<String>,
+    /// Allow to import a snapshot not signed by a trusted publisher.
+    /// 
+    /// Warning: the snapshot content can't be verified, only use it with snapshots you created.
+    #[clap(long)]
+    pub allow_unsigned_snapshot: bool,
+    /// Password required to use the admin RPC methods.
+    /// 
+    /// The admin methods (rewind, prune, caches, log level, peers management) are only enabled when it is set.
+    /// They are served on the `/admin/json_rpc` route using HTTP Basic authentication.
+    #[clap(long)]
+    pub rpc_admin_password: Option<String>,
+    /// Username required to use the admin RPC methods.
+    #[clap(long, default_value_t = String::from(DEFAULT_RPC_ADMIN_USERNAME))]
+    pub rpc_admin_username: String,
+    /// Premine allocation credited at topoheight 0 in the format `address:amount` (amount in XEL).
+    /// 
+    /// Only allowed in dev network, it is applied when the genesis block is executed.
+    /// All the nodes of a private network must use the same allocations.
+    #[clap(long)]
+    pub genesis_premine: Vec<String>,
+    /// Start as a hot standby of the primary node given (`ip:port`).
+    /// 
+    /// The node connects only to its primary and applies its blocks without relaying them to anyone.
+    /// Mining and transactions submission are refused until it is promoted to full mode.
+    /// Promotion is done using the `promote` command or the `admin.promote_standby` RPC method.
+    #[clap(long)]
+    pub standby_primary: Option<String>,
+    /// TOML file overriding the chain parameters, only allowed in dev network.
+    /// 
+    /// Available keys: `block_time_millis`, `minimum_difficulty`, `stable_limit`, `emission_speed_factor`, `max_block_size`,
+    /// `timestamp_future_limit_millis` and `timestamp_median_blocks`.
+    /// Keys not set keep their default value. All the nodes of the network must use the same parameters.
+    #[clap(long)]
+    pub chain_config: Option<String>
+}
+
+impl Config {
+    // Size of each storage cache, the global cache size is used if not set
+    pub fn get_cache_sizes(&self) -> CacheSizes {
+        CacheSizes {
+            blocks: self.blocks_cache_size.unwrap_or(self.cache_size),
+            transactions: self.transactions_cache_size.unwrap_or(self.cache_size),
+            balances: self.balances_cache_size.unwrap_or(self.cache_size),
+            topoheights: self.topoheights_cache_size.unwrap_or(self.cache_size),
+            others: self.cache_size
+        }
+    }
+}
+
+// Use the same default values as the CLI
+// This allows to embed the daemon without parsing any argument
+impl Default for Config {
+    fn default() -> Self {
+        let command = <Self as clap::Args>::augment_args(clap::Command::new("xelis_daemon"));
+        let matches = command.get_matches_from(["xelis_daemon"]);
+        <Self as clap::FromArgMatches>::from_arg_matches(&matches)
+            .expect("Default config must be valid")
+    }
+}
+
+// Block verified against the chain state, ready to be committed in storage
+// The validation only requires a read access to the storage
+pub struct BlockValidationContext {
+    block: Block,
+    block_hash: Hash,
+    // size of the block with its transactions
+    block_size: usize,
+    difficulty: Difficulty,
+    p: VarUint,
+    // tips of the chain at the validation
+    // the block must be validated again if they changed before the commit
+    tips: Tips,
+    // when the processing of the block started
+    start: Instant
+}
+
+impl BlockValidationContext {
+    pub fn get_block(&self) -> &Block {
+        &self.block
+    }
+
+    pub fn get_hash(&self) -> &Hash {
+        &self.block_hash
+    }
+
+    pub fn get_difficulty(&self) -> &Difficulty {
+        &self.difficulty
+    }
+
+    pub fn get_block_size(&self) -> usize {
+        self.block_size
+    }
 }
 
 pub struct Blockchain<S: Storage> {
@@ -228,21 +513,207 @@ pub struct Blockchain<S: Storage> {
     // key is (tip hash, tip height) while value is (base hash, base height)
     tip_base_cache: Mutex<LruCache<(Hash, u64), (Hash, u64)>>,
     // tip work score is used to determine the best tip based on a block, tip base ands a base height
-    tip_work_score_cache: Mutex<LruCache<(Hash, Hash, u64), (HashSet<Hash>, CumulativeDifficulty)>>,
+    // it is backed by the work scores persisted in storage
+    tip_work_score_cache: Mutex<LruCache<(Hash, Hash, u64), CumulativeDifficulty>>,
+    // common base (hash, height) of sorted tips
+    common_base_cache: Mutex<LruCache<Vec<Hash>, (Hash, u64)>>,
+    // blocks reachable from a block, it only depends on the DAG so it is never invalidated
+    reachability_cache: Mutex<LruCache<Hash, Arc<HashSet<Hash>>>>,
     // using base hash, current tip hash and base height, this cache is used to store the DAG order
     full_order_cache: Mutex<LruCache<(Hash, Hash, u64), IndexSet<Hash>>>,
+    // last block template built, key is (tips, topoheight, mempool generation)
+    // it is reused by miners polling until a new block or tx is received
+    block_template_cache: Mutex<Option<((HashSet<Hash>, u64, u64), BlockHeader)>>,
     // auto prune mode if enabled, will delete all blocks every N and keep only N top blocks (topoheight based)
-    auto_prune_keep_n_blocks: Option<u64>
+    auto_prune_keep_n_blocks: Option<u64>,
+    // depth below the stable topoheight and count of last versions kept when pruning the balances
+    balance_pruning: Option<(u64, usize)>,
+    // nonces reserved by external signers
+    nonce_reservations: Mutex<NonceReservations>,
+    // last transactions detected in conflict with a mempool transaction
+    tx_conflicts: Mutex<VecDeque<TransactionConflict>>,
+    // transactions submitted through RPC, broadcasted again until they leave the mempool
+    local_txs: Mutex<LocalTransactions>,
+    // topoheights before broadcasting again a local tx and its time to live in seconds
+    tx_rebroadcast_delay: u64,
+    tx_rebroadcast_ttl: u64,
+    // duration in seconds after which a tx is deleted from mempool (0 = disabled)
+    mempool_tx_ttl: u64,
+    // new blocks waiting to be processed by the block processing task
+    block_queue: mpsc::Sender<BlockSubmission>,
+    block_processing_metrics: Mutex<BlockProcessingMetrics>,
+    // second handle on the storage to read the committed data without its lock
+    storage_reader: S,
+    storage_rewrites: StorageRewrites,
+    // time at which the blockchain has been started
+    started_at: TimestampMillis,
+    // balances credited when the genesis block is executed
+    premine: Vec<(PublicKey, u64)>,
+    // primary node followed in standby mode
+    standby_primary: Option<SocketAddr>,
+    // set until the standby node is promoted to full mode
+    standby: AtomicBool,
+    // consensus parameters, only dev network can override them
+    chain_params: ChainParams
 }
 
 impl<S: Storage> Blockchain<S> {
     pub async fn new(config: Config, network: Network, storage: S) -> Result<Arc<Self>, Error> {
+        Self::new_internal(config, network, storage, None).await
+    }
+
+    // Create the blockchain with the genesis block given if the storage is empty
+    // Dev network has no hardcoded genesis block, this allows several dev nodes to share the same chain
+    pub async fn new_with_genesis_block(config: Config, network: Network, storage: S, genesis_block: Block) -> Result<Arc<Self>, Error> {
+        Self::new_internal(config, network, storage, Some(genesis_block)).await
+    }
+
+    // Rebuild a chain in the empty target storage by replaying all the blocks stored in the source storage
+    // All the data derived from the blocks (DAG order, balances, nonces, supply, registrations...) is recomputed
+    // Returns the rebuilt chain with no module started and the count of blocks replayed
+    pub async fn reindex(mut config: Config, network: Network, source: &S, target: S) -> Result<(Arc<Self>, u64), Error> {
+        // Blocks were already verified when they were added the first time
+        config.skip_pow_verification = true;
+        Self::replay(config, network, source, target, "Reindex").await
+    }
+
+    // Verify the whole chain stored in the source storage
+    // All blocks are replayed in the empty target storage through the complete validation path,
+    // then the DAG order, supply, rewards, nonces and balances computed are compared to the stored ones
+    // Returns the count of blocks verified or the first inconsistency found
+    pub async fn verify_chain(config: Config, network: Network, source: &S, target: S) -> Result<u64, Error> {
+        let (blockchain, replayed) = Self::replay(config, network, source, target, "Verification").await?;
+        let result = blockchain.compare_with(source).await;
+        blockchain.stop().await;
+        result?;
+
+        Ok(replayed)
+    }
+
+    // Replay all the blocks stored in the source storage by ascending height so each block is added after its tips
+    async fn replay(mut config: Config, network: Network, source: &S, target: S, label: &str) -> Result<(Arc<Self>, u64), Error> {
+        if let Some(pruned_topoheight) = source.get_pruned_topoheight().await? {
+            return Err(BlockchainError::ReindexPrunedChain(pruned_topoheight).into())
+        }
+
+        config.disable_p2p_server = true;
+        config.disable_rpc_server = true;
+        config.simulator = None;
+        config.auto_prune_keep_n_blocks = None;
+        config.standby_primary = None;
+        config.storage_maintenance_interval = 0;
+        config.balance_pruning_depth = None;
+        config.tx_rebroadcast_delay = 0;
+
+        // Use the same genesis block as the source, it may have been generated
+        let genesis_hash = source.get_hash_at_topo_height(0).await?;
+        let genesis_block = source.get_block_by_hash(&genesis_hash).await?;
+        let blockchain = Self::new_internal(config, network, target, Some(genesis_block)).await?;
+
+        let top_height = source.get_top_height()?;
+        let total_blocks = source.count_blocks().await?;
+        info!("{}: replaying {} blocks until height {}...", label, total_blocks, top_height);
+        let start = Instant::now();
+        let mut replayed = 1;
+        for height in 1..=top_height {
+            for hash in source.get_blocks_at_height(height).await? {
+                let block = source.get_block_by_hash(&hash).await?;
+                if let Err(e) = blockchain.add_new_block(block, false, false).await {
+                    error!("{}: block {} at height {} is invalid: {}", label, hash, height, e);
+                    return Err(BlockchainError::ReplayFailed(hash, height, Box::new(e)).into())
+                }
+
+                replayed += 1;
+                if replayed % REINDEX_PROGRESS_INTERVAL == 0 {
+                    info!("{} progress: {}/{} blocks ({:.2}%), height {}/{}", label, replayed, total_blocks, replayed as f64 * 100f64 / total_blocks as f64, height, top_height);
+                }
+            }
+        }
+
+        info!("{} of {} blocks done in {}s, topoheight is {}", label, replayed, start.elapsed().as_secs(), blockchain.get_topo_height());
+        Ok((blockchain, replayed))
+    }
+
+    // Compare the data computed by a replay with the one stored in the source storage
+    // Returns the first inconsistency found
+    async fn compare_with(&self, source: &S) -> Result<(), BlockchainError> {
+        let storage = self.storage.read().await;
+        let top_topoheight = source.get_top_topoheight()?;
+        if storage.get_top_topoheight()? != top_topoheight {
+            return Err(BlockchainError::ChainInconsistency(top_topoheight, format!("replayed chain stops at topoheight {}", storage.get_top_topoheight()?)))
+        }
+
+        info!("Verification: comparing {} topoheights...", top_topoheight + 1);
+        for topoheight in 0..=top_topoheight {
+            let hash = source.get_hash_at_topo_height(topoheight).await?;
+            let replayed_hash = storage.get_hash_at_topo_height(topoheight).await?;
+            if hash != replayed_hash {
+                return Err(BlockchainError::ChainInconsistency(topoheight, format!("block {} is ordered instead of {}", replayed_hash, hash)))
+            }
+
+            let supply = source.get_supply_at_topo_height(topoheight).await?;
+            let replayed_supply = storage.get_supply_at_topo_height(topoheight).await?;
+            if supply != replayed_supply {
+                return Err(BlockchainError::ChainInconsistency(topoheight, format!("supply is {} instead of {}", replayed_supply, supply)))
+            }
+
+            let reward = source.get_block_reward_at_topo_height(topoheight)?;
+            let replayed_reward = storage.get_block_reward_at_topo_height(topoheight)?;
+            if reward != replayed_reward {
+                return Err(BlockchainError::ChainInconsistency(topoheight, format!("block reward is {} instead of {}", replayed_reward, reward)))
+            }
+
+            if topoheight % REINDEX_PROGRESS_INTERVAL == 0 {
+                info!("Verification progress: topoheight {}/{}", topoheight, top_topoheight);
+            }
+        }
+
+        let accounts = source.count_accounts().await?;
+        info!("Verification: comparing nonces and balances of {} accounts...", accounts);
+        let mut skip = 0;
+        loop {
+            let keys = source.get_partial_keys(VERIFY_CHAIN_ACCOUNTS_BATCH, skip, 0, top_topoheight).await?;
+            if keys.is_empty() {
+                break;
+            }
+            skip += keys.len();
+
+            for key in keys {
+                let address = key.as_address(self.network.is_mainnet());
+                let (topoheight, nonce) = source.get_last_nonce(&key).await?;
+                let (replayed_topoheight, replayed_nonce) = storage.get_last_nonce(&key).await
+                    .map_err(|_| BlockchainError::ChainInconsistency(topoheight, format!("account {} has no nonce", address)))?;
+                if topoheight != replayed_topoheight || nonce.get_nonce() != replayed_nonce.get_nonce() {
+                    return Err(BlockchainError::ChainInconsistency(topoheight, format!("nonce of {} is {} at topoheight {} instead of {}", address, replayed_nonce.get_nonce(), replayed_topoheight, nonce.get_nonce())))
+                }
+
+                for asset in source.get_assets_for(&key).await? {
+                    let (topoheight, balance) = source.get_last_balance(&key, &asset).await?;
+                    let (replayed_topoheight, replayed_balance) = storage.get_last_balance(&key, &asset).await
+                        .map_err(|_| BlockchainError::ChainInconsistency(topoheight, format!("account {} has no balance for asset {}", address, asset)))?;
+                    if topoheight != replayed_topoheight || balance.get_balance().compress() != replayed_balance.get_balance().compress() {
+                        return Err(BlockchainError::ChainInconsistency(topoheight, format!("balance of {} for asset {} is different (replayed at topoheight {})", address, asset, replayed_topoheight)))
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Create the blockchain using the genesis block given if the storage is empty
+    async fn new_internal(config: Config, network: Network, storage: S, genesis_block: Option<Block>) -> Result<Arc<Self>, Error> {
         // Do some checks on config params
         {
             if config.simulator.is_some() && network != Network::Dev {
                 error!("Impossible to enable simulator mode except in dev network!");
                 return Err(BlockchainError::InvalidNetwork.into())
             }
+
+            if config.simulator_keys == 0 || config.simulator_blocks_per_interval == Some(0) {
+                error!("Simulator requires at least one key and one block per interval");
+                return Err(BlockchainError::SimulatorMode.into())
+            }
     
             if let Some(keep_only) = config.auto_prune_keep_n_blocks {
                 if keep_only < PRUNE_SAFETY_LIMIT {
@@ -251,6 +722,13 @@ impl<S: Storage> Blockchain<S> {
                 }
             }
 
+            if let Some(depth) = config.balance_pruning_depth {
+                if depth < PRUNE_SAFETY_LIMIT || config.balance_pruning_keep_versions == 0 {
+                    error!("Balance pruning should have a depth of at least {} and keep at least one version", PRUNE_SAFETY_LIMIT);
+                    return Err(BlockchainError::BalancePruning.into())
+                }
+            }
+
             if let Some(size) = config.max_chain_response_size {
                 if size < CHAIN_SYNC_RESPONSE_MIN_BLOCKS || size > CHAIN_SYNC_RESPONSE_MAX_BLOCKS {
                     error!("Max chain response size should be in inclusive range of [{}-{}]", CHAIN_SYNC_RESPONSE_MIN_BLOCKS, CHAIN_SYNC_RESPONSE_MAX_BLOCKS);
@@ -266,8 +744,43 @@ impl<S: Storage> Blockchain<S> {
             if config.skip_pow_verification {
                 warn!("PoW verification is disabled! This is dangerous in production!");
             }
+
+            if !config.genesis_premine.is_empty() && network != Network::Dev {
+                error!("Genesis premine is only allowed in dev network!");
+                return Err(BlockchainError::InvalidNetwork.into())
+            }
+
+            if config.chain_config.is_some() && network != Network::Dev {
+                error!("Chain parameters can only be overridden in dev network!");
+                return Err(BlockchainError::InvalidNetwork.into())
+            }
         }
 
+        let chain_params = match config.chain_config.as_ref() {
+            Some(path) => {
+                let params = ChainParams::load(&network, path)?;
+                warn!("Using custom chain parameters: {:?}", params);
+                params
+            },
+            None => ChainParams::new(&network)
+        };
+
+        let premine = parse_premine_allocations(&network, &config.genesis_premine)?;
+
+        let standby_primary = match config.standby_primary.as_ref() {
+            Some(primary) => {
+                if config.disable_p2p_server || !config.exclusive_nodes.is_empty() || config.simulator.is_some() {
+                    error!("Standby mode requires the P2P server and can't be used with exclusive nodes or the simulator!");
+                    return Err(BlockchainError::ConfigStandbyMode.into())
+                }
+
+                let addr: SocketAddr = primary.parse().map_err(|_| BlockchainError::ConfigStandbyMode)?;
+                info!("Starting in standby mode, following primary node {}", addr);
+                Some(addr)
+            },
+            None => None
+        };
+
         let on_disk = storage.has_blocks().await;
         let (height, topoheight) = if on_disk {
             info!("Reading last metadata available...");
@@ -278,6 +791,8 @@ impl<S: Storage> Blockchain<S> {
         } else { (0, 0) };
 
         info!("Initializing chain...");
+        let (block_queue, block_queue_receiver) = mpsc::channel(BLOCK_QUEUE_SIZE);
+        let storage_reader = storage.create_read_handle();
         let blockchain = Self {
             height: AtomicU64::new(height),
             topoheight: AtomicU64::new(topoheight),
@@ -293,13 +808,32 @@ impl<S: Storage> Blockchain<S> {
             network,
             tip_base_cache: Mutex::new(LruCache::new(NonZeroUsize::new(1024).unwrap())),
             tip_work_score_cache: Mutex::new(LruCache::new(NonZeroUsize::new(1024).unwrap())),
+            common_base_cache: Mutex::new(LruCache::new(NonZeroUsize::new(1024).unwrap())),
+            reachability_cache: Mutex::new(LruCache::new(NonZeroUsize::new(1024).unwrap())),
             full_order_cache: Mutex::new(LruCache::new(NonZeroUsize::new(1024).unwrap())),
-            auto_prune_keep_n_blocks: config.auto_prune_keep_n_blocks
+            block_template_cache: Mutex::new(None),
+            auto_prune_keep_n_blocks: config.auto_prune_keep_n_blocks,
+            balance_pruning: config.balance_pruning_depth.map(|depth| (depth, config.balance_pruning_keep_versions)),
+            nonce_reservations: Mutex::new(NonceReservations::new()),
+            tx_conflicts: Mutex::new(VecDeque::new()),
+            local_txs: Mutex::new(LocalTransactions::new()),
+            tx_rebroadcast_delay: config.tx_rebroadcast_delay,
+            tx_rebroadcast_ttl: config.tx_rebroadcast_ttl,
+            mempool_tx_ttl: config.mempool_tx_ttl,
+            block_queue,
+            block_processing_metrics: Mutex::new(BlockProcessingMetrics::new()),
+            storage_reader,
+            storage_rewrites: StorageRewrites::new(),
+            started_at: get_current_time_in_millis(),
+            premine,
+            standby: AtomicBool::new(standby_primary.is_some()),
+            standby_primary,
+            chain_params
         };
 
         // include genesis block
         if !on_disk {
-            blockchain.create_genesis_block().await?;
+            blockchain.create_genesis_block(genesis_block).await?;
         } else {
             debug!("Retrieving tips for computing current difficulty");
             let storage = blockchain.get_storage().read().await;
@@ -321,6 +855,8 @@ impl<S: Storage> Blockchain<S> {
         }
 
         let arc = Arc::new(blockchain);
+        spawn_task("block-processing", Self::block_processing_loop(Arc::downgrade(&arc), block_queue_receiver));
+
         // create P2P Server
         if !config.disable_p2p_server {
             info!("Starting P2p server...");
@@ -337,7 +873,25 @@ impl<S: Storage> Blockchain<S> {
                 exclusive_nodes.push(addr);
             }
 
-            match P2pServer::new(config.p2p_concurrency_task_count_limit, config.dir_path, config.tag, config.max_peers, config.p2p_bind_address, Arc::clone(&arc), exclusive_nodes.is_empty(), exclusive_nodes, config.allow_fast_sync, config.allow_boost_sync, config.max_chain_response_size, !config.disable_ip_sharing, config.disable_p2p_outgoing_connections) {
+            let rate_limits = RateLimits {
+                blocks_per_second: config.p2p_max_blocks_per_second,
+                txs_per_second: config.p2p_max_txs_per_second,
+                bytes_per_second: config.p2p_max_bytes_per_second
+            };
+
+            let tls = if config.p2p_tls_bind_address.is_some() || config.p2p_tls_outgoing {
+                match P2pTls::new(config.p2p_tls_bind_address.as_deref(), &config.p2p_tls_server_name, config.p2p_tls_certificate.as_deref(), config.p2p_tls_private_key.as_deref(), config.p2p_tls_outgoing) {
+                    Ok(tls) => Some(tls),
+                    Err(e) => {
+                        error!("Error while setting up P2P TLS: {}", e);
+                        None
+                    }
+                }
+            } else {
+                None
+            };
+
+            match P2pServer::new(config.p2p_concurrency_task_count_limit, config.dir_path, config.tag, config.max_peers, config.p2p_bind_address, Arc::clone(&arc), exclusive_nodes.is_empty(), exclusive_nodes, config.allow_fast_sync, config.allow_boost_sync, config.max_chain_response_size, !config.disable_ip_sharing, config.disable_p2p_outgoing_connections, rate_limits, config.p2p_proxy, config.disable_p2p_inbound_connections, config.enable_upnp, config.p2p_max_outbound_peers, config.p2p_max_inbound_peers, tls) {
                 Ok(p2p) => {
                     // connect to priority nodes
                     for addr in config.priority_nodes {
@@ -349,7 +903,7 @@ impl<S: Storage> Blockchain<S> {
                                         info!("Valid host found for {}", addr);
                                         for addr in it {
                                             info!("Trying to connect to priority node with IP from DNS resolution: {}", addr);
-                                            p2p.try_to_connect_to_peer(addr, true).await;
+                                            p2p.add_anchor(addr).await;
                                         }
                                     },
                                     Err(e2) => {
@@ -360,7 +914,7 @@ impl<S: Storage> Blockchain<S> {
                             }
                         };
                         info!("Trying to connect to priority node: {}", addr);
-                        p2p.try_to_connect_to_peer(addr, true).await;
+                        p2p.add_anchor(addr).await;
                     }
                     *arc.p2p.write().await = Some(p2p);
                 },
@@ -371,7 +925,20 @@ impl<S: Storage> Blockchain<S> {
         // create RPC Server
         if !config.disable_rpc_server {
             info!("RPC Server will listen on: {}", config.rpc_bind_address);
-            match DaemonRpcServer::new(config.rpc_bind_address, Arc::clone(&arc), config.disable_getwork_server).await {
+            let request_timeout = if config.rpc_request_timeout > 0 {
+                Some(Duration::from_secs(config.rpc_request_timeout))
+            } else {
+                None
+            };
+            let admin_auth = config.rpc_admin_password.map(|password| AdminAuthConfig {
+                username: config.rpc_admin_username,
+                password
+            });
+            let stratum_config = config.stratum_bind_address.map(|bind_address| StratumConfig {
+                bind_address,
+                share_difficulty: Difficulty::from_u64(config.stratum_share_difficulty)
+            });
+            match DaemonRpcServer::new(config.rpc_bind_address, Arc::clone(&arc), config.disable_getwork_server, config.enable_p2p_rpc_management, admin_auth, request_timeout, stratum_config, config.event_journal_size, config.webhook_urls).await {
                 Ok(server) => *arc.rpc.write().await = Some(server),
                 Err(e) => error!("Error while starting RPC server: {}", e)
             };
@@ -381,14 +948,183 @@ impl<S: Storage> Blockchain<S> {
         if let Some(simulator) = arc.simulator {
             warn!("Simulator {} mode enabled!", simulator);
             let blockchain = Arc::clone(&arc);
+            let simulator_config = SimulatorConfig {
+                blocks_per_interval: config.simulator_blocks_per_interval,
+                keys: config.simulator_keys,
+                txs_per_interval: config.simulator_txs_per_interval
+            };
             spawn_task("simulator", async move {
-                simulator.start(blockchain).await;
+                simulator.start(blockchain, simulator_config).await;
             });
         }
 
+        if config.storage_maintenance_interval > 0 {
+            spawn_task("storage-maintenance", Self::storage_maintenance_loop(Arc::downgrade(&arc), config.storage_maintenance_interval));
+        }
+
+        spawn_task("mempool-cleanup", Self::mempool_cleanup_loop(Arc::downgrade(&arc)));
+
+        if arc.tx_rebroadcast_delay > 0 {
+            spawn_task("tx-rebroadcast", Self::tx_rebroadcast_loop(Arc::downgrade(&arc)));
+        }
+
         Ok(arc)
     }
 
+    // Run the storage maintenance at each interval
+    // The task stops once the blockchain is dropped
+    async fn storage_maintenance_loop(blockchain: Weak<Self>, interval_seconds: u64) {
+        let mut interval = interval(Duration::from_secs(interval_seconds));
+        // first tick is instant
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            let Some(blockchain) = blockchain.upgrade() else {
+                break;
+            };
+
+            if let Err(e) = blockchain.run_storage_maintenance().await {
+                error!("Error while running storage maintenance: {}", e);
+            }
+        }
+    }
+
+    // Clean the mempool periodically, not only when a new block is added
+    // The task stops once the blockchain is dropped
+    async fn mempool_cleanup_loop(blockchain: Weak<Self>) {
+        let mut interval = interval(Duration::from_secs(MEMPOOL_CLEANUP_INTERVAL));
+        // first tick is instant
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            let Some(blockchain) = blockchain.upgrade() else {
+                break;
+            };
+
+            if let Err(e) = blockchain.clean_mempool().await {
+                error!("Error while cleaning mempool: {}", e);
+            }
+        }
+    }
+
+    // Delete the expired txs from mempool and those whose nonce can't be used anymore
+    // Returns the count of txs deleted
+    pub async fn clean_mempool(&self) -> Result<usize, BlockchainError> {
+        trace!("Cleaning mempool");
+        let storage = self.storage.read().await;
+        let mut mempool = self.mempool.write().await;
+
+        let mut deleted = 0;
+        if self.mempool_tx_ttl > 0 {
+            let expired = mempool.drop_expired_txs(get_current_time_in_seconds(), self.mempool_tx_ttl);
+            for (hash, _) in expired.iter() {
+                debug!("TX {} has expired in mempool", hash);
+            }
+            deleted += expired.len();
+        }
+
+        // also verify again the txs left and their nonces against the chain state
+        let block_version = self.get_version_at_height(self.get_height() + 1);
+        deleted += mempool.clean_up(&*storage, self.get_topo_height(), block_version).await.len();
+        if deleted > 0 {
+            info!("{} TXs deleted from mempool during cleanup", deleted);
+        }
+
+        Ok(deleted)
+    }
+
+    // Broadcast again the local txs still in mempool
+    // The task stops once the blockchain is dropped
+    async fn tx_rebroadcast_loop(blockchain: Weak<Self>) {
+        let mut interval = interval(Duration::from_millis(TX_REBROADCAST_CHECK_INTERVAL_MILLIS));
+        loop {
+            interval.tick().await;
+            let Some(blockchain) = blockchain.upgrade() else {
+                break;
+            };
+
+            blockchain.rebroadcast_local_txs().await;
+        }
+    }
+
+    // Track a tx submitted through RPC so it is broadcasted again until it leaves the mempool
+    pub async fn track_local_tx(&self, hash: Hash) {
+        if self.tx_rebroadcast_delay > 0 {
+            self.local_txs.lock().await.track(hash, self.get_topo_height(), get_current_time_in_millis());
+        }
+    }
+
+    // Get the local txs still tracked
+    pub async fn get_local_txs(&self) -> Vec<LocalTransactionEntry> {
+        self.local_txs.lock().await.get_entries()
+    }
+
+    // Broadcast again the local txs still in mempool after the rebroadcast delay
+    // Executed or orphaned txs are not in mempool anymore and are not tracked anymore
+    async fn rebroadcast_local_txs(&self) {
+        let txs = {
+            let mempool = self.mempool.read().await;
+            let mut local_txs = self.local_txs.lock().await;
+            local_txs.retain(|hash| mempool.contains_tx(hash));
+            local_txs.select_for_rebroadcast(self.get_topo_height(), get_current_time_in_millis(), self.tx_rebroadcast_delay, self.tx_rebroadcast_ttl * MILLIS_PER_SECOND)
+        };
+
+        if txs.is_empty() {
+            return;
+        }
+
+        if let Some(p2p) = self.p2p.read().await.as_ref() {
+            debug!("Broadcasting again {} local txs", txs.len());
+            p2p.rebroadcast_txs_hashes(txs).await;
+        }
+    }
+
+    // Flush the storage on disk and delete the data not used anymore
+    // Storage is locked during the whole maintenance
+    pub async fn run_storage_maintenance(&self) -> Result<AdminStorageMaintenanceResult, BlockchainError> {
+        debug!("Running storage maintenance");
+        let start = Instant::now();
+        let mut storage = self.storage.write().await;
+        let size_before = storage.get_size_on_disk().await?;
+
+        // new blocks compute their work scores from a base above the stable topoheight
+        let stable_topoheight = self.get_stable_topoheight();
+        storage.delete_work_scores_below_topoheight(stable_topoheight).await?;
+
+        let mut pruned_balances = 0;
+        if let Some((depth, keep_versions)) = self.balance_pruning {
+            if stable_topoheight > depth {
+                let _rewrite = self.storage_rewrites.begin();
+                pruned_balances = storage.prune_versioned_balances_below_topoheight(stable_topoheight - depth, keep_versions).await?;
+            }
+        }
+
+        let dropped_trees = storage.drop_unused_trees().await?;
+        let flushed_bytes = storage.flush().await?;
+
+        let size_after = storage.get_size_on_disk().await?;
+        let duration_millis = start.elapsed().as_millis() as u64;
+        info!("Storage maintenance done in {}ms: size on disk {} -> {}, {} balance versions pruned, {} trees dropped, {} bytes flushed", duration_millis, human_bytes(size_before as f64), human_bytes(size_after as f64), pruned_balances, dropped_trees, flushed_bytes);
+
+        Ok(AdminStorageMaintenanceResult {
+            size_before,
+            size_after,
+            pruned_balances,
+            dropped_trees,
+            flushed_bytes,
+            duration_millis
+        })
+    }
+
+    // Get the hash of the genesis block
+    // Dev network has no hardcoded genesis block, it is read from the storage
+    pub async fn get_genesis_hash_for_storage(&self, storage: &S) -> Result<Cow<'static, Hash>, BlockchainError> {
+        match self.network {
+            Network::Dev => Ok(Cow::Owned(storage.get_hash_at_topo_height(0).await?)),
+            _ => Ok(Cow::Borrowed(get_genesis_block_hash(&self.network)))
+        }
+    }
+
     // Detect if the simulator task has been started
     pub fn is_simulator_enabled(&self) -> bool {
         self.simulator.is_some()
@@ -465,15 +1201,44 @@ impl<S: Storage> Blockchain<S> {
         Ok(())
     }
 
+    // Clear the storage caches and the DAG caches
+    pub async fn clear_caches(&self) -> Result<(), BlockchainError> {
+        debug!("Clearing caches");
+        {
+            let mut storage = self.storage.write().await;
+            storage.clear_caches().await?;
+        }
+
+        self.clear_dag_caches().await;
+        self.reachability_cache.lock().await.clear();
+        *self.block_template_cache.lock().await = None;
+
+        Ok(())
+    }
+
+    // Clear the DAG caches depending on the blocks order
+    // They must be cleared once blocks are re-ordered or pruned
+    async fn clear_dag_caches(&self) {
+        trace!("Clearing DAG caches");
+        self.tip_base_cache.lock().await.clear();
+        self.tip_work_score_cache.lock().await.clear();
+        self.common_base_cache.lock().await.clear();
+        self.full_order_cache.lock().await.clear();
+    }
+
     // function to include the genesis block and register the public dev key.
-    async fn create_genesis_block(&self) -> Result<(), BlockchainError> {
+    // If no genesis block is given, the one of the network is used or generated
+    async fn create_genesis_block(&self, genesis_block: Option<Block>) -> Result<(), BlockchainError> {
         let mut storage = self.storage.write().await;
 
         // register XELIS asset
         debug!("Registering XELIS asset: {} at topoheight 0", XELIS_ASSET);
         storage.add_asset(&XELIS_ASSET, AssetData::new(0, COIN_DECIMALS)).await?;
 
-        let (genesis_block, genesis_hash) = if let Some(genesis_block) = get_hex_genesis_block(&self.network) {
+        let (genesis_block, genesis_hash) = if let Some(genesis_block) = genesis_block {
+            let genesis_hash = genesis_block.hash();
+            (genesis_block, genesis_hash)
+        } else if let Some(genesis_block) = get_hex_genesis_block(&self.network) {
             info!("De-serializing genesis block for network {}...", self.network);
             let genesis = Block::from_hex(genesis_block.to_owned())?;
             if *genesis.get_miner() != *DEV_PUBLIC_KEY {
@@ -527,7 +1292,7 @@ impl<S: Storage> Blockchain<S> {
                 header = self.get_block_template(key.clone()).await?;
             }
             header.nonce += 1;
-            header.timestamp = get_current_time_in_millis();
+            header.timestamp = get_current_time_in_millis().max(header.timestamp);
             hash = header.get_pow_hash()?;
         }
 
@@ -569,6 +1334,7 @@ impl<S: Storage> Blockchain<S> {
         debug!("Located sync topoheight found: {}", located_sync_topoheight);
 
         if located_sync_topoheight > last_pruned_topoheight {
+            let _rewrite = self.storage_rewrites.begin();
             // create snapshots of balances to located_sync_topoheight
             storage.create_snapshot_balances_at_topoheight(located_sync_topoheight).await?;
             storage.create_snapshot_nonces_at_topoheight(located_sync_topoheight).await?;
@@ -587,6 +1353,9 @@ impl<S: Storage> Blockchain<S> {
             storage.delete_versioned_nonces_below_topoheight(located_sync_topoheight).await?;
             // Also delete registrations
             storage.delete_registrations_below_topoheight(located_sync_topoheight).await?;
+            // and the work scores computed from a pruned base
+            storage.delete_work_scores_below_topoheight(located_sync_topoheight).await?;
+            self.clear_dag_caches().await;
 
             // Update the pruned topoheight
             storage.set_pruned_topoheight(located_sync_topoheight).await?;
@@ -654,6 +1423,72 @@ impl<S: Storage> Blockchain<S> {
         self.mempool.read().await.size()
     }
 
+    // Get the uptime of the blockchain in milliseconds
+    pub fn get_uptime(&self) -> TimestampMillis {
+        get_current_time_in_millis().saturating_sub(self.started_at)
+    }
+
+    pub fn get_auto_prune_keep_n_blocks(&self) -> Option<u64> {
+        self.auto_prune_keep_n_blocks
+    }
+
+    pub fn get_chain_params(&self) -> &ChainParams {
+        &self.chain_params
+    }
+
+    // Is the node following its primary without mining or relaying anything
+    pub fn is_standby(&self) -> bool {
+        self.standby.load(Ordering::Acquire)
+    }
+
+    // Primary node to connect to, only set while in standby mode
+    pub fn get_standby_primary(&self) -> Option<&SocketAddr> {
+        if self.is_standby() {
+            self.standby_primary.as_ref()
+        } else {
+            None
+        }
+    }
+
+    // Switch from standby to full mode
+    // Peers discovery, gossip and mining are enabled directly without restarting
+    // Returns false if the node was not in standby mode
+    pub async fn promote(&self) -> bool {
+        if !self.standby.swap(false, Ordering::AcqRel) {
+            return false
+        }
+
+        info!("Standby node promoted to full mode");
+        if let Some(rpc) = self.rpc.read().await.as_ref() {
+            // miners may be connected already, send them a job
+            if let Some(getwork) = rpc.getwork_server() {
+                let getwork = getwork.clone();
+                spawn_task("promote-notify-new-job", async move {
+                    if let Err(e) = getwork.notify_new_job().await {
+                        debug!("Error while notifying new job to miners: {}", e);
+                    }
+                });
+            }
+
+            if let Some(stratum) = rpc.stratum_server() {
+                let stratum = stratum.clone();
+                spawn_task("promote-notify-new-stratum-job", async move {
+                    if let Err(e) = stratum.notify_new_job().await {
+                        debug!("Error while notifying new job to stratum miners: {}", e);
+                    }
+                });
+            }
+        }
+
+        true
+    }
+
+    // Total of the premine allocations credited at topoheight 0
+    // It can't overflow as it has been verified against the maximum supply
+    pub fn get_premine_supply(&self) -> u64 {
+        self.premine.iter().map(|(_, amount)| amount).sum()
+    }
+
     // Get the current top block hash in chain
     pub async fn get_top_block_hash(&self) -> Result<Hash, BlockchainError> {
         let storage = self.storage.read().await;
@@ -693,7 +1528,7 @@ impl<S: Storage> Blockchain<S> {
         }
 
         // block must be ordered and in stable height
-        if block_height + STABLE_LIMIT > height || !provider.is_block_topological_ordered(hash).await {
+        if block_height + self.chain_params.stable_limit > height || !provider.is_block_topological_ordered(hash).await {
             return Ok(false)
         }
 
@@ -724,10 +1559,11 @@ impl<S: Storage> Blockchain<S> {
         }
 
         // now lets check all blocks until STABLE_LIMIT height before the block
-        let stable_point = if block_height >= STABLE_LIMIT {
-            block_height - STABLE_LIMIT
+        let stable_limit = self.chain_params.stable_limit;
+        let stable_point = if block_height >= stable_limit {
+            block_height - stable_limit
         } else {
-            STABLE_LIMIT - block_height
+            stable_limit - block_height
         };
         let mut i = block_height - 1;
         let mut pre_blocks = HashSet::new();
@@ -850,6 +1686,15 @@ impl<S: Storage> Blockchain<S> {
         I: IntoIterator<Item = &'a Hash> + Copy,
     {
         debug!("Searching for common base for tips {}", tips.into_iter().map(|h| h.to_string()).collect::<Vec<String>>().join(", "));
+        let mut key: Vec<Hash> = tips.into_iter().cloned().collect();
+        key.sort();
+
+        let mut cache = self.common_base_cache.lock().await;
+        if let Some((base_hash, base_height)) = cache.get(&key) {
+            trace!("Common base {} with height {} found in cache", base_hash, base_height);
+            return Ok((base_hash.clone(), *base_height))
+        }
+
         let mut best_height = 0;
         // first, we check the best (highest) height of all tips
         for hash in tips.into_iter() {
@@ -882,16 +1727,24 @@ impl<S: Storage> Blockchain<S> {
         // and we want the lowest height
         let (base_hash, base_height) = bases.remove(bases.len() - 1);
         debug!("Common base {} with height {} on {}", base_hash, base_height, bases.len() + 1);
+        cache.put(key, (base_hash.clone(), base_height));
+
         Ok((base_hash, base_height))
     }
 
-    async fn build_reachability(&self, storage: &S, hash: Hash) -> Result<HashSet<Hash>, BlockchainError> {
+    async fn build_reachability(&self, storage: &S, hash: Hash) -> Result<Arc<HashSet<Hash>>, BlockchainError> {
+        let mut cache = self.reachability_cache.lock().await;
+        if let Some(set) = cache.get(&hash) {
+            trace!("Reachability of {} found in cache", hash);
+            return Ok(set.clone())
+        }
+
         let mut set = HashSet::new();
         let mut stack: VecDeque<(Hash, u64)> = VecDeque::new();
-        stack.push_back((hash, 0));
+        stack.push_back((hash.clone(), 0));
     
         while let Some((current_hash, current_level)) = stack.pop_back() {
-            if current_level >= 2 * STABLE_LIMIT {
+            if current_level >= 2 * self.chain_params.stable_limit {
                 trace!("Level limit reached, adding {}", current_hash);
                 set.insert(current_hash);
             } else {
@@ -906,6 +1759,9 @@ impl<S: Storage> Blockchain<S> {
             }
         }
 
+        let set = Arc::new(set);
+        cache.put(hash, set.clone());
+
         Ok(set)
     }
 
@@ -1022,19 +1878,26 @@ impl<S: Storage> Blockchain<S> {
     }
 
     // find the sum of work done
-    pub async fn find_tip_work_score<P>(&self, provider: &P, hash: &Hash, base: &Hash, base_height: u64) -> Result<(HashSet<Hash>, CumulativeDifficulty), BlockchainError>
+    pub async fn find_tip_work_score<P>(&self, provider: &P, hash: &Hash, base: &Hash, base_height: u64) -> Result<CumulativeDifficulty, BlockchainError>
     where
-        P: DifficultyProvider + DagOrderProvider
+        P: DifficultyProvider + DagOrderProvider + WorkScoreProvider
     {
+        let key = (hash.clone(), base.clone(), base_height);
         let mut cache = self.tip_work_score_cache.lock().await;
-        if let Some(value) = cache.get(&(hash.clone(), base.clone(), base_height)) {
-            trace!("Found tip work score in cache: set [{}], height: {}", value.0.iter().map(|h| h.to_string()).collect::<Vec<String>>().join(", "), value.1);
-            return Ok(value.clone())
+        if let Some(score) = cache.get(&key) {
+            trace!("Found tip work score in cache: {}", score);
+            return Ok(*score)
+        }
+
+        let base_topoheight = provider.get_topo_height_for_hash(base).await?;
+        if let Some(score) = provider.get_work_score(hash, base, base_topoheight).await? {
+            trace!("Found tip work score in storage: {}", score);
+            cache.put(key, score);
+            return Ok(score)
         }
 
         let block = provider.get_block_header_by_hash(hash).await?;
         let mut map: HashMap<Hash, CumulativeDifficulty> = HashMap::new();
-        let base_topoheight = provider.get_topo_height_for_hash(base).await?;
         for hash in block.get_tips() {
             if !map.contains_key(hash) {
                 let is_ordered = provider.is_block_topological_ordered(hash).await;
@@ -1049,17 +1912,16 @@ impl<S: Storage> Blockchain<S> {
         }
         map.insert(hash.clone(), provider.get_difficulty_for_block_hash(hash).await?.into());
 
-        let mut set = HashSet::with_capacity(map.len());
         let mut score = CumulativeDifficulty::zero();
-        for (hash, value) in map {
-            set.insert(hash);
-            score += value;
+        for value in map.into_values() {
+            score = score.checked_add(value).ok_or(BlockchainError::Overflow)?;
         }
 
         // save this result in cache
-        cache.put((hash.clone(), base.clone(), base_height), (set.clone(), score));
+        provider.set_work_score(hash, base, base_topoheight, &score).await?;
+        cache.put(key, score);
 
-        Ok((set, score))
+        Ok(score)
     }
 
     // find the best tip (highest cumulative difficulty)
@@ -1071,7 +1933,7 @@ impl<S: Storage> Blockchain<S> {
 
         let mut scores = Vec::with_capacity(tips.len());
         for hash in tips {
-            let (_, cumulative_difficulty) = self.find_tip_work_score::<S>(storage, hash, base, base_height).await?;
+            let cumulative_difficulty = self.find_tip_work_score::<S>(storage, hash, base, base_height).await?;
             scores.push((hash, cumulative_difficulty));
         }
 
@@ -1190,7 +2052,7 @@ impl<S: Storage> Blockchain<S> {
         let height = blockdag::calculate_height_at_tips(provider, tips.clone().into_iter()).await?;
         // Simulator is enabled, don't calculate difficulty
         if height <= 1 || self.is_simulator_enabled() {
-            return Ok((get_minimum_difficulty(self.get_network()), difficulty::P))
+            return Ok((self.chain_params.minimum_difficulty, difficulty::P))
         }
 
         // Search the highest difficulty available
@@ -1207,8 +2069,8 @@ impl<S: Storage> Blockchain<S> {
         let p = provider.get_estimated_covariance_for_block_hash(best_tip).await?;
 
         // Get the minimum difficulty configured
-        let minimum_difficulty = get_minimum_difficulty(self.get_network());
-        let (difficulty, p_new) = difficulty::calculate_difficulty(parent_newest_tip_timestamp, newest_tip_timestamp, biggest_difficulty, p, minimum_difficulty);
+        let minimum_difficulty = self.chain_params.minimum_difficulty;
+        let (difficulty, p_new) = difficulty::calculate_difficulty(parent_newest_tip_timestamp, newest_tip_timestamp, biggest_difficulty, p, minimum_difficulty, self.chain_params.block_time_millis);
         Ok((difficulty, p_new))
     }
 
@@ -1252,6 +2114,37 @@ impl<S: Storage> Blockchain<S> {
         &self.rpc
     }
 
+    // Get the capabilities enabled on this node, except the P2P ones
+    // This doesn't lock the P2P module, so it can be called from it
+    pub async fn get_capabilities_for_storage(&self, storage: &S) -> Result<Capabilities, BlockchainError> {
+        let mut capabilities = Capabilities::empty();
+        capabilities.set(Capabilities::PRUNED, storage.get_pruned_topoheight().await?.is_some());
+        capabilities.set(Capabilities::AUTO_PRUNE, self.auto_prune_keep_n_blocks.is_some());
+        capabilities.set(Capabilities::SIMULATOR, self.simulator.is_some());
+        capabilities.set(Capabilities::SKIP_POW_VERIFICATION, self.skip_pow_verification);
+
+        if let Some(rpc) = self.rpc.read().await.as_ref() {
+            capabilities.insert(Capabilities::RPC_SERVER);
+            capabilities.set(Capabilities::GETWORK_SERVER, rpc.getwork_server().is_some());
+        }
+
+        Ok(capabilities)
+    }
+
+    // Get all the capabilities enabled on this node
+    pub async fn get_capabilities(&self) -> Result<Capabilities, BlockchainError> {
+        let mut capabilities = {
+            let storage = self.storage.read().await;
+            self.get_capabilities_for_storage(&storage).await?
+        };
+
+        if let Some(p2p) = self.p2p.read().await.as_ref() {
+            capabilities.insert(p2p.get_capabilities());
+        }
+
+        Ok(capabilities)
+    }
+
     // Returns the storage used for blockchain
     pub fn get_storage(&self) -> &RwLock<S> {
         &self.storage
@@ -1262,6 +2155,48 @@ impl<S: Storage> Blockchain<S> {
         &self.mempool
     }
 
+    // Get a view of the data committed at the current topoheight, readable without the storage lock
+    // Returns None if a reorg, a rewind or a pruning is rewriting the committed data
+    pub fn get_committed_view(&self) -> Option<CommittedView<'_, S>> {
+        let generation = self.storage_rewrites.get_generation()?;
+        let topoheight = self.get_topo_height();
+        // a rewrite may have started while reading the topoheight
+        if !self.storage_rewrites.is_unchanged_since(generation) {
+            return None
+        }
+
+        Some(CommittedView::new(&self.storage_reader, topoheight, generation, &self.storage_rewrites))
+    }
+
+    // Get the last balance of an account at the current topoheight
+    // RPC queries don't wait behind the block commits unless the committed data is rewritten
+    pub async fn get_committed_balance(&self, key: &PublicKey, asset: &Hash) -> Result<(u64, VersionedBalance), BlockchainError> {
+        if let Some(view) = self.get_committed_view() {
+            let result = view.get_storage().get_balance_at_maximum_topoheight(key, asset, view.get_topoheight()).await;
+            if view.is_valid() {
+                return result?.ok_or_else(|| BlockchainError::NoBalance(key.as_address(self.network.is_mainnet())))
+            }
+            debug!("Committed data rewritten while reading balance, reading it again with lock");
+        }
+
+        let storage = self.storage.read().await;
+        storage.get_last_balance(key, asset).await
+    }
+
+    // Get the last nonce of an account at the current topoheight
+    pub async fn get_committed_nonce(&self, key: &PublicKey) -> Result<(u64, VersionedNonce), BlockchainError> {
+        if let Some(view) = self.get_committed_view() {
+            let result = view.get_storage().get_nonce_at_maximum_topoheight(key, view.get_topoheight()).await;
+            if view.is_valid() {
+                return result?.ok_or_else(|| BlockchainError::NoNonce(key.as_address(self.network.is_mainnet())))
+            }
+            debug!("Committed data rewritten while reading nonce, reading it again with lock");
+        }
+
+        let storage = self.storage.read().await;
+        storage.get_last_nonce(key).await
+    }
+
     // Add a tx to the mempool, its hash will be computed
     pub async fn add_tx_to_mempool(&self, tx: Transaction, broadcast: bool) -> Result<(), BlockchainError> {
         let hash = tx.hash();
@@ -1276,6 +2211,79 @@ impl<S: Storage> Blockchain<S> {
 
     // Add a tx to the mempool with the given hash, it will verify the TX and check that it is not already in mempool or in blockchain
     // and its validity (nonce, balance, etc...)
+    // Get the next nonce to use for an account, including its txs in mempool
+    async fn get_next_nonce_for_storage(&self, storage: &S, key: &PublicKey) -> Result<u64, BlockchainError> {
+        if let Some(cache) = self.mempool.read().await.get_cache_for(key) {
+            return Ok(cache.get_next_nonce())
+        }
+
+        if !storage.has_nonce(key).await? {
+            return Ok(0)
+        }
+
+        let (_, version) = storage.get_last_nonce(key).await?;
+        Ok(version.get_nonce())
+    }
+
+    // Record a TX in conflict with a mempool TX and notify it
+    // Only the last conflicts are kept
+    async fn on_tx_conflict(&self, conflict: TransactionConflict) {
+        info!("TX {} is in conflict with TX {} in mempool (nonce {} of {})", conflict.conflicting_tx_hash, conflict.mempool_tx_hash, conflict.nonce, conflict.owner);
+        if let Some(rpc) = self.rpc.read().await.as_ref() {
+            if rpc.is_event_tracked(&NotifyEvent::TransactionConflictDetected).await {
+                let rpc = rpc.clone();
+                let value = json!(conflict);
+                spawn_task("rpc-notify-tx-conflict", async move {
+                    if let Err(e) = rpc.notify_clients(&NotifyEvent::TransactionConflictDetected, value).await {
+                        debug!("Error while broadcasting event TransactionConflictDetected to websocket: {}", e);
+                    }
+                });
+            }
+        }
+
+        let mut conflicts = self.tx_conflicts.lock().await;
+        if conflicts.len() >= MAX_TX_CONFLICTS {
+            conflicts.pop_front();
+        }
+        conflicts.push_back(conflict);
+    }
+
+    // Get the last TXs detected in conflict with a mempool TX, only those involving the TX hash if set
+    pub async fn get_tx_conflicts(&self, hash: Option<&Hash>) -> Vec<TransactionConflict> {
+        self.tx_conflicts.lock().await.iter()
+            .filter(|conflict| hash.map_or(true, |hash| conflict.mempool_tx_hash == *hash || conflict.conflicting_tx_hash == *hash))
+            .cloned()
+            .collect()
+    }
+
+    // Reserve the next nonce available for an account for a limited time in seconds
+    // Each call reserves a different nonce until the reservation expires, is released or consumed by a tx
+    pub async fn reserve_nonce(&self, key: &PublicKey, duration: u64) -> Result<NonceReservation, BlockchainError> {
+        if duration == 0 || duration > NONCE_RESERVATION_MAX_DURATION {
+            return Err(BlockchainError::InvalidNonceReservationDuration(duration, NONCE_RESERVATION_MAX_DURATION))
+        }
+
+        let storage = self.storage.read().await;
+        // lock the reservations while computing the next nonce so two calls can't reserve the same nonce
+        let mut reservations = self.nonce_reservations.lock().await;
+        let next_nonce = self.get_next_nonce_for_storage(&storage, key).await?;
+        reservations.reserve(key, next_nonce, duration * MILLIS_PER_SECOND, get_current_time_in_millis())
+    }
+
+    // Extend a nonce reservation for the duration in seconds from now
+    pub async fn renew_nonce_reservation(&self, key: &PublicKey, id: u64, duration: u64) -> Result<NonceReservation, BlockchainError> {
+        if duration == 0 || duration > NONCE_RESERVATION_MAX_DURATION {
+            return Err(BlockchainError::InvalidNonceReservationDuration(duration, NONCE_RESERVATION_MAX_DURATION))
+        }
+
+        self.nonce_reservations.lock().await.renew(key, id, duration * MILLIS_PER_SECOND, get_current_time_in_millis())
+    }
+
+    // Release a nonce reservation before its expiration
+    pub async fn release_nonce_reservation(&self, key: &PublicKey, id: u64) -> Result<(), BlockchainError> {
+        self.nonce_reservations.lock().await.release(key, id)
+    }
+
     pub async fn add_tx_to_mempool_with_storage_and_hash<'a>(&'a self, storage: &S, tx: Arc<Transaction>, hash: Hash, broadcast: bool) -> Result<(), BlockchainError> {
         let tx_size = tx.size();
         if tx_size > MAX_TRANSACTION_SIZE {
@@ -1295,13 +2303,36 @@ impl<S: Storage> Blockchain<S> {
             }
 
             let current_topoheight = self.get_topo_height();
+            // TX from the same owner and nonce replaced by this one
+            let mut replaced_tx_hash = None;
             // get the highest nonce available
             // if presents, it means we have at least one tx from this owner in mempool
             if let Some(cache) = mempool.get_cache_for(tx.get_source()) {
-                // we accept to delete a tx from mempool if the new one has a higher fee
-                if let Some(hash) = cache.has_tx_with_same_nonce(tx.get_nonce()) {
+                if let Some(mempool_tx_hash) = cache.has_tx_with_same_nonce(tx.get_nonce()) {
                     // A TX with the same nonce is already in mempool
-                    return Err(BlockchainError::TxNonceAlreadyUsed(tx.get_nonce(), hash.as_ref().clone()))
+                    let mempool_tx_hash = mempool_tx_hash.as_ref().clone();
+                    // we accept to delete a tx from mempool if the new one has a high enough fee
+                    // and no other TX of the owner is built on top of its balances
+                    if cache.get_txs().len() == 1 {
+                        let required_fee = get_replacement_min_fee(mempool.get_sorted_tx(&mempool_tx_hash)?.get_fee());
+                        if tx.get_fee() < required_fee {
+                            debug!("TX {} can't replace TX {}, fee {} is below {}", hash, mempool_tx_hash, tx.get_fee(), required_fee);
+                            return Err(BlockchainError::FeesToLowToOverride(required_fee, tx.get_fee()))
+                        }
+                        replaced_tx_hash = Some(mempool_tx_hash);
+                    } else {
+                        drop(mempool);
+
+                        self.on_tx_conflict(TransactionConflict {
+                            owner: tx.get_source().as_address(storage.is_mainnet()),
+                            nonce: tx.get_nonce(),
+                            mempool_tx_hash: mempool_tx_hash.clone(),
+                            conflicting_tx_hash: hash,
+                            block_hash: None,
+                            timestamp: get_current_time_in_millis()
+                        }).await;
+                        return Err(BlockchainError::TxNonceAlreadyUsed(tx.get_nonce(), mempool_tx_hash))
+                    }
                 }
 
                 // check that the nonce is in the range
@@ -1311,9 +2342,29 @@ impl<S: Storage> Blockchain<S> {
                 }
             }
 
-            mempool.add_tx(storage, current_topoheight, hash.clone(), tx.clone(), tx_size).await?;
+            let block_version = self.get_version_at_height(self.get_height() + 1);
+            if let Some(replaced_tx_hash) = replaced_tx_hash {
+                // Its owner cache is deleted so the new TX is verified against the stored balances
+                let replaced_tx = mempool.get_tx(&replaced_tx_hash)?;
+                let replaced_tx_size = mempool.get_sorted_tx(&replaced_tx_hash)?.get_size();
+                mempool.remove_tx(&replaced_tx_hash)?;
+
+                if let Err(e) = mempool.add_tx(storage, current_topoheight, block_version, hash.clone(), tx.clone(), tx_size).await {
+                    debug!("TX {} can't replace TX {}, adding it back: {}", hash, replaced_tx_hash, e);
+                    if let Err(e) = mempool.add_tx(storage, current_topoheight, block_version, replaced_tx_hash.clone(), replaced_tx, replaced_tx_size).await {
+                        warn!("Error while adding back TX {} in mempool: {}", replaced_tx_hash, e);
+                    }
+                    return Err(e)
+                }
+                info!("TX {} replaced TX {} with nonce {} in mempool by paying a higher fee", hash, replaced_tx_hash, tx.get_nonce());
+            } else {
+                mempool.add_tx(storage, current_topoheight, block_version, hash.clone(), tx.clone(), tx_size).await?;
+            }
         }
 
+        // the nonce is now used, release its reservation if any
+        self.nonce_reservations.lock().await.consume(tx.get_source(), tx.get_nonce());
+
         if broadcast {
             // P2p broadcast to others peers
             if let Some(p2p) = self.p2p.read().await.as_ref() {
@@ -1327,7 +2378,7 @@ impl<S: Storage> Blockchain<S> {
             // broadcast to websocket this tx
             if let Some(rpc) = self.rpc.read().await.as_ref() {
                 // Notify miners if getwork is enabled
-                if let Some(getwork) = rpc.getwork_server() {
+                if let Some(getwork) = rpc.getwork_server().as_ref().filter(|_| !self.is_standby()) {
                     let getwork = getwork.clone();
                     spawn_task("tx-notify-new-job", async move {
                         if let Err(e) = getwork.notify_new_job_rate_limited().await {
@@ -1336,6 +2387,15 @@ impl<S: Storage> Blockchain<S> {
                     });
                 }
 
+                if let Some(stratum) = rpc.stratum_server().as_ref().filter(|_| !self.is_standby()) {
+                    let stratum = stratum.clone();
+                    spawn_task("tx-notify-new-stratum-job", async move {
+                        if let Err(e) = stratum.notify_new_job_rate_limited().await {
+                            debug!("Error while notifying stratum miners for new tx: {}", e);
+                        }
+                    });
+                }
+
                 if rpc.is_event_tracked(&NotifyEvent::TransactionAddedInMempool).await {
                     let data = RPCTransaction::from_tx(&tx, &hash, storage.is_mainnet());
                     let data: TransactionResponse<'_> = TransactionResponse {
@@ -1360,9 +2420,16 @@ impl<S: Storage> Blockchain<S> {
         Ok(())
     }
 
-    // this will be used in future for hard fork versions
-    pub fn get_version_at_height(&self, _height: u64) -> u8 {
-        0
+    // Get the block version required at this height based on the hard forks
+    pub fn get_version_at_height(&self, height: u64) -> u8 {
+        hard_fork::get_version_at_height(&self.network, height)
+    }
+
+    // Get the maximum size of a block at this height
+    // Dev network may use a lower limit than the hard fork
+    pub fn get_max_block_size_at_height(&self, height: u64) -> usize {
+        let hard_fork = hard_fork::get_hard_fork_at_height(&self.network, height);
+        self.chain_params.max_block_size.min(hard_fork.max_block_size)
     }
 
     // Get a block template for the new block work (mining)
@@ -1436,7 +2503,7 @@ impl<S: Storage> Blockchain<S> {
 
                     let distance = self.calculate_distance_from_mainchain(storage, &hash).await?;
                     debug!("Distance from mainchain for tip {} is {}", hash, distance);
-                    if distance <= current_height && current_height - distance >= STABLE_LIMIT {
+                    if distance <= current_height && current_height - distance >= self.chain_params.stable_limit {
                         warn!("Tip {} is not selected for mining: too far from mainchain (distance: {}, height: {})", hash, distance, current_height);
                         continue;
                     }
@@ -1458,7 +2525,22 @@ impl<S: Storage> Blockchain<S> {
         }
 
         let height = blockdag::calculate_height_at_tips(storage, sorted_tips.iter()).await?;
-        let block = BlockHeader::new(self.get_version_at_height(height), height, get_current_time_in_millis(), sorted_tips, extra_nonce, address, IndexSet::new());
+        let version = self.get_version_at_height(height);
+        // our local time may be behind the minimum timestamp allowed
+        let mut timestamp = get_current_time_in_millis();
+        if !sorted_tips.is_empty() {
+            let minimum_timestamp = if hard_fork::is_median_timestamp_enabled(&self.network, version) {
+                blockdag::calculate_median_timestamp_at_tips(storage, sorted_tips.iter(), self.chain_params.timestamp_median_blocks).await?
+            } else {
+                let mut parents_timestamp = 0;
+                for hash in sorted_tips.iter() {
+                    parents_timestamp = parents_timestamp.max(storage.get_timestamp_for_block_hash(hash).await?);
+                }
+                parents_timestamp
+            };
+            timestamp = timestamp.max(minimum_timestamp);
+        }
+        let block = BlockHeader::new(version, height, timestamp, sorted_tips, extra_nonce, address, IndexSet::new());
 
         Ok(block)
     }
@@ -1466,13 +2548,31 @@ impl<S: Storage> Blockchain<S> {
     // Get the mining block template for miners
     // This function is called when a miner request a new block template
     // We create a block candidate with selected TXs from mempool
+    // The last template is cached and only rebuilt when the tips or the mempool have changed
     pub async fn get_block_template_for_storage(&self, storage: &S, address: PublicKey) -> Result<BlockHeader, BlockchainError> {
-        let mut block = self.get_block_header_template_for_storage(storage, address).await?;
+        if self.is_standby() {
+            return Err(BlockchainError::StandbyMode)
+        }
 
         trace!("Locking mempool for building block template");
         let mempool = self.mempool.read().await;
         trace!("Mempool locked for building block template");
 
+        let key = (storage.get_tips().await?, self.get_topo_height(), mempool.get_generation());
+        {
+            let cache = self.block_template_cache.lock().await;
+            if let Some((_, header)) = cache.as_ref().filter(|(cached_key, _)| *cached_key == key) {
+                trace!("Using cached block template at height {}", header.get_height());
+                let mut block = header.clone();
+                block.timestamp = get_current_time_in_millis().max(header.timestamp);
+                block.set_extra_nonce(rand::thread_rng().gen::<[u8; EXTRA_NONCE_SIZE]>());
+                block.set_miner(address);
+                return Ok(block)
+            }
+        }
+
+        let mut block = self.get_block_header_template_for_storage(storage, address).await?;
+
         // use the mempool cache to get all availables txs grouped by account
         let caches = mempool.get_caches();
         let mut entries: Vec<Vec<TxSelectorEntry>> = Vec::with_capacity(caches.len());
@@ -1497,11 +2597,12 @@ impl<S: Storage> Blockchain<S> {
         // data used to verify txs
         let topoheight = self.get_topo_height();
         trace!("build chain state for block template");
-        let mut chain_state = ChainState::new(storage, topoheight);
+        let mut chain_state = ChainState::new(storage, topoheight, block.get_version());
 
+        let max_block_size = self.get_max_block_size_at_height(block.get_height());
         let mut failed_sources = HashSet::new();
         while let Some(TxSelectorEntry { size, hash, tx }) = tx_selector.next() {
-            if block_size + total_txs_size + size >= MAX_BLOCK_SIZE {
+            if block_size + total_txs_size + size >= max_block_size {
                 break;
             }
 
@@ -1525,6 +2626,8 @@ impl<S: Storage> Blockchain<S> {
             }
         }
 
+        *self.block_template_cache.lock().await = Some((key, block.clone()));
+
         Ok(block)
     }
 
@@ -1552,15 +2655,86 @@ impl<S: Storage> Blockchain<S> {
     }
 
     // Add a new block in chain
+    // The block is queued to the block processing task so only one block is written at a time
+    // Wait until the queue has space and the block is processed
     pub async fn add_new_block(&self, block: Block, broadcast: bool, mining: bool) -> Result<(), BlockchainError> {
-        let mut storage = self.storage.write().await;
-        self.add_new_block_for_storage(&mut storage, block, broadcast, mining).await
+        let (submission, receiver) = BlockSubmission::new(block, broadcast, mining);
+        self.block_queue.send(submission).await
+            .map_err(|_| BlockchainError::BlockQueueClosed)?;
+
+        receiver.await.map_err(|_| BlockchainError::BlockQueueClosed)?
+    }
+
+    // Process the blocks submitted one by one
+    // The task stops once the blockchain is dropped
+    async fn block_processing_loop(blockchain: Weak<Self>, mut receiver: mpsc::Receiver<BlockSubmission>) {
+        while let Some(submission) = receiver.recv().await {
+            let Some(blockchain) = blockchain.upgrade() else {
+                break;
+            };
+
+            blockchain.process_block_submission(submission).await;
+        }
+    }
+
+    // The block is validated with a read lock on the storage so other requests aren't blocked
+    // and the write lock is only held to commit it
+    async fn process_block_submission(&self, submission: BlockSubmission) {
+        let BlockSubmission { block, broadcast, mining, queued_at, response } = submission;
+        let hash = block.hash();
+        let queue_time = queued_at.elapsed();
+
+        let start = Instant::now();
+        let context = {
+            let storage = self.storage.read().await;
+            self.validate_block(&storage, block, mining).await
+        };
+        let validation_time = start.elapsed();
+
+        let start = Instant::now();
+        let result = match context {
+            Ok(context) => {
+                let mut storage = self.storage.write().await;
+                self.commit_block(&mut storage, context, broadcast, mining).await
+            },
+            Err(e) => Err(e)
+        };
+        let commit_time = start.elapsed();
+
+        if let Err(e) = result.as_ref() {
+            debug!("Block {} rejected after {}ms in queue: {}", hash, queue_time.as_millis(), e);
+        }
+        self.block_processing_metrics.lock().await.record(hash, result.is_ok(), queue_time, validation_time, commit_time);
+
+        // submitter may not wait for the result anymore
+        if response.send(result).is_err() {
+            trace!("Block submitter dropped before the end of its processing");
+        }
+    }
+
+    // Get the metrics of the block processing task
+    pub async fn get_block_processing_stats(&self) -> BlockProcessingStats {
+        let queue_capacity = self.block_queue.max_capacity();
+        let queue_size = queue_capacity - self.block_queue.capacity();
+        self.block_processing_metrics.lock().await.to_stats(queue_size, queue_capacity)
     }
 
     // Add a new block in chain using the requested storage
     pub async fn add_new_block_for_storage(&self, storage: &mut S, block: Block, broadcast: bool, mining: bool) -> Result<(), BlockchainError> {
+        let context = self.validate_block(storage, block, mining).await?;
+        self.commit_block(storage, context, broadcast, mining).await
+    }
+
+    // Verify the block and its transactions against the current chain state
+    // Nothing is written in storage
+    pub async fn validate_block(&self, storage: &S, block: Block, mining: bool) -> Result<BlockValidationContext, BlockchainError> {
         let start = Instant::now();
 
+        // Only the blocks of the primary are applied in standby mode
+        if mining && self.is_standby() {
+            return Err(BlockchainError::StandbyMode)
+        }
+
         // Verify that the block is on the correct version
         if block.get_version() != self.get_version_at_height(block.get_height()) {
             return Err(BlockchainError::InvalidBlockVersion)
@@ -1574,7 +2748,7 @@ impl<S: Storage> Blockchain<S> {
         }
 
         let current_timestamp = get_current_time_in_millis(); 
-        if block.get_timestamp() > current_timestamp + TIMESTAMP_IN_FUTURE_LIMIT { // accept 2s in future
+        if block.get_timestamp() > current_timestamp + self.chain_params.timestamp_future_limit_millis {
             debug!("Block timestamp is too much in future!");
             return Err(BlockchainError::TimestampIsInFuture(current_timestamp, block.get_timestamp()));
         }
@@ -1605,9 +2779,10 @@ impl<S: Storage> Blockchain<S> {
 
         // block contains header and full TXs
         let block_size = block.size();
-        if block_size > MAX_BLOCK_SIZE {
-            debug!("Block size ({} bytes) is greater than the limit ({} bytes)", block.size(), MAX_BLOCK_SIZE);
-            return Err(BlockchainError::InvalidBlockSize(MAX_BLOCK_SIZE, block.size()));
+        let max_block_size = self.get_max_block_size_at_height(block.get_height());
+        if block_size > max_block_size {
+            debug!("Block size ({} bytes) is greater than the limit ({} bytes)", block.size(), max_block_size);
+            return Err(BlockchainError::InvalidBlockSize(max_block_size, block.size()));
         }
 
         for tip in block.get_tips() {
@@ -1638,18 +2813,32 @@ impl<S: Storage> Blockchain<S> {
             return Err(BlockchainError::InvalidReachability)
         }
 
-        for hash in block.get_tips() {
-            let previous_timestamp = storage.get_timestamp_for_block_hash(hash).await?;
-            // block timestamp can't be less than previous block.
-            if block.get_timestamp() < previous_timestamp {
-                debug!("Invalid block timestamp, parent ({}) is less than new block {}", hash, block_hash);
-                return Err(BlockchainError::TimestampIsLessThanParent(block.get_timestamp()));
+        if hard_fork::is_median_timestamp_enabled(&self.network, block.get_version()) {
+            if tips_count > 0 {
+                // block timestamp can't be less than the median timestamp of its past blocks
+                // a single parent can't be used to manipulate the difficulty
+                let median_timestamp = blockdag::calculate_median_timestamp_at_tips(storage, block.get_tips().iter(), self.chain_params.timestamp_median_blocks).await?;
+                if block.get_timestamp() < median_timestamp {
+                    debug!("Invalid block timestamp {} for {}, median timestamp of past blocks is {}", block.get_timestamp(), block_hash, median_timestamp);
+                    return Err(BlockchainError::TimestampIsLessThanMedian(block.get_timestamp(), median_timestamp));
+                }
             }
+        } else {
+            for hash in block.get_tips() {
+                let previous_timestamp = storage.get_timestamp_for_block_hash(hash).await?;
+                // block timestamp can't be less than previous block.
+                if block.get_timestamp() < previous_timestamp {
+                    debug!("Invalid block timestamp, parent ({}) is less than new block {}", hash, block_hash);
+                    return Err(BlockchainError::TimestampIsLessThanParent(block.get_timestamp()));
+                }
+            }
+        }
 
+        for hash in block.get_tips() {
             trace!("calculate distance from mainchain for tips: {}", hash);
             let distance = self.calculate_distance_from_mainchain(storage, hash).await?;
-            if distance <= current_height && current_height - distance >= STABLE_LIMIT {
-                debug!("{} with hash {} have deviated too much, maximum allowed is {} (current height: {}, distance: {})", block, block_hash, STABLE_LIMIT, current_height, distance);
+            if distance <= current_height && current_height - distance >= self.chain_params.stable_limit {
+                debug!("{} with hash {} have deviated too much, maximum allowed is {} (current height: {}, distance: {})", block, block_hash, self.chain_params.stable_limit, current_height, distance);
                 return Err(BlockchainError::BlockDeviation)
             }
         }
@@ -1679,7 +2868,7 @@ impl<S: Storage> Blockchain<S> {
         let (difficulty, p) = self.verify_proof_of_work(storage, &pow_hash, block.get_tips().iter()).await?;
         debug!("PoW is valid for difficulty {}", difficulty);
 
-        let mut current_topoheight = self.get_topo_height();
+        let current_topoheight = self.get_topo_height();
         // Transaction verification
         // Here we are going to verify all TXs in the block
         // For this, we must select TXs that are not doing collisions with other TXs in block
@@ -1695,7 +2884,7 @@ impl<S: Storage> Blockchain<S> {
             }
 
             trace!("verifying {} TXs in block {}", txs_len, block_hash);
-            let mut chain_state = ChainState::new(storage, current_topoheight);
+            let mut chain_state = ChainState::new(storage, current_topoheight, block.get_version());
             // Cache to retrieve only one time all TXs hashes until stable height
             let mut all_parents_txs: Option<HashSet<Hash>> = None;
             let mut batch = Vec::with_capacity(block.get_txs_count());
@@ -1760,9 +2949,59 @@ impl<S: Storage> Blockchain<S> {
 
             debug!("proof verifications of TXs ({}) in block {}", batch.iter().map(|v| v.hash().to_string()).collect::<Vec<String>>().join(","), block_hash);
             // Verify all valid transactions in one batch
+            // Signatures, sigma proofs and range proofs are each checked together for the whole block
             Transaction::verify_batch(batch.as_slice(), &mut chain_state).await?;
         }
 
+        let tips = storage.get_tips().await?;
+        Ok(BlockValidationContext {
+            block,
+            block_hash,
+            block_size,
+            difficulty,
+            p,
+            tips,
+            start
+        })
+    }
+
+    // Save a validated block in storage and order the DAG again
+    // The block is validated again if the chain changed since its validation
+    pub async fn commit_block(&self, storage: &mut S, context: BlockValidationContext, broadcast: bool, mining: bool) -> Result<(), BlockchainError> {
+        let context = if storage.get_tips().await? != context.tips {
+            debug!("Chain changed since the validation of block {}, validating it again", context.block_hash);
+            self.validate_block(storage, context.block, mining).await?
+        } else {
+            context
+        };
+
+        let BlockValidationContext { block, block_hash, block_size, difficulty, p, start, .. } = context;
+        let tips_count = block.get_tips().len();
+        let current_height = self.get_height();
+        let mut current_topoheight = self.get_topo_height();
+
+        // Detect the mempool TXs using the same nonce as a TX of the block, they will be deleted from mempool
+        let conflicts = {
+            let mempool = self.mempool.read().await;
+            block.get_transactions().iter().zip(block.get_txs_hashes())
+                .filter_map(|(tx, hash)| {
+                    let mempool_tx_hash = mempool.get_cache_for(tx.get_source())?.has_tx_with_same_nonce(tx.get_nonce())?;
+                    (mempool_tx_hash.as_ref() != hash).then(|| TransactionConflict {
+                        owner: tx.get_source().as_address(storage.is_mainnet()),
+                        nonce: tx.get_nonce(),
+                        mempool_tx_hash: mempool_tx_hash.as_ref().clone(),
+                        conflicting_tx_hash: hash.clone(),
+                        block_hash: Some(block_hash.clone()),
+                        timestamp: get_current_time_in_millis()
+                    })
+                })
+                .collect::<Vec<_>>()
+        };
+
+        for conflict in conflicts {
+            self.on_tx_conflict(conflict).await;
+        }
+
         // Save transactions & block
         let (block, txs) = block.split();
         let block = block.to_arc();
@@ -1777,8 +3016,7 @@ impl<S: Storage> Blockchain<S> {
                 GENESIS_BLOCK_DIFFICULTY.into()
             } else {
                 let (base, base_height) = self.find_common_base(storage, block.get_tips()).await?;
-                let (_, cumulative_difficulty) = self.find_tip_work_score::<S>(&storage, &block_hash, &base, base_height).await?;
-                cumulative_difficulty
+                self.find_tip_work_score::<S>(&storage, &block_hash, &base, base_height).await?
             };
             storage.set_cumulative_difficulty_for_block_hash(&block_hash, cumulative_difficulty).await?;
             debug!("Cumulative difficulty for block {}: {}", block_hash, cumulative_difficulty);
@@ -1818,6 +3056,8 @@ impl<S: Storage> Blockchain<S> {
         let mut highest_topo = 0;
         // Tells if the new block added is ordered in DAG or not
         let block_is_ordered = full_order.contains(&block_hash);
+        // set once the reorg starts to rewrite the committed data, until the commit is done
+        let mut _rewrite = None;
         {
             let mut is_written = base_topo_height == 0;
             let mut skipped = 0;
@@ -1844,6 +3084,15 @@ impl<S: Storage> Blockchain<S> {
                         is_written = true;
                     }
 
+                    // only once, from the lowest topoheight reverted
+                    if topoheight == base_topo_height + skipped {
+                        _rewrite = Some(self.storage_rewrites.begin());
+
+                        // scores and orders computed from a base above are not valid anymore
+                        storage.delete_work_scores_from_topoheight(topoheight).await?;
+                        self.clear_dag_caches().await;
+                    }
+
                     debug!("Cleaning transactions executions at topo height {} (block {})", topoheight, hash_at_topo);
 
                     let block = storage.get_block_header_by_hash(&hash_at_topo).await?;
@@ -1875,6 +3124,13 @@ impl<S: Storage> Blockchain<S> {
                     storage.delete_versioned_balances_at_topoheight(topoheight).await?;
                     storage.delete_versioned_nonces_at_topoheight(topoheight).await?;
                     storage.delete_registrations_at_topoheight(topoheight).await?;
+                    storage.delete_asset_states_at_topoheight(topoheight).await?;
+                    storage.delete_multisigs_at_topoheight(topoheight).await?;
+                    storage.delete_locked_transfers_at_topoheight(topoheight).await?;
+                    storage.delete_contracts_at_topoheight(topoheight).await?;
+                    storage.delete_contract_data_at_topoheight(topoheight).await?;
+                    storage.delete_assets_at_topoheight(topoheight).await?;
+                    storage.rewind_block_stats(topoheight).await?;
 
                     topoheight += 1;
                 }
@@ -1901,7 +3157,8 @@ impl<S: Storage> Blockchain<S> {
 
                 storage.set_topo_height_for_block(&hash, highest_topo).await?;
                 let past_supply = if highest_topo == 0 {
-                    0
+                    // premine is emitted before the genesis block reward
+                    self.get_premine_supply()
                 } else {
                     storage.get_supply_at_topo_height(highest_topo - 1).await?
                 };
@@ -1943,12 +3200,108 @@ impl<S: Storage> Blockchain<S> {
 
                 // All fees from the transactions executed in this block
                 let mut total_fees = 0;
+                // Count of transactions executed in this block
+                let mut txs_executed = 0;
+                // Coins emitted before the dev fee part is deducted
+                // Genesis block also emits the premine
+                let emission = if highest_topo == 0 { supply } else { block_reward };
                 // Chain State used for the verification
                 trace!("building chain state to execute TXs in block {}", block_hash);
-                let mut chain_state = ApplicableChainState::new(storage, highest_topo);
+                let mut chain_state = ApplicableChainState::new(storage, highest_topo, block.get_version());
+
+                // Credit the premine allocations with the genesis block
+                if highest_topo == 0 {
+                    for (key, amount) in self.premine.iter() {
+                        chain_state.reward_miner(key, *amount).await?;
+                    }
+                }
+
+                // Transactions touching disjoint parts of the state are grouped in a batch
+                // Their proofs are verified in parallel, then they are applied in the block order
+                let mut conflict_detector = ConflictDetector::new();
+                let mut batch: Vec<(&Transaction, &Hash)> = Vec::new();
+                let mut verifications = Vec::new();
 
                 // compute rewards & execute txs
-                for (tx, tx_hash) in block.get_transactions().iter().zip(block.get_txs_hashes()) { // execute all txs
+                // None is used to apply the last batch
+                let txs = block.get_transactions().iter().zip(block.get_txs_hashes());
+                for next in txs.map(Some).chain(iter::once(None)) {
+                    // A conflicting transaction must see the changes of the current batch
+                    let conflict = match next {
+                        Some((tx, tx_hash)) => !conflict_detector.try_add(tx, tx_hash),
+                        None => true
+                    };
+
+                    if conflict {
+                        let results = verify_in_parallel(&mut verifications).await?;
+                        for ((tx, tx_hash), (verification, valid)) in batch.drain(..).zip(verifications.drain(..).zip(results)) {
+                            // Execute the transaction by applying changes in storage
+                            debug!("Executing tx {} in block {} with nonce {}", tx_hash, hash, tx.get_nonce());
+                            if !valid {
+                                warn!("Error while executing TX {} with current DAG org: invalid proofs", tx_hash);
+                                // TX may be orphaned if not added again in good order in next blocks
+                                orphaned_transactions.insert(tx_hash.clone());
+                                continue;
+                            }
+
+                            if let Err(e) = tx.apply_partial_verified(chain_state.as_mut(), verification).await {
+                                warn!("Error while executing TX {} with current DAG org: {}", tx_hash, e);
+                                // TX may be orphaned if not added again in good order in next blocks
+                                orphaned_transactions.insert(tx_hash.clone());
+                                continue;
+                            }
+
+                            // Calculate the new nonce
+                            // This has to be done in case of side blocks where TX B would be before TX A
+                            let next_nonce = nonce_checker.get_new_nonce(tx.get_source(), self.network.is_mainnet())?;
+                            chain_state.as_mut().update_account_nonce(tx.get_source(), next_nonce).await?;
+
+                            // mark tx as executed
+                            chain_state.get_mut_storage().set_tx_executed_in_block(tx_hash, &hash)?;
+
+                            // Register the new asset, its hash is the one of the transaction
+                            if let TransactionType::CreateAsset(payload) = tx.get_data() {
+                                debug!("Registering asset {} ({}) at topoheight {}", tx_hash, payload.name, highest_topo);
+                                let data = AssetData::with_details(highest_topo, payload.decimals, payload.name.clone(), payload.max_supply);
+                                if should_track_events.contains(&NotifyEvent::NewAsset) {
+                                    let value = json!(NewAssetEvent::new(tx_hash.clone(), data.clone()));
+                                    events.entry(NotifyEvent::NewAsset).or_insert_with(Vec::new).push(value);
+                                }
+                                chain_state.get_mut_storage().add_asset(tx_hash, data).await?;
+                            }
+
+                            // Delete the transaction from  the list if it was marked as orphaned
+                            if orphaned_transactions.remove(tx_hash) {
+                                trace!("Transaction {} was marked as orphaned, but got executed again", tx_hash);
+                            }
+
+                            // if the rpc_server is enable, track events
+                            if should_track_events.contains(&NotifyEvent::TransactionExecuted) {
+                                let value = json!(TransactionExecutedEvent {
+                                    tx_hash: Cow::Borrowed(tx_hash),
+                                    block_hash: Cow::Borrowed(&hash),
+                                    topoheight: highest_topo,
+                                });
+                                events.entry(NotifyEvent::TransactionExecuted).or_insert_with(Vec::new).push(value);
+                            }
+
+                            // Increase total tx fees for miner
+                            total_fees += tx.get_fee();
+                            txs_executed += 1;
+                        }
+
+                        conflict_detector.clear();
+                    }
+
+                    let Some((tx, tx_hash)) = next else {
+                        break;
+                    };
+
+                    if conflict {
+                        // Alone in the new batch, it can't conflict
+                        conflict_detector.try_add(tx, tx_hash);
+                    }
+
                     // Link the transaction hash to this block
                     if !chain_state.get_mut_storage().add_block_linked_to_tx_if_not_present(&tx_hash, &hash)? {
                         trace!("Block {} is now linked to tx {}", hash, tx_hash);
@@ -1967,40 +3320,18 @@ impl<S: Storage> Blockchain<S> {
                             continue;
                         }
 
-                        // Execute the transaction by applying changes in storage
-                        debug!("Executing tx {} in block {} with nonce {}", tx_hash, hash, tx.get_nonce());
-                        if let Err(e) = tx.apply_with_partial_verify(chain_state.as_mut()).await {
-                            warn!("Error while executing TX {} with current DAG org: {}", tx_hash, e);
-                            // TX may be orphaned if not added again in good order in next blocks
-                            orphaned_transactions.insert(tx_hash.clone());
-                            continue;
-                        }
-
-                        // Calculate the new nonce
-                        // This has to be done in case of side blocks where TX B would be before TX A
-                        let next_nonce = nonce_checker.get_new_nonce(tx.get_source(), self.network.is_mainnet())?;
-                        chain_state.as_mut().update_account_nonce(tx.get_source(), next_nonce).await?;
-
-                        // mark tx as executed
-                        chain_state.get_mut_storage().set_tx_executed_in_block(tx_hash, &hash)?;
-
-                        // Delete the transaction from  the list if it was marked as orphaned
-                        if orphaned_transactions.remove(&tx_hash) {
-                            trace!("Transaction {} was marked as orphaned, but got executed again", tx_hash);
-                        }
-
-                        // if the rpc_server is enable, track events
-                        if should_track_events.contains(&NotifyEvent::TransactionExecuted) {
-                            let value = json!(TransactionExecutedEvent {
-                                tx_hash: Cow::Borrowed(&tx_hash),
-                                block_hash: Cow::Borrowed(&hash),
-                                topoheight: highest_topo,
-                            });
-                            events.entry(NotifyEvent::TransactionExecuted).or_insert_with(Vec::new).push(value);
-                        }
-
-                        // Increase total tx fees for miner
-                        total_fees += tx.get_fee();
+                        // Proofs are verified later with the whole batch
+                        match tx.prepare_partial_verify(chain_state.as_mut()).await {
+                            Ok(verification) => {
+                                batch.push((tx, tx_hash));
+                                verifications.push(verification);
+                            },
+                            Err(e) => {
+                                warn!("Error while executing TX {} with current DAG org: {}", tx_hash, e);
+                                // TX may be orphaned if not added again in good order in next blocks
+                                orphaned_transactions.insert(tx_hash.clone());
+                            }
+                        };
                     }
                 }
 
@@ -2019,6 +3350,16 @@ impl<S: Storage> Blockchain<S> {
                 // apply changes from Chain State
                 chain_state.apply_changes().await?;
 
+                // Update the daily chain stats
+                let stats = ChainStats {
+                    blocks: 1,
+                    txs: txs_executed,
+                    fees: total_fees,
+                    new_accounts: storage.count_registrations_at_topoheight(highest_topo).await?,
+                    emission
+                };
+                storage.add_block_stats(highest_topo, block.get_timestamp(), &stats).await?;
+
                 if should_track_events.contains(&NotifyEvent::BlockOrdered) {
                     let value = json!(BlockOrderedEvent {
                         block_hash: Cow::Borrowed(&hash),
@@ -2035,7 +3376,7 @@ impl<S: Storage> Blockchain<S> {
         for hash in tips {
             let distance = self.calculate_distance_from_mainchain(storage, &hash).await?;
             trace!("tip base distance: {}, best height: {}", distance, best_height);
-            if distance <= current_height && current_height - distance >= STABLE_LIMIT {
+            if distance <= current_height && current_height - distance >= self.chain_params.stable_limit {
                 warn!("Rusty TIP declared stale {} with best height: {}, tip base distance: {}", hash, best_height, distance);
             } else {
                 trace!("Adding {} as new tips", hash);
@@ -2136,7 +3477,8 @@ impl<S: Storage> Blockchain<S> {
             debug!("Locking mempool write mode");
             let mut mempool = self.mempool.write().await;
             debug!("mempool write mode ok");
-            mempool.clean_up(&*storage, highest_topo).await
+            let block_version = self.get_version_at_height(self.get_height() + 1);
+            mempool.clean_up(&*storage, highest_topo, block_version).await
         } else {
             Vec::new()
         };
@@ -2223,7 +3565,8 @@ impl<S: Storage> Blockchain<S> {
         // broadcast to websocket new block
         if let Some(rpc) = rpc_server.as_ref() {
             // if we have a getwork server, and that its not from syncing, notify miners
-            if broadcast {
+            // a standby node has no miners to notify until it is promoted
+            if broadcast && !self.is_standby() {
                 if let Some(getwork) = rpc.getwork_server() {
                     let getwork = getwork.clone();
                     spawn_task("notify-new-job", async move {
@@ -2232,6 +3575,15 @@ impl<S: Storage> Blockchain<S> {
                         }
                     });
                 }
+
+                if let Some(stratum) = rpc.stratum_server() {
+                    let stratum = stratum.clone();
+                    spawn_task("notify-new-stratum-job", async move {
+                        if let Err(e) = stratum.notify_new_job().await {
+                            debug!("Error while notifying new job to stratum miners: {}", e);
+                        }
+                    });
+                }
             }
 
             // atm, we always notify websocket clients
@@ -2268,13 +3620,13 @@ impl<S: Storage> Blockchain<S> {
     pub async fn internal_get_block_reward(&self, past_supply: u64, is_side_block: bool, side_blocks_count: u64) -> Result<u64, BlockchainError> {
         trace!("internal get block reward");
         let block_reward = if is_side_block {
-            let reward = get_block_reward(past_supply);
+            let reward = get_block_reward(past_supply, &self.chain_params);
             let side_block_percent = side_block_reward_percentage(side_blocks_count);
             trace!("side block reward: {}%", side_block_percent);
 
             reward * side_block_percent / 100
         } else {
-            get_block_reward(past_supply)
+            get_block_reward(past_supply, &self.chain_params)
         };
         Ok(block_reward)
     }
@@ -2298,6 +3650,80 @@ impl<S: Storage> Blockchain<S> {
         self.internal_get_block_reward(past_supply, is_side_block, side_blocks_count).await
     }
 
+    // Recompute the block rewards from the emission and verify the supply stored at each topoheight
+    // Each topoheight is verified against the supply stored at the previous one, so a divergence is reported only once
+    // Near the pruned topoheight, previous blocks are unknown and the stored block rewards are trusted
+    // Coins burned in the range are summed to compute the circulating supply
+    pub async fn validate_supply(&self, storage: &S, start_topoheight: Option<u64>, end_topoheight: Option<u64>) -> Result<AdminValidateSupplyResult, BlockchainError> {
+        // The supply before the first topoheight verified must be available
+        let pruned_topoheight = storage.get_pruned_topoheight().await?;
+        let minimum_topoheight = pruned_topoheight.map_or(0, |topoheight| topoheight + 1);
+        let start_topoheight = start_topoheight.unwrap_or(minimum_topoheight);
+        let end_topoheight = end_topoheight.unwrap_or_else(|| self.get_topo_height());
+        if start_topoheight < minimum_topoheight || start_topoheight > end_topoheight || end_topoheight > self.get_topo_height() {
+            return Err(BlockchainError::InvalidTopoHeightRange(start_topoheight, end_topoheight))
+        }
+
+        let mut past_supply = if start_topoheight == 0 {
+            // premine is emitted before the genesis block reward
+            self.get_premine_supply()
+        } else {
+            storage.get_supply_at_topo_height(start_topoheight - 1).await?
+        };
+
+        let mut burned_supply = 0;
+        let mut divergences = Vec::new();
+        for topoheight in start_topoheight..=end_topoheight {
+            let hash = storage.get_hash_at_topo_height(topoheight).await?;
+            let block_reward = storage.get_block_reward_at_topo_height(topoheight)?;
+            let expected_block_reward = if pruned_topoheight.map_or(true, |pruned| topoheight - pruned > self.chain_params.stable_limit) {
+                self.get_block_reward(storage, &hash, past_supply, topoheight).await?
+            } else {
+                block_reward
+            };
+
+            let expected_supply = past_supply + expected_block_reward;
+            let supply = storage.get_supply_at_topo_height(topoheight).await?;
+            if block_reward != expected_block_reward || supply != expected_supply {
+                warn!("Supply divergence for block {} at topoheight {}: reward {} (expected {}), supply {} (expected {})", hash, topoheight, block_reward, expected_block_reward, supply, expected_supply);
+                divergences.push(SupplyDivergence {
+                    topoheight,
+                    block_hash: hash.clone(),
+                    expected_block_reward,
+                    block_reward,
+                    expected_supply,
+                    supply
+                });
+            }
+
+            let block = storage.get_block_by_hash(&hash).await?;
+            for (tx_hash, tx) in block.get_txs_hashes().iter().zip(block.get_transactions()) {
+                let burned = match tx.get_data() {
+                    TransactionType::Burn(payload) if payload.asset == XELIS_ASSET => payload.amount,
+                    TransactionType::CreateAsset(_) => ASSET_REGISTRATION_FEE,
+                    // Gas is always fully burned, even if the execution failed
+                    TransactionType::InvokeContract(payload) => payload.max_gas,
+                    _ => continue
+                };
+
+                if storage.is_tx_executed_in_block(tx_hash, &hash)? {
+                    burned_supply += burned;
+                }
+            }
+
+            past_supply = supply;
+        }
+
+        Ok(AdminValidateSupplyResult {
+            start_topoheight,
+            end_topoheight,
+            supply: past_supply,
+            burned_supply,
+            circulating_supply: past_supply.saturating_sub(burned_supply),
+            divergences
+        })
+    }
+
     // retrieve all txs hashes until height or until genesis block that were executed in a block
     // for this we get all tips and recursively retrieve all txs from tips until we reach height
     async fn get_all_executed_txs_until_height<P>(&self, provider: &P, until_height: u64, tips: impl Iterator<Item = Hash>) -> Result<HashSet<Hash>, BlockchainError>
@@ -2375,7 +3801,7 @@ impl<S: Storage> Blockchain<S> {
         // verify if there is a block with height higher than this block in past 8 topo blocks
         let mut counter = 0;
         let mut i = topoheight - 1;
-        while counter < STABLE_LIMIT && i > 0 {
+        while counter < self.chain_params.stable_limit && i > 0 {
             let hash = provider.get_hash_at_topo_height(i).await?;
             let previous_height = provider.get_height_for_block_hash(&hash).await?;
 
@@ -2397,7 +3823,7 @@ impl<S: Storage> Blockchain<S> {
         trace!("has block {} stable order at topoheight {}", hash, topoheight);
         if provider.is_block_topological_ordered(hash).await {
             let block_topo_height = provider.get_topo_height_for_hash(hash).await?;
-            return Ok(block_topo_height + STABLE_LIMIT <= topoheight)
+            return Ok(block_topo_height + self.chain_params.stable_limit <= topoheight)
         }
         Ok(false)
     }
@@ -2419,8 +3845,10 @@ impl<S: Storage> Blockchain<S> {
         } else {
             0
         };
+        let _rewrite = self.storage_rewrites.begin();
         let (new_height, new_topoheight, txs) = storage.pop_blocks(current_height, current_topoheight, count, until).await?;
         debug!("New topoheight: {} (diff: {})", new_topoheight, current_topoheight - new_topoheight);
+        self.clear_dag_caches().await;
 
         // Try to add all txs back to mempool if possible
         // We try to prevent lost/to be orphaned
@@ -2485,7 +3913,7 @@ impl<S: Storage> Blockchain<S> {
         let mut count = if topoheight > 50 {
             50
         } else if topoheight <= 1 {
-            return Ok(BLOCK_TIME_MILLIS);
+            return Ok(self.chain_params.block_time_millis);
         } else {
             topoheight - 1
         };
@@ -2503,7 +3931,8 @@ impl<S: Storage> Blockchain<S> {
         let count_hash = provider.get_hash_at_topo_height(topoheight - count).await?;
         let count_timestamp = provider.get_timestamp_for_block_hash(&count_hash).await?;
 
-        let diff = now_timestamp - count_timestamp;
+        // Timestamps are not strictly increasing in the topological order
+        let diff = now_timestamp.saturating_sub(count_timestamp);
         Ok(diff / count)
     }
 }
@@ -2542,15 +3971,48 @@ pub fn side_block_reward_percentage(side_blocks: u64) -> u64 {
 }
 
 // Calculate the block reward based on the current supply
-pub fn get_block_reward(supply: u64) -> u64 {
+pub fn get_block_reward(supply: u64, params: &ChainParams) -> u64 {
     // Prevent any overflow
     if supply >= MAXIMUM_SUPPLY {
         // Max supply reached, do we want to generate small fixed amount of coins? 
         return 0
     }
 
-    let base_reward = (MAXIMUM_SUPPLY - supply) >> EMISSION_SPEED_FACTOR;
-    base_reward * BLOCK_TIME_MILLIS / MILLIS_PER_SECOND / 180
+    let base_reward = (MAXIMUM_SUPPLY - supply) >> params.emission_speed_factor;
+    base_reward * params.block_time_millis / MILLIS_PER_SECOND / 180
+}
+
+// Parse the premine allocations in the format `address:amount`
+// Each address can only be allocated once and the total can't be above the maximum supply
+pub fn parse_premine_allocations(network: &Network, allocations: &[String]) -> Result<Vec<(PublicKey, u64)>, BlockchainError> {
+    let mut premine = Vec::with_capacity(allocations.len());
+    let mut total: u64 = 0;
+    for allocation in allocations {
+        let (address, amount) = allocation.split_once(':')
+            .ok_or_else(|| BlockchainError::InvalidPremineAllocation(allocation.clone()))?;
+
+        let address = Address::from_string(address)
+            .map_err(|_| BlockchainError::InvalidPremineAllocation(allocation.clone()))?;
+        if !address.is_normal() || address.is_mainnet() != network.is_mainnet() {
+            return Err(BlockchainError::InvalidPremineAllocation(allocation.clone()))
+        }
+
+        let amount = from_xelis(amount)
+            .filter(|amount| *amount > 0)
+            .ok_or_else(|| BlockchainError::InvalidPremineAllocation(allocation.clone()))?;
+
+        let key = address.to_public_key();
+        if premine.iter().any(|(k, _)| *k == key) {
+            return Err(BlockchainError::InvalidPremineAllocation(allocation.clone()))
+        }
+
+        total = total.checked_add(amount)
+            .filter(|total| *total <= MAXIMUM_SUPPLY)
+            .ok_or(BlockchainError::PremineAboveMaxSupply)?;
+        premine.push((key, amount));
+    }
+
+    Ok(premine)
 }
 
 // Returns the fee percentage for a block at a given height
@@ -2606,4 +4068,31 @@ mod tests {
         assert_eq!(get_block_dev_fee(DEV_FEES[1].height), 5);
         assert_eq!(get_block_dev_fee(DEV_FEES[1].height + 1), 5);
     }
+
+    #[test]
+    fn test_parse_premine_allocations() {
+        use xelis_common::crypto::KeyPair;
+
+        let key = KeyPair::new().get_public_key().compress();
+        let address = key.as_address(false).to_string();
+        let premine = parse_premine_allocations(&Network::Dev, &[format!("{}:1.5", address)]).unwrap();
+        assert_eq!(premine, vec![(key, 150_000_000)]);
+
+        // duplicated address
+        let allocations = vec![format!("{}:1", address), format!("{}:2", address)];
+        assert!(parse_premine_allocations(&Network::Dev, &allocations).is_err());
+
+        // zero amount or invalid format
+        assert!(parse_premine_allocations(&Network::Dev, &[format!("{}:0", address)]).is_err());
+        assert!(parse_premine_allocations(&Network::Dev, &[address.clone()]).is_err());
+    }
+
+    #[test]
+    fn test_premine_above_max_supply() {
+        use xelis_common::crypto::KeyPair;
+
+        let address = KeyPair::new().get_public_key().compress().as_address(false).to_string();
+        let amount = format_xelis(MAXIMUM_SUPPLY + 1);
+        assert!(matches!(parse_premine_allocations(&Network::Dev, &[format!("{}:{}", address, amount)]), Err(BlockchainError::PremineAboveMaxSupply)));
+    }
 }
\ No newline at end of file
@@ -1,3 +1,4 @@
+use std::collections::{BinaryHeap, HashSet};
 use indexmap::IndexSet;
 use log::trace;
 use xelis_common::{
@@ -77,6 +78,45 @@ where
     }
 }
 
+// Calculate the median timestamp of the N closest past blocks of the tips
+// Past blocks are visited from the highest height, the hash is used to order blocks at the same height
+// Blocks deleted by pruning are not counted
+pub async fn calculate_median_timestamp_at_tips<'a, S, I>(storage: &S, tips: I, count: u64) -> Result<TimestampMillis, BlockchainError>
+where
+    S: Storage,
+    I: Iterator<Item = &'a Hash>
+{
+    trace!("calculate median timestamp at tips");
+    let mut queue = BinaryHeap::new();
+    let mut visited = HashSet::new();
+    for hash in tips {
+        if visited.insert(hash.clone()) {
+            let height = storage.get_height_for_block_hash(hash).await?;
+            queue.push((height, hash.clone()));
+        }
+    }
+
+    let mut timestamps = Vec::with_capacity(count as usize);
+    while let Some((_, hash)) = queue.pop() {
+        timestamps.push(storage.get_timestamp_for_block_hash(&hash).await?);
+        if timestamps.len() as u64 >= count {
+            break;
+        }
+
+        for tip in storage.get_past_blocks_for_block_hash(&hash).await?.iter() {
+            if visited.insert(tip.clone()) && storage.has_block_with_hash(tip).await? {
+                let height = storage.get_height_for_block_hash(tip).await?;
+                queue.push((height, tip.clone()));
+            }
+        }
+    }
+
+    timestamps.sort_unstable();
+    timestamps.get(timestamps.len() / 2)
+        .copied()
+        .ok_or(BlockchainError::ExpectedTips)
+}
+
 // determine he lowest height possible based on tips and do N+1
 pub async fn calculate_height_at_tips<'a, D, I>(provider: &D, tips: I) -> Result<u64, BlockchainError>
 where
@@ -0,0 +1,158 @@
+use std::fs;
+use toml_edit::Document;
+use xelis_common::{
+    difficulty::Difficulty,
+    network::Network
+};
+use crate::config::{
+    get_minimum_difficulty,
+    BLOCK_TIME_MILLIS,
+    EMISSION_SPEED_FACTOR,
+    MAX_BLOCK_SIZE,
+    MAX_TIMESTAMP_MEDIAN_BLOCKS,
+    MILLIS_PER_SECOND,
+    STABLE_LIMIT,
+    TIMESTAMP_IN_FUTURE_LIMIT,
+    TIMESTAMP_MEDIAN_BLOCKS
+};
+use super::error::BlockchainError;
+
+// Consensus parameters used by the chain
+// Only the dev network can override them, others networks always use the default values
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainParams {
+    // expected time between two blocks
+    pub block_time_millis: u64,
+    // difficulty can't go below it
+    pub minimum_difficulty: Difficulty,
+    // in how many height we consider the block stable
+    pub stable_limit: u64,
+    // the higher it is, the slower the emission is
+    pub emission_speed_factor: u64,
+    // maximum size of a block with its transactions
+    pub max_block_size: usize,
+    // how far in the future a block timestamp can be compared to our local time
+    pub timestamp_future_limit_millis: u64,
+    // count of past blocks used to compute the median timestamp
+    pub timestamp_median_blocks: u64
+}
+
+impl ChainParams {
+    // Default parameters of the network
+    pub fn new(network: &Network) -> Self {
+        Self {
+            block_time_millis: BLOCK_TIME_MILLIS,
+            minimum_difficulty: get_minimum_difficulty(network),
+            stable_limit: STABLE_LIMIT,
+            emission_speed_factor: EMISSION_SPEED_FACTOR,
+            max_block_size: MAX_BLOCK_SIZE,
+            timestamp_future_limit_millis: TIMESTAMP_IN_FUTURE_LIMIT,
+            timestamp_median_blocks: TIMESTAMP_MEDIAN_BLOCKS
+        }
+    }
+
+    // Read the parameters from a TOML file
+    pub fn load(network: &Network, path: &str) -> Result<Self, BlockchainError> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| BlockchainError::InvalidChainConfig(format!("can't read {}: {}", path, e)))?;
+        Self::from_toml(network, &content)
+    }
+
+    // Override the default parameters of the network with the keys set in the TOML content
+    // Unknown keys are rejected to detect typos
+    pub fn from_toml(network: &Network, content: &str) -> Result<Self, BlockchainError> {
+        let document: Document = content.parse()
+            .map_err(|e| BlockchainError::InvalidChainConfig(format!("{}", e)))?;
+
+        let mut params = Self::new(network);
+        for (key, item) in document.iter() {
+            let value = item.as_integer()
+                .and_then(|v| u64::try_from(v).ok())
+                .ok_or_else(|| BlockchainError::InvalidChainConfig(format!("{} must be a positive integer", key)))?;
+
+            match key {
+                "block_time_millis" => params.block_time_millis = value,
+                "minimum_difficulty" => params.minimum_difficulty = Difficulty::from_u64(value),
+                "stable_limit" => params.stable_limit = value,
+                "emission_speed_factor" => params.emission_speed_factor = value,
+                "max_block_size" => params.max_block_size = value as usize,
+                "timestamp_future_limit_millis" => params.timestamp_future_limit_millis = value,
+                "timestamp_median_blocks" => params.timestamp_median_blocks = value,
+                _ => return Err(BlockchainError::InvalidChainConfig(format!("unknown key {}", key)))
+            };
+        }
+
+        params.verify()?;
+        Ok(params)
+    }
+
+    // Verify that the parameters can't break the chain
+    fn verify(&self) -> Result<(), BlockchainError> {
+        if self.block_time_millis == 0 {
+            return Err(BlockchainError::InvalidChainConfig("block_time_millis can't be zero".into()))
+        }
+
+        if self.minimum_difficulty == Difficulty::zero() {
+            return Err(BlockchainError::InvalidChainConfig("minimum_difficulty can't be zero".into()))
+        }
+
+        if self.stable_limit == 0 {
+            return Err(BlockchainError::InvalidChainConfig("stable_limit can't be zero".into()))
+        }
+
+        // the reward is computed by shifting the remaining supply
+        if self.emission_speed_factor == 0 || self.emission_speed_factor >= u64::BITS as u64 {
+            return Err(BlockchainError::InvalidChainConfig(format!("emission_speed_factor must be in range [1-{}]", u64::BITS - 1)))
+        }
+
+        // P2P packets are limited based on the default maximum block size
+        if self.max_block_size == 0 || self.max_block_size > MAX_BLOCK_SIZE {
+            return Err(BlockchainError::InvalidChainConfig(format!("max_block_size must be in range [1-{}]", MAX_BLOCK_SIZE)))
+        }
+
+        // the median timestamp is computed at each new block
+        if self.timestamp_median_blocks == 0 || self.timestamp_median_blocks > MAX_TIMESTAMP_MEDIAN_BLOCKS {
+            return Err(BlockchainError::InvalidChainConfig(format!("timestamp_median_blocks must be in range [1-{}]", MAX_TIMESTAMP_MEDIAN_BLOCKS)))
+        }
+
+        Ok(())
+    }
+
+    // Estimate the network hashrate from the difficulty
+    pub fn estimate_hashrate(&self, difficulty: Difficulty) -> Difficulty {
+        difficulty * MILLIS_PER_SECOND / self.block_time_millis
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chain_params_from_toml() {
+        let params = ChainParams::from_toml(&Network::Dev, "block_time_millis = 1000\nstable_limit = 4\ntimestamp_median_blocks = 5").unwrap();
+        assert_eq!(params.block_time_millis, 1000);
+        assert_eq!(params.stable_limit, 4);
+        assert_eq!(params.timestamp_median_blocks, 5);
+        // not set, default value is kept
+        assert_eq!(params.emission_speed_factor, EMISSION_SPEED_FACTOR);
+        assert_eq!(params.max_block_size, MAX_BLOCK_SIZE);
+        assert_eq!(params.timestamp_future_limit_millis, TIMESTAMP_IN_FUTURE_LIMIT);
+
+        assert_eq!(ChainParams::from_toml(&Network::Dev, "").unwrap(), ChainParams::new(&Network::Dev));
+    }
+
+    #[test]
+    fn test_invalid_chain_params() {
+        // unknown key
+        assert!(ChainParams::from_toml(&Network::Dev, "block_time = 1000").is_err());
+        // invalid values
+        assert!(ChainParams::from_toml(&Network::Dev, "block_time_millis = 0").is_err());
+        assert!(ChainParams::from_toml(&Network::Dev, "stable_limit = -1").is_err());
+        assert!(ChainParams::from_toml(&Network::Dev, "stable_limit = \"8\"").is_err());
+        assert!(ChainParams::from_toml(&Network::Dev, "emission_speed_factor = 64").is_err());
+        assert!(ChainParams::from_toml(&Network::Dev, &format!("max_block_size = {}", MAX_BLOCK_SIZE + 1)).is_err());
+        assert!(ChainParams::from_toml(&Network::Dev, "timestamp_median_blocks = 0").is_err());
+        assert!(ChainParams::from_toml(&Network::Dev, &format!("timestamp_median_blocks = {}", MAX_TIMESTAMP_MEDIAN_BLOCKS + 1)).is_err());
+    }
+}
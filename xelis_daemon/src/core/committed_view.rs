@@ -0,0 +1,116 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use super::storage::Storage;
+
+// Tracks the operations rewriting the committed data of the storage (reorg, rewind, pruning)
+// Appending a new block only writes above the committed topoheight,
+// so the versioned data read at or below it doesn't change until a rewrite happens
+pub struct StorageRewrites {
+    // count of rewrites in progress
+    active: AtomicU64,
+    // increased at each rewrite started
+    generation: AtomicU64
+}
+
+impl StorageRewrites {
+    pub fn new() -> Self {
+        Self {
+            active: AtomicU64::new(0),
+            generation: AtomicU64::new(0)
+        }
+    }
+
+    // Mark the committed data as being rewritten until the guard is dropped
+    pub fn begin(&self) -> StorageRewriteGuard<'_> {
+        self.active.fetch_add(1, Ordering::SeqCst);
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        StorageRewriteGuard(self)
+    }
+
+    // Get the current generation, None if a rewrite is in progress
+    pub fn get_generation(&self) -> Option<u64> {
+        let generation = self.generation.load(Ordering::SeqCst);
+        if self.active.load(Ordering::SeqCst) != 0 {
+            return None
+        }
+
+        Some(generation)
+    }
+
+    // No rewrite happened since this generation
+    pub fn is_unchanged_since(&self, generation: u64) -> bool {
+        self.get_generation() == Some(generation)
+    }
+}
+
+pub struct StorageRewriteGuard<'a>(&'a StorageRewrites);
+
+impl Drop for StorageRewriteGuard<'_> {
+    fn drop(&mut self) {
+        self.0.active.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+// View of the data committed at a topoheight, read without the storage lock
+// The result of a read must be discarded if the view is not valid anymore once done
+pub struct CommittedView<'a, S: Storage> {
+    storage: &'a S,
+    topoheight: u64,
+    generation: u64,
+    rewrites: &'a StorageRewrites
+}
+
+impl<'a, S: Storage> CommittedView<'a, S> {
+    // Generation must be read before the topoheight
+    // so a rewrite lowering the topoheight in between invalidates the view
+    pub fn new(storage: &'a S, topoheight: u64, generation: u64, rewrites: &'a StorageRewrites) -> Self {
+        Self {
+            storage,
+            topoheight,
+            generation,
+            rewrites
+        }
+    }
+
+    // Only the versioned data at or below the topoheight of the view can be read
+    pub fn get_storage(&self) -> &S {
+        self.storage
+    }
+
+    pub fn get_topoheight(&self) -> u64 {
+        self.topoheight
+    }
+
+    // No rewrite started since the view was created
+    pub fn is_valid(&self) -> bool {
+        self.rewrites.is_unchanged_since(self.generation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nested_rewrites() {
+        let rewrites = StorageRewrites::new();
+        let generation = rewrites.get_generation().unwrap();
+        assert!(rewrites.is_unchanged_since(generation));
+
+        {
+            let _reorg = rewrites.begin();
+            assert!(rewrites.get_generation().is_none());
+            {
+                // auto pruning during a reorg
+                let _prune = rewrites.begin();
+                assert!(rewrites.get_generation().is_none());
+            }
+            assert!(rewrites.get_generation().is_none());
+            assert!(!rewrites.is_unchanged_since(generation));
+        }
+
+        // a read started before the rewrite is not valid anymore
+        assert!(!rewrites.is_unchanged_since(generation));
+        let generation = rewrites.get_generation().unwrap();
+        assert!(rewrites.is_unchanged_since(generation));
+    }
+}
@@ -7,7 +7,6 @@ use xelis_common::{
     utils::format_difficulty,
     varuint::VarUint
 };
-use crate::config::BLOCK_TIME_MILLIS;
 
 const SHIFT: u64 = 32;
 // This is equal to 2 ** 32
@@ -55,8 +54,10 @@ pub fn kalman_filter(z: VarUint, x_est_prev: VarUint, p_prev: VarUint) -> (VarUi
 
 // Calculate the required difficulty for the next block based on the solve time of the previous block
 // We are using a Kalman filter to estimate the hashrate and adjust the difficulty
-pub fn calculate_difficulty(parent_timestamp: TimestampMillis, timestamp: TimestampMillis, previous_difficulty: Difficulty, p: VarUint, minimum_difficulty: Difficulty) -> (Difficulty, VarUint) {
-    let mut solve_time = timestamp - parent_timestamp;
+pub fn calculate_difficulty(parent_timestamp: TimestampMillis, timestamp: TimestampMillis, previous_difficulty: Difficulty, p: VarUint, minimum_difficulty: Difficulty, block_time_millis: u64) -> (Difficulty, VarUint) {
+    // Timestamp may be lower than its parent one as long as it is above the median timestamp
+    // Before the median timestamp hard fork, it is always above its parents so the result is unchanged
+    let mut solve_time = timestamp.saturating_sub(parent_timestamp);
 
     // Someone trying to do something shady or really lucky
     // 1ms is the minimum solve time
@@ -66,10 +67,10 @@ pub fn calculate_difficulty(parent_timestamp: TimestampMillis, timestamp: Timest
 
     let z = previous_difficulty / solve_time;
     trace!("Calculating difficulty, solve time: {}, previous_difficulty: {}, z: {}, p: {}", format_duration(Duration::from_millis(solve_time)), format_difficulty(previous_difficulty), z, p);
-    let (x_est_new, p_new) = kalman_filter(z, previous_difficulty / BLOCK_TIME_MILLIS, p);
+    let (x_est_new, p_new) = kalman_filter(z, previous_difficulty / block_time_millis, p);
     trace!("x_est_new: {}, p_new: {}", x_est_new, p_new);
 
-    let difficulty = x_est_new * BLOCK_TIME_MILLIS;
+    let difficulty = x_est_new * block_time_millis;
     if difficulty < minimum_difficulty {
         return (minimum_difficulty, P);
     }
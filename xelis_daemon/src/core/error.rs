@@ -1,4 +1,7 @@
-use crate::p2p::error::P2pError;
+use crate::{
+    core::snapshot::SnapshotError,
+    p2p::error::P2pError
+};
 use std::sync::PoisonError;
 use thiserror::Error;
 use xelis_common::{
@@ -111,6 +114,8 @@ pub enum BlockchainError {
     TxTooBig(usize, usize),
     #[error("Timestamp {} is less than parent", _0)]
     TimestampIsLessThanParent(TimestampMillis),
+    #[error("Timestamp {} is less than the median timestamp {} of past blocks", _0, _1)]
+    TimestampIsLessThanMedian(TimestampMillis, TimestampMillis),
     #[error("Timestamp {} is greater than current time {}", _0, _1)]
     TimestampIsInFuture(TimestampMillis, TimestampMillis), // left is expected, right is got
     #[error("Block height mismatch, expected {}, got {}.", _0, _1)]
@@ -203,6 +208,8 @@ pub enum BlockchainError {
     ErrorOnPrompt(#[from] PromptError),
     #[error(transparent)]
     ErrorOnSignature(#[from] ed25519_dalek::SignatureError),
+    #[error("Error while waiting on a blocking task: {}", _0)]
+    ErrorOnJoin(#[from] tokio::task::JoinError),
     #[error("Poison Error: {}", _0)]
     PoisonError(String),
     #[error("Blockchain is syncing")]
@@ -223,8 +230,6 @@ pub enum BlockchainError {
     Unknown,
     #[error("No signature found for this TX")]
     NoTxSignature,
-    #[error("Smart Contract not supported yet")]
-    SmartContractTodo,
     #[error("Unexpected transaction variant to set fees")]
     UnexpectedTransactionVariant,
     #[error("Unexpected error on database: {}", _0)]
@@ -261,6 +266,12 @@ pub enum BlockchainError {
     InvalidTxNonce(Hash, u64, u64, Address),
     #[error("Invalid tx nonce {} for mempool cache, range: [{}-{}]", _0, _1, _2)]
     InvalidTxNonceMempoolCache(u64, u64, u64),
+    #[error("Nonce reservation {} not found", _0)]
+    NonceReservationNotFound(u64),
+    #[error("Too many nonce reservations for this account, maximum is {}", _0)]
+    TooManyNonceReservations(usize),
+    #[error("Invalid nonce reservation duration {}s, maximum is {}s", _0, _1)]
+    InvalidNonceReservationDuration(u64, u64),
     #[error("Invalid asset ID: {}", _0)]
     AssetNotFound(Hash),
     #[error(transparent)]
@@ -289,6 +300,30 @@ pub enum BlockchainError {
     PruneLowerThanLastPruned,
     #[error("Auto prune mode is misconfigured")]
     AutoPruneMode,
+    #[error("Balance pruning is misconfigured")]
+    BalancePruning,
+    #[error("Invalid premine allocation '{}', expected format is address:amount", _0)]
+    InvalidPremineAllocation(String),
+    #[error("Invalid chain config: {}", _0)]
+    InvalidChainConfig(String),
+    #[error("Premine allocations are above the maximum supply")]
+    PremineAboveMaxSupply,
+    #[error("Invalid config: standby mode requires a valid primary address and the P2P server, without exclusive nodes or simulator")]
+    ConfigStandbyMode,
+    #[error("Simulator is misconfigured")]
+    SimulatorMode,
+    #[error("Node is in standby mode, promote it to full mode first")]
+    StandbyMode,
+    #[error("Block processing task is stopped")]
+    BlockQueueClosed,
+    #[error("Cannot reindex a pruned chain, blocks below topoheight {} are not available anymore", _0)]
+    ReindexPrunedChain(u64),
+    #[error("Error while replaying block {} at height {}: {}", _0, _1, _2)]
+    ReplayFailed(Hash, u64, Box<BlockchainError>),
+    #[error("Chain inconsistency at topoheight {}: {}", _0, _1)]
+    ChainInconsistency(u64, String),
+    #[error("Invalid topoheight range: {} to {}", _0, _1)]
+    InvalidTopoHeightRange(u64, u64),
     #[error(transparent)]
     TryFromSliceError(#[from] std::array::TryFromSliceError),
     #[error("Invalid ciphertext")]
@@ -309,6 +344,8 @@ pub enum BlockchainError {
     TransactionProof(ProofVerificationError),
     #[error("Error while generating pow hash")]
     POWHashError(#[from] XelisHashError),
+    #[error(transparent)]
+    SnapshotError(#[from] SnapshotError),
 }
 
 impl BlockchainError {
@@ -0,0 +1,187 @@
+use serde::Serialize;
+use xelis_common::{
+    block::MAX_BLOCK_VERSION,
+    network::Network,
+    transaction::{Transaction, TX_VERSION, TX_VERSION_MULTISIG}
+};
+use crate::config::MAX_BLOCK_SIZE;
+
+// Consensus rules activated starting from a block height
+// Each hard fork increases the block version so a block must always use the version of its height
+// Any consensus change must be done by adding a new hard fork here instead of checking heights in the code
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct HardFork {
+    // Height at which the rules are activated
+    pub height: u64,
+    // Version of the blocks starting from this height
+    pub version: u8,
+    // Highest transaction version accepted in blocks
+    pub max_tx_version: u8,
+    // Maximum size of a block with its transactions
+    pub max_block_size: usize,
+    // Block timestamp must be above the median timestamp of its past blocks instead of each of its parents
+    pub median_timestamp: bool,
+    // Summary of the changes
+    pub changelog: &'static str
+}
+
+// Assets, multisig and locked transfers are enabled by the second version
+const MAINNET_HARD_FORKS: [HardFork; 2] = [
+    HardFork {
+        height: 0,
+        version: 0,
+        max_tx_version: 0,
+        max_block_size: MAX_BLOCK_SIZE,
+        median_timestamp: false,
+        changelog: "Initial version"
+    },
+    HardFork {
+        height: 1_500_000,
+        version: 1,
+        max_tx_version: TX_VERSION_MULTISIG,
+        max_block_size: MAX_BLOCK_SIZE,
+        median_timestamp: true,
+        changelog: "Assets creation and minting, multisig accounts, time and hash locked transfers, median timestamp of past blocks"
+    }
+];
+
+// Smart contracts are only enabled on testnet and devnet for now
+const TESTNET_HARD_FORKS: [HardFork; 2] = [
+    HardFork {
+        height: 0,
+        version: 0,
+        max_tx_version: 0,
+        max_block_size: MAX_BLOCK_SIZE,
+        median_timestamp: false,
+        changelog: "Initial version"
+    },
+    HardFork {
+        height: 1_000_000,
+        version: 1,
+        max_tx_version: TX_VERSION,
+        max_block_size: MAX_BLOCK_SIZE,
+        median_timestamp: true,
+        changelog: "Assets creation and minting, multisig accounts, time and hash locked transfers, smart contracts, median timestamp of past blocks"
+    }
+];
+
+// Dev networks start from scratch, everything is enabled from genesis
+const DEV_HARD_FORKS: [HardFork; 1] = [
+    HardFork {
+        height: 0,
+        version: 0,
+        max_tx_version: TX_VERSION,
+        max_block_size: MAX_BLOCK_SIZE,
+        median_timestamp: true,
+        changelog: "Initial version with all features"
+    }
+];
+
+// Get all hard forks of the network ordered by height
+pub const fn get_hard_forks(network: &Network) -> &'static [HardFork] {
+    match network {
+        Network::Mainnet => &MAINNET_HARD_FORKS,
+        Network::Testnet => &TESTNET_HARD_FORKS,
+        Network::Dev => &DEV_HARD_FORKS
+    }
+}
+
+// Get the hard fork whose rules apply at this height
+pub fn get_hard_fork_at_height(network: &Network, height: u64) -> &'static HardFork {
+    let hard_forks = get_hard_forks(network);
+    hard_forks.iter()
+        .rev()
+        .find(|hard_fork| hard_fork.height <= height)
+        // First hard fork is always at genesis
+        .unwrap_or(&hard_forks[0])
+}
+
+// Get the hard fork for a block version, None if it is unknown on this network
+pub fn get_hard_fork_for_version(network: &Network, version: u8) -> Option<&'static HardFork> {
+    get_hard_forks(network).iter().find(|hard_fork| hard_fork.version == version)
+}
+
+// Get the block version required at this height
+pub fn get_version_at_height(network: &Network, height: u64) -> u8 {
+    get_hard_fork_at_height(network, height).version
+}
+
+// Is the block timestamp verified against the median timestamp of its past blocks
+pub fn is_median_timestamp_enabled(network: &Network, block_version: u8) -> bool {
+    get_hard_fork_for_version(network, block_version)
+        .map_or(false, |hard_fork| hard_fork.median_timestamp)
+}
+
+// Is the transaction version accepted in a block of this version
+pub fn is_tx_version_allowed_in_block_version(network: &Network, block_version: u8, tx_version: u8) -> bool {
+    get_hard_fork_for_version(network, block_version)
+        .map_or(false, |hard_fork| tx_version <= hard_fork.max_tx_version)
+}
+
+// Is the transaction accepted in a block of this version
+// Its version must be allowed and high enough for its type
+pub fn is_tx_allowed_in_block_version(network: &Network, block_version: u8, tx: &Transaction) -> bool {
+    tx.get_version() >= tx.get_data().get_min_tx_version()
+        && is_tx_version_allowed_in_block_version(network, block_version, tx.get_version())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hard_forks_order() {
+        for network in [Network::Mainnet, Network::Testnet, Network::Dev] {
+            let hard_forks = get_hard_forks(&network);
+            assert_eq!(hard_forks[0].height, 0);
+            assert_eq!(hard_forks[0].version, 0);
+            for window in hard_forks.windows(2) {
+                assert!(window[0].height < window[1].height);
+                assert_eq!(window[0].version + 1, window[1].version);
+            }
+
+            let last = hard_forks.last().unwrap();
+            assert!(last.version <= MAX_BLOCK_VERSION);
+            assert!(last.max_tx_version <= TX_VERSION);
+            assert!(hard_forks.iter().all(|hard_fork| hard_fork.max_block_size <= MAX_BLOCK_SIZE));
+        }
+    }
+
+    #[test]
+    fn test_version_at_height() {
+        for network in [Network::Mainnet, Network::Testnet, Network::Dev] {
+            for hard_fork in get_hard_forks(&network) {
+                assert_eq!(get_version_at_height(&network, hard_fork.height), hard_fork.version);
+                assert_eq!(get_hard_fork_for_version(&network, hard_fork.version), Some(hard_fork));
+            }
+            assert_eq!(get_version_at_height(&network, u64::MAX), get_hard_forks(&network).last().unwrap().version);
+            assert!(get_hard_fork_for_version(&network, MAX_BLOCK_VERSION + 1).is_none());
+        }
+    }
+
+    #[test]
+    fn test_tx_version_allowed() {
+        assert!(is_tx_version_allowed_in_block_version(&Network::Mainnet, 0, 0));
+        // New transaction types are only enabled by the hard fork
+        assert!(!is_tx_version_allowed_in_block_version(&Network::Mainnet, 0, TX_VERSION_MULTISIG));
+        assert!(is_tx_version_allowed_in_block_version(&Network::Mainnet, 1, TX_VERSION_MULTISIG));
+        // Smart contracts are not enabled on mainnet
+        assert!(!is_tx_version_allowed_in_block_version(&Network::Mainnet, 1, TX_VERSION));
+        assert!(!is_tx_version_allowed_in_block_version(&Network::Testnet, 0, TX_VERSION_MULTISIG));
+        assert!(is_tx_version_allowed_in_block_version(&Network::Testnet, 1, TX_VERSION));
+        assert!(is_tx_version_allowed_in_block_version(&Network::Dev, 0, TX_VERSION));
+        // Unknown block version
+        assert!(!is_tx_version_allowed_in_block_version(&Network::Mainnet, MAX_BLOCK_VERSION + 1, 0));
+    }
+
+    #[test]
+    fn test_median_timestamp_enabled() {
+        // Parents timestamps rule is kept until the hard fork
+        assert!(!is_median_timestamp_enabled(&Network::Mainnet, 0));
+        assert!(is_median_timestamp_enabled(&Network::Mainnet, 1));
+        assert!(!is_median_timestamp_enabled(&Network::Testnet, 0));
+        assert!(is_median_timestamp_enabled(&Network::Testnet, 1));
+        assert!(is_median_timestamp_enabled(&Network::Dev, 0));
+        assert!(!is_median_timestamp_enabled(&Network::Mainnet, MAX_BLOCK_VERSION + 1));
+    }
+}
@@ -0,0 +1,124 @@
+use indexmap::{IndexMap, IndexSet};
+use log::{debug, trace};
+use xelis_common::{
+    api::daemon::LocalTransactionEntry,
+    crypto::Hash,
+    time::TimestampMillis
+};
+
+// A transaction submitted through the RPC of this node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct LocalTransaction {
+    // time at which it was submitted
+    submitted_at: TimestampMillis,
+    // topoheight at which it was broadcasted for the last time
+    broadcast_topoheight: u64,
+    // count of times it was broadcasted again
+    rebroadcasts: u32
+}
+
+// Transactions submitted through the RPC of this node, tracked until they leave the mempool
+// A broadcast may be dropped by our peers, so they are broadcasted again
+// if they are still in mempool some topoheights after their last broadcast
+pub struct LocalTransactions {
+    txs: IndexMap<Hash, LocalTransaction>
+}
+
+impl LocalTransactions {
+    pub fn new() -> Self {
+        Self {
+            txs: IndexMap::new()
+        }
+    }
+
+    // Track a transaction broadcasted at this topoheight
+    pub fn track(&mut self, hash: Hash, topoheight: u64, now: TimestampMillis) {
+        trace!("tracking local tx {} at topoheight {}", hash, topoheight);
+        self.txs.insert(hash, LocalTransaction {
+            submitted_at: now,
+            broadcast_topoheight: topoheight,
+            rebroadcasts: 0
+        });
+    }
+
+    // Stop tracking the transactions not matching the predicate
+    pub fn retain<F: FnMut(&Hash) -> bool>(&mut self, mut f: F) {
+        self.txs.retain(|hash, _| f(hash));
+    }
+
+    // Select the transactions to broadcast again, their last broadcast is now at this topoheight
+    // Transactions submitted for more than ttl milliseconds are not tracked anymore
+    pub fn select_for_rebroadcast(&mut self, topoheight: u64, now: TimestampMillis, delay: u64, ttl: u64) -> IndexSet<Hash> {
+        self.txs.retain(|hash, tx| {
+            let expired = now.saturating_sub(tx.submitted_at) >= ttl;
+            if expired {
+                debug!("Local tx {} has expired after {} rebroadcasts", hash, tx.rebroadcasts);
+            }
+            !expired
+        });
+
+        let mut selected = IndexSet::new();
+        for (hash, tx) in self.txs.iter_mut() {
+            if topoheight >= tx.broadcast_topoheight + delay {
+                tx.broadcast_topoheight = topoheight;
+                tx.rebroadcasts += 1;
+                selected.insert(hash.clone());
+            }
+        }
+
+        selected
+    }
+
+    pub fn get_entries(&self) -> Vec<LocalTransactionEntry> {
+        self.txs.iter()
+            .map(|(hash, tx)| LocalTransactionEntry {
+                hash: hash.clone(),
+                submitted_at: tx.submitted_at,
+                broadcast_topoheight: tx.broadcast_topoheight,
+                rebroadcasts: tx.rebroadcasts
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_for_rebroadcast() {
+        let hash = Hash::zero();
+        let mut txs = LocalTransactions::new();
+        txs.track(hash.clone(), 10, 0);
+
+        // not enough topoheights since the broadcast
+        assert!(txs.select_for_rebroadcast(14, 1000, 5, 10_000).is_empty());
+        assert!(txs.select_for_rebroadcast(15, 1000, 5, 10_000).contains(&hash));
+        // delay restarts from the last broadcast
+        assert!(txs.select_for_rebroadcast(16, 1000, 5, 10_000).is_empty());
+        assert_eq!(txs.get_entries()[0].rebroadcasts, 1);
+        assert_eq!(txs.get_entries()[0].broadcast_topoheight, 15);
+    }
+
+    #[test]
+    fn test_local_tx_expiration() {
+        let hash = Hash::zero();
+        let mut txs = LocalTransactions::new();
+        txs.track(hash.clone(), 10, 0);
+
+        assert!(txs.select_for_rebroadcast(100, 10_000, 5, 10_000).is_empty());
+        assert!(txs.get_entries().is_empty());
+    }
+
+    #[test]
+    fn test_retain_local_txs() {
+        let mut txs = LocalTransactions::new();
+        txs.track(Hash::zero(), 10, 0);
+        txs.track(Hash::max(), 10, 0);
+
+        txs.retain(|hash| *hash != Hash::zero());
+        let entries = txs.get_entries();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].hash, Hash::max());
+    }
+}
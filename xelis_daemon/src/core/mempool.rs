@@ -55,7 +55,10 @@ pub struct Mempool {
     // store all txs waiting to be included in a block
     txs: HashMap<Arc<Hash>, SortedTx>,
     // store all sender's nonce for faster finding
-    caches: HashMap<PublicKey, AccountCache>
+    caches: HashMap<PublicKey, AccountCache>,
+    // incremented at each change of the txs
+    // used to detect if a block template built from it is outdated
+    generation: u64
 }
 
 impl Mempool {
@@ -64,13 +67,15 @@ impl Mempool {
         Mempool {
             mainnet: network.is_mainnet(),
             txs: HashMap::new(),
-            caches: HashMap::new()
+            caches: HashMap::new(),
+            generation: 0
         }
     }
 
     // All checks are made in Blockchain before calling this function
-    pub async fn add_tx<S: Storage>(&mut self, storage: &S, topoheight: u64, hash: Hash, tx: Arc<Transaction>, size: usize) -> Result<(), BlockchainError> {
-        let mut state = MempoolState::new(&self, storage, topoheight);
+    // Block version is the version of the next block in which the TX can be included
+    pub async fn add_tx<S: Storage>(&mut self, storage: &S, topoheight: u64, block_version: u8, hash: Hash, tx: Arc<Transaction>, size: usize) -> Result<(), BlockchainError> {
+        let mut state = MempoolState::new(&self, storage, topoheight, block_version);
         tx.verify(&mut state).await?;
 
         let balances = state.get_sender_balances(tx.get_source())
@@ -133,6 +138,7 @@ impl Mempool {
 
         // insert in map
         self.txs.insert(hash, sorted_tx);
+        self.generation += 1;
 
         Ok(())
     }
@@ -141,6 +147,7 @@ impl Mempool {
     // This will recalculate the cache bounds
     pub fn remove_tx(&mut self, hash: &Hash) -> Result<(), BlockchainError> {
         let tx = self.txs.remove(hash).ok_or_else(|| BlockchainError::TxNotFound(hash.clone()))?;
+        self.generation += 1;
         // remove the tx hash from sorted txs
         let key = tx.get_tx().get_source();
         let mut delete = false;
@@ -256,10 +263,16 @@ impl Mempool {
         self.txs.len()
     }
 
+    // Get the current generation, it changes each time the txs are updated
+    pub fn get_generation(&self) -> u64 {
+        self.generation
+    }
+
     // Clear all txs and caches in mempool
     pub fn clear(&mut self) {
         self.txs.clear();
         self.caches.clear();
+        self.generation += 1;
     }
 
     // delete all old txs not compatible anymore with current state of chain
@@ -267,8 +280,10 @@ impl Mempool {
     // Because of DAG reorg, we can't only check updated keys from new block,
     // as a block could be orphaned and the nonce order would change
     // So we need to check all keys from mempool and compare it from storage
-    pub async fn clean_up<S: Storage>(&mut self, storage: &S, topoheight: u64) -> Vec<(Arc<Hash>, SortedTx)> {
+    pub async fn clean_up<S: Storage>(&mut self, storage: &S, topoheight: u64, block_version: u8) -> Vec<(Arc<Hash>, SortedTx)> {
         trace!("Cleaning up mempool...");
+        // balances of the caches may be updated even if no tx is deleted
+        self.generation += 1;
 
         // All deleted sorted txs with their hashes
         let mut deleted_transactions: Vec<(Arc<Hash>, SortedTx)> = Vec::new();
@@ -398,7 +413,7 @@ impl Mempool {
                         // NOTE: this can be revert easily in case we are deleting valid TXs also,
                         // But will be slower during high traffic
                         debug!("Verifying TXs ({}) for sender {} at topoheight {}", txs_hashes.iter().map(|hash| hash.to_string()).collect::<Vec<String>>().join(", "), key.as_address(self.mainnet), topoheight);
-                        let mut state = MempoolState::new(&self, storage, topoheight);
+                        let mut state = MempoolState::new(&self, storage, topoheight, block_version);
                         if let Err(e) = Transaction::verify_batch(txs.as_slice(), &mut state).await {
                             warn!("Error while verifying TXs ({}) for sender {}: {}", txs_hashes.iter().map(|hash| hash.to_string()).collect::<Vec<String>>().join(", "), key.as_address(self.mainnet), e);
                             // We may have only one TX invalid, but because they are all linked to each others we delete the whole cache
@@ -442,6 +457,45 @@ impl Mempool {
         deleted_transactions
     }
 
+    // delete all txs added for more than ttl seconds
+    // txs of the same owner with a higher nonce are also deleted as they can't be executed anymore
+    // expected balances of the caches must be recomputed using clean_up
+    pub fn drop_expired_txs(&mut self, now: TimestampSeconds, ttl: u64) -> Vec<(Arc<Hash>, SortedTx)> {
+        trace!("Dropping expired txs from mempool...");
+        let mut deleted_transactions: Vec<(Arc<Hash>, SortedTx)> = Vec::new();
+        let mainnet = self.mainnet;
+        let txs = &mut self.txs;
+        self.caches.retain(|key, cache| {
+            // txs are ordered by nonce
+            let position = cache.txs.iter()
+                .position(|hash| txs.get(hash).map_or(true, |tx| tx.first_seen + ttl <= now));
+
+            if let Some(index) = position {
+                debug!("Dropping {} expired TXs for owner {}", cache.txs.len() - index, key.as_address(mainnet));
+                for hash in cache.txs.drain(index..) {
+                    if let Some(sorted_tx) = txs.remove(&hash) {
+                        deleted_transactions.push((hash, sorted_tx));
+                    } else {
+                        warn!("TX {} not found in mempool while dropping expired TXs", hash);
+                    }
+                }
+
+                // nonces left are still following each other from the lowest one
+                if !cache.txs.is_empty() {
+                    cache.max = cache.min + cache.txs.len() as u64 - 1;
+                }
+            }
+
+            !cache.txs.is_empty()
+        });
+
+        if !deleted_transactions.is_empty() {
+            self.generation += 1;
+        }
+
+        deleted_transactions
+    }
+
     pub async fn stop(&mut self) {
         info!("Stopping mempool...");
         self.clear();
@@ -8,4 +8,11 @@ pub mod simulator;
 pub mod nonce_checker;
 pub mod tx_selector;
 pub mod state;
-pub mod merkle;
\ No newline at end of file
+pub mod merkle;
+pub mod nonce_reservation;
+pub mod local_txs;
+pub mod block_processor;
+pub mod committed_view;
+pub mod snapshot;
+pub mod chain_params;
+pub mod hard_fork;
\ No newline at end of file
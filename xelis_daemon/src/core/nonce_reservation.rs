@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+use log::trace;
+use xelis_common::{
+    crypto::PublicKey,
+    time::TimestampMillis
+};
+use crate::config::NONCE_RESERVATION_MAX_PER_ACCOUNT;
+use super::error::BlockchainError;
+
+// A nonce reserved by an external signer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NonceReservation {
+    // random id required to renew or release it
+    id: u64,
+    nonce: u64,
+    expire_at: TimestampMillis
+}
+
+impl NonceReservation {
+    pub fn get_id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn get_nonce(&self) -> u64 {
+        self.nonce
+    }
+
+    pub fn get_expire_at(&self) -> TimestampMillis {
+        self.expire_at
+    }
+}
+
+// Nonces reserved per account for a limited time
+// This prevents several services building txs for the same key to use the same nonce
+// A reservation is consumed when a tx using its nonce is added in mempool
+pub struct NonceReservations {
+    accounts: HashMap<PublicKey, Vec<NonceReservation>>
+}
+
+impl NonceReservations {
+    pub fn new() -> Self {
+        Self {
+            accounts: HashMap::new()
+        }
+    }
+
+    // Delete all expired reservations
+    fn clean_expired(&mut self, now: TimestampMillis) {
+        self.accounts.retain(|_, reservations| {
+            reservations.retain(|reservation| reservation.expire_at > now);
+            !reservations.is_empty()
+        });
+    }
+
+    // Reserve the lowest nonce not reserved starting from the next nonce of the account
+    pub fn reserve(&mut self, key: &PublicKey, next_nonce: u64, duration: u64, now: TimestampMillis) -> Result<NonceReservation, BlockchainError> {
+        self.clean_expired(now);
+
+        let reservations = self.accounts.entry(key.clone()).or_insert_with(Vec::new);
+        // nonces already used in chain or mempool are not reserved anymore
+        reservations.retain(|reservation| reservation.nonce >= next_nonce);
+        if reservations.len() >= NONCE_RESERVATION_MAX_PER_ACCOUNT {
+            return Err(BlockchainError::TooManyNonceReservations(NONCE_RESERVATION_MAX_PER_ACCOUNT))
+        }
+
+        let mut nonce = next_nonce;
+        while reservations.iter().any(|reservation| reservation.nonce == nonce) {
+            nonce += 1;
+        }
+
+        let reservation = NonceReservation {
+            id: rand::random(),
+            nonce,
+            expire_at: now + duration
+        };
+        trace!("reserved nonce {} until {}", nonce, reservation.expire_at);
+        reservations.push(reservation);
+
+        Ok(reservation)
+    }
+
+    // Extend the reservation for the duration from now
+    pub fn renew(&mut self, key: &PublicKey, id: u64, duration: u64, now: TimestampMillis) -> Result<NonceReservation, BlockchainError> {
+        self.clean_expired(now);
+
+        let reservation = self.accounts.get_mut(key)
+            .and_then(|reservations| reservations.iter_mut().find(|reservation| reservation.id == id))
+            .ok_or(BlockchainError::NonceReservationNotFound(id))?;
+
+        reservation.expire_at = now + duration;
+        Ok(*reservation)
+    }
+
+    // Release the reservation so its nonce can be reserved again
+    pub fn release(&mut self, key: &PublicKey, id: u64) -> Result<(), BlockchainError> {
+        let reservations = self.accounts.get_mut(key).ok_or(BlockchainError::NonceReservationNotFound(id))?;
+        let index = reservations.iter()
+            .position(|reservation| reservation.id == id)
+            .ok_or(BlockchainError::NonceReservationNotFound(id))?;
+
+        reservations.swap_remove(index);
+        if reservations.is_empty() {
+            self.accounts.remove(key);
+        }
+
+        Ok(())
+    }
+
+    // A tx using this nonce has been added in mempool, the reservation is not needed anymore
+    pub fn consume(&mut self, key: &PublicKey, nonce: u64) {
+        if let Some(reservations) = self.accounts.get_mut(key) {
+            reservations.retain(|reservation| reservation.nonce != nonce);
+            if reservations.is_empty() {
+                self.accounts.remove(key);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use xelis_common::crypto::KeyPair;
+    use super::*;
+
+    #[test]
+    fn test_reserve_next_nonces() {
+        let key = KeyPair::new().get_public_key().compress();
+        let mut reservations = NonceReservations::new();
+
+        let first = reservations.reserve(&key, 5, 1000, 0).unwrap();
+        let second = reservations.reserve(&key, 5, 1000, 0).unwrap();
+        assert_eq!(first.get_nonce(), 5);
+        assert_eq!(second.get_nonce(), 6);
+
+        // released nonce can be reserved again
+        reservations.release(&key, first.get_id()).unwrap();
+        assert_eq!(reservations.reserve(&key, 5, 1000, 0).unwrap().get_nonce(), 5);
+    }
+
+    #[test]
+    fn test_reservation_expiration() {
+        let key = KeyPair::new().get_public_key().compress();
+        let mut reservations = NonceReservations::new();
+
+        let reservation = reservations.reserve(&key, 0, 1000, 0).unwrap();
+        let renewed = reservations.renew(&key, reservation.get_id(), 1000, 500).unwrap();
+        assert_eq!(renewed.get_expire_at(), 1500);
+
+        // expired, nonce is available again
+        assert_eq!(reservations.reserve(&key, 0, 1000, 1500).unwrap().get_nonce(), 0);
+        assert!(reservations.renew(&key, reservation.get_id(), 1000, 1500).is_err());
+    }
+
+    #[test]
+    fn test_consume_reservation() {
+        let key = KeyPair::new().get_public_key().compress();
+        let mut reservations = NonceReservations::new();
+
+        let reservation = reservations.reserve(&key, 0, 1000, 0).unwrap();
+        reservations.consume(&key, reservation.get_nonce());
+        assert!(reservations.release(&key, reservation.get_id()).is_err());
+    }
+}
@@ -1,19 +1,46 @@
 use std::{
+    collections::{HashMap, HashSet},
     str::FromStr,
     fmt::{Display, Formatter},
     sync::Arc,
     time::Duration,
 };
-use log::{info, error};
+use log::{info, debug, error};
 use rand::{rngs::OsRng, Rng};
 use tokio::time::interval;
 use xelis_common::{
-    crypto::KeyPair,
-    config::TIPS_LIMIT,
-    block::Block
+    account::CiphertextCache,
+    block::Block,
+    config::{TIPS_LIMIT, XELIS_ASSET},
+    crypto::{
+        elgamal::{Ciphertext, CompressedCiphertext},
+        Hash,
+        Hashable,
+        KeyPair,
+        PublicKey
+    },
+    transaction::{
+        builder::{
+            AccountState,
+            FeeBuilder,
+            FeeHelper,
+            TransactionBuilder,
+            TransactionTypeBuilder,
+            TransferBuilder
+        },
+        Reference
+    }
+};
+use super::{
+    blockchain::{get_block_dev_fee, Blockchain},
+    error::BlockchainError,
+    storage::Storage
 };
-use crate::config::BLOCK_TIME_MILLIS;
-use super::{blockchain::Blockchain, storage::Storage};
+
+// Maximum transfers in a simulated transaction
+const MAX_SIMULATED_TRANSFERS: usize = 5;
+// Maximum amount sent per simulated transfer
+const MAX_SIMULATED_AMOUNT: u64 = 5000;
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Simulator {
@@ -23,6 +50,9 @@ pub enum Simulator {
     BlockDag,
     // Same as blockDAG but generates much more blocks and TXs for stress test
     Stress,
+    // No block is generated automatically, they are mined on demand
+    // This is used to control the chain in tests
+    Manual,
 }
 
 impl FromStr for Simulator {
@@ -33,6 +63,7 @@ impl FromStr for Simulator {
             "blockchain" | "0" => Self::Blockchain,
             "blockdag" | "1" => Self::BlockDag,
             "stress" | "2" => Self::Stress,
+            "manual" | "3" => Self::Manual,
             _ => return Err("Invalid simulator type".into())
         })
     }
@@ -44,41 +75,229 @@ impl Display for Simulator {
             Self::Blockchain => "blockchain",
             Self::BlockDag => "blockdag",
             Self::Stress => "stress",
+            Self::Manual => "manual",
         };
         write!(f, "{}", str)
     }
 }
 
+// Options of the simulator
+#[derive(Debug, Clone)]
+pub struct SimulatorConfig {
+    // Maximum blocks generated per interval
+    // If not set, it depends on the simulator mode
+    pub blocks_per_interval: Option<usize>,
+    // Number of keys used to mine blocks and to send transactions
+    pub keys: usize,
+    // Maximum transactions generated per interval
+    // If not set, it depends on the simulator mode
+    pub txs_per_interval: Option<usize>
+}
+
+// Transaction generated by the simulator
+// Amounts are encrypted on chain, so we keep them to compute the balances of our keys
+struct SimulatedTransaction {
+    source: PublicKey,
+    // total amount sent including the fee
+    spent: u64,
+    transfers: Vec<(PublicKey, u64)>,
+    // topoheight at which it was generated
+    topoheight: u64
+}
+
+// Plaintext balance of a key verified against its ciphertext at a topoheight
+struct KnownBalance {
+    topoheight: u64,
+    ciphertext: CompressedCiphertext,
+    balance: u64
+}
+
+// State used by the transaction builder for a simulated key
+struct SimulatedAccountState {
+    mainnet: bool,
+    balance: u64,
+    ciphertext: CiphertextCache,
+    reference: Reference,
+    nonce: u64
+}
+
+impl FeeHelper for SimulatedAccountState {
+    type Error = BlockchainError;
+
+    // Simulated keys may not be registered yet, paying the account creation fee is always valid
+    fn account_exists(&self, _: &PublicKey) -> Result<bool, Self::Error> {
+        Ok(false)
+    }
+}
+
+impl AccountState for SimulatedAccountState {
+    fn is_mainnet(&self) -> bool {
+        self.mainnet
+    }
+
+    fn get_account_balance(&self, _: &Hash) -> Result<u64, Self::Error> {
+        Ok(self.balance)
+    }
+
+    fn get_reference(&self) -> Reference {
+        self.reference.clone()
+    }
+
+    fn get_account_ciphertext(&self, _: &Hash) -> Result<CiphertextCache, Self::Error> {
+        Ok(self.ciphertext.clone())
+    }
+
+    fn update_account_balance(&mut self, _: &Hash, balance: u64, ciphertext: Ciphertext) -> Result<(), Self::Error> {
+        self.balance = balance;
+        self.ciphertext = CiphertextCache::Decompressed(ciphertext);
+        Ok(())
+    }
+
+    fn get_nonce(&self) -> Result<u64, Self::Error> {
+        Ok(self.nonce)
+    }
+
+    fn update_nonce(&mut self, new_nonce: u64) -> Result<(), Self::Error> {
+        self.nonce = new_nonce;
+        Ok(())
+    }
+}
+
+// Keys used by the simulator and what we know about them
+struct SimulatorState {
+    keys: Vec<KeyPair>,
+    balances: HashMap<PublicKey, KnownBalance>,
+    txs: HashMap<Hash, SimulatedTransaction>
+}
+
+impl SimulatorState {
+    fn new(keys_count: usize) -> Self {
+        Self {
+            keys: (0..keys_count).map(|_| KeyPair::new()).collect(),
+            balances: HashMap::new(),
+            txs: HashMap::new()
+        }
+    }
+
+    // Compute the plaintext balance of a key from its versioned balances
+    // Each version not known yet is rebuilt using the block reward and the simulated transactions
+    // Returns None if the result doesn't match the ciphertext (transfer not generated by us)
+    async fn get_balance<S: Storage>(&mut self, storage: &S, keypair: &KeyPair) -> Result<Option<(u64, CiphertextCache)>, BlockchainError> {
+        let key = keypair.get_public_key().compress();
+        if !storage.has_balance_for(&key, &XELIS_ASSET).await? {
+            return Ok(None)
+        }
+
+        let (topoheight, last) = storage.get_last_balance(&key, &XELIS_ASSET).await?;
+        let mut ciphertext = last.get_balance().clone();
+
+        // Search the last known version, versions rewritten by a reorg are not matching anymore
+        let known = self.balances.get(&key);
+        let mut balance = 0;
+        let mut versions = Vec::new();
+        let mut current = Some((topoheight, last));
+        while let Some((topo, version)) = current.take() {
+            if let Some(known) = known.filter(|k| k.topoheight == topo && *version.get_balance().compress() == k.ciphertext) {
+                balance = known.balance;
+                break;
+            }
+
+            if let Some(previous) = version.get_previous_topoheight() {
+                current = Some((previous, storage.get_balance_at_exact_topoheight(&key, &XELIS_ASSET, previous).await?));
+            }
+            versions.push(topo);
+        }
+
+        for topo in versions.into_iter().rev() {
+            balance = self.apply_block_changes(storage, &key, topo, balance).await?;
+        }
+
+        // Verify that we have the right balance
+        let expected = keypair.decrypt_to_point(&keypair.get_public_key().encrypt(balance));
+        if keypair.decrypt_to_point(ciphertext.decompressed()?) != expected {
+            debug!("Unknown balance for simulated key {} at topoheight {}", key.as_address(storage.is_mainnet()), topoheight);
+            return Ok(None)
+        }
+
+        self.balances.insert(key, KnownBalance {
+            topoheight,
+            ciphertext: ciphertext.compressed().clone(),
+            balance
+        });
+
+        Ok(Some((balance, ciphertext)))
+    }
+
+    // Apply the changes made by the block ordered at this topoheight to the balance of a key
+    async fn apply_block_changes<S: Storage>(&self, storage: &S, key: &PublicKey, topoheight: u64, mut balance: u64) -> Result<u64, BlockchainError> {
+        let hash = storage.get_hash_at_topo_height(topoheight).await?;
+        let block = storage.get_block_by_hash(&hash).await?;
+
+        let mut total_fees = 0;
+        for (tx_hash, tx) in block.get_txs_hashes().iter().zip(block.get_transactions()) {
+            if !storage.is_tx_executed_in_block(tx_hash, &hash)? {
+                continue;
+            }
+
+            total_fees += tx.get_fee();
+            if let Some(simulated) = self.txs.get(tx_hash) {
+                if simulated.source == *key {
+                    balance = balance.saturating_sub(simulated.spent);
+                }
+
+                for (destination, amount) in simulated.transfers.iter() {
+                    if destination == key {
+                        balance += amount;
+                    }
+                }
+            }
+        }
+
+        if block.get_miner() == key {
+            let mut reward = storage.get_block_reward_at_topo_height(topoheight)?;
+            reward -= reward * get_block_dev_fee(block.get_height()) / 100;
+            balance += reward + total_fees;
+        }
+
+        Ok(balance)
+    }
+}
+
 impl Simulator {
     // Start the Simulator mode to generate new blocks automatically
-    // It generates random miner keys and mine blocks with them
-    pub async fn start<S: Storage>(&self, blockchain: Arc<Blockchain<S>>) {
+    // It generates random keys, mine blocks with them and send transfers between them
+    pub async fn start<S: Storage>(&self, blockchain: Arc<Blockchain<S>>, config: SimulatorConfig) {
+        if *self == Self::Manual {
+            info!("Simulator is in manual mode, no block will be generated automatically");
+            return;
+        }
+
         let millis_interval = match self {
             Self::Stress => 300,
-            _ => BLOCK_TIME_MILLIS
+            _ => blockchain.get_chain_params().block_time_millis
         };
 
+        let max_blocks = config.blocks_per_interval.unwrap_or_else(|| match self {
+            Self::BlockDag => TIPS_LIMIT,
+            Self::Stress => 10,
+            _ => 1
+        });
+
+        let max_txs = config.txs_per_interval.unwrap_or_else(|| match self {
+            Self::Stress => 200,
+            _ => 15
+        });
+
         let mut interval = interval(Duration::from_millis(millis_interval));
         let mut rng = OsRng;
-        let mut keys: Vec<KeyPair> = Vec::new();
-
-        // Generate 100 random keys for mining
-        for _ in 0..100 {
-            keys.push(KeyPair::new());
-        }
+        let mut state = SimulatorState::new(config.keys);
 
         loop {
             interval.tick().await;
             info!("Adding new simulated block...");
-            // Number of blocks to generate
-            let blocks_count = match self {
-                Self::BlockDag => rng.gen_range(1..=TIPS_LIMIT),
-                Self::Stress => rng.gen_range(1..=10),
-                _ => 1
-            };
 
             // Generate blocks
-            let blocks = self.generate_blocks(blocks_count, &mut rng, &keys, &blockchain).await;
+            let blocks = self.generate_blocks(max_blocks, &mut rng, &state.keys, &blockchain).await;
 
             // Add all blocks to the chain
             for block in blocks {
@@ -90,13 +309,11 @@ impl Simulator {
                 }
             }
 
-            // TODO
-            // let max_txs = match self {
-            //     Self::Stress => 200,
-            //     _ => 15
-            // };
-
-            // self.generate_txs_in_mempool(max_txs, 15, 50, &mut rng, &keys, &blockchain).await;
+            if max_txs > 0 {
+                if let Err(e) = self.generate_txs_in_mempool(max_txs, &mut rng, &mut state, &blockchain).await {
+                    error!("Error while generating simulated TXs: {}", e);
+                }
+            }
         }
     }
 
@@ -118,98 +335,109 @@ impl Simulator {
         blocks
     }
 
-    // TODO use transaction builder
-    // async fn generate_txs_in_mempool(&self, max_txs: usize, max_transfers: usize, max_amount: u64, rng: &mut OsRng, keys: &Vec<KeyPair>, blockchain: &Arc<Blockchain<impl Storage>>) {
-    //     info!("Adding simulated TXs in mempool");
-    //     let n = rng.gen_range(0..max_txs);
-    //     let mut local_nonces = HashMap::new();
-    //     let mut local_balances = HashMap::new();
-    //     for _ in 0..n {
-    //         let index = rng.gen_range(0..keys.len());
-    //         let keypair = &keys[index];
-
-    //         let storage = blockchain.get_storage().read().await;
-    //         if let Ok(true) = storage.has_nonce(keypair.get_public_key()).await {
-    //             let mut transfers = Vec::new();
-    //             // Total XEL spend for this tx
-    //             let mut total_amount = FEE_PER_KB;
-    //             // Generate all transfers
-    //             for _ in 0..rng.gen_range(1..=max_transfers) {
-
-    //                 // Prevent to send to ourself
-    //                 let mut n = rng.gen_range(0..keys.len());
-    //                 while n == index {
-    //                     n = rng.gen_range(0..keys.len());
-    //                 }
-
-    //                 let amount = rng.gen_range(1..=max_amount);
-    //                 total_amount += amount;
-
-    //                 transfers.push(Transfer {
-    //                     to: keys[n].get_public_key().clone(),
-    //                     asset: XELIS_ASSET,
-    //                     amount,
-    //                     extra_data: None
-    //                 });
-    //             }
-
-    //             // Check if we have enough balance
-    //             match local_balances.entry(keypair.get_public_key()) {
-    //                 Entry::Occupied(mut e) => {
-    //                     let balance = e.get_mut();
-    //                     if *balance < total_amount {
-    //                         continue;
-    //                     }
-    //                     *balance -= total_amount;
-    //                 },
-    //                 Entry::Vacant(e) => {
-    //                     let balance = storage.get_last_balance(keypair.get_public_key(), &XELIS_ASSET).await.map(|(_, v)| v.get_balance()).unwrap();
-    //                     let balance = e.insert(balance);
-    //                     if *balance < total_amount {
-    //                         continue;
-    //                     }
-    //                     *balance -= total_amount;
-    //                 }
-    //             };
-
-    //             let data = TransactionType::Transfer(transfers);
-
-    //             // Get the last nonce for the key, it allow to have several txs from same sender
-    //             let nonce = match local_nonces.entry(keypair.get_public_key()) {
-    //                 Entry::Occupied(mut e) => {
-    //                     let nonce = e.get_mut();
-    //                     *nonce += 1;
-    //                     *nonce
-    //                 },
-    //                 Entry::Vacant(e) => {
-    //                     let (topo, version) = storage.get_last_nonce(keypair.get_public_key()).await.unwrap();
-    //                     let nonce = version.get_nonce();
-    //                     debug!("Loaded nonce from last nonce for {} is {} at topo {}", keypair.get_public_key(), nonce, topo);
-    //                     e.insert(nonce);
-    //                     nonce
-    //                 }
-    //             };
-
-    //             // Check if this nonce is not already used
-    //             {
-    //                 let mempool = blockchain.get_mempool().read().await;
-    //                 if mempool.is_nonce_used(&keypair.get_public_key(), nonce) {
-    //                     debug!("Nonce {} already used for key {}", nonce, keypair.get_public_key());
-    //                     continue;
-    //                 }
-    //             }
-
-    //             let key = keypair.get_public_key().clone();
-    //             // We create a fake signature because it is skipped in simulator mode
-    //             let signature = keypair.sign(b"invalid");
-    //             let tx = Transaction::new(key, data, FEE_PER_KB, nonce, signature);
-    //             let hash = tx.hash();
-
-    //             debug!("Simulated tx: {}, key: {}, nonce: {}, fee: {}", hash, tx.get_owner(), tx.get_nonce(), tx.get_fee());
-    //             if let Err(e) = blockchain.add_tx_to_mempool_with_hash(tx, hash, false).await {
-    //                 error!("Error while adding simulated tx to mempool: {}, key: {}", e, keypair.get_public_key());
-    //             }
-    //         }
-    //     }
-    // }
-}
\ No newline at end of file
+    // Generate random transfers between our keys and add them in mempool
+    // Only one TX per sender is pending at a time, so its balance is always the last one on chain
+    async fn generate_txs_in_mempool<S: Storage>(&self, max_txs: usize, rng: &mut OsRng, state: &mut SimulatorState, blockchain: &Arc<Blockchain<S>>) -> Result<(), BlockchainError> {
+        info!("Adding simulated TXs in mempool");
+        let n = rng.gen_range(0..=max_txs);
+        if n == 0 || state.keys.len() < 2 {
+            return Ok(())
+        }
+
+        let storage = blockchain.get_storage().read().await;
+        let mainnet = storage.is_mainnet();
+        let topoheight = blockchain.get_topo_height();
+        let reference = Reference {
+            hash: storage.get_hash_at_topo_height(topoheight).await?,
+            topoheight
+        };
+
+        // Forget the TXs that are too old to be reorganized
+        let stable_limit = blockchain.get_chain_params().stable_limit;
+        state.txs.retain(|_, tx| tx.topoheight + stable_limit * 2 >= topoheight);
+
+        // Senders with a TX still pending in mempool
+        let mut senders: HashSet<PublicKey> = {
+            let mempool = blockchain.get_mempool().read().await;
+            mempool.get_caches().keys().cloned().collect()
+        };
+
+        for _ in 0..n {
+            let index = rng.gen_range(0..state.keys.len());
+            let keypair = state.keys[index].clone();
+            let source = keypair.get_public_key().compress();
+            if senders.contains(&source) || !storage.has_nonce(&source).await? {
+                continue;
+            }
+
+            let Some((balance, ciphertext)) = state.get_balance(&*storage, &keypair).await? else {
+                continue;
+            };
+
+            let mut transfers = Vec::new();
+            let mut builders = Vec::new();
+            let mut total_amount = 0;
+            for _ in 0..rng.gen_range(1..=MAX_SIMULATED_TRANSFERS) {
+                // Prevent to send to ourself
+                let mut n = rng.gen_range(0..state.keys.len());
+                while n == index {
+                    n = rng.gen_range(0..state.keys.len());
+                }
+
+                let destination = state.keys[n].get_public_key();
+                let amount = rng.gen_range(1..=MAX_SIMULATED_AMOUNT);
+                total_amount += amount;
+
+                builders.push(TransferBuilder {
+                    asset: XELIS_ASSET,
+                    amount,
+                    destination: destination.to_address(mainnet),
+                    extra_data: None,
+                    lock: None
+                });
+                transfers.push((destination.compress(), amount));
+            }
+
+            // Keep the remaining balance for the fee
+            if balance <= total_amount {
+                continue;
+            }
+
+            let (_, version) = storage.get_last_nonce(&source).await?;
+            let mut account = SimulatedAccountState {
+                mainnet,
+                balance,
+                ciphertext,
+                reference: reference.clone(),
+                nonce: version.get_nonce()
+            };
+
+            let builder = TransactionBuilder::new(0, source.clone(), TransactionTypeBuilder::Transfers(builders), FeeBuilder::default());
+            let tx = match builder.build(&mut account, &keypair) {
+                Ok(tx) => tx,
+                Err(e) => {
+                    debug!("Error while building simulated tx: {}", e);
+                    continue;
+                }
+            };
+
+            let hash = tx.hash();
+            debug!("Simulated tx: {}, key: {}, nonce: {}, fee: {}", hash, source.as_address(mainnet), tx.get_nonce(), tx.get_fee());
+            let spent = total_amount + tx.get_fee();
+            if let Err(e) = blockchain.add_tx_to_mempool_with_storage_and_hash(&*storage, Arc::new(tx), hash.clone(), false).await {
+                error!("Error while adding simulated tx to mempool: {}, key: {}", e, source.as_address(mainnet));
+                continue;
+            }
+
+            state.txs.insert(hash, SimulatedTransaction {
+                source: source.clone(),
+                spent,
+                transfers,
+                topoheight
+            });
+            senders.insert(source);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,406 @@
+use std::{
+    fs::{self, File},
+    io::{BufReader, BufWriter, Read, Write},
+    path::Path
+};
+use ed25519_dalek::{
+    Keypair,
+    PublicKey,
+    SecretKey,
+    Signature,
+    Signer
+};
+use log::{debug, warn};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use xelis_common::{
+    crypto::Hash,
+    network::Network,
+    time::{get_current_time_in_millis, TimestampMillis}
+};
+use super::{
+    blockchain::Blockchain,
+    error::BlockchainError,
+    storage::Storage
+};
+
+// Files of a snapshot directory
+pub const SNAPSHOT_DATA_FILE: &str = "snapshot.bin";
+pub const SNAPSHOT_MANIFEST_FILE: &str = "manifest.json";
+pub const SNAPSHOT_SIGNATURE_FILE: &str = "manifest.sig";
+// Version of the snapshot format
+pub const SNAPSHOT_VERSION: u8 = 1;
+
+// Records of the data file
+const RECORD_TREE: u8 = 0;
+const RECORD_ENTRY: u8 = 1;
+const RECORD_END: u8 = 2;
+// Maximum size of a key or value in the data file
+const MAX_RECORD_SIZE: u32 = 64 * 1024 * 1024;
+// Entries inserted per batch during an import
+const IMPORT_BATCH_SIZE: usize = 10_000;
+
+#[derive(Error, Debug)]
+pub enum SnapshotError {
+    #[error("Snapshot path '{}' already exists", _0)]
+    AlreadyExists(String),
+    #[error("No trusted snapshot publisher configured")]
+    NoTrustedPublishers,
+    #[error("Snapshot is not signed")]
+    Unsigned,
+    #[error("Snapshot publisher {} is not trusted", _0)]
+    UntrustedPublisher(String),
+    #[error("Invalid snapshot signature")]
+    InvalidSignature,
+    #[error("Invalid ed25519 key")]
+    InvalidKey,
+    #[error("Unsupported snapshot version {}", _0)]
+    UnsupportedVersion(u8),
+    #[error("Snapshot is for network {} but expected {}", _0, _1)]
+    InvalidNetwork(Network, Network),
+    #[error("Snapshot data doesn't match its manifest")]
+    DataMismatch,
+    #[error("Invalid snapshot data")]
+    InvalidData,
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Hex(#[from] hex::FromHexError),
+    #[error(transparent)]
+    Database(#[from] sled::Error)
+}
+
+// Describe the data file of a snapshot
+// The publisher signature is done over the bytes of the manifest file
+#[derive(Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    pub version: u8,
+    pub network: Network,
+    pub topoheight: u64,
+    pub top_block_hash: Hash,
+    pub created_at: TimestampMillis,
+    // size in bytes of the data file
+    pub size: u64,
+    // blake3 hash of the data file
+    pub hash: Hash
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SnapshotSignature {
+    // hex encoded ed25519 public key of the publisher
+    pub publisher: String,
+    // hex encoded signature of the manifest file
+    pub signature: String
+}
+
+// Write the data file of a snapshot while hashing it
+pub struct SnapshotWriter {
+    writer: BufWriter<File>,
+    hasher: blake3::Hasher,
+    size: u64
+}
+
+impl SnapshotWriter {
+    fn new(file: File) -> Self {
+        Self {
+            writer: BufWriter::new(file),
+            hasher: blake3::Hasher::new(),
+            size: 0
+        }
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> Result<(), SnapshotError> {
+        self.writer.write_all(bytes)?;
+        self.hasher.update(bytes);
+        self.size += bytes.len() as u64;
+        Ok(())
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), SnapshotError> {
+        self.write(&(bytes.len() as u32).to_be_bytes())?;
+        self.write(bytes)
+    }
+
+    // All the next entries are stored in this tree
+    pub fn write_tree(&mut self, name: &[u8]) -> Result<(), SnapshotError> {
+        self.write(&[RECORD_TREE])?;
+        self.write_bytes(name)
+    }
+
+    pub fn write_entry(&mut self, key: &[u8], value: &[u8]) -> Result<(), SnapshotError> {
+        self.write(&[RECORD_ENTRY])?;
+        self.write_bytes(key)?;
+        self.write_bytes(value)
+    }
+
+    // Returns the size and the hash of the data file
+    fn finish(mut self) -> Result<(u64, Hash), SnapshotError> {
+        self.write(&[RECORD_END])?;
+        self.writer.flush()?;
+        Ok((self.size, Hash::new(self.hasher.finalize().into())))
+    }
+}
+
+// Read the data file of a snapshot while hashing it
+struct SnapshotReader<R: Read> {
+    reader: R,
+    hasher: blake3::Hasher,
+    size: u64
+}
+
+impl<R: Read> SnapshotReader<R> {
+    fn new(reader: R) -> Self {
+        Self {
+            reader,
+            hasher: blake3::Hasher::new(),
+            size: 0
+        }
+    }
+
+    fn read(&mut self, buf: &mut [u8]) -> Result<(), SnapshotError> {
+        self.reader.read_exact(buf)?;
+        self.hasher.update(buf);
+        self.size += buf.len() as u64;
+        Ok(())
+    }
+
+    fn read_u8(&mut self) -> Result<u8, SnapshotError> {
+        let mut buf = [0u8; 1];
+        self.read(&mut buf)?;
+        Ok(buf[0])
+    }
+
+    fn read_bytes(&mut self) -> Result<Vec<u8>, SnapshotError> {
+        let mut len = [0u8; 4];
+        self.read(&mut len)?;
+        let len = u32::from_be_bytes(len);
+        if len > MAX_RECORD_SIZE {
+            return Err(SnapshotError::InvalidData)
+        }
+
+        let mut bytes = vec![0u8; len as usize];
+        self.read(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    fn finish(self) -> (u64, Hash) {
+        (self.size, Hash::new(self.hasher.finalize().into()))
+    }
+}
+
+// Load a hex encoded ed25519 secret key from a file
+pub fn load_signing_key(path: &Path) -> Result<Keypair, SnapshotError> {
+    let bytes = hex::decode(fs::read_to_string(path)?.trim())?;
+    let secret = SecretKey::from_bytes(&bytes).map_err(|_| SnapshotError::InvalidKey)?;
+    let public = PublicKey::from(&secret);
+    Ok(Keypair { secret, public })
+}
+
+// Generate a new signing key for a snapshot publisher and save it hex encoded
+// Returns its public key, which must be trusted by the nodes importing the snapshots
+pub fn generate_signing_key(path: &Path) -> Result<PublicKey, SnapshotError> {
+    if path.exists() {
+        return Err(SnapshotError::AlreadyExists(path.display().to_string()))
+    }
+
+    let bytes: [u8; 32] = rand::thread_rng().gen();
+    let secret = SecretKey::from_bytes(&bytes).map_err(|_| SnapshotError::InvalidKey)?;
+    fs::write(path, hex::encode(secret.as_bytes()))?;
+    Ok(PublicKey::from(&secret))
+}
+
+// Parse the hex encoded public keys of the trusted publishers
+pub fn parse_publisher_keys(keys: &[String]) -> Result<Vec<PublicKey>, SnapshotError> {
+    keys.iter()
+        .map(|key| PublicKey::from_bytes(&hex::decode(key)?).map_err(|_| SnapshotError::InvalidKey))
+        .collect()
+}
+
+fn sign_manifest(manifest: &[u8], key: &Keypair) -> SnapshotSignature {
+    let signature = key.sign(manifest);
+    SnapshotSignature {
+        publisher: hex::encode(key.public.as_bytes()),
+        signature: hex::encode(signature.to_bytes())
+    }
+}
+
+// Verify that the manifest is signed by one of the trusted publishers
+fn verify_manifest_signature(manifest: &[u8], signature: &SnapshotSignature, trusted_publishers: &[PublicKey]) -> Result<(), SnapshotError> {
+    let publisher = PublicKey::from_bytes(&hex::decode(&signature.publisher)?)
+        .map_err(|_| SnapshotError::InvalidKey)?;
+    if !trusted_publishers.contains(&publisher) {
+        return Err(SnapshotError::UntrustedPublisher(signature.publisher.clone()))
+    }
+
+    let signature = Signature::try_from(hex::decode(&signature.signature)?.as_slice())
+        .map_err(|_| SnapshotError::InvalidSignature)?;
+    publisher.verify_strict(manifest, &signature)
+        .map_err(|_| SnapshotError::InvalidSignature)
+}
+
+// Verify the manifest of a snapshot and its publisher signature
+// Snapshots unsigned or signed by an untrusted publisher are refused unless allow_unsigned is set
+pub fn verify_manifest(dir: &Path, network: Network, trusted_publishers: &[PublicKey], allow_unsigned: bool) -> Result<SnapshotManifest, SnapshotError> {
+    if trusted_publishers.is_empty() && !allow_unsigned {
+        return Err(SnapshotError::NoTrustedPublishers)
+    }
+
+    let manifest_bytes = fs::read(dir.join(SNAPSHOT_MANIFEST_FILE))?;
+    let signature_path = dir.join(SNAPSHOT_SIGNATURE_FILE);
+    let result = if signature_path.exists() {
+        let signature: SnapshotSignature = serde_json::from_slice(&fs::read(signature_path)?)?;
+        verify_manifest_signature(&manifest_bytes, &signature, trusted_publishers)
+    } else {
+        Err(SnapshotError::Unsigned)
+    };
+
+    match result {
+        Ok(()) => debug!("Snapshot manifest signature is valid"),
+        // A trusted publisher with an invalid signature means the snapshot got tampered
+        Err(e @ (SnapshotError::Unsigned | SnapshotError::UntrustedPublisher(_))) if allow_unsigned => {
+            warn!("Snapshot is not signed by a trusted publisher: {}", e);
+        },
+        Err(e) => return Err(e)
+    };
+
+    let manifest: SnapshotManifest = serde_json::from_slice(&manifest_bytes)?;
+    if manifest.version != SNAPSHOT_VERSION {
+        return Err(SnapshotError::UnsupportedVersion(manifest.version))
+    }
+
+    if manifest.network != network {
+        return Err(SnapshotError::InvalidNetwork(manifest.network, network))
+    }
+
+    Ok(manifest)
+}
+
+// Export the chain in a new snapshot directory, signed if a key is provided
+// The storage is locked during the export so the snapshot is consistent
+pub async fn export_snapshot<S: Storage>(blockchain: &Blockchain<S>, dir: &Path, signing_key: Option<&Keypair>) -> Result<SnapshotManifest, BlockchainError> {
+    if dir.exists() {
+        return Err(SnapshotError::AlreadyExists(dir.display().to_string()).into())
+    }
+    fs::create_dir_all(dir)?;
+
+    let (topoheight, top_block_hash, size, hash) = {
+        let storage = blockchain.get_storage().read().await;
+        let topoheight = storage.get_top_topoheight()?;
+        let top_block_hash = storage.get_top_block_hash().await?;
+
+        let mut writer = SnapshotWriter::new(File::create(dir.join(SNAPSHOT_DATA_FILE))?);
+        storage.export_snapshot(&mut writer).await?;
+        let (size, hash) = writer.finish()?;
+        (topoheight, top_block_hash, size, hash)
+    };
+
+    let manifest = SnapshotManifest {
+        version: SNAPSHOT_VERSION,
+        network: *blockchain.get_network(),
+        topoheight,
+        top_block_hash,
+        created_at: get_current_time_in_millis(),
+        size,
+        hash
+    };
+
+    let manifest_bytes = serde_json::to_vec_pretty(&manifest).map_err(SnapshotError::from)?;
+    fs::write(dir.join(SNAPSHOT_MANIFEST_FILE), &manifest_bytes)?;
+
+    if let Some(key) = signing_key {
+        let signature = sign_manifest(&manifest_bytes, key);
+        fs::write(dir.join(SNAPSHOT_SIGNATURE_FILE), serde_json::to_vec_pretty(&signature).map_err(SnapshotError::from)?)?;
+    }
+
+    Ok(manifest)
+}
+
+// Import the data file of a verified manifest in a new database
+// The data is hashed while imported and must match the manifest
+pub fn import_snapshot_data(dir: &Path, manifest: &SnapshotManifest, db_path: &Path) -> Result<(), SnapshotError> {
+    if db_path.exists() {
+        return Err(SnapshotError::AlreadyExists(db_path.display().to_string()))
+    }
+
+    let db = sled::open(db_path)?;
+    let mut reader = SnapshotReader::new(BufReader::new(File::open(dir.join(SNAPSHOT_DATA_FILE))?));
+    let mut tree: Option<sled::Tree> = None;
+    let mut batch = sled::Batch::default();
+    let mut pending = 0;
+    loop {
+        match reader.read_u8()? {
+            RECORD_TREE => {
+                if let Some(tree) = tree.take() {
+                    tree.apply_batch(std::mem::take(&mut batch))?;
+                    pending = 0;
+                }
+
+                let name = reader.read_bytes()?;
+                tree = Some(db.open_tree(name)?);
+            },
+            RECORD_ENTRY => {
+                let Some(tree) = tree.as_ref() else {
+                    return Err(SnapshotError::InvalidData)
+                };
+
+                let key = reader.read_bytes()?;
+                let value = reader.read_bytes()?;
+                batch.insert(key, value);
+                pending += 1;
+                if pending >= IMPORT_BATCH_SIZE {
+                    tree.apply_batch(std::mem::take(&mut batch))?;
+                    pending = 0;
+                }
+            },
+            RECORD_END => break,
+            _ => return Err(SnapshotError::InvalidData)
+        }
+    }
+
+    if let Some(tree) = tree {
+        tree.apply_batch(batch)?;
+    }
+
+    let (size, hash) = reader.finish();
+    if size != manifest.size || hash != manifest.hash {
+        return Err(SnapshotError::DataMismatch)
+    }
+
+    db.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keypair() -> Keypair {
+        let secret = SecretKey::from_bytes(&[7u8; 32]).unwrap();
+        let public = PublicKey::from(&secret);
+        Keypair { secret, public }
+    }
+
+    #[test]
+    fn test_manifest_signature() {
+        let key = keypair();
+        let manifest = b"manifest";
+        let signature = sign_manifest(manifest, &key);
+        assert!(verify_manifest_signature(manifest, &signature, &[key.public]).is_ok());
+
+        // tampered manifest
+        assert!(matches!(verify_manifest_signature(b"manifest2", &signature, &[key.public]), Err(SnapshotError::InvalidSignature)));
+        // publisher not trusted
+        assert!(matches!(verify_manifest_signature(manifest, &signature, &[]), Err(SnapshotError::UntrustedPublisher(_))));
+    }
+
+    #[test]
+    fn test_parse_publisher_keys() {
+        let key = keypair();
+        let keys = parse_publisher_keys(&[hex::encode(key.public.as_bytes())]).unwrap();
+        assert_eq!(keys, vec![key.public]);
+        assert!(parse_publisher_keys(&["00".to_owned()]).is_err());
+    }
+}
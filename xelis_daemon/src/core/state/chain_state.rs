@@ -8,7 +8,9 @@ use xelis_common::{
         VersionedBalance,
         VersionedNonce
     },
+    asset::AssetState,
     config::XELIS_ASSET,
+    contract::Program,
     crypto::{
         elgamal::Ciphertext,
         Hash,
@@ -16,16 +18,21 @@ use xelis_common::{
         PublicKey
     },
     transaction::{
+        lock::LockedTransfer,
         verify::BlockchainVerificationState,
+        MultiSigPayload,
         Reference,
         Transaction
     },
     utils::format_xelis
 };
-use crate::core::{
-    blockchain,
-    error::BlockchainError,
-    storage::Storage
+use crate::{
+    core::{
+        blockchain,
+        error::BlockchainError,
+        hard_fork::is_tx_allowed_in_block_version,
+        storage::Storage
+    }
 };
 
 // Sender changes
@@ -82,7 +89,12 @@ struct Account<'a> {
     // TODO: they must store also the ciphertext change
     // It will be added by next change at each TX
     // This is necessary to easily build the final user balance
-    assets: HashMap<&'a Hash, Echange>
+    assets: HashMap<&'a Hash, Echange>,
+    // Multisig configuration of the account
+    // None if the account has no multisig
+    multisig: Option<MultiSigPayload>,
+    // If the multisig configuration was changed by a transaction
+    multisig_updated: bool
 }
 
 pub enum StorageReference<'a, S: Storage> {
@@ -136,8 +148,21 @@ pub struct ChainState<'a, S: Storage> {
     // Sender accounts
     // This is used to verify ZK Proofs and store/update nonces
     accounts: HashMap<&'a PublicKey, Account<'a>>,
+    // States of the assets used by the transactions
+    // None if the asset has no state
+    assets: HashMap<Hash, Option<AssetState>>,
+    // Locked transfers created or unlocked by the transactions
+    // None once unlocked
+    locks: HashMap<Hash, Option<LockedTransfer>>,
+    // Contracts deployed by the transactions
+    contracts: HashMap<Hash, Program>,
+    // Contracts storage values changed by the transactions
+    // None once deleted
+    contract_data: HashMap<(Hash, u64), Option<u64>>,
     // Current topoheight of the snapshot
-    topoheight: u64
+    topoheight: u64,
+    // Version of the block in which the transactions are executed
+    block_version: u8
 }
 
 // Chain State that can be applied to the mutable storage
@@ -172,9 +197,9 @@ impl<'a, S: Storage> AsMut<ChainState<'a, S>> for ApplicableChainState<'a, S> {
 }
 
 impl<'a, S: Storage> ApplicableChainState<'a, S> {
-    pub fn new(storage: &'a mut S, topoheight: u64) -> Self {
+    pub fn new(storage: &'a mut S, topoheight: u64, block_version: u8) -> Self {
         Self {
-            inner: ChainState::with(StorageReference::Mutable(storage), topoheight)
+            inner: ChainState::with(StorageReference::Mutable(storage), topoheight, block_version)
         }
     }
 
@@ -192,6 +217,13 @@ impl<'a, S: Storage> ApplicableChainState<'a, S> {
             trace!("Saving {} for {} at topoheight {}", account.nonce, key.as_address(self.inner.storage.is_mainnet()), self.inner.topoheight);
             self.inner.storage.set_last_nonce_to(key, self.inner.topoheight, &account.nonce).await?;
 
+            if account.multisig_updated {
+                if let Some(multisig) = &account.multisig {
+                    trace!("Saving multisig for {} at topoheight {}", key.as_address(self.inner.storage.is_mainnet()), self.inner.topoheight);
+                    self.inner.storage.set_multisig_for(key, self.inner.topoheight, multisig).await?;
+                }
+            }
+
             let balances = self.inner.receiver_balances.entry(&key).or_insert_with(HashMap::new);
             // Because account balances are only used to verify the validity of ZK Proofs, we can't store them
             // We have to recompute the final balance for each asset using the existing current balance
@@ -267,6 +299,32 @@ impl<'a, S: Storage> ApplicableChainState<'a, S> {
             }
         }
 
+        // Apply all asset states changes at topoheight
+        for (asset, state) in &self.inner.assets {
+            if let Some(state) = state {
+                trace!("Saving asset state {} at topoheight {}", asset, self.inner.topoheight);
+                self.inner.storage.set_asset_state(asset, self.inner.topoheight, state).await?;
+            }
+        }
+
+        // Apply all locked transfers changes at topoheight
+        for (id, locked) in &self.inner.locks {
+            trace!("Saving locked transfer {} at topoheight {}", id, self.inner.topoheight);
+            self.inner.storage.set_locked_transfer(id, self.inner.topoheight, locked.as_ref()).await?;
+        }
+
+        // Apply all contracts deployed at topoheight
+        for (contract, program) in &self.inner.contracts {
+            trace!("Saving contract {} at topoheight {}", contract, self.inner.topoheight);
+            self.inner.storage.set_contract(contract, self.inner.topoheight, program).await?;
+        }
+
+        // Apply all contracts storage changes at topoheight
+        for ((contract, key), value) in &self.inner.contract_data {
+            trace!("Saving contract {} data {} at topoheight {}", contract, key, self.inner.topoheight);
+            self.inner.storage.set_contract_data(contract, *key, self.inner.topoheight, *value).await?;
+        }
+
         // Apply all balances changes at topoheight
         // We injected the sender balances in the receiver balances previously
         for (account, balances) in self.inner.receiver_balances {
@@ -292,17 +350,22 @@ impl<'a, S: Storage> ApplicableChainState<'a, S> {
 }
 
 impl<'a, S: Storage> ChainState<'a, S> {
-    fn with(storage: StorageReference<'a, S>, topoheight: u64) -> Self {
+    fn with(storage: StorageReference<'a, S>, topoheight: u64, block_version: u8) -> Self {
         Self {
             storage,
             receiver_balances: HashMap::new(),
             accounts: HashMap::new(),
-            topoheight
+            assets: HashMap::new(),
+            locks: HashMap::new(),
+            contracts: HashMap::new(),
+            contract_data: HashMap::new(),
+            topoheight,
+            block_version
         }
     }
 
-    pub fn new(storage: &'a S, topoheight: u64) -> Self {
-        Self::with(StorageReference::Immutable(storage), topoheight)
+    pub fn new(storage: &'a S, topoheight: u64, block_version: u8) -> Self {
+        Self::with(StorageReference::Immutable(storage), topoheight, block_version)
     }
 
     // Get the storage used by the chain state
@@ -329,9 +392,14 @@ impl<'a, S: Storage> ChainState<'a, S> {
             .ok_or_else(|| BlockchainError::AccountNotFound(key.as_address(storage.is_mainnet())))?;
         version.set_previous_topoheight(Some(topo));
 
+        let multisig = storage.get_multisig_at_maximum_topoheight_for(key, topoheight).await?
+            .map(|(_, multisig)| multisig);
+
         Ok(Account {
             nonce: version,
-            assets: HashMap::new()
+            assets: HashMap::new(),
+            multisig,
+            multisig_updated: false
         })
     }
 
@@ -423,6 +491,73 @@ impl<'a, S: Storage> ChainState<'a, S> {
         Ok(())
     }
 
+    // Retrieve the multisig configuration of a sender account
+    async fn internal_get_multisig_state<'b>(&'b mut self, key: &'a PublicKey) -> Result<Option<&'b MultiSigPayload>, BlockchainError> {
+        let account = match self.accounts.entry(key) {
+            Entry::Occupied(o) => o.into_mut(),
+            Entry::Vacant(e) => {
+                let account = Self::create_sender_account(key, &self.storage, self.topoheight).await?;
+                e.insert(account)
+            }
+        };
+
+        Ok(account.multisig.as_ref().filter(|multisig| !multisig.is_delete()))
+    }
+
+    // Update the multisig configuration of a sender account
+    // Account must have been fetched before calling this function
+    async fn internal_set_multisig_state(&mut self, key: &'a PublicKey, multisig: &MultiSigPayload) -> Result<(), BlockchainError> {
+        let account = self.accounts.get_mut(key)
+            .ok_or_else(|| BlockchainError::NoTxSender(key.as_address(self.storage.is_mainnet())))?;
+
+        account.multisig = Some(multisig.clone());
+        account.multisig_updated = true;
+
+        Ok(())
+    }
+
+    // Retrieve the asset state, fetched lazily from the storage
+    async fn internal_get_asset_state<'b>(&'b mut self, asset: &Hash) -> Result<Option<&'b mut AssetState>, BlockchainError> {
+        match self.assets.entry(asset.clone()) {
+            Entry::Occupied(o) => Ok(o.into_mut().as_mut()),
+            Entry::Vacant(e) => {
+                let state = self.storage.get_asset_state_at_maximum_topoheight(asset, self.topoheight).await?
+                    .map(|(_, state)| state);
+                Ok(e.insert(state).as_mut())
+            }
+        }
+    }
+
+    // Retrieve a locked transfer from our changes or from the storage
+    async fn internal_get_locked_transfer(&self, id: &Hash) -> Result<Option<LockedTransfer>, BlockchainError> {
+        if let Some(locked) = self.locks.get(id) {
+            return Ok(locked.clone());
+        }
+
+        Ok(self.storage.get_locked_transfer_at_maximum_topoheight(id, self.topoheight).await?
+            .and_then(|(_, locked)| locked))
+    }
+
+    // Retrieve a contract deployed by our changes or from the storage
+    async fn internal_get_contract(&self, contract: &Hash) -> Result<Option<Program>, BlockchainError> {
+        if let Some(program) = self.contracts.get(contract) {
+            return Ok(Some(program.clone()));
+        }
+
+        Ok(self.storage.get_contract_at_maximum_topoheight(contract, self.topoheight).await?
+            .map(|(_, program)| program))
+    }
+
+    // Retrieve a contract storage value from our changes or from the storage
+    async fn internal_get_contract_data(&self, contract: &Hash, key: u64) -> Result<Option<u64>, BlockchainError> {
+        if let Some(value) = self.contract_data.get(&(contract.clone(), key)) {
+            return Ok(*value);
+        }
+
+        Ok(self.storage.get_contract_data_at_maximum_topoheight(contract, key, self.topoheight).await?
+            .and_then(|(_, value)| value))
+    }
+
     // Reward a miner for the block mined
     pub async fn reward_miner(&mut self, miner: &'a PublicKey, reward: u64) -> Result<(), BlockchainError> {
         debug!("Rewarding miner {} with {} XEL at topoheight {}", miner.as_address(self.storage.is_mainnet()), format_xelis(reward), self.topoheight);
@@ -442,7 +577,7 @@ impl<'a, S: Storage> BlockchainVerificationState<'a, BlockchainError> for ChainS
         tx: &Transaction,
     ) -> Result<(), BlockchainError> {
         // Check the version
-        if tx.get_version() != 0 {
+        if !is_tx_allowed_in_block_version(&self.storage.get_network()?, self.block_version, tx) {
             debug!("Invalid version for tx {}: {}", tx.hash(), tx.get_version());
             return Err(BlockchainError::InvalidTxVersion);
         }
@@ -521,4 +656,100 @@ impl<'a, S: Storage> BlockchainVerificationState<'a, BlockchainError> for ChainS
     ) -> Result<(), BlockchainError> {
         self.internal_update_account_nonce(account, new_nonce).await
     }
+
+    /// Get the state of an asset created by a transaction
+    async fn get_asset_state<'b>(
+        &'b mut self,
+        asset: &Hash
+    ) -> Result<Option<&'b mut AssetState>, BlockchainError> {
+        self.internal_get_asset_state(asset).await
+    }
+
+    /// Set the initial state of an asset created by a transaction
+    async fn set_asset_state(
+        &mut self,
+        asset: Hash,
+        state: AssetState
+    ) -> Result<(), BlockchainError> {
+        self.assets.insert(asset, Some(state));
+        Ok(())
+    }
+
+    /// Get the multisig configuration of an account
+    async fn get_multisig_state<'b>(
+        &'b mut self,
+        account: &'a PublicKey
+    ) -> Result<Option<&'b MultiSigPayload>, BlockchainError> {
+        self.internal_get_multisig_state(account).await
+    }
+
+    /// Set the multisig configuration of an account
+    async fn set_multisig_state(
+        &mut self,
+        account: &'a PublicKey,
+        config: &MultiSigPayload
+    ) -> Result<(), BlockchainError> {
+        self.internal_set_multisig_state(account, config).await
+    }
+
+    /// Topoheight at which the transactions are executed
+    fn get_verification_topoheight(&self) -> u64 {
+        self.topoheight
+    }
+
+    /// Get a locked transfer
+    async fn get_locked_transfer(
+        &mut self,
+        id: &Hash
+    ) -> Result<Option<LockedTransfer>, BlockchainError> {
+        self.internal_get_locked_transfer(id).await
+    }
+
+    /// Set a locked transfer, None once it is unlocked
+    async fn set_locked_transfer(
+        &mut self,
+        id: Hash,
+        locked: Option<LockedTransfer>
+    ) -> Result<(), BlockchainError> {
+        self.locks.insert(id, locked);
+        Ok(())
+    }
+
+    /// Get the program of a deployed contract
+    async fn get_contract(
+        &mut self,
+        contract: &Hash
+    ) -> Result<Option<Program>, BlockchainError> {
+        self.internal_get_contract(contract).await
+    }
+
+    /// Set the program of a contract deployed by a transaction
+    async fn set_contract(
+        &mut self,
+        contract: Hash,
+        program: Program
+    ) -> Result<(), BlockchainError> {
+        self.contracts.insert(contract, program);
+        Ok(())
+    }
+
+    /// Get a value from the storage of a contract
+    async fn get_contract_data(
+        &mut self,
+        contract: &Hash,
+        key: u64
+    ) -> Result<Option<u64>, BlockchainError> {
+        self.internal_get_contract_data(contract, key).await
+    }
+
+    /// Set a value in the storage of a contract, None to delete it
+    async fn set_contract_data(
+        &mut self,
+        contract: &Hash,
+        key: u64,
+        value: Option<u64>
+    ) -> Result<(), BlockchainError> {
+        self.contract_data.insert((contract.clone(), key), value);
+        Ok(())
+    }
 } 
\ No newline at end of file
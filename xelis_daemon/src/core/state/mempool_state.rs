@@ -2,23 +2,30 @@ use std::collections::{hash_map::Entry, HashMap};
 use async_trait::async_trait;
 use log::debug;
 use xelis_common::{
+    asset::AssetState,
+    contract::Program,
     crypto::{
         elgamal::Ciphertext,
         Hash,
         PublicKey
     },
     transaction::{
+        lock::LockedTransfer,
         verify::BlockchainVerificationState,
+        MultiSigPayload,
         Reference,
         Transaction
     },
     utils::format_xelis
 };
-use crate::core::{
-    blockchain,
-    error::BlockchainError,
-    mempool::Mempool,
-    storage::Storage
+use crate::{
+    core::{
+        blockchain,
+        error::BlockchainError,
+        hard_fork::is_tx_allowed_in_block_version,
+        mempool::Mempool,
+        storage::Storage
+    }
 };
 
 struct Account<'a> {
@@ -41,18 +48,40 @@ pub struct MempoolState<'a, S: Storage> {
     // Sender accounts
     // This is used to verify ZK Proofs and store/update nonces
     accounts: HashMap<&'a PublicKey, Account<'a>>,
+    // States of the assets used by the transactions
+    // Assets created by a TX still in mempool have no state yet
+    assets: HashMap<Hash, Option<AssetState>>,
+    // Multisig configurations of the sender accounts
+    // None if the account has no multisig
+    multisigs: HashMap<&'a PublicKey, Option<MultiSigPayload>>,
+    // Locked transfers used by the transactions
+    // Transfers locked by a TX still in mempool can't be unlocked yet
+    locks: HashMap<Hash, Option<LockedTransfer>>,
+    // Contracts used by the transactions
+    // None if the contract doesn't exist
+    contracts: HashMap<Hash, Option<Program>>,
+    // Contracts storage values used by the transactions
+    contract_data: HashMap<(Hash, u64), Option<u64>>,
     // The current topoheight of the chain
     topoheight: u64,
+    // Version of the next block
+    block_version: u8,
 }
 
 impl<'a, S: Storage> MempoolState<'a, S> {
-    pub fn new(mempool: &'a Mempool, storage: &'a S, topoheight: u64) -> Self {
+    pub fn new(mempool: &'a Mempool, storage: &'a S, topoheight: u64, block_version: u8) -> Self {
         Self {
             mempool,
             storage,
             receiver_balances: HashMap::new(),
             accounts: HashMap::new(),
+            assets: HashMap::new(),
+            multisigs: HashMap::new(),
+            locks: HashMap::new(),
+            contracts: HashMap::new(),
+            contract_data: HashMap::new(),
             topoheight,
+            block_version,
         }
     }
 
@@ -172,6 +201,68 @@ impl<'a, S: Storage> MempoolState<'a, S> {
         }
         Ok(())
     }
+
+    // Retrieve the asset state from our internal cache or from storage
+    async fn internal_get_asset_state<'b>(&'b mut self, asset: &Hash) -> Result<Option<&'b mut AssetState>, BlockchainError> {
+        match self.assets.entry(asset.clone()) {
+            Entry::Occupied(o) => Ok(o.into_mut().as_mut()),
+            Entry::Vacant(e) => {
+                let state = self.storage.get_asset_state_at_maximum_topoheight(asset, self.topoheight).await?
+                    .map(|(_, state)| state);
+                Ok(e.insert(state).as_mut())
+            }
+        }
+    }
+
+    // Retrieve the multisig configuration from our internal cache or from storage
+    async fn internal_get_multisig_state<'b>(&'b mut self, key: &'a PublicKey) -> Result<Option<&'b MultiSigPayload>, BlockchainError> {
+        let multisig = match self.multisigs.entry(key) {
+            Entry::Occupied(o) => o.into_mut(),
+            Entry::Vacant(e) => {
+                let multisig = self.storage.get_multisig_at_maximum_topoheight_for(key, self.topoheight).await?
+                    .map(|(_, multisig)| multisig);
+                e.insert(multisig)
+            }
+        };
+
+        Ok(multisig.as_ref().filter(|multisig| !multisig.is_delete()))
+    }
+
+    // Retrieve a locked transfer from our internal cache or from storage
+    async fn internal_get_locked_transfer(&mut self, id: &Hash) -> Result<Option<LockedTransfer>, BlockchainError> {
+        match self.locks.entry(id.clone()) {
+            Entry::Occupied(o) => Ok(o.get().clone()),
+            Entry::Vacant(e) => {
+                let locked = self.storage.get_locked_transfer_at_maximum_topoheight(id, self.topoheight).await?
+                    .and_then(|(_, locked)| locked);
+                Ok(e.insert(locked).clone())
+            }
+        }
+    }
+
+    // Retrieve a contract from our internal cache or from storage
+    async fn internal_get_contract(&mut self, contract: &Hash) -> Result<Option<Program>, BlockchainError> {
+        match self.contracts.entry(contract.clone()) {
+            Entry::Occupied(o) => Ok(o.get().clone()),
+            Entry::Vacant(e) => {
+                let program = self.storage.get_contract_at_maximum_topoheight(contract, self.topoheight).await?
+                    .map(|(_, program)| program);
+                Ok(e.insert(program).clone())
+            }
+        }
+    }
+
+    // Retrieve a contract storage value from our internal cache or from storage
+    async fn internal_get_contract_data(&mut self, contract: &Hash, key: u64) -> Result<Option<u64>, BlockchainError> {
+        match self.contract_data.entry((contract.clone(), key)) {
+            Entry::Occupied(o) => Ok(*o.get()),
+            Entry::Vacant(e) => {
+                let value = self.storage.get_contract_data_at_maximum_topoheight(contract, key, self.topoheight).await?
+                    .and_then(|(_, value)| value);
+                Ok(*e.insert(value))
+            }
+        }
+    }
 }
 
 #[async_trait]
@@ -182,7 +273,7 @@ impl<'a, S: Storage> BlockchainVerificationState<'a, BlockchainError> for Mempoo
         tx: &Transaction,
     ) -> Result<(), BlockchainError> {
         // Check the version
-        if tx.get_version() != 0 {
+        if !is_tx_allowed_in_block_version(&self.storage.get_network()?, self.block_version, tx) {
             debug!("Invalid version: {}", tx.get_version());
             return Err(BlockchainError::InvalidTxVersion);
         }
@@ -255,4 +346,101 @@ impl<'a, S: Storage> BlockchainVerificationState<'a, BlockchainError> for Mempoo
     ) -> Result<(), BlockchainError> {
         self.internal_update_account_nonce(account, new_nonce).await
     }
+
+    /// Get the state of an asset created by a transaction
+    async fn get_asset_state<'b>(
+        &'b mut self,
+        asset: &Hash
+    ) -> Result<Option<&'b mut AssetState>, BlockchainError> {
+        self.internal_get_asset_state(asset).await
+    }
+
+    /// Set the initial state of an asset created by a transaction
+    async fn set_asset_state(
+        &mut self,
+        asset: Hash,
+        state: AssetState
+    ) -> Result<(), BlockchainError> {
+        self.assets.insert(asset, Some(state));
+        Ok(())
+    }
+
+    /// Get the multisig configuration of an account
+    async fn get_multisig_state<'b>(
+        &'b mut self,
+        account: &'a PublicKey
+    ) -> Result<Option<&'b MultiSigPayload>, BlockchainError> {
+        self.internal_get_multisig_state(account).await
+    }
+
+    /// Set the multisig configuration of an account
+    async fn set_multisig_state(
+        &mut self,
+        account: &'a PublicKey,
+        config: &MultiSigPayload
+    ) -> Result<(), BlockchainError> {
+        self.multisigs.insert(account, Some(config.clone()));
+        Ok(())
+    }
+
+    /// Topoheight at which the transactions are executed
+    fn get_verification_topoheight(&self) -> u64 {
+        self.topoheight
+    }
+
+    /// Get a locked transfer
+    async fn get_locked_transfer(
+        &mut self,
+        id: &Hash
+    ) -> Result<Option<LockedTransfer>, BlockchainError> {
+        self.internal_get_locked_transfer(id).await
+    }
+
+    /// Set a locked transfer, None once it is unlocked
+    async fn set_locked_transfer(
+        &mut self,
+        id: Hash,
+        locked: Option<LockedTransfer>
+    ) -> Result<(), BlockchainError> {
+        self.locks.insert(id, locked);
+        Ok(())
+    }
+
+    /// Get the program of a deployed contract
+    async fn get_contract(
+        &mut self,
+        contract: &Hash
+    ) -> Result<Option<Program>, BlockchainError> {
+        self.internal_get_contract(contract).await
+    }
+
+    /// Set the program of a contract deployed by a transaction
+    async fn set_contract(
+        &mut self,
+        contract: Hash,
+        program: Program
+    ) -> Result<(), BlockchainError> {
+        self.contracts.insert(contract, Some(program));
+        Ok(())
+    }
+
+    /// Get a value from the storage of a contract
+    async fn get_contract_data(
+        &mut self,
+        contract: &Hash,
+        key: u64
+    ) -> Result<Option<u64>, BlockchainError> {
+        self.internal_get_contract_data(contract, key).await
+    }
+
+    /// Set a value in the storage of a contract, None to delete it
+    async fn set_contract_data(
+        &mut self,
+        contract: &Hash,
+        key: u64,
+        value: Option<u64>
+    ) -> Result<(), BlockchainError> {
+        self.contract_data.insert((contract.clone(), key), value);
+        Ok(())
+    }
 }
\ No newline at end of file
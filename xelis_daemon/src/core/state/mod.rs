@@ -1,9 +1,11 @@
 mod mempool_state;
 mod chain_state;
+mod parallel;
 
 use log::{trace, debug};
 pub use mempool_state::MempoolState;
 pub use chain_state::{ChainState, ApplicableChainState, StorageReference};
+pub use parallel::{ConflictDetector, verify_in_parallel};
 use xelis_common::{account::VersionedBalance, crypto::{Hash, PublicKey}, transaction::Reference};
 
 use super::{error::BlockchainError, storage::Storage};
@@ -0,0 +1,123 @@
+use std::{
+    collections::HashSet,
+    thread::available_parallelism
+};
+use tokio::task::spawn_blocking;
+use xelis_common::{
+    crypto::{proofs::BatchCollector, Hash, PublicKey},
+    transaction::{
+        verify::PartialVerification,
+        Transaction,
+        TransactionType
+    }
+};
+use crate::core::error::BlockchainError;
+
+// Part of the chain state read or written by a transaction
+#[derive(Hash, Eq, PartialEq)]
+enum Resource<'a> {
+    Account(&'a PublicKey),
+    Asset(&'a Hash),
+    Lock(&'a Hash),
+    Contract(&'a Hash)
+}
+
+// Detect the transactions of a block touching the same part of the chain state
+// Transactions accepted together are independent:
+// their proofs can be verified in parallel and applied in any order
+#[derive(Default)]
+pub struct ConflictDetector<'a> {
+    resources: HashSet<Resource<'a>>,
+    // A transaction which can't be run with any other is present
+    exclusive: bool,
+    count: usize
+}
+
+impl<'a> ConflictDetector<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Returns false if the transaction conflicts with the ones accepted
+    // In this case, nothing is registered
+    pub fn try_add(&mut self, tx: &'a Transaction, hash: &'a Hash) -> bool {
+        let mut resources = vec![Resource::Account(tx.get_source())];
+        let mut exclusive = false;
+        match tx.get_data() {
+            TransactionType::Transfers(transfers) => {
+                resources.extend(transfers.iter().map(|transfer| Resource::Account(transfer.get_destination())));
+            },
+            TransactionType::Burn(_) | TransactionType::MultiSig(_) => {},
+            TransactionType::CreateAsset(_) => resources.push(Resource::Asset(hash)),
+            TransactionType::MintAsset(payload) => resources.push(Resource::Asset(&payload.asset)),
+            TransactionType::TransferAssetOwnership(payload) => resources.push(Resource::Asset(&payload.asset)),
+            TransactionType::Unlock(payload) => resources.push(Resource::Lock(&payload.id)),
+            TransactionType::DeployContract(_) => resources.push(Resource::Contract(hash)),
+            // Its execution may read any part of the state
+            TransactionType::InvokeContract(_) => exclusive = true
+        };
+
+        if self.count > 0 && (self.exclusive || exclusive || resources.iter().any(|resource| self.resources.contains(resource))) {
+            return false
+        }
+
+        self.resources.extend(resources);
+        self.exclusive |= exclusive;
+        self.count += 1;
+
+        true
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.resources.clear();
+        self.exclusive = false;
+        self.count = 0;
+    }
+}
+
+fn verify_proofs(proofs: &Option<BatchCollector>) -> bool {
+    proofs.as_ref().map_or(false, |proofs| proofs.verify().is_ok())
+}
+
+// Verify the proofs of independent transactions in parallel
+// Proofs are taken from the verifications and checked on the blocking threads
+// so the runtime is not blocked
+// Results are in the same order as the verifications
+pub async fn verify_in_parallel(verifications: &mut [PartialVerification<'_>]) -> Result<Vec<bool>, BlockchainError> {
+    if verifications.len() <= 1 {
+        return Ok(verifications.iter()
+            .map(|verification| verification.verify().is_ok())
+            .collect())
+    }
+
+    let threads = available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .min(verifications.len());
+
+    let chunk_size = verifications.len().div_ceil(threads);
+    let mut proofs: Vec<_> = verifications.iter_mut()
+        .map(|verification| verification.take_proofs())
+        .collect();
+
+    let mut handles = Vec::with_capacity(threads);
+    while !proofs.is_empty() {
+        let chunk: Vec<_> = proofs.drain(..chunk_size.min(proofs.len())).collect();
+        handles.push(spawn_blocking(move || {
+            chunk.iter()
+                .map(verify_proofs)
+                .collect::<Vec<_>>()
+        }));
+    }
+
+    let mut results = Vec::with_capacity(verifications.len());
+    for handle in handles {
+        results.extend(handle.await?);
+    }
+
+    Ok(results)
+}
@@ -0,0 +1,77 @@
+use std::{
+    hash::Hash as StdHash,
+    num::NonZeroUsize,
+    sync::atomic::{AtomicU64, Ordering}
+};
+use lru::LruCache;
+use tokio::sync::{Mutex, MutexGuard};
+use xelis_common::api::daemon::CacheStats;
+
+// Size of each LRU cache of the storage, 0 to disable it
+#[derive(Debug, Clone, Copy)]
+pub struct CacheSizes {
+    // block headers and their tips
+    pub blocks: usize,
+    pub transactions: usize,
+    // versioned balances
+    pub balances: usize,
+    // topoheight by hash and hash at topoheight
+    pub topoheights: usize,
+    // cumulative difficulties and assets
+    pub others: usize
+}
+
+impl CacheSizes {
+    // Use the same size for all the caches
+    pub fn all(size: usize) -> Self {
+        Self {
+            blocks: size,
+            transactions: size,
+            balances: size,
+            topoheights: size,
+            others: size
+        }
+    }
+}
+
+// LRU cache in front of a tree, counting its hits and misses
+pub struct StorageCache<K: StdHash + Eq, V> {
+    inner: Mutex<LruCache<K, V>>,
+    hits: AtomicU64,
+    misses: AtomicU64
+}
+
+impl<K: StdHash + Eq, V> StorageCache<K, V> {
+    // Returns None if the cache is disabled
+    pub fn new(size: usize) -> Option<Self> {
+        NonZeroUsize::new(size).map(|size| Self {
+            inner: Mutex::new(LruCache::new(size)),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0)
+        })
+    }
+
+    pub async fn lock(&self) -> MutexGuard<'_, LruCache<K, V>> {
+        self.inner.lock().await
+    }
+
+    // Count a lookup in the cache
+    pub fn record(&self, hit: bool) {
+        if hit {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub async fn get_stats(&self, name: &str) -> CacheStats {
+        let cache = self.inner.lock().await;
+        CacheStats {
+            name: name.to_owned(),
+            capacity: cache.cap().get(),
+            size: cache.len(),
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed)
+        }
+    }
+}
@@ -1,32 +1,41 @@
 mod providers;
 mod sled;
+mod cache;
 
 pub use self::{
     sled::SledStorage,
+    cache::CacheSizes,
     providers::*,
 };
 
 use std::{collections::HashSet, sync::Arc};
 use async_trait::async_trait;
 use xelis_common::{
+    api::daemon::CacheStats,
     block::{Block, BlockHeader},
     crypto::Hash,
     network::Network,
     transaction::Transaction,
 };
-use crate::core::error::BlockchainError;
+use crate::core::{
+    error::BlockchainError,
+    snapshot::SnapshotWriter
+};
 
 // Represents the tips of the chain or of a block
 pub type Tips = HashSet<Hash>;
 
 #[async_trait]
-pub trait Storage: BlockExecutionOrderProvider + DagOrderProvider + PrunedTopoheightProvider + NonceProvider + AccountProvider + ClientProtocolProvider + BlockDagProvider + MerkleHashProvider + Sync + Send + 'static {
+pub trait Storage: BlockExecutionOrderProvider + DagOrderProvider + PrunedTopoheightProvider + NonceProvider + AccountProvider + ClientProtocolProvider + BlockDagProvider + MerkleHashProvider + StatsProvider + MultiSigProvider + LockProvider + ContractProvider + WorkScoreProvider + EventJournalProvider + Sync + Send + 'static {
     // Is the chain running on mainnet
     fn is_mainnet(&self) -> bool;
 
     // Clear caches if exists
     async fn clear_caches(&mut self) -> Result<(), BlockchainError>;
 
+    // Get the hits and misses of each enabled cache
+    async fn get_cache_stats(&self) -> Vec<CacheStats>;
+
     // delete block at topoheight, and all pointers (hash_at_topo, topo_by_hash, reward, supply, diff, cumulative diff...)
     async fn delete_block_at_topoheight(&mut self, topoheight: u64) -> Result<(Hash, Arc<BlockHeader>, Vec<(Hash, Arc<Transaction>)>), BlockchainError>;
 
@@ -54,6 +63,17 @@ pub trait Storage: BlockExecutionOrderProvider + DagOrderProvider + PrunedTopohe
     // delete versioned nonces below topoheight
     async fn delete_versioned_nonces_below_topoheight(&mut self, topoheight: u64) -> Result<(), BlockchainError>;
 
+    // collapse the versioned balances set below topoheight into a single base version per key and asset
+    // the last `keep_versions` versions of each balance are always kept for reorg safety
+    // returns the number of versions deleted
+    async fn prune_versioned_balances_below_topoheight(&mut self, topoheight: u64, keep_versions: usize) -> Result<u64, BlockchainError>;
+
+    // delete work scores computed from a base at or above topoheight
+    async fn delete_work_scores_from_topoheight(&mut self, topoheight: u64) -> Result<(), BlockchainError>;
+
+    // delete work scores computed from a base below topoheight
+    async fn delete_work_scores_below_topoheight(&mut self, topoheight: u64) -> Result<(), BlockchainError>;
+
     // delete all versions of balances under the specified topoheight
     // for those who don't have more recents, set it to the topoheight
     // for those above it, cut the chain by deleting the previous topoheight when it's going under
@@ -107,6 +127,21 @@ pub trait Storage: BlockExecutionOrderProvider + DagOrderProvider + PrunedTopohe
     // Get the size of the chain on disk in bytes
     async fn get_size_on_disk(&self) -> Result<u64, BlockchainError>;
 
+    // Drop the dynamic trees that are not referenced anymore
+    // Returns the number of trees dropped
+    async fn drop_unused_trees(&mut self) -> Result<usize, BlockchainError>;
+
+    // Flush all the pending writes on disk
+    // Returns the number of bytes flushed
+    async fn flush(&mut self) -> Result<usize, BlockchainError>;
+
+    // Write all the stored data in a snapshot
+    async fn export_snapshot(&self, writer: &mut SnapshotWriter) -> Result<(), BlockchainError>;
+
+    // Open another handle on the same data without the caches
+    // Only the versioned data can be read through it while the storage is written, see CommittedView
+    fn create_read_handle(&self) -> Self where Self: Sized;
+
     // Stop the storage and wait for it to finish
     async fn stop(&mut self) -> Result<(), BlockchainError>;
 }
\ No newline at end of file
@@ -18,6 +18,9 @@ pub trait AccountProvider {
 
     // Delete all registrations at a certain topoheight
     async fn delete_registrations_at_topoheight(&mut self, topoheight: u64) -> Result<(), BlockchainError>;
+
+    // Count the accounts registered at a certain topoheight
+    async fn count_registrations_at_topoheight(&self, topoheight: u64) -> Result<u64, BlockchainError>;
 }
 
 fn prefixed_db_key(topoheight: u64, key: &PublicKey) -> [u8; 40] {
@@ -76,4 +79,14 @@ impl AccountProvider for SledStorage {
 
         Ok(())
     }
+
+    async fn count_registrations_at_topoheight(&self, topoheight: u64) -> Result<u64, BlockchainError> {
+        let mut count = 0;
+        for el in self.registrations_prefixed.scan_prefix(topoheight.to_bytes()).keys() {
+            el?;
+            count += 1;
+        }
+
+        Ok(count)
+    }
 }
\ No newline at end of file
@@ -3,7 +3,8 @@ use async_trait::async_trait;
 use indexmap::IndexSet;
 use log::trace;
 use xelis_common::{
-    asset::{AssetData, AssetWithData},
+    asset::{AssetData, AssetState, AssetWithData},
+    config::XELIS_ASSET,
     crypto::{Hash, HASH_SIZE, PublicKey},
     serializer::Serializer,
 };
@@ -41,6 +42,36 @@ pub trait AssetProvider {
 
     // Add an asset to the storage
     async fn add_asset(&mut self, hash: &Hash, data: AssetData) -> Result<(), BlockchainError>;
+
+    // Delete all assets registered at this topoheight
+    async fn delete_assets_at_topoheight(&mut self, topoheight: u64) -> Result<(), BlockchainError>;
+
+    // Get the latest state of an asset set at or below the maximum topoheight
+    async fn get_asset_state_at_maximum_topoheight(&self, asset: &Hash, maximum_topoheight: u64) -> Result<Option<(u64, AssetState)>, BlockchainError>;
+
+    // Set the state of an asset at a specific topoheight
+    async fn set_asset_state(&mut self, asset: &Hash, topoheight: u64, state: &AssetState) -> Result<(), BlockchainError>;
+
+    // Delete all asset states set at this topoheight
+    async fn delete_asset_states_at_topoheight(&mut self, topoheight: u64) -> Result<(), BlockchainError>;
+}
+
+impl SledStorage {
+    // Key is [asset hash][topoheight] so states of an asset are ordered by topoheight
+    fn get_asset_state_key(asset: &Hash, topoheight: u64) -> [u8; 40] {
+        let mut key = [0; 40];
+        key[0..HASH_SIZE].copy_from_slice(asset.as_bytes());
+        key[HASH_SIZE..].copy_from_slice(&topoheight.to_be_bytes());
+        key
+    }
+
+    // Key is [topoheight][asset hash] so assets registered at a topoheight are found by prefix
+    pub(super) fn get_asset_topoheight_key(asset: &Hash, topoheight: u64) -> [u8; 40] {
+        let mut key = [0; 40];
+        key[0..8].copy_from_slice(&topoheight.to_be_bytes());
+        key[8..].copy_from_slice(asset.as_bytes());
+        key
+    }
 }
 
 #[async_trait]
@@ -117,6 +148,7 @@ impl AssetProvider for SledStorage {
     async fn add_asset(&mut self, asset: &Hash, data: AssetData) -> Result<(), BlockchainError> {
         trace!("add asset {} at topoheight {}", asset, data.get_topoheight());
         self.assets.insert(asset.as_bytes(), data.to_bytes())?;
+        self.assets_topoheight.insert(Self::get_asset_topoheight_key(asset, data.get_topoheight()), &[])?;
 
         // Update counter
         self.store_assets_count(self.count_assets().await? + 1)?;
@@ -127,4 +159,60 @@ impl AssetProvider for SledStorage {
         }
         Ok(())
     }
-}
\ No newline at end of file
+
+    async fn delete_assets_at_topoheight(&mut self, topoheight: u64) -> Result<(), BlockchainError> {
+        trace!("delete assets at topoheight {}", topoheight);
+        for el in self.assets_topoheight.scan_prefix(topoheight.to_be_bytes()).keys() {
+            let key = el?;
+            let asset = Hash::from_bytes(&key[8..])?;
+            // native asset is registered by the genesis block only
+            if asset == XELIS_ASSET {
+                continue;
+            }
+
+            trace!("deleting asset {} registered at topoheight {}", asset, topoheight);
+            self.assets.remove(asset.as_bytes())?;
+            self.assets_topoheight.remove(&key)?;
+            self.db.drop_tree(asset.as_bytes())?;
+            self.store_assets_count(self.count_assets().await? - 1)?;
+
+            if let Some(cache) = &self.assets_cache {
+                let mut cache = cache.lock().await;
+                cache.pop(&asset);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn get_asset_state_at_maximum_topoheight(&self, asset: &Hash, maximum_topoheight: u64) -> Result<Option<(u64, AssetState)>, BlockchainError> {
+        trace!("get asset state {} at maximum topoheight {}", asset, maximum_topoheight);
+        let start = Self::get_asset_state_key(asset, 0);
+        let end = Self::get_asset_state_key(asset, maximum_topoheight);
+        match self.asset_states.range(start..=end).next_back() {
+            Some(el) => {
+                let (key, value) = el?;
+                let topoheight = u64::from_be_bytes(key[HASH_SIZE..].try_into()?);
+                Ok(Some((topoheight, AssetState::from_bytes(&value)?)))
+            },
+            None => Ok(None)
+        }
+    }
+
+    async fn set_asset_state(&mut self, asset: &Hash, topoheight: u64, state: &AssetState) -> Result<(), BlockchainError> {
+        trace!("set asset state {} at topoheight {}", asset, topoheight);
+        self.asset_states.insert(Self::get_asset_state_key(asset, topoheight), state.to_bytes())?;
+        Ok(())
+    }
+
+    async fn delete_asset_states_at_topoheight(&mut self, topoheight: u64) -> Result<(), BlockchainError> {
+        trace!("delete asset states at topoheight {}", topoheight);
+        for el in self.assets.iter().keys() {
+            let key = el?;
+            let asset = Hash::from_bytes(&key)?;
+            self.asset_states.remove(Self::get_asset_state_key(&asset, topoheight))?;
+        }
+
+        Ok(())
+    }
+}
@@ -134,7 +134,7 @@ impl BalanceProvider for SledStorage {
         }
 
         let key = self.get_versioned_balance_key(key, asset, topoheight);
-        self.contains_data::<_, ()>(&self.versioned_balances, &None, &key).await
+        self.contains_data(&self.versioned_balances, &self.versioned_balances_cache, &key).await
     }
 
     // get the balance at a specific topoheight
@@ -148,7 +148,7 @@ impl BalanceProvider for SledStorage {
         }
 
         let disk_key = self.get_versioned_balance_key(key, asset, topoheight);
-        self.get_cacheable_data(&self.versioned_balances, &None, &disk_key, DiskContext::BalanceAtTopoHeight).await
+        self.get_cacheable_data(&self.versioned_balances, &self.versioned_balances_cache, &disk_key, DiskContext::BalanceAtTopoHeight).await
             .map_err(|_| BlockchainError::NoBalanceChanges(key.as_address(self.is_mainnet()), topoheight, asset.clone()))
     }
 
@@ -213,7 +213,7 @@ impl BalanceProvider for SledStorage {
     async fn delete_balance_at_topoheight(&mut self, key: &PublicKey, asset: &Hash, topoheight: u64) -> Result<VersionedBalance, BlockchainError> {
         trace!("delete balance {} for {} at topoheight {}", asset, key.as_address(self.is_mainnet()), topoheight);
         let disk_key = self.get_versioned_balance_key(key, asset, topoheight);
-        self.delete_cacheable_data(&self.versioned_balances, &None, &disk_key).await.map_err(|_| BlockchainError::NoBalanceChanges(key.as_address(self.is_mainnet()), topoheight, asset.clone()))
+        self.delete_cacheable_data(&self.versioned_balances, &self.versioned_balances_cache, &disk_key).await.map_err(|_| BlockchainError::NoBalanceChanges(key.as_address(self.is_mainnet()), topoheight, asset.clone()))
     }
 
     // returns a new versioned balance with already-set previous topoheight
@@ -299,6 +299,11 @@ impl BalanceProvider for SledStorage {
         trace!("set balance {} at topoheight {} for {}", asset, topoheight, key.as_address(self.is_mainnet()));
         let key = self.get_versioned_balance_key(key, asset, topoheight);
         self.versioned_balances.insert(key, balance.to_bytes())?;
+
+        if let Some(cache) = &self.versioned_balances_cache {
+            let mut cache = cache.lock().await;
+            cache.put(key, balance.clone());
+        }
         Ok(())
     }
 }
\ No newline at end of file
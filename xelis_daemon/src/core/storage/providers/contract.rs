@@ -0,0 +1,119 @@
+use async_trait::async_trait;
+use log::trace;
+use xelis_common::{
+    contract::Program,
+    crypto::{Hash, HASH_SIZE},
+    serializer::Serializer,
+};
+use crate::core::{
+    error::BlockchainError,
+    storage::SledStorage,
+};
+
+#[async_trait]
+pub trait ContractProvider {
+    // Get the program of a contract deployed at or below the maximum topoheight
+    async fn get_contract_at_maximum_topoheight(&self, contract: &Hash, maximum_topoheight: u64) -> Result<Option<(u64, Program)>, BlockchainError>;
+
+    // Set the program of a contract deployed at a specific topoheight
+    async fn set_contract(&mut self, contract: &Hash, topoheight: u64, program: &Program) -> Result<(), BlockchainError>;
+
+    // Get the latest version of a contract storage value set at or below the maximum topoheight
+    // A deleted value is returned as None
+    async fn get_contract_data_at_maximum_topoheight(&self, contract: &Hash, key: u64, maximum_topoheight: u64) -> Result<Option<(u64, Option<u64>)>, BlockchainError>;
+
+    // Set a contract storage value at a specific topoheight, None to delete it
+    async fn set_contract_data(&mut self, contract: &Hash, key: u64, topoheight: u64, value: Option<u64>) -> Result<(), BlockchainError>;
+
+    // Delete all contracts deployed at this topoheight
+    async fn delete_contracts_at_topoheight(&mut self, topoheight: u64) -> Result<(), BlockchainError>;
+
+    // Delete all contracts storage changes made at this topoheight
+    async fn delete_contract_data_at_topoheight(&mut self, topoheight: u64) -> Result<(), BlockchainError>;
+}
+
+impl SledStorage {
+    // Key is [contract][topoheight]
+    fn get_contract_key(contract: &Hash, topoheight: u64) -> [u8; 40] {
+        let mut buf = [0; 40];
+        buf[0..HASH_SIZE].copy_from_slice(contract.as_bytes());
+        buf[HASH_SIZE..].copy_from_slice(&topoheight.to_be_bytes());
+        buf
+    }
+
+    // Key is [contract][key][topoheight] so versions of a value are ordered by topoheight
+    fn get_contract_data_key(contract: &Hash, key: u64, topoheight: u64) -> [u8; 48] {
+        let mut buf = [0; 48];
+        buf[0..HASH_SIZE].copy_from_slice(contract.as_bytes());
+        buf[HASH_SIZE..HASH_SIZE + 8].copy_from_slice(&key.to_be_bytes());
+        buf[HASH_SIZE + 8..].copy_from_slice(&topoheight.to_be_bytes());
+        buf
+    }
+}
+
+#[async_trait]
+impl ContractProvider for SledStorage {
+    async fn get_contract_at_maximum_topoheight(&self, contract: &Hash, maximum_topoheight: u64) -> Result<Option<(u64, Program)>, BlockchainError> {
+        trace!("get contract {} at maximum topoheight {}", contract, maximum_topoheight);
+        let start = Self::get_contract_key(contract, 0);
+        let end = Self::get_contract_key(contract, maximum_topoheight);
+        match self.contracts.range(start..=end).next_back() {
+            Some(el) => {
+                let (k, value) = el?;
+                let topoheight = u64::from_be_bytes(k[HASH_SIZE..].try_into()?);
+                Ok(Some((topoheight, Program::from_bytes(&value)?)))
+            },
+            None => Ok(None)
+        }
+    }
+
+    async fn set_contract(&mut self, contract: &Hash, topoheight: u64, program: &Program) -> Result<(), BlockchainError> {
+        trace!("set contract {} at topoheight {}", contract, topoheight);
+        self.contracts.insert(Self::get_contract_key(contract, topoheight), program.to_bytes())?;
+        Ok(())
+    }
+
+    async fn get_contract_data_at_maximum_topoheight(&self, contract: &Hash, key: u64, maximum_topoheight: u64) -> Result<Option<(u64, Option<u64>)>, BlockchainError> {
+        trace!("get contract {} data {} at maximum topoheight {}", contract, key, maximum_topoheight);
+        let start = Self::get_contract_data_key(contract, key, 0);
+        let end = Self::get_contract_data_key(contract, key, maximum_topoheight);
+        match self.contract_data.range(start..=end).next_back() {
+            Some(el) => {
+                let (k, value) = el?;
+                let topoheight = u64::from_be_bytes(k[HASH_SIZE + 8..].try_into()?);
+                Ok(Some((topoheight, Option::from_bytes(&value)?)))
+            },
+            None => Ok(None)
+        }
+    }
+
+    async fn set_contract_data(&mut self, contract: &Hash, key: u64, topoheight: u64, value: Option<u64>) -> Result<(), BlockchainError> {
+        trace!("set contract {} data {} at topoheight {}", contract, key, topoheight);
+        self.contract_data.insert(Self::get_contract_data_key(contract, key, topoheight), value.to_bytes())?;
+        Ok(())
+    }
+
+    async fn delete_contracts_at_topoheight(&mut self, topoheight: u64) -> Result<(), BlockchainError> {
+        trace!("delete contracts at topoheight {}", topoheight);
+        for el in self.contracts.iter().keys() {
+            let key = el?;
+            if u64::from_be_bytes(key[HASH_SIZE..].try_into()?) == topoheight {
+                self.contracts.remove(&key)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn delete_contract_data_at_topoheight(&mut self, topoheight: u64) -> Result<(), BlockchainError> {
+        trace!("delete contract data at topoheight {}", topoheight);
+        for el in self.contract_data.iter().keys() {
+            let key = el?;
+            if u64::from_be_bytes(key[HASH_SIZE + 8..].try_into()?) == topoheight {
+                self.contract_data.remove(&key)?;
+            }
+        }
+
+        Ok(())
+    }
+}
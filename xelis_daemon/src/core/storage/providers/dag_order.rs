@@ -69,8 +69,10 @@ impl DagOrderProvider for SledStorage {
         let hash = if let Some(cache) = &self.hash_at_topo_cache {
             let mut hash_at_topo = cache.lock().await;
             if let Some(value) = hash_at_topo.get(&topoheight) {
+                cache.record(true);
                 return Ok(value.clone())
             }
+            cache.record(false);
             let hash: Hash = self.load_from_disk(&self.hash_at_topo, &topoheight.to_be_bytes(), DiskContext::GetBlockHashAtTopoHeight(topoheight))?;
             hash_at_topo.put(topoheight, hash.clone());
             hash
@@ -78,15 +78,17 @@ impl DifficultyProvider for SledStorage {
     async fn get_past_blocks_for_block_hash(&self, hash: &Hash) -> Result<Immutable<IndexSet<Hash>>, BlockchainError> {
         trace!("get past blocks of {}", hash);
         let tips = if let Some(cache) = &self.past_blocks_cache {
-            let mut cache = cache.lock().await;
-            if let Some(tips) = cache.get(hash) {
+            let mut lru = cache.lock().await;
+            if let Some(tips) = lru.get(hash) {
+                cache.record(true);
                 return Ok(Immutable::Arc(tips.clone()))
             }
+            cache.record(false);
     
             let block = self.get_block_header_by_hash(hash).await?;
         
             let tips = Arc::new(block.get_tips().clone());
-            cache.put(hash.clone(), tips.clone());
+            lru.put(hash.clone(), tips.clone());
             Immutable::Arc(tips)
         } else {
             let block = self.get_block_header_by_hash(hash).await?;
@@ -0,0 +1,74 @@
+use async_trait::async_trait;
+use log::trace;
+use xelis_common::api::daemon::JournalEvent;
+use crate::core::{
+    error::BlockchainError,
+    storage::SledStorage,
+};
+
+// Bounded journal of the events notified to the websocket clients
+// Events are keyed by their sequence number so a client can catch up after a disconnection
+// Writing it from a shared reference is allowed as only the RPC server appends to it
+#[async_trait]
+pub trait EventJournalProvider {
+    // Get the sequence number of the last event saved, 0 if the journal is empty
+    async fn get_last_event_sequence(&self) -> Result<u64, BlockchainError>;
+
+    // Get the sequence number of the oldest event saved
+    async fn get_oldest_event_sequence(&self) -> Result<Option<u64>, BlockchainError>;
+
+    // Get up to maximum events saved with a sequence number above the one requested
+    async fn get_events_since(&self, sequence: u64, maximum: usize) -> Result<Vec<JournalEvent>, BlockchainError>;
+
+    // Save an event in the journal
+    async fn append_event(&self, event: &JournalEvent) -> Result<(), BlockchainError>;
+
+    // Delete all the events saved below the sequence number
+    async fn delete_events_below_sequence(&self, sequence: u64) -> Result<(), BlockchainError>;
+}
+
+#[async_trait]
+impl EventJournalProvider for SledStorage {
+    async fn get_last_event_sequence(&self) -> Result<u64, BlockchainError> {
+        trace!("get last event sequence");
+        match self.events.last()? {
+            Some((key, _)) => Ok(u64::from_be_bytes(key.as_ref().try_into()?)),
+            None => Ok(0)
+        }
+    }
+
+    async fn get_oldest_event_sequence(&self) -> Result<Option<u64>, BlockchainError> {
+        trace!("get oldest event sequence");
+        match self.events.first()? {
+            Some((key, _)) => Ok(Some(u64::from_be_bytes(key.as_ref().try_into()?))),
+            None => Ok(None)
+        }
+    }
+
+    async fn get_events_since(&self, sequence: u64, maximum: usize) -> Result<Vec<JournalEvent>, BlockchainError> {
+        trace!("get events since sequence {}", sequence);
+        let mut events = Vec::new();
+        for el in self.events.range((sequence.saturating_add(1)).to_be_bytes()..).values().take(maximum) {
+            let value = el?;
+            events.push(serde_json::from_slice(&value).map_err(anyhow::Error::from)?);
+        }
+
+        Ok(events)
+    }
+
+    async fn append_event(&self, event: &JournalEvent) -> Result<(), BlockchainError> {
+        trace!("append event {:?} with sequence {}", event.event, event.sequence);
+        let value = serde_json::to_vec(event).map_err(anyhow::Error::from)?;
+        self.events.insert(event.sequence.to_be_bytes(), value)?;
+        Ok(())
+    }
+
+    async fn delete_events_below_sequence(&self, sequence: u64) -> Result<(), BlockchainError> {
+        trace!("delete events below sequence {}", sequence);
+        for el in self.events.range(..sequence.to_be_bytes()).keys() {
+            let key = el?;
+            self.events.remove(&key)?;
+        }
+        Ok(())
+    }
+}
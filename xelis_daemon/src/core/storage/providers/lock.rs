@@ -0,0 +1,69 @@
+use async_trait::async_trait;
+use log::trace;
+use xelis_common::{
+    crypto::{Hash, HASH_SIZE},
+    serializer::Serializer,
+    transaction::lock::LockedTransfer,
+};
+use crate::core::{
+    error::BlockchainError,
+    storage::SledStorage,
+};
+
+#[async_trait]
+pub trait LockProvider {
+    // Get the latest version of a locked transfer set at or below the maximum topoheight
+    // An unlocked transfer is returned as None
+    async fn get_locked_transfer_at_maximum_topoheight(&self, id: &Hash, maximum_topoheight: u64) -> Result<Option<(u64, Option<LockedTransfer>)>, BlockchainError>;
+
+    // Set the locked transfer at a specific topoheight, None once it is unlocked
+    async fn set_locked_transfer(&mut self, id: &Hash, topoheight: u64, locked: Option<&LockedTransfer>) -> Result<(), BlockchainError>;
+
+    // Delete all locked transfers changes made at this topoheight
+    async fn delete_locked_transfers_at_topoheight(&mut self, topoheight: u64) -> Result<(), BlockchainError>;
+}
+
+impl SledStorage {
+    // Key is [lock id][topoheight] so versions of a lock are ordered by topoheight
+    fn get_lock_key(id: &Hash, topoheight: u64) -> [u8; 40] {
+        let mut buf = [0; 40];
+        buf[0..HASH_SIZE].copy_from_slice(id.as_bytes());
+        buf[HASH_SIZE..].copy_from_slice(&topoheight.to_be_bytes());
+        buf
+    }
+}
+
+#[async_trait]
+impl LockProvider for SledStorage {
+    async fn get_locked_transfer_at_maximum_topoheight(&self, id: &Hash, maximum_topoheight: u64) -> Result<Option<(u64, Option<LockedTransfer>)>, BlockchainError> {
+        trace!("get locked transfer {} at maximum topoheight {}", id, maximum_topoheight);
+        let start = Self::get_lock_key(id, 0);
+        let end = Self::get_lock_key(id, maximum_topoheight);
+        match self.locks.range(start..=end).next_back() {
+            Some(el) => {
+                let (k, value) = el?;
+                let topoheight = u64::from_be_bytes(k[HASH_SIZE..].try_into()?);
+                Ok(Some((topoheight, Option::from_bytes(&value)?)))
+            },
+            None => Ok(None)
+        }
+    }
+
+    async fn set_locked_transfer(&mut self, id: &Hash, topoheight: u64, locked: Option<&LockedTransfer>) -> Result<(), BlockchainError> {
+        trace!("set locked transfer {} at topoheight {}", id, topoheight);
+        self.locks.insert(Self::get_lock_key(id, topoheight), locked.cloned().to_bytes())?;
+        Ok(())
+    }
+
+    async fn delete_locked_transfers_at_topoheight(&mut self, topoheight: u64) -> Result<(), BlockchainError> {
+        trace!("delete locked transfers at topoheight {}", topoheight);
+        for el in self.locks.iter().keys() {
+            let key = el?;
+            if u64::from_be_bytes(key[HASH_SIZE..].try_into()?) == topoheight {
+                self.locks.remove(&key)?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -12,6 +12,12 @@ mod blockdag;
 mod merkle;
 mod account;
 mod block_execution_order;
+mod stats;
+mod multisig;
+mod lock;
+mod contract;
+mod work_score;
+mod event_journal;
 
 pub use asset::AssetProvider;
 pub use blocks_at_height::BlocksAtHeightProvider;
@@ -26,4 +32,10 @@ pub use block::BlockProvider;
 pub use blockdag::BlockDagProvider;
 pub use merkle::MerkleHashProvider;
 pub use account::AccountProvider;
-pub use block_execution_order::BlockExecutionOrderProvider;
\ No newline at end of file
+pub use block_execution_order::BlockExecutionOrderProvider;
+pub use stats::{StatsProvider, ChainStats, get_day_for_timestamp};
+pub use multisig::MultiSigProvider;
+pub use lock::LockProvider;
+pub use contract::ContractProvider;
+pub use work_score::WorkScoreProvider;
+pub use event_journal::EventJournalProvider;
\ No newline at end of file
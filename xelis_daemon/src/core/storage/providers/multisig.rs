@@ -0,0 +1,78 @@
+use async_trait::async_trait;
+use log::trace;
+use xelis_common::{
+    crypto::{PublicKey, HASH_SIZE},
+    serializer::Serializer,
+    transaction::MultiSigPayload,
+};
+use crate::core::{
+    error::BlockchainError,
+    storage::SledStorage,
+};
+
+#[async_trait]
+pub trait MultiSigProvider {
+    // Get the latest multisig configuration of an account set at or below the maximum topoheight
+    // A deleted multisig is returned with a threshold of 0 and no participants
+    async fn get_multisig_at_maximum_topoheight_for(&self, key: &PublicKey, maximum_topoheight: u64) -> Result<Option<(u64, MultiSigPayload)>, BlockchainError>;
+
+    // Check if the account has an active multisig at or below the maximum topoheight
+    async fn has_multisig_at_maximum_topoheight_for(&self, key: &PublicKey, maximum_topoheight: u64) -> Result<bool, BlockchainError>;
+
+    // Set the multisig configuration of an account at a specific topoheight
+    async fn set_multisig_for(&mut self, key: &PublicKey, topoheight: u64, multisig: &MultiSigPayload) -> Result<(), BlockchainError>;
+
+    // Delete all multisig configurations set at this topoheight
+    async fn delete_multisigs_at_topoheight(&mut self, topoheight: u64) -> Result<(), BlockchainError>;
+}
+
+impl SledStorage {
+    // Key is [public key][topoheight] so configurations of an account are ordered by topoheight
+    fn get_multisig_key(key: &PublicKey, topoheight: u64) -> [u8; 40] {
+        let mut buf = [0; 40];
+        buf[0..HASH_SIZE].copy_from_slice(key.as_bytes());
+        buf[HASH_SIZE..].copy_from_slice(&topoheight.to_be_bytes());
+        buf
+    }
+}
+
+#[async_trait]
+impl MultiSigProvider for SledStorage {
+    async fn get_multisig_at_maximum_topoheight_for(&self, key: &PublicKey, maximum_topoheight: u64) -> Result<Option<(u64, MultiSigPayload)>, BlockchainError> {
+        trace!("get multisig for {} at maximum topoheight {}", key.as_address(self.is_mainnet()), maximum_topoheight);
+        let start = Self::get_multisig_key(key, 0);
+        let end = Self::get_multisig_key(key, maximum_topoheight);
+        match self.multisig.range(start..=end).next_back() {
+            Some(el) => {
+                let (k, value) = el?;
+                let topoheight = u64::from_be_bytes(k[HASH_SIZE..].try_into()?);
+                Ok(Some((topoheight, MultiSigPayload::from_bytes(&value)?)))
+            },
+            None => Ok(None)
+        }
+    }
+
+    async fn has_multisig_at_maximum_topoheight_for(&self, key: &PublicKey, maximum_topoheight: u64) -> Result<bool, BlockchainError> {
+        trace!("has multisig for {} at maximum topoheight {}", key.as_address(self.is_mainnet()), maximum_topoheight);
+        Ok(self.get_multisig_at_maximum_topoheight_for(key, maximum_topoheight).await?
+            .map_or(false, |(_, multisig)| !multisig.is_delete()))
+    }
+
+    async fn set_multisig_for(&mut self, key: &PublicKey, topoheight: u64, multisig: &MultiSigPayload) -> Result<(), BlockchainError> {
+        trace!("set multisig for {} at topoheight {}", key.as_address(self.is_mainnet()), topoheight);
+        self.multisig.insert(Self::get_multisig_key(key, topoheight), multisig.to_bytes())?;
+        Ok(())
+    }
+
+    async fn delete_multisigs_at_topoheight(&mut self, topoheight: u64) -> Result<(), BlockchainError> {
+        trace!("delete multisigs at topoheight {}", topoheight);
+        for el in self.multisig.iter().keys() {
+            let key = el?;
+            if u64::from_be_bytes(key[HASH_SIZE..].try_into()?) == topoheight {
+                self.multisig.remove(&key)?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,144 @@
+use async_trait::async_trait;
+use log::trace;
+use xelis_common::{
+    serializer::Serializer,
+    time::TimestampMillis
+};
+use crate::{
+    config::STATS_DAY_DURATION_MILLIS,
+    core::{
+        error::BlockchainError,
+        storage::SledStorage
+    }
+};
+
+// Statistics of blocks ordered in the chain
+// Used for a single block and for the aggregate of a whole day
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serializer)]
+pub struct ChainStats {
+    pub blocks: u64,
+    // transactions executed
+    pub txs: u64,
+    // fees paid by the executed transactions
+    pub fees: u64,
+    // accounts registered
+    pub new_accounts: u64,
+    // coins emitted by block rewards
+    pub emission: u64
+}
+
+impl ChainStats {
+    pub fn add(&mut self, other: &Self) {
+        self.blocks += other.blocks;
+        self.txs += other.txs;
+        self.fees += other.fees;
+        self.new_accounts += other.new_accounts;
+        self.emission += other.emission;
+    }
+
+    pub fn sub(&mut self, other: &Self) {
+        self.blocks = self.blocks.saturating_sub(other.blocks);
+        self.txs = self.txs.saturating_sub(other.txs);
+        self.fees = self.fees.saturating_sub(other.fees);
+        self.new_accounts = self.new_accounts.saturating_sub(other.new_accounts);
+        self.emission = self.emission.saturating_sub(other.emission);
+    }
+}
+
+// Day index of a block timestamp
+pub fn get_day_for_timestamp(timestamp: TimestampMillis) -> u64 {
+    timestamp / STATS_DAY_DURATION_MILLIS
+}
+
+// Chain statistics aggregated per day
+// Each block ordered adds its stats to the day of its timestamp
+// and they are removed when the block is re-ordered by a DAG reorg or popped
+// so queries over a range of days don't have to iterate over all blocks
+// Days aggregates are kept when the chain is pruned
+#[async_trait]
+pub trait StatsProvider {
+    // Add the stats of the block ordered at this topoheight to its day
+    // If stats were already registered at this topoheight, they are rewinded first
+    async fn add_block_stats(&mut self, topoheight: u64, timestamp: TimestampMillis, stats: &ChainStats) -> Result<(), BlockchainError>;
+
+    // Remove the stats of the block ordered at this topoheight from its day
+    async fn rewind_block_stats(&mut self, topoheight: u64) -> Result<(), BlockchainError>;
+
+    // Get the stats of all days in the inclusive range in ascending order
+    // Days without any block ordered are not returned
+    async fn get_daily_stats(&self, minimum_day: u64, maximum_day: u64) -> Result<Vec<(u64, ChainStats)>, BlockchainError>;
+}
+
+#[async_trait]
+impl StatsProvider for SledStorage {
+    async fn add_block_stats(&mut self, topoheight: u64, timestamp: TimestampMillis, stats: &ChainStats) -> Result<(), BlockchainError> {
+        trace!("add block stats at topoheight {}", topoheight);
+        self.rewind_block_stats(topoheight).await?;
+
+        let day = get_day_for_timestamp(timestamp);
+        let mut daily: ChainStats = self.load_optional_from_disk(&self.daily_stats, &day.to_be_bytes())?.unwrap_or_default();
+        daily.add(stats);
+
+        self.daily_stats.insert(day.to_be_bytes(), daily.to_bytes())?;
+        self.block_stats.insert(topoheight.to_be_bytes(), (day, stats.clone()).to_bytes())?;
+        Ok(())
+    }
+
+    async fn rewind_block_stats(&mut self, topoheight: u64) -> Result<(), BlockchainError> {
+        trace!("rewind block stats at topoheight {}", topoheight);
+        let Some((day, stats)) = self.load_optional_from_disk::<(u64, ChainStats)>(&self.block_stats, &topoheight.to_be_bytes())? else {
+            return Ok(())
+        };
+
+        if let Some(mut daily) = self.load_optional_from_disk::<ChainStats>(&self.daily_stats, &day.to_be_bytes())? {
+            daily.sub(&stats);
+            if daily.blocks == 0 {
+                self.daily_stats.remove(day.to_be_bytes())?;
+            } else {
+                self.daily_stats.insert(day.to_be_bytes(), daily.to_bytes())?;
+            }
+        }
+
+        self.block_stats.remove(topoheight.to_be_bytes())?;
+        Ok(())
+    }
+
+    async fn get_daily_stats(&self, minimum_day: u64, maximum_day: u64) -> Result<Vec<(u64, ChainStats)>, BlockchainError> {
+        trace!("get daily stats from day {} to {}", minimum_day, maximum_day);
+        let mut days = Vec::new();
+        for el in self.daily_stats.range(minimum_day.to_be_bytes()..=maximum_day.to_be_bytes()) {
+            let (key, value) = el?;
+            let day = u64::from_bytes(&key)?;
+            let stats = ChainStats::from_bytes(&value)?;
+            days.push((day, stats));
+        }
+
+        Ok(days)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_day_for_timestamp() {
+        assert_eq!(get_day_for_timestamp(0), 0);
+        assert_eq!(get_day_for_timestamp(STATS_DAY_DURATION_MILLIS - 1), 0);
+        assert_eq!(get_day_for_timestamp(STATS_DAY_DURATION_MILLIS), 1);
+    }
+
+    #[test]
+    fn test_rewind_stats() {
+        let block = ChainStats { blocks: 1, txs: 3, fees: 150, new_accounts: 2, emission: 1000 };
+        let mut daily = ChainStats::default();
+        daily.add(&block);
+        daily.add(&block);
+        daily.sub(&block);
+        assert_eq!(daily, block);
+
+        let bytes = daily.to_bytes();
+        assert_eq!(bytes.len(), daily.size());
+        assert_eq!(ChainStats::from_bytes(&bytes).unwrap(), block);
+    }
+}
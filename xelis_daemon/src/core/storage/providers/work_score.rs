@@ -0,0 +1,49 @@
+use async_trait::async_trait;
+use log::trace;
+use xelis_common::{
+    crypto::{Hash, HASH_SIZE},
+    difficulty::CumulativeDifficulty,
+    serializer::Serializer,
+};
+use crate::core::{
+    error::BlockchainError,
+    storage::SledStorage,
+};
+
+// Persisted index of the work scores computed by `find_tip_work_score`
+// A work score only depends on the blocks ordered above its base, so it stays valid
+// until a reorg happens at or below the topoheight of the base
+// Writing it from a shared reference is allowed as it can always be recomputed
+#[async_trait]
+pub trait WorkScoreProvider {
+    // Get the work score of a block computed from a base block at its topoheight
+    async fn get_work_score(&self, hash: &Hash, base: &Hash, base_topoheight: u64) -> Result<Option<CumulativeDifficulty>, BlockchainError>;
+
+    // Save the work score of a block computed from a base block at its topoheight
+    async fn set_work_score(&self, hash: &Hash, base: &Hash, base_topoheight: u64, score: &CumulativeDifficulty) -> Result<(), BlockchainError>;
+}
+
+impl SledStorage {
+    // Key is [base topoheight][hash][base] so all scores of a topoheight range can be deleted at once
+    fn get_work_score_key(hash: &Hash, base: &Hash, base_topoheight: u64) -> [u8; 72] {
+        let mut buf = [0; 72];
+        buf[0..8].copy_from_slice(&base_topoheight.to_be_bytes());
+        buf[8..8 + HASH_SIZE].copy_from_slice(hash.as_bytes());
+        buf[8 + HASH_SIZE..].copy_from_slice(base.as_bytes());
+        buf
+    }
+}
+
+#[async_trait]
+impl WorkScoreProvider for SledStorage {
+    async fn get_work_score(&self, hash: &Hash, base: &Hash, base_topoheight: u64) -> Result<Option<CumulativeDifficulty>, BlockchainError> {
+        trace!("get work score of {} from base {} at topoheight {}", hash, base, base_topoheight);
+        self.load_optional_from_disk(&self.work_scores, &Self::get_work_score_key(hash, base, base_topoheight))
+    }
+
+    async fn set_work_score(&self, hash: &Hash, base: &Hash, base_topoheight: u64, score: &CumulativeDifficulty) -> Result<(), BlockchainError> {
+        trace!("set work score of {} from base {} at topoheight {}", hash, base, base_topoheight);
+        self.work_scores.insert(Self::get_work_score_key(hash, base, base_topoheight), score.to_bytes())?;
+        Ok(())
+    }
+}
@@ -3,12 +3,16 @@ use async_trait::async_trait;
 use indexmap::IndexSet;
 use crate::{
     config::PRUNE_SAFETY_LIMIT,
-    core::error::{BlockchainError, DiskContext}
+    core::{
+        error::{BlockchainError, DiskContext},
+        snapshot::SnapshotWriter
+    }
 };
 use xelis_common::{
     account::{VersionedBalance, VersionedNonce},
+    asset::AssetData,
     block::{Block, BlockHeader},
-    crypto::{Hash, PublicKey},
+    crypto::{Hash, PublicKey, HASH_SIZE},
     difficulty::{CumulativeDifficulty, Difficulty},
     immutable::Immutable,
     network::Network,
@@ -18,15 +22,13 @@ use xelis_common::{
 use std::{
     collections::HashSet,
     hash::Hash as StdHash,
-    sync::{Arc, atomic::{AtomicU64, Ordering}},
-    num::NonZeroUsize
+    sync::{Arc, atomic::{AtomicU64, Ordering}}
 };
-use tokio::sync::Mutex;
-use lru::LruCache;
 use sled::Tree;
 use log::{debug, trace, warn, info};
 
 use super::{
+    cache::{CacheSizes, StorageCache},
     BalanceProvider,
     BlocksAtHeightProvider,
     DagOrderProvider,
@@ -36,6 +38,7 @@ use super::{
     ClientProtocolProvider,
     TransactionProvider,
     BlockProvider,
+    StatsProvider,
     Storage,
     Tips
 };
@@ -52,6 +55,10 @@ pub(super) const TXS_COUNT: &[u8; 4] = b"CTXS";
 const ASSETS_COUNT: &[u8; 4] = b"CAST";
 pub(super) const BLOCKS_COUNT: &[u8; 4] = b"CBLK";
 pub(super) const BLOCKS_EXECUTION_ORDER_COUNT: &[u8; 4] = b"EBLK";
+// Set once all the assets are stored with their details and indexed by topoheight
+const ASSETS_FORMAT: &[u8; 4] = b"AFMT";
+// Size of an asset stored by older versions (topoheight + decimals)
+const LEGACY_ASSET_SIZE: usize = 9;
 
 pub struct SledStorage {
     // Network used by the storage
@@ -79,6 +86,30 @@ pub struct SledStorage {
     pub(super) difficulty_covariance: Tree,
     // keep tracks of all available assets on network
     pub(super) assets: Tree,
+    // assets indexed by their registration topoheight
+    // key is [topoheight][asset hash]
+    pub(super) assets_topoheight: Tree,
+    // versioned state (owner, supply) of created assets
+    // key is [asset hash][topoheight]
+    pub(super) asset_states: Tree,
+    // versioned multisig configurations of accounts
+    // key is [public key][topoheight]
+    pub(super) multisig: Tree,
+    // versioned locked transfers, None once unlocked
+    // key is [lock id][topoheight]
+    pub(super) locks: Tree,
+    // programs of deployed contracts
+    // key is [contract][topoheight]
+    pub(super) contracts: Tree,
+    // versioned storage values of contracts, None once deleted
+    // key is [contract][key][topoheight]
+    pub(super) contract_data: Tree,
+    // work scores of blocks computed from a base
+    // key is [base topoheight][hash][base]
+    pub(super) work_scores: Tree,
+    // events notified to the websocket clients
+    // key is the event sequence number
+    pub(super) events: Tree,
     // account nonces to prevent TX replay attack
     pub(super) nonces: Tree,
     // block reward for each block topoheight
@@ -101,28 +132,32 @@ pub struct SledStorage {
     pub(super) registrations: Tree,
     // Account registrations prefixed by their topoheight for easier deletion
     pub(super) registrations_prefixed: Tree,
+    // Stats of each block ordered with its day
+    pub(super) block_stats: Tree,
+    // Stats aggregated per day
+    pub(super) daily_stats: Tree,
     // opened DB used for assets to create dynamic assets
-    db: sled::Db,
+    pub(super) db: sled::Db,
 
     // all available caches
     // Transaction cache
-    pub(super) transactions_cache: Option<Mutex<LruCache<Hash, Arc<Transaction>>>>,
+    pub(super) transactions_cache: Option<StorageCache<Hash, Arc<Transaction>>>,
     // Block header cache
-    pub(super) blocks_cache: Option<Mutex<LruCache<Hash, Arc<BlockHeader>>>>,
+    pub(super) blocks_cache: Option<StorageCache<Hash, Arc<BlockHeader>>>,
     // Blocks Tips cache
-    pub(super) past_blocks_cache: Option<Mutex<LruCache<Hash, Arc<IndexSet<Hash>>>>>,
+    pub(super) past_blocks_cache: Option<StorageCache<Hash, Arc<IndexSet<Hash>>>>,
     // Topoheight by hash cache
-    pub(super) topo_by_hash_cache: Option<Mutex<LruCache<Hash, u64>>>,
+    pub(super) topo_by_hash_cache: Option<StorageCache<Hash, u64>>,
     // Hash by topoheight cache
-    pub(super) hash_at_topo_cache: Option<Mutex<LruCache<u64, Hash>>>,
+    pub(super) hash_at_topo_cache: Option<StorageCache<u64, Hash>>,
     // Cumulative difficulty cache
-    pub(super) cumulative_difficulty_cache: Option<Mutex<LruCache<Hash, CumulativeDifficulty>>>,
+    pub(super) cumulative_difficulty_cache: Option<StorageCache<Hash, CumulativeDifficulty>>,
     // Assets cache
-    pub(super) assets_cache: Option<Mutex<LruCache<Hash, ()>>>,
-    // Balances Trees cache: keep opened trees in memory to prevent re-open
-    balances_trees_cache: Option<Mutex<LruCache<(Hash, u64), Tree>>>,
+    pub(super) assets_cache: Option<StorageCache<Hash, ()>>,
+    // Versioned balances cache
+    pub(super) versioned_balances_cache: Option<StorageCache<[u8; 72], VersionedBalance>>,
     // Nonces Trees cache: keep opened trees in memory to prevent re-open
-    nonces_trees_cache: Option<Mutex<LruCache<u64, Tree>>>,
+    nonces_trees_cache: Option<StorageCache<u64, Tree>>,
     // Tips cache: current chain Tips
     tips_cache: Tips,
     // Pruned topoheight cache
@@ -141,18 +176,8 @@ pub struct SledStorage {
     pub(super) blocks_execution_count: AtomicU64
 }
 
-macro_rules! init_cache {
-    ($cache_size: expr) => {{
-        if let Some(size) = &$cache_size {
-            Some(Mutex::new(LruCache::new(NonZeroUsize::new(*size).unwrap())))
-        } else {
-            None
-        }
-    }};
-}
-
 impl SledStorage {
-    pub fn new(dir_path: String, cache_size: Option<usize>, network: Network) -> Result<Self, BlockchainError> {
+    pub fn new(dir_path: String, cache_sizes: CacheSizes, network: Network) -> Result<Self, BlockchainError> {
         let sled = sled::open(format!("{}{}", dir_path, network.to_string().to_lowercase()))?;
         let mut storage = Self {
             network,
@@ -167,6 +192,14 @@ impl SledStorage {
             cumulative_difficulty: sled.open_tree("cumulative_difficulty")?,
             difficulty_covariance: sled.open_tree("difficulty_covariance")?,
             assets: sled.open_tree("assets")?,
+            assets_topoheight: sled.open_tree("assets_topoheight")?,
+            asset_states: sled.open_tree("asset_states")?,
+            multisig: sled.open_tree("multisig")?,
+            locks: sled.open_tree("locks")?,
+            contracts: sled.open_tree("contracts")?,
+            contract_data: sled.open_tree("contract_data")?,
+            work_scores: sled.open_tree("work_scores")?,
+            events: sled.open_tree("events")?,
             nonces: sled.open_tree("nonces")?,
             rewards: sled.open_tree("rewards")?,
             supply: sled.open_tree("supply")?,
@@ -178,16 +211,18 @@ impl SledStorage {
             merkle_hashes: sled.open_tree("merkle_hashes")?,
             registrations: sled.open_tree("registrations")?,
             registrations_prefixed: sled.open_tree("registrations_prefixed")?,
+            block_stats: sled.open_tree("block_stats")?,
+            daily_stats: sled.open_tree("daily_stats")?,
             db: sled,
-            transactions_cache: init_cache!(cache_size),
-            blocks_cache: init_cache!(cache_size),
-            past_blocks_cache: init_cache!(cache_size),
-            topo_by_hash_cache: init_cache!(cache_size),
-            hash_at_topo_cache: init_cache!(cache_size),
-            cumulative_difficulty_cache: init_cache!(cache_size),
-            assets_cache: init_cache!(cache_size),
-            balances_trees_cache: init_cache!(cache_size),
-            nonces_trees_cache: init_cache!(cache_size),
+            transactions_cache: StorageCache::new(cache_sizes.transactions),
+            blocks_cache: StorageCache::new(cache_sizes.blocks),
+            past_blocks_cache: StorageCache::new(cache_sizes.blocks),
+            topo_by_hash_cache: StorageCache::new(cache_sizes.topoheights),
+            hash_at_topo_cache: StorageCache::new(cache_sizes.topoheights),
+            cumulative_difficulty_cache: StorageCache::new(cache_sizes.others),
+            assets_cache: StorageCache::new(cache_sizes.others),
+            versioned_balances_cache: StorageCache::new(cache_sizes.balances),
+            nonces_trees_cache: StorageCache::new(cache_sizes.others),
             tips_cache: HashSet::new(),
             pruned_topoheight: None,
             assets_count: AtomicU64::new(0),
@@ -208,6 +243,11 @@ impl SledStorage {
             storage.set_network(&network)?;
         }
 
+        // Convert the assets saved by older versions
+        if !storage.extra.contains_key(ASSETS_FORMAT)? {
+            storage.migrate_legacy_assets()?;
+        }
+
         // Load tips from disk if available
         if let Ok(tips) = storage.load_from_disk::<Tips>(&storage.extra, TIPS, DiskContext::Tips) {
             debug!("Found tips: {}", tips.len());
@@ -257,6 +297,35 @@ impl SledStorage {
         self.network.is_mainnet()
     }
 
+    // Older versions were saving only the topoheight and decimals of an asset
+    // rewrite them with empty details and index all assets by topoheight
+    fn migrate_legacy_assets(&self) -> Result<(), BlockchainError> {
+        trace!("migrate legacy assets");
+        let mut migrated = 0;
+        for el in self.assets.iter() {
+            let (key, value) = el?;
+            let asset = Hash::from_bytes(&key)?;
+            let data = if value.len() == LEGACY_ASSET_SIZE {
+                let mut reader = Reader::new(&value);
+                let data = AssetData::new(reader.read_u64()?, reader.read_u8()?);
+                self.assets.insert(&key, data.to_bytes())?;
+                migrated += 1;
+                data
+            } else {
+                AssetData::from_bytes(&value)?
+            };
+
+            self.assets_topoheight.insert(Self::get_asset_topoheight_key(&asset, data.get_topoheight()), &[])?;
+        }
+
+        if migrated > 0 {
+            info!("Migrated {} assets", migrated);
+        }
+
+        self.extra.insert(ASSETS_FORMAT, 1u8.to_bytes())?;
+        Ok(())
+    }
+
     pub(super) fn load_optional_from_disk<T: Serializer>(&self, tree: &Tree, key: &[u8]) -> Result<Option<T>, BlockchainError> {
         match tree.get(key)? {
             Some(bytes) => {
@@ -281,15 +350,17 @@ impl SledStorage {
         }
     }
 
-    pub(super) async fn get_cacheable_arc_data<K: Eq + StdHash + Serializer + Clone, V: Serializer>(&self, tree: &Tree, cache: &Option<Mutex<LruCache<K, Arc<V>>>>, key: &K, context: DiskContext) -> Result<Arc<V>, BlockchainError> {
+    pub(super) async fn get_cacheable_arc_data<K: Eq + StdHash + Serializer + Clone, V: Serializer>(&self, tree: &Tree, cache: &Option<StorageCache<K, Arc<V>>>, key: &K, context: DiskContext) -> Result<Arc<V>, BlockchainError> {
         let value = if let Some(cache) = cache {
-            let mut cache = cache.lock().await;
-            if let Some(value) = cache.get(key) {
+            let mut lru = cache.lock().await;
+            if let Some(value) = lru.get(key) {
+                cache.record(true);
                 return Ok(Arc::clone(&value));
             }
+            cache.record(false);
 
             let value = Arc::new(self.load_from_disk(tree, &key.to_bytes(), context)?);
-            cache.put(key.clone(), Arc::clone(&value));
+            lru.put(key.clone(), Arc::clone(&value));
             value
         } else {
             Arc::new(self.load_from_disk(tree, &key.to_bytes(), context)?)
@@ -298,15 +369,17 @@ impl SledStorage {
         Ok(value)
     }
 
-    pub(super) async fn get_cacheable_data<K: Eq + StdHash + Serializer + Clone, V: Serializer + Clone>(&self, tree: &Tree, cache: &Option<Mutex<LruCache<K, V>>>, key: &K, context: DiskContext) -> Result<V, BlockchainError> {
+    pub(super) async fn get_cacheable_data<K: Eq + StdHash + Serializer + Clone, V: Serializer + Clone>(&self, tree: &Tree, cache: &Option<StorageCache<K, V>>, key: &K, context: DiskContext) -> Result<V, BlockchainError> {
         let value = if let Some(cache) = cache {
-            let mut cache = cache.lock().await;
-            if let Some(value) = cache.get(key) {
+            let mut lru = cache.lock().await;
+            if let Some(value) = lru.get(key) {
+                cache.record(true);
                 return Ok(value.clone());
             }
+            cache.record(false);
 
             let value: V = self.load_from_disk(tree, &key.to_bytes(), context)?;
-            cache.put(key.clone(), value.clone());
+            lru.put(key.clone(), value.clone());
             value
         } else {
             self.load_from_disk(tree, &key.to_bytes(), context)?
@@ -315,7 +388,7 @@ impl SledStorage {
         Ok(value)
     }
 
-    pub(super) async fn delete_cacheable_data<K: Eq + StdHash + Serializer + Clone, V: Serializer>(&self, tree: &Tree, cache: &Option<Mutex<LruCache<K, V>>>, key: &K) -> Result<V, BlockchainError> {
+    pub(super) async fn delete_cacheable_data<K: Eq + StdHash + Serializer + Clone, V: Serializer>(&self, tree: &Tree, cache: &Option<StorageCache<K, V>>, key: &K) -> Result<V, BlockchainError> {
         let bytes = match tree.remove(key.to_bytes())? {
             Some(data) => data.to_vec(),
             None => return Err(BlockchainError::NotFoundOnDisk(DiskContext::DeleteData))
@@ -333,7 +406,7 @@ impl SledStorage {
         Ok(value)
     }
 
-    pub(super) async fn delete_data<K: Eq + StdHash + Serializer + Clone, V: Serializer>(&self, tree: &Tree, cache: &Option<Mutex<LruCache<K, Arc<V>>>>, key: &K) -> Result<Arc<V>, BlockchainError> {
+    pub(super) async fn delete_data<K: Eq + StdHash + Serializer + Clone, V: Serializer>(&self, tree: &Tree, cache: &Option<StorageCache<K, Arc<V>>>, key: &K) -> Result<Arc<V>, BlockchainError> {
         let bytes = match tree.remove(key.to_bytes())? {
             Some(data) => data.to_vec(),
             None => return Err(BlockchainError::NotFoundOnDisk(DiskContext::DeleteData))
@@ -351,10 +424,19 @@ impl SledStorage {
         Ok(Arc::new(value))
     }
 
-    pub(super) async fn contains_data<K: Eq + StdHash + Serializer + Clone, V>(&self, tree: &Tree, cache: &Option<Mutex<LruCache<K, V>>>, key: &K) -> Result<bool, BlockchainError> {
+    // Versioned balances are deleted and rewritten by scanning their tree
+    // so the whole cache is cleared instead
+    async fn clear_versioned_balances_cache(&self) {
+        if let Some(cache) = &self.versioned_balances_cache {
+            cache.lock().await.clear();
+        }
+    }
+
+    pub(super) async fn contains_data<K: Eq + StdHash + Serializer + Clone, V>(&self, tree: &Tree, cache: &Option<StorageCache<K, V>>, key: &K) -> Result<bool, BlockchainError> {
         if let Some(cache) = cache {
-            let cache = cache.lock().await;
-            return Ok(cache.contains(key) || tree.contains_key(&key.to_bytes())?)
+            let hit = cache.lock().await.contains(key);
+            cache.record(hit);
+            return Ok(hit || tree.contains_key(&key.to_bytes())?)
         }
 
         Ok(tree.contains_key(&key.to_bytes())?)
@@ -398,6 +480,99 @@ impl Storage for SledStorage {
         self.network.is_mainnet()
     }
 
+    fn create_read_handle(&self) -> Self {
+        // Trees are shared, caches and counters are not kept up to date
+        Self {
+            network: self.network,
+            transactions: self.transactions.clone(),
+            txs_executed: self.txs_executed.clone(),
+            blocks_execution_order: self.blocks_execution_order.clone(),
+            blocks: self.blocks.clone(),
+            blocks_at_height: self.blocks_at_height.clone(),
+            extra: self.extra.clone(),
+            topo_by_hash: self.topo_by_hash.clone(),
+            hash_at_topo: self.hash_at_topo.clone(),
+            cumulative_difficulty: self.cumulative_difficulty.clone(),
+            difficulty_covariance: self.difficulty_covariance.clone(),
+            assets: self.assets.clone(),
+            assets_topoheight: self.assets_topoheight.clone(),
+            asset_states: self.asset_states.clone(),
+            multisig: self.multisig.clone(),
+            locks: self.locks.clone(),
+            contracts: self.contracts.clone(),
+            contract_data: self.contract_data.clone(),
+            work_scores: self.work_scores.clone(),
+            events: self.events.clone(),
+            nonces: self.nonces.clone(),
+            rewards: self.rewards.clone(),
+            supply: self.supply.clone(),
+            difficulty: self.difficulty.clone(),
+            tx_blocks: self.tx_blocks.clone(),
+            versioned_nonces: self.versioned_nonces.clone(),
+            balances: self.balances.clone(),
+            versioned_balances: self.versioned_balances.clone(),
+            merkle_hashes: self.merkle_hashes.clone(),
+            registrations: self.registrations.clone(),
+            registrations_prefixed: self.registrations_prefixed.clone(),
+            block_stats: self.block_stats.clone(),
+            daily_stats: self.daily_stats.clone(),
+            db: self.db.clone(),
+            transactions_cache: None,
+            blocks_cache: None,
+            past_blocks_cache: None,
+            topo_by_hash_cache: None,
+            hash_at_topo_cache: None,
+            cumulative_difficulty_cache: None,
+            assets_cache: None,
+            versioned_balances_cache: None,
+            nonces_trees_cache: None,
+            tips_cache: self.tips_cache.clone(),
+            pruned_topoheight: self.pruned_topoheight,
+            assets_count: AtomicU64::new(self.assets_count.load(Ordering::SeqCst)),
+            accounts_count: AtomicU64::new(self.accounts_count.load(Ordering::SeqCst)),
+            transactions_count: AtomicU64::new(self.transactions_count.load(Ordering::SeqCst)),
+            blocks_count: AtomicU64::new(self.blocks_count.load(Ordering::SeqCst)),
+            blocks_execution_count: AtomicU64::new(self.blocks_execution_count.load(Ordering::SeqCst))
+        }
+    }
+
+    async fn get_cache_stats(&self) -> Vec<CacheStats> {
+        let mut stats = Vec::new();
+        if let Some(cache) = &self.transactions_cache {
+            stats.push(cache.get_stats("transactions").await);
+        }
+
+        if let Some(cache) = &self.blocks_cache {
+            stats.push(cache.get_stats("blocks").await);
+        }
+
+        if let Some(cache) = &self.past_blocks_cache {
+            stats.push(cache.get_stats("past_blocks").await);
+        }
+
+        if let Some(cache) = &self.topo_by_hash_cache {
+            stats.push(cache.get_stats("topo_by_hash").await);
+        }
+
+        if let Some(cache) = &self.hash_at_topo_cache {
+            stats.push(cache.get_stats("hash_at_topo").await);
+        }
+
+        if let Some(cache) = &self.cumulative_difficulty_cache {
+            stats.push(cache.get_stats("cumulative_difficulty").await);
+        }
+
+        if let Some(cache) = &self.assets_cache {
+            stats.push(cache.get_stats("assets").await);
+        }
+
+        if let Some(cache) = &self.versioned_balances_cache {
+            stats.push(cache.get_stats("versioned_balances").await);
+        }
+
+        stats
+    }
+
     async fn clear_caches(&mut self) -> Result<(), BlockchainError> {
         if let Some(cache) = self.transactions_cache.as_ref() {
             let mut cache = cache.lock().await;
@@ -434,7 +609,7 @@ impl Storage for SledStorage {
             cache.clear();
         }
 
-        if let Some(cache) = self.balances_trees_cache.as_ref() {
+        if let Some(cache) = self.versioned_balances_cache.as_ref() {
             let mut cache = cache.lock().await;
             cache.clear();
         }
@@ -515,6 +690,7 @@ impl Storage for SledStorage {
 
     async fn delete_versioned_balances_at_topoheight(&mut self, topoheight: u64) -> Result<(), BlockchainError> {
         trace!("delete versioned balances at topoheight {}", topoheight);
+        self.clear_versioned_balances_cache().await;
         for el in self.versioned_balances.scan_prefix(&topoheight.to_be_bytes()) {
             let (key, value) = el?;
             // Delete this version from DB
@@ -577,6 +753,7 @@ impl Storage for SledStorage {
 
     async fn delete_versioned_balances_above_topoheight(&mut self, topoheight: u64) -> Result<(), BlockchainError> {
         trace!("delete versioned balances above topoheight {}!", topoheight);
+        self.clear_versioned_balances_cache().await;
         self.delete_versioned_tree_above_topoheight(&self.versioned_balances, topoheight)
     }
 
@@ -620,6 +797,7 @@ impl Storage for SledStorage {
 
     async fn delete_versioned_balances_below_topoheight(&mut self, topoheight: u64) -> Result<(), BlockchainError> {
         trace!("delete versioned balances below topoheight {}!", topoheight);
+        self.clear_versioned_balances_cache().await;
         self.delete_versioned_tree_below_topoheight(&self.versioned_balances, topoheight)
     }
 
@@ -628,6 +806,69 @@ impl Storage for SledStorage {
         self.delete_versioned_tree_below_topoheight(&self.versioned_nonces, topoheight)
     }
 
+    async fn prune_versioned_balances_below_topoheight(&mut self, topoheight: u64, keep_versions: usize) -> Result<u64, BlockchainError> {
+        trace!("prune versioned balances below topoheight {} keeping {} versions", topoheight, keep_versions);
+        self.clear_versioned_balances_cache().await;
+        let mut deleted = 0;
+        for el in self.balances.iter() {
+            let (key_bytes, value) = el?;
+            let key = PublicKey::from_bytes(&key_bytes[0..32])?;
+            let asset = Hash::from_bytes(&key_bytes[32..64])?;
+            let mut base_topoheight = u64::from_bytes(&value)?;
+            let mut base_version = self.get_balance_at_exact_topoheight(&key, &asset, base_topoheight).await?;
+
+            // search the base version: the first one below topoheight once enough versions are kept
+            let mut kept = 1;
+            while base_topoheight >= topoheight || kept < keep_versions {
+                match base_version.get_previous_topoheight() {
+                    Some(previous_topoheight) => {
+                        base_version = self.get_balance_at_exact_topoheight(&key, &asset, previous_topoheight).await?;
+                        base_topoheight = previous_topoheight;
+                        kept += 1;
+                    },
+                    None => break
+                }
+            }
+
+            // nothing to prune if the chain ends here
+            let mut previous = base_version.get_previous_topoheight();
+            if previous.is_none() {
+                continue;
+            }
+
+            // cut the chain at the base version
+            base_version.set_previous_topoheight(None);
+            self.set_balance_at_topoheight(&asset, base_topoheight, &key, &base_version).await?;
+
+            // delete all the versions below it
+            while let Some(previous_topoheight) = previous {
+                let version = self.delete_balance_at_topoheight(&key, &asset, previous_topoheight).await?;
+                previous = version.get_previous_topoheight();
+                deleted += 1;
+            }
+        }
+
+        Ok(deleted)
+    }
+
+    async fn delete_work_scores_from_topoheight(&mut self, topoheight: u64) -> Result<(), BlockchainError> {
+        trace!("delete work scores from topoheight {}", topoheight);
+        for el in self.work_scores.range(topoheight.to_be_bytes()..).keys() {
+            let key = el?;
+            self.work_scores.remove(&key)?;
+        }
+        Ok(())
+    }
+
+    async fn delete_work_scores_below_topoheight(&mut self, topoheight: u64) -> Result<(), BlockchainError> {
+        trace!("delete work scores below topoheight {}", topoheight);
+        for el in self.work_scores.range(..topoheight.to_be_bytes()).keys() {
+            let key = el?;
+            self.work_scores.remove(&key)?;
+        }
+        Ok(())
+    }
+
     // The first versioned balance that is under the topoheight is bumped to topoheight
     async fn create_snapshot_balances_at_topoheight(&mut self, topoheight: u64) -> Result<(), BlockchainError> {
         // asset tree where PublicKey are stored with the highest balance topoheight in it
@@ -681,6 +922,7 @@ impl Storage for SledStorage {
             }
         }
 
+        self.clear_versioned_balances_cache().await;
         Ok(())
     }
 
@@ -828,6 +1070,7 @@ impl Storage for SledStorage {
             // Delete the hash at topoheight
             let (hash, block, block_txs) = self.delete_block_at_topoheight(topoheight).await?;
             trace!("Block {} at topoheight {} deleted", hash, topoheight);
+            self.rewind_block_stats(topoheight).await?;
             txs.extend(block_txs);
 
             // generate new tips
@@ -868,25 +1111,76 @@ impl Storage for SledStorage {
         // All deleted assets
         let mut deleted_assets = HashSet::new();
         
-        // clean all assets
-        for el in self.assets.iter() {
-            let (key, value) = el.context("error on asset iterator")?;
-            let asset = Hash::from_bytes(&key)?;
-            trace!("verifying asset registered: {}", asset);
+        // clean all assets registered above the new topoheight
+        for el in self.assets_topoheight.range((topoheight + 1).to_be_bytes()..) {
+            let key = el.context("error on asset iterator")?.0;
+            let registration_topoheight = u64::from_be_bytes(key[0..8].try_into()?);
+            let asset = Hash::from_bytes(&key[8..])?;
+            trace!("Asset {} was registered at topoheight {}, deleting", asset, registration_topoheight);
+            // Delete it from registered assets
+            self.assets.remove(asset.as_bytes()).context(format!("Error while deleting asset {asset} from registered assets"))?;
+            self.assets_topoheight.remove(&key)?;
+
+            // drop the tree for this asset
+            self.db.drop_tree(asset.as_bytes()).context(format!("error on dropping asset {asset} tree"))?;
+
+            deleted_assets.insert(asset);
+        }
 
-            let registration_topoheight = u64::from_bytes(&value)?;
-            if registration_topoheight > topoheight {
-                trace!("Asset {} was registered at topoheight {}, deleting", asset, registration_topoheight);
-                // Delete it from registered assets
-                self.assets.remove(&key).context(format!("Error while deleting asset {asset} from registered assets"))?;
+        trace!("Cleaning asset states");
+        // delete all asset states set above the new topoheight
+        for el in self.asset_states.iter().keys() {
+            let key = el?;
+            let state_topoheight = u64::from_be_bytes(key[HASH_SIZE..].try_into()?);
+            if state_topoheight > topoheight {
+                self.asset_states.remove(&key)?;
+            }
+        }
 
-                // drop the tree for this asset
-                self.db.drop_tree(key).context(format!("error on dropping asset {asset} tree"))?;
+        trace!("Cleaning multisigs");
+        // delete all multisig configurations set above the new topoheight
+        for el in self.multisig.iter().keys() {
+            let key = el?;
+            let multisig_topoheight = u64::from_be_bytes(key[HASH_SIZE..].try_into()?);
+            if multisig_topoheight > topoheight {
+                self.multisig.remove(&key)?;
+            }
+        }
 
-                deleted_assets.insert(asset);
+        trace!("Cleaning locked transfers");
+        // delete all locked transfers changes made above the new topoheight
+        for el in self.locks.iter().keys() {
+            let key = el?;
+            let lock_topoheight = u64::from_be_bytes(key[HASH_SIZE..].try_into()?);
+            if lock_topoheight > topoheight {
+                self.locks.remove(&key)?;
             }
         }
 
+        trace!("Cleaning contracts");
+        // delete all contracts deployed above the new topoheight
+        for el in self.contracts.iter().keys() {
+            let key = el?;
+            let contract_topoheight = u64::from_be_bytes(key[HASH_SIZE..].try_into()?);
+            if contract_topoheight > topoheight {
+                self.contracts.remove(&key)?;
+            }
+        }
+
+        trace!("Cleaning contracts data");
+        // delete all contracts storage changes made above the new topoheight
+        for el in self.contract_data.iter().keys() {
+            let key = el?;
+            let data_topoheight = u64::from_be_bytes(key[HASH_SIZE + 8..].try_into()?);
+            if data_topoheight > topoheight {
+                self.contract_data.remove(&key)?;
+            }
+        }
+
+        trace!("Cleaning work scores");
+        // scores computed from a base above the new topoheight may use orphaned blocks
+        self.delete_work_scores_from_topoheight(topoheight + 1).await?;
+
         trace!("Cleaning nonces");
         // now let's process nonces versions
         // we set the new highest topoheight to the highest found under the new topoheight
@@ -1061,6 +1355,45 @@ impl Storage for SledStorage {
         Ok(self.db.size_on_disk()?)
     }
 
+    // Assets have their own tree named by their hash
+    // It may be left behind if the node was stopped during a rewind
+    async fn drop_unused_trees(&mut self) -> Result<usize, BlockchainError> {
+        trace!("drop unused trees");
+        let mut dropped = 0;
+        for name in self.db.tree_names() {
+            if name.len() != HASH_SIZE || self.assets.contains_key(&name)? {
+                continue;
+            }
+
+            debug!("Dropping unused tree {}", Hash::from_bytes(&name)?);
+            if self.db.drop_tree(&name)? {
+                dropped += 1;
+            }
+        }
+
+        Ok(dropped)
+    }
+
+    async fn flush(&mut self) -> Result<usize, BlockchainError> {
+        trace!("flush");
+        Ok(self.db.flush_async().await?)
+    }
+
+    // Export every tree, including the dynamic ones
+    async fn export_snapshot(&self, writer: &mut SnapshotWriter) -> Result<(), BlockchainError> {
+        for name in self.db.tree_names() {
+            trace!("exporting tree {}", String::from_utf8_lossy(&name));
+            let tree = self.db.open_tree(&name)?;
+            writer.write_tree(&name)?;
+            for entry in tree.iter() {
+                let (key, value) = entry?;
+                writer.write_entry(&key, &value)?;
+            }
+        }
+
+        Ok(())
+    }
+
     async fn stop(&mut self) -> Result<(), BlockchainError> {
         info!("Stopping Storage...");
         info!("Flushing Sled database");
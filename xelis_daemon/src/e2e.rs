@@ -0,0 +1,343 @@
+// End-to-end tests running two daemons connected over local P2P
+// and a wallet attached to each of them in the same process
+// Daemons are in manual simulator mode: PoW is skipped and blocks are mined on demand
+// Run them with: cargo test -p xelis_daemon --features e2e_tests
+use std::{
+    future::Future,
+    net::{SocketAddr, TcpListener},
+    sync::Arc,
+    time::{Duration, Instant}
+};
+use rand::Rng;
+use tokio::time::sleep;
+use xelis_common::{
+    config::XELIS_ASSET,
+    crypto::{Hash, Hashable, KeyPair, PublicKey},
+    network::Network,
+    transaction::builder::{FeeBuilder, TransactionTypeBuilder, TransferBuilder}
+};
+use xelis_wallet::{
+    entry::EntryData,
+    wallet::{LogProgressTableGenerationReportFunction, Wallet}
+};
+use crate::core::{
+    blockchain::{Blockchain, Config},
+    error::BlockchainError,
+    simulator::Simulator,
+    storage::{
+        BalanceProvider,
+        BlockProvider,
+        CacheSizes,
+        ClientProtocolProvider,
+        DagOrderProvider,
+        SledStorage
+    }
+};
+
+type Daemon = Arc<Blockchain<SledStorage>>;
+
+const WAIT_TIMEOUT: Duration = Duration::from_secs(60);
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+const PASSWORD: &str = "e2e";
+const FIRST_AMOUNT: u64 = 100_000;
+const SECOND_AMOUNT: u64 = 50_000;
+
+// Get a free port on the loopback interface
+fn get_free_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .map(|addr| addr.port())
+        .expect("No free port available")
+}
+
+fn create_config(dir_path: &str, p2p_port: u16, rpc_port: u16) -> Config {
+    let mut config = Config::default();
+    config.dir_path = Some(dir_path.to_owned());
+    config.p2p_bind_address = format!("127.0.0.1:{}", p2p_port);
+    config.rpc_bind_address = format!("127.0.0.1:{}", rpc_port);
+    config.simulator = Some(Simulator::Manual);
+    config.disable_getwork_server = true;
+    config
+}
+
+// Poll the condition until it is true, panic if it takes too long
+async fn wait_for<F, Fut>(what: &str, mut condition: F)
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = bool>
+{
+    let start = Instant::now();
+    while !condition().await {
+        if start.elapsed() > WAIT_TIMEOUT {
+            panic!("Timeout while waiting for {}", what);
+        }
+        sleep(POLL_INTERVAL).await;
+    }
+}
+
+async fn get_top_hash(daemon: &Daemon) -> Hash {
+    daemon.get_top_block_hash().await.expect("Top block hash")
+}
+
+async fn get_peer_count(daemon: &Daemon) -> usize {
+    match daemon.get_p2p().read().await.as_ref() {
+        Some(p2p) => p2p.get_peer_count().await,
+        None => 0
+    }
+}
+
+async fn is_tx_executed(daemon: &Daemon, hash: &Hash) -> bool {
+    let storage = daemon.get_storage().read().await;
+    storage.is_tx_executed_in_a_block(hash).unwrap_or(false)
+}
+
+// Mine blocks on top of the daemon chain and propagate them
+async fn mine_blocks(daemon: &Daemon, miner: &PublicKey, count: usize) {
+    for _ in 0..count {
+        let block = daemon.mine_block(miner).await.expect("Mine block");
+        daemon.add_new_block(block, true, true).await.expect("Add mined block");
+    }
+}
+
+async fn wait_for_same_chain(a: &Daemon, b: &Daemon) {
+    wait_for("daemons to be on the same chain", move || async move {
+        a.get_topo_height() == b.get_topo_height() && get_top_hash(a).await == get_top_hash(b).await
+    }).await;
+}
+
+async fn wait_for_wallet_sync(wallet: &Arc<Wallet>, daemon: &Daemon) {
+    wait_for("wallet to be synced", move || async move {
+        let top_hash = get_top_hash(daemon).await;
+        let storage = wallet.get_storage().read().await;
+        storage.get_top_block_hash().map(|hash| hash == top_hash).unwrap_or(false)
+            && storage.get_synced_topoheight().map(|topoheight| topoheight == daemon.get_topo_height()).unwrap_or(false)
+    }).await;
+}
+
+// Send the amount from a wallet to another through the daemon of the sender
+async fn transfer(from: &Arc<Wallet>, to: &Arc<Wallet>, amount: u64) -> Hash {
+    let transfer = TransferBuilder {
+        destination: to.get_address(),
+        amount,
+        asset: XELIS_ASSET,
+        extra_data: None,
+        lock: None
+    };
+    let tx = from.create_transaction(TransactionTypeBuilder::Transfers(vec![transfer]), FeeBuilder::default()).await
+        .expect("Create transaction");
+    from.submit_transaction(&tx).await.expect("Submit transaction");
+    tx.hash()
+}
+
+// Disconnect the daemons and prevent any reconnection
+async fn partition(a: &Daemon, b: &Daemon, addr: &SocketAddr) {
+    if let Some(p2p) = b.get_p2p().read().await.as_ref() {
+        p2p.remove_anchor(addr).await;
+    }
+
+    for daemon in [a, b] {
+        if let Some(p2p) = daemon.get_p2p().read().await.as_ref() {
+            p2p.get_peer_list().blacklist_address(&addr.ip()).await;
+        }
+    }
+
+    wait_for("daemons to be disconnected", move || async move {
+        get_peer_count(a).await == 0 && get_peer_count(b).await == 0
+    }).await;
+}
+
+// Allow the daemons to connect again and reconnect the second one to the first one
+// Loopback address is whitelisted so failed attempts can't temp ban it
+async fn connect(a: &Daemon, b: &Daemon, addr: &SocketAddr) {
+    for daemon in [a, b] {
+        if let Some(p2p) = daemon.get_p2p().read().await.as_ref() {
+            p2p.get_peer_list().whitelist_address(&addr.ip()).await;
+        }
+    }
+
+    if let Some(p2p) = b.get_p2p().read().await.as_ref() {
+        p2p.add_anchor(*addr).await;
+    }
+
+    wait_for("daemons to be connected", move || async move {
+        get_peer_count(a).await == 1 && get_peer_count(b).await == 1
+    }).await;
+}
+
+// Verify that the wallet balance matches the one stored by the daemon
+async fn assert_balance_matches(wallet: &Arc<Wallet>, daemon: &Daemon) -> u64 {
+    let expected = {
+        let storage = wallet.get_storage().read().await;
+        storage.get_plaintext_balance_for(&XELIS_ASSET).await.expect("Wallet balance")
+    };
+
+    let (_, version) = {
+        let storage = daemon.get_storage().read().await;
+        storage.get_last_balance(wallet.get_public_key(), &XELIS_ASSET).await.expect("Daemon balance")
+    };
+    let ciphertext = version.take_balance().take_ciphertext().expect("Valid ciphertext");
+    let balance = Arc::clone(wallet).decrypt_ciphertext(ciphertext).await.expect("Decrypt balance");
+    assert_eq!(balance, expected);
+
+    balance
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_transfers_across_daemons_with_reorg() {
+    let temp_dir = std::env::temp_dir();
+    let dir = format!("{}/xelis-e2e-{}/", temp_dir.display(), rand::thread_rng().gen::<u64>());
+    // Precomputed tables are kept between runs as they are long to generate
+    let tables_dir = format!("{}/xelis-e2e-tables/", temp_dir.display());
+
+    let (p2p_port_a, rpc_port_a) = (get_free_port(), get_free_port());
+    let (p2p_port_b, rpc_port_b) = (get_free_port(), get_free_port());
+    let addr_a: SocketAddr = format!("127.0.0.1:{}", p2p_port_a).parse().unwrap();
+
+    // Dev network has no hardcoded genesis block: the one generated by the first daemon is reused
+    let dir_a = format!("{}daemon-a/", dir);
+    let storage = SledStorage::new(dir_a.clone(), CacheSizes::all(0), Network::Dev).unwrap();
+    let daemon_a = Blockchain::new(create_config(&dir_a, p2p_port_a, rpc_port_a), Network::Dev, storage).await.unwrap();
+    let genesis_block = {
+        let storage = daemon_a.get_storage().read().await;
+        let hash = storage.get_hash_at_topo_height(0).await.unwrap();
+        storage.get_block_by_hash(&hash).await.unwrap()
+    };
+
+    let dir_b = format!("{}daemon-b/", dir);
+    let storage = SledStorage::new(dir_b.clone(), CacheSizes::all(0), Network::Dev).unwrap();
+    let daemon_b = Blockchain::new_with_genesis_block(create_config(&dir_b, p2p_port_b, rpc_port_b), Network::Dev, storage, genesis_block).await.unwrap();
+
+    connect(&daemon_a, &daemon_b, &addr_a).await;
+
+    let precomputed_tables = Wallet::read_or_generate_precomputed_tables(Some(tables_dir), LogProgressTableGenerationReportFunction).unwrap();
+    let wallet_a = Wallet::create(format!("{}wallet-a", dir), PASSWORD.to_owned(), None, None, Network::Dev, precomputed_tables.clone()).unwrap();
+    let wallet_b = Wallet::create(format!("{}wallet-b", dir), PASSWORD.to_owned(), None, None, Network::Dev, precomputed_tables).unwrap();
+    wallet_a.set_online_mode(&format!("127.0.0.1:{}", rpc_port_a), true).await.unwrap();
+    wallet_b.set_online_mode(&format!("127.0.0.1:{}", rpc_port_b), true).await.unwrap();
+
+    // Blocks rewards are sent to a third key so only the transfers change the wallets balances
+    // except the first blocks funding the wallet A
+    let miner = KeyPair::new().get_public_key().compress();
+
+    // Fund the wallet A
+    mine_blocks(&daemon_a, wallet_a.get_public_key(), 5).await;
+    wait_for_same_chain(&daemon_a, &daemon_b).await;
+    wait_for_wallet_sync(&wallet_a, &daemon_a).await;
+    wait_for_wallet_sync(&wallet_b, &daemon_b).await;
+
+    // First transfer, mined by the daemon A and synced by the daemon B
+    let first_tx = transfer(&wallet_a, &wallet_b, FIRST_AMOUNT).await;
+    mine_blocks(&daemon_a, &miner, 1).await;
+    wait_for_same_chain(&daemon_a, &daemon_b).await;
+    assert!(is_tx_executed(&daemon_a, &first_tx).await);
+    assert!(is_tx_executed(&daemon_b, &first_tx).await);
+
+    // Split the network: the daemon A mines the second transfer
+    // while the daemon B builds a heavier chain without it
+    partition(&daemon_a, &daemon_b, &addr_a).await;
+    wait_for_wallet_sync(&wallet_a, &daemon_a).await;
+    let second_tx = transfer(&wallet_a, &wallet_b, SECOND_AMOUNT).await;
+    mine_blocks(&daemon_a, &miner, 1).await;
+    assert!(is_tx_executed(&daemon_a, &second_tx).await);
+    mine_blocks(&daemon_b, &miner, 3).await;
+    assert!(!is_tx_executed(&daemon_b, &second_tx).await);
+
+    // Reconnect them, the daemon A reorgs on the chain of the daemon B
+    // The second transfer is either merged in the DAG or put back in mempool and mined again
+    connect(&daemon_a, &daemon_b, &addr_a).await;
+    {
+        let (a, b, miner, second_tx) = (&daemon_a, &daemon_b, &miner, &second_tx);
+        wait_for("second transfer to be executed on both daemons", move || async move {
+            wait_for_same_chain(a, b).await;
+            if is_tx_executed(a, second_tx).await && is_tx_executed(b, second_tx).await {
+                return true
+            }
+
+            mine_blocks(a, miner, 1).await;
+            false
+        }).await;
+    }
+    wait_for_same_chain(&daemon_a, &daemon_b).await;
+
+    wait_for_wallet_sync(&wallet_a, &daemon_a).await;
+    wait_for_wallet_sync(&wallet_b, &daemon_b).await;
+
+    // Balances are the same on both daemons and match the wallets ones
+    let balance_a = assert_balance_matches(&wallet_a, &daemon_a).await;
+    assert_eq!(assert_balance_matches(&wallet_a, &daemon_b).await, balance_a);
+    let balance_b = assert_balance_matches(&wallet_b, &daemon_b).await;
+    assert_eq!(assert_balance_matches(&wallet_b, &daemon_a).await, balance_b);
+    assert_eq!(balance_b, FIRST_AMOUNT + SECOND_AMOUNT);
+
+    // Each transfer is in both histories exactly once, at the same topoheight
+    let history_a = wallet_a.get_storage().read().await.get_transactions().unwrap();
+    let history_b = wallet_b.get_storage().read().await.get_transactions().unwrap();
+    for (hash, amount) in [(&first_tx, FIRST_AMOUNT), (&second_tx, SECOND_AMOUNT)] {
+        let outgoing: Vec<_> = history_a.iter().filter(|entry| entry.get_hash() == hash).collect();
+        let incoming: Vec<_> = history_b.iter().filter(|entry| entry.get_hash() == hash).collect();
+        assert_eq!(outgoing.len(), 1);
+        assert_eq!(incoming.len(), 1);
+        assert_eq!(outgoing[0].get_topoheight(), incoming[0].get_topoheight());
+
+        match outgoing[0].get_entry() {
+            EntryData::Outgoing { transfers, .. } => {
+                assert_eq!(transfers.len(), 1);
+                assert_eq!(transfers[0].get_amount(), amount);
+                assert_eq!(transfers[0].get_destination(), wallet_b.get_public_key());
+            },
+            _ => panic!("Expected an outgoing entry for {}", hash)
+        }
+
+        match incoming[0].get_entry() {
+            EntryData::Incoming { from, transfers } => {
+                assert_eq!(from, wallet_a.get_public_key());
+                assert_eq!(transfers.len(), 1);
+                assert_eq!(transfers[0].get_amount(), amount);
+            },
+            _ => panic!("Expected an incoming entry for {}", hash)
+        }
+    }
+
+    for wallet in [wallet_a, wallet_b] {
+        wallet.close().await;
+    }
+    for daemon in [daemon_a, daemon_b] {
+        daemon.stop().await;
+    }
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_validate_block_before_commit() {
+    let dir = format!("{}/xelis-e2e-{}/", std::env::temp_dir().display(), rand::thread_rng().gen::<u64>());
+    let storage = SledStorage::new(dir.clone(), CacheSizes::all(0), Network::Dev).unwrap();
+    let daemon = Blockchain::new(create_config(&dir, get_free_port(), get_free_port()), Network::Dev, storage).await.unwrap();
+    let miner = KeyPair::new().get_public_key().compress();
+
+    // Validating a block doesn't change the chain
+    let block = daemon.mine_block(&miner).await.unwrap();
+    let hash = block.hash();
+    let context = {
+        let storage = daemon.get_storage().read().await;
+        daemon.validate_block(&storage, block.clone(), true).await.unwrap()
+    };
+    assert_eq!(context.get_hash(), &hash);
+    assert_eq!(daemon.get_topo_height(), 0);
+
+    // Another block is added before the commit, the first one is validated again
+    mine_blocks(&daemon, &miner, 1).await;
+    {
+        let mut storage = daemon.get_storage().write().await;
+        daemon.commit_block(&mut storage, context, false, true).await.unwrap();
+        assert!(storage.has_block_with_hash(&hash).await.unwrap());
+    }
+
+    // A committed block can't be validated again
+    {
+        let storage = daemon.get_storage().read().await;
+        assert!(matches!(daemon.validate_block(&storage, block, true).await, Err(BlockchainError::AlreadyInChain)));
+    }
+
+    daemon.stop().await;
+    let _ = std::fs::remove_dir_all(&dir);
+}
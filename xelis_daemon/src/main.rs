@@ -2,24 +2,40 @@ pub mod rpc;
 pub mod p2p;
 pub mod core;
 pub mod config;
+mod config_file;
 
-use config::{DEV_PUBLIC_KEY, STABLE_LIMIT};
+#[cfg(all(test, feature = "e2e_tests"))]
+mod e2e;
+
+use config::DEV_PUBLIC_KEY;
 use fern::colors::Color;
 use humantime::format_duration;
 use log::{trace, error, info, warn};
 use p2p::P2pServer;
 use rpc::{
+    admin,
     getwork_server::SharedGetWorkServer,
-    rpc::get_block_response_for_hash
+    rpc::{get_block_response_for_hash, register_methods},
+    stratum_server::SharedStratumServer
 };
+use serde::Serialize;
+use serde_json::{json, Value};
 use xelis_common::{
+    api::daemon::{
+        AdminRewindChainParams,
+        GetBalanceParams,
+        GetBlockAtTopoHeightParams,
+        GetBlockByHashParams,
+        GetTransactionParams
+    },
     async_handler,
     config::{VERSION, XELIS_ASSET},
     context::Context,
     crypto::{
-        Address,Hashable
+        Address,
+        Hash,
+        Hashable
     },
-    difficulty::Difficulty,
     network::Network,
     prompt::{
         Prompt,
@@ -38,7 +54,13 @@ use xelis_common::{
         LogLevel,
         ShareablePrompt
     },
-    rpc_server::WebSocketServerHandler,
+    rpc_server::{
+        Id,
+        RPCHandler,
+        RpcRequest,
+        WebSocketServerHandler,
+        JSON_RPC_VERSION
+    },
     serializer::Serializer,
     transaction::Transaction,
     utils::{
@@ -54,25 +76,33 @@ use crate::{
             Blockchain,
             get_block_reward
         },
+        snapshot,
         storage::{
+            CacheSizes,
             Storage,
             SledStorage
         }
     },
     config::{
-        BLOCK_TIME_MILLIS,
-        MILLIS_PER_SECOND
+        MILLIS_PER_SECOND,
+        REINDEX_DIR_NAME,
+        SNAPSHOT_IMPORT_DIR_NAME,
+        VERIFY_CHAIN_DIR_NAME
     }
 };
 use core::blockdag;
 use std::{
-    fs::File,
+    borrow::Cow,
+    env,
+    ffi::OsString,
+    fs::{self, File},
     io::Write,
+    path::Path,
     net::{IpAddr, SocketAddr},
     sync::Arc,
     time::Duration
 };
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches, Parser};
 use anyhow::{
     Result,
     Context as AnyContext
@@ -116,14 +146,37 @@ pub struct NodeConfig {
     logs_path: String,
     /// Network selected for chain
     #[clap(long, value_enum, default_value_t = Network::Mainnet)]
-    network: Network
+    network: Network,
+    /// Load the options from a TOML configuration file
+    /// 
+    /// Keys are the names of the options with '_' instead of '-'.
+    /// Command line arguments have priority over the file.
+    #[clap(long)]
+    config_file: Option<String>,
+    /// Print a configuration file template with all the options and exit
+    #[clap(long)]
+    generate_config: bool
 }
 
-const BLOCK_TIME: Difficulty = Difficulty::from_u64(BLOCK_TIME_MILLIS / MILLIS_PER_SECOND);
-
 #[tokio::main]
 async fn main() -> Result<()> {
     let mut config: NodeConfig = NodeConfig::parse();
+    if config.generate_config {
+        print!("{}", config_file::generate_template(&NodeConfig::command()));
+        return Ok(())
+    }
+
+    if let Some(path) = config.config_file.as_ref() {
+        // Options from the file are placed first so the command line ones override them
+        let mut args: Vec<OsString> = env::args_os().take(1).collect();
+        args.extend(config_file::load_args(&NodeConfig::command(), path)?);
+        args.extend(env::args_os().skip(1));
+
+        let matches = NodeConfig::command()
+            .args_override_self(true)
+            .get_matches_from(args);
+        config = NodeConfig::from_arg_matches(&matches)?;
+    }
 
     let prompt = Prompt::new(config.log_level, &config.logs_path, &config.filename_log, config.disable_file_logging, config.disable_file_log_date_based, config.disable_log_color, !config.disable_interactive_mode)?;
     info!("XELIS Blockchain running version: {}", VERSION);
@@ -141,15 +194,23 @@ async fn main() -> Result<()> {
         }
     }
 
-    let storage = {
-        let use_cache = if blockchain_config.cache_size > 0 {
-            Some(blockchain_config.cache_size)
-        } else {
-            None
-        };
+    let cache_sizes = blockchain_config.get_cache_sizes();
 
+    if let Some(path) = blockchain_config.import_snapshot.as_ref() {
+        import_snapshot(&blockchain_config, config.network, path)?;
+    }
+
+    if blockchain_config.reindex {
+        reindex_chain(&blockchain_config, config.network, cache_sizes).await?;
+    }
+
+    if blockchain_config.verify_chain {
+        verify_chain(&blockchain_config, config.network, cache_sizes).await?;
+    }
+
+    let storage = {
         let dir_path = blockchain_config.dir_path.clone().unwrap_or_default();
-        SledStorage::new(dir_path, use_cache, config.network)?
+        SledStorage::new(dir_path, cache_sizes, config.network)?
     };
 
     let blockchain = Blockchain::new(blockchain_config, config.network, storage).await?;
@@ -161,10 +222,121 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+// Rebuild the chain from its stored blocks in a temporary directory
+// and replace the current chain with it once done
+// The current chain is kept intact if the reindex fails or is interrupted
+async fn reindex_chain(config: &Config, network: Network, cache_sizes: CacheSizes) -> Result<()> {
+    let dir_path = config.dir_path.clone().unwrap_or_default();
+    let reindex_path = format!("{}{}", dir_path, REINDEX_DIR_NAME);
+    let network_name = network.to_string().to_lowercase();
+    let current_chain_path = format!("{}{}", dir_path, network_name);
+    let reindex_chain_path = format!("{}{}", reindex_path, network_name);
+
+    // Delete any previous reindex that was interrupted
+    if Path::new(&reindex_chain_path).exists() {
+        warn!("Deleting previous unfinished reindex at {}", reindex_chain_path);
+        fs::remove_dir_all(&reindex_chain_path).context("Error while deleting previous reindex")?;
+    }
+
+    {
+        let source = SledStorage::new(dir_path, cache_sizes, network)?;
+        let target = SledStorage::new(reindex_path.clone(), cache_sizes, network)?;
+        let (blockchain, _) = Blockchain::reindex(config.clone(), network, &source, target).await?;
+        // flush the rebuilt chain on disk
+        blockchain.stop().await;
+    }
+
+    // Swap the chains, the old one is deleted only once the new one is in place
+    let old_chain_path = format!("{}.old", current_chain_path);
+    fs::rename(&current_chain_path, &old_chain_path).context("Error while moving the current chain")?;
+    fs::rename(&reindex_chain_path, &current_chain_path).context("Error while moving the reindexed chain")?;
+    fs::remove_dir_all(&old_chain_path).context("Error while deleting the old chain")?;
+    fs::remove_dir_all(&reindex_path).context("Error while deleting the reindex directory")?;
+    info!("Chain has been reindexed");
+
+    Ok(())
+}
+
+// Replay the whole chain with a full verification in a temporary directory
+// and compare the result with the current chain, which is never modified
+async fn verify_chain(config: &Config, network: Network, cache_sizes: CacheSizes) -> Result<()> {
+    let dir_path = config.dir_path.clone().unwrap_or_default();
+    let verify_path = format!("{}{}", dir_path, VERIFY_CHAIN_DIR_NAME);
+
+    // Delete any previous verification that was interrupted
+    if Path::new(&verify_path).exists() {
+        warn!("Deleting previous unfinished verification at {}", verify_path);
+        fs::remove_dir_all(&verify_path).context("Error while deleting previous verification")?;
+    }
+
+    let result = {
+        let source = SledStorage::new(dir_path, cache_sizes, network)?;
+        let target = SledStorage::new(verify_path.clone(), cache_sizes, network)?;
+        Blockchain::verify_chain(config.clone(), network, &source, target).await
+    };
+
+    fs::remove_dir_all(&verify_path).context("Error while deleting the verification directory")?;
+    let verified = result.context("Chain verification failed")?;
+    info!("Chain has been verified, {} blocks replayed without any inconsistency", verified);
+
+    Ok(())
+}
+
+// Import a snapshot in a temporary directory once its manifest signature is verified
+// and replace the current chain with it only if its data matches the manifest
+fn import_snapshot(config: &Config, network: Network, snapshot_path: &str) -> Result<()> {
+    let trusted_publishers = snapshot::parse_publisher_keys(&config.trusted_snapshot_publishers)
+        .context("Error while parsing trusted snapshot publishers")?;
+    let snapshot_dir = Path::new(snapshot_path);
+    let manifest = snapshot::verify_manifest(snapshot_dir, network, &trusted_publishers, config.allow_unsigned_snapshot)
+        .context("Error while verifying snapshot")?;
+    info!("Importing snapshot at topoheight {} with top block {}", manifest.topoheight, manifest.top_block_hash);
+
+    let dir_path = config.dir_path.clone().unwrap_or_default();
+    let import_path = format!("{}{}", dir_path, SNAPSHOT_IMPORT_DIR_NAME);
+    let network_name = network.to_string().to_lowercase();
+    let current_chain_path = format!("{}{}", dir_path, network_name);
+    let import_chain_path = format!("{}{}", import_path, network_name);
+
+    // Delete any previous import that was interrupted
+    if Path::new(&import_chain_path).exists() {
+        warn!("Deleting previous unfinished snapshot import at {}", import_chain_path);
+        fs::remove_dir_all(&import_chain_path).context("Error while deleting previous snapshot import")?;
+    }
+
+    if let Err(e) = snapshot::import_snapshot_data(snapshot_dir, &manifest, Path::new(&import_chain_path)) {
+        // Don't keep a partial or tampered chain on disk
+        if let Err(e) = fs::remove_dir_all(&import_path) {
+            warn!("Error while deleting the snapshot import directory: {}", e);
+        }
+        return Err(anyhow::Error::from(e).context("Error while importing snapshot"));
+    }
+
+    // Swap the chains, the old one is deleted only once the new one is in place
+    if Path::new(&current_chain_path).exists() {
+        let old_chain_path = format!("{}.old", current_chain_path);
+        fs::rename(&current_chain_path, &old_chain_path).context("Error while moving the current chain")?;
+        fs::rename(&import_chain_path, &current_chain_path).context("Error while moving the imported chain")?;
+        fs::remove_dir_all(&old_chain_path).context("Error while deleting the old chain")?;
+    } else {
+        fs::rename(&import_chain_path, &current_chain_path).context("Error while moving the imported chain")?;
+    }
+    fs::remove_dir_all(&import_path).context("Error while deleting the snapshot import directory")?;
+    info!("Snapshot has been imported");
+
+    Ok(())
+}
+
 async fn run_prompt<S: Storage>(prompt: ShareablePrompt, blockchain: Arc<Blockchain<S>>, network: Network) -> Result<(), PromptError> {
     let mut context = Context::default();
     context.store(blockchain.clone());
 
+    // RPC methods called by the inspection commands
+    let mut rpc_handler = RPCHandler::new(blockchain.clone());
+    register_methods(&mut rpc_handler, false, false);
+    admin::register_methods(&mut rpc_handler);
+    context.store(rpc_handler);
+
     let command_manager = CommandManager::with_context(context, prompt.clone());
     command_manager.register_default_commands()?;
 
@@ -182,29 +354,38 @@ async fn run_prompt<S: Storage>(prompt: ShareablePrompt, blockchain: Arc<Blockch
     command_manager.add_command(Command::new("status", "Current daemon status", CommandHandler::Async(async_handler!(status::<S>))))?;
     command_manager.add_command(Command::with_optional_arguments("blacklist", "View blacklist or add a peer address in it", vec![Arg::new("address", ArgType::String)], CommandHandler::Async(async_handler!(blacklist::<S>))))?;
     command_manager.add_command(Command::with_optional_arguments("whitelist", "View whitelist or add a peer address in it", vec![Arg::new("address", ArgType::String)], CommandHandler::Async(async_handler!(whitelist::<S>))))?;
-    command_manager.add_command(Command::with_optional_arguments("verify_chain", "Check chain supply", vec![Arg::new("topoheight", ArgType::Number)], CommandHandler::Async(async_handler!(verify_chain::<S>))))?;
+    command_manager.add_command(Command::with_optional_arguments("validate_supply", "Recompute the supply and compare it to the stored one", vec![Arg::new("start_topoheight", ArgType::Number), Arg::new("end_topoheight", ArgType::Number)], CommandHandler::Async(async_handler!(validate_supply::<S>))))?;
     command_manager.add_command(Command::with_required_arguments("kick_peer", "Kick a peer using its ip:port", vec![Arg::new("address", ArgType::String)], CommandHandler::Async(async_handler!(kick_peer::<S>))))?;
-    command_manager.add_command(Command::new("clear_caches", "Clear storage caches", CommandHandler::Async(async_handler!(clear_caches::<S>))))?;
+    command_manager.add_command(Command::new("clear_caches", "Clear storage and DAG caches", CommandHandler::Async(async_handler!(clear_caches::<S>))))?;
+    command_manager.add_command(Command::new("promote", "Promote the standby node to full mode", CommandHandler::Async(async_handler!(promote::<S>))))?;
     command_manager.add_command(Command::new("clear_rpc_connections", "Clear all WS connections from RPC", CommandHandler::Async(async_handler!(clear_rpc_connections::<S>))))?;
     command_manager.add_command(Command::new("clear_p2p_connections", "Clear all P2P connections", CommandHandler::Async(async_handler!(clear_p2p_connections::<S>))))?;
     command_manager.add_command(Command::new("clear_p2p_peerlist", "Clear P2P peerlist", CommandHandler::Async(async_handler!(clear_p2p_peerlist::<S>))))?;
     command_manager.add_command(Command::with_optional_arguments("difficulty_dataset", "Create a dataset for difficulty from chain", vec![Arg::new("output", ArgType::String)], CommandHandler::Async(async_handler!(difficulty_dataset::<S>))))?;
     command_manager.add_command(Command::with_optional_arguments("mine_block", "Mine a block on testnet", vec![Arg::new("count", ArgType::Number)], CommandHandler::Async(async_handler!(mine_block::<S>))))?;
     command_manager.add_command(Command::new("p2p_outgoing_connections", "Accept/refuse to connect to outgoing nodes", CommandHandler::Async(async_handler!(p2p_outgoing_connections::<S>))))?;
+    command_manager.add_command(Command::with_arguments("export_snapshot", "Export the chain in a snapshot directory, signed if a key file is set", vec![Arg::new("path", ArgType::String)], vec![Arg::new("signing_key", ArgType::String)], CommandHandler::Async(async_handler!(export_snapshot::<S>))))?;
+    command_manager.add_command(Command::with_required_arguments("generate_snapshot_key", "Generate a key file to sign the snapshots", vec![Arg::new("path", ArgType::String)], CommandHandler::Async(async_handler!(generate_snapshot_key::<S>))))?;
     command_manager.add_command(Command::with_required_arguments("add_peer", "Connect to a new peer using ip:port format", vec![Arg::new("address", ArgType::String)], CommandHandler::Async(async_handler!(add_peer::<S>))))?;
+    command_manager.add_command(Command::with_required_arguments("block", "Print a block by its hash or topoheight", vec![Arg::new("hash_or_topoheight", ArgType::String)], CommandHandler::Async(async_handler!(block::<S>))))?;
+    command_manager.add_command(Command::with_required_arguments("tx", "Print a transaction from chain or mempool", vec![Arg::new("hash", ArgType::Hash)], CommandHandler::Async(async_handler!(tx::<S>))))?;
+    command_manager.add_command(Command::with_arguments("balance", "Print the last balance version of an address", vec![Arg::new("address", ArgType::String)], vec![Arg::new("asset", ArgType::Hash)], CommandHandler::Async(async_handler!(balance::<S>))))?;
+    command_manager.add_command(Command::new("peers", "Print the peers shared by the RPC server", CommandHandler::Async(async_handler!(peers::<S>))))?;
+    command_manager.add_command(Command::with_required_arguments("rewind", "Rewind the chain by N blocks", vec![Arg::new("count", ArgType::Number)], CommandHandler::Async(async_handler!(rewind::<S>))))?;
+    command_manager.add_command(Command::new("mempool", "Print the mempool summary with its fee rate histogram", CommandHandler::Async(async_handler!(mempool::<S>))))?;
 
 
     // Don't keep the lock for ever
-    let (p2p, getwork) = {
+    let (p2p, getwork, stratum) = {
         let p2p: Option<Arc<P2pServer<S>>> = match blockchain.get_p2p().read().await.as_ref() {
             Some(p2p) => Some(p2p.clone()),
             None => None
         };
-        let getwork: Option<SharedGetWorkServer<S>> = match blockchain.get_rpc().read().await.as_ref() {
-            Some(rpc) => rpc.getwork_server().clone(),
-            None => None
+        let (getwork, stratum): (Option<SharedGetWorkServer<S>>, Option<SharedStratumServer<S>>) = match blockchain.get_rpc().read().await.as_ref() {
+            Some(rpc) => (rpc.getwork_server().clone(), rpc.stratum_server().clone()),
+            None => (None, None)
         };
-        (p2p, getwork)
+        (p2p, getwork, stratum)
     };
 
     let rpc = {
@@ -230,10 +411,13 @@ async fn run_prompt<S: Storage>(prompt: ShareablePrompt, blockchain: Arc<Blockch
         };
 
         trace!("Retrieving miners count");
-        let miners = match &getwork {
+        let mut miners = match &getwork {
             Some(getwork) => getwork.count_miners().await,
             None => 0
         };
+        if let Some(stratum) = &stratum {
+            miners += stratum.count_miners().await;
+        }
 
         trace!("Retrieving mempool size");
         let mempool = {
@@ -242,7 +426,7 @@ async fn run_prompt<S: Storage>(prompt: ShareablePrompt, blockchain: Arc<Blockch
         };
 
         trace!("Retrieving network hashrate");
-        let network_hashrate: f64 = (blockchain.get_difficulty().await / BLOCK_TIME).into();
+        let network_hashrate: f64 = blockchain.get_chain_params().estimate_hashrate(blockchain.get_difficulty().await).into();
 
         trace!("Building prompt message");
         Ok( 
@@ -317,53 +501,33 @@ fn build_prompt_message(prompt: &ShareablePrompt, topoheight: u64, median_topohe
     )
 }
 
-async fn verify_chain<S: Storage>(manager: &CommandManager, mut args: ArgumentManager) -> Result<(), CommandError> {
+async fn validate_supply<S: Storage>(manager: &CommandManager, mut args: ArgumentManager) -> Result<(), CommandError> {
     let context = manager.get_context().lock()?;
     let blockchain: &Arc<Blockchain<S>> = context.get()?;
 
-    let storage = blockchain.get_storage().read().await;
-    let mut pruned_topoheight = storage.get_pruned_topoheight().await.context("Error on pruned topoheight")?.unwrap_or(0);
-    let mut expected_supply = if pruned_topoheight > 0 {
-        let supply = storage.get_supply_at_topo_height(pruned_topoheight).await.context("Error while retrieving starting expected supply")?;
-        pruned_topoheight += 1;
-        supply
+    let start_topoheight = if args.has_argument("start_topoheight") {
+        Some(args.get_value("start_topoheight")?.to_number()?)
     } else {
-        0
+        None
     };
-
-    let topoheight = if args.has_argument("topoheight") {
-        args.get_value("topoheight")?.to_number()?
+    let end_topoheight = if args.has_argument("end_topoheight") {
+        Some(args.get_value("end_topoheight")?.to_number()?)
     } else {
-        blockchain.get_topo_height()
+        None
     };
 
-    for topo in pruned_topoheight..=topoheight {
-        let hash_at_topo = storage.get_hash_at_topo_height(topo).await.context("Error while retrieving hash at topo")?;
-        let block_reward = if pruned_topoheight == 0 || topo - pruned_topoheight > STABLE_LIMIT {
-            let block_reward = blockchain.get_block_reward(&*storage, &hash_at_topo, expected_supply, topo).await.context("Error while calculating block reward")?;
-            let expected_block_reward = storage.get_block_reward_at_topo_height(topo).context("Error while retrieving block reward")?;
-            // Verify the saved block reward
-            if block_reward != expected_block_reward {
-                manager.error(format!("Block reward saved is incorrect for {} at topoheight {}, got {} while expecting {}", hash_at_topo, topo, format_xelis(block_reward), format_xelis(expected_block_reward)));
-                return Ok(())
-            }
-            block_reward
-        } else {
-            // We are too near from the pruned topoheight, as we don't know previous blocks we can't verify if block was side block or not for rewards
-            // Let's trust its stored reward
-            storage.get_block_reward_at_topo_height(topo).context("Error while retrieving block reward for pruned topo")?
-        };
-
-        let supply = storage.get_supply_at_topo_height(topo).await.context("Error while retrieving supply at topoheight")?;
-        expected_supply += block_reward;
+    let storage = blockchain.get_storage().read().await;
+    let result = blockchain.validate_supply(&storage, start_topoheight, end_topoheight).await.context("Error while validating supply")?;
+    for divergence in result.divergences.iter() {
+        manager.error(format!("Divergence for block {} at topoheight {}: block reward {} (expected {}), supply {} (expected {})", divergence.block_hash, divergence.topoheight, format_xelis(divergence.block_reward), format_xelis(divergence.expected_block_reward), format_xelis(divergence.supply), format_xelis(divergence.expected_supply)));
+    }
 
-        // Verify the supply at block
-        if supply != expected_supply {
-            manager.error(format!("Error for block {} at topoheight {}, expected {} found {}", hash_at_topo, topo, expected_supply, supply));
-            return Ok(())
-        }
+    manager.message(format!("Supply: {}, burned: {}, circulating: {} (topoheight {} to {})", format_xelis(result.supply), format_xelis(result.burned_supply), format_xelis(result.circulating_supply), result.start_topoheight, result.end_topoheight));
+    if result.divergences.is_empty() {
+        manager.message("Supply is valid");
+    } else {
+        manager.error(format!("{} divergences found", result.divergences.len()));
     }
-    manager.message("Supply is valid");
 
     Ok(())
 }
@@ -398,16 +562,26 @@ async fn list_miners<S: Storage>(manager: &CommandManager, _: ArgumentManager) -
     let context = manager.get_context().lock()?;
     let blockchain: &Arc<Blockchain<S>> = context.get()?;
     match blockchain.get_rpc().read().await.as_ref() {
-        Some(rpc) => match rpc.getwork_server() {
-            Some(getwork) => {
-                let miners = getwork.get_miners().lock().await;
-                manager.message(format!("Miners ({}):", miners.len()));
+        Some(rpc) => {
+            match rpc.getwork_server() {
+                Some(getwork) => {
+                    let miners = getwork.get_miners().lock().await;
+                    manager.message(format!("Miners ({}):", miners.len()));
+                    for miner in miners.values() {
+                        manager.message(format!("- {}", miner));
+                    }
+                },
+                None => {
+                    manager.message("No miners running!");
+                }
+            }
+
+            if let Some(stratum) = rpc.stratum_server() {
+                let miners = stratum.get_miners().lock().await;
+                manager.message(format!("Stratum miners ({}):", miners.len()));
                 for miner in miners.values() {
                     manager.message(format!("- {}", miner));
                 }
-            },
-            None => {
-                manager.message("No miners running!");
             }
         },
         None => {
@@ -613,12 +787,12 @@ async fn status<S: Storage>(manager: &CommandManager, _: ArgumentManager) -> Res
     manager.message(format!("Stable Topo Height: {}", stable_topoheight));
     manager.message(format!("Topo Height: {}", topoheight));
     manager.message(format!("Difficulty: {}", format_difficulty(difficulty)));
-    manager.message(format!("Network Hashrate: {}", format_hashrate((difficulty / BLOCK_TIME).into())));
+    manager.message(format!("Network Hashrate: {}", format_hashrate(blockchain.get_chain_params().estimate_hashrate(difficulty).into())));
     manager.message(format!("Top block hash: {}", top_block_hash));
     manager.message(format!("Average Block Time: {:.2}s", avg_block_time as f64 / MILLIS_PER_SECOND as f64));
-    manager.message(format!("Target Block Time: {:.2}s", BLOCK_TIME_MILLIS as f64 / MILLIS_PER_SECOND as f64));
+    manager.message(format!("Target Block Time: {:.2}s", blockchain.get_chain_params().block_time_millis as f64 / MILLIS_PER_SECOND as f64));
     manager.message(format!("Current Supply: {} XELIS", format_xelis(supply)));
-    manager.message(format!("Current Block Reward: {} XELIS", format_xelis(get_block_reward(supply))));
+    manager.message(format!("Current Block Reward: {} XELIS", format_xelis(get_block_reward(supply, blockchain.get_chain_params()))));
     manager.message(format!("Stored accounts/transactions/blocks/assets: {}/{}/{}/{}", accounts_count, transactions_count, blocks_count, assets));
 
     manager.message(format!("Tips ({}):", tips.len()));
@@ -695,13 +869,24 @@ async fn clear_p2p_peerlist<S: Storage>(manager: &CommandManager, _: ArgumentMan
 async fn clear_caches<S: Storage>(manager: &CommandManager, _: ArgumentManager) -> Result<(), CommandError> {
     let context = manager.get_context().lock()?;
     let blockchain: &Arc<Blockchain<S>> = context.get()?;
-    let mut storage = blockchain.get_storage().write().await;
 
-    storage.clear_caches().await.context("Error while clearing caches")?;
+    blockchain.clear_caches().await.context("Error while clearing caches")?;
     manager.message("Caches cleared");
     Ok(())
 }
 
+async fn promote<S: Storage>(manager: &CommandManager, _: ArgumentManager) -> Result<(), CommandError> {
+    let context = manager.get_context().lock()?;
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+
+    if blockchain.promote().await {
+        manager.message("Node promoted to full mode");
+    } else {
+        manager.warn("Node is not in standby mode");
+    }
+    Ok(())
+}
+
 async fn blacklist<S: Storage>(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
     let context = manager.get_context().lock()?;
     let blockchain: &Arc<Blockchain<S>> = context.get()?;
@@ -881,4 +1066,107 @@ async fn add_peer<S: Storage>(manager: &CommandManager, mut args: ArgumentManage
     };
 
     Ok(())
-}
\ No newline at end of file
+}
+
+async fn export_snapshot<S: Storage>(manager: &CommandManager, mut args: ArgumentManager) -> Result<(), CommandError> {
+    let path = args.get_value("path")?.to_string_value()?;
+    let signing_key = if args.has_argument("signing_key") {
+        let key_path = args.get_value("signing_key")?.to_string_value()?;
+        Some(snapshot::load_signing_key(Path::new(&key_path)).context("Error while loading signing key")?)
+    } else {
+        manager.warn("No signing key provided, the snapshot will not be signed");
+        None
+    };
+
+    let context = manager.get_context().lock()?;
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    manager.message(format!("Exporting snapshot to {}...", path));
+    let manifest = snapshot::export_snapshot(blockchain, Path::new(&path), signing_key.as_ref()).await.context("Error while exporting snapshot")?;
+    manager.message(format!("Snapshot exported at topoheight {} ({} bytes)", manifest.topoheight, manifest.size));
+
+    Ok(())
+}
+
+async fn generate_snapshot_key<S: Storage>(manager: &CommandManager, mut args: ArgumentManager) -> Result<(), CommandError> {
+    let path = args.get_value("path")?.to_string_value()?;
+    let public_key = snapshot::generate_signing_key(Path::new(&path)).context("Error while generating signing key")?;
+    manager.message(format!("Signing key saved to {}", path));
+    manager.message(format!("Publisher public key to trust: {}", hex::encode(public_key.as_bytes())));
+
+    Ok(())
+}
+
+// Call a RPC method of the daemon and print its result
+async fn call_rpc_method<S: Storage, P: Serialize>(manager: &CommandManager, method: &str, params: P) -> Result<(), CommandError> {
+    let context = manager.get_context().lock()?;
+    let handler: &RPCHandler<Arc<Blockchain<S>>> = context.get()?;
+    let request = RpcRequest {
+        jsonrpc: JSON_RPC_VERSION.to_owned(),
+        id: Some(Id::Number(0)),
+        method: method.to_owned(),
+        params: Some(json!(params))
+    };
+
+    let response = handler.execute_method(&context, request).await
+        .map_err(|e| anyhow::anyhow!("Error while calling {}: {}", method, e))?;
+    let result = response.and_then(|mut response| response.get_mut("result").map(Value::take))
+        .unwrap_or(Value::Null);
+    manager.message(serde_json::to_string_pretty(&result).context("Error while serializing")?);
+
+    Ok(())
+}
+
+async fn block<S: Storage>(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let value = arguments.get_value("hash_or_topoheight")?.to_string_value()?;
+    if let Ok(topoheight) = value.parse::<u64>() {
+        call_rpc_method::<S, _>(manager, "get_block_at_topoheight", GetBlockAtTopoHeightParams {
+            topoheight,
+            include_txs: false
+        }).await
+    } else {
+        let hash = Hash::from_hex(value).map_err(|_| CommandError::InvalidArgument("Expected a block hash or a topoheight".into()))?;
+        call_rpc_method::<S, _>(manager, "get_block_by_hash", GetBlockByHashParams {
+            hash: Cow::Owned(hash),
+            include_txs: false
+        }).await
+    }
+}
+
+async fn tx<S: Storage>(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let hash = arguments.get_value("hash")?.to_hash()?;
+    call_rpc_method::<S, _>(manager, "get_transaction", GetTransactionParams {
+        hash: Cow::Owned(hash)
+    }).await
+}
+
+async fn balance<S: Storage>(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let value = arguments.get_value("address")?.to_string_value()?;
+    let address = Address::from_string(&value).context("Invalid address")?;
+    let asset = if arguments.has_argument("asset") {
+        arguments.get_value("asset")?.to_hash()?
+    } else {
+        XELIS_ASSET
+    };
+
+    call_rpc_method::<S, _>(manager, "get_balance", GetBalanceParams {
+        address: Cow::Owned(address),
+        asset: Cow::Owned(asset)
+    }).await
+}
+
+async fn peers<S: Storage>(manager: &CommandManager, _: ArgumentManager) -> Result<(), CommandError> {
+    call_rpc_method::<S, _>(manager, "get_peers", Value::Null).await
+}
+
+async fn rewind<S: Storage>(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let count = arguments.get_value("count")?.to_number()?;
+    info!("Trying to rewind {} blocks from chain...", count);
+    call_rpc_method::<S, _>(manager, "admin.rewind_chain", AdminRewindChainParams {
+        count,
+        until_stable_height: false
+    }).await
+}
+
+async fn mempool<S: Storage>(manager: &CommandManager, _: ArgumentManager) -> Result<(), CommandError> {
+    call_rpc_method::<S, _>(manager, "get_mempool_summary", Value::Null).await
+}
@@ -23,7 +23,8 @@ use crate::core::{
         DifficultyProvider,
         MerkleHashProvider,
         PrunedTopoheightProvider,
-        Storage
+        Storage,
+        WorkScoreProvider
     }
 };
 use log::{debug, trace};
@@ -135,7 +136,7 @@ impl<'a, S: Storage> ChainValidator<'a, S> {
         let (base, base_height) = self.blockchain.find_common_base(self, header.get_tips()).await?;
 
         // Find the cumulative difficulty for this block
-        let (_, cumulative_difficulty) = self.blockchain.find_tip_work_score(self, &hash, &base, base_height).await?;
+        let cumulative_difficulty = self.blockchain.find_tip_work_score(self, &hash, &base, base_height).await?;
 
         // Store the block in both maps
         // One is for blocks at height and the other is for the block data
@@ -225,6 +226,19 @@ impl<S: Storage> DifficultyProvider for ChainValidator<'_, S> {
     }
 }
 
+// Blocks are ordered differently than in the storage until the chain is accepted
+// so scores are never loaded from or saved to the persisted index
+#[async_trait]
+impl<S: Storage> WorkScoreProvider for ChainValidator<'_, S> {
+    async fn get_work_score(&self, _: &Hash, _: &Hash, _: u64) -> Result<Option<CumulativeDifficulty>, BlockchainError> {
+        Ok(None)
+    }
+
+    async fn set_work_score(&self, _: &Hash, _: &Hash, _: u64, _: &CumulativeDifficulty) -> Result<(), BlockchainError> {
+        Ok(())
+    }
+}
+
 #[async_trait]
 impl<S: Storage> DagOrderProvider for ChainValidator<'_, S> {
     async fn get_topo_height_for_hash(&self, hash: &Hash) -> Result<u64, BlockchainError> {
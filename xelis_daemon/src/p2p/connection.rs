@@ -20,11 +20,8 @@ use std::{
 use human_bytes::human_bytes;
 use humantime::format_duration;
 use tokio::{
-    io::{AsyncReadExt, AsyncWriteExt},
-    net::{
-        tcp::{OwnedReadHalf, OwnedWriteHalf},
-        TcpStream
-    },
+    io::{split, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::TcpStream,
     sync::Mutex,
     time::timeout
 };
@@ -37,6 +34,10 @@ use log::{debug, error, trace, warn};
 
 type P2pResult<T> = Result<T, P2pError>;
 
+// Halves of the stream, which can be a plain TCP stream or a TLS session over it
+type ReadHalf = Box<dyn AsyncRead + Send + Unpin>;
+type WriteHalf = Box<dyn AsyncWrite + Send + Unpin>;
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum State {
     Pending, // connection is new, no handshake received
@@ -51,9 +52,11 @@ pub struct Connection {
     // State of the connection
     state: State,
     // write to stream
-    write: Mutex<OwnedWriteHalf>,
+    write: Mutex<WriteHalf>,
     // read from stream
-    read: Mutex<OwnedReadHalf>,
+    read: Mutex<ReadHalf>,
+    // if the stream is wrapped in a TLS session
+    tls: bool,
     // TCP Address
     addr: SocketAddr,
     // total bytes read
@@ -62,6 +65,10 @@ pub struct Connection {
     bytes_out: AtomicUsize,
     // total bytes sent using current key
     bytes_out_key: AtomicUsize,
+    // total packets read
+    packets_in: AtomicUsize,
+    // total packets sent
+    packets_out: AtomicUsize,
     // when the connection was established
     connected_on: TimestampSeconds,
     // if Connection#close() is called, close is set to true
@@ -80,16 +87,29 @@ const ROTATE_EVERY_N_BYTES: usize = 1024 * 1024 * 1024;
 impl Connection {
     pub fn new(stream: TcpStream, addr: SocketAddr, out: bool) -> Self {
         let (read, write) = stream.into_split();
+        Self::with_halves(Box::new(read), Box::new(write), addr, out, false)
+    }
+
+    // Create a connection using a TLS session
+    pub fn new_tls<T: AsyncRead + AsyncWrite + Send + Unpin + 'static>(stream: T, addr: SocketAddr, out: bool) -> Self {
+        let (read, write) = split(stream);
+        Self::with_halves(Box::new(read), Box::new(write), addr, out, true)
+    }
+
+    fn with_halves(read: ReadHalf, write: WriteHalf, addr: SocketAddr, out: bool, tls: bool) -> Self {
         Self {
             out,
             state: State::Pending,
             write: Mutex::new(write),
             read: Mutex::new(read),
+            tls,
             addr,
             connected_on: get_current_time_in_seconds(),
             bytes_in: AtomicUsize::new(0),
             bytes_out: AtomicUsize::new(0),
             bytes_out_key: AtomicUsize::new(0),
+            packets_in: AtomicUsize::new(0),
+            packets_out: AtomicUsize::new(0),
             closed: AtomicBool::new(false),
             rotate_key_in: AtomicUsize::new(0),
             rotate_key_out: AtomicUsize::new(0),
@@ -153,6 +173,11 @@ impl Connection {
         self.out
     }
 
+    // Verify if the connection is wrapped in a TLS session
+    pub fn is_tls(&self) -> bool {
+        self.tls
+    }
+
     // This will send to the peer a packet to rotate the key
     async fn rotate_key_packet(&self) -> P2pResult<Bytes> {
         trace!("rotating our encryption key for peer {}", self.get_address());
@@ -198,7 +223,7 @@ impl Connection {
     // This function will send the packet to the peer without flushing the stream
     // Packet length is ALWAYS sent in raw (not encrypted)
     // Otherwise, we can't know how much bytes to read for each ciphertext/packet
-    async fn send_packet_bytes_internal(&self, stream: &mut OwnedWriteHalf, packet: &[u8]) -> P2pResult<()> {
+    async fn send_packet_bytes_internal(&self, stream: &mut WriteHalf, packet: &[u8]) -> P2pResult<()> {
         let packet_len = packet.len() as u32;
         stream.write_all(&packet_len.to_be_bytes()).await?;
         stream.write_all(packet).await?;
@@ -213,6 +238,7 @@ impl Connection {
 
         // Count the bytes sent
         self.bytes_out.fetch_add(packet.len(), Ordering::Relaxed);
+        self.packets_out.fetch_add(1, Ordering::Relaxed);
 
         if self.encryption.is_write_ready().await {
             let buffer = self.encryption.encrypt_packet(packet).await?;
@@ -271,12 +297,13 @@ impl Connection {
     // This will read the packet size and then read the packet bytes
     pub async fn read_packet(&self, buf: &mut [u8], max_size: u32) -> P2pResult<Packet<'static>> {
         let bytes = self.read_packet_bytes(buf, max_size).await?;
+        self.packets_in.fetch_add(1, Ordering::Relaxed);
         self.read_packet_from_bytes(&bytes).await
     }
 
     // Read the packet size, this is always sent in raw (not encrypted)
     // And packet size must be a u32 in big endian
-    async fn read_packet_size(&self, stream: &mut OwnedReadHalf, buf: &mut [u8], max_usize: u32) -> P2pResult<u32> {
+    async fn read_packet_size(&self, stream: &mut ReadHalf, buf: &mut [u8], max_usize: u32) -> P2pResult<u32> {
         let read = self.read_bytes_from_stream(stream, &mut buf[0..4]).await?;
         if read != 4 {
             if self.get_state() == State::Success {
@@ -300,7 +327,7 @@ impl Connection {
 
     // Read all bytes until the the buffer is full with the requested size
     // This support fragmented packets and encryption
-    async fn read_all_bytes(&self, stream: &mut OwnedReadHalf, buf: &mut [u8], mut left: u32) -> P2pResult<Vec<u8>> {
+    async fn read_all_bytes(&self, stream: &mut ReadHalf, buf: &mut [u8], mut left: u32) -> P2pResult<Vec<u8>> {
         let buf_size = buf.len() as u32;
         let mut bytes = Vec::new();
         while left > 0 {
@@ -326,7 +353,7 @@ impl Connection {
     // this function will wait until something is sent to the socket if it's in blocking mode
     // this return the size of data read & set in the buffer.
     // used to only lock one time the stream and read on it
-    async fn read_bytes_from_stream(&self, stream: &mut OwnedReadHalf, buf: &mut [u8]) -> P2pResult<usize> {
+    async fn read_bytes_from_stream(&self, stream: &mut ReadHalf, buf: &mut [u8]) -> P2pResult<usize> {
         let mut read = 0;
         let buf_len = buf.len();
         // Packet may have been fragmented, try to read it completely
@@ -384,6 +411,16 @@ impl Connection {
         self.bytes_in.load(Ordering::Relaxed)
     }
 
+    // Get the total packets sent
+    pub fn packets_out(&self) -> usize {
+        self.packets_out.load(Ordering::Relaxed)
+    }
+
+    // Get the total packets read
+    pub fn packets_in(&self) -> usize {
+        self.packets_in.load(Ordering::Relaxed)
+    }
+
     // Get the key rotation in
     pub fn key_rotation_in(&self) -> usize {
         self.rotate_key_in.load(Ordering::Relaxed)
@@ -413,6 +450,6 @@ impl Connection {
 
 impl Display for Connection {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::result::Result<(), Error> {
-        write!(f, "Connection[state: {:?}, peer: {}, read: {}, sent: {}, key rotation (in/out): ({}/{}), connected since: {}, closed: {}]", self.state, self.get_address(), human_bytes(self.bytes_in() as f64), human_bytes(self.bytes_out() as f64), self.key_rotation_in(), self.key_rotation_out(), self.get_human_uptime(), self.is_closed())
+        write!(f, "Connection[state: {:?}, peer: {}, read: {}, sent: {}, key rotation (in/out): ({}/{}), connected since: {}, closed: {}, tls: {}]", self.state, self.get_address(), human_bytes(self.bytes_in() as f64), human_bytes(self.bytes_out() as f64), self.key_rotation_in(), self.key_rotation_out(), self.get_human_uptime(), self.is_closed(), self.is_tls())
     }
 }
\ No newline at end of file
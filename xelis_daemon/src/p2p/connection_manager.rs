@@ -0,0 +1,118 @@
+use std::net::SocketAddr;
+use indexmap::IndexMap;
+use log::debug;
+use xelis_common::time::TimestampMillis;
+use crate::config::{
+    P2P_ANCHOR_RETRY_BASE_DELAY_MILLIS,
+    P2P_ANCHOR_RETRY_MAX_DELAY_MILLIS
+};
+
+// Reconnection state of an anchor
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Anchor {
+    // connection attempts done since it got disconnected
+    failures: u32,
+    // time at which we can retry to connect to it
+    next_attempt: TimestampMillis
+}
+
+impl Anchor {
+    pub fn get_failures(&self) -> u32 {
+        self.failures
+    }
+
+    pub fn get_next_attempt(&self) -> TimestampMillis {
+        self.next_attempt
+    }
+}
+
+// Manage the connections slots for each direction
+// and the anchors, which are priority nodes we always reconnect to
+// An anchor connection is retried with an exponential backoff until it is connected again
+pub struct ConnectionManager {
+    anchors: IndexMap<SocketAddr, Anchor>,
+    // max outgoing connections done by us
+    max_outbound: usize,
+    // max incoming connections accepted
+    max_inbound: usize
+}
+
+impl ConnectionManager {
+    pub fn new(max_outbound: usize, max_inbound: usize) -> Self {
+        Self {
+            anchors: IndexMap::new(),
+            max_outbound,
+            max_inbound
+        }
+    }
+
+    pub fn get_max_outbound(&self) -> usize {
+        self.max_outbound
+    }
+
+    pub fn set_max_outbound(&mut self, value: usize) {
+        self.max_outbound = value;
+    }
+
+    pub fn get_max_inbound(&self) -> usize {
+        self.max_inbound
+    }
+
+    pub fn set_max_inbound(&mut self, value: usize) {
+        self.max_inbound = value;
+    }
+
+    // Returns false if it was already an anchor
+    pub fn add_anchor(&mut self, addr: SocketAddr) -> bool {
+        if self.anchors.contains_key(&addr) {
+            return false
+        }
+
+        debug!("Adding {} as anchor", addr);
+        self.anchors.insert(addr, Anchor::default());
+        true
+    }
+
+    // Returns false if it was not an anchor
+    pub fn remove_anchor(&mut self, addr: &SocketAddr) -> bool {
+        debug!("Removing {} from anchors", addr);
+        self.anchors.shift_remove(addr).is_some()
+    }
+
+    pub fn is_anchor(&self, addr: &SocketAddr) -> bool {
+        self.anchors.contains_key(addr)
+    }
+
+    pub fn get_anchors(&self) -> &IndexMap<SocketAddr, Anchor> {
+        &self.anchors
+    }
+
+    // Anchor is connected, reset its backoff
+    pub fn on_anchor_connected(&mut self, addr: &SocketAddr) {
+        if let Some(anchor) = self.anchors.get_mut(addr) {
+            anchor.failures = 0;
+            anchor.next_attempt = 0;
+        }
+    }
+
+    // Returns true if we should try to connect to the anchor now
+    // The next attempt is delayed exponentially, up to the max delay
+    pub fn should_retry_anchor(&mut self, addr: &SocketAddr, now: TimestampMillis) -> bool {
+        let Some(anchor) = self.anchors.get_mut(addr) else {
+            return false
+        };
+
+        if anchor.next_attempt > now {
+            return false
+        }
+
+        let delay = 1u64.checked_shl(anchor.failures)
+            .unwrap_or(u64::MAX)
+            .saturating_mul(P2P_ANCHOR_RETRY_BASE_DELAY_MILLIS)
+            .min(P2P_ANCHOR_RETRY_MAX_DELAY_MILLIS);
+
+        anchor.failures = anchor.failures.saturating_add(1);
+        anchor.next_attempt = now + delay;
+        true
+    }
+}
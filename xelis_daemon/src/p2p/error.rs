@@ -27,6 +27,8 @@ use std::{
 use thiserror::Error;
 use super::{
     encryption::EncryptionError,
+    proxy::ProxyError,
+    tls::TlsError,
     packet::{
         bootstrap_chain::StepKind,
         object::ObjectRequest,
@@ -97,6 +99,10 @@ pub enum P2pError {
     Disconnected,
     #[error("Invalid handshake")]
     InvalidHandshake,
+    #[error("No common protocol version with peer supporting versions {} to {}", _0, _1)]
+    IncompatibleProtocolVersion(u16, u16),
+    #[error("Packet #{} requires protocol version {} but version {} was negotiated", _0, _1, _2)]
+    UnsupportedPacketVersion(u8, u16, u16),
     #[error("Expected Handshake packet")]
     ExpectedHandshake,
     #[error("Invalid peer address, {}", _0)]
@@ -163,6 +169,20 @@ pub enum P2pError {
     PeerInvalidPeerListCountdown(u64),
     #[error("Peer sent us a ping packet faster than protocol rules")]
     PeerInvalidPingCoutdown,
+    #[error("Peer sent us a peer exchange request faster than protocol rules, expected to wait {} seconds more", _0)]
+    PeerInvalidPeerExchangeCountdown(u64),
+    #[error("Peer sent us a latency ping faster than protocol rules, expected to wait {} seconds more", _0)]
+    PeerInvalidLatencyPingCountdown(u64),
+    #[error("No peer available to sync the chain")]
+    NoSyncPeerAvailable,
+    #[error("Received a unrequested peer exchange response")]
+    UnrequestedPeerExchangeResponse,
+    #[error("Txs for block {} are already requested", _0)]
+    TxsAlreadyRequested(Hash),
+    #[error("Received a unrequested txs response for block {}", _0)]
+    UnrequestedTxsResponse(Hash),
+    #[error("Invalid txs response for block {}", _0)]
+    InvalidTxsResponse(Hash),
     #[error(transparent)]
     BlockchainError(#[from] Box<BlockchainError>),
     #[error("Invalid content in peerlist shared")]
@@ -175,6 +195,10 @@ pub enum P2pError {
     SemaphoreAcquireError(#[from] AcquireError),
     #[error(transparent)]
     EncryptionError(#[from] EncryptionError),
+    #[error(transparent)]
+    ProxyError(#[from] ProxyError),
+    #[error(transparent)]
+    TlsError(#[from] TlsError),
 }
 
 impl From<BlockchainError> for P2pError {
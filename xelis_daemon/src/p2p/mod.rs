@@ -4,6 +4,13 @@ pub mod error;
 pub mod packet;
 pub mod peer_list;
 pub mod chain_validator;
+pub mod rate_limiter;
+pub mod proxy;
+pub mod network_view;
+pub mod upnp;
+pub mod connection_manager;
+pub mod tls;
+mod sync_scheduler;
 mod tracker;
 mod encryption;
 
@@ -19,6 +26,7 @@ use xelis_common::{
         PeerPeerDisconnectedEvent
     },
     block::{Block, BlockHeader},
+    capabilities::Capabilities,
     config::{TIPS_LIMIT, VERSION},
     crypto::{Hash, Hashable},
     difficulty::CumulativeDifficulty,
@@ -34,14 +42,16 @@ use xelis_common::{
 };
 use crate::{
     config::{
-        get_genesis_block_hash,
         get_seed_nodes,
         CHAIN_SYNC_DEFAULT_RESPONSE_BLOCKS, CHAIN_SYNC_DELAY, CHAIN_SYNC_REQUEST_EXPONENTIAL_INDEX_START,
         CHAIN_SYNC_REQUEST_MAX_BLOCKS, CHAIN_SYNC_RESPONSE_MAX_BLOCKS, CHAIN_SYNC_RESPONSE_MIN_BLOCKS,
         CHAIN_SYNC_TOP_BLOCKS, MILLIS_PER_SECOND, NETWORK_ID, P2P_AUTO_CONNECT_PRIORITY_NODES_DELAY,
         P2P_EXTEND_PEERLIST_DELAY, P2P_PING_DELAY, P2P_PING_PEER_LIST_DELAY, P2P_PING_PEER_LIST_LIMIT,
         PEER_FAIL_LIMIT, PEER_MAX_PACKET_SIZE, PEER_TIMEOUT_INIT_CONNECTION, PEER_TIMEOUT_INIT_OUTGOING_CONNECTION,
-        PRUNE_SAFETY_LIMIT, STABLE_LIMIT, P2P_PING_TIMEOUT, P2P_HEARTBEAT_INTERVAL, PEER_SEND_BYTES_TIMEOUT
+        PRUNE_SAFETY_LIMIT, STABLE_LIMIT, P2P_PING_TIMEOUT, P2P_HEARTBEAT_INTERVAL, PEER_SEND_BYTES_TIMEOUT,
+        P2P_PEER_EXCHANGE_DELAY, P2P_PEER_EXCHANGE_LIMIT, P2P_TXS_BATCH_INTERVAL_MILLIS, P2P_TXS_BATCH_MAX_SIZE,
+        P2P_UPNP_LEASE_DURATION, P2P_UPNP_TIMEOUT_MILLIS, CHAIN_SYNC_MAX_PEERS, P2P_MIN_PROTOCOL_VERSION,
+        P2P_MAX_PROTOCOL_VERSION, P2P_LATENCY_PING_DELAY, P2P_ANCHOR_CHECK_DELAY
     },
     core::{
         blockchain::Blockchain,
@@ -63,7 +73,13 @@ use crate::{
                 NotifyInventoryRequest,
                 NotifyInventoryResponse,
                 NOTIFY_MAX_LEN
-            }
+            },
+            peer_exchange::{
+                PeerExchangeRequest,
+                PeerExchangeResponse
+            },
+            txs::{TxsResponse, TxsPropagation},
+            latency::LatencyPing
         },
         tracker::ResponseBlocker
     },
@@ -82,7 +98,12 @@ use self::{
     },
     peer::{Peer, TaskState, Rx},
     peer_list::{PeerList, SharedPeerList},
-    tracker::{ObjectTracker, SharedObjectTracker}
+    rate_limiter::{RateLimits, RateLimitResult},
+    proxy::Socks5Proxy,
+    upnp::PortMapping,
+    tracker::{ObjectTracker, SharedObjectTracker},
+    connection_manager::{Anchor, ConnectionManager},
+    tls::P2pTls
 };
 use tokio::{
     io::AsyncWriteExt,
@@ -143,6 +164,8 @@ pub struct P2pServer<S: Storage> {
     blocks_propagation_queue: Mutex<LruCache<Hash, ()>>,
     // Sender for the blocks processing task to have a ordered queue
     blocks_processor: Sender<(Arc<Peer>, BlockHeader, Hash)>,
+    // txs hashes waiting to be broadcasted in one batch
+    txs_broadcast_queue: Mutex<IndexSet<Hash>>,
     // allow fast syncing (only balances / assets / Smart Contracts changes)
     // without syncing the history
     allow_fast_sync_mode: bool,
@@ -165,12 +188,27 @@ pub struct P2pServer<S: Storage> {
     outgoing_connections_disabled: AtomicBool,
     // Are we syncing the chain with another peer
     is_syncing: AtomicBool,
+    // Limits applied on packets received from each peer
+    rate_limits: RateLimits,
+    // SOCKS5 proxy used for all outgoing connections
+    proxy: Option<Socks5Proxy>,
+    // Do we listen for incoming connections
+    // If disabled, our listening port is never sent to others peers
+    inbound_connections_disabled: bool,
+    // Request a port mapping on the local gateway using UPnP
+    upnp_enabled: bool,
+    // Port mapping requested, removed at shutdown
+    upnp_mapping: Mutex<Option<PortMapping>>,
+    // Connections slots per direction and anchors to reconnect to
+    connection_manager: Mutex<ConnectionManager>,
+    // Optional TLS layer for incoming and/or outgoing connections
+    tls: Option<P2pTls>,
     // Exit channel to notify all tasks to stop
     exit_sender: broadcast::Sender<()>
 }
 
 impl<S: Storage> P2pServer<S> {
-    pub fn new(concurrency: usize, dir_path: Option<String>, tag: Option<String>, max_peers: usize, bind_address: String, blockchain: Arc<Blockchain<S>>, use_peerlist: bool, exclusive_nodes: Vec<SocketAddr>, allow_fast_sync_mode: bool, allow_boost_sync_mode: bool, max_chain_response_size: Option<usize>, sharable: bool, disable_outgoing_connections: bool) -> Result<Arc<Self>, P2pError> {
+    pub fn new(concurrency: usize, dir_path: Option<String>, tag: Option<String>, max_peers: usize, bind_address: String, blockchain: Arc<Blockchain<S>>, use_peerlist: bool, exclusive_nodes: Vec<SocketAddr>, allow_fast_sync_mode: bool, allow_boost_sync_mode: bool, max_chain_response_size: Option<usize>, sharable: bool, disable_outgoing_connections: bool, rate_limits: RateLimits, proxy: Option<String>, disable_inbound_connections: bool, enable_upnp: bool, max_outbound_peers: Option<usize>, max_inbound_peers: Option<usize>, tls: Option<P2pTls>) -> Result<Arc<Self>, P2pError> {
         if tag.as_ref().is_some_and(|tag| tag.len() == 0 || tag.len() > 16) {
             return Err(P2pError::InvalidTag);
         }
@@ -183,6 +221,20 @@ impl<S: Storage> P2pServer<S> {
             return Err(P2pError::InvalidMaxPeers);
         }
 
+        let proxy = proxy.map(|proxy| Socks5Proxy::parse(&proxy)).transpose()?;
+        if let Some(proxy) = proxy.as_ref() {
+            info!("All outgoing P2P connections will use the proxy {}", proxy);
+            if !disable_inbound_connections {
+                warn!("Inbound connections are enabled while using a proxy, your IP address may be discovered by others peers");
+            }
+        }
+
+        if let Some(tls) = tls.as_ref() {
+            if tls.get_bind_address().is_some() && disable_inbound_connections {
+                warn!("Inbound connections are disabled, TLS listener will not be started");
+            }
+        }
+
         // set channel to communicate with listener thread
         let mut rng = rand::thread_rng();
         let peer_id: u64 = rng.gen(); // generate a random peer id for network
@@ -211,12 +263,21 @@ impl<S: Storage> P2pServer<S> {
             is_running: AtomicBool::new(true),
             blocks_propagation_queue: Mutex::new(LruCache::new(NonZeroUsize::new(STABLE_LIMIT as usize * TIPS_LIMIT).unwrap())),
             blocks_processor,
+            txs_broadcast_queue: Mutex::new(IndexSet::new()),
             allow_fast_sync_mode,
             allow_boost_sync_mode,
             max_chain_response_size: max_chain_response_size.unwrap_or(CHAIN_SYNC_DEFAULT_RESPONSE_BLOCKS),
             exclusive_nodes: IndexSet::from_iter(exclusive_nodes.into_iter()),
-            sharable,
+            // we can't be shared if nobody can connect to us
+            sharable: sharable && !disable_inbound_connections,
             is_syncing: AtomicBool::new(false),
+            rate_limits,
+            proxy,
+            inbound_connections_disabled: disable_inbound_connections,
+            upnp_enabled: enable_upnp && !disable_inbound_connections,
+            upnp_mapping: Mutex::new(None),
+            connection_manager: Mutex::new(ConnectionManager::new(max_outbound_peers.unwrap_or(max_peers), max_inbound_peers.unwrap_or(max_peers))),
+            tls,
             outgoing_connections_disabled: AtomicBool::new(disable_outgoing_connections),
             exit_sender,
         };
@@ -243,6 +304,13 @@ impl<S: Storage> P2pServer<S> {
             error!("Error while sending Exit message to stop all tasks: {}", e);
         }
 
+        if let Some(mapping) = self.upnp_mapping.lock().await.take() {
+            info!("Removing UPnP port mapping...");
+            if let Err(e) = mapping.remove(Duration::from_millis(P2P_UPNP_TIMEOUT_MILLIS)).await {
+                warn!("Error while removing UPnP port mapping: {}", e);
+            }
+        }
+
         info!("Waiting for all peers to be closed...");
         self.peer_list.close_all().await;
         info!("P2p Server is now stopped!");
@@ -261,6 +329,22 @@ impl<S: Storage> P2pServer<S> {
         self.outgoing_connections_disabled.store(disable, Ordering::Release);
     }
 
+    pub fn is_inbound_connections_disabled(&self) -> bool {
+        self.inbound_connections_disabled
+    }
+
+    pub fn get_proxy(&self) -> Option<&Socks5Proxy> {
+        self.proxy.as_ref()
+    }
+
+    // Get the address of the TLS listener if it is started
+    pub fn get_tls_bind_address(&self) -> Option<&SocketAddr> {
+        if self.inbound_connections_disabled {
+            return None
+        }
+        self.tls.as_ref().and_then(|tls| tls.get_bind_address())
+    }
+
     // every 10 seconds, verify and connect if necessary to a random node
     async fn maintains_connection_to_nodes(self: &Arc<Self>, nodes: IndexSet<SocketAddr>, sender: Sender<SocketAddr>) -> Result<(), P2pError> {
         debug!("Starting maintains seed nodes task...");
@@ -279,12 +363,19 @@ impl<S: Storage> P2pServer<S> {
                         break;
                     }
 
+                    // a standby node only maintains the connection to its primary
+                    let standby_primary = self.blockchain.get_standby_primary().copied();
                     let connect = if self.peer_list.size().await >= self.max_peers {
                         // if we have already reached the limit, we ignore this new connection
                         None
                     } else {
+                        let candidates: Vec<&SocketAddr> = match standby_primary.as_ref() {
+                            Some(primary) => vec![primary],
+                            None => nodes.iter().collect()
+                        };
+
                         let mut potential_nodes = Vec::new();
-                        for node in &nodes {
+                        for node in candidates {
                             if !self.peer_list.is_connected_to_addr(&node).await {
                                 potential_nodes.push(node);
                             }
@@ -308,8 +399,23 @@ impl<S: Storage> P2pServer<S> {
     // connect to seed nodes, start p2p server
     // and wait on all new connections
     async fn start(self: &Arc<Self>, receiver: Receiver<(SocketAddr, bool)>, blocks_processor_receiver: Receiver<(Arc<Peer>, BlockHeader, Hash)>, event_receiver: Receiver<Arc<Peer>>, use_peerlist: bool, concurrency: usize) -> Result<(), P2pError> {
-        let listener = TcpListener::bind(self.get_bind_address()).await?;
-        info!("P2p Server will listen on: {}", self.get_bind_address());
+        let listener = if self.inbound_connections_disabled {
+            info!("P2p Server inbound connections are disabled");
+            None
+        } else {
+            let listener = TcpListener::bind(self.get_bind_address()).await?;
+            info!("P2p Server will listen on: {}", self.get_bind_address());
+            Some(listener)
+        };
+
+        let tls_listener = match self.get_tls_bind_address() {
+            Some(addr) => {
+                let listener = TcpListener::bind(addr).await?;
+                info!("P2p Server will listen for TLS connections on: {}", addr);
+                Some(listener)
+            },
+            None => None
+        };
 
         let mut exclusive_nodes = self.exclusive_nodes.clone();
         if exclusive_nodes.is_empty() {
@@ -329,15 +435,24 @@ impl<S: Storage> P2pServer<S> {
             };
         });
 
+        // start the task reconnecting to the anchors
+        spawn_task("p2p-anchors", Arc::clone(&self).anchors_loop());
+
         // start a new task for chain sync
         spawn_task("p2p-chain-sync", Arc::clone(&self).chain_sync_loop());
 
         // start another task for ping loop
         spawn_task("p2p-ping", Arc::clone(&self).ping_loop());
 
+        // start the task measuring the latency of each peer
+        spawn_task("p2p-latency", Arc::clone(&self).latency_loop());
+
         // start the blocks processing task to have a queued handler
         spawn_task("p2p-blocks", Arc::clone(&self).blocks_processing_task(blocks_processor_receiver));
 
+        // start the task flushing the txs hashes to broadcast
+        spawn_task("p2p-txs-broadcast", Arc::clone(&self).txs_broadcast_loop());
+
         // start the event loop task to handle peer disconnect events
         spawn_task("p2p-events", Arc::clone(&self).event_loop(event_receiver));
 
@@ -345,11 +460,26 @@ impl<S: Storage> P2pServer<S> {
         // start another task for peerlist loop
         if use_peerlist {
             spawn_task("p2p-peerlist", Arc::clone(&self).peerlist_loop());
+
+            // exclusive nodes don't need to discover new peers
+            if self.exclusive_nodes.is_empty() {
+                spawn_task("p2p-peer-exchange", Arc::clone(&self).peer_exchange_loop());
+            }
         }
 
         let (tx, mut rx) = channel(1);
         spawn_task("p2p-outgoing-connections", Arc::clone(&self).handle_outgoing_connections(priority_connections, receiver, tx.clone()));
-        spawn_task("p2p-incoming-connections", Arc::clone(&self).handle_incoming_connections(listener, tx, concurrency));
+        if let Some(listener) = tls_listener {
+            spawn_task("p2p-tls-incoming-connections", Arc::clone(&self).handle_incoming_connections(listener, tx.clone(), concurrency, true));
+        }
+
+        if let Some(listener) = listener {
+            spawn_task("p2p-incoming-connections", Arc::clone(&self).handle_incoming_connections(listener, tx, concurrency, false));
+
+            if self.upnp_enabled {
+                spawn_task("p2p-upnp", Arc::clone(&self).upnp_loop());
+            }
+        }
 
         let mut exit_receiver = self.exit_sender.subscribe();
         loop {
@@ -399,6 +529,99 @@ impl<S: Storage> P2pServer<S> {
         Ok(())
     }
 
+    // Reconnect to the anchors disconnected
+    // Each anchor is retried with an exponential backoff until it is connected again
+    async fn anchors_loop(self: Arc<Self>) {
+        debug!("Starting anchors task...");
+        let mut interval = interval(Duration::from_secs(P2P_ANCHOR_CHECK_DELAY));
+        let mut exit_receiver = self.exit_sender.subscribe();
+        loop {
+            select! {
+                biased;
+                _ = exit_receiver.recv() => {
+                    debug!("Received exit message, exiting anchors task");
+                    break;
+                },
+                _ = interval.tick() => {
+                    if !self.is_running() {
+                        debug!("Anchors task is stopped!");
+                        break;
+                    }
+
+                    let anchors: Vec<SocketAddr> = {
+                        let manager = self.connection_manager.lock().await;
+                        manager.get_anchors().keys().copied().collect()
+                    };
+
+                    for addr in anchors {
+                        let connected = self.is_connected_to_addr(&addr).await;
+                        let retry = {
+                            let mut manager = self.connection_manager.lock().await;
+                            if connected {
+                                manager.on_anchor_connected(&addr);
+                                false
+                            } else {
+                                manager.should_retry_anchor(&addr, get_current_time_in_millis())
+                            }
+                        };
+
+                        if retry {
+                            debug!("Trying to reconnect to anchor {}", addr);
+                            self.try_to_connect_to_peer(addr, true).await;
+                        }
+                    }
+                }
+            }
+        }
+
+        debug!("Anchors task has exited");
+    }
+
+    // Request a port mapping on the local gateway and renew it before its lease expires
+    // The mapping is removed when the P2p server is stopped
+    async fn upnp_loop(self: Arc<Self>) {
+        let port = self.bind_address.port();
+        let timeout_duration = Duration::from_millis(P2P_UPNP_TIMEOUT_MILLIS);
+        let mut interval = interval(Duration::from_secs(P2P_UPNP_LEASE_DURATION as u64 / 2));
+        let mut exit_receiver = self.exit_sender.subscribe();
+        loop {
+            select! {
+                biased;
+                _ = exit_receiver.recv() => {
+                    debug!("Received exit message, exiting UPnP task");
+                    break;
+                },
+                _ = interval.tick() => {
+                    if !self.is_running() {
+                        break;
+                    }
+
+                    let mut mapping = self.upnp_mapping.lock().await;
+                    match mapping.as_ref() {
+                        Some(mapping) => {
+                            trace!("Renewing UPnP port mapping for port {}", mapping.get_port());
+                            if let Err(e) = mapping.renew(P2P_UPNP_LEASE_DURATION, timeout_duration).await {
+                                warn!("Error while renewing UPnP port mapping: {}", e);
+                            }
+                        },
+                        None => match PortMapping::request(port, P2P_UPNP_LEASE_DURATION, timeout_duration).await {
+                            Ok(new_mapping) => {
+                                match new_mapping.get_external_ip(timeout_duration).await {
+                                    Ok(ip) => info!("UPnP port mapping added, node is reachable at {}", SocketAddr::new(ip, port)),
+                                    Err(e) => info!("UPnP port mapping added for {}:{} but external address is unknown: {}", new_mapping.get_local_ip(), port, e)
+                                };
+                                *mapping = Some(new_mapping);
+                            },
+                            Err(e) => warn!("Error while requesting UPnP port mapping: {}", e)
+                        }
+                    };
+                }
+            }
+        }
+
+        debug!("UPnP task has exited");
+    }
+
     async fn handle_outgoing_connections(self: Arc<Self>, mut priority_connections: Receiver<SocketAddr>, mut receiver: Receiver<(SocketAddr, bool)>, tx: Sender<(Peer, Rx)>) {
         // only allocate one time the buffer for this packet
         let mut handshake_buffer = [0; 512];
@@ -435,7 +658,7 @@ impl<S: Storage> P2pServer<S> {
             if !priority {
                 trace!("checking if connection can be accepted");
                 // check that this incoming peer isn't blacklisted
-                if !self.accept_new_connections().await || !self.peer_list.is_allowed(&addr.ip()).await {
+                if !self.accept_new_outgoing_connections().await || !self.peer_list.is_allowed(&addr.ip()).await {
                     debug!("{} is not allowed, we can't connect to it", addr);
                     continue;
                 }
@@ -482,13 +705,13 @@ impl<S: Storage> P2pServer<S> {
     // This task will handle an incoming connection request
     // It will verify if we can accept this connection
     // If we can, we will create a new peer and send it to the listener
-    async fn handle_incoming_connection(self: &Arc<Self>, res: io::Result<(TcpStream, SocketAddr)>, thread_pool: &ThreadPool, tx: &Sender<(Peer, Rx)>) -> Result<(), P2pError> {
+    async fn handle_incoming_connection(self: &Arc<Self>, res: io::Result<(TcpStream, SocketAddr)>, thread_pool: &ThreadPool, tx: &Sender<(Peer, Rx)>, tls: bool) -> Result<(), P2pError> {
         let (mut stream, addr) = res?;
 
         // Verify if we can accept new connections
         let reject = !self.is_compatible_with_exclusive_nodes(&addr)
             // check that this incoming peer isn't blacklisted
-            || !self.accept_new_connections().await
+            || !self.accept_new_incoming_connections().await
             || !self.peer_list.is_allowed(&addr.ip()).await
             || self.is_connected_to_addr(&addr).await;
 
@@ -499,12 +722,16 @@ impl<S: Storage> P2pServer<S> {
             return Ok(())
         }
 
-        let connection = Connection::new(stream, addr, false);
         let zelf = Arc::clone(&self);
         let tx = tx.clone();
         thread_pool.execute(async move {
             let mut buffer = [0; 512];
-            match zelf.create_verified_peer(&mut buffer, connection, false).await {
+            let res = match zelf.accept_connection(stream, addr, tls).await {
+                Ok(connection) => zelf.create_verified_peer(&mut buffer, connection, false).await,
+                Err(e) => Err(e)
+            };
+
+            match res {
                 Ok((peer, rx)) => {
                     if let Err(e) = tx.send((peer, rx)).await {
                         error!("Error while sending new connection to listener: {}", e);
@@ -520,10 +747,23 @@ impl<S: Storage> P2pServer<S> {
         Ok(())
     }
 
+    // Create the connection of an incoming stream
+    // It is wrapped in a TLS session if received on the TLS listener
+    async fn accept_connection(&self, stream: TcpStream, addr: SocketAddr, tls: bool) -> Result<Connection, P2pError> {
+        match self.tls.as_ref() {
+            Some(p2p_tls) if tls => {
+                trace!("Accepting TLS session from {}", addr);
+                let stream = timeout(Duration::from_millis(PEER_TIMEOUT_INIT_CONNECTION), p2p_tls.accept(stream)).await??;
+                Ok(Connection::new_tls(stream, addr, false))
+            },
+            _ => Ok(Connection::new(stream, addr, false))
+        }
+    }
+
     // This task will handle all incoming connections requests
     // Based on the concurrency set, it will create a thread pool to handle requests and wait when
     // a worker is free to accept a new connection
-    async fn handle_incoming_connections(self: Arc<Self>, listener: TcpListener, tx: Sender<(Peer, Rx)>, concurrency: usize) {
+    async fn handle_incoming_connections(self: Arc<Self>, listener: TcpListener, tx: Sender<(Peer, Rx)>, concurrency: usize, tls: bool) {
         let mut thread_pool = ThreadPool::new(concurrency);
         let mut exit_receiver = self.exit_sender.subscribe();
         loop {
@@ -539,7 +779,7 @@ impl<S: Storage> P2pServer<S> {
                         break;
                     }
 
-                    self.handle_incoming_connection(res, &thread_pool, &tx).await.unwrap_or_else(|e| {
+                    self.handle_incoming_connection(res, &thread_pool, &tx, tls).await.unwrap_or_else(|e| {
                         debug!("Error while handling incoming connection: {}", e);
                     });
                 }
@@ -554,7 +794,8 @@ impl<S: Storage> P2pServer<S> {
     // Verify handshake send by a new connection
     // based on data size, network ID, peers address validity
     // block height and block top hash of this peer (to know if we are on the same chain)
-    async fn verify_handshake(&self, connection: &mut Connection, handshake: &mut Handshake<'_>) -> Result<(), P2pError> {
+    // Returns the protocol version negotiated with the peer
+    async fn verify_handshake(&self, connection: &mut Connection, handshake: &mut Handshake<'_>) -> Result<u16, P2pError> {
         if handshake.get_network() != self.blockchain.get_network() {
             trace!("{} has an invalid network: {}", connection, handshake.get_network());
             return Err(P2pError::InvalidNetwork)
@@ -568,7 +809,12 @@ impl<S: Storage> P2pServer<S> {
             return Err(P2pError::PeerIdAlreadyUsed(handshake.get_peer_id()));
         }
 
-        if *handshake.get_block_genesis_hash() != *get_genesis_block_hash(self.blockchain.get_network()) {
+        let genesis_hash = {
+            let storage = self.blockchain.get_storage().read().await;
+            self.blockchain.get_genesis_hash_for_storage(&*storage).await?
+        };
+
+        if *handshake.get_block_genesis_hash() != *genesis_hash {
             debug!("Invalid genesis block hash {}", handshake.get_block_genesis_hash());
             return Err(P2pError::InvalidHandshake)
         }
@@ -581,7 +827,11 @@ impl<S: Storage> P2pServer<S> {
             }
         }
 
-        Ok(())
+        let protocol_version = handshake.negotiate_protocol_version(P2P_MIN_PROTOCOL_VERSION, P2P_MAX_PROTOCOL_VERSION)
+            .ok_or(P2pError::IncompatibleProtocolVersion(handshake.get_min_protocol_version(), handshake.get_max_protocol_version()))?;
+        debug!("Protocol version {} negotiated with {}", protocol_version, connection);
+
+        Ok(protocol_version)
     }
 
     // Build a handshake packet
@@ -592,15 +842,24 @@ impl<S: Storage> P2pServer<S> {
         let topoheight = self.blockchain.get_topo_height();
         let pruned_topoheight = storage.get_pruned_topoheight().await?;
         let cumulative_difficulty = storage.get_cumulative_difficulty_for_block_hash(&top_hash).await.unwrap_or_else(|_| CumulativeDifficulty::zero());
-        let genesis_block = get_genesis_block_hash(self.blockchain.get_network());
-        let handshake = Handshake::new(Cow::Owned(VERSION.to_owned()), *self.blockchain.get_network(), Cow::Borrowed(self.get_tag()), Cow::Borrowed(&NETWORK_ID), self.get_peer_id(), self.bind_address.port(), get_current_time_in_seconds(), topoheight, block.get_height(), pruned_topoheight, Cow::Borrowed(&top_hash), Cow::Borrowed(genesis_block), Cow::Borrowed(&cumulative_difficulty), self.sharable);
+        let genesis_block = self.blockchain.get_genesis_hash_for_storage(&*storage).await?;
+        let mut capabilities = self.blockchain.get_capabilities_for_storage(&*storage).await?;
+        capabilities.insert(self.get_capabilities());
+        // don't leak our listening port if nobody can connect to us
+        let local_port = if self.inbound_connections_disabled {
+            0
+        } else {
+            self.bind_address.port()
+        };
+        let tls_port = self.get_tls_bind_address().map(|addr| addr.port());
+        let handshake = Handshake::new(Cow::Owned(VERSION.to_owned()), *self.blockchain.get_network(), Cow::Borrowed(self.get_tag()), Cow::Borrowed(&NETWORK_ID), self.get_peer_id(), local_port, get_current_time_in_seconds(), topoheight, block.get_height(), pruned_topoheight, Cow::Borrowed(&top_hash), genesis_block, Cow::Borrowed(&cumulative_difficulty), self.sharable && !self.blockchain.is_standby(), capabilities, P2P_MIN_PROTOCOL_VERSION, P2P_MAX_PROTOCOL_VERSION, tls_port);
         Ok(Packet::Handshake(Cow::Owned(handshake)).to_bytes())
     }
 
     // Create a valid peer using the connection, if an error happen, it will close the stream and return the error
     async fn create_verified_peer(&self, buf: &mut [u8], mut connection: Connection, priority: bool) -> Result<(Peer, Rx), P2pError> {
-        let handshake = match self.verify_connection(buf, &mut connection).await {
-            Ok(handshake) => handshake,
+        let (handshake, protocol_version) = match self.verify_connection(buf, &mut connection).await {
+            Ok(res) => res,
             Err(e) => {
                 debug!("Error while verifying connection with {}: {}", connection, e);
                 connection.close().await?;
@@ -608,14 +867,18 @@ impl<S: Storage> P2pServer<S> {
             }
         };
 
-        let (peer, rx) = handshake.create_peer(connection, priority, self.peer_list.clone());
+        let negotiated_capabilities = self.get_capabilities()
+            .intersection(handshake.get_capabilities())
+            .intersection(Capabilities::P2P_FEATURES);
+        let (peer, rx) = handshake.create_peer(connection, priority, self.peer_list.clone(), protocol_version, negotiated_capabilities);
         Ok((peer, rx))
     }
 
     // this function handle all new connections
     // A new connection have to send an Handshake
     // if the handshake is valid, we accept it & register it on server
-    async fn verify_connection(&self, buf: &mut [u8], connection: &mut Connection) -> Result<Handshake, P2pError> {
+    // Returns the handshake received with the protocol version negotiated
+    async fn verify_connection(&self, buf: &mut [u8], connection: &mut Connection) -> Result<(Handshake, u16), P2pError> {
         trace!("New connection: {}", connection);
 
         // Exchange encryption keys
@@ -635,7 +898,7 @@ impl<S: Storage> P2pServer<S> {
         };
 
         trace!("received handshake packet!");
-        self.verify_handshake(connection, &mut handshake).await?;
+        let protocol_version = self.verify_handshake(connection, &mut handshake).await?;
 
         trace!("Handshake has been verified");
         // if it's a outgoing connection, don't send the handshake back
@@ -648,7 +911,7 @@ impl<S: Storage> P2pServer<S> {
         // if we reach here, handshake is all good, we can start listening this new peer
         connection.set_state(State::Success);
 
-        Ok(handshake)
+        Ok((handshake, protocol_version))
     }
 
     async fn handle_new_peer(self: &Arc<Self>, peer: &Arc<Peer>, rx: Rx) -> Result<(), P2pError> {
@@ -675,7 +938,12 @@ impl<S: Storage> P2pServer<S> {
     }
 
     // Verify that we don't have any exclusive nodes configured OR that we are part of this list
+    // In standby mode, the primary node is the only one allowed
     pub fn is_compatible_with_exclusive_nodes(&self, addr: &SocketAddr) -> bool {
+        if let Some(primary) = self.blockchain.get_standby_primary() {
+            return primary == addr
+        }
+
         self.exclusive_nodes.is_empty() || self.exclusive_nodes.contains(addr)
     }
 
@@ -717,8 +985,27 @@ impl<S: Storage> P2pServer<S> {
             }
         }
 
-        let stream = timeout(Duration::from_millis(PEER_TIMEOUT_INIT_OUTGOING_CONNECTION), TcpStream::connect(&addr)).await??;
-        let connection = Connection::new(stream, addr, true);
+        // Use the TLS listener of the peer if it advertised one
+        let tls = match self.tls.as_ref() {
+            Some(p2p_tls) if p2p_tls.is_outgoing_enabled() => self.peer_list.get_stored_tls_port(&addr).await
+                .map(|port| (p2p_tls, SocketAddr::new(addr.ip(), port))),
+            _ => None
+        };
+        let target = tls.map(|(_, target)| target).unwrap_or(addr);
+
+        let stream = match self.proxy.as_ref() {
+            Some(proxy) => timeout(Duration::from_millis(PEER_TIMEOUT_INIT_OUTGOING_CONNECTION), proxy.connect(&target)).await??,
+            None => timeout(Duration::from_millis(PEER_TIMEOUT_INIT_OUTGOING_CONNECTION), TcpStream::connect(&target)).await??
+        };
+
+        let connection = match tls {
+            Some((p2p_tls, target)) => {
+                trace!("Starting TLS session with {}", target);
+                let stream = timeout(Duration::from_millis(PEER_TIMEOUT_INIT_OUTGOING_CONNECTION), p2p_tls.connect(stream)).await??;
+                Connection::new_tls(stream, target, true)
+            },
+            None => Connection::new(stream, addr, true)
+        };
         Ok(connection)
     }
 
@@ -745,7 +1032,10 @@ impl<S: Storage> P2pServer<S> {
             match storage.get_top_block_hash().await {
                 Err(e) => {
                     error!("Couldn't get the top block hash from storage for generic ping packet: {}", e);
-                    (CumulativeDifficulty::zero(), get_genesis_block_hash(self.blockchain.get_network()).clone(), pruned_topoheight)
+                    let genesis_hash = self.blockchain.get_genesis_hash_for_storage(storage).await
+                        .map(Cow::into_owned)
+                        .unwrap_or_else(|_| Hash::zero());
+                    (CumulativeDifficulty::zero(), genesis_hash, pruned_topoheight)
                 },
                 Ok(hash) => (storage.get_cumulative_difficulty_for_block_hash(&hash).await.unwrap_or_else(|_| CumulativeDifficulty::zero()), hash, pruned_topoheight)
             }
@@ -764,15 +1054,16 @@ impl<S: Storage> P2pServer<S> {
         self.build_generic_ping_packet_with_storage(&*storage).await
     }
 
-    // select a random peer which is greater than us to sync chain
+    // select the peer with the most work done to sync chain
     // candidate peer should have a greater topoheight or a higher block height than us
     // It must also have a greater cumulative difficulty than us
     // Cumulative difficulty is used in case two chains are running at same speed
     // We must determine which one has the most work done
     // if we are not in fast sync mode, we must verify its pruned topoheight to be sure
     // he have the blocks we need
-    async fn select_random_best_peer(&self, fast_sync: bool, previous_peer: Option<&(Arc<Peer>, bool)>) -> Result<Option<Arc<Peer>>, BlockchainError> {
-        trace!("select random best peer");
+    // The chain state of each peer is the one advertised in its handshake and last ping
+    async fn select_best_peer(&self, fast_sync: bool, previous_peer: Option<&(Arc<Peer>, bool)>) -> Result<Option<Arc<Peer>>, BlockchainError> {
+        trace!("select best peer");
         
         let our_height = self.blockchain.get_height();
         let our_topoheight = self.blockchain.get_topo_height();
@@ -785,21 +1076,18 @@ impl<S: Storage> P2pServer<S> {
             storage.get_cumulative_difficulty_for_block_hash(&hash).await?
         };
 
-        trace!("peer list locked for select random best peer");
+        trace!("peer list locked for select best peer");
 
         // search for peers which are greater than us
         // and that are pruned but before our height so we can sync correctly
         let available_peers = self.peer_list.get_cloned_peers().await;
-        // IndexSet is used to select by random index
-        let mut peers: IndexSet<Arc<Peer>> = IndexSet::with_capacity(available_peers.len());
+        let mut candidates: Vec<(Arc<Peer>, CumulativeDifficulty)> = Vec::with_capacity(available_peers.len());
 
         for p in available_peers {
             // Avoid selecting peers that have a weaker cumulative difficulty than us
-            {
-                let cumulative_difficulty = p.get_cumulative_difficulty().lock().await;
-                if *cumulative_difficulty <= our_cumulative_difficulty {
-                    continue;
-                }
+            let cumulative_difficulty = *p.get_cumulative_difficulty().lock().await;
+            if cumulative_difficulty <= our_cumulative_difficulty {
+                continue;
             }
 
             let peer_topoheight = p.get_topoheight();
@@ -830,29 +1118,72 @@ impl<S: Storage> P2pServer<S> {
                 continue;
             }
 
-            peers.insert(p);
+            candidates.push((p, cumulative_difficulty));
         }
 
-        // Try to not reuse the same peer between each sync
-        if let Some((previous_peer, err)) = previous_peer {
-            if peers.len() > 1 || (*err && !previous_peer.is_priority()) {
-                debug!("removing previous peer {} from random selection, err: {}, priority: {}", previous_peer, err, previous_peer.is_priority());
+        // Don't retry a peer that failed the previous sync unless it's a priority node
+        if let Some((previous_peer, true)) = previous_peer {
+            if !previous_peer.is_priority() {
+                debug!("removing previous peer {} from selection after an error", previous_peer);
+                candidates.retain(|(p, _)| p.get_id() != previous_peer.get_id());
+            }
+        }
+
+        // Only the peers with the most work done are kept
+        let best_cumulative_difficulty = match candidates.iter().map(|(_, cumulative_difficulty)| *cumulative_difficulty).max() {
+            Some(cumulative_difficulty) => cumulative_difficulty,
+            None => return Ok(None)
+        };
+
+        // IndexSet is used to select by random index
+        let mut peers: IndexSet<Arc<Peer>> = candidates.into_iter()
+            .filter(|(_, cumulative_difficulty)| *cumulative_difficulty == best_cumulative_difficulty)
+            .map(|(p, _)| p)
+            .collect();
+
+        // Try to not reuse the same peer between each sync if others are on the same chain
+        if let Some((previous_peer, _)) = previous_peer {
+            if peers.len() > 1 {
+                debug!("removing previous peer {} from random selection", previous_peer);
                 // We don't need to preserve the order
                 peers.swap_remove(previous_peer);
             }
         }
 
         let count = peers.len();
-        trace!("peers available for random selection: {}", count);
-        if count == 0 {
-            return Ok(None)
-        }
+        trace!("peers with best cumulative difficulty {} available for random selection: {}", best_cumulative_difficulty, count);
 
         let selected = rand::thread_rng().gen_range(0..count);
         // clone the Arc to prevent the lock until the end of the sync request
         Ok(peers.swap_remove_index(selected))
     }
 
+    // Select the peers to request the blocks from during a chain sync
+    // The peer that sent us the chain response is always the first one
+    // Others peers must be ahead of us and have the blocks we need (not pruned)
+    // If a peer is on another chain, it will not find the blocks and will be discarded by the scheduler
+    async fn select_sync_peers(&self, peer: &Arc<Peer>) -> Vec<Arc<Peer>> {
+        let our_topoheight = self.blockchain.get_topo_height();
+        let mut peers = vec![Arc::clone(peer)];
+        for p in self.peer_list.get_cloned_peers().await {
+            if peers.len() >= CHAIN_SYNC_MAX_PEERS {
+                break;
+            }
+
+            if p.get_id() == peer.get_id() || p.get_topoheight() <= our_topoheight {
+                continue;
+            }
+
+            if p.get_pruned_topoheight().is_some_and(|pruned_topoheight| pruned_topoheight > our_topoheight) {
+                continue;
+            }
+
+            peers.push(p);
+        }
+
+        peers
+    }
+
     // Check if user has allowed fast sync mode
     // This is useful for light node by syncing only the top chain while staying fully compatible
     pub fn allow_fast_sync(&self) -> bool {
@@ -865,6 +1196,18 @@ impl<S: Storage> P2pServer<S> {
         self.allow_boost_sync_mode
     }
 
+    // Get the P2P capabilities enabled on this node
+    pub fn get_capabilities(&self) -> Capabilities {
+        let mut capabilities = Capabilities::empty();
+        capabilities.insert(Capabilities::PEER_EXCHANGE);
+        capabilities.insert(Capabilities::GET_TXS);
+        capabilities.insert(Capabilities::TXS_PROPAGATION);
+        capabilities.insert(Capabilities::LATENCY_PING);
+        capabilities.set(Capabilities::FAST_SYNC, self.allow_fast_sync());
+        capabilities.set(Capabilities::BOOST_SYNC, self.allow_boost_sync());
+        capabilities
+    }
+
     // Set the chain syncing state
     fn set_chain_syncing(&self, syncing: bool) {
         self.is_syncing.store(syncing, Ordering::Release);
@@ -915,10 +1258,10 @@ impl<S: Storage> P2pServer<S> {
                 false
             };
 
-            let peer_selected = match self.select_random_best_peer(fast_sync, previous_peer.as_ref()).await {
+            let peer_selected = match self.select_best_peer(fast_sync, previous_peer.as_ref()).await {
                 Ok(peer) => peer,
                 Err(e) => {
-                    error!("Error while selecting random best peer for chain sync: {}", e);
+                    error!("Error while selecting best peer for chain sync: {}", e);
                     None
                 }
             };
@@ -1070,6 +1413,48 @@ impl<S: Storage> P2pServer<S> {
         }
     }
 
+    // send a latency ping to each peer supporting it to measure the round-trip time
+    // a ping not answered before the next one is considered as lost
+    async fn latency_loop(self: Arc<Self>) {
+        debug!("Starting latency loop...");
+        let mut interval = interval(Duration::from_secs(P2P_LATENCY_PING_DELAY));
+        let mut exit_receiver = self.exit_sender.subscribe();
+        loop {
+            select! {
+                biased;
+                _ = exit_receiver.recv() => {
+                    debug!("Received exit message, exiting latency task");
+                    break;
+                },
+                _ = interval.tick() => {
+                    if !self.is_running() {
+                        debug!("Latency task is stopped!");
+                        break;
+                    }
+
+                    for peer in self.peer_list.get_cloned_peers().await {
+                        if peer.get_connection().is_closed() || !peer.get_negotiated_capabilities().contains(Capabilities::LATENCY_PING) {
+                            continue;
+                        }
+
+                        if peer.has_pending_latency_ping() {
+                            debug!("{} didn't answer our latency ping in time", peer);
+                        }
+
+                        let id: u64 = rand::thread_rng().gen();
+                        trace!("Sending latency ping {} to {}", id, peer);
+                        // set it before sending so a fast pong is not ignored
+                        peer.set_pending_latency_ping(id, get_current_time_in_millis());
+                        if let Err(e) = peer.send_packet(Packet::LatencyPing(LatencyPing::new(id))).await {
+                            debug!("Error while sending latency ping to {}: {}", peer, e);
+                            peer.clear_pending_latency_ping();
+                        }
+                    }
+                }
+            }
+        }
+    }
+
     // try to extend our peerlist each time its possible by searching in known peerlist from disk
     async fn peerlist_loop(self: Arc<Self>) {
         debug!("Starting peerlist task...");
@@ -1080,7 +1465,12 @@ impl<S: Storage> P2pServer<S> {
                 break;
             }
 
-            if self.accept_new_connections().await {
+            if self.blockchain.is_standby() {
+                trace!("Standby mode, skipping peerlist extension");
+                continue;
+            }
+
+            if self.accept_new_outgoing_connections().await {
                 let peer = {
                     trace!("Locking peer list write mode (peerlist loop)");
                     self.peer_list.find_peer_to_connect().await
@@ -1097,6 +1487,92 @@ impl<S: Storage> P2pServer<S> {
         }
     }
 
+    // Broadcast the txs hashes queued at regular interval
+    // so we send one packet per peer for several txs
+    async fn txs_broadcast_loop(self: Arc<Self>) {
+        debug!("Starting txs broadcast task...");
+        let mut interval = interval(Duration::from_millis(P2P_TXS_BATCH_INTERVAL_MILLIS));
+        let mut exit_receiver = self.exit_sender.subscribe();
+        loop {
+            select! {
+                biased;
+                _ = exit_receiver.recv() => {
+                    debug!("Received exit message, exiting txs broadcast task");
+                    break;
+                },
+                _ = interval.tick() => {
+                    if !self.is_running() {
+                        debug!("Txs broadcast task is stopped!");
+                        break;
+                    }
+
+                    let txs = {
+                        let mut queue = self.txs_broadcast_queue.lock().await;
+                        if queue.is_empty() {
+                            continue;
+                        }
+                        std::mem::take(&mut *queue)
+                    };
+
+                    self.broadcast_txs_hashes(txs).await;
+                }
+            }
+        }
+    }
+
+    // Periodically request a sample of known peers to a random peer
+    // This allows us to discover new peers without relying only on seed nodes
+    async fn peer_exchange_loop(self: Arc<Self>) {
+        debug!("Starting peer exchange task...");
+        let mut interval = interval(Duration::from_secs(P2P_PEER_EXCHANGE_DELAY));
+        let mut exit_receiver = self.exit_sender.subscribe();
+        loop {
+            select! {
+                biased;
+                _ = exit_receiver.recv() => {
+                    debug!("Received exit message, exiting peer exchange task");
+                    break;
+                },
+                _ = interval.tick() => {
+                    if !self.is_running() {
+                        debug!("Peer exchange task is stopped!");
+                        break;
+                    }
+
+                    if self.is_outgoing_connections_disabled() || self.blockchain.is_standby() {
+                        trace!("Outgoing connections are disabled, skipping peer exchange");
+                        continue;
+                    }
+
+                    let peers = self.peer_list.get_cloned_peers().await;
+                    // Requests never answered must not prevent asking the peer again
+                    let current_time = get_current_time_in_seconds();
+                    for peer in peers.iter() {
+                        if peer.expire_requested_peer_exchange(current_time) {
+                            debug!("{} didn't answer our peer exchange request in time", peer);
+                        }
+                    }
+
+                    let peer = peers.into_iter()
+                        .filter(|peer| peer.get_negotiated_capabilities().contains(Capabilities::PEER_EXCHANGE))
+                        .filter(|peer| !peer.has_requested_peer_exchange() && !peer.get_connection().is_closed())
+                        .choose(&mut rand::thread_rng());
+
+                    if let Some(peer) = peer {
+                        debug!("Requesting peer exchange to {}", peer);
+                        peer.set_requested_peer_exchange(true);
+                        if let Err(e) = peer.send_packet(Packet::PeerExchangeRequest(PeerExchangeRequest::new(P2P_PEER_EXCHANGE_LIMIT as u8))).await {
+                            debug!("Error while sending peer exchange request to {}: {}", peer, e);
+                            peer.set_requested_peer_exchange(false);
+                        }
+                    } else {
+                        trace!("No peer available for peer exchange");
+                    }
+                }
+            }
+        }
+    }
+
     // This function is used to broadcast PeerDisconnected event to listeners
     // We use a channel to avoid having to pass the Blockchain<S> to the Peerlist & Peers
     async fn event_loop(self: Arc<Self>, mut receiver: Receiver<Arc<Peer>>) {
@@ -1149,27 +1625,47 @@ impl<S: Storage> P2pServer<S> {
                     };
 
                     let mut response_blockers: Vec<ResponseBlocker> = Vec::new();
+                    let mut missing_txs = IndexSet::new();
                     for hash in header.get_txs_hashes() {
-                        let contains = { // we don't lock one time because we may wait on p2p response
-                            // Check in ObjectTracker
-                            if let Some(response_blocker) = self.object_tracker.get_response_blocker_for_requested_object(hash).await {
-                                trace!("{} is already requested, waiting on response blocker for block {}", hash, block_hash);
-                                response_blockers.push(response_blocker);
-                                true
-                            } else {
-                                self.blockchain.has_tx(hash).await.unwrap_or(false)
+                        // Check in ObjectTracker
+                        if let Some(response_blocker) = self.object_tracker.get_response_blocker_for_requested_object(hash).await {
+                            trace!("{} is already requested, waiting on response blocker for block {}", hash, block_hash);
+                            response_blockers.push(response_blocker);
+                        } else if !self.blockchain.has_tx(hash).await.unwrap_or(false) {
+                            missing_txs.insert(hash.clone());
+                        }
+                    }
+
+                    if missing_txs.is_empty() {
+                        trace!("No missing TXs for block {}", block_hash);
+                    } else if peer.get_negotiated_capabilities().contains(Capabilities::GET_TXS) {
+                        // Request all the missing txs in one round-trip
+                        debug!("Requesting {} missing TXs to {} for block {}", missing_txs.len(), peer, block_hash);
+                        let txs = match peer.request_txs(&block_hash, &missing_txs).await {
+                            Ok(txs) => txs,
+                            Err(e) => {
+                                error!("Error while requesting missing TXs to {} for block {}: {}", peer, block_hash, e);
+                                peer.increment_fail_count();
+                                continue;
                             }
                         };
 
-                        if !contains { // retrieve one by one to prevent acquiring the lock for nothing
+                        for (hash, tx) in txs {
+                            if let Err(e) = self.blockchain.add_tx_to_mempool_with_hash(tx, hash.clone(), false).await {
+                                debug!("Error while adding TX {} from {} for block {} to mempool: {}", hash, peer, block_hash, e);
+                            }
+                        }
+                    } else {
+                        // Peer doesn't support GetTxs, retrieve them one by one
+                        for hash in missing_txs {
                             debug!("Requesting TX {} to {} for block {}", hash, peer, block_hash);
                             if let Err(e) = self.object_tracker.request_object_from_peer(Arc::clone(&peer), ObjectRequest::Transaction(hash.clone()), false).await {
-                                    error!("Error while requesting TX {} to {} for block {}: {}", hash, peer, block_hash, e);
-                                    peer.increment_fail_count();
-                                    continue;
+                                error!("Error while requesting TX {} to {} for block {}: {}", hash, peer, block_hash, e);
+                                peer.increment_fail_count();
+                                continue;
                             }
 
-                            if let Some(response_blocker) = self.object_tracker.get_response_blocker_for_requested_object(hash).await {
+                            if let Some(response_blocker) = self.object_tracker.get_response_blocker_for_requested_object(&hash).await {
                                 response_blockers.push(response_blocker);
                             }
                         }
@@ -1394,6 +1890,11 @@ impl<S: Storage> P2pServer<S> {
     // This is handling each packet available in our p2p protocol
     // Each packet is a enum variant
     async fn handle_incoming_packet(self: &Arc<Self>, peer: &Arc<Peer>, packet: Packet<'_>) -> Result<(), P2pError> {
+        // Packets from a newer protocol version than the negotiated one are not expected
+        if packet.get_protocol_version() > peer.get_protocol_version() {
+            return Err(P2pError::UnsupportedPacketVersion(packet.get_id(), packet.get_protocol_version(), peer.get_protocol_version()))
+        }
+
         match packet {
             Packet::Handshake(_) => {
                 error!("{} sent us handshake packet (not valid!)", peer);
@@ -1411,39 +1912,15 @@ impl<S: Storage> P2pServer<S> {
                 let hash = hash.into_owned();
 
                 ping.into_owned().update_peer(peer, &self.blockchain).await?;
+                self.handle_txs_propagated(peer, [hash]).await?;
+            },
+            Packet::TransactionsPropagation(packet_wrapper) => {
+                trace!("{}: Transactions Propagation packet", peer);
+                let (txs, ping) = packet_wrapper.consume();
+                let txs = txs.into_owned().consume();
 
-                // peer should not send us twice the same transaction
-                debug!("Received tx hash {} from {}", hash, peer.get_outgoing_address());
-                {
-                    let mut txs_cache = peer.get_txs_cache().lock().await;
-
-                    if let Some(direction) = txs_cache.get_mut(&hash) {
-                        if !direction.update(Direction::In) {
-                            debug!("{} send us a transaction ({}) already tracked by him ({:?})", peer, hash, direction);
-                            // return Err(P2pError::AlreadyTrackedTx(hash))
-                        }
-                    } else {
-                        txs_cache.put(hash.clone(), Direction::In);
-                    }
-                }
-
-                // Check that the tx is not in mempool or on disk already
-                if !self.blockchain.has_tx(&hash).await? {
-                    trace!("Requesting tx {} propagated because we don't have it", hash);
-                    if !self.object_tracker.request_object_from_peer(Arc::clone(peer), ObjectRequest::Transaction(hash.clone()), true).await? {
-                        debug!("TX propagated {} was already requested, ignoring", hash);
-                    }
-                }
-
-                // Avoid sending the TX propagated to a common peer
-                // because we track peerlist of each peers, we can try to determinate it
-                // iterate over all common peers of this peer broadcaster
-                for common_peer in self.get_common_peers_for(&peer).await {
-                    debug!("{} is a common peer with {}, adding TX {} to its cache", common_peer, peer, hash);
-                    let mut txs_cache = common_peer.get_txs_cache().lock().await;
-                    // Set it as Out so we don't send it anymore but we can get it one time in case of bad common peer prediction
-                    txs_cache.put(hash.clone(), Direction::Out);
-                }
+                ping.into_owned().update_peer(peer, &self.blockchain).await?;
+                self.handle_txs_propagated(peer, txs).await?;
             },
             Packet::BlockPropagation(packet_wrapper) => {
                 trace!("Received a block propagation packet from {}", peer);
@@ -1468,6 +1945,9 @@ impl<S: Storage> P2pServer<S> {
                     }
                 }
 
+                // The peer has all the txs of its block, don't announce them to it
+                peer.mark_txs_as_known(header.get_txs_hashes(), Direction::In).await;
+
                 // Avoid sending the same block to a common peer that may have already got it
                 // because we track peerlist of each peers, we can try to determinate it
                 for common_peer in self.get_common_peers_for(&peer).await {
@@ -1757,6 +2237,103 @@ impl<S: Storage> P2pServer<S> {
                     return Err(P2pError::UnrequestedBootstrapChainResponse)
                 }
             },
+            Packet::PeerExchangeRequest(request) => {
+                trace!("Received a peer exchange request from {}", peer);
+                let current_time = get_current_time_in_seconds();
+                let last_peer_exchange = peer.get_last_peer_exchange();
+                let diff = current_time - last_peer_exchange;
+                // Don't allow faster than 2/3 of the delay (because of connection latency / packets being queued)
+                if last_peer_exchange != 0 && diff < (P2P_PEER_EXCHANGE_DELAY * 2 / 3) {
+                    return Err(P2pError::PeerInvalidPeerExchangeCountdown(P2P_PEER_EXCHANGE_DELAY - diff))
+                }
+                peer.set_last_peer_exchange(current_time);
+
+                // Don't share local network addresses if it's external peer
+                let is_local_peer = is_local_address(peer.get_connection().get_address());
+                let peers = self.peer_list.get_peers_sample(request.get_limit() as usize, peer.get_outgoing_address(), is_local_peer).await;
+                debug!("Sending {} peers to {} for peer exchange", peers.len(), peer);
+                peer.send_packet(Packet::PeerExchangeResponse(PeerExchangeResponse::new(Cow::Owned(peers)))).await?;
+            },
+            Packet::PeerExchangeResponse(response) => {
+                trace!("Received a peer exchange response from {}", peer);
+                if !peer.has_requested_peer_exchange() {
+                    return Err(P2pError::UnrequestedPeerExchangeResponse)
+                }
+                peer.set_requested_peer_exchange(false);
+
+                let is_local_peer = is_local_address(peer.get_connection().get_address());
+                let mut addresses = Vec::with_capacity(response.get_peers().len());
+                for addr in response.consume() {
+                    if is_local_address(&addr) && !is_local_peer {
+                        error!("{} is a local address from {} but peer is external", addr, peer);
+                        return Err(P2pError::InvalidPeerlist)
+                    }
+
+                    if addr.port() == 0 || addr.ip().is_unspecified() {
+                        error!("{} sent us an invalid address {} in peer exchange", peer, addr);
+                        return Err(P2pError::InvalidPeerlist)
+                    }
+
+                    // Skip ourself, the peer itself and peers we are already connected to
+                    if addr == *self.get_bind_address() || addr == *peer.get_outgoing_address() || self.is_connected_to_addr(&addr).await {
+                        trace!("Skipping {} from peer exchange of {}", addr, peer);
+                        continue;
+                    }
+
+                    addresses.push(addr);
+                }
+
+                let stored = self.peer_list.store_peer_addresses(addresses).await;
+                debug!("Stored {} new peers from peer exchange of {}", stored, peer);
+            },
+            Packet::GetTxs(request) => {
+                trace!("Received a get txs request from {}", peer);
+                let block_hash = request.get_block_hash();
+                let mut txs = Vec::with_capacity(request.get_txs().len());
+                for hash in request.get_txs() {
+                    match self.blockchain.get_tx(hash).await {
+                        Ok(tx) => txs.push(tx),
+                        Err(e) => debug!("{} asked tx '{}' for block {} but not present in our chain: {}", peer, hash, block_hash, e)
+                    }
+                }
+
+                debug!("Sending {}/{} txs requested for block {} to {}", txs.len(), request.get_txs().len(), block_hash, peer);
+                let txs = txs.iter().map(|tx| Cow::Borrowed(tx.as_ref())).collect();
+                peer.send_packet(Packet::TxsResponse(TxsResponse::new(Cow::Borrowed(block_hash), txs))).await?;
+            },
+            Packet::LatencyPing(ping) => {
+                trace!("Received a latency ping from {}", peer);
+                let current_time = get_current_time_in_seconds();
+                let last_latency_ping = peer.get_last_latency_ping();
+                let diff = current_time - last_latency_ping;
+                // Don't allow faster than 2/3 of the delay (because of connection latency / packets being queued)
+                if last_latency_ping != 0 && diff < (P2P_LATENCY_PING_DELAY * 2 / 3) {
+                    return Err(P2pError::PeerInvalidLatencyPingCountdown(P2P_LATENCY_PING_DELAY - diff))
+                }
+                peer.set_last_latency_ping(current_time);
+
+                // answer directly with the same id
+                peer.send_packet(Packet::LatencyPong(ping)).await?;
+            },
+            Packet::LatencyPong(pong) => {
+                trace!("Received a latency pong from {}", peer);
+                match peer.on_latency_pong(pong.get_id(), get_current_time_in_millis()) {
+                    Some(rtt) => trace!("RTT with {} is {}ms (latency: {:?}, jitter: {:?})", peer, rtt, peer.get_latency(), peer.get_jitter()),
+                    None => debug!("Received an unrequested latency pong {} from {}", pong.get_id(), peer)
+                }
+            },
+            Packet::TxsResponse(response) => {
+                trace!("Received a txs response from {}", peer);
+                let (block_hash, txs) = response.consume();
+                let sender = peer.get_txs_requested()
+                    .lock().await
+                    .remove(&block_hash)
+                    .ok_or_else(|| P2pError::UnrequestedTxsResponse(block_hash.clone()))?;
+
+                if sender.send(txs).is_err() {
+                    error!("Error while sending txs response for block {} to channel of {}", block_hash, peer);
+                }
+            },
             Packet::PeerDisconnected(packet) => {
                 // This packet is used to keep sync between peers being shared
                 let addr = packet.to_addr();
@@ -1795,8 +2372,33 @@ impl<S: Storage> P2pServer<S> {
     async fn listen_connection(self: &Arc<Self>, buf: &mut [u8], peer: &Arc<Peer>) -> Result<(), P2pError> {
         // Read & parse the packet
         // 16 additional bytes are for AEAD
-        let packet = peer.get_connection().read_packet(buf, PEER_MAX_PACKET_SIZE).await?;
+        let connection = peer.get_connection();
+        let bytes_in = connection.bytes_in();
+        let packet = connection.read_packet(buf, PEER_MAX_PACKET_SIZE).await?;
+        let size = connection.bytes_in() - bytes_in;
         let packet_id = packet.get_id();
+
+        // Verify that the peer respects the rate limits
+        let (blocks, txs) = match &packet {
+            Packet::BlockPropagation(_) => (1, 0),
+            Packet::TransactionPropagation(_) => (0, 1),
+            Packet::TransactionsPropagation(wrapper) => (0, wrapper.get_packet().get_txs().len() as u64),
+            _ => (0, 0)
+        };
+
+        match peer.get_rate_limiter().record(&self.rate_limits, blocks, txs, size as u64) {
+            RateLimitResult::Allowed => {},
+            RateLimitResult::Drop => {
+                // don't block the read task, the propagated objects can be retrieved later during sync
+                debug!("{} exceeded the rate limits, dropping packet #{}", peer, packet_id);
+                return Ok(())
+            },
+            RateLimitResult::Abuse => {
+                warn!("{} keeps exceeding the rate limits! Closing connection...", peer);
+                peer.close_and_temp_ban().await?;
+                return Ok(())
+            }
+        }
         // Handle the packet
         if let Err(e) = self.handle_incoming_packet(&peer, packet).await {
             match e {
@@ -2126,6 +2728,9 @@ impl<S: Storage> P2pServer<S> {
                 (None, None)
             };
 
+            // blocks to request from several peers if boost sync is not allowed
+            let mut missing_blocks = Vec::new();
+
             // Peekable is here to help to know if we are at the last element
             // so we create only one channel for the last blocker
             let mut blocks_iter = blocks.into_iter().peekable();
@@ -2147,15 +2752,8 @@ impl<S: Storage> P2pServer<S> {
                             final_blocker = Some(blocker);
                         }
                     } else {
-                        // Otherwise, request them one by one and wait for the response
-                        let response = peer.request_blocking_object(ObjectRequest::Block(hash)).await?;
-                        if let OwnedObjectResponse::Block(block, hash) = response {
-                            trace!("Received block {} at height {} from {}", hash, block.get_height(), peer);
-                            self.blockchain.add_new_block(block, false, false).await?;
-                        } else {
-                            error!("{} sent us an invalid block response", peer);
-                            return Err(P2pError::ExpectedBlock.into())
-                        }
+                        // Otherwise, they are requested in chunks to all peers able to serve them
+                        missing_blocks.push(hash);
                     }
                     total_requested += 1;
                 } else {
@@ -2163,6 +2761,12 @@ impl<S: Storage> P2pServer<S> {
                 }
             }
 
+            if !missing_blocks.is_empty() {
+                let peers = self.select_sync_peers(peer).await;
+                debug!("Requesting {} blocks to {} peers", missing_blocks.len(), peers.len());
+                sync_scheduler::sync_blocks(&self.blockchain, peers, missing_blocks).await?;
+            }
+
             if let (Some(mut notifier), Some(mut blocker)) = (notifier, final_blocker) {
                 debug!("Waiting for final blocker to finish...");
                 select! {
@@ -2196,7 +2800,7 @@ impl<S: Storage> P2pServer<S> {
         if peer_topoheight > our_previous_topoheight && blocks_len < requested_max_size {
             let our_topoheight = self.blockchain.get_topo_height();
             // verify that we synced it partially well
-            if peer_topoheight >= our_topoheight && peer_topoheight - our_topoheight < STABLE_LIMIT {
+            if peer_topoheight >= our_topoheight && peer_topoheight - our_topoheight < self.blockchain.get_chain_params().stable_limit {
                 if let Err(e) = self.request_inventory_of(&peer).await {
                     error!("Error while asking inventory to {}: {}", peer, e);
                 }
@@ -2214,7 +2818,7 @@ impl<S: Storage> P2pServer<S> {
         for peer in self.peer_list.get_peers().read().await.values() {
             if peer.is_priority() {
                 let peer_topoheight = peer.get_topoheight();
-                if peer_topoheight >= topoheight || topoheight - peer_topoheight < STABLE_LIMIT {
+                if peer_topoheight >= topoheight || topoheight - peer_topoheight < self.blockchain.get_chain_params().stable_limit {
                     return true
                 }
             }
@@ -2242,6 +2846,69 @@ impl<S: Storage> P2pServer<S> {
         self.get_peer_count().await < self.get_max_peers()
     }
 
+    // Check if we have free slots available for a new outgoing connection
+    pub async fn accept_new_outgoing_connections(&self) -> bool {
+        let (outgoing, _) = self.peer_list.count_by_direction().await;
+        self.accept_new_connections().await && outgoing < self.connection_manager.lock().await.get_max_outbound()
+    }
+
+    // Check if we have free slots available for a new incoming connection
+    pub async fn accept_new_incoming_connections(&self) -> bool {
+        let (_, incoming) = self.peer_list.count_by_direction().await;
+        self.accept_new_connections().await && incoming < self.connection_manager.lock().await.get_max_inbound()
+    }
+
+    // Get the max outgoing and incoming connections
+    pub async fn get_connection_targets(&self) -> (usize, usize) {
+        let manager = self.connection_manager.lock().await;
+        (manager.get_max_outbound(), manager.get_max_inbound())
+    }
+
+    // Update the max outgoing and incoming connections
+    // Peers already connected are kept
+    pub async fn set_connection_targets(&self, max_outbound: Option<usize>, max_inbound: Option<usize>) {
+        let mut manager = self.connection_manager.lock().await;
+        if let Some(value) = max_outbound {
+            info!("Max outgoing connections set to {}", value);
+            manager.set_max_outbound(value);
+        }
+
+        if let Some(value) = max_inbound {
+            info!("Max incoming connections set to {}", value);
+            manager.set_max_inbound(value);
+        }
+    }
+
+    // Add an anchor and try to connect to it directly
+    // Returns false if it was already an anchor
+    pub async fn add_anchor(&self, addr: SocketAddr) -> bool {
+        let retry = {
+            let mut manager = self.connection_manager.lock().await;
+            if !manager.add_anchor(addr) {
+                return false
+            }
+            manager.should_retry_anchor(&addr, get_current_time_in_millis())
+        };
+
+        if retry {
+            self.try_to_connect_to_peer(addr, true).await;
+        }
+
+        true
+    }
+
+    // Stop reconnecting to this anchor, it is not disconnected
+    // Returns false if it was not an anchor
+    pub async fn remove_anchor(&self, addr: &SocketAddr) -> bool {
+        self.connection_manager.lock().await.remove_anchor(addr)
+    }
+
+    // Get all anchors with their reconnection state
+    pub async fn get_anchors(&self) -> Vec<(SocketAddr, Anchor)> {
+        let manager = self.connection_manager.lock().await;
+        manager.get_anchors().iter().map(|(addr, anchor)| (*addr, *anchor)).collect()
+    }
+
     // Returns the count of peers connected
     pub async fn get_peer_count(&self) -> usize {
         self.peer_list.size().await
@@ -2287,40 +2954,158 @@ impl<S: Storage> P2pServer<S> {
         &self.peer_list
     }
 
+    // Handle the txs hashes propagated by a peer
+    // Request the ones we don't have and update the txs cache of the peer
+    async fn handle_txs_propagated(self: &Arc<Self>, peer: &Arc<Peer>, txs: impl IntoIterator<Item = Hash>) -> Result<(), P2pError> {
+        let common_peers = self.get_common_peers_for(&peer).await;
+        for hash in txs {
+            // peer should not send us twice the same transaction
+            debug!("Received tx hash {} from {}", hash, peer.get_outgoing_address());
+            let already_announced = {
+                let mut txs_cache = peer.get_txs_cache().lock().await;
+
+                if let Some(direction) = txs_cache.get_mut(&hash) {
+                    // Out is only a prediction, it is updated as the peer never announced it to us
+                    let updated = direction.update(Direction::In);
+                    if !updated {
+                        debug!("{} send us a transaction ({}) already tracked by him ({:?})", peer, hash, direction);
+                        // return Err(P2pError::AlreadyTrackedTx(hash))
+                    }
+                    !updated
+                } else {
+                    txs_cache.put(hash.clone(), Direction::In);
+                    false
+                }
+            };
+
+            // Check that the tx is not in mempool or on disk already
+            // A tx already announced by this peer was already requested from it,
+            // if we don't have it anymore it was rejected or dropped, so don't request it again
+            if already_announced {
+                trace!("TX {} was already announced by {}, not requesting it again", hash, peer);
+            } else if !self.blockchain.has_tx(&hash).await? {
+                trace!("Requesting tx {} propagated because we don't have it", hash);
+                if !self.object_tracker.request_object_from_peer(Arc::clone(peer), ObjectRequest::Transaction(hash.clone()), true).await? {
+                    debug!("TX propagated {} was already requested, ignoring", hash);
+                }
+            }
+
+            // Avoid sending the TX propagated to a common peer
+            // because we track peerlist of each peers, we can try to determinate it
+            // iterate over all common peers of this peer broadcaster
+            for common_peer in common_peers.iter() {
+                debug!("{} is a common peer with {}, adding TX {} to its cache", common_peer, peer, hash);
+                let mut txs_cache = common_peer.get_txs_cache().lock().await;
+                // Set it as Out so we don't send it anymore but we can get it one time in case of bad common peer prediction
+                txs_cache.put(hash.clone(), Direction::Out);
+            }
+        }
+
+        Ok(())
+    }
+
     // Broadcast a new transaction hash using propagation packet
     // This is used so we don't overload the network during spam or high transactions count
     // We simply share its hash to nodes and others nodes can check if they have it already or not
+    // The hash is queued to be broadcasted in the next batch, flushed directly if it's full
     pub async fn broadcast_tx_hash(&self, tx: Hash) {
-        debug!("Broadcasting tx hash {}", tx);
+        // standby node doesn't participate in the gossip
+        if self.blockchain.is_standby() {
+            return;
+        }
+
+        debug!("Queueing tx hash {} for broadcast", tx);
+        let txs = {
+            let mut queue = self.txs_broadcast_queue.lock().await;
+            queue.insert(tx);
+            if queue.len() < P2P_TXS_BATCH_MAX_SIZE {
+                return;
+            }
+            std::mem::take(&mut *queue)
+        };
+
+        self.broadcast_txs_hashes(txs).await;
+    }
+
+    // Broadcast again the txs hashes to all our peers, including those having them in their txs cache
+    // A peer already having a tx in its mempool will not request it again
+    pub async fn rebroadcast_txs_hashes(&self, txs: IndexSet<Hash>) {
+        if self.blockchain.is_standby() {
+            return;
+        }
+
+        for peer in self.peer_list.get_cloned_peers().await {
+            let mut txs_cache = peer.get_txs_cache().lock().await;
+            for tx in txs.iter() {
+                txs_cache.pop(tx);
+            }
+        }
+
+        self.broadcast_txs_hashes(txs).await;
+    }
+
+    // Broadcast the txs hashes to all our peers
+    // Each peer only receives the hashes not present in its txs cache
+    async fn broadcast_txs_hashes(&self, txs: IndexSet<Hash>) {
+        debug!("Broadcasting {} txs hashes", txs.len());
         let ping = self.build_generic_ping_packet().await;
-        debug!("Ping packet has been generated for tx broadcast");
+        debug!("Ping packet has been generated for txs broadcast");
         let current_topoheight = ping.get_topoheight();
-        let packet = Packet::TransactionPropagation(PacketWrapper::new(Cow::Borrowed(&tx), Cow::Owned(ping)));
-        // transform packet to bytes (so we don't need to transform it for each peer)
-        let bytes = Bytes::from(packet.to_bytes());
-        trace!("Locking peer list for tx broadcast");
+        // transform packet to bytes (so we don't need to transform it for each peer knowing none of them)
+        let bytes = Bytes::from(Packet::TransactionsPropagation(PacketWrapper::new(Cow::Owned(TxsPropagation::new(Cow::Borrowed(&txs))), Cow::Borrowed(&ping))).to_bytes());
+        trace!("Locking peer list for txs broadcast");
         let peers = self.peer_list.get_cloned_peers().await;
-        trace!("Lock acquired for tx broadcast");
+        trace!("Lock acquired for txs broadcast");
 
+        let stable_limit = self.blockchain.get_chain_params().stable_limit;
         for peer in peers {
             // check that the peer is not too far from us
             // otherwise we may spam him for nothing
             let peer_topoheight = peer.get_topoheight();
-            if (peer_topoheight >= current_topoheight && peer_topoheight - current_topoheight < STABLE_LIMIT) || (current_topoheight >= peer_topoheight && current_topoheight - peer_topoheight < STABLE_LIMIT) {
-                trace!("Peer {} is not too far from us, checking cache for tx hash {}", peer, tx);
+            if !((peer_topoheight >= current_topoheight && peer_topoheight - current_topoheight < stable_limit) || (current_topoheight >= peer_topoheight && current_topoheight - peer_topoheight < stable_limit)) {
+                continue;
+            }
+
+            trace!("Peer {} is not too far from us, checking cache for {} txs hashes", peer, txs.len());
+            let missing = {
                 let mut txs_cache = peer.get_txs_cache().lock().await;
-                trace!("Cache locked for tx hash {}", tx);
-                // check that we didn't already send this tx to this peer or that he don't already have it
-                if !txs_cache.contains(&tx) {
+                let mut missing = IndexSet::new();
+                for tx in txs.iter() {
+                    // check that we didn't already send this tx to this peer or that he don't already have it
+                    if !txs_cache.contains(tx) {
+                        // Set it as "In" so we can't get it back as we are the sender of it
+                        txs_cache.put(tx.clone(), Direction::In);
+                        missing.insert(tx.clone());
+                    } else {
+                        trace!("{} have tx hash {} in cache, skipping", peer, tx);
+                    }
+                }
+                missing
+            };
+
+            if missing.is_empty() {
+                continue;
+            }
+
+            if peer.get_negotiated_capabilities().contains(Capabilities::TXS_PROPAGATION) {
+                trace!("Broadcasting {} txs hashes to {}", missing.len(), peer);
+                let res = if missing.len() == txs.len() {
+                    peer.send_bytes(bytes.clone()).await
+                } else {
+                    peer.send_packet(Packet::TransactionsPropagation(PacketWrapper::new(Cow::Owned(TxsPropagation::new(Cow::Owned(missing))), Cow::Borrowed(&ping)))).await
+                };
+
+                if let Err(e) = res {
+                    error!("Error while broadcasting txs hashes to {}: {}", peer, e);
+                }
+            } else {
+                // peer doesn't support batch, send them one by one
+                for tx in missing {
                     trace!("Broadcasting tx hash {} to {}", tx, peer);
-                    if let Err(e) = peer.send_bytes(bytes.clone()).await {
+                    if let Err(e) = peer.send_packet(Packet::TransactionPropagation(PacketWrapper::new(Cow::Borrowed(&tx), Cow::Borrowed(&ping)))).await {
                         error!("Error while broadcasting tx hash {} to {}: {}", tx, peer, e);
+                        break;
                     }
-                    trace!("Adding tx hash {} to cache for {}", tx, peer);
-                    // Set it as "In" so we can't get it back as we are the sender of it
-                    txs_cache.put(tx.clone(), Direction::In);
-                } else {
-                    trace!("{} have tx hash {} in cache, skipping", peer, tx);
                 }
             }
         }
@@ -2328,6 +3113,12 @@ impl<S: Storage> P2pServer<S> {
 
     // broadcast block to all peers that can accept directly this new block
     pub async fn broadcast_block(&self, block: &BlockHeader, cumulative_difficulty: CumulativeDifficulty, our_topoheight: u64, our_height: u64, pruned_topoheight: Option<u64>, hash: &Hash, lock: bool) {
+        // standby node doesn't participate in the gossip
+        if self.blockchain.is_standby() {
+            debug!("Standby mode, block {} is not broadcasted", hash);
+            return;
+        }
+
         debug!("Broadcasting block {} at height {}", hash, block.get_height());
         // we build the ping packet ourself this time (we have enough data for it)
         // because this function can be call from Blockchain, which would lead to a deadlock
@@ -2338,7 +3129,11 @@ impl<S: Storage> P2pServer<S> {
 
         trace!("Locking peer list for broadcasting block {}", hash);
         trace!("start broadcasting block {} to all peers", hash);
-        for peer in self.peer_list.get_cloned_peers().await {
+        // send it first to the lowest latency peers, peers not measured yet are last
+        let mut peers: Vec<_> = self.peer_list.get_cloned_peers().await.into_iter().collect();
+        peers.sort_by_key(|peer| peer.get_latency().unwrap_or(u64::MAX));
+        let stable_limit = self.blockchain.get_chain_params().stable_limit;
+        for peer in peers {
             // if the peer can directly accept this new block, send it
             let peer_height = peer.get_height();
 
@@ -2347,7 +3142,7 @@ impl<S: Storage> P2pServer<S> {
             // or, check that peer height as difference of maximum 1 block
             // (block height is always + 1 above the highest tip height, so we can just check that peer height is not above block height + 1, it's enough in 90% of time)
             // chain can accept old blocks (up to STABLE_LIMIT) but new blocks only N+1
-            if (peer_height >= block.get_height() && peer_height - block.get_height() < STABLE_LIMIT) || (peer_height <= block.get_height() && block.get_height() - peer_height <= 1) {
+            if (peer_height >= block.get_height() && peer_height - block.get_height() < stable_limit) || (peer_height <= block.get_height() && block.get_height() - peer_height <= 1) {
                 trace!("locking blocks propagation for peer {}", peer);
                 let mut blocks_propagation = peer.get_blocks_propagation().lock().await;
                 trace!("end locking blocks propagation for peer {}", peer);
@@ -2356,6 +3151,8 @@ impl<S: Storage> P2pServer<S> {
                     // we broadcasted to him, add it to the cache
                     // he should not send it back to us if it's a block found by us
                     blocks_propagation.put(hash.clone(), if lock { Direction::Both } else { Direction::Out });
+                    // he will get the txs with the block, no need to announce them
+                    peer.mark_txs_as_known(block.get_txs_hashes(), Direction::Out).await;
 
                     debug!("Broadcast {} to {} (lock: {})", hash, peer, lock);
                     if let Err(e) = peer.send_bytes(packet_block_bytes.clone()).await {
@@ -0,0 +1,75 @@
+use log::trace;
+use xelis_common::{
+    api::daemon::PeerChainStatus,
+    crypto::Hash,
+    difficulty::CumulativeDifficulty,
+    time::TimestampMillis
+};
+use crate::core::storage::Storage;
+
+// Chain view advertised by a peer at a given time
+// Used to detect early a chain split between us and our peers
+// Its status is only computed when requested to not read the storage on each ping
+#[derive(Debug, Clone)]
+pub struct PeerChainView {
+    // when we received it
+    timestamp: TimestampMillis,
+    // our topoheight when we received it
+    our_topoheight: u64,
+    topoheight: u64,
+    top_hash: Hash,
+    cumulative_difficulty: CumulativeDifficulty
+}
+
+impl PeerChainView {
+    pub fn new(timestamp: TimestampMillis, our_topoheight: u64, topoheight: u64, top_hash: Hash, cumulative_difficulty: CumulativeDifficulty) -> Self {
+        Self {
+            timestamp,
+            our_topoheight,
+            topoheight,
+            top_hash,
+            cumulative_difficulty
+        }
+    }
+
+    pub fn get_timestamp(&self) -> TimestampMillis {
+        self.timestamp
+    }
+
+    pub fn get_topoheight(&self) -> u64 {
+        self.topoheight
+    }
+
+    pub fn get_top_hash(&self) -> &Hash {
+        &self.top_hash
+    }
+
+    pub fn get_cumulative_difficulty(&self) -> &CumulativeDifficulty {
+        &self.cumulative_difficulty
+    }
+
+    // Status compared to our chain at the time we received it
+    pub async fn get_status<S: Storage>(&self, storage: &S) -> PeerChainStatus {
+        get_chain_status(storage, self.our_topoheight, self.topoheight, &self.top_hash).await
+    }
+}
+
+// Compare the top block advertised by a peer with our chain
+// If the peer is ahead of us, we can't verify it until we sync its blocks
+async fn get_chain_status<S: Storage>(storage: &S, our_topoheight: u64, topoheight: u64, top_hash: &Hash) -> PeerChainStatus {
+    trace!("get chain status for peer at topoheight {} with top hash {}", topoheight, top_hash);
+    if topoheight > our_topoheight {
+        return PeerChainStatus::Ahead
+    }
+
+    // block may be pruned from our chain
+    match storage.get_hash_at_topo_height(topoheight).await {
+        Ok(hash) if hash == *top_hash => if topoheight == our_topoheight {
+            PeerChainStatus::Synced
+        } else {
+            PeerChainStatus::Behind
+        },
+        Ok(_) => PeerChainStatus::Diverged,
+        Err(_) => PeerChainStatus::Unknown
+    }
+}
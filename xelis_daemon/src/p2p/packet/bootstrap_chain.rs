@@ -35,7 +35,7 @@ use crate::config::CHAIN_SYNC_REQUEST_MAX_BLOCKS;
 
 pub const MAX_ITEMS_PER_PAGE: usize = 1024;
 
-#[derive(Debug)]
+#[derive(Debug, Serializer)]
 pub struct BlockMetadata {
     // Hash of the block
     pub hash: Hash,
@@ -65,44 +65,6 @@ impl PartialEq for BlockMetadata {
 
 impl Eq for BlockMetadata {}
 
-impl Serializer for BlockMetadata {
-    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
-        let hash = reader.read_hash()?;
-        let supply = reader.read_u64()?;
-        let reward = reader.read_u64()?;
-        let difficulty = Difficulty::read(reader)?;
-        let cumulative_difficulty = CumulativeDifficulty::read(reader)?;
-        let p = VarUint::read(reader)?;
-
-        Ok(Self {
-            hash,
-            supply,
-            reward,
-            difficulty,
-            cumulative_difficulty,
-            p
-        })
-    }
-
-    fn write(&self, writer: &mut Writer) {
-        writer.write_hash(&self.hash);
-        writer.write_u64(&self.supply);
-        writer.write_u64(&self.reward);
-        self.difficulty.write(writer);
-        self.cumulative_difficulty.write(writer);
-        self.p.write(writer);
-    }
-
-    fn size(&self) -> usize {
-        self.hash.size()
-        + self.supply.size()
-        + self.reward.size()
-        + self.difficulty.size()
-        + self.cumulative_difficulty.size()
-        + self.p.size()
-    }
-}
-
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Ord, PartialOrd)]
 pub enum StepKind {
     ChainInfo,
@@ -467,7 +429,7 @@ impl Serializer for BootstrapChainRequest<'_> {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serializer)]
 pub struct BootstrapChainResponse {
     response: StepResponse
 }
@@ -487,17 +449,3 @@ impl BootstrapChainResponse {
         self.response
     }
 }
-
-impl Serializer for BootstrapChainResponse {
-    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
-        Ok(Self::new(StepResponse::read(reader)?))
-    }
-
-    fn write(&self, writer: &mut Writer) {
-        self.response.write(writer);
-    }
-
-    fn size(&self) -> usize {
-        self.response.size()
-    }
-}
@@ -18,7 +18,7 @@ use crate::config::{
 };
 use std::hash::{Hash as StdHash, Hasher};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serializer)]
 pub struct BlockId {
     hash: Hash,
     topoheight: u64
@@ -59,21 +59,6 @@ impl PartialEq for BlockId {
 
 impl Eq for BlockId {}
 
-impl Serializer for BlockId {
-    fn write(&self, writer: &mut Writer) {
-        writer.write_hash(self.get_hash());
-        writer.write_u64(&self.get_topoheight());
-    }
-
-    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
-        Ok(Self::new(reader.read_hash()?, reader.read_u64()?))
-    }
-
-    fn size(&self) -> usize {
-        self.hash.size() + self.topoheight.size()
-    }
-}
-
 #[derive(Clone, Debug)]
 pub struct ChainRequest {
     blocks: IndexSet<BlockId>,
@@ -143,7 +128,7 @@ impl Serializer for ChainRequest {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serializer)]
 pub struct CommonPoint {
     hash: Hash,
     topoheight: u64
@@ -166,23 +151,6 @@ impl CommonPoint {
     }
 }
 
-impl Serializer for CommonPoint {
-    fn write(&self, writer: &mut Writer) {
-        writer.write_hash(&self.hash);
-        writer.write_u64(&self.topoheight);
-    }
-
-    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
-        let hash = reader.read_hash()?;
-        let topoheight = reader.read_u64()?;
-        Ok(Self { hash, topoheight })
-    }
-
-    fn size(&self) -> usize {
-        self.hash.size() + self.topoheight.size()
-    }
-}
-
 #[derive(Debug)]
 pub struct ChainResponse {
     // Common point between us and the peer
@@ -1,5 +1,6 @@
 use log::debug;
 use xelis_common::{
+    capabilities::Capabilities,
     crypto::Hash,
     difficulty::CumulativeDifficulty,
     network::Network,
@@ -52,13 +53,20 @@ pub struct Handshake<'a> {
     cumulative_difficulty: Cow<'a, CumulativeDifficulty>,
     // By default it's true, and peer allow to be shared to others and/or through API
     // If false, we must not share it
-    can_be_shared: bool
+    can_be_shared: bool,
+    // features and runtime modes enabled on the node
+    capabilities: Capabilities,
+    // range of P2P protocol versions supported
+    min_protocol_version: u16,
+    max_protocol_version: u16,
+    // port of the TLS P2P listener if enabled
+    tls_port: Option<u16>
 } // Server reply with his own list of peers, but we remove all already known by requester for the response.
 
 impl<'a> Handshake<'a> {
     pub const MAX_LEN: usize = 16;
 
-    pub fn new(version: Cow<'a, String>, network: Network, node_tag: Cow<'a, Option<String>>, network_id: Cow<'a, [u8; 16]>, peer_id: u64, local_port: u16, utc_time: TimestampSeconds, topoheight: u64, height: u64, pruned_topoheight: Option<u64>, top_hash: Cow<'a, Hash>, genesis_hash: Cow<'a, Hash>, cumulative_difficulty: Cow<'a, CumulativeDifficulty>, can_be_shared: bool) -> Self {
+    pub fn new(version: Cow<'a, String>, network: Network, node_tag: Cow<'a, Option<String>>, network_id: Cow<'a, [u8; 16]>, peer_id: u64, local_port: u16, utc_time: TimestampSeconds, topoheight: u64, height: u64, pruned_topoheight: Option<u64>, top_hash: Cow<'a, Hash>, genesis_hash: Cow<'a, Hash>, cumulative_difficulty: Cow<'a, CumulativeDifficulty>, can_be_shared: bool, capabilities: Capabilities, min_protocol_version: u16, max_protocol_version: u16, tls_port: Option<u16>) -> Self {
         debug_assert!(version.len() > 0 && version.len() <= Handshake::MAX_LEN);
         // version cannot be greater than 16 chars
         if let Some(node_tag) = node_tag.as_ref() {
@@ -80,14 +88,30 @@ impl<'a> Handshake<'a> {
             top_hash,
             genesis_hash,
             cumulative_difficulty,
-            can_be_shared
+            can_be_shared,
+            capabilities,
+            min_protocol_version,
+            max_protocol_version,
+            tls_port
         }
     }
 
     // Create a new peer using its connection and this handshake packet
-    pub fn create_peer(self, connection: Connection, priority: bool, peer_list: SharedPeerList) -> (Peer, Rx) {
+    // Protocol version and capabilities are the ones negotiated with the peer
+    pub fn create_peer(self, connection: Connection, priority: bool, peer_list: SharedPeerList, protocol_version: u16, negotiated_capabilities: Capabilities) -> (Peer, Rx) {
         let peers = HashSet::new();
-        Peer::new(connection, self.get_peer_id(), self.node_tag.into_owned(), self.local_port, self.version.into_owned(), self.top_hash.into_owned(), self.topoheight, self.height, self.pruned_topoheight, priority, self.cumulative_difficulty.into_owned(), peer_list, peers, self.can_be_shared)
+        Peer::new(connection, self.get_peer_id(), self.node_tag.into_owned(), self.local_port, self.version.into_owned(), self.top_hash.into_owned(), self.topoheight, self.height, self.pruned_topoheight, priority, self.cumulative_difficulty.into_owned(), peer_list, peers, self.can_be_shared, self.capabilities, protocol_version, negotiated_capabilities, self.tls_port)
+    }
+
+    // Select the highest protocol version supported by both sides
+    // Returns None if the ranges don't overlap
+    pub fn negotiate_protocol_version(&self, min_protocol_version: u16, max_protocol_version: u16) -> Option<u16> {
+        let version = max_protocol_version.min(self.max_protocol_version);
+        if version >= min_protocol_version.max(self.min_protocol_version) {
+            Some(version)
+        } else {
+            None
+        }
     }
 
     pub fn get_version(&self) -> &String {
@@ -133,6 +157,22 @@ impl<'a> Handshake<'a> {
     pub fn get_pruned_topoheight(&self) -> &Option<u64> {
         &self.pruned_topoheight
     }
+
+    pub fn get_capabilities(&self) -> Capabilities {
+        self.capabilities
+    }
+
+    pub fn get_min_protocol_version(&self) -> u16 {
+        self.min_protocol_version
+    }
+
+    pub fn get_max_protocol_version(&self) -> u16 {
+        self.max_protocol_version
+    }
+
+    pub fn get_tls_port(&self) -> Option<u16> {
+        self.tls_port
+    }
 }
 
 impl Serializer for Handshake<'_> {
@@ -158,6 +198,10 @@ impl Serializer for Handshake<'_> {
         writer.write_hash(&self.genesis_hash); // Genesis Hash
         self.cumulative_difficulty.write(writer); // Cumulative Difficulty
         writer.write_bool(self.can_be_shared); // Can be shared
+        self.capabilities.write(writer); // Capabilities
+        writer.write_u16(self.min_protocol_version); // Min protocol version
+        writer.write_u16(self.max_protocol_version); // Max protocol version
+        self.tls_port.write(writer); // TLS port
     }
 
     fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
@@ -199,8 +243,20 @@ impl Serializer for Handshake<'_> {
         let genesis_hash = reader.read_hash()?;
         let cumulative_difficulty = CumulativeDifficulty::read(reader)?;
         let can_be_shared = reader.read_bool()?;
+        // Capabilities are optional to stay compatible with older nodes
+        let capabilities = reader.read_trailing()?
+            .unwrap_or_else(Capabilities::empty);
+        // Protocol versions are optional too, older nodes are on version 0
+        let (min_protocol_version, max_protocol_version) = reader.read_trailing()?
+            .unwrap_or((0, 0));
+        if min_protocol_version > max_protocol_version {
+            debug!("Invalid protocol versions range ({}-{}) in handshake packet", min_protocol_version, max_protocol_version);
+            return Err(ReaderError::InvalidValue)
+        }
+        // TLS port is optional, older nodes don't have a TLS listener
+        let tls_port = reader.read_trailing()?.flatten();
 
-        Ok(Handshake::new(Cow::Owned(version), network, Cow::Owned(node_tag), Cow::Owned(network_id), peer_id, local_port, utc_time, topoheight, height, pruned_topoheight, Cow::Owned(top_hash), Cow::Owned(genesis_hash), Cow::Owned(cumulative_difficulty), can_be_shared))
+        Ok(Handshake::new(Cow::Owned(version), network, Cow::Owned(node_tag), Cow::Owned(network_id), peer_id, local_port, utc_time, topoheight, height, pruned_topoheight, Cow::Owned(top_hash), Cow::Owned(genesis_hash), Cow::Owned(cumulative_difficulty), can_be_shared, capabilities, min_protocol_version, max_protocol_version, tls_port))
     }
 
     fn size(&self) -> usize {
@@ -231,7 +287,14 @@ impl Serializer for Handshake<'_> {
         // Cumulative Difficulty
         self.cumulative_difficulty.size() +
         // Can be shared
-        self.can_be_shared.size()
+        self.can_be_shared.size() +
+        // Capabilities
+        self.capabilities.size() +
+        // Protocol versions
+        self.min_protocol_version.size() +
+        self.max_protocol_version.size() +
+        // TLS port
+        self.tls_port.size()
     }
 }
 
@@ -246,4 +309,37 @@ impl Display for Handshake<'_> {
         };
         write!(f, "Handshake[version: {}, node tag: {}, network_id: {}, peer_id: {}, utc_time: {}, block_height: {}, block_top_hash: {}]", self.get_version(), node_tag, hex::encode(self.get_network_id()), self.get_peer_id(), self.get_utc_time(), self.get_block_height(), self.get_block_top_hash())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use xelis_common::serializer::compat::read_from_older;
+    use super::*;
+
+    fn handshake() -> Handshake<'static> {
+        let mut capabilities = Capabilities::empty();
+        capabilities.insert(Capabilities::PEER_EXCHANGE);
+
+        Handshake::new(Cow::Owned("1.0.0".to_owned()), Network::Mainnet, Cow::Owned(None), Cow::Owned([0u8; 16]), 42, 2801, 100, 10, 8, None, Cow::Owned(Hash::zero()), Cow::Owned(Hash::zero()), Cow::Owned(CumulativeDifficulty::zero()), true, capabilities, 0, 1, Some(2802))
+    }
+
+    #[test]
+    fn test_handshake_without_tls_port() {
+        // TLS port is written as Some(u16)
+        let read = read_from_older(&handshake(), 3);
+        assert!(read.get_capabilities().contains(Capabilities::PEER_EXCHANGE));
+        assert_eq!(read.get_max_protocol_version(), 1);
+        assert_eq!(read.get_tls_port(), None);
+    }
+
+    #[test]
+    fn test_handshake_without_capabilities() {
+        // capabilities, protocol versions and TLS port
+        let read = read_from_older(&handshake(), 8 + 2 * 2 + 3);
+        assert_eq!(read.get_capabilities(), Capabilities::empty());
+        assert_eq!(read.get_min_protocol_version(), 0);
+        assert_eq!(read.get_max_protocol_version(), 0);
+        assert_eq!(read.get_tls_port(), None);
+        assert_eq!(read.get_peer_id(), 42);
+    }
+}
@@ -0,0 +1,21 @@
+use xelis_common::serializer::Serializer;
+
+// Packet used to measure the round-trip time with a peer
+// A LatencyPing is answered by a LatencyPong using the same id
+// The id is random so a peer can't answer before receiving the ping
+#[derive(Debug, Serializer)]
+pub struct LatencyPing {
+    id: u64
+}
+
+impl LatencyPing {
+    pub fn new(id: u64) -> Self {
+        Self {
+            id
+        }
+    }
+
+    pub fn get_id(&self) -> u64 {
+        self.id
+    }
+}
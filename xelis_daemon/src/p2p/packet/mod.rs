@@ -5,6 +5,9 @@ pub mod object;
 pub mod inventory;
 pub mod bootstrap_chain;
 pub mod peer_disconnected;
+pub mod peer_exchange;
+pub mod txs;
+pub mod latency;
 
 use self::bootstrap_chain::{BootstrapChainRequest, BootstrapChainResponse};
 use self::inventory::{NotifyInventoryResponse, NotifyInventoryRequest};
@@ -12,7 +15,10 @@ use self::object::{ObjectRequest, ObjectResponse};
 use self::chain::{ChainRequest, ChainResponse};
 use self::handshake::Handshake;
 use self::peer_disconnected::PacketPeerDisconnected;
+use self::peer_exchange::{PeerExchangeRequest, PeerExchangeResponse};
 use self::ping::Ping;
+use self::txs::{GetTxs, TxsResponse, TxsPropagation};
+use self::latency::LatencyPing;
 use std::borrow::Cow;
 use log::{debug, trace};
 use xelis_common::{
@@ -38,6 +44,13 @@ const NOTIFY_INV_RESPONSE_ID: u8 = 10;
 const BOOTSTRAP_CHAIN_REQUEST_ID: u8 = 11;
 const BOOTSTRAP_CHAIN_RESPONSE_ID: u8 = 12;
 const PEER_DISCONNECTED_ID: u8 = 13;
+const PEER_EXCHANGE_REQUEST_ID: u8 = 14;
+const PEER_EXCHANGE_RESPONSE_ID: u8 = 15;
+const GET_TXS_ID: u8 = 16;
+const TXS_RESPONSE_ID: u8 = 17;
+const TXS_PROPAGATION_ID: u8 = 18;
+const LATENCY_PING_ID: u8 = 19;
+const LATENCY_PONG_ID: u8 = 20;
 
 // PacketWrapper allows us to link any Packet to a Ping
 #[derive(Debug)]
@@ -54,6 +67,10 @@ impl<'a, T: Serializer + Clone> PacketWrapper<'a, T> {
         }
     }
 
+    pub fn get_packet(&self) -> &T {
+        &self.packet
+    }
+
     pub fn consume(self) -> (Cow<'a, T>, Cow<'a, Ping<'a>>) {
         (self.packet, self.ping)
     }
@@ -86,6 +103,10 @@ pub enum Packet<'a> {
     // so the peer that already have this TX in mempool don't have to read it again
     // imo: can be useful when the network is spammed by alot of txs
     TransactionPropagation(PacketWrapper<'a, Hash>),
+    // same as above but for several txs hashes at once
+    TransactionsPropagation(PacketWrapper<'a, TxsPropagation<'a>>),
+    // compact block: only the header with its txs hashes is sent
+    // receiver reconstruct it from its mempool and request the missing txs using GetTxs
     BlockPropagation(PacketWrapper<'a, BlockHeader>),
     ChainRequest(PacketWrapper<'a, ChainRequest>),
     ChainResponse(ChainResponse),
@@ -97,6 +118,15 @@ pub enum Packet<'a> {
     BootstrapChainRequest(BootstrapChainRequest<'a>),
     BootstrapChainResponse(BootstrapChainResponse),
     PeerDisconnected(PacketPeerDisconnected),
+    // Peer exchange (PEX) to discover new peers
+    PeerExchangeRequest(PeerExchangeRequest),
+    PeerExchangeResponse(PeerExchangeResponse<'a>),
+    // Request the missing txs of a propagated block in one round-trip
+    GetTxs(GetTxs<'a>),
+    TxsResponse(TxsResponse<'a>),
+    // Round-trip time measurement
+    LatencyPing(LatencyPing),
+    LatencyPong(LatencyPing),
     // Encryption
     KeyExchange(Cow<'a, EncryptionKey>),
 }
@@ -106,6 +136,7 @@ impl Packet<'_> {
         match self {
             Packet::Handshake(_) => HANDSHAKE_ID,
             Packet::TransactionPropagation(_) => TX_PROPAGATION_ID,
+            Packet::TransactionsPropagation(_) => TXS_PROPAGATION_ID,
             Packet::BlockPropagation(_) => BLOCK_PROPAGATION_ID,
             Packet::ChainRequest(_) => CHAIN_REQUEST_ID,
             Packet::ChainResponse(_) => CHAIN_RESPONSE_ID,
@@ -117,9 +148,30 @@ impl Packet<'_> {
             Packet::BootstrapChainRequest(_) => BOOTSTRAP_CHAIN_REQUEST_ID,
             Packet::BootstrapChainResponse(_) => BOOTSTRAP_CHAIN_RESPONSE_ID,
             Packet::PeerDisconnected(_) => PEER_DISCONNECTED_ID,
+            Packet::PeerExchangeRequest(_) => PEER_EXCHANGE_REQUEST_ID,
+            Packet::PeerExchangeResponse(_) => PEER_EXCHANGE_RESPONSE_ID,
+            Packet::GetTxs(_) => GET_TXS_ID,
+            Packet::TxsResponse(_) => TXS_RESPONSE_ID,
+            Packet::LatencyPing(_) => LATENCY_PING_ID,
+            Packet::LatencyPong(_) => LATENCY_PONG_ID,
             Packet::KeyExchange(_) => KEY_EXCHANGE_ID,
         }
     }
+
+    // P2P protocol version in which the packet was introduced
+    // It can only be exchanged with peers which negotiated this version or a newer one
+    pub fn get_protocol_version(&self) -> u16 {
+        match self {
+            Packet::PeerExchangeRequest(_)
+            | Packet::PeerExchangeResponse(_)
+            | Packet::GetTxs(_)
+            | Packet::TxsResponse(_)
+            | Packet::TransactionsPropagation(_)
+            | Packet::LatencyPing(_)
+            | Packet::LatencyPong(_) => 1,
+            _ => 0
+        }
+    }
 }
 
 impl<'a> Serializer for Packet<'a> {
@@ -130,6 +182,7 @@ impl<'a> Serializer for Packet<'a> {
             KEY_EXCHANGE_ID => Packet::KeyExchange(Cow::Owned(EncryptionKey::read(reader)?)),
             HANDSHAKE_ID => Packet::Handshake(Cow::Owned(Handshake::read(reader)?)),
             TX_PROPAGATION_ID => Packet::TransactionPropagation(PacketWrapper::read(reader)?),
+            TXS_PROPAGATION_ID => Packet::TransactionsPropagation(PacketWrapper::read(reader)?),
             BLOCK_PROPAGATION_ID => Packet::BlockPropagation(PacketWrapper::read(reader)?),
             CHAIN_REQUEST_ID => Packet::ChainRequest(PacketWrapper::read(reader)?),
             CHAIN_RESPONSE_ID => Packet::ChainResponse(ChainResponse::read(reader)?),
@@ -141,6 +194,12 @@ impl<'a> Serializer for Packet<'a> {
             BOOTSTRAP_CHAIN_REQUEST_ID => Packet::BootstrapChainRequest(BootstrapChainRequest::read(reader)?),
             BOOTSTRAP_CHAIN_RESPONSE_ID => Packet::BootstrapChainResponse(BootstrapChainResponse::read(reader)?),
             PEER_DISCONNECTED_ID => Packet::PeerDisconnected(PacketPeerDisconnected::read(reader)?),
+            PEER_EXCHANGE_REQUEST_ID => Packet::PeerExchangeRequest(PeerExchangeRequest::read(reader)?),
+            PEER_EXCHANGE_RESPONSE_ID => Packet::PeerExchangeResponse(PeerExchangeResponse::read(reader)?),
+            GET_TXS_ID => Packet::GetTxs(GetTxs::read(reader)?),
+            TXS_RESPONSE_ID => Packet::TxsResponse(TxsResponse::read(reader)?),
+            LATENCY_PING_ID => Packet::LatencyPing(LatencyPing::read(reader)?),
+            LATENCY_PONG_ID => Packet::LatencyPong(LatencyPing::read(reader)?),
             id => {
                 debug!("invalid packet id received: {}", id);
                 return Err(ReaderError::InvalidValue)
@@ -159,6 +218,7 @@ impl<'a> Serializer for Packet<'a> {
             Packet::KeyExchange(key) => (KEY_EXCHANGE_ID, key),
             Packet::Handshake(handshake) => (HANDSHAKE_ID, handshake.as_ref()),
             Packet::TransactionPropagation(tx) => (TX_PROPAGATION_ID, tx),
+            Packet::TransactionsPropagation(txs) => (TXS_PROPAGATION_ID, txs),
             Packet::BlockPropagation(block) => (BLOCK_PROPAGATION_ID, block),
             Packet::ChainRequest(request) => (CHAIN_REQUEST_ID, request),
             Packet::ChainResponse(response) => (CHAIN_RESPONSE_ID, response),
@@ -170,10 +230,120 @@ impl<'a> Serializer for Packet<'a> {
             Packet::BootstrapChainRequest(request) => (BOOTSTRAP_CHAIN_REQUEST_ID, request),
             Packet::BootstrapChainResponse(response) => (BOOTSTRAP_CHAIN_RESPONSE_ID, response),
             Packet::PeerDisconnected(disconnected) => (PEER_DISCONNECTED_ID, disconnected),
+            Packet::PeerExchangeRequest(request) => (PEER_EXCHANGE_REQUEST_ID, request),
+            Packet::PeerExchangeResponse(response) => (PEER_EXCHANGE_RESPONSE_ID, response),
+            Packet::GetTxs(request) => (GET_TXS_ID, request),
+            Packet::TxsResponse(response) => (TXS_RESPONSE_ID, response),
+            Packet::LatencyPing(ping) => (LATENCY_PING_ID, ping),
+            Packet::LatencyPong(pong) => (LATENCY_PONG_ID, pong),
         };
 
         let packet = serializer.to_bytes();
         writer.write_u8(id);
         writer.write_bytes(&packet);
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use xelis_common::{
+        capabilities::Capabilities,
+        serializer::compat::read_golden
+    };
+    use super::*;
+
+    // Hex of `count` zero bytes
+    fn zeros(count: usize) -> String {
+        "00".repeat(count)
+    }
+
+    #[test]
+    fn test_handshake_golden() {
+        // Handshake as written by the first release, without capabilities, protocol versions and TLS port
+        let golden = format!(
+            "{}{}{}{}{}{}{}{}{}{}{}{}{}{}",
+            // packet id
+            "01",
+            // version "1.0.0"
+            "05312e302e30",
+            // mainnet, no node tag
+            "0000",
+            // network id
+            zeros(16),
+            // peer id
+            "000000000000002a",
+            // local port
+            "0af1",
+            // utc time
+            "0000000000000064",
+            // topoheight
+            "000000000000000a",
+            // height
+            "0000000000000008",
+            // not pruned
+            "00",
+            // top hash and genesis hash
+            zeros(32),
+            zeros(32),
+            // cumulative difficulty
+            "00",
+            // can be shared
+            "01"
+        );
+
+        let bytes = hex::decode(&golden).unwrap();
+        let mut reader = Reader::new(&bytes);
+        let packet = Packet::read(&mut reader).unwrap();
+        assert_eq!(reader.size(), 0);
+        let Packet::Handshake(handshake) = packet else {
+            panic!("expected a handshake packet")
+        };
+
+        assert_eq!(handshake.get_version(), "1.0.0");
+        assert_eq!(handshake.get_peer_id(), 42);
+        assert_eq!(handshake.get_topoheight(), 10);
+        assert_eq!(handshake.get_block_height(), 8);
+        // older nodes are on protocol version 0 without capabilities
+        assert_eq!(handshake.get_capabilities(), Capabilities::empty());
+        assert_eq!(handshake.get_min_protocol_version(), 0);
+        assert_eq!(handshake.get_max_protocol_version(), 0);
+        assert_eq!(handshake.get_tls_port(), None);
+    }
+
+    #[test]
+    fn test_ping_golden() {
+        let golden = format!(
+            "{}{}{}{}{}{}{}",
+            // packet id
+            "06",
+            // top hash
+            zeros(32),
+            // topoheight and height
+            "000000000000000a0000000000000008",
+            // not pruned
+            "00",
+            // cumulative difficulty
+            "00",
+            // one peer: 127.0.0.1:2801
+            "01",
+            "007f0000010af1"
+        );
+
+        let packet: Packet = read_golden(&golden);
+        let Packet::Ping(ping) = packet else {
+            panic!("expected a ping packet")
+        };
+
+        assert_eq!(ping.get_topoheight(), 10);
+        assert_eq!(ping.get_height(), 8);
+        assert_eq!(ping.get_peers().len(), 1);
+        assert!(ping.get_peers().contains(&"127.0.0.1:2801".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_packet_protocol_version() {
+        assert_eq!(Packet::PeerExchangeRequest(PeerExchangeRequest::new(8)).get_protocol_version(), 1);
+        assert_eq!(Packet::LatencyPing(LatencyPing::new(1)).get_protocol_version(), 1);
+        assert_eq!(Packet::ObjectRequest(Cow::Owned(ObjectRequest::Block(Hash::zero()))).get_protocol_version(), 0);
+    }
+}
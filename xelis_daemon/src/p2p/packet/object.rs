@@ -1,8 +1,7 @@
 use xelis_common::{
     crypto::{
         Hash,
-        Hashable,
-        HASH_SIZE
+        Hashable
     },
     block::{
         Block,
@@ -18,7 +17,7 @@ use xelis_common::{
 };
 use std::{borrow::Cow, fmt::{Display, Formatter, self}};
 
-#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Serializer)]
 pub enum ObjectRequest {
     Block(Hash),
     BlockHeader(Hash),
@@ -35,39 +34,6 @@ impl ObjectRequest {
     }
 }
 
-impl Serializer for ObjectRequest {
-    fn write(&self, writer: &mut Writer) {
-        match &self {
-            Self::Block(hash) => {
-                writer.write_u8(0);
-                writer.write_hash(hash);
-            },
-            Self::BlockHeader(hash) => {
-                writer.write_u8(1);
-                writer.write_hash(hash);
-            },
-            Self::Transaction(hash) => {
-                writer.write_u8(2);
-                writer.write_hash(hash);
-            }
-        }
-    }
-
-    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
-        let id = reader.read_u8()?;
-        Ok(match id {
-            0 => ObjectRequest::Block(reader.read_hash()?),
-            1 => ObjectRequest::BlockHeader(reader.read_hash()?),
-            2 => ObjectRequest::Transaction(reader.read_hash()?),
-            _ => return Err(ReaderError::InvalidValue)
-        })
-    }
-
-    fn size(&self) -> usize {
-        1 + HASH_SIZE
-    }
-}
-
 impl Display for ObjectRequest {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
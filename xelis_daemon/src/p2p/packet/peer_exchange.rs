@@ -0,0 +1,145 @@
+use std::{borrow::Cow, net::SocketAddr};
+use indexmap::IndexSet;
+use log::debug;
+use xelis_common::serializer::{Serializer, Reader, ReaderError, Writer};
+use crate::config::P2P_PEER_EXCHANGE_LIMIT;
+
+// Request a sample of the known peers addresses of a peer
+// This is used to discover new peers without relying on seed nodes
+#[derive(Debug)]
+pub struct PeerExchangeRequest {
+    // maximum number of addresses we want to receive
+    limit: u8
+}
+
+impl PeerExchangeRequest {
+    pub fn new(limit: u8) -> Self {
+        Self {
+            limit
+        }
+    }
+
+    pub fn get_limit(&self) -> u8 {
+        self.limit
+    }
+}
+
+impl Serializer for PeerExchangeRequest {
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        let limit = reader.read_u8()?;
+        if limit == 0 || limit as usize > P2P_PEER_EXCHANGE_LIMIT {
+            debug!("Invalid limit {} in peer exchange request", limit);
+            return Err(ReaderError::InvalidValue)
+        }
+
+        Ok(Self::new(limit))
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        writer.write_u8(self.limit);
+    }
+
+    fn size(&self) -> usize {
+        self.limit.size()
+    }
+}
+
+// Response to a peer exchange request
+// Contains a random sample of addresses known by the peer
+#[derive(Debug)]
+pub struct PeerExchangeResponse<'a> {
+    peers: Cow<'a, IndexSet<SocketAddr>>
+}
+
+impl<'a> PeerExchangeResponse<'a> {
+    pub fn new(peers: Cow<'a, IndexSet<SocketAddr>>) -> Self {
+        Self {
+            peers
+        }
+    }
+
+    pub fn get_peers(&self) -> &IndexSet<SocketAddr> {
+        &self.peers
+    }
+
+    pub fn consume(self) -> IndexSet<SocketAddr> {
+        self.peers.into_owned()
+    }
+}
+
+impl<'a> Serializer for PeerExchangeResponse<'a> {
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        let len = reader.read_u8()? as usize;
+        if len > P2P_PEER_EXCHANGE_LIMIT {
+            debug!("Too much peers sent in peer exchange response: received {} while max is {}", len, P2P_PEER_EXCHANGE_LIMIT);
+            return Err(ReaderError::InvalidValue)
+        }
+
+        let mut peers = IndexSet::with_capacity(len);
+        for _ in 0..len {
+            let addr = SocketAddr::read(reader)?;
+            if !peers.insert(addr) {
+                debug!("Duplicated peer {} in peer exchange response", addr);
+                return Err(ReaderError::InvalidValue)
+            }
+        }
+
+        Ok(Self::new(Cow::Owned(peers)))
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        writer.write_u8(self.peers.len() as u8);
+        for addr in self.peers.iter() {
+            addr.write(writer);
+        }
+    }
+
+    fn size(&self) -> usize {
+        // u8 for the length of the peer list
+        1 + self.peers.iter().map(|addr| addr.size()).sum::<usize>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_peer_exchange_request_limit() {
+        let request = PeerExchangeRequest::from_bytes(&PeerExchangeRequest::new(8).to_bytes()).unwrap();
+        assert_eq!(request.get_limit(), 8);
+
+        assert!(PeerExchangeRequest::from_bytes(&[0]).is_err());
+        assert!(PeerExchangeRequest::from_bytes(&[P2P_PEER_EXCHANGE_LIMIT as u8 + 1]).is_err());
+    }
+
+    #[test]
+    fn test_peer_exchange_response() {
+        let mut peers = IndexSet::new();
+        peers.insert("1.2.3.4:2125".parse().unwrap());
+        peers.insert("[::1]:2125".parse().unwrap());
+
+        let response = PeerExchangeResponse::new(Cow::Borrowed(&peers));
+        let bytes = response.to_bytes();
+        assert_eq!(bytes.len(), response.size());
+        assert_eq!(PeerExchangeResponse::from_bytes(&bytes).unwrap().consume(), peers);
+    }
+
+    #[test]
+    fn test_peer_exchange_response_duplicated_peer() {
+        let addr: SocketAddr = "1.2.3.4:2125".parse().unwrap();
+        let mut bytes = vec![2];
+        bytes.extend(addr.to_bytes());
+        bytes.extend(addr.to_bytes());
+        assert!(PeerExchangeResponse::from_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_peer_exchange_response_too_many_peers() {
+        let mut bytes = vec![P2P_PEER_EXCHANGE_LIMIT as u8 + 1];
+        for i in 0..=P2P_PEER_EXCHANGE_LIMIT as u16 {
+            bytes.extend(SocketAddr::from(([1, 2, 3, 4], i)).to_bytes());
+        }
+        assert!(PeerExchangeResponse::from_bytes(&bytes).is_err());
+    }
+}
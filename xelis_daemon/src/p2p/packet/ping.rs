@@ -3,6 +3,7 @@ use xelis_common::{
     api::daemon::{Direction, NotifyEvent, PeerPeerListUpdatedEvent},
     crypto::Hash,
     difficulty::CumulativeDifficulty,
+    time::get_current_time_in_millis,
     serializer::{
         Reader,
         ReaderError,
@@ -19,6 +20,7 @@ use crate::{
     p2p::{
         error::P2pError,
         peer::Peer,
+        network_view::PeerChainView,
         is_local_address,
     },
     rpc::rpc::get_peer_entry
@@ -55,7 +57,7 @@ impl<'a> Ping<'a> {
 
     pub async fn update_peer<S: Storage>(self, peer: &Arc<Peer>, blockchain: &Arc<Blockchain<S>>) -> Result<(), P2pError> {
         trace!("Updating {} with {}", peer, self);
-        peer.set_top_block_hash(self.top_hash.into_owned()).await;
+        peer.set_top_block_hash(self.top_hash.as_ref().clone()).await;
         peer.set_topoheight(self.topoheight);
         peer.set_height(self.height);
 
@@ -77,6 +79,9 @@ impl<'a> Ping<'a> {
         }
 
         peer.set_pruned_topoheight(self.pruned_topoheight);
+
+        // keep track of the peer chain to detect a chain split
+        peer.add_chain_view(PeerChainView::new(get_current_time_in_millis(), blockchain.get_topo_height(), self.topoheight, self.top_hash.into_owned(), self.cumulative_difficulty.clone())).await;
         peer.set_cumulative_difficulty(self.cumulative_difficulty).await;
 
         if peer.sharable() {
@@ -0,0 +1,193 @@
+use std::borrow::Cow;
+use indexmap::IndexSet;
+use log::debug;
+use xelis_common::{
+    crypto::{Hash, HASH_SIZE},
+    serializer::{Serializer, Reader, ReaderError, Writer},
+    transaction::Transaction
+};
+use crate::config::{P2P_TXS_BATCH_MAX_SIZE, MAX_BLOCK_SIZE};
+
+// A block can't reference more txs than the hashes fitting in its max size
+const MAX_TXS_PER_BLOCK: usize = MAX_BLOCK_SIZE / HASH_SIZE;
+
+// Propagate several txs hashes at once
+// Like TransactionPropagation, it is a "notification" and the peer
+// will request the txs it doesn't have
+#[derive(Clone, Debug)]
+pub struct TxsPropagation<'a> {
+    txs: Cow<'a, IndexSet<Hash>>
+}
+
+impl<'a> TxsPropagation<'a> {
+    pub fn new(txs: Cow<'a, IndexSet<Hash>>) -> Self {
+        Self {
+            txs
+        }
+    }
+
+    pub fn get_txs(&self) -> &IndexSet<Hash> {
+        &self.txs
+    }
+
+    pub fn consume(self) -> IndexSet<Hash> {
+        self.txs.into_owned()
+    }
+}
+
+impl<'a> Serializer for TxsPropagation<'a> {
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        let len = reader.read_u16()? as usize;
+        if len == 0 || len > P2P_TXS_BATCH_MAX_SIZE {
+            debug!("Invalid txs propagation count: received {} while max is {}", len, P2P_TXS_BATCH_MAX_SIZE);
+            return Err(ReaderError::InvalidValue)
+        }
+
+        let mut txs = IndexSet::with_capacity(len);
+        for _ in 0..len {
+            let hash = reader.read_hash()?;
+            if !txs.insert(hash) {
+                debug!("Duplicated tx in txs propagation");
+                return Err(ReaderError::InvalidValue)
+            }
+        }
+
+        Ok(Self::new(Cow::Owned(txs)))
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        writer.write_u16(self.txs.len() as u16);
+        for hash in self.txs.iter() {
+            writer.write_hash(hash);
+        }
+    }
+
+    fn size(&self) -> usize {
+        // u16 for the length + each tx hash
+        2 + self.txs.len() * HASH_SIZE
+    }
+}
+
+// Request several transactions at once
+// This is used to reconstruct a propagated block
+// by requesting only the txs that are missing from our mempool
+#[derive(Debug)]
+pub struct GetTxs<'a> {
+    // block for which we request the txs
+    block_hash: Cow<'a, Hash>,
+    // txs hashes we are missing
+    txs: Cow<'a, IndexSet<Hash>>
+}
+
+impl<'a> GetTxs<'a> {
+    pub fn new(block_hash: Cow<'a, Hash>, txs: Cow<'a, IndexSet<Hash>>) -> Self {
+        Self {
+            block_hash,
+            txs
+        }
+    }
+
+    pub fn get_block_hash(&self) -> &Hash {
+        &self.block_hash
+    }
+
+    pub fn get_txs(&self) -> &IndexSet<Hash> {
+        &self.txs
+    }
+}
+
+impl<'a> Serializer for GetTxs<'a> {
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        let block_hash = reader.read_hash()?;
+        let len = reader.read_u16()? as usize;
+        if len == 0 || len > MAX_TXS_PER_BLOCK {
+            debug!("Invalid get txs request count for block {}: received {} while max is {}", block_hash, len, MAX_TXS_PER_BLOCK);
+            return Err(ReaderError::InvalidValue)
+        }
+
+        let mut txs = IndexSet::with_capacity(len);
+        for _ in 0..len {
+            let hash = reader.read_hash()?;
+            if !txs.insert(hash) {
+                debug!("Duplicated tx in get txs request for block {}", block_hash);
+                return Err(ReaderError::InvalidValue)
+            }
+        }
+
+        Ok(Self::new(Cow::Owned(block_hash), Cow::Owned(txs)))
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        writer.write_hash(&self.block_hash);
+        writer.write_u16(self.txs.len() as u16);
+        for hash in self.txs.iter() {
+            writer.write_hash(hash);
+        }
+    }
+
+    fn size(&self) -> usize {
+        // block hash + u16 for the length + each tx hash
+        HASH_SIZE + 2 + self.txs.len() * HASH_SIZE
+    }
+}
+
+// Response to a GetTxs request
+// Contains all the txs requested that were found by the peer
+#[derive(Debug)]
+pub struct TxsResponse<'a> {
+    block_hash: Cow<'a, Hash>,
+    txs: Vec<Cow<'a, Transaction>>
+}
+
+impl<'a> TxsResponse<'a> {
+    pub fn new(block_hash: Cow<'a, Hash>, txs: Vec<Cow<'a, Transaction>>) -> Self {
+        Self {
+            block_hash,
+            txs
+        }
+    }
+
+    pub fn get_block_hash(&self) -> &Hash {
+        &self.block_hash
+    }
+
+    pub fn get_txs(&self) -> &Vec<Cow<'a, Transaction>> {
+        &self.txs
+    }
+
+    pub fn consume(self) -> (Hash, Vec<Transaction>) {
+        (self.block_hash.into_owned(), self.txs.into_iter().map(|tx| tx.into_owned()).collect())
+    }
+}
+
+impl<'a> Serializer for TxsResponse<'a> {
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        let block_hash = reader.read_hash()?;
+        let len = reader.read_u16()? as usize;
+        if len > MAX_TXS_PER_BLOCK {
+            debug!("Invalid txs response count for block {}: received {} while max is {}", block_hash, len, MAX_TXS_PER_BLOCK);
+            return Err(ReaderError::InvalidValue)
+        }
+
+        // Don't pre-allocate as each tx size is only known once read
+        let mut txs = Vec::new();
+        for _ in 0..len {
+            txs.push(Cow::Owned(Transaction::read(reader)?));
+        }
+
+        Ok(Self::new(Cow::Owned(block_hash), txs))
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        writer.write_hash(&self.block_hash);
+        writer.write_u16(self.txs.len() as u16);
+        for tx in self.txs.iter() {
+            tx.write(writer);
+        }
+    }
+
+    fn size(&self) -> usize {
+        // block hash + u16 for the length + each tx
+        HASH_SIZE + 2 + self.txs.iter().map(|tx| tx.size()).sum::<usize>()
+    }
+}
@@ -3,16 +3,21 @@ use crate::{
         PEER_FAIL_TIME_RESET, PEER_BLOCK_CACHE_SIZE, PEER_TX_CACHE_SIZE,
         PEER_TEMP_BAN_TIME, PEER_TIMEOUT_BOOTSTRAP_STEP,
         PEER_TIMEOUT_REQUEST_OBJECT, CHAIN_SYNC_TIMEOUT_SECS,
-        PEER_PACKET_CHANNEL_SIZE
+        PEER_PACKET_CHANNEL_SIZE, PEER_CHAIN_VIEW_HISTORY_SIZE,
+        P2P_LATENCY_SMOOTHING, P2P_JITTER_SMOOTHING, P2P_PEER_EXCHANGE_TIMEOUT
     },
+    core::storage::Storage,
     p2p::packet::PacketWrapper
 };
 use xelis_common::{
-    api::daemon::Direction,
-    crypto::Hash,
+    api::daemon::{Direction, PeerChainStatus},
+    capabilities::Capabilities,
+    crypto::{Hash, Hashable},
     difficulty::CumulativeDifficulty,
     serializer::Serializer,
+    transaction::Transaction,
     time::{
+        TimestampMillis,
         TimestampSeconds,
         get_current_time_in_seconds
     }
@@ -32,16 +37,19 @@ use super::{
             ObjectRequest,
             OwnedObjectResponse
         },
+        txs::GetTxs,
         Packet
     },
     peer_list::SharedPeerList,
+    rate_limiter::RateLimiter,
+    network_view::PeerChainView,
     connection::Connection,
     error::P2pError
 };
 use std::{
     num::NonZeroUsize,
     borrow::Cow,
-    collections::{HashMap, HashSet},
+    collections::{HashMap, HashSet, VecDeque},
     fmt::{Display, Error, Formatter},
     hash::{Hash as StdHash, Hasher},
     net::{IpAddr, SocketAddr},
@@ -53,6 +61,7 @@ use tokio::{
     time::timeout,
 };
 use lru::LruCache;
+use indexmap::IndexSet;
 use bytes::Bytes;
 use log::{
     Level,
@@ -66,6 +75,10 @@ use log::{
 // This is done to be awaitable with a timeout
 pub type RequestedObjects = HashMap<ObjectRequest, Sender<OwnedObjectResponse>>;
 
+// A RequestedTxs is a map of all txs batches requested from a peer
+// The key is the block hash for which the txs are requested
+pub type RequestedTxs = HashMap<Hash, Sender<Vec<Transaction>>>;
+
 pub type Tx = mpsc::Sender<Bytes>;
 pub type Rx = mpsc::Receiver<Bytes>;
 
@@ -115,6 +128,8 @@ pub struct Peer {
     peer_list: SharedPeerList,
     // map of requested objects from this peer
     objects_requested: Mutex<RequestedObjects>,
+    // txs requested to reconstruct a propagated block
+    txs_requested: Mutex<RequestedTxs>,
     // all peers sent/received
     peers: Mutex<HashMap<SocketAddr, Direction>>,
     // last time we received a peerlist from this peer
@@ -123,8 +138,22 @@ pub struct Peer {
     last_ping: AtomicU64,
     // last time we sent a ping packet to this peer
     last_ping_sent: AtomicU64,
+    // last time we got a latency ping packet from this peer
+    last_latency_ping: AtomicU64,
+    // id of the latency ping waiting for a pong
+    latency_ping_id: AtomicU64,
+    // time in ms at which the pending latency ping was sent, 0 if none
+    latency_ping_sent: AtomicU64,
+    // rolling round-trip time in ms
+    latency: AtomicU64,
+    // rolling deviation of the round-trip time in ms
+    jitter: AtomicU64,
+    // count of RTT samples received
+    latency_samples: AtomicU64,
     // cumulative difficulty of peer chain
     cumulative_difficulty: Mutex<CumulativeDifficulty>,
+    // last chain views advertised by the peer
+    chain_views: Mutex<VecDeque<PeerChainView>>,
     // All transactions propagated from/to this peer
     txs_cache: Mutex<LruCache<Hash, Direction>>,
     // last blocks propagated to/from this peer
@@ -133,6 +162,10 @@ pub struct Peer {
     last_inventory: AtomicU64,
     // if we requested this peer to send us an inventory notification
     requested_inventory: AtomicBool,
+    // last time we got a peer exchange request from this peer
+    last_peer_exchange: AtomicU64,
+    // when we requested this peer to send us a sample of its known peers, 0 if not requested
+    requested_peer_exchange: AtomicU64,
     // pruned topoheight if its a pruned node
     pruned_topoheight: AtomicU64,
     // Store the pruned state of the peer
@@ -146,6 +179,16 @@ pub struct Peer {
     outgoing_address: SocketAddr,
     // Determine if this peer allows to be shared to others and/or through API
     sharable: bool,
+    // Features and runtime modes enabled on this peer
+    capabilities: Capabilities,
+    // P2P protocol version negotiated during the handshake
+    protocol_version: u16,
+    // P2P features supported by both sides
+    negotiated_capabilities: Capabilities,
+    // port of its TLS P2P listener if enabled
+    tls_port: Option<u16>,
+    // Rate limiter applied on packets received from this peer
+    rate_limiter: RateLimiter,
     // Channel to send bytes to the writer task
     tx: Tx,
     // Channel to notify the tasks to exit
@@ -156,7 +199,7 @@ pub struct Peer {
 }
 
 impl Peer {
-    pub fn new(connection: Connection, id: u64, node_tag: Option<String>, local_port: u16, version: String, top_hash: Hash, topoheight: u64, height: u64, pruned_topoheight: Option<u64>, priority: bool, cumulative_difficulty: CumulativeDifficulty, peer_list: SharedPeerList, peers_received: HashSet<SocketAddr>, sharable: bool) -> (Self, Rx) {
+    pub fn new(connection: Connection, id: u64, node_tag: Option<String>, local_port: u16, version: String, top_hash: Hash, topoheight: u64, height: u64, pruned_topoheight: Option<u64>, priority: bool, cumulative_difficulty: CumulativeDifficulty, peer_list: SharedPeerList, peers_received: HashSet<SocketAddr>, sharable: bool, capabilities: Capabilities, protocol_version: u16, negotiated_capabilities: Capabilities, tls_port: Option<u16>) -> (Self, Rx) {
         let mut outgoing_address = *connection.get_address();
         outgoing_address.set_port(local_port);
 
@@ -183,21 +226,36 @@ impl Peer {
             last_chain_sync: AtomicU64::new(0),
             peer_list,
             objects_requested: Mutex::new(HashMap::new()),
+            txs_requested: Mutex::new(HashMap::new()),
             peers: Mutex::new(peers),
             last_peer_list: AtomicU64::new(0),
             last_ping: AtomicU64::new(0),
             last_ping_sent: AtomicU64::new(0),
+            last_latency_ping: AtomicU64::new(0),
+            latency_ping_id: AtomicU64::new(0),
+            latency_ping_sent: AtomicU64::new(0),
+            latency: AtomicU64::new(0),
+            jitter: AtomicU64::new(0),
+            latency_samples: AtomicU64::new(0),
             cumulative_difficulty: Mutex::new(cumulative_difficulty),
+            chain_views: Mutex::new(VecDeque::with_capacity(PEER_CHAIN_VIEW_HISTORY_SIZE)),
             txs_cache: Mutex::new(LruCache::new(NonZeroUsize::new(PEER_TX_CACHE_SIZE).unwrap())),
             blocks_propagation: Mutex::new(LruCache::new(NonZeroUsize::new(PEER_BLOCK_CACHE_SIZE).unwrap())),
             last_inventory: AtomicU64::new(0),
             requested_inventory: AtomicBool::new(false),
+            last_peer_exchange: AtomicU64::new(0),
+            requested_peer_exchange: AtomicU64::new(0),
             pruned_topoheight: AtomicU64::new(pruned_topoheight.unwrap_or(0)),
             is_pruned: AtomicBool::new(pruned_topoheight.is_some()),
             bootstrap_chain: Mutex::new(None),
             sync_chain: Mutex::new(None),
             outgoing_address,
             sharable,
+            capabilities,
+            protocol_version,
+            negotiated_capabilities,
+            tls_port,
+            rate_limiter: RateLimiter::new(),
             exit_channel,
             tx,
             read_task: Mutex::new(TaskState::Inactive),
@@ -225,6 +283,19 @@ impl Peer {
         &self.blocks_propagation
     }
 
+    // Mark txs as known by the peer without any announcement
+    // The txs of a block propagated from/to the peer don't need to be announced to it
+    pub async fn mark_txs_as_known<'a, I: IntoIterator<Item = &'a Hash>>(&self, txs: I, direction: Direction) {
+        let mut txs_cache = self.txs_cache.lock().await;
+        for hash in txs {
+            if let Some(current) = txs_cache.get_mut(hash) {
+                current.update(direction);
+            } else {
+                txs_cache.put(hash.clone(), direction);
+            }
+        }
+    }
+
     // Get its connection object to manage p2p communication
     pub fn get_connection(&self) -> &Connection {
         &self.connection
@@ -315,6 +386,38 @@ impl Peer {
         *self.cumulative_difficulty.lock().await = cumulative_difficulty;
     }
 
+    // Store a new chain view advertised by the peer
+    // Only the last PEER_CHAIN_VIEW_HISTORY_SIZE views are kept
+    pub async fn add_chain_view(&self, view: PeerChainView) {
+        let mut chain_views = self.chain_views.lock().await;
+        if chain_views.len() >= PEER_CHAIN_VIEW_HISTORY_SIZE {
+            chain_views.pop_front();
+        }
+        chain_views.push_back(view);
+    }
+
+    // Get the last chain views advertised by the peer
+    pub fn get_chain_views(&self) -> &Mutex<VecDeque<PeerChainView>> {
+        &self.chain_views
+    }
+
+    // Get the current chain status of the peer and since when it has it
+    pub async fn get_chain_status<S: Storage>(&self, storage: &S) -> Option<(PeerChainStatus, TimestampMillis)> {
+        let chain_views = self.chain_views.lock().await;
+        let mut views = chain_views.iter().rev();
+        let last = views.next()?;
+        let status = last.get_status(storage).await;
+        let mut since = last.get_timestamp();
+        for view in views {
+            if view.get_status(storage).await != status {
+                break;
+            }
+            since = view.get_timestamp();
+        }
+
+        Some((status, since))
+    }
+
     // Verify if its a outgoing connection
     pub fn is_out(&self) -> bool {
         self.connection.is_out()
@@ -331,6 +434,31 @@ impl Peer {
         self.sharable
     }
 
+    // Get the features and runtime modes enabled on the peer
+    pub fn get_capabilities(&self) -> Capabilities {
+        self.capabilities
+    }
+
+    // Get the P2P protocol version used with this peer
+    pub fn get_protocol_version(&self) -> u16 {
+        self.protocol_version
+    }
+
+    // Get the P2P features that can be used with this peer
+    pub fn get_negotiated_capabilities(&self) -> Capabilities {
+        self.negotiated_capabilities
+    }
+
+    // Get the rate limiter of the peer
+    // Get the port of its TLS P2P listener
+    pub fn get_tls_port(&self) -> Option<u16> {
+        self.tls_port
+    }
+
+    pub fn get_rate_limiter(&self) -> &RateLimiter {
+        &self.rate_limiter
+    }
+
     // Get the last time we got a fail from the peer
     pub fn get_last_fail_count(&self) -> u64 {
         self.last_fail_count.load(Ordering::Acquire)
@@ -442,6 +570,54 @@ impl Peer {
         Ok(object)
     }
 
+    // Get all txs batches requested from this peer
+    pub fn get_txs_requested(&self) -> &Mutex<RequestedTxs> {
+        &self.txs_requested
+    }
+
+    // Request the missing txs of a propagated block and wait on them until we receive them or until timeout
+    // Returns the txs with their hashes, all requested txs must be present in the response
+    pub async fn request_txs(&self, block_hash: &Hash, txs: &IndexSet<Hash>) -> Result<Vec<(Hash, Transaction)>, P2pError> {
+        debug!("Requesting {} txs for block {} from {}", txs.len(), block_hash, self);
+        let receiver = {
+            let mut requested = self.txs_requested.lock().await;
+            if requested.contains_key(block_hash) {
+                return Err(P2pError::TxsAlreadyRequested(block_hash.clone()));
+            }
+            self.send_packet(Packet::GetTxs(GetTxs::new(Cow::Borrowed(block_hash), Cow::Borrowed(txs)))).await?;
+            let (sender, receiver) = tokio::sync::oneshot::channel();
+            requested.insert(block_hash.clone(), sender);
+            receiver
+        };
+
+        let response = match timeout(Duration::from_millis(PEER_TIMEOUT_REQUEST_OBJECT), receiver).await {
+            Ok(res) => res?,
+            Err(e) => {
+                debug!("Requested txs for block {} have timed out", block_hash);
+                let mut requested = self.txs_requested.lock().await;
+                requested.remove(block_hash);
+                return Err(P2pError::AsyncTimeOut(e));
+            }
+        };
+
+        // Verify that we received exactly the txs requested
+        if response.len() != txs.len() {
+            return Err(P2pError::InvalidTxsResponse(block_hash.clone()));
+        }
+
+        let mut received = HashSet::with_capacity(response.len());
+        let mut result = Vec::with_capacity(response.len());
+        for tx in response {
+            let hash = tx.hash();
+            if !txs.contains(&hash) || !received.insert(hash.clone()) {
+                return Err(P2pError::InvalidTxsResponse(block_hash.clone()));
+            }
+            result.push((hash, tx));
+        }
+
+        Ok(result)
+    }
+
     // Request a bootstrap chain from this peer and wait on it until we receive it or until timeout
     pub async fn request_boostrap_chain(&self, step: StepRequest<'_>) -> Result<StepResponse, P2pError> {
         debug!("Requesting bootstrap chain step: {:?}", step.kind());
@@ -545,6 +721,80 @@ impl Peer {
         self.last_ping.store(value, Ordering::Release)
     }
 
+    // Get the last time we got a latency ping packet from this peer
+    pub fn get_last_latency_ping(&self) -> TimestampSeconds {
+        self.last_latency_ping.load(Ordering::Acquire)
+    }
+
+    // Track the last time we got a latency ping packet from this peer
+    pub fn set_last_latency_ping(&self, value: TimestampSeconds) {
+        self.last_latency_ping.store(value, Ordering::Release)
+    }
+
+    // Check if we are waiting for a latency pong from this peer
+    pub fn has_pending_latency_ping(&self) -> bool {
+        self.latency_ping_sent.load(Ordering::Acquire) != 0
+    }
+
+    // Track the latency ping sent to this peer
+    pub fn set_pending_latency_ping(&self, id: u64, sent_at: TimestampMillis) {
+        self.latency_ping_id.store(id, Ordering::Release);
+        self.latency_ping_sent.store(sent_at, Ordering::Release);
+    }
+
+    // Cancel the pending latency ping, its pong will be ignored
+    pub fn clear_pending_latency_ping(&self) {
+        self.latency_ping_sent.store(0, Ordering::Release);
+    }
+
+    // A latency pong has been received from this peer
+    // Returns the round-trip time in ms if it matches the pending latency ping
+    pub fn on_latency_pong(&self, id: u64, received_at: TimestampMillis) -> Option<u64> {
+        let sent_at = self.latency_ping_sent.load(Ordering::Acquire);
+        if sent_at == 0 || self.latency_ping_id.load(Ordering::Acquire) != id {
+            return None
+        }
+        self.clear_pending_latency_ping();
+
+        let rtt = received_at.saturating_sub(sent_at);
+        self.update_latency(rtt);
+        Some(rtt)
+    }
+
+    // Update the rolling latency and jitter with a new RTT sample
+    // Same smoothing as the TCP retransmission timer (RFC 6298)
+    fn update_latency(&self, rtt: u64) {
+        if self.latency_samples.fetch_add(1, Ordering::AcqRel) == 0 {
+            self.latency.store(rtt, Ordering::Release);
+            self.jitter.store(rtt / 2, Ordering::Release);
+            return
+        }
+
+        let latency = self.latency.load(Ordering::Acquire);
+        let jitter = self.jitter.load(Ordering::Acquire);
+        let deviation = latency.abs_diff(rtt);
+        self.jitter.store((jitter * (P2P_JITTER_SMOOTHING - 1) + deviation) / P2P_JITTER_SMOOTHING, Ordering::Release);
+        self.latency.store((latency * (P2P_LATENCY_SMOOTHING - 1) + rtt) / P2P_LATENCY_SMOOTHING, Ordering::Release);
+    }
+
+    // Get the rolling round-trip time in ms, None if never measured
+    pub fn get_latency(&self) -> Option<u64> {
+        if self.latency_samples.load(Ordering::Acquire) == 0 {
+            None
+        } else {
+            Some(self.latency.load(Ordering::Acquire))
+        }
+    }
+
+    // Get the rolling deviation of the round-trip time in ms, None if never measured
+    pub fn get_jitter(&self) -> Option<u64> {
+        if self.latency_samples.load(Ordering::Acquire) == 0 {
+            None
+        } else {
+            Some(self.jitter.load(Ordering::Acquire))
+        }
+    }
+
     // Get the last time a inventory has been requested
     pub fn get_last_inventory(&self) -> TimestampSeconds {
         self.last_inventory.load(Ordering::Acquire)
@@ -565,6 +815,40 @@ impl Peer {
         self.requested_inventory.store(value, Ordering::Release)
     }
 
+    // Get the last time we got a peer exchange request from this peer
+    pub fn get_last_peer_exchange(&self) -> TimestampSeconds {
+        self.last_peer_exchange.load(Ordering::Acquire)
+    }
+
+    // Track the last time we got a peer exchange request
+    // This is used to prevent spamming the peer exchange
+    pub fn set_last_peer_exchange(&self, value: TimestampSeconds) {
+        self.last_peer_exchange.store(value, Ordering::Release)
+    }
+
+    // Get the requested peer exchange flag
+    pub fn has_requested_peer_exchange(&self) -> bool {
+        self.requested_peer_exchange.load(Ordering::Acquire) != 0
+    }
+
+    // Set the requested peer exchange flag
+    // The time of the request is kept to expire it if the peer never answers
+    pub fn set_requested_peer_exchange(&self, value: bool) {
+        let time = if value { get_current_time_in_seconds() } else { 0 };
+        self.requested_peer_exchange.store(time, Ordering::Release)
+    }
+
+    // Reset the requested peer exchange flag if the peer didn't answer in time
+    // Returns true if the request has expired
+    pub fn expire_requested_peer_exchange(&self, current_time: TimestampSeconds) -> bool {
+        let requested = self.requested_peer_exchange.load(Ordering::Acquire);
+        if requested == 0 || requested + P2P_PEER_EXCHANGE_TIMEOUT > current_time {
+            return false
+        }
+
+        self.requested_peer_exchange.compare_exchange(requested, 0, Ordering::AcqRel, Ordering::Acquire).is_ok()
+    }
+
     // Get the outgoing address of the peer
     // This represents the IP address of the peer and the port on which it is listening
     pub fn get_outgoing_address(&self) -> &SocketAddr {
@@ -629,7 +913,12 @@ impl Peer {
 
     // Send a packet to the peer
     // This will transform the packet into bytes and send it to the peer
+    // Packets from a newer protocol version than the negotiated one are refused
     pub async fn send_packet(&self, packet: Packet<'_>) -> Result<(), P2pError> {
+        if packet.get_protocol_version() > self.protocol_version {
+            return Err(P2pError::UnsupportedPacketVersion(packet.get_id(), packet.get_protocol_version(), self.protocol_version))
+        }
+
         self.send_bytes(Bytes::from(packet.to_bytes())).await
     }
 
@@ -1,6 +1,8 @@
 use crate::{
     config::{
         P2P_EXTEND_PEERLIST_DELAY,
+        P2P_MAX_STORED_PEERS,
+        P2P_PEER_EXCHANGE_MAX_AGE,
         PEER_FAIL_LIMIT,
         PEER_FAIL_TO_CONNECT_LIMIT,
         PEER_TEMP_BAN_TIME_ON_CONNECT,
@@ -8,7 +10,7 @@ use crate::{
     },
     p2p::packet::peer_disconnected::PacketPeerDisconnected
 };
-use super::{peer::Peer, packet::Packet, error::P2pError};
+use super::{peer::Peer, packet::Packet, error::P2pError, is_local_address};
 use std::{
     collections::{hash_map::Entry, HashMap, HashSet},
     fmt::{self, Display, Formatter},
@@ -16,6 +18,8 @@ use std::{
     time::Duration
 };
 use humantime::format_duration;
+use indexmap::IndexSet;
+use rand::seq::IteratorRandom;
 use serde::{Serialize, Deserialize};
 use tokio::{sync::{mpsc::Sender, RwLock}, time::timeout};
 use xelis_common::{
@@ -62,7 +66,13 @@ pub struct StoredPeer {
     local_port: u16,
     // Until when the peer is banned
     temp_ban_until: Option<u64>,
-    state: StoredPeerState
+    state: StoredPeerState,
+    // Can we share this peer with others peers
+    #[serde(default)]
+    sharable: bool,
+    // Port of its TLS P2P listener advertised in its handshake
+    #[serde(default)]
+    tls_port: Option<u16>
 }
 
 impl PeerList {
@@ -229,12 +239,24 @@ impl PeerList {
             stored_peer.set_fail_count(0);
             stored_peer.set_last_seen(get_current_time_in_seconds());
             stored_peer.set_local_port(peer.get_local_port());
+            stored_peer.set_sharable(peer.sharable());
+            stored_peer.set_tls_port(peer.get_tls_port());
         } else {
             debug!("Saving {} in stored peerlist", peer);
-            stored_peers.insert(ip, StoredPeer::new(peer.get_local_port(), StoredPeerState::Graylist));
+            let mut stored_peer = StoredPeer::new(peer.get_local_port(), StoredPeerState::Graylist, peer.sharable());
+            stored_peer.set_tls_port(peer.get_tls_port());
+            stored_peers.insert(ip, stored_peer);
         }
     }
 
+    // Get the TLS port advertised by the peer listening on this address
+    pub async fn get_stored_tls_port(&self, addr: &SocketAddr) -> Option<u16> {
+        let stored_peers = self.stored_peers.read().await;
+        stored_peers.get(&addr.ip())
+            .filter(|stored_peer| stored_peer.get_local_port() == addr.port())
+            .and_then(|stored_peer| stored_peer.get_tls_port())
+    }
+
     // Verify if the peer is connected (in peerlist)
     pub async fn has_peer(&self, peer_id: &u64) -> bool {
         let peers = self.peers.read().await;
@@ -265,6 +287,13 @@ impl PeerList {
         peers.len()
     }
 
+    // Returns the count of outgoing and incoming peers
+    pub async fn count_by_direction(&self) -> (usize, usize) {
+        let peers = self.peers.read().await;
+        let outgoing = peers.values().filter(|peer| peer.is_out()).count();
+        (outgoing, peers.len() - outgoing)
+    }
+
     pub async fn close_all(&self) {
         trace!("closing all peers");
         let peers = {
@@ -389,7 +418,7 @@ impl PeerList {
         if let Some(stored_peer) = stored_peers.get_mut(addr) {
             stored_peer.set_state(state);
         } else {
-            stored_peers.insert(addr.clone(), StoredPeer::new(0, state));
+            stored_peers.insert(addr.clone(), StoredPeer::new(0, state, false));
         }
     }
 
@@ -462,10 +491,34 @@ impl PeerList {
         if let Some(stored_peer) = stored_peers.get_mut(ip) {
             stored_peer.set_temp_ban_until(Some(get_current_time_in_seconds() + seconds));
         } else {
-            stored_peers.insert(ip.clone(), StoredPeer::new(0, StoredPeerState::Graylist));
+            let mut stored_peer = StoredPeer::new(0, StoredPeerState::Graylist, false);
+            stored_peer.set_temp_ban_until(Some(get_current_time_in_seconds() + seconds));
+            stored_peers.insert(ip.clone(), stored_peer);
         }
     }
 
+    // Remove the blacklist state and the temp ban of a peer address
+    // Returns false if the address was not banned
+    pub async fn unban_address(&self, ip: &IpAddr) -> bool {
+        let (temp_banned, blacklisted) = {
+            let mut stored_peers = self.stored_peers.write().await;
+            match stored_peers.get_mut(ip) {
+                Some(stored_peer) => {
+                    let temp_banned = stored_peer.get_temp_ban_until().is_some();
+                    stored_peer.set_temp_ban_until(None);
+                    (temp_banned, *stored_peer.get_state() == StoredPeerState::Blacklist)
+                },
+                None => (false, false)
+            }
+        };
+
+        if blacklisted {
+            self.set_graylist_for_peer(ip).await;
+        }
+
+        temp_banned || blacklisted
+    }
+
     // whitelist a peer address
     // if this peer is already known, change its state to whitelist
     // otherwise create a new StoredPeer with state whitelist
@@ -513,7 +566,7 @@ impl PeerList {
         let mut stored_peers = self.stored_peers.write().await;
         let stored_peer = match stored_peers.entry(*ip) {
             Entry::Occupied(entry) => entry.into_mut(),
-            Entry::Vacant(entry) => entry.insert(StoredPeer::new(0, StoredPeerState::Graylist))
+            Entry::Vacant(entry) => entry.insert(StoredPeer::new(0, StoredPeerState::Graylist, false))
         };
         let fail_count = stored_peer.get_fail_count();
         if *stored_peer.get_state() != StoredPeerState::Whitelist {
@@ -537,11 +590,70 @@ impl PeerList {
             return false;
         }
 
-        stored_peers.insert(ip, StoredPeer::new(addr.port(), StoredPeerState::Graylist));
+        stored_peers.insert(ip, StoredPeer::new(addr.port(), StoredPeerState::Graylist, true));
 
         true
     }
 
+    // Store all new peer addresses received through a peer exchange
+    // They are not sharable until we connect to them and their handshake allows it
+    // We stop once the stored peerlist has reached its limit
+    // Returns how many addresses were stored
+    pub async fn store_peer_addresses(&self, addresses: impl IntoIterator<Item = SocketAddr>) -> usize {
+        let mut stored_peers = self.stored_peers.write().await;
+        let mut count = 0;
+        for addr in addresses {
+            if stored_peers.len() >= P2P_MAX_STORED_PEERS {
+                debug!("Stored peerlist is full, ignoring remaining addresses");
+                break;
+            }
+
+            if let Entry::Vacant(entry) = stored_peers.entry(addr.ip()) {
+                trace!("Storing {} from peer exchange", addr);
+                entry.insert(StoredPeer::new(addr.port(), StoredPeerState::Graylist, false));
+                count += 1;
+            }
+        }
+
+        count
+    }
+
+    // Select a random sample of known peers addresses to be shared through a peer exchange
+    // Only sharable peers that are not blacklisted / temp banned and that were seen recently are selected
+    // Local addresses are only shared if allowed (requesting peer is also in our local network)
+    pub async fn get_peers_sample(&self, limit: usize, exclude: &SocketAddr, allow_local: bool) -> IndexSet<SocketAddr> {
+        let mut candidates = HashSet::new();
+        {
+            let peers = self.peers.read().await;
+            for peer in peers.values().filter(|peer| peer.sharable()) {
+                candidates.insert(*peer.get_outgoing_address());
+            }
+        }
+
+        {
+            let current_time = get_current_time_in_seconds();
+            let stored_peers = self.stored_peers.read().await;
+            for (ip, stored_peer) in stored_peers.iter() {
+                if !stored_peer.is_sharable()
+                    || *stored_peer.get_state() == StoredPeerState::Blacklist
+                    || stored_peer.get_local_port() == 0
+                    || stored_peer.get_fail_count() != 0
+                    || stored_peer.get_last_seen() + P2P_PEER_EXCHANGE_MAX_AGE < current_time
+                    || stored_peer.get_temp_ban_until().is_some_and(|temp_ban_until| temp_ban_until > current_time) {
+                    continue;
+                }
+
+                candidates.insert(SocketAddr::new(*ip, stored_peer.get_local_port()));
+            }
+        }
+
+        candidates.into_iter()
+            .filter(|addr| addr != exclude && (allow_local || !is_local_address(addr)))
+            .choose_multiple(&mut rand::thread_rng(), limit)
+            .into_iter()
+            .collect()
+    }
+
     // serialize the stored peers to a file
     fn save_peers_to_file(&self, stored_peers: &HashMap<IpAddr, StoredPeer>) -> Result<(), P2pError> {
         trace!("saving peerlist to file");
@@ -553,7 +665,7 @@ impl PeerList {
 }
 
 impl StoredPeer {
-    fn new(local_port: u16, state: StoredPeerState) -> Self {
+    fn new(local_port: u16, state: StoredPeerState, sharable: bool) -> Self {
         let current_time = get_current_time_in_seconds();
         Self {
             first_seen: current_time,
@@ -562,7 +674,9 @@ impl StoredPeer {
             fail_count: 0,
             local_port,
             temp_ban_until: None,
-            state
+            state,
+            sharable,
+            tls_port: None
         }
     }
 
@@ -574,6 +688,10 @@ impl StoredPeer {
         &self.state
     }
 
+    fn get_last_seen(&self) -> TimestampSeconds {
+        self.last_seen
+    }
+
     fn set_last_seen(&mut self, last_seen: TimestampSeconds) {
         self.last_seen = last_seen;
     }
@@ -609,6 +727,22 @@ impl StoredPeer {
     fn get_local_port(&self) -> u16 {
         self.local_port
     }
+
+    fn is_sharable(&self) -> bool {
+        self.sharable
+    }
+
+    fn set_sharable(&mut self, sharable: bool) {
+        self.sharable = sharable;
+    }
+
+    fn get_tls_port(&self) -> Option<u16> {
+        self.tls_port
+    }
+
+    fn set_tls_port(&mut self, tls_port: Option<u16>) {
+        self.tls_port = tls_port;
+    }
 }
 
 impl Display for StoredPeer {
@@ -616,4 +750,40 @@ impl Display for StoredPeer {
         let current_time = get_current_time_in_seconds();
         write!(f, "StoredPeer[first seen: {} ago, last seen: {} ago]", format_duration(Duration::from_secs(current_time - self.first_seen)), format_duration(Duration::from_secs(current_time - self.last_seen)))
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn peer_list() -> SharedPeerList {
+        let filename = format!("{}/xelis-peerlist-{}.json", std::env::temp_dir().display(), rand::random::<u64>());
+        PeerList::new(8, filename, None)
+    }
+
+    #[tokio::test]
+    async fn test_peer_exchange_addresses_are_not_sharable() {
+        let peer_list = peer_list();
+        let addr: SocketAddr = "1.2.3.4:2125".parse().unwrap();
+        assert_eq!(peer_list.store_peer_addresses([addr]).await, 1);
+        // already known
+        assert_eq!(peer_list.store_peer_addresses([addr]).await, 0);
+
+        {
+            let stored_peers = peer_list.stored_peers.read().await;
+            let stored_peer = stored_peers.get(&addr.ip()).unwrap();
+            assert!(!stored_peer.is_sharable());
+            assert_eq!(stored_peer.get_local_port(), addr.port());
+        }
+
+        // Never connected, so it must not be gossiped to others
+        let exclude = "5.6.7.8:2125".parse().unwrap();
+        assert!(peer_list.get_peers_sample(32, &exclude, true).await.is_empty());
+
+        // Once a handshake allowed it, it can be shared
+        peer_list.stored_peers.write().await.get_mut(&addr.ip()).unwrap().set_sharable(true);
+        assert!(peer_list.get_peers_sample(32, &exclude, true).await.contains(&addr));
+
+        let _ = fs::remove_file(&peer_list.filename);
+    }
+}
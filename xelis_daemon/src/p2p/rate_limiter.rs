@@ -0,0 +1,164 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering};
+use xelis_common::time::{get_current_time_in_millis, TimestampMillis};
+use crate::config::{MILLIS_PER_SECOND, P2P_RATE_LIMIT_MAX_VIOLATIONS};
+
+// Limits applied on each peer per second
+// A value of 0 means no limit
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimits {
+    // blocks propagated
+    pub blocks_per_second: u64,
+    // txs hashes propagated
+    pub txs_per_second: u64,
+    // bytes received
+    pub bytes_per_second: u64
+}
+
+impl RateLimits {
+    fn is_exceeded(limit: u64, value: u64) -> bool {
+        limit != 0 && value > limit
+    }
+}
+
+// Result of a packet recorded in the rate limiter
+#[derive(Debug, PartialEq, Eq)]
+pub enum RateLimitResult {
+    // Packet is accepted
+    Allowed,
+    // Propagated blocks or txs over the limits, the packet must be dropped
+    Drop,
+    // Peer exceeded the limits too many times
+    Abuse
+}
+
+// Rate limiter of a peer based on a fixed window of one second
+// It is only updated from the read task of the peer
+// Only propagated blocks and txs are dropped: other packets may be responses to our own requests
+pub struct RateLimiter {
+    // start of the current window
+    window_start: AtomicU64,
+    // blocks received in the current window
+    blocks: AtomicU64,
+    // txs received in the current window
+    txs: AtomicU64,
+    // bytes received in the current window
+    bytes: AtomicU64,
+    // if the peer exceeded the blocks or txs limit in the current window
+    violated: AtomicBool,
+    // count of windows in which the peer exceeded the blocks or txs limit
+    // decreased by one for each window without violation
+    violations: AtomicU8,
+    // total count of packets dropped
+    throttled: AtomicU64
+}
+
+impl RateLimiter {
+    pub fn new() -> Self {
+        Self {
+            window_start: AtomicU64::new(0),
+            blocks: AtomicU64::new(0),
+            txs: AtomicU64::new(0),
+            bytes: AtomicU64::new(0),
+            violated: AtomicBool::new(false),
+            violations: AtomicU8::new(0),
+            throttled: AtomicU64::new(0)
+        }
+    }
+
+    // Record a packet received from the peer
+    pub fn record(&self, limits: &RateLimits, blocks: u64, txs: u64, bytes: u64) -> RateLimitResult {
+        self.record_at(limits, blocks, txs, bytes, get_current_time_in_millis())
+    }
+
+    fn record_at(&self, limits: &RateLimits, blocks: u64, txs: u64, bytes: u64, now: TimestampMillis) -> RateLimitResult {
+        let window_start = self.window_start.load(Ordering::Relaxed);
+        if now.saturating_sub(window_start) >= MILLIS_PER_SECOND {
+            // each window without violation, even without any packet, decays the violations counter
+            let windows = now.saturating_sub(window_start) / MILLIS_PER_SECOND;
+            let clean_windows = if self.violated.swap(false, Ordering::Relaxed) { windows - 1 } else { windows };
+            let violations = self.violations.load(Ordering::Relaxed);
+            self.violations.store(violations.saturating_sub(clean_windows.min(u8::MAX as u64) as u8), Ordering::Relaxed);
+
+            self.window_start.store(now, Ordering::Relaxed);
+            self.blocks.store(0, Ordering::Relaxed);
+            self.txs.store(0, Ordering::Relaxed);
+            self.bytes.store(0, Ordering::Relaxed);
+        }
+
+        // Packets without blocks or txs may be responses to our requests (chain sync), they are never dropped
+        let propagation = blocks != 0 || txs != 0;
+
+        let blocks = self.blocks.fetch_add(blocks, Ordering::Relaxed) + blocks;
+        let txs = self.txs.fetch_add(txs, Ordering::Relaxed) + txs;
+        let bytes = self.bytes.fetch_add(bytes, Ordering::Relaxed) + bytes;
+
+        let objects_exceeded = RateLimits::is_exceeded(limits.blocks_per_second, blocks) || RateLimits::is_exceeded(limits.txs_per_second, txs);
+        let bytes_exceeded = RateLimits::is_exceeded(limits.bytes_per_second, bytes);
+        if !propagation || (!objects_exceeded && !bytes_exceeded) {
+            return RateLimitResult::Allowed
+        }
+
+        // bytes may be used by responses we requested, so only the blocks and txs limits are violations
+        if objects_exceeded && !self.violated.swap(true, Ordering::Relaxed) {
+            let violations = self.violations.load(Ordering::Relaxed).saturating_add(1);
+            self.violations.store(violations, Ordering::Relaxed);
+            if violations >= P2P_RATE_LIMIT_MAX_VIOLATIONS {
+                return RateLimitResult::Abuse
+            }
+        }
+
+        self.throttled.fetch_add(1, Ordering::Relaxed);
+        RateLimitResult::Drop
+    }
+
+    // Get how many packets of the peer got dropped
+    pub fn get_throttled_count(&self) -> u64 {
+        self.throttled.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMITS: RateLimits = RateLimits {
+        blocks_per_second: 1,
+        txs_per_second: 10,
+        bytes_per_second: 1000
+    };
+
+    #[test]
+    fn test_drop_propagation_only() {
+        let limiter = RateLimiter::new();
+        let now = 10 * MILLIS_PER_SECOND;
+        assert_eq!(limiter.record_at(&LIMITS, 1, 0, 100, now), RateLimitResult::Allowed);
+        assert_eq!(limiter.record_at(&LIMITS, 1, 0, 100, now), RateLimitResult::Drop);
+        // too many bytes but it may be a response we requested
+        assert_eq!(limiter.record_at(&LIMITS, 0, 0, 2000, now), RateLimitResult::Allowed);
+        assert_eq!(limiter.record_at(&LIMITS, 0, 1, 100, now), RateLimitResult::Drop);
+        assert_eq!(limiter.get_throttled_count(), 2);
+
+        // new window
+        assert_eq!(limiter.record_at(&LIMITS, 1, 0, 100, now + MILLIS_PER_SECOND), RateLimitResult::Allowed);
+    }
+
+    #[test]
+    fn test_violations_decay() {
+        let limiter = RateLimiter::new();
+        let mut now = 10 * MILLIS_PER_SECOND;
+        // violate in every other window, the clean windows compensate
+        for _ in 0..P2P_RATE_LIMIT_MAX_VIOLATIONS * 2 {
+            limiter.record_at(&LIMITS, 2, 0, 0, now);
+            now += 2 * MILLIS_PER_SECOND;
+        }
+        assert_eq!(limiter.record_at(&LIMITS, 2, 0, 0, now), RateLimitResult::Drop);
+
+        // violate in each window until the peer is considered abusive
+        let mut result = RateLimitResult::Allowed;
+        for _ in 0..P2P_RATE_LIMIT_MAX_VIOLATIONS {
+            now += MILLIS_PER_SECOND;
+            result = limiter.record_at(&LIMITS, 2, 0, 0, now);
+        }
+        assert_eq!(result, RateLimitResult::Abuse);
+    }
+}
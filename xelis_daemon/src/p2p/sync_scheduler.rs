@@ -0,0 +1,119 @@
+use std::{
+    collections::{BTreeSet, HashMap, VecDeque},
+    sync::Arc,
+    time::Duration
+};
+use log::{debug, trace, warn};
+use tokio::{task::JoinSet, time::timeout};
+use xelis_common::{
+    block::Block,
+    crypto::Hash
+};
+use crate::{
+    config::{CHAIN_SYNC_CHUNK_SIZE, CHAIN_SYNC_CHUNK_TIMEOUT_SECS, CHAIN_SYNC_CHUNKS_AHEAD},
+    core::{
+        blockchain::Blockchain,
+        error::BlockchainError,
+        storage::Storage
+    }
+};
+use super::{
+    error::P2pError,
+    packet::object::{ObjectRequest, OwnedObjectResponse},
+    peer::Peer
+};
+
+type ChunkResult = (usize, Arc<Peer>, Result<Vec<(Block, Hash)>, P2pError>);
+
+// Request all blocks of a chunk from a peer in the order given
+async fn request_chunk(peer: &Peer, hashes: Vec<Hash>) -> Result<Vec<(Block, Hash)>, P2pError> {
+    let mut blocks = Vec::with_capacity(hashes.len());
+    for hash in hashes {
+        match peer.request_blocking_object(ObjectRequest::Block(hash)).await? {
+            OwnedObjectResponse::Block(block, hash) => blocks.push((block, hash)),
+            _ => return Err(P2pError::ExpectedBlock)
+        }
+    }
+
+    Ok(blocks)
+}
+
+// Sync the blocks from several peers at once
+// Blocks hashes must be ordered by topoheight as received in a chain response
+// They are split in chunks requested concurrently to each peer,
+// and applied in the same order as soon as all previous chunks are applied
+// A chunk failing or stalling on a peer is re-assigned to another peer,
+// and the peer is not used anymore for this sync
+// Returns the count of blocks added to the chain
+pub async fn sync_blocks<S: Storage>(blockchain: &Arc<Blockchain<S>>, peers: Vec<Arc<Peer>>, hashes: Vec<Hash>) -> Result<usize, BlockchainError> {
+    let chunks: Vec<Vec<Hash>> = hashes.chunks(CHAIN_SYNC_CHUNK_SIZE).map(|chunk| chunk.to_vec()).collect();
+    debug!("Syncing {} blocks in {} chunks from {} peers", hashes.len(), chunks.len(), peers.len());
+
+    // Don't download too far ahead of the chunks applied to limit the memory usage
+    let window = peers.len() * CHAIN_SYNC_CHUNKS_AHEAD;
+    let mut idle_peers: VecDeque<Arc<Peer>> = peers.into();
+    let mut pending: BTreeSet<usize> = (0..chunks.len()).collect();
+    let mut downloaded: HashMap<usize, Vec<(Block, Hash)>> = HashMap::new();
+    let mut tasks: JoinSet<ChunkResult> = JoinSet::new();
+    let mut next_chunk = 0;
+    let mut total_added = 0;
+
+    while next_chunk < chunks.len() {
+        // assign the lowest pending chunks to the idle peers
+        while let Some(&index) = pending.first() {
+            if index >= next_chunk + window {
+                break;
+            }
+
+            let Some(peer) = idle_peers.pop_front() else {
+                break;
+            };
+
+            pending.remove(&index);
+            trace!("Requesting chunk {} to {}", index, peer);
+            let hashes = chunks[index].clone();
+            tasks.spawn(async move {
+                let res = match timeout(Duration::from_secs(CHAIN_SYNC_CHUNK_TIMEOUT_SECS), request_chunk(&peer, hashes)).await {
+                    Ok(res) => res,
+                    Err(e) => Err(P2pError::AsyncTimeOut(e))
+                };
+                (index, peer, res)
+            });
+        }
+
+        let Some(res) = tasks.join_next().await else {
+            warn!("No peer left to sync the remaining {} chunks", pending.len());
+            return Err(P2pError::NoSyncPeerAvailable.into())
+        };
+
+        let (index, peer, res) = res.map_err(|e| BlockchainError::Any(e.into()))?;
+        match res {
+            Ok(blocks) => {
+                trace!("Chunk {} received from {}", index, peer);
+                downloaded.insert(index, blocks);
+                idle_peers.push_back(peer);
+            },
+            Err(e) => {
+                warn!("Error while requesting chunk {} from {}: {}, re-assigning it", index, peer, e);
+                pending.insert(index);
+            }
+        }
+
+        // apply all the chunks ready in the chain order
+        while let Some(blocks) = downloaded.remove(&next_chunk) {
+            for (block, hash) in blocks {
+                // block may have been propagated to us in the meantime
+                if blockchain.has_block(&hash).await? {
+                    trace!("Block {} is already in chain, skipping it", hash);
+                    continue;
+                }
+
+                blockchain.add_new_block(block, false, false).await?;
+                total_added += 1;
+            }
+            next_chunk += 1;
+        }
+    }
+
+    Ok(total_added)
+}
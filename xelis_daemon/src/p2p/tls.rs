@@ -0,0 +1,222 @@
+use std::{
+    fs::File,
+    io::BufReader,
+    net::SocketAddr,
+    sync::Arc
+};
+use log::debug;
+use rand::Rng;
+use ring::{
+    rand::SystemRandom,
+    signature::{Ed25519KeyPair, KeyPair}
+};
+use thiserror::Error;
+use tokio::net::TcpStream;
+use tokio_rustls::{
+    client,
+    rustls::{
+        self,
+        client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+        crypto::{ring::default_provider, verify_tls12_signature, verify_tls13_signature},
+        pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer, ServerName, UnixTime},
+        ClientConfig,
+        DigitallySignedStruct,
+        ServerConfig,
+        SignatureScheme
+    },
+    server,
+    TlsAcceptor,
+    TlsConnector
+};
+
+// Ed25519 algorithm identifier (RFC 8410)
+const ED25519_OID: [u8; 3] = [0x2b, 0x65, 0x70];
+// Common name attribute
+const COMMON_NAME_OID: [u8; 3] = [0x55, 0x04, 0x03];
+// Subject alternative name extension
+const SUBJECT_ALT_NAME_OID: [u8; 3] = [0x55, 0x1d, 0x11];
+// Validity of the generated certificates, no expiration (RFC 5280)
+const NOT_BEFORE: &[u8] = b"240101000000Z";
+const NOT_AFTER: &[u8] = b"99991231235959Z";
+
+#[derive(Error, Debug)]
+pub enum TlsError {
+    #[error("Invalid TLS bind address '{}'", _0)]
+    InvalidBindAddress(String),
+    #[error("Invalid TLS server name '{}', expected a DNS name", _0)]
+    InvalidServerName(String),
+    #[error("Both TLS certificate and private key files must be provided")]
+    MissingCertificateOrKey,
+    #[error("No certificate found in '{}'", _0)]
+    NoCertificate(String),
+    #[error("No private key found in '{}'", _0)]
+    NoPrivateKey(String),
+    #[error("Error while generating the TLS certificate")]
+    CertificateGeneration,
+    #[error(transparent)]
+    Rustls(#[from] rustls::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error)
+}
+
+// Optional TLS layer of the P2P connections
+// This is only used to look like regular HTTPS traffic in networks throttling
+// unknown protocols: peers are not authenticated by their certificates,
+// the P2P encryption is still applied inside the TLS stream
+pub struct P2pTls {
+    // address of the TLS listener, None if only used for outgoing connections
+    bind_address: Option<SocketAddr>,
+    acceptor: TlsAcceptor,
+    connector: TlsConnector,
+    // name sent in the SNI extension and used in the generated certificate
+    server_name: ServerName<'static>,
+    // Connect using TLS to the peers advertising a TLS port
+    outgoing: bool
+}
+
+impl P2pTls {
+    // Create the TLS layer using the certificate and private key PEM files provided
+    // or an ephemeral self-signed certificate for the server name
+    pub fn new(bind_address: Option<&str>, name: &str, cert_path: Option<&str>, key_path: Option<&str>, outgoing: bool) -> Result<Self, TlsError> {
+        let bind_address = bind_address.map(|addr| addr.parse::<SocketAddr>().map_err(|_| TlsError::InvalidBindAddress(addr.to_owned())))
+            .transpose()?;
+
+        let server_name = match ServerName::try_from(name.to_owned()) {
+            Ok(server_name @ ServerName::DnsName(_)) => server_name,
+            _ => return Err(TlsError::InvalidServerName(name.to_owned()))
+        };
+
+        let (certs, key) = match (cert_path, key_path) {
+            (Some(cert_path), Some(key_path)) => Self::load_certificate(cert_path, key_path)?,
+            (None, None) => {
+                debug!("Generating an ephemeral TLS certificate for {}", name);
+                let (cert, key) = generate_self_signed_certificate(name)?;
+                (vec![cert], key)
+            },
+            _ => return Err(TlsError::MissingCertificateOrKey)
+        };
+
+        let server_config = ServerConfig::builder()
+            .with_no_client_auth()
+            .with_single_cert(certs, key)?;
+
+        let client_config = ClientConfig::builder()
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(NoCertificateVerification))
+            .with_no_client_auth();
+
+        Ok(Self {
+            bind_address,
+            acceptor: TlsAcceptor::from(Arc::new(server_config)),
+            connector: TlsConnector::from(Arc::new(client_config)),
+            server_name,
+            outgoing
+        })
+    }
+
+    // Load the certificate chain and private key from PEM files
+    fn load_certificate(cert_path: &str, key_path: &str) -> Result<(Vec<CertificateDer<'static>>, PrivateKeyDer<'static>), TlsError> {
+        let mut reader = BufReader::new(File::open(cert_path)?);
+        let certs = rustls_pemfile::certs(&mut reader).collect::<Result<Vec<_>, _>>()?;
+        if certs.is_empty() {
+            return Err(TlsError::NoCertificate(cert_path.to_owned()))
+        }
+
+        let mut reader = BufReader::new(File::open(key_path)?);
+        let key = rustls_pemfile::private_key(&mut reader)?
+            .ok_or_else(|| TlsError::NoPrivateKey(key_path.to_owned()))?;
+
+        Ok((certs, key))
+    }
+
+    pub fn get_bind_address(&self) -> Option<&SocketAddr> {
+        self.bind_address.as_ref()
+    }
+
+    pub fn is_outgoing_enabled(&self) -> bool {
+        self.outgoing
+    }
+
+    // Accept a TLS session on an incoming connection
+    pub async fn accept(&self, stream: TcpStream) -> Result<server::TlsStream<TcpStream>, TlsError> {
+        Ok(self.acceptor.accept(stream).await?)
+    }
+
+    // Start a TLS session on an outgoing connection
+    pub async fn connect(&self, stream: TcpStream) -> Result<client::TlsStream<TcpStream>, TlsError> {
+        Ok(self.connector.connect(self.server_name.clone(), stream).await?)
+    }
+}
+
+// Peers certificates are self-signed, they can't be verified
+// Only the handshake signature is checked to complete the TLS session
+#[derive(Debug)]
+struct NoCertificateVerification;
+
+impl ServerCertVerifier for NoCertificateVerification {
+    fn verify_server_cert(&self, _: &CertificateDer<'_>, _: &[CertificateDer<'_>], _: &ServerName<'_>, _: &[u8], _: UnixTime) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(&self, message: &[u8], cert: &CertificateDer<'_>, dss: &DigitallySignedStruct) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(message, cert, dss, &default_provider().signature_verification_algorithms)
+    }
+
+    fn verify_tls13_signature(&self, message: &[u8], cert: &CertificateDer<'_>, dss: &DigitallySignedStruct) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(message, cert, dss, &default_provider().signature_verification_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        default_provider().signature_verification_algorithms.supported_schemes()
+    }
+}
+
+// Encode a DER element
+fn der(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut bytes = vec![tag];
+    let len = content.len();
+    if len < 0x80 {
+        bytes.push(len as u8);
+    } else if len <= 0xff {
+        bytes.extend([0x81, len as u8]);
+    } else {
+        bytes.extend([0x82, (len >> 8) as u8, len as u8]);
+    }
+    bytes.extend_from_slice(content);
+    bytes
+}
+
+// Generate a X.509 v3 certificate self-signed with a new Ed25519 key
+fn generate_self_signed_certificate(server_name: &str) -> Result<(CertificateDer<'static>, PrivateKeyDer<'static>), TlsError> {
+    let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new())
+        .map_err(|_| TlsError::CertificateGeneration)?;
+    let key_pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref())
+        .map_err(|_| TlsError::CertificateGeneration)?;
+
+    // positive serial number without leading zero
+    let mut serial: [u8; 8] = rand::thread_rng().gen();
+    serial[0] = (serial[0] & 0x7f) | 0x40;
+
+    let algorithm = der(0x30, &der(0x06, &ED25519_OID));
+    let name = der(0x30, &der(0x31, &der(0x30, &[der(0x06, &COMMON_NAME_OID), der(0x0c, server_name.as_bytes())].concat())));
+    let validity = der(0x30, &[der(0x17, NOT_BEFORE), der(0x18, NOT_AFTER)].concat());
+    let public_key = der(0x30, &[algorithm.clone(), der(0x03, &[&[0], key_pair.public_key().as_ref()].concat())].concat());
+    let alt_name = der(0x30, &[der(0x06, &SUBJECT_ALT_NAME_OID), der(0x04, &der(0x30, &der(0x82, server_name.as_bytes())))].concat());
+    let extensions = der(0xa3, &der(0x30, &alt_name));
+
+    let tbs = der(0x30, &[
+        der(0xa0, &der(0x02, &[2])),
+        der(0x02, &serial),
+        algorithm.clone(),
+        name.clone(),
+        validity,
+        name,
+        public_key,
+        extensions
+    ].concat());
+
+    let signature = key_pair.sign(&tbs);
+    let cert = der(0x30, &[tbs, algorithm, der(0x03, &[&[0], signature.as_ref()].concat())].concat());
+
+    Ok((CertificateDer::from(cert), PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(pkcs8.as_ref().to_vec()))))
+}
@@ -0,0 +1,240 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    time::Duration
+};
+use log::{debug, trace};
+use thiserror::Error;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpStream, UdpSocket},
+    time::timeout
+};
+
+// SSDP multicast address used to discover the gateway
+const SSDP_ADDRESS: &str = "239.255.255.250:1900";
+const SSDP_SEARCH_TARGET: &str = "urn:schemas-upnp-org:device:InternetGatewayDevice:1";
+// Services supported to request a port mapping, in order of preference
+const SERVICE_TYPES: [&str; 3] = [
+    "urn:schemas-upnp-org:service:WANIPConnection:2",
+    "urn:schemas-upnp-org:service:WANIPConnection:1",
+    "urn:schemas-upnp-org:service:WANPPPConnection:1"
+];
+const PORT_MAPPING_DESCRIPTION: &str = "XELIS P2P";
+// Max size of a HTTP response from the gateway
+const MAX_RESPONSE_SIZE: u64 = 64 * 1024;
+
+#[derive(Error, Debug)]
+pub enum UpnpError {
+    #[error("No port mapping service found on the gateway")]
+    NoService,
+    #[error("Invalid URL '{}' received from gateway", _0)]
+    InvalidUrl(String),
+    #[error("Invalid response from gateway: {}", _0)]
+    InvalidResponse(&'static str),
+    #[error("Gateway returned an error for action {}: {}", _0, _1)]
+    ActionFailed(&'static str, String),
+    #[error("Gateway request timed out")]
+    Timeout,
+    #[error(transparent)]
+    Io(#[from] std::io::Error)
+}
+
+// A HTTP URL from the gateway split in host and path
+struct Url {
+    host: String,
+    path: String
+}
+
+impl Url {
+    fn parse(value: &str) -> Result<Self, UpnpError> {
+        let without_scheme = value.strip_prefix("http://").ok_or_else(|| UpnpError::InvalidUrl(value.to_owned()))?;
+        let (host, path) = match without_scheme.find('/') {
+            Some(index) => (&without_scheme[..index], &without_scheme[index..]),
+            None => (without_scheme, "/")
+        };
+
+        if host.is_empty() {
+            return Err(UpnpError::InvalidUrl(value.to_owned()))
+        }
+
+        Ok(Self {
+            host: host.to_owned(),
+            path: path.to_owned()
+        })
+    }
+}
+
+// Port mapping requested on the gateway of the local network (UPnP IGD)
+// The mapping has a lease duration and must be renewed before it expires
+pub struct PortMapping {
+    // host:port of the gateway control endpoint
+    control_host: String,
+    control_path: String,
+    service_type: &'static str,
+    // our IP address on the local network
+    local_ip: IpAddr,
+    port: u16
+}
+
+impl PortMapping {
+    // Discover the gateway and request a TCP port mapping for the port
+    // The same port is used for the external and internal port
+    pub async fn request(port: u16, lease_duration: u32, timeout_duration: Duration) -> Result<Self, UpnpError> {
+        let location = with_timeout(timeout_duration, discover_gateway()).await?;
+        debug!("UPnP gateway found at {}", location);
+        let location = Url::parse(&location)?;
+
+        let (local_ip, description) = with_timeout(timeout_duration, http_request(&location.host, &format!("GET {} HTTP/1.0\r\nHost: {}\r\n\r\n", location.path, location.host))).await?;
+        let (service_type, control_url) = find_control_url(&description)?;
+        let (control_host, control_path) = if control_url.starts_with("http://") {
+            let url = Url::parse(&control_url)?;
+            (url.host, url.path)
+        } else if control_url.starts_with('/') {
+            (location.host, control_url)
+        } else {
+            (location.host, format!("/{}", control_url))
+        };
+
+        let mapping = Self {
+            control_host,
+            control_path,
+            service_type,
+            local_ip,
+            port
+        };
+        mapping.renew(lease_duration, timeout_duration).await?;
+
+        Ok(mapping)
+    }
+
+    pub fn get_port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn get_local_ip(&self) -> &IpAddr {
+        &self.local_ip
+    }
+
+    // Request again the port mapping to extend its lease
+    pub async fn renew(&self, lease_duration: u32, timeout_duration: Duration) -> Result<(), UpnpError> {
+        let arguments = format!(
+            "<NewRemoteHost></NewRemoteHost><NewExternalPort>{port}</NewExternalPort><NewProtocol>TCP</NewProtocol><NewInternalPort>{port}</NewInternalPort><NewInternalClient>{ip}</NewInternalClient><NewEnabled>1</NewEnabled><NewPortMappingDescription>{description}</NewPortMappingDescription><NewLeaseDuration>{lease_duration}</NewLeaseDuration>",
+            port = self.port,
+            ip = self.local_ip,
+            description = PORT_MAPPING_DESCRIPTION,
+            lease_duration = lease_duration
+        );
+        with_timeout(timeout_duration, self.soap_request("AddPortMapping", &arguments)).await?;
+        Ok(())
+    }
+
+    // Remove the port mapping from the gateway
+    pub async fn remove(&self, timeout_duration: Duration) -> Result<(), UpnpError> {
+        let arguments = format!("<NewRemoteHost></NewRemoteHost><NewExternalPort>{}</NewExternalPort><NewProtocol>TCP</NewProtocol>", self.port);
+        with_timeout(timeout_duration, self.soap_request("DeletePortMapping", &arguments)).await?;
+        Ok(())
+    }
+
+    // Get the external IP address of the gateway
+    pub async fn get_external_ip(&self, timeout_duration: Duration) -> Result<IpAddr, UpnpError> {
+        let response = with_timeout(timeout_duration, self.soap_request("GetExternalIPAddress", "")).await?;
+        let ip = find_tag(&response, "NewExternalIPAddress").ok_or(UpnpError::InvalidResponse("no external IP address"))?;
+        ip.trim().parse().map_err(|_| UpnpError::InvalidResponse("invalid external IP address"))
+    }
+
+    async fn soap_request(&self, action: &'static str, arguments: &str) -> Result<String, UpnpError> {
+        trace!("UPnP action {} on {}{}", action, self.control_host, self.control_path);
+        let body = format!(
+            "<?xml version=\"1.0\"?><s:Envelope xmlns:s=\"http://schemas.xmlsoap.org/soap/envelope/\" s:encodingStyle=\"http://schemas.xmlsoap.org/soap/encoding/\"><s:Body><u:{action} xmlns:u=\"{service}\">{arguments}</u:{action}></s:Body></s:Envelope>",
+            action = action,
+            service = self.service_type,
+            arguments = arguments
+        );
+        let request = format!(
+            "POST {} HTTP/1.0\r\nHost: {}\r\nContent-Type: text/xml; charset=\"utf-8\"\r\nSOAPAction: \"{}#{}\"\r\nContent-Length: {}\r\n\r\n{}",
+            self.control_path,
+            self.control_host,
+            self.service_type,
+            action,
+            body.len(),
+            body
+        );
+
+        http_request(&self.control_host, &request).await
+            .map(|(_, response)| response)
+            .map_err(|e| match e {
+                UpnpError::InvalidResponse(_) => UpnpError::ActionFailed(action, e.to_string()),
+                e => e
+            })
+    }
+}
+
+async fn with_timeout<T, F: std::future::Future<Output = Result<T, UpnpError>>>(duration: Duration, future: F) -> Result<T, UpnpError> {
+    timeout(duration, future).await.map_err(|_| UpnpError::Timeout)?
+}
+
+// Search the gateway using SSDP and returns its description location
+async fn discover_gateway() -> Result<String, UpnpError> {
+    let socket = UdpSocket::bind("0.0.0.0:0").await?;
+    let request = format!("M-SEARCH * HTTP/1.1\r\nHOST: {}\r\nMAN: \"ssdp:discover\"\r\nMX: 2\r\nST: {}\r\n\r\n", SSDP_ADDRESS, SSDP_SEARCH_TARGET);
+    socket.send_to(request.as_bytes(), SSDP_ADDRESS).await?;
+
+    let mut buffer = [0u8; 2048];
+    loop {
+        let (read, addr) = socket.recv_from(&mut buffer).await?;
+        let response = String::from_utf8_lossy(&buffer[..read]);
+        trace!("SSDP response from {}: {}", addr, response);
+        let location = response.lines()
+            .filter_map(|line| line.split_once(':'))
+            .find(|(key, _)| key.trim().eq_ignore_ascii_case("location"))
+            .map(|(_, value)| value.trim().to_owned());
+
+        if let Some(location) = location {
+            return Ok(location)
+        }
+    }
+}
+
+// Send a HTTP/1.0 request and returns our local IP used and the response body
+// HTTP/1.0 is used to not have to handle chunked responses
+async fn http_request(host: &str, request: &str) -> Result<(IpAddr, String), UpnpError> {
+    let mut stream = TcpStream::connect(host).await?;
+    let local_addr: SocketAddr = stream.local_addr()?;
+    stream.write_all(request.as_bytes()).await?;
+
+    let mut response = Vec::new();
+    stream.take(MAX_RESPONSE_SIZE).read_to_end(&mut response).await?;
+    let response = String::from_utf8_lossy(&response);
+
+    let (headers, body) = response.split_once("\r\n\r\n").ok_or(UpnpError::InvalidResponse("no HTTP body"))?;
+    let status = headers.lines().next().ok_or(UpnpError::InvalidResponse("no HTTP status"))?;
+    if status.split_whitespace().nth(1) != Some("200") {
+        debug!("UPnP gateway answered with status '{}': {}", status, body);
+        return Err(UpnpError::InvalidResponse("HTTP status is not 200"))
+    }
+
+    Ok((local_addr.ip(), body.to_owned()))
+}
+
+// Find the content of the first tag with this name
+fn find_tag<'a>(xml: &'a str, tag: &str) -> Option<&'a str> {
+    let start_tag = format!("<{}>", tag);
+    let start = xml.find(&start_tag)? + start_tag.len();
+    let end = xml[start..].find(&format!("</{}>", tag))?;
+    Some(&xml[start..start + end])
+}
+
+// Find the control URL of a supported service in the gateway description
+fn find_control_url(description: &str) -> Result<(&'static str, String), UpnpError> {
+    for service_type in SERVICE_TYPES {
+        let tag = format!("<serviceType>{}</serviceType>", service_type);
+        if let Some(index) = description.find(&tag) {
+            let service = &description[index..];
+            if let Some(control_url) = find_tag(service, "controlURL") {
+                return Ok((service_type, control_url.trim().to_owned()))
+            }
+        }
+    }
+
+    Err(UpnpError::NoService)
+}
@@ -0,0 +1,206 @@
+use std::{str::FromStr, sync::Arc};
+use anyhow::Context as AnyContext;
+use log::{info, warn};
+use serde_json::{json, Value};
+use xelis_common::{
+    api::daemon::{
+        AdminBanPeerParams,
+        AdminPeerAddressParams,
+        AdminPruneChainParams,
+        AdminPruneChainResult,
+        AdminRewindChainParams,
+        AdminRewindChainResult,
+        AdminSetLogLevelParams,
+        AdminUnbanPeerParams,
+        AdminValidateSupplyParams
+    },
+    async_handler,
+    context::Context,
+    prompt::LogLevel,
+    rpc_server::{
+        parse_params,
+        InternalRpcError,
+        RPCHandler
+    }
+};
+use crate::{
+    core::{
+        blockchain::Blockchain,
+        storage::Storage
+    },
+    p2p::P2pServer
+};
+use super::ApiError;
+
+// Register the operator methods under the admin namespace
+// They must only be registered in the admin handler which requires authentication
+pub fn register_methods<S: Storage>(handler: &mut RPCHandler<Arc<Blockchain<S>>>) {
+    info!("Registering admin RPC methods...");
+    handler.register_method("admin.rewind_chain", async_handler!(rewind_chain::<S>));
+    handler.register_method("admin.prune_chain", async_handler!(prune_chain::<S>));
+    handler.register_method("admin.clear_caches", async_handler!(clear_caches::<S>));
+    handler.register_method("admin.run_storage_maintenance", async_handler!(run_storage_maintenance::<S>));
+    handler.register_method("admin.clear_mempool", async_handler!(clear_mempool::<S>));
+    handler.register_method("admin.get_local_transactions", async_handler!(get_local_transactions::<S>));
+    handler.register_method("admin.set_log_level", async_handler!(set_log_level::<S>));
+    handler.register_method("admin.connect_peer", async_handler!(connect_peer::<S>));
+    handler.register_method("admin.kick_peer", async_handler!(kick_peer::<S>));
+    handler.register_method("admin.ban_peer", async_handler!(ban_peer::<S>));
+    handler.register_method("admin.unban_peer", async_handler!(unban_peer::<S>));
+    handler.register_method("admin.promote_standby", async_handler!(promote_standby::<S>));
+    handler.register_method("admin.validate_supply", async_handler!(validate_supply::<S>));
+}
+
+async fn get_p2p<S: Storage>(blockchain: &Blockchain<S>) -> Result<Arc<P2pServer<S>>, InternalRpcError> {
+    let p2p = { blockchain.get_p2p().read().await.clone() };
+    p2p.ok_or_else(|| InternalRpcError::InvalidParamsAny(ApiError::NoP2p.into()))
+}
+
+async fn rewind_chain<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: AdminRewindChainParams = parse_params(body)?;
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    if params.count == 0 || params.count >= blockchain.get_topo_height() {
+        return Err(InternalRpcError::InvalidParams("Invalid amount of blocks to rewind"))
+    }
+
+    warn!("Rewinding {} blocks from admin RPC", params.count);
+    let topoheight = blockchain.rewind_chain(params.count, params.until_stable_height).await.context("Error while rewinding chain")?;
+    Ok(json!(AdminRewindChainResult { topoheight }))
+}
+
+async fn prune_chain<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: AdminPruneChainParams = parse_params(body)?;
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+
+    info!("Pruning chain until topoheight {} from admin RPC", params.topoheight);
+    let pruned_topoheight = blockchain.prune_until_topoheight(params.topoheight).await.context("Error while pruning chain")?;
+    Ok(json!(AdminPruneChainResult { pruned_topoheight }))
+}
+
+async fn clear_caches<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    if body != Value::Null {
+        return Err(InternalRpcError::UnexpectedParams)
+    }
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    blockchain.clear_caches().await.context("Error while clearing caches")?;
+    Ok(json!(true))
+}
+
+async fn run_storage_maintenance<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    if body != Value::Null {
+        return Err(InternalRpcError::UnexpectedParams)
+    }
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+
+    info!("Running storage maintenance from admin RPC");
+    let result = blockchain.run_storage_maintenance().await.context("Error while running storage maintenance")?;
+    Ok(json!(result))
+}
+
+async fn clear_mempool<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    if body != Value::Null {
+        return Err(InternalRpcError::UnexpectedParams)
+    }
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let mut mempool = blockchain.get_mempool().write().await;
+    mempool.clear();
+    Ok(json!(true))
+}
+
+// Transactions submitted through RPC which are broadcasted again until they leave the mempool
+async fn get_local_transactions<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    if body != Value::Null {
+        return Err(InternalRpcError::UnexpectedParams)
+    }
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    Ok(json!(blockchain.get_local_txs().await))
+}
+
+async fn set_log_level<S: Storage>(_: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: AdminSetLogLevelParams = parse_params(body)?;
+    let level = LogLevel::from_str(&params.level)
+        .map_err(|_| InternalRpcError::InvalidParamsAny(ApiError::InvalidLogLevel(params.level.into_owned()).into()))?;
+
+    log::set_max_level(level.into());
+    info!("Log level set to {} from admin RPC", level);
+    Ok(json!(true))
+}
+
+async fn connect_peer<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: AdminPeerAddressParams = parse_params(body)?;
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let p2p = get_p2p(blockchain).await?;
+
+    p2p.try_to_connect_to_peer(params.address, false).await;
+    Ok(json!(true))
+}
+
+// Returns false if no peer is connected with this address
+async fn kick_peer<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: AdminPeerAddressParams = parse_params(body)?;
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let p2p = get_p2p(blockchain).await?;
+
+    let peer = p2p.get_peer_list().get_peer_by_addr(&params.address).await;
+    match peer {
+        Some(peer) => {
+            peer.close().await.context("Error while closing peer connection")?;
+            Ok(json!(true))
+        },
+        None => Ok(json!(false))
+    }
+}
+
+// Ban temporarily the IP if a duration is set, otherwise blacklist it
+// Connected peers using this IP are disconnected
+async fn ban_peer<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: AdminBanPeerParams = parse_params(body)?;
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let p2p = get_p2p(blockchain).await?;
+    let peer_list = p2p.get_peer_list();
+
+    match params.seconds {
+        Some(seconds) => {
+            peer_list.temp_ban_address(&params.ip, seconds).await;
+            for peer in peer_list.get_cloned_peers().await {
+                if peer.get_connection().get_address().ip() == params.ip {
+                    peer_list.temp_ban_peer(&peer, seconds).await;
+                }
+            }
+        },
+        None => peer_list.blacklist_address(&params.ip).await
+    };
+
+    Ok(json!(true))
+}
+
+// Returns false if the IP was not banned
+async fn unban_peer<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: AdminUnbanPeerParams = parse_params(body)?;
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let p2p = get_p2p(blockchain).await?;
+
+    Ok(json!(p2p.get_peer_list().unban_address(&params.ip).await))
+}
+
+// Switch the standby node to full mode
+// Returns false if the node was not in standby mode
+async fn promote_standby<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    if body != Value::Null {
+        return Err(InternalRpcError::UnexpectedParams)
+    }
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+
+    warn!("Promoting standby node from admin RPC");
+    Ok(json!(blockchain.promote().await))
+}
+
+async fn validate_supply<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: AdminValidateSupplyParams = parse_params(body)?;
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+
+    info!("Validating supply from admin RPC");
+    let storage = blockchain.get_storage().read().await;
+    let result = blockchain.validate_supply(&storage, params.start_topoheight, params.end_topoheight).await.context("Error while validating supply")?;
+    Ok(json!(result))
+}
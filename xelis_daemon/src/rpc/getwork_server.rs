@@ -34,10 +34,15 @@ use rand::{
 };
 use serde::Serialize;
 use serde_json::json;
-use tokio::sync::Mutex;
+use tokio::{
+    sync::Mutex,
+    task::spawn_blocking
+};
 use xelis_common::{
     api::daemon::{
         GetMinerWorkResult,
+        GetWorkMinerEntry,
+        GetWorkMinersResult,
         SubmitMinerWorkParams
     },
     block::{
@@ -45,11 +50,15 @@ use xelis_common::{
         MinerWork
     },
     crypto::{
+        Address,
         Hash,
         Hashable,
         PublicKey
     },
-    difficulty::Difficulty,
+    difficulty::{
+        check_difficulty,
+        Difficulty
+    },
     immutable::Immutable,
     rpc_server::{
         InternalRpcError,
@@ -69,10 +78,19 @@ use crate::{
     },
     config::{
         DEV_PUBLIC_KEY,
+        GETWORK_VARDIFF_INITIAL_DIFFICULTY,
+        GETWORK_VARDIFF_MAX_FACTOR,
+        GETWORK_VARDIFF_MIN_DIFFICULTY,
+        GETWORK_VARDIFF_RETARGET_MILLIS,
+        GETWORK_VARDIFF_SHARE_TIME_MILLIS,
+        MILLIS_PER_SECOND,
         STABLE_LIMIT
     }
 };
 
+// Keep the PoW hashes of the last N shares of a miner to detect duplicates
+const SUBMITTED_SHARES_CACHE_SIZE: usize = 1024;
+
 pub type SharedGetWorkServer<S> = Arc<GetWorkServer<S>>;
 
 #[derive(Serialize, PartialEq)]
@@ -80,7 +98,8 @@ pub type SharedGetWorkServer<S> = Arc<GetWorkServer<S>>;
 pub enum Response {
     NewJob(GetMinerWorkResult),
     BlockAccepted,
-    BlockRejected(String)
+    BlockRejected(String),
+    ShareAccepted
 }
 
 impl TMessage for Response {
@@ -101,19 +120,47 @@ pub struct Miner {
     // blocks rejected since he is connected
     blocks_rejected: usize,
     // timestamp of the last invalid block received
-    last_invalid_block: TimestampMillis
+    last_invalid_block: TimestampMillis,
+    // difficulty expected for each share, adjusted to its hashrate
+    difficulty: Difficulty,
+    // shares accepted since he is connected
+    shares_accepted: usize,
+    // invalid shares received since he is connected
+    shares_rejected: usize,
+    // shares accepted since the last difficulty adjustment
+    shares_since_retarget: u64,
+    // timestamp of the last difficulty adjustment
+    last_retarget: TimestampMillis,
+    // sum of the difficulties of the accepted shares
+    total_work: Difficulty,
+    // PoW hashes of the last shares received to detect duplicates
+    submitted_shares: LruCache<Hash, ()>,
+    // jobs sent since he is connected
+    jobs_sent: usize,
+    // timestamp of the last job sent
+    last_job: Option<TimestampMillis>
 }
 
 impl Miner {
     pub fn new(mainnet: bool, key: PublicKey, name: String) -> Self {
+        let now = get_current_time_in_millis();
         Self {
             mainnet,
-            first_seen: get_current_time_in_millis(),
+            first_seen: now,
             key,
             name,
             blocks_accepted: IndexSet::new(),
             blocks_rejected: 0,
-            last_invalid_block: 0
+            last_invalid_block: 0,
+            difficulty: GETWORK_VARDIFF_INITIAL_DIFFICULTY,
+            shares_accepted: 0,
+            shares_rejected: 0,
+            shares_since_retarget: 0,
+            last_retarget: now,
+            total_work: Difficulty::zero(),
+            submitted_shares: LruCache::new(NonZeroUsize::new(SUBMITTED_SHARES_CACHE_SIZE).unwrap()),
+            jobs_sent: 0,
+            last_job: None
         }
     }
 
@@ -132,12 +179,97 @@ impl Miner {
     pub fn get_blocks_accepted(&self) -> usize {
         self.blocks_accepted.len()
     }
+
+    pub fn get_blocks_rejected(&self) -> usize {
+        self.blocks_rejected
+    }
+
+    pub fn get_difficulty(&self) -> &Difficulty {
+        &self.difficulty
+    }
+
+    pub fn get_shares_accepted(&self) -> usize {
+        self.shares_accepted
+    }
+
+    pub fn get_shares_rejected(&self) -> usize {
+        self.shares_rejected
+    }
+
+    // Hashrate estimated from the work of the accepted shares since he is connected
+    pub fn get_estimated_hashrate(&self) -> f64 {
+        let elapsed = get_current_time_in_millis().saturating_sub(self.first_seen).max(1);
+        let work: f64 = self.total_work.into();
+        work * MILLIS_PER_SECOND as f64 / elapsed as f64
+    }
+
+    pub fn get_address(&self) -> Address {
+        self.key.as_address(self.mainnet)
+    }
+
+    pub fn get_jobs_sent(&self) -> usize {
+        self.jobs_sent
+    }
+
+    pub fn get_last_job_timestamp(&self) -> Option<TimestampMillis> {
+        self.last_job
+    }
+
+    fn add_job_sent(&mut self) {
+        self.jobs_sent += 1;
+        self.last_job = Some(get_current_time_in_millis());
+    }
+
+    fn add_accepted_share(&mut self) {
+        self.shares_accepted += 1;
+        self.shares_since_retarget += 1;
+        self.total_work += self.difficulty;
+    }
+
+    fn add_rejected_share(&mut self) {
+        self.shares_rejected += 1;
+        self.last_invalid_block = get_current_time_in_millis();
+    }
+
+    // Adjust the share difficulty so the miner sends a share every GETWORK_VARDIFF_SHARE_TIME_MILLIS
+    // It is done at most once every GETWORK_VARDIFF_RETARGET_MILLIS
+    fn update_difficulty(&mut self) {
+        let now = get_current_time_in_millis();
+        let elapsed = now.saturating_sub(self.last_retarget);
+        if elapsed < GETWORK_VARDIFF_RETARGET_MILLIS {
+            return;
+        }
+
+        let difficulty = compute_share_difficulty(self.difficulty, self.shares_since_retarget, elapsed);
+        if difficulty != self.difficulty {
+            debug!("Share difficulty of {} adjusted from {} to {}", self, self.difficulty, difficulty);
+            self.difficulty = difficulty;
+        }
+        self.shares_since_retarget = 0;
+        self.last_retarget = now;
+    }
+}
+
+// Compute the next share difficulty of a miner based on the shares it sent during the elapsed time
+// The change is bounded by GETWORK_VARDIFF_MAX_FACTOR to stay stable on hashrate spikes
+fn compute_share_difficulty(difficulty: Difficulty, shares: u64, elapsed: TimestampMillis) -> Difficulty {
+    let next = if shares == 0 {
+        // no share received, its difficulty is too high
+        difficulty / GETWORK_VARDIFF_MAX_FACTOR
+    } else {
+        // shares * difficulty is the work done during the elapsed time
+        difficulty * shares * GETWORK_VARDIFF_SHARE_TIME_MILLIS / elapsed.max(1)
+    };
+
+    next.min(difficulty * GETWORK_VARDIFF_MAX_FACTOR)
+        .max(difficulty / GETWORK_VARDIFF_MAX_FACTOR)
+        .max(GETWORK_VARDIFF_MIN_DIFFICULTY)
 }
 
 impl Display for Miner {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let valid_blocks = self.blocks_accepted.iter().map(|h| h.to_string()).collect::<Vec<_>>().join(",");
-        write!(f, "Miner[address={}, name={}, accepted={} ({}), rejected={}]", self.key.as_address(self.mainnet), self.name, self.blocks_accepted.len(), valid_blocks, self.blocks_rejected)
+        write!(f, "Miner[address={}, name={}, accepted={} ({}), rejected={}, shares={}/{}, difficulty={}]", self.key.as_address(self.mainnet), self.name, self.blocks_accepted.len(), valid_blocks, self.blocks_rejected, self.shares_accepted, self.shares_rejected, self.difficulty)
     }
 }
 
@@ -243,10 +375,53 @@ impl<S: Storage> GetWorkServer<S> {
         &self.miners
     }
 
+    // Build the statistics of all the miners connected
+    pub async fn get_miners_stats(&self) -> GetWorkMinersResult<'static> {
+        let miners = self.miners.lock().await;
+        let mut result = GetWorkMinersResult {
+            total_hashrate: 0f64,
+            total_shares_accepted: 0,
+            total_shares_rejected: 0,
+            last_job_timestamp: None,
+            miners: Vec::with_capacity(miners.len())
+        };
+
+        for miner in miners.values() {
+            let entry = GetWorkMinerEntry {
+                address: Cow::Owned(miner.get_address()),
+                name: Cow::Owned(miner.get_name().clone()),
+                first_seen: miner.first_seen(),
+                share_difficulty: *miner.get_difficulty(),
+                shares_accepted: miner.get_shares_accepted(),
+                shares_rejected: miner.get_shares_rejected(),
+                blocks_accepted: miner.get_blocks_accepted(),
+                blocks_rejected: miner.get_blocks_rejected(),
+                hashrate: miner.get_estimated_hashrate(),
+                jobs_sent: miner.get_jobs_sent(),
+                last_job_timestamp: miner.get_last_job_timestamp()
+            };
+
+            result.total_hashrate += entry.hashrate;
+            result.total_shares_accepted += entry.shares_accepted;
+            result.total_shares_rejected += entry.shares_rejected;
+            result.last_job_timestamp = result.last_job_timestamp.max(entry.last_job_timestamp);
+            result.miners.push(entry);
+        }
+
+        result
+    }
+
     // retrieve last mining job and set random extra nonce and miner public key
-    // then, send it
-    async fn send_new_job(self: Arc<Self>, addr: Addr<GetWorkWebSocketHandler<S>>, key: PublicKey) -> Result<(), InternalRpcError> {
+    // then, send it with the share difficulty of the miner
+    async fn send_new_job(self: Arc<Self>, addr: Addr<GetWorkWebSocketHandler<S>>) -> Result<(), InternalRpcError> {
         debug!("Sending new job to miner");
+        let (key, share_difficulty) = {
+            let mut miners = self.miners.lock().await;
+            let miner = miners.get_mut(&addr).ok_or(InternalRpcError::ClientNotFound)?;
+            miner.update_difficulty();
+            (miner.key.clone(), miner.difficulty)
+        };
+
         let (mut job, height, difficulty) = {
             let mut hash = self.last_header_hash.lock().await;
             let mut mining_jobs = self.mining_jobs.lock().await;
@@ -285,7 +460,12 @@ impl<S: Storage> GetWorkServer<S> {
 
         let topoheight = self.blockchain.get_topo_height();
         debug!("Sending job to new miner");
+        let difficulty = share_difficulty.min(difficulty);
         addr.send(Response::NewJob(GetMinerWorkResult { template: job.to_hex(), height, topoheight, difficulty })).await.context("error while sending block template")??;
+
+        if let Some(miner) = self.miners.lock().await.get_mut(&addr) {
+            miner.add_job_sent();
+        }
         Ok(())
     }
 
@@ -301,7 +481,7 @@ impl<S: Storage> GetWorkServer<S> {
         // notify the new miner so he can work ASAP
         let zelf = Arc::clone(&self);
         spawn_task("getwork-new-job", async move {
-            if let Err(e) = zelf.send_new_job(addr, key).await {
+            if let Err(e) = zelf.send_new_job(addr).await {
                 error!("Error while sending new job to miner: {}", e);
             }
         });
@@ -315,23 +495,37 @@ impl<S: Storage> GetWorkServer<S> {
         }
     }
 
-    // this function is called when a miner send a new block
+    // this function is called when a miner send a new share
     // we retrieve the block header saved in cache using the mining job "header_work_hash"
     // its used to check that the job come from our server
     // when it's found, we merge the miner job inside the block header
-    async fn accept_miner_job(&self, job: MinerWork<'_>) -> Result<(Response, Hash), InternalRpcError> {
+    // If its PoW is valid for the block difficulty, the block is added to the chain
+    // Otherwise, it must be valid for the share difficulty of the miner
+    // Returns the block hash if the share was a block
+    async fn accept_miner_job(&self, addr: &Addr<GetWorkWebSocketHandler<S>>, job: MinerWork<'_>) -> Result<(Response, Option<Hash>), InternalRpcError> {
         trace!("accept miner job");
         if job.get_miner().is_none() {
             return Err(InternalRpcError::InvalidJSONRequest);
         }
 
+        let share_difficulty = {
+            let miners = self.miners.lock().await;
+            let miner = miners.get(addr).ok_or(InternalRpcError::ClientNotFound)?;
+            if job.get_miner() != Some(&miner.key) {
+                return Err(InternalRpcError::InvalidParams("Miner work is not for the connected miner"))
+            }
+            miner.difficulty
+        };
+
         let mut miner_header;
+        let block_difficulty;
         {
             let mining_jobs = self.mining_jobs.lock().await;
-            if let Some((header, _)) = mining_jobs.peek(job.get_header_work_hash()) {
+            if let Some((header, difficulty)) = mining_jobs.peek(job.get_header_work_hash()) {
                 // job is found in cache, clone it and put miner data inside
                 miner_header = header.clone();
                 miner_header.apply_miner_work(job);
+                block_difficulty = *difficulty;
             } else {
                 // really old job, or miner send invalid job
                 debug!("Job {} was not found in cache", job.get_header_work_hash());
@@ -339,13 +533,40 @@ impl<S: Storage> GetWorkServer<S> {
             };
         }
 
+        // PoW is verified when adding the block if its not skipped
+        if !self.blockchain.skip_pow_verification() {
+            // PoW hash is CPU heavy, don't block the runtime
+            let (header, pow_hash) = spawn_blocking(move || {
+                let pow_hash = miner_header.get_pow_hash();
+                (miner_header, pow_hash)
+            }).await.context("Error while waiting on PoW hash")?;
+            let pow_hash = pow_hash.context("Error while computing PoW hash")?;
+            miner_header = header;
+
+            {
+                let mut miners = self.miners.lock().await;
+                let miner = miners.get_mut(addr).ok_or(InternalRpcError::ClientNotFound)?;
+                if miner.submitted_shares.put(pow_hash.clone(), ()).is_some() {
+                    return Err(InternalRpcError::InvalidParams("Duplicate share"))
+                }
+            }
+
+            if !check_difficulty(&pow_hash, &block_difficulty).context("Error while checking block difficulty")? {
+                if !check_difficulty(&pow_hash, &share_difficulty).context("Error while checking share difficulty")? {
+                    return Err(InternalRpcError::InvalidParams("Low difficulty share"))
+                }
+
+                return Ok((Response::ShareAccepted, None))
+            }
+        }
+
         let block = self.blockchain.build_block_from_header(Immutable::Owned(miner_header)).await.context("Error while building block from header")?;
         let block_hash = block.hash();
         Ok(match self.blockchain.add_new_block(block, true, true).await {
-            Ok(_) => (Response::BlockAccepted, block_hash),
+            Ok(_) => (Response::BlockAccepted, Some(block_hash)),
             Err(e) => {
                 debug!("Error while accepting miner block: {}", e);
-                (Response::BlockRejected(e.to_string()), block_hash)
+                (Response::BlockRejected(e.to_string()), Some(block_hash))
             }
         })
     }
@@ -356,8 +577,8 @@ impl<S: Storage> GetWorkServer<S> {
     pub async fn handle_block_for(self: Arc<Self>, addr: Addr<GetWorkWebSocketHandler<S>>, submitted_work: SubmitMinerWorkParams) {
         trace!("handle block for");
         let (response, hash) = match MinerWork::from_hex(submitted_work.miner_work) {
-            Ok(job) => match self.accept_miner_job(job).await {
-                Ok((response, hash)) => (response, Some(hash)),
+            Ok(job) => match self.accept_miner_job(&addr, job).await {
+                Ok((response, hash)) => (response, hash),
                 Err(e) => {
                     debug!("Error while accepting miner job: {}", e);
                     (Response::BlockRejected(e.to_string()), None)
@@ -373,25 +594,36 @@ impl<S: Storage> GetWorkServer<S> {
         {
             let mut miners = self.miners.lock().await;
             if let Some(miner) = miners.get_mut(&addr) {
-                match &response {
-                    Response::BlockAccepted => {
-                        let hash = hash.unwrap();
+                match (&response, hash) {
+                    (Response::BlockAccepted, Some(hash)) => {
                         debug!("Miner {} found block {}!", miner, hash);
+                        miner.add_accepted_share();
                         miner.blocks_accepted.insert(hash);
                     },
-                    Response::BlockRejected(_) => {
+                    (Response::ShareAccepted, _) => {
+                        trace!("Miner {} sent a valid share", miner);
+                        miner.add_accepted_share();
+                    },
+                    (Response::BlockRejected(_), Some(_)) => {
+                        // its PoW was valid but the block was rejected by the chain
                         debug!("Miner {} sent an invalid block", miner);
+                        miner.add_accepted_share();
                         miner.blocks_rejected += 1;
                         miner.last_invalid_block = get_current_time_in_millis();
                     },
+                    (Response::BlockRejected(_), None) => {
+                        debug!("Miner {} sent an invalid share", miner);
+                        miner.add_rejected_share();
+                    },
                     _ => {}
                 }
             }
         }
 
         spawn_task("getwork-reply", async move {
+            // miner is waiting on a new job after each share sent
             let resend_job = match response {
-                Response::BlockRejected(_) => true,
+                Response::BlockRejected(_) | Response::ShareAccepted => true,
                 _ => false
             };
             debug!("Sending response to the miner");
@@ -401,20 +633,9 @@ impl<S: Storage> GetWorkServer<S> {
 
             if resend_job {
                 debug!("Resending job to the miner");
-                let key = {
-                    let miners = self.miners.lock().await;
-                    if let Some(miner) = miners.get(&addr) {
-                        Some(miner.get_public_key().clone())
-                    } else {
-                        error!("Miner not found in the list of miners! (should not happen)");
-                        None
-                    }
+                if let Err(e) = self.send_new_job(addr).await {
+                    error!("Error while sending new job to miner: {}", e);
                 };
-                if let Some(key) = key {
-                    if let Err(e) = self.send_new_job(addr, key).await {
-                        error!("Error while sending new job to miner: {}", e);
-                    };
-                }
             }
             debug!("Response sent!");
         });
@@ -481,9 +702,12 @@ impl<S: Storage> GetWorkServer<S> {
         miners.retain(|addr, _| addr.connected());
 
         let topoheight = self.blockchain.get_topo_height();
-        for (addr, miner) in miners.iter() {
+        for (addr, miner) in miners.iter_mut() {
             debug!("Notifying {} for new job", miner);
             let addr = addr.clone();
+            miner.update_difficulty();
+            miner.add_job_sent();
+            let share_difficulty = miner.difficulty.min(difficulty);
 
             job.set_miner(Cow::Borrowed(miner.get_public_key()));
             OsRng.fill_bytes(job.get_extra_nonce());
@@ -492,7 +716,7 @@ impl<S: Storage> GetWorkServer<S> {
             // New task for each miner in case a miner is slow
             // we don't want to wait for him
             spawn_task("getwork-notify-new-job", async move {
-                match addr.send(Response::NewJob(GetMinerWorkResult { template, height, topoheight, difficulty })).await {
+                match addr.send(Response::NewJob(GetMinerWorkResult { template, height, topoheight, difficulty: share_difficulty })).await {
                     Ok(request) => {
                         if let Err(e) = request {
                             warn!("Error while sending new job to addr {:?}: {}", addr, e);
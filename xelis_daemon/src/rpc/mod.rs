@@ -1,5 +1,8 @@
 pub mod rpc;
+pub mod admin;
 pub mod getwork_server;
+pub mod stratum_server;
+pub mod webhook;
 
 use crate::{
     core::{
@@ -7,7 +10,16 @@ use crate::{
         error::BlockchainError,
         blockchain::Blockchain
     },
-    rpc::getwork_server::GetWorkServer,
+    config::GETWORK_MINERS_STATS_INTERVAL_MILLIS,
+    rpc::{
+        getwork_server::GetWorkServer,
+        stratum_server::{
+            SharedStratumServer,
+            StratumConfig,
+            StratumServer
+        },
+        webhook::{WebhookNotifier, WEBHOOK_EVENTS}
+    },
 };
 use actix_web::{
     get,
@@ -22,14 +34,21 @@ use actix_web::{
         Data,
         Payload
     },
-    dev::ServerHandle,
-    error::Error
+    dev::{ServerHandle, ServiceRequest},
+    error::{Error, ErrorBadGateway, ErrorBadRequest, ErrorNotFound, ErrorUnauthorized}
 };
 use actix_web_actors::ws::WsResponseBuilder;
+use actix_web_httpauth::{
+    extractors::basic::BasicAuth,
+    middleware::HttpAuthentication
+};
 use serde_json::{Value, json};
-use tokio::sync::Mutex;
+use tokio::{sync::Mutex, time::interval};
 use xelis_common::{
-    api::daemon::NotifyEvent,
+    api::{
+        daemon::{JournalEvent, NotifyEvent},
+        EventResult
+    },
     config,
     crypto::Address,
     rpc_server::{
@@ -41,6 +60,7 @@ use xelis_common::{
             WebSocketServerShared
         },
         InternalRpcError,
+        RpcResponseError,
         RPCHandler,
         RPCServerHandler,
         WebSocketServerHandler
@@ -48,8 +68,10 @@ use xelis_common::{
     utils::spawn_task,
 };
 use std::{
+    borrow::Cow,
     collections::HashSet,
-    sync::Arc,
+    sync::{Arc, Weak},
+    time::Duration
 };
 use log::{
     trace,
@@ -65,10 +87,42 @@ use self::getwork_server::{
 
 pub type SharedDaemonRpcServer<S> = Arc<DaemonRpcServer<S>>;
 
+pub struct AdminAuthConfig {
+    pub username: String,
+    pub password: String
+}
+
+// Admin methods are registered in their own handler
+// so they can't be reached from the public routes
+struct AdminRpc<S: Storage> {
+    handler: RPCHandler<Arc<Blockchain<S>>>,
+    auth_config: AdminAuthConfig
+}
+
+// Events saved in the event journal
+// They describe the chain state so they are always generated when the journal is enabled
+const JOURNALED_EVENTS: [NotifyEvent; 7] = [
+    NotifyEvent::NewBlock,
+    NotifyEvent::BlockOrdered,
+    NotifyEvent::BlockOrphaned,
+    NotifyEvent::StableHeightChanged,
+    NotifyEvent::TransactionOrphaned,
+    NotifyEvent::TransactionExecuted,
+    NotifyEvent::NewAsset
+];
+
 pub struct DaemonRpcServer<S: Storage> {
     handle: Mutex<Option<ServerHandle>>,
     websocket: WebSocketServerShared<EventWebSocketHandler<Arc<Blockchain<S>>, NotifyEvent>>,
-    getwork: Option<SharedGetWorkServer<S>>
+    getwork: Option<SharedGetWorkServer<S>>,
+    stratum: Option<SharedStratumServer<S>>,
+    admin: Option<AdminRpc<S>>,
+    // webhooks notified of the chain events
+    webhooks: Option<WebhookNotifier>,
+    // sequence number of the last event notified
+    event_sequence: Mutex<u64>,
+    // maximum count of events kept in the journal (0 = disabled)
+    event_journal_size: u64
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -80,11 +134,17 @@ pub enum ApiError {
     #[error("P2p engine is not running")]
     NoP2p,
     #[error("WebSocket server is not started")]
-    NoWebSocketServer
+    NoWebSocketServer,
+    #[error("GetWork server is not enabled")]
+    NoGetWorkServer,
+    #[error("Event journal is not enabled")]
+    NoEventJournal,
+    #[error("Invalid log level '{}'", _0)]
+    InvalidLogLevel(String)
 }
 
 impl<S: Storage> DaemonRpcServer<S> {
-    pub async fn new(bind_address: String, blockchain: Arc<Blockchain<S>>, disable_getwork_server: bool) -> Result<SharedDaemonRpcServer<S>, BlockchainError> {
+    pub async fn new(bind_address: String, blockchain: Arc<Blockchain<S>>, disable_getwork_server: bool, enable_p2p_rpc_management: bool, admin_auth: Option<AdminAuthConfig>, request_timeout: Option<Duration>, stratum_config: Option<StratumConfig>, event_journal_size: u64, webhook_urls: Vec<String>) -> Result<SharedDaemonRpcServer<S>, BlockchainError> {
         let getwork: Option<SharedGetWorkServer<S>> = if !disable_getwork_server {
             info!("Creating GetWork server...");
             Some(Arc::new(GetWorkServer::new(blockchain.clone())))
@@ -92,9 +152,44 @@ impl<S: Storage> DaemonRpcServer<S> {
             None
         };
 
+        let stratum = match stratum_config {
+            Some(config) => {
+                info!("Creating Stratum server...");
+                match StratumServer::new(blockchain.clone(), config).await {
+                    Ok(server) => Some(server),
+                    Err(e) => {
+                        error!("Error while starting Stratum server: {}", e);
+                        None
+                    }
+                }
+            },
+            None => None
+        };
+
+        let admin = admin_auth.map(|auth_config| {
+            info!("Admin RPC methods are enabled");
+            let mut handler = RPCHandler::new(blockchain.clone());
+            admin::register_methods(&mut handler);
+            AdminRpc {
+                handler,
+                auth_config
+            }
+        });
+
+        let webhooks = if !webhook_urls.is_empty() {
+            info!("Creating webhook notifier for {} URLs...", webhook_urls.len());
+            Some(WebhookNotifier::new(webhook_urls)?)
+        } else {
+            None
+        };
+
+        // continue the sequence of the events already saved in the journal
+        let event_sequence = blockchain.get_storage().read().await.get_last_event_sequence().await?;
+
         // create the RPC Handler which will register and contains all available methods
         let mut rpc_handler = RPCHandler::new(blockchain);
-        rpc::register_methods(&mut rpc_handler, !disable_getwork_server);
+        rpc_handler.set_request_timeout(request_timeout);
+        rpc::register_methods(&mut rpc_handler, !disable_getwork_server, enable_p2p_rpc_management);
 
         // create the default websocket server (support event & rpc methods)
         let ws = WebSocketServer::new(EventWebSocketHandler::new(rpc_handler));
@@ -103,6 +198,11 @@ impl<S: Storage> DaemonRpcServer<S> {
             handle: Mutex::new(None),
             websocket: ws,
             getwork,
+            stratum,
+            admin,
+            webhooks,
+            event_sequence: Mutex::new(event_sequence),
+            event_journal_size
         });
 
         {
@@ -115,6 +215,12 @@ impl<S: Storage> DaemonRpcServer<S> {
                     // WebSocket support
                     .route("/json_rpc", web::get().to(websocket::<EventWebSocketHandler<Arc<Blockchain<S>>, NotifyEvent>, DaemonRpcServer<S>>))
                     .route("/getwork/{address}/{worker}", web::get().to(getwork_endpoint::<S>))
+                    // Admin methods, behind authentication
+                    .service(
+                        web::resource("/admin/json_rpc")
+                            .wrap(HttpAuthentication::basic(admin_validator::<S>))
+                            .route(web::post().to(admin_json_rpc::<S>))
+                    )
                     .service(index)
             })
             .disable_signals()
@@ -129,15 +235,65 @@ impl<S: Storage> DaemonRpcServer<S> {
             }
             spawn_task("rpc-server", http_server);
         }
+
+        if server.getwork.is_some() {
+            spawn_task("getwork-miners-stats", Self::getwork_miners_stats_loop(Arc::downgrade(&server)));
+        }
+
         Ok(server)
     }
 
+    // Send periodically the statistics of the GetWork miners to the websocket clients tracking them
+    // The task stops once the RPC server is stopped
+    async fn getwork_miners_stats_loop(server: Weak<Self>) {
+        let mut interval = interval(Duration::from_millis(GETWORK_MINERS_STATS_INTERVAL_MILLIS));
+        loop {
+            interval.tick().await;
+            let Some(server) = server.upgrade() else {
+                break;
+            };
+
+            if server.handle.lock().await.is_none() {
+                debug!("RPC server is stopped, exiting GetWork miners stats task");
+                break;
+            }
+
+            if let Some(getwork) = server.getwork.as_ref() {
+                if server.is_event_tracked(&NotifyEvent::GetWorkMinersStats).await {
+                    let stats = getwork.get_miners_stats().await;
+                    server.notify_clients_with(&NotifyEvent::GetWorkMinersStats, stats).await;
+                }
+            }
+        }
+    }
+
+    // Journaled events are always tracked so the journal doesn't miss any of them
     pub async fn get_tracked_events(&self) -> HashSet<NotifyEvent> {
-        self.get_websocket().get_handler().get_tracked_events().await
+        let mut events = self.get_websocket().get_handler().get_tracked_events().await;
+        if self.is_event_journal_enabled() {
+            events.extend(JOURNALED_EVENTS.iter().cloned());
+        }
+
+        if self.webhooks.is_some() {
+            events.extend(WEBHOOK_EVENTS.iter().cloned());
+        }
+        events
     }
 
     pub async fn is_event_tracked(&self, event: &NotifyEvent) -> bool {
-        self.get_websocket().get_handler().is_event_tracked(event).await
+        self.is_event_journaled(event) || self.is_event_pushed(event) || self.get_websocket().get_handler().is_event_tracked(event).await
+    }
+
+    pub fn is_event_journal_enabled(&self) -> bool {
+        self.event_journal_size > 0
+    }
+
+    fn is_event_journaled(&self, event: &NotifyEvent) -> bool {
+        self.is_event_journal_enabled() && JOURNALED_EVENTS.contains(event)
+    }
+
+    fn is_event_pushed(&self, event: &NotifyEvent) -> bool {
+        self.webhooks.as_ref().map_or(false, |webhooks| webhooks.is_event_pushed(event))
     }
 
     pub async fn notify_clients_with<V: serde::Serialize>(&self, event: &NotifyEvent, value: V) {
@@ -146,12 +302,44 @@ impl<S: Storage> DaemonRpcServer<S> {
         }
     }
 
-    pub async fn notify_clients(&self, event: &NotifyEvent, value: Value) -> Result<(), anyhow::Error> {
+    // Each event notified has a sequence number set in its value
+    pub async fn notify_clients(&self, event: &NotifyEvent, mut value: Value) -> Result<(), anyhow::Error> {
+        {
+            // keep the lock until the event is journaled so the journal stays ordered
+            let mut sequence = self.event_sequence.lock().await;
+            *sequence += 1;
+
+            if self.is_event_journaled(event) {
+                let storage = self.get_rpc_handler().get_data().get_storage().read().await;
+                storage.append_event(&JournalEvent {
+                    sequence: *sequence,
+                    event: event.clone(),
+                    value: value.clone()
+                }).await?;
+
+                if *sequence > self.event_journal_size {
+                    storage.delete_events_below_sequence(*sequence - self.event_journal_size + 1).await?;
+                }
+            }
+
+            if let Value::Object(map) = &mut value {
+                map.insert("sequence".to_owned(), json!(*sequence));
+            }
+        }
+
+        if let Some(webhooks) = self.webhooks.as_ref().filter(|webhooks| webhooks.is_event_pushed(event)) {
+            webhooks.notify(&json!(EventResult { event: Cow::Borrowed(event), value: value.clone() }));
+        }
+
         self.get_websocket().get_handler().notify(event, value).await;
         Ok(())
     }
 
     pub async fn stop(&self) {
+        if let Some(stratum) = &self.stratum {
+            stratum.stop();
+        }
+
         info!("Stopping RPC Server...");
         let mut handle = self.handle.lock().await;
         if let Some(handle) = handle.take() {
@@ -165,6 +353,26 @@ impl<S: Storage> DaemonRpcServer<S> {
     pub fn getwork_server(&self) -> &Option<SharedGetWorkServer<S>> {
         &self.getwork
     }
+
+    pub fn stratum_server(&self) -> &Option<SharedStratumServer<S>> {
+        &self.stratum
+    }
+
+    async fn authenticate_admin(&self, credentials: BasicAuth) -> Result<(), Error> {
+        let config = match &self.admin {
+            Some(admin) => &admin.auth_config,
+            None => return Err(ErrorNotFound("Admin RPC is not enabled"))
+        };
+
+        let user = credentials.user_id();
+        let password = credentials.password().ok_or(ErrorBadRequest("Missing password"))?;
+        if *config.username != *user || *config.password != *password {
+            warn!("Invalid credentials received on admin RPC");
+            return Err(ErrorUnauthorized("Username/password are invalid"))
+        }
+
+        Ok(())
+    }
 }
 
 impl<S: Storage> WebSocketServerHandler<EventWebSocketHandler<Arc<Blockchain<S>>, NotifyEvent>> for DaemonRpcServer<S> {
@@ -185,6 +393,29 @@ async fn index() -> impl Responder {
     HttpResponse::Ok().body(format!("Hello, world!\nRunning on: {}", config::VERSION))
 }
 
+async fn admin_validator<S: Storage>(request: ServiceRequest, credentials: BasicAuth) -> Result<ServiceRequest, (Error, ServiceRequest)> {
+    let data: Option<&Data<DaemonRpcServer<S>>> = request.app_data();
+    match data {
+        Some(server) => match server.authenticate_admin(credentials).await {
+            Ok(_) => Ok(request),
+            Err(e) => Err((e, request))
+        },
+        None => Err((ErrorBadGateway("RPC Server was not found"), request))
+    }
+}
+
+// JSON RPC endpoint of the admin methods
+async fn admin_json_rpc<S: Storage>(server: Data<DaemonRpcServer<S>>, body: web::Bytes) -> Result<HttpResponse, RpcResponseError> {
+    match &server.admin {
+        Some(admin) => {
+            let result = admin.handler.handle_request(&body).await?;
+            Ok(HttpResponse::Ok().json(result))
+        },
+        // authentication is refusing the requests already
+        None => Ok(HttpResponse::NotFound().reason("Admin RPC is not enabled").finish())
+    }
+}
+
 async fn getwork_endpoint<S: Storage>(server: Data<DaemonRpcServer<S>>, request: HttpRequest, stream: Payload, path: Path<(String, String)>) -> Result<HttpResponse, Error> {
     match &server.getwork {
         Some(getwork) => {
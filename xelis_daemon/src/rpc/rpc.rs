@@ -1,8 +1,12 @@
 use crate::{
     config::{
-        BLOCK_TIME_MILLIS,
         DEV_FEES,
-        DEV_PUBLIC_KEY
+        DEV_PUBLIC_KEY,
+        MAX_DAILY_STATS_DAYS,
+        MILLIS_PER_SECOND,
+        NONCE_RESERVATION_DEFAULT_DURATION,
+        STATS_DAY_DURATION_MILLIS,
+        get_compiled_features
     },
     core::{
         blockchain::{
@@ -11,13 +15,13 @@ use crate::{
             Blockchain
         },
         error::BlockchainError,
+        hard_fork::get_hard_forks,
         mempool::Mempool,
-        storage::Storage
+        storage::{get_day_for_timestamp, Storage}
     },
-    p2p::peer::Peer,
-    BLOCK_TIME
+    p2p::peer::Peer
 };
-use super::{InternalRpcError, ApiError};
+use super::{getwork_server::SharedGetWorkServer, InternalRpcError, ApiError};
 use xelis_common::{
     api::{
         daemon::{
@@ -31,8 +35,10 @@ use xelis_common::{
             GetAccountRegistrationParams,
             GetAccountsParams,
             GetAssetParams,
+            GetAssetResult,
             GetAssetsParams,
             GetBalanceAtTopoHeightParams,
+            GetBalanceHistoryParams,
             GetBalanceParams,
             GetBalanceResult,
             GetBlockAtTopoHeightParams,
@@ -40,26 +46,67 @@ use xelis_common::{
             GetBlockTemplateParams,
             GetBlockTemplateResult,
             GetBlocksAtHeightParams,
+            DailyStatsEntry,
+            DagBlockEntry,
+            GetDagInfoParams,
+            GetDagInfoResult,
+            GetDailyStatsParams,
             GetDifficultyResult,
+            GetDifficultyHistoryParams,
+            GetDifficultyHistoryResult,
+            GetEventsSinceParams,
+            GetEventsSinceResult,
+            DifficultySample,
             GetHeightRangeParams,
             GetInfoResult,
+            GetStatusResult,
+            MempoolStatus,
+            PruningStatus,
+            GetCapabilitiesResult,
             GetMempoolCacheParams,
+            GetMempoolSummaryResult,
+            GetMempoolTransactionParams,
+            GetMempoolTransactionResult,
+            FeeRateBucket,
+            MempoolTransactionSummary,
+            GetLockedTransferParams,
+            GetLockedTransferResult,
+            GetContractParams,
+            GetContractResult,
+            GetContractDataParams,
+            GetContractDataResult,
+            GetMultiSigParams,
+            GetMultiSigResult,
             GetNonceAtTopoHeightParams,
             GetNonceParams,
             GetNonceResult,
             GetPeersResponse,
+            GetNetworkViewResult,
             GetTopBlockParams,
             GetTopoHeightRangeParams,
             GetTransactionParams,
             GetTransactionsParams,
+            GetTransactionConflictsParams,
+            BalanceHistoryEntry,
             HasBalanceParams,
             HasBalanceResult,
+            HasMultiSigParams,
             HasNonceParams,
             HasNonceResult,
             IsAccountRegisteredParams,
+            NonceReservationResult,
+            ReleaseNonceReservationParams,
+            RenewNonceReservationParams,
+            ReserveNonceParams,
             IsTxExecutedInBlockParams,
             P2pStatusResult,
             PeerEntry,
+            AnchorPeerEntry,
+            AnchorPeerParams,
+            GetConnectionManagerResult,
+            SetConnectionTargetsParams,
+            PeerChainStatus,
+            PeerChainViewEntry,
             RPCBlockResponse,
             SizeOnDiskResult,
             SubmitBlockParams,
@@ -84,20 +131,25 @@ use xelis_common::{
         MinerWork
     },
     config::{
+        ASSET_REGISTRATION_FEE,
+        FEE_PER_KB,
         MAXIMUM_SUPPLY,
         MAX_TRANSACTION_SIZE,
         VERSION,
         XELIS_ASSET
     },
     context::Context,
-    crypto::Hash,
+    crypto::{Hash, Hashable},
     difficulty::{
+        compact_from_target,
+        compute_difficulty_target,
         CumulativeDifficulty,
         Difficulty
     },
     immutable::Immutable,
     rpc_server::{
         parse_params,
+        CancellationToken,
         RPCHandler
     },
     serializer::Serializer,
@@ -315,14 +367,27 @@ pub async fn get_peer_entry(peer: &Peer) -> PeerEntry {
         peers: Cow::Owned(peers),
         pruned_topoheight: peer.get_pruned_topoheight(),
         cumulative_difficulty: Cow::Owned(cumulative_difficulty),
-        connected_on: peer.get_connection().connected_on()
+        connected_on: peer.get_connection().connected_on(),
+        bytes_in: peer.get_connection().bytes_in(),
+        bytes_out: peer.get_connection().bytes_out(),
+        packets_in: peer.get_connection().packets_in(),
+        packets_out: peer.get_connection().packets_out(),
+        throttled: peer.get_rate_limiter().get_throttled_count(),
+        protocol_version: peer.get_protocol_version(),
+        capabilities: peer.get_capabilities(),
+        latency: peer.get_latency(),
+        jitter: peer.get_jitter(),
+        tls: peer.get_connection().is_tls(),
+        tls_port: peer.get_tls_port(),
+        topoheight_diff: None
     }
 }
 
 // This function is used to register all the RPC methods
-pub fn register_methods<S: Storage>(handler: &mut RPCHandler<Arc<Blockchain<S>>>, allow_mining_methods: bool) {
+pub fn register_methods<S: Storage>(handler: &mut RPCHandler<Arc<Blockchain<S>>>, allow_mining_methods: bool, allow_p2p_management_methods: bool) {
     info!("Registering RPC methods...");
     handler.register_method("get_version", async_handler!(version::<S>));
+    handler.register_method("get_capabilities", async_handler!(get_capabilities::<S>));
     handler.register_method("get_height", async_handler!(get_height::<S>));
     handler.register_method("get_topoheight", async_handler!(get_topoheight::<S>));
     handler.register_method("get_stableheight", async_handler!(get_stableheight::<S>));
@@ -333,10 +398,21 @@ pub fn register_methods<S: Storage>(handler: &mut RPCHandler<Arc<Blockchain<S>>>
     handler.register_method("get_balance", async_handler!(get_balance::<S>));
     handler.register_method("has_balance", async_handler!(has_balance::<S>));
     handler.register_method("get_balance_at_topoheight", async_handler!(get_balance_at_topoheight::<S>));
+    handler.register_method("get_balance_at_maximum_topoheight", async_handler!(get_balance_at_maximum_topoheight::<S>));
+    handler.register_method("get_balance_history", async_handler!(get_balance_history::<S>));
     handler.register_method("get_info", async_handler!(get_info::<S>));
+    handler.register_method("get_status", async_handler!(get_status::<S>));
     handler.register_method("get_nonce", async_handler!(get_nonce::<S>));
     handler.register_method("has_nonce", async_handler!(has_nonce::<S>));
     handler.register_method("get_nonce_at_topoheight", async_handler!(get_nonce_at_topoheight::<S>));
+    handler.register_method("reserve_nonce", async_handler!(reserve_nonce::<S>));
+    handler.register_method("renew_nonce_reservation", async_handler!(renew_nonce_reservation::<S>));
+    handler.register_method("release_nonce_reservation", async_handler!(release_nonce_reservation::<S>));
+    handler.register_method("get_multisig", async_handler!(get_multisig::<S>));
+    handler.register_method("has_multisig", async_handler!(has_multisig::<S>));
+    handler.register_method("get_locked_transfer", async_handler!(get_locked_transfer::<S>));
+    handler.register_method("get_contract", async_handler!(get_contract::<S>));
+    handler.register_method("get_contract_data", async_handler!(get_contract_data::<S>));
     handler.register_method("get_asset", async_handler!(get_asset::<S>));
     handler.register_method("get_assets", async_handler!(get_assets::<S>));
     handler.register_method("count_assets", async_handler!(count_assets::<S>));
@@ -347,9 +423,16 @@ pub fn register_methods<S: Storage>(handler: &mut RPCHandler<Arc<Blockchain<S>>>
     handler.register_method("get_transaction_executor", async_handler!(get_transaction_executor::<S>));
     handler.register_method("p2p_status", async_handler!(p2p_status::<S>));
     handler.register_method("get_peers", async_handler!(get_peers::<S>));
+    handler.register_method("get_network_view", async_handler!(get_network_view::<S>));
     handler.register_method("get_mempool", async_handler!(get_mempool::<S>));
+    handler.register_method("get_mempool_summary", async_handler!(get_mempool_summary::<S>));
+    handler.register_method("get_mempool_tx", async_handler!(get_mempool_tx::<S>));
+    handler.register_method("get_transaction_conflicts", async_handler!(get_transaction_conflicts::<S>));
+    handler.register_method("get_block_processing_stats", async_handler!(get_block_processing_stats::<S>));
+    handler.register_method("get_cache_stats", async_handler!(get_cache_stats::<S>));
     handler.register_method("get_tips", async_handler!(get_tips::<S>));
     handler.register_method("get_dag_order", async_handler!(get_dag_order::<S>));
+    handler.register_method("get_dag_info", async_handler!(get_dag_info::<S>));
     handler.register_method("get_blocks_range_by_topoheight", async_handler!(get_blocks_range_by_topoheight::<S>));
     handler.register_method("get_blocks_range_by_height", async_handler!(get_blocks_range_by_height::<S>));
     handler.register_method("get_transactions", async_handler!(get_transactions::<S>));
@@ -360,17 +443,30 @@ pub fn register_methods<S: Storage>(handler: &mut RPCHandler<Arc<Blockchain<S>>>
     handler.register_method("get_account_registration_topoheight", async_handler!(get_account_registration_topoheight::<S>));
     handler.register_method("is_tx_executed_in_block", async_handler!(is_tx_executed_in_block::<S>));
     handler.register_method("get_dev_fee_thresholds", async_handler!(get_dev_fee_thresholds::<S>));
+    handler.register_method("get_hard_forks", async_handler!(get_hard_forks::<S>));
     handler.register_method("get_size_on_disk", async_handler!(get_size_on_disk::<S>));
     handler.register_method("get_mempool_cache", async_handler!(get_mempool_cache::<S>));
     handler.register_method("get_difficulty", async_handler!(get_difficulty::<S>));
+    handler.register_method("get_difficulty_history", async_handler!(get_difficulty_history::<S>));
+    handler.register_method("get_daily_stats", async_handler!(get_daily_stats::<S>));
+    handler.register_method("get_events_since", async_handler!(get_events_since::<S>));
     handler.register_method("validate_address", async_handler!(validate_address::<S>));
     handler.register_method("split_address", async_handler!(split_address::<S>));
     handler.register_method("extract_key_from_address", async_handler!(extract_key_from_address::<S>));
 
+    if allow_p2p_management_methods {
+        handler.register_method("get_connection_manager", async_handler!(get_connection_manager::<S>));
+        handler.register_method("set_connection_targets", async_handler!(set_connection_targets::<S>));
+        handler.register_method("add_anchor_peer", async_handler!(add_anchor_peer::<S>));
+        handler.register_method("remove_anchor_peer", async_handler!(remove_anchor_peer::<S>));
+    }
+
     if allow_mining_methods {
         handler.register_method("get_block_template", async_handler!(get_block_template::<S>));
         handler.register_method("create_miner_work", async_handler!(create_miner_work::<S>));
         handler.register_method("submit_block", async_handler!(submit_block::<S>));
+        handler.register_method("get_miners", async_handler!(get_miners::<S>));
+        handler.register_method("get_getwork_miners", async_handler!(get_getwork_miners::<S>));
     }
 }
 
@@ -381,6 +477,19 @@ async fn version<S: Storage>(_: &Context, body: Value) -> Result<Value, Internal
     Ok(json!(VERSION))
 }
 
+async fn get_capabilities<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    if body != Value::Null {
+        return Err(InternalRpcError::UnexpectedParams)
+    }
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let capabilities = blockchain.get_capabilities().await.context("Error while retrieving capabilities")?;
+    Ok(json!(GetCapabilitiesResult {
+        capabilities,
+        names: capabilities.get_names().into_iter().map(Cow::Borrowed).collect(),
+        features: get_compiled_features().into_iter().map(Cow::Borrowed).collect()
+    }))
+}
+
 async fn get_height<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
     if body != Value::Null {
         return Err(InternalRpcError::UnexpectedParams)
@@ -483,6 +592,36 @@ async fn submit_block<S: Storage>(context: &Context, body: Value) -> Result<Valu
     Ok(json!(true))
 }
 
+async fn get_getwork_server<S: Storage>(blockchain: &Blockchain<S>) -> Result<SharedGetWorkServer<S>, InternalRpcError> {
+    let getwork = match blockchain.get_rpc().read().await.as_ref() {
+        Some(rpc) => rpc.getwork_server().clone(),
+        None => None
+    };
+    getwork.ok_or_else(|| InternalRpcError::InvalidParamsAny(ApiError::NoGetWorkServer.into()))
+}
+
+// Miners connected to the GetWork server with their shares accounting
+async fn get_miners<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    if body != Value::Null {
+        return Err(InternalRpcError::UnexpectedParams)
+    }
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let getwork = get_getwork_server(blockchain).await?;
+
+    Ok(json!(getwork.get_miners_stats().await.miners))
+}
+
+// Same as get_miners with the totals of the GetWork server
+async fn get_getwork_miners<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    if body != Value::Null {
+        return Err(InternalRpcError::UnexpectedParams)
+    }
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let getwork = get_getwork_server(blockchain).await?;
+
+    Ok(json!(getwork.get_miners_stats().await))
+}
+
 async fn get_balance<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
     let params: GetBalanceParams = parse_params(body)?;
     let blockchain: &Arc<Blockchain<S>> = context.get()?;
@@ -490,8 +629,7 @@ async fn get_balance<S: Storage>(context: &Context, body: Value) -> Result<Value
         return Err(InternalRpcError::InvalidParamsAny(BlockchainError::InvalidNetwork.into()))
     }
 
-    let storage = blockchain.get_storage().read().await;
-    let (topoheight, version) = storage.get_last_balance(params.address.get_public_key(), &params.asset).await.context("Error while retrieving last balance")?;
+    let (topoheight, version) = blockchain.get_committed_balance(params.address.get_public_key(), &params.asset).await.context("Error while retrieving last balance")?;
     Ok(json!(GetBalanceResult {
         version,
         topoheight
@@ -533,11 +671,13 @@ async fn get_info<S: Storage>(context: &Context, body: Value) -> Result<Value, I
         (top_block_hash, supply, pruned_topoheight, average_block_time)
     };
     let difficulty = blockchain.get_difficulty().await;
-    let block_time_target = BLOCK_TIME_MILLIS;
-    let block_reward = get_block_reward(circulating_supply);
+    let block_time_target = blockchain.get_chain_params().block_time_millis;
+    let block_reward = get_block_reward(circulating_supply, blockchain.get_chain_params());
     let mempool_size = blockchain.get_mempool_size().await;
     let version = VERSION.into();
     let network = *blockchain.get_network();
+    let capabilities = blockchain.get_capabilities().await.context("Error while retrieving capabilities")?;
+    let block_version = blockchain.get_version_at_height(height + 1);
 
     Ok(json!(GetInfoResult {
         height,
@@ -553,7 +693,81 @@ async fn get_info<S: Storage>(context: &Context, body: Value) -> Result<Value, I
         block_reward,
         mempool_size,
         version,
-        network
+        network,
+        capabilities,
+        block_version
+    }))
+}
+
+// Health check of the node in a single call
+// Sync progress is estimated against the best topoheight of our peers
+async fn get_status<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    if body != Value::Null {
+        return Err(InternalRpcError::UnexpectedParams)
+    }
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let height = blockchain.get_height();
+    let topoheight = blockchain.get_topo_height();
+    let stableheight = blockchain.get_stable_height();
+
+    let p2p = { blockchain.get_p2p().read().await.clone() };
+    let (best_topoheight, median_topoheight, peer_count, syncing) = match p2p.as_ref() {
+        Some(p2p) => (
+            Some(p2p.get_best_topoheight().await),
+            Some(p2p.get_median_topoheight_of_peers().await),
+            p2p.get_peer_count().await,
+            p2p.is_syncing_chain()
+        ),
+        None => (None, None, 0, false)
+    };
+
+    let sync_progress = match best_topoheight {
+        Some(best) if best > topoheight => topoheight as f64 * 100f64 / best as f64,
+        _ => 100f64
+    };
+
+    // Without P2P, we are the only source of truth
+    let ready = !syncing && match (p2p.is_some(), median_topoheight) {
+        (true, Some(median)) => peer_count > 0 && topoheight >= median,
+        _ => true
+    };
+
+    let mempool = {
+        let mempool = blockchain.get_mempool().read().await;
+        let (total_size, total_fees) = mempool.get_txs()
+            .values()
+            .fold((0, 0), |(size, fees), tx| (size + tx.get_size(), fees + tx.get_fee()));
+        MempoolStatus {
+            tx_count: mempool.size(),
+            total_size,
+            total_fees
+        }
+    };
+
+    let pruned_topoheight = {
+        let storage = blockchain.get_storage().read().await;
+        storage.get_pruned_topoheight().await.context("Error while retrieving pruned topoheight")?
+    };
+
+    Ok(json!(GetStatusResult {
+        version: Cow::Borrowed(VERSION),
+        network: *blockchain.get_network(),
+        uptime: blockchain.get_uptime() / MILLIS_PER_SECOND,
+        height,
+        topoheight,
+        stableheight,
+        best_topoheight,
+        median_topoheight,
+        sync_progress,
+        syncing,
+        ready,
+        peer_count,
+        standby: blockchain.is_standby(),
+        mempool,
+        pruning: PruningStatus {
+            pruned_topoheight,
+            auto_prune_keep_n_blocks: blockchain.get_auto_prune_keep_n_blocks()
+        }
     }))
 }
 
@@ -574,6 +788,91 @@ async fn get_balance_at_topoheight<S: Storage>(context: &Context, body: Value) -
     Ok(json!(balance))
 }
 
+// Get the version of the balance at or below the requested topoheight
+async fn get_balance_at_maximum_topoheight<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: GetBalanceAtTopoHeightParams = parse_params(body)?;
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let topoheight = blockchain.get_topo_height();
+    if params.topoheight > topoheight {
+        return Err(InternalRpcError::UnexpectedParams).context("Topoheight cannot be greater than current chain topoheight")?
+    }
+
+    if params.address.is_mainnet() != blockchain.get_network().is_mainnet() {
+        return Err(InternalRpcError::InvalidParamsAny(BlockchainError::InvalidNetwork.into()))
+    }
+
+    let storage = blockchain.get_storage().read().await;
+    let pruned_topoheight = storage.get_pruned_topoheight().await.context("Error while retrieving pruned topoheight")?.unwrap_or(0);
+    if params.topoheight < pruned_topoheight {
+        return Err(InternalRpcError::InvalidParams("Topoheight is lower than pruned topoheight"));
+    }
+
+    let (topoheight, version) = storage.get_balance_at_maximum_topoheight(params.address.get_public_key(), &params.asset, params.topoheight).await
+        .context("Error while retrieving balance at maximum topo height")?
+        .ok_or(InternalRpcError::InvalidParams("No balance found at or below requested topoheight"))?;
+
+    Ok(json!(GetBalanceResult {
+        version,
+        topoheight
+    }))
+}
+
+const MAX_BALANCE_HISTORY: usize = 100;
+// walk the versioned balances of an account from the latest to the oldest one
+async fn get_balance_history<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: GetBalanceHistoryParams = parse_params(body)?;
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    if params.address.is_mainnet() != blockchain.get_network().is_mainnet() {
+        return Err(InternalRpcError::InvalidParamsAny(BlockchainError::InvalidNetwork.into()))
+    }
+
+    let maximum = if let Some(maximum) = params.maximum {
+        if maximum > MAX_BALANCE_HISTORY {
+            return Err(InternalRpcError::InvalidJSONRequest).context(format!("Maximum versions requested cannot be greater than {}", MAX_BALANCE_HISTORY))?
+        }
+        maximum
+    } else {
+        MAX_BALANCE_HISTORY
+    };
+
+    let key = params.address.get_public_key();
+    let storage = blockchain.get_storage().read().await;
+    let pruned_topoheight = storage.get_pruned_topoheight().await.context("Error while retrieving pruned topoheight")?.unwrap_or(0);
+    let mut version = if let Some(topo) = params.maximum_topoheight {
+        if topo < pruned_topoheight {
+            return Err(InternalRpcError::InvalidParams("Maximum topoheight is lower than pruned topoheight"));
+        }
+        storage.get_balance_at_maximum_topoheight(key, &params.asset, topo).await.context(format!("Error while retrieving balance at topo height {topo}"))?
+    } else if storage.has_balance_for(key, &params.asset).await.context("Error while checking balance for account")? {
+        Some(storage.get_last_balance(key, &params.asset).await.context("Error while retrieving last balance")?)
+    } else {
+        None
+    };
+
+    let token = CancellationToken::current();
+    let mut history = Vec::new();
+    while let Some((topoheight, versioned_balance)) = version.take() {
+        token.check()?;
+        if history.len() >= maximum || topoheight < pruned_topoheight {
+            break;
+        }
+
+        let previous = versioned_balance.get_previous_topoheight();
+        let block_hash = storage.get_hash_at_topo_height(topoheight).await.context(format!("Error while retrieving hash at topo height {topoheight}"))?;
+        history.push(BalanceHistoryEntry {
+            topoheight,
+            block_hash,
+            version: versioned_balance
+        });
+
+        if let Some(previous) = previous.filter(|previous| *previous >= pruned_topoheight) {
+            version = Some((previous, storage.get_balance_at_exact_topoheight(key, &params.asset, previous).await.context(format!("Error while retrieving previous balance at topo height {previous}"))?));
+        }
+    }
+
+    Ok(json!(history))
+}
+
 async fn has_nonce<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
     let params: HasNonceParams = parse_params(body)?;
     let blockchain: &Arc<Blockchain<S>> = context.get()?;
@@ -598,8 +897,7 @@ async fn get_nonce<S: Storage>(context: &Context, body: Value) -> Result<Value,
         return Err(InternalRpcError::InvalidParamsAny(BlockchainError::InvalidNetwork.into()))
     }
 
-    let storage = blockchain.get_storage().read().await;
-    let (topoheight, version) = storage.get_last_nonce(params.address.get_public_key()).await
+    let (topoheight, version) = blockchain.get_committed_nonce(params.address.get_public_key()).await
         .context("Error while retrieving nonce for account")?;
 
     Ok(json!(GetNonceResult { topoheight, version }))
@@ -622,12 +920,151 @@ async fn get_nonce_at_topoheight<S: Storage>(context: &Context, body: Value) ->
     Ok(json!(nonce))
 }
 
+async fn reserve_nonce<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: ReserveNonceParams = parse_params(body)?;
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    if params.address.is_mainnet() != blockchain.get_network().is_mainnet() {
+        return Err(InternalRpcError::InvalidParamsAny(BlockchainError::InvalidNetwork.into()))
+    }
+
+    let duration = params.duration.unwrap_or(NONCE_RESERVATION_DEFAULT_DURATION);
+    let reservation = blockchain.reserve_nonce(params.address.get_public_key(), duration).await?;
+    Ok(json!(NonceReservationResult {
+        id: reservation.get_id(),
+        nonce: reservation.get_nonce(),
+        expire_at: reservation.get_expire_at()
+    }))
+}
+
+async fn renew_nonce_reservation<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: RenewNonceReservationParams = parse_params(body)?;
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    if params.address.is_mainnet() != blockchain.get_network().is_mainnet() {
+        return Err(InternalRpcError::InvalidParamsAny(BlockchainError::InvalidNetwork.into()))
+    }
+
+    let duration = params.duration.unwrap_or(NONCE_RESERVATION_DEFAULT_DURATION);
+    let reservation = blockchain.renew_nonce_reservation(params.address.get_public_key(), params.id, duration).await?;
+    Ok(json!(NonceReservationResult {
+        id: reservation.get_id(),
+        nonce: reservation.get_nonce(),
+        expire_at: reservation.get_expire_at()
+    }))
+}
+
+async fn release_nonce_reservation<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: ReleaseNonceReservationParams = parse_params(body)?;
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    if params.address.is_mainnet() != blockchain.get_network().is_mainnet() {
+        return Err(InternalRpcError::InvalidParamsAny(BlockchainError::InvalidNetwork.into()))
+    }
+
+    blockchain.release_nonce_reservation(params.address.get_public_key(), params.id).await?;
+    Ok(json!(true))
+}
+
+async fn get_multisig<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: GetMultiSigParams = parse_params(body)?;
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let mainnet = blockchain.get_network().is_mainnet();
+    if params.address.is_mainnet() != mainnet {
+        return Err(InternalRpcError::InvalidParamsAny(BlockchainError::InvalidNetwork.into()))
+    }
+
+    let storage = blockchain.get_storage().read().await;
+    let (topoheight, multisig) = storage.get_multisig_at_maximum_topoheight_for(params.address.get_public_key(), blockchain.get_topo_height()).await
+        .context("Error while retrieving multisig for account")?
+        .filter(|(_, multisig)| !multisig.is_delete())
+        .context("Account has no multisig")?;
+
+    Ok(json!(GetMultiSigResult {
+        topoheight,
+        threshold: multisig.threshold,
+        participants: multisig.participants.iter().map(|key| key.as_address(mainnet)).collect()
+    }))
+}
+
+async fn has_multisig<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: HasMultiSigParams = parse_params(body)?;
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    if params.address.is_mainnet() != blockchain.get_network().is_mainnet() {
+        return Err(InternalRpcError::InvalidParamsAny(BlockchainError::InvalidNetwork.into()))
+    }
+
+    let storage = blockchain.get_storage().read().await;
+    let exist = storage.has_multisig_at_maximum_topoheight_for(params.address.get_public_key(), blockchain.get_topo_height()).await
+        .context("Error while checking multisig for account")?;
+
+    Ok(json!(exist))
+}
+
+async fn get_locked_transfer<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: GetLockedTransferParams = parse_params(body)?;
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let mainnet = blockchain.get_network().is_mainnet();
+    let storage = blockchain.get_storage().read().await;
+    let (topoheight, locked) = storage.get_locked_transfer_at_maximum_topoheight(&params.id, blockchain.get_topo_height()).await
+        .context("Error while retrieving locked transfer")?
+        .and_then(|(topoheight, locked)| locked.map(|locked| (topoheight, locked)))
+        .context("Locked transfer was not found or is already unlocked")?;
+
+    Ok(json!(GetLockedTransferResult {
+        topoheight,
+        source: locked.source.as_address(mainnet),
+        destination: locked.destination.as_address(mainnet),
+        asset: locked.asset,
+        lock: locked.lock
+    }))
+}
+
+async fn get_contract<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: GetContractParams = parse_params(body)?;
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let storage = blockchain.get_storage().read().await;
+    let (topoheight, program) = storage.get_contract_at_maximum_topoheight(&params.contract, blockchain.get_topo_height()).await
+        .context("Error while retrieving contract")?
+        .context("Contract was not found")?;
+
+    Ok(json!(GetContractResult {
+        topoheight,
+        program: Cow::Owned(program)
+    }))
+}
+
+async fn get_contract_data<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: GetContractDataParams = parse_params(body)?;
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let storage = blockchain.get_storage().read().await;
+    let topoheight = blockchain.get_topo_height();
+    let (deploy_topoheight, _) = storage.get_contract_at_maximum_topoheight(&params.contract, topoheight).await
+        .context("Error while retrieving contract")?
+        .context("Contract was not found")?;
+
+    // A key never set is returned as unset since the contract deployment
+    let result = match storage.get_contract_data_at_maximum_topoheight(&params.contract, params.key, topoheight).await.context("Error while retrieving contract data")? {
+        Some((topoheight, value)) => GetContractDataResult { topoheight, value },
+        None => GetContractDataResult { topoheight: deploy_topoheight, value: None }
+    };
+
+    Ok(json!(result))
+}
+
 async fn get_asset<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
     let params: GetAssetParams = parse_params(body)?;
     let blockchain: &Arc<Blockchain<S>> = context.get()?;
     let storage = blockchain.get_storage().read().await;
-    let asset = storage.get_asset(&params.asset).await.context("Asset was not found")?;
-    Ok(json!(asset))
+    let data = storage.get_asset(&params.asset).await.context("Asset was not found")?;
+    // Only assets created by a transaction have an owner and a supply
+    let state = storage.get_asset_state_at_maximum_topoheight(&params.asset, blockchain.get_topo_height()).await
+        .context("Error while retrieving asset state")?
+        .map(|(_, state)| state);
+
+    let mainnet = blockchain.get_network().is_mainnet();
+    Ok(json!(GetAssetResult {
+        data,
+        owner: state.as_ref().map(|state| state.get_owner().as_address(mainnet)),
+        supply: state.map(|state| state.get_supply())
+    }))
 }
 
 const MAX_ASSETS: usize = 100;
@@ -694,7 +1131,15 @@ async fn submit_transaction<S: Storage>(context: &Context, body: Value) -> Resul
         .map_err(|err| InternalRpcError::InvalidParamsAny(err.into()))?;
 
     let blockchain: &Arc<Blockchain<S>> = context.get()?;
-    blockchain.add_tx_to_mempool(transaction, true).await?;
+    // a standby node would not relay it
+    if blockchain.is_standby() {
+        return Err(BlockchainError::StandbyMode.into())
+    }
+
+    let hash = transaction.hash();
+    blockchain.add_tx_to_mempool_with_hash(transaction, hash.clone(), true).await?;
+    // broadcast it again if it is dropped by our peers
+    blockchain.track_local_tx(hash).await;
 
     Ok(json!(true))
 }
@@ -767,9 +1212,12 @@ async fn get_peers<S: Storage>(context: &Context, body: Value) -> Result<Value,
             let mut peers = Vec::new();
             let peers_availables = peer_list.get_cloned_peers().await;
             let total_peers = peers_availables.len();
+            let topoheight = blockchain.get_topo_height();
             let mut sharable_peers = 0;
             for p in peers_availables.iter().filter(|p| p.sharable()) {
-                peers.push(get_peer_entry(p).await);
+                let mut entry = get_peer_entry(p).await;
+                entry.topoheight_diff = Some(p.get_topoheight() as i64 - topoheight as i64);
+                peers.push(entry);
                 sharable_peers += 1;
             }
             Ok(json!(GetPeersResponse {
@@ -782,6 +1230,142 @@ async fn get_peers<S: Storage>(context: &Context, body: Value) -> Result<Value,
     }
 }
 
+// Summarize how many peers agree with our chain
+// A high count of diverged peers is an early warning of a chain split
+async fn get_network_view<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    if body != Value::Null {
+        return Err(InternalRpcError::UnexpectedParams)
+    }
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let p2p = { blockchain.get_p2p().read().await.clone() };
+    match p2p.as_ref() {
+        Some(p2p) => {
+            // Chain views status are computed here, not on each ping
+            let storage = blockchain.get_storage().read().await;
+            let our_top_block_hash = storage.get_top_block_hash().await.context("Error while retrieving top block hash")?;
+            let our_topoheight = blockchain.get_topo_height();
+
+            let (mut synced, mut behind, mut ahead, mut diverged, mut unknown) = (0, 0, 0, 0, 0);
+            let mut peers = Vec::new();
+            for peer in p2p.get_peer_list().get_cloned_peers().await {
+                let (status, status_since) = match peer.get_chain_status(&*storage).await {
+                    Some((status, since)) => (status, Some(since)),
+                    None => (PeerChainStatus::Unknown, None)
+                };
+
+                match status {
+                    PeerChainStatus::Synced => synced += 1,
+                    PeerChainStatus::Behind => behind += 1,
+                    PeerChainStatus::Ahead => ahead += 1,
+                    PeerChainStatus::Diverged => diverged += 1,
+                    PeerChainStatus::Unknown => unknown += 1
+                };
+
+                if !peer.sharable() {
+                    continue;
+                }
+
+                let topoheight = peer.get_topoheight();
+                let top_block_hash = { peer.get_top_block_hash().lock().await.clone() };
+                let cumulative_difficulty = { peer.get_cumulative_difficulty().lock().await.clone() };
+                peers.push(PeerChainViewEntry {
+                    id: peer.get_id(),
+                    addr: Cow::Owned(*peer.get_connection().get_address()),
+                    status,
+                    status_since,
+                    topoheight,
+                    top_block_hash: Cow::Owned(top_block_hash),
+                    cumulative_difficulty: Cow::Owned(cumulative_difficulty),
+                    topoheight_difference: topoheight as i64 - our_topoheight as i64
+                });
+            }
+
+            Ok(json!(GetNetworkViewResult {
+                our_topoheight,
+                our_top_block_hash: Cow::Owned(our_top_block_hash),
+                synced,
+                behind,
+                ahead,
+                diverged,
+                unknown,
+                peers
+            }))
+        },
+        None => Err(InternalRpcError::InvalidParamsAny(ApiError::NoP2p.into()))
+    }
+}
+
+// Get the connections limits per direction and the anchors state
+async fn get_connection_manager<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    if body != Value::Null {
+        return Err(InternalRpcError::UnexpectedParams)
+    }
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let p2p = { blockchain.get_p2p().read().await.clone() };
+    match p2p.as_ref() {
+        Some(p2p) => {
+            let (max_outbound, max_inbound) = p2p.get_connection_targets().await;
+            let (outbound, inbound) = p2p.get_peer_list().count_by_direction().await;
+            let mut anchors = Vec::new();
+            for (addr, anchor) in p2p.get_anchors().await {
+                anchors.push(AnchorPeerEntry {
+                    addr,
+                    connected: p2p.is_connected_to_addr(&addr).await,
+                    failures: anchor.get_failures(),
+                    next_attempt: anchor.get_next_attempt()
+                });
+            }
+
+            Ok(json!(GetConnectionManagerResult {
+                max_outbound,
+                max_inbound,
+                outbound,
+                inbound,
+                anchors
+            }))
+        },
+        None => Err(InternalRpcError::InvalidParamsAny(ApiError::NoP2p.into()))
+    }
+}
+
+async fn set_connection_targets<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: SetConnectionTargetsParams = parse_params(body)?;
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let p2p = { blockchain.get_p2p().read().await.clone() };
+    match p2p.as_ref() {
+        Some(p2p) => {
+            let max_peers = p2p.get_max_peers();
+            if params.max_outbound.is_some_and(|v| v > max_peers) || params.max_inbound.is_some_and(|v| v > max_peers) {
+                return Err(InternalRpcError::InvalidJSONRequest).context(format!("Connections limits cannot be above max peers ({})", max_peers))?
+            }
+
+            p2p.set_connection_targets(params.max_outbound, params.max_inbound).await;
+            Ok(json!(true))
+        },
+        None => Err(InternalRpcError::InvalidParamsAny(ApiError::NoP2p.into()))
+    }
+}
+
+async fn add_anchor_peer<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: AnchorPeerParams = parse_params(body)?;
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let p2p = { blockchain.get_p2p().read().await.clone() };
+    match p2p.as_ref() {
+        Some(p2p) => Ok(json!(p2p.add_anchor(params.address).await)),
+        None => Err(InternalRpcError::InvalidParamsAny(ApiError::NoP2p.into()))
+    }
+}
+
+async fn remove_anchor_peer<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: AnchorPeerParams = parse_params(body)?;
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let p2p = { blockchain.get_p2p().read().await.clone() };
+    match p2p.as_ref() {
+        Some(p2p) => Ok(json!(p2p.remove_anchor(&params.address).await)),
+        None => Err(InternalRpcError::InvalidParamsAny(ApiError::NoP2p.into()))
+    }
+}
+
 async fn get_mempool<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
     if body != Value::Null {
         return Err(InternalRpcError::UnexpectedParams)
@@ -797,6 +1381,125 @@ async fn get_mempool<S: Storage>(context: &Context, body: Value) -> Result<Value
     Ok(json!(transactions))
 }
 
+// Fee paid per kB (1024 bytes) by a transaction
+fn get_fee_per_kb(fee: u64, size: usize) -> u64 {
+    (fee as u128 * 1024 / size.max(1) as u128) as u64
+}
+
+// Lower bounds of the fee rate histogram buckets, in multiple of FEE_PER_KB
+const FEE_RATE_BUCKETS: [u64; 7] = [0, 1, 2, 5, 10, 50, 100];
+
+// Summary of the mempool with its fee rate histogram to estimate the congestion
+async fn get_mempool_summary<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    if body != Value::Null {
+        return Err(InternalRpcError::UnexpectedParams)
+    }
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let mainnet = blockchain.get_network().is_mainnet();
+    let mempool = blockchain.get_mempool().read().await;
+
+    let mut histogram: Vec<FeeRateBucket> = FEE_RATE_BUCKETS.iter()
+        .enumerate()
+        .map(|(i, min)| FeeRateBucket {
+            min_fee_per_kb: min * FEE_PER_KB,
+            max_fee_per_kb: FEE_RATE_BUCKETS.get(i + 1).map(|max| max * FEE_PER_KB),
+            tx_count: 0,
+            total_size: 0
+        })
+        .collect();
+
+    let mut total_size = 0;
+    let mut total_fees = 0;
+    let mut transactions = Vec::with_capacity(mempool.size());
+    for (hash, sorted_tx) in mempool.get_txs() {
+        let tx = sorted_tx.get_tx();
+        let size = sorted_tx.get_size();
+        let fee_per_kb = get_fee_per_kb(tx.get_fee(), size);
+        total_size += size;
+        total_fees += tx.get_fee();
+
+        // buckets are sorted by their lower bound
+        if let Some(bucket) = histogram.iter_mut().rev().find(|bucket| bucket.min_fee_per_kb <= fee_per_kb) {
+            bucket.tx_count += 1;
+            bucket.total_size += size;
+        }
+
+        transactions.push(MempoolTransactionSummary {
+            hash: Hash::clone(hash),
+            fee: tx.get_fee(),
+            size,
+            fee_per_kb,
+            owner: tx.get_source().as_address(mainnet),
+            nonce: tx.get_nonce(),
+            first_seen: sorted_tx.get_first_seen()
+        });
+    }
+
+    // highest fee rate first, then by owner and nonce so an account txs stay ordered
+    transactions.sort_by(|a, b| b.fee_per_kb.cmp(&a.fee_per_kb)
+        .then_with(|| a.owner.get_public_key().as_bytes().cmp(b.owner.get_public_key().as_bytes()))
+        .then_with(|| a.nonce.cmp(&b.nonce))
+    );
+
+    Ok(json!(GetMempoolSummaryResult {
+        tx_count: mempool.size(),
+        total_size,
+        total_fees,
+        histogram,
+        transactions
+    }))
+}
+
+// Get a transaction from the mempool with its fee rate and rank
+async fn get_mempool_tx<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: GetMempoolTransactionParams = parse_params(body)?;
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let mempool = blockchain.get_mempool().read().await;
+    let sorted_tx = mempool.get_sorted_tx(&params.hash).context("Transaction not found in mempool")?;
+    let tx = sorted_tx.get_tx();
+    let size = sorted_tx.get_size();
+    let fee_per_kb = get_fee_per_kb(tx.get_fee(), size);
+    let rank = mempool.get_txs()
+        .values()
+        .filter(|other| get_fee_per_kb(other.get_fee(), other.get_size()) > fee_per_kb)
+        .count();
+
+    Ok(json!(GetMempoolTransactionResult {
+        size,
+        fee_per_kb,
+        first_seen: sorted_tx.get_first_seen(),
+        rank,
+        data: RPCTransaction::from_tx(tx, &params.hash, blockchain.get_network().is_mainnet())
+    }))
+}
+
+// get the last transactions detected in conflict with a mempool transaction (same owner and nonce)
+async fn get_transaction_conflicts<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: GetTransactionConflictsParams = parse_params(body)?;
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let conflicts = blockchain.get_tx_conflicts(params.hash.as_deref()).await;
+    Ok(json!(conflicts))
+}
+
+// metrics of the queue in which the new blocks wait to be processed
+async fn get_block_processing_stats<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    if body != Value::Null {
+        return Err(InternalRpcError::UnexpectedParams)
+    }
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    Ok(json!(blockchain.get_block_processing_stats().await))
+}
+
+// hits and misses of each storage cache enabled
+async fn get_cache_stats<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    if body != Value::Null {
+        return Err(InternalRpcError::UnexpectedParams)
+    }
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let storage = blockchain.get_storage().read().await;
+    Ok(json!(storage.get_cache_stats().await))
+}
+
 async fn get_blocks_at_height<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
     let params: GetBlocksAtHeightParams = parse_params(body)?;
     let blockchain: &Arc<Blockchain<S>> = context.get()?;
@@ -830,9 +1533,11 @@ async fn get_dag_order<S: Storage>(context: &Context, body: Value) -> Result<Val
     let (start_topoheight, end_topoheight) = get_range(params.start_topoheight, params.end_topoheight, MAX_DAG_ORDER, current)?;
     let count = end_topoheight - start_topoheight;
 
+    let token = CancellationToken::current();
     let storage = blockchain.get_storage().read().await;
     let mut order = Vec::with_capacity(count as usize);
     for i in start_topoheight..=end_topoheight {
+        token.check()?;
         let hash = storage.get_hash_at_topo_height(i).await.context("Error while retrieving hash at topo height")?;
         order.push(hash);
     }
@@ -840,6 +1545,98 @@ async fn get_dag_order<S: Storage>(context: &Context, body: Value) -> Result<Val
     Ok(json!(order))
 }
 
+// Build the Graphviz DOT representation of the blocks
+// Each block points to its tips, tips outside of the range are also drawn
+fn build_dag_dot(blocks: &[DagBlockEntry<'_>]) -> String {
+    let mut dot = String::from("digraph dag {\n    rankdir=RL;\n    node [shape=box, style=filled];\n");
+    for block in blocks {
+        let color = match block.block_type {
+            BlockType::Sync => "palegreen",
+            BlockType::Side => "khaki",
+            BlockType::Orphaned => "lightcoral",
+            BlockType::Normal => "lightblue"
+        };
+        let topoheight = block.topoheight.map(|topo| topo.to_string()).unwrap_or_else(|| String::from("-"));
+        let hash = block.hash.to_hex();
+        dot.push_str(&format!("    \"{}\" [label=\"{}...\\nheight: {}\\ntopoheight: {}\\n{:?}\\ncumulative difficulty: {}\", fillcolor={}];\n", hash, &hash[..8], block.height, topoheight, block.block_type, block.cumulative_difficulty, color));
+        for tip in block.tips.iter() {
+            dot.push_str(&format!("    \"{}\" -> \"{}\";\n", hash, tip));
+        }
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+// get the DAG structure for a topoheight range
+// orphaned blocks at the heights of the range are also included
+async fn get_dag_info<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: GetDagInfoParams = parse_params(body)?;
+
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let current = blockchain.get_topo_height();
+    let (start_topoheight, end_topoheight) = get_range(params.start_topoheight, params.end_topoheight, MAX_DAG_ORDER, current)?;
+
+    let token = CancellationToken::current();
+    let storage = blockchain.get_storage().read().await;
+    let mut blocks = Vec::with_capacity((end_topoheight - start_topoheight + 1) as usize);
+    let mut min_height = u64::MAX;
+    let mut max_height = 0;
+    for topoheight in start_topoheight..=end_topoheight {
+        token.check()?;
+        let hash = storage.get_hash_at_topo_height(topoheight).await.context("Error while retrieving hash at topo height")?;
+        let header = storage.get_block_header_by_hash(&hash).await.context("Error while retrieving block header")?;
+        let height = header.get_height();
+        min_height = min_height.min(height);
+        max_height = max_height.max(height);
+
+        let block_type = get_block_type_for_block(&blockchain, &storage, &hash).await?;
+        let cumulative_difficulty = storage.get_cumulative_difficulty_for_block_hash(&hash).await.context("Error while retrieving cumulative difficulty")?;
+        blocks.push(DagBlockEntry {
+            tips: Cow::Owned(header.get_tips().clone()),
+            hash: Cow::Owned(hash),
+            height,
+            topoheight: Some(topoheight),
+            block_type,
+            cumulative_difficulty: Cow::Owned(cumulative_difficulty)
+        });
+    }
+
+    // Search the orphaned blocks, they are not ordered
+    for height in min_height..=max_height {
+        token.check()?;
+        let blocks_at_height = storage.get_blocks_at_height(height).await.context("Error while retrieving blocks at height")?;
+        for hash in blocks_at_height {
+            if storage.is_block_topological_ordered(&hash).await {
+                continue;
+            }
+
+            let header = storage.get_block_header_by_hash(&hash).await.context("Error while retrieving block header")?;
+            let cumulative_difficulty = storage.get_cumulative_difficulty_for_block_hash(&hash).await.context("Error while retrieving cumulative difficulty")?;
+            blocks.push(DagBlockEntry {
+                tips: Cow::Owned(header.get_tips().clone()),
+                hash: Cow::Owned(hash),
+                height,
+                topoheight: None,
+                block_type: BlockType::Orphaned,
+                cumulative_difficulty: Cow::Owned(cumulative_difficulty)
+            });
+        }
+    }
+
+    let dot = if params.include_dot {
+        Some(build_dag_dot(&blocks))
+    } else {
+        None
+    };
+
+    Ok(json!(GetDagInfoResult {
+        start_topoheight,
+        end_topoheight,
+        blocks,
+        dot
+    }))
+}
+
 const MAX_BLOCKS: u64 = 20;
 
 fn get_range(start: Option<u64>, end: Option<u64>, maximum: u64, current: u64) -> Result<(u64, u64), InternalRpcError> {
@@ -875,9 +1672,11 @@ async fn get_blocks_range_by_topoheight<S: Storage>(context: &Context, body: Val
     let current_topoheight = blockchain.get_topo_height();
     let (start_topoheight, end_topoheight) = get_range(params.start_topoheight, params.end_topoheight, MAX_BLOCKS, current_topoheight)?;
 
+    let token = CancellationToken::current();
     let storage = blockchain.get_storage().read().await;
     let mut blocks = Vec::with_capacity((end_topoheight - start_topoheight) as usize);
     for i in start_topoheight..=end_topoheight {
+        token.check()?;
         let hash = storage.get_hash_at_topo_height(i).await.context("Error while retrieving hash at topo height")?;
         let response = get_block_response_for_hash(&blockchain, &storage, &hash, false).await?;
         blocks.push(response);
@@ -895,9 +1694,11 @@ async fn get_blocks_range_by_height<S: Storage>(context: &Context, body: Value)
     let current_height = blockchain.get_height();
     let (start_height, end_height) = get_range(params.start_height, params.end_height, MAX_BLOCKS, current_height)?;
 
+    let token = CancellationToken::current();
     let storage = blockchain.get_storage().read().await;
     let mut blocks = Vec::with_capacity((end_height - start_height) as usize);
     for i in start_height..=end_height {
+        token.check()?;
         let blocks_at_height = storage.get_blocks_at_height(i).await.context("Error while retrieving blocks at height")?;
         for hash in blocks_at_height {
             let response = get_block_response_for_hash(&blockchain, &storage, &hash, false).await?;
@@ -959,10 +1760,12 @@ async fn get_account_history<S: Storage>(context: &Context, body: Value) -> Resu
         Some(storage.get_last_balance(key, &params.asset).await.context("Error while retrieving last balance")?)
     };
 
+    let token = CancellationToken::current();
     let mut history_count = 0;
     let mut history = Vec::new();
     let is_dev_address = *key == *DEV_PUBLIC_KEY;
     loop {
+        token.check()?;
         if let Some((topo, versioned_balance)) = version.take() {
             trace!("Searching history at topoheight {}", topo);
             if topo < minimum_topoheight || topo < pruned_topoheight {
@@ -1004,6 +1807,7 @@ async fn get_account_history<S: Storage>(context: &Context, body: Value) -> Resu
 
             // Reverse the order of transactions to get the latest first
             for tx_hash in block_header.get_transactions().iter().rev() {
+                token.check()?;
                 trace!("Searching tx {} in block {}", tx_hash, hash);
                 let tx = storage.get_transaction(tx_hash).await.context(format!("Error while retrieving transaction {tx_hash} from block {hash}"))?;
                 let is_sender = *tx.get_source() == *key;
@@ -1011,7 +1815,8 @@ async fn get_account_history<S: Storage>(context: &Context, body: Value) -> Resu
                     TransactionType::Transfers(transfers) => {
                         for transfer in transfers {
                             if *transfer.get_asset() == params.asset {
-                                if *transfer.get_destination() == *key {
+                                // Locked transfers are credited once unlocked
+                                if *transfer.get_destination() == *key && transfer.get_lock().is_none() {
                                     history.push(AccountHistoryEntry {
                                         topoheight: topo,
                                         hash: tx_hash.clone(),
@@ -1047,6 +1852,50 @@ async fn get_account_history<S: Storage>(context: &Context, body: Value) -> Resu
                             }
                         }
                     }
+                    TransactionType::CreateAsset(_) => {
+                        if params.asset == XELIS_ASSET && is_sender {
+                            history.push(AccountHistoryEntry {
+                                topoheight: topo,
+                                hash: tx_hash.clone(),
+                                history_type: AccountHistoryType::AssetCreation { amount: ASSET_REGISTRATION_FEE },
+                                block_timestamp: block_header.get_timestamp()
+                            });
+                        }
+                    }
+                    TransactionType::MintAsset(payload) => {
+                        if payload.asset == params.asset && is_sender {
+                            history.push(AccountHistoryEntry {
+                                topoheight: topo,
+                                hash: tx_hash.clone(),
+                                history_type: AccountHistoryType::Mint { amount: payload.amount },
+                                block_timestamp: block_header.get_timestamp()
+                            });
+                        }
+                    }
+                    TransactionType::Unlock(payload) => {
+                        if payload.asset == params.asset && is_sender {
+                            history.push(AccountHistoryEntry {
+                                topoheight: topo,
+                                hash: tx_hash.clone(),
+                                history_type: AccountHistoryType::Unlock { id: payload.id.clone() },
+                                block_timestamp: block_header.get_timestamp()
+                            });
+                        }
+                    }
+                    TransactionType::InvokeContract(payload) => {
+                        if params.asset == XELIS_ASSET && is_sender {
+                            history.push(AccountHistoryEntry {
+                                topoheight: topo,
+                                hash: tx_hash.clone(),
+                                history_type: AccountHistoryType::InvokeContract { contract: payload.contract.clone(), gas: payload.max_gas },
+                                block_timestamp: block_header.get_timestamp()
+                            });
+                        }
+                    }
+                    // No balance change
+                    TransactionType::TransferAssetOwnership(_)
+                    | TransactionType::MultiSig(_)
+                    | TransactionType::DeployContract(_) => {}
                 }
             }
 
@@ -1172,6 +2021,16 @@ async fn get_dev_fee_thresholds<S: Storage>(_: &Context, body: Value) -> Result<
     Ok(json!(DEV_FEES))
 }
 
+// Get all hard forks of the network with the rules they activate
+async fn get_hard_forks<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    if body != Value::Null {
+        return Err(InternalRpcError::UnexpectedParams)
+    }
+
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    Ok(json!(get_hard_forks(blockchain.get_network())))
+}
+
 // Get size on disk of the chain database
 async fn get_size_on_disk<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
     if body != Value::Null {
@@ -1214,7 +2073,7 @@ async fn get_difficulty<S: Storage>(context: &Context, body: Value) -> Result<Va
 
     let blockchain: &Arc<Blockchain<S>> = context.get()?;
     let difficulty = blockchain.get_difficulty().await;
-    let hashrate = difficulty / BLOCK_TIME;
+    let hashrate = blockchain.get_chain_params().estimate_hashrate(difficulty);
     let hashrate_formatted = format_hashrate(hashrate.into());
     Ok(json!(GetDifficultyResult {
         hashrate,
@@ -1223,6 +2082,123 @@ async fn get_difficulty<S: Storage>(context: &Context, body: Value) -> Result<Va
     }))
 }
 
+const MAX_DIFFICULTY_SAMPLES: u64 = 100;
+
+// get the difficulty of the last N blocks ordered with their solve time
+async fn get_difficulty_history<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: GetDifficultyHistoryParams = parse_params(body)?;
+    if params.count == 0 || params.count > MAX_DIFFICULTY_SAMPLES {
+        return Err(InternalRpcError::InvalidJSONRequest).context(format!("Invalid count requested, received {} but maximum is {}", params.count, MAX_DIFFICULTY_SAMPLES))?
+    }
+
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let difficulty = blockchain.get_difficulty().await;
+    let target = compute_difficulty_target(&difficulty).context("Error while computing current target")?;
+
+    let storage = blockchain.get_storage().read().await;
+    let topoheight = blockchain.get_topo_height();
+    let mut minimum_topoheight = (topoheight + 1).saturating_sub(params.count);
+    // blocks below the pruned topoheight may not be available anymore
+    if let Some(pruned_topoheight) = storage.get_pruned_topoheight().await.context("Error while retrieving pruned topoheight")? {
+        minimum_topoheight = minimum_topoheight.max(pruned_topoheight);
+    }
+
+    let token = CancellationToken::current();
+    let mut samples = Vec::with_capacity((topoheight + 1 - minimum_topoheight) as usize);
+    for topoheight in (minimum_topoheight..=topoheight).rev() {
+        token.check()?;
+        let hash = storage.get_hash_at_topo_height(topoheight).await.context("Error while retrieving hash at topo height")?;
+        let header = storage.get_block_header_by_hash(&hash).await.context("Error while retrieving block header")?;
+        let difficulty = storage.get_difficulty_for_block_hash(&hash).await.context("Error while retrieving difficulty")?;
+
+        let mut tips_timestamp = None;
+        for tip in header.get_tips() {
+            let timestamp = storage.get_timestamp_for_block_hash(tip).await.context("Error while retrieving tip timestamp")?;
+            tips_timestamp = tips_timestamp.max(Some(timestamp));
+        }
+        // genesis block has no tips
+        let solve_time = tips_timestamp.map(|timestamp| header.get_timestamp().saturating_sub(timestamp)).unwrap_or(0);
+
+        samples.push(DifficultySample {
+            topoheight,
+            hash,
+            difficulty,
+            solve_time
+        });
+    }
+
+    Ok(json!(GetDifficultyHistoryResult {
+        difficulty,
+        target: compact_from_target(&target),
+        samples
+    }))
+}
+
+// get the chain stats aggregated per day between two timestamps
+async fn get_daily_stats<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: GetDailyStatsParams = parse_params(body)?;
+    if params.from > params.to {
+        return Err(InternalRpcError::InvalidJSONRequest).context(format!("Invalid range requested, from {} is above to {}", params.from, params.to))?
+    }
+
+    let minimum_day = get_day_for_timestamp(params.from);
+    let maximum_day = get_day_for_timestamp(params.to);
+    if maximum_day - minimum_day >= MAX_DAILY_STATS_DAYS {
+        return Err(InternalRpcError::InvalidJSONRequest).context(format!("Invalid range requested, maximum is {} days", MAX_DAILY_STATS_DAYS))?
+    }
+
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let storage = blockchain.get_storage().read().await;
+    let days = storage.get_daily_stats(minimum_day, maximum_day).await.context("Error while retrieving daily stats")?
+        .into_iter()
+        .map(|(day, stats)| DailyStatsEntry {
+            day: day * STATS_DAY_DURATION_MILLIS,
+            blocks: stats.blocks,
+            txs: stats.txs,
+            fees: stats.fees,
+            new_accounts: stats.new_accounts,
+            emission: stats.emission
+        })
+        .collect::<Vec<_>>();
+
+    Ok(json!(days))
+}
+
+const MAX_EVENTS: usize = 100;
+// get the journaled events notified after the requested sequence number
+// this allows a websocket client to catch up the events it missed while disconnected
+async fn get_events_since<S: Storage>(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: GetEventsSinceParams = parse_params(body)?;
+    let maximum = if let Some(maximum) = params.maximum {
+        if maximum > MAX_EVENTS {
+            return Err(InternalRpcError::InvalidJSONRequest).context(format!("Maximum events requested cannot be greater than {}", MAX_EVENTS))?
+        }
+        maximum
+    } else {
+        MAX_EVENTS
+    };
+
+    let blockchain: &Arc<Blockchain<S>> = context.get()?;
+    let enabled = match blockchain.get_rpc().read().await.as_ref() {
+        Some(rpc) => rpc.is_event_journal_enabled(),
+        None => false
+    };
+    if !enabled {
+        return Err(InternalRpcError::InvalidParamsAny(ApiError::NoEventJournal.into()))
+    }
+
+    let storage = blockchain.get_storage().read().await;
+    let events = storage.get_events_since(params.sequence, maximum).await.context("Error while retrieving events")?;
+    let oldest_sequence = storage.get_oldest_event_sequence().await.context("Error while retrieving oldest event sequence")?;
+    let last_sequence = storage.get_last_event_sequence().await.context("Error while retrieving last event sequence")?;
+
+    Ok(json!(GetEventsSinceResult {
+        oldest_sequence,
+        last_sequence,
+        events
+    }))
+}
+
 async fn validate_address<S: Storage>(_: &Context, body: Value) -> Result<Value, InternalRpcError> {
     let params: ValidateAddressParams = parse_params(body)?;
 
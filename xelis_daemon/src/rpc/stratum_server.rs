@@ -0,0 +1,652 @@
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fmt::Display,
+    net::SocketAddr,
+    num::NonZeroUsize,
+    sync::{
+        atomic::{
+            AtomicU64,
+            Ordering
+        },
+        Arc
+    }
+};
+use anyhow::Context;
+use indexmap::IndexSet;
+use log::{debug, error, info, trace, warn};
+use lru::LruCache;
+use rand::{
+    rngs::OsRng,
+    RngCore
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use thiserror::Error;
+use tokio::{
+    io::{
+        AsyncBufReadExt,
+        AsyncReadExt,
+        AsyncWriteExt,
+        BufReader
+    },
+    net::{
+        TcpListener,
+        TcpStream
+    },
+    select,
+    sync::{
+        broadcast,
+        mpsc,
+        Mutex
+    },
+    task::spawn_blocking
+};
+use xelis_common::{
+    block::{
+        BlockHeader,
+        MinerWork
+    },
+    crypto::{
+        Address,
+        Hash,
+        Hashable,
+        PublicKey
+    },
+    difficulty::{
+        check_difficulty,
+        Difficulty
+    },
+    immutable::Immutable,
+    rpc_server::{
+        Id,
+        InternalRpcError,
+        RpcResponse,
+        RpcResponseError,
+        JSON_RPC_VERSION
+    },
+    serializer::Serializer,
+    time::{
+        get_current_time_in_millis,
+        TimestampMillis
+    },
+    utils::spawn_task
+};
+use crate::{
+    core::{
+        blockchain::Blockchain,
+        error::BlockchainError,
+        storage::Storage
+    },
+    config::{
+        DEV_PUBLIC_KEY,
+        STABLE_LIMIT
+    }
+};
+
+// Maximum size of a message sent by a miner
+const MAX_MESSAGE_SIZE: usize = 4096;
+// Maximum size of a worker name
+const MAX_WORKER_NAME_SIZE: usize = 32;
+// Messages waiting to be written to a miner
+// If the queue is full, the miner is too slow and messages are dropped
+const MESSAGES_QUEUE_SIZE: usize = 64;
+// Keep the PoW hashes of the last N shares of a miner to detect duplicates
+const SUBMITTED_SHARES_CACHE_SIZE: usize = 1024;
+
+pub type SharedStratumServer<S> = Arc<StratumServer<S>>;
+
+pub struct StratumConfig {
+    // address to listen on for miners connections
+    pub bind_address: String,
+    // default and minimum difficulty of the shares
+    pub share_difficulty: Difficulty
+}
+
+// Errors sent to the miners, codes are the ones used by Stratum pools
+#[derive(Debug, Error)]
+pub enum StratumError {
+    #[error("Job not found")]
+    JobNotFound,
+    #[error("Duplicate share")]
+    DuplicateShare,
+    #[error("Low difficulty share")]
+    LowDifficultyShare,
+    #[error("Unauthorized worker")]
+    Unauthorized,
+    #[error("Worker is already authorized")]
+    AlreadyAuthorized,
+    #[error("Invalid miner address")]
+    InvalidAddress,
+    #[error("Miner work is not for the authorized address")]
+    InvalidMiner
+}
+
+impl StratumError {
+    pub fn get_code(&self) -> i16 {
+        match self {
+            Self::JobNotFound => 21,
+            Self::DuplicateShare => 22,
+            Self::LowDifficultyShare => 23,
+            Self::Unauthorized => 24,
+            Self::AlreadyAuthorized | Self::InvalidAddress | Self::InvalidMiner => 20
+        }
+    }
+}
+
+impl From<StratumError> for InternalRpcError {
+    fn from(e: StratumError) -> Self {
+        InternalRpcError::Custom(e.get_code(), e.to_string())
+    }
+}
+
+// Request sent by a miner, one JSON object per line
+#[derive(Deserialize)]
+struct StratumRequest {
+    id: Option<Id>,
+    method: String,
+    #[serde(default)]
+    params: Value
+}
+
+pub struct StratumMiner {
+    // Used to display correctly its address
+    mainnet: bool,
+    // address of the TCP connection
+    address: SocketAddr,
+    // public key of account (address), set once authorized
+    key: Option<PublicKey>,
+    // worker name
+    name: String,
+    // difficulty expected for each share
+    difficulty: Difficulty,
+    // shares accepted since he is connected
+    shares_accepted: usize,
+    // shares rejected since he is connected
+    shares_rejected: usize,
+    // blocks accepted by us since he is connected
+    blocks_accepted: IndexSet<Hash>,
+    // PoW hashes of the last shares received
+    submitted_shares: LruCache<Hash, ()>,
+    // queue of the messages to write
+    sender: mpsc::Sender<String>
+}
+
+impl StratumMiner {
+    fn new(mainnet: bool, address: SocketAddr, difficulty: Difficulty, sender: mpsc::Sender<String>) -> Self {
+        Self {
+            mainnet,
+            address,
+            key: None,
+            name: String::new(),
+            difficulty,
+            shares_accepted: 0,
+            shares_rejected: 0,
+            blocks_accepted: IndexSet::new(),
+            submitted_shares: LruCache::new(NonZeroUsize::new(SUBMITTED_SHARES_CACHE_SIZE).unwrap()),
+            sender
+        }
+    }
+
+    // Queue a message to be sent to the miner
+    // It is dropped if the miner is disconnected or too slow to read its messages
+    fn send(&self, message: String) {
+        if let Err(e) = self.sender.try_send(message) {
+            debug!("Error while sending message to stratum miner {}: {}", self.address, e);
+        }
+    }
+}
+
+impl Display for StratumMiner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let address = match &self.key {
+            Some(key) => key.as_address(self.mainnet).to_string(),
+            None => "unauthorized".to_owned()
+        };
+        write!(f, "StratumMiner[ip={}, address={}, name={}, shares={}/{}, blocks={}]", self.address, address, self.name, self.shares_accepted, self.shares_rejected, self.blocks_accepted.len())
+    }
+}
+
+// Build a notification line sent to a miner
+fn build_notification(method: &str, params: Value) -> String {
+    let mut line = json!({
+        "jsonrpc": JSON_RPC_VERSION,
+        "method": method,
+        "params": params
+    }).to_string();
+    line.push('\n');
+    line
+}
+
+// TCP server using a Stratum-like protocol for mining pools
+// Each message is a JSON-RPC object on its own line
+// Jobs are derived from the block template and every share is verified against the miner difficulty
+pub struct StratumServer<S: Storage> {
+    miners: Mutex<HashMap<u64, StratumMiner>>,
+    next_miner_id: AtomicU64,
+    blockchain: Arc<Blockchain<S>>,
+    // all potential jobs sent to miners
+    // we can keep them in cache up to STABLE_LIMIT blocks
+    // so even a late miner have a chance to not be orphaned and be included in chain
+    mining_jobs: Mutex<LruCache<Hash, (BlockHeader, Difficulty)>>,
+    last_header_hash: Mutex<Option<Hash>>,
+    share_difficulty: Difficulty,
+    // used only when a new TX is received in mempool
+    last_notify: AtomicU64,
+    notify_rate_limit_ms: u64,
+    exit_sender: broadcast::Sender<()>
+}
+
+impl<S: Storage> StratumServer<S> {
+    pub async fn new(blockchain: Arc<Blockchain<S>>, config: StratumConfig) -> Result<SharedStratumServer<S>, BlockchainError> {
+        let listener = TcpListener::bind(&config.bind_address).await?;
+        info!("Stratum server listening on {}", config.bind_address);
+
+        let (exit_sender, _) = broadcast::channel(1);
+        let server = Arc::new(Self {
+            miners: Mutex::new(HashMap::new()),
+            next_miner_id: AtomicU64::new(0),
+            blockchain,
+            mining_jobs: Mutex::new(LruCache::new(NonZeroUsize::new(STABLE_LIMIT as usize).unwrap())),
+            last_header_hash: Mutex::new(None),
+            share_difficulty: config.share_difficulty,
+            last_notify: AtomicU64::new(0),
+            notify_rate_limit_ms: 500, // maximum one time every 500ms
+            exit_sender
+        });
+
+        spawn_task("stratum-listener", Arc::clone(&server).listen(listener));
+        Ok(server)
+    }
+
+    pub fn stop(&self) {
+        info!("Stopping Stratum server...");
+        if let Err(e) = self.exit_sender.send(()) {
+            debug!("Error while sending exit signal to stratum server: {}", e);
+        }
+    }
+
+    // Returns the number of authorized miners connected to the stratum server
+    pub async fn count_miners(&self) -> usize {
+        trace!("count stratum miners");
+        self.miners.lock().await.values().filter(|miner| miner.key.is_some()).count()
+    }
+
+    // Returns the list of miners connected to the stratum server
+    pub fn get_miners(&self) -> &Mutex<HashMap<u64, StratumMiner>> {
+        &self.miners
+    }
+
+    async fn listen(self: Arc<Self>, listener: TcpListener) {
+        let mut exit_receiver = self.exit_sender.subscribe();
+        loop {
+            select! {
+                _ = exit_receiver.recv() => {
+                    debug!("Exit signal received, stopping stratum listener");
+                    break;
+                },
+                res = listener.accept() => match res {
+                    Ok((stream, address)) => {
+                        debug!("New stratum connection from {}", address);
+                        spawn_task(format!("stratum-{}", address), Arc::clone(&self).handle_connection(stream, address));
+                    },
+                    Err(e) => {
+                        debug!("Error while accepting stratum connection: {}", e);
+                    }
+                }
+            }
+        }
+    }
+
+    // Read the messages of a miner until it disconnects
+    // Writes are done in their own task so a slow miner never blocks the server
+    async fn handle_connection(self: Arc<Self>, stream: TcpStream, address: SocketAddr) {
+        let id = self.next_miner_id.fetch_add(1, Ordering::SeqCst);
+        let (sender, mut receiver) = mpsc::channel::<String>(MESSAGES_QUEUE_SIZE);
+        {
+            let mut miners = self.miners.lock().await;
+            let miner = StratumMiner::new(self.blockchain.get_network().is_mainnet(), address, self.share_difficulty, sender.clone());
+            miners.insert(id, miner);
+        }
+
+        let (read, mut write) = stream.into_split();
+        spawn_task(format!("stratum-writer-{}", address), async move {
+            while let Some(message) = receiver.recv().await {
+                if let Err(e) = write.write_all(message.as_bytes()).await {
+                    debug!("Error while writing to stratum miner {}: {}", address, e);
+                    break;
+                }
+            }
+        });
+
+        let mut reader = BufReader::new(read);
+        let mut line = String::new();
+        let mut exit_receiver = self.exit_sender.subscribe();
+        loop {
+            let mut limited_reader = (&mut reader).take(MAX_MESSAGE_SIZE as u64);
+            select! {
+                _ = exit_receiver.recv() => break,
+                res = limited_reader.read_line(&mut line) => match res {
+                    Ok(0) => break,
+                    Ok(_) => {
+                        if line.len() >= MAX_MESSAGE_SIZE && !line.ends_with('\n') {
+                            debug!("Stratum miner {} sent a message too big, closing connection", address);
+                            break;
+                        }
+
+                        let message = line.trim();
+                        if !message.is_empty() && !self.handle_message(id, &sender, message).await {
+                            break;
+                        }
+                        line.clear();
+                    },
+                    Err(e) => {
+                        debug!("Error while reading from stratum miner {}: {}", address, e);
+                        break;
+                    }
+                }
+            }
+        }
+
+        let mut miners = self.miners.lock().await;
+        if let Some(miner) = miners.remove(&id) {
+            debug!("{} disconnected", miner);
+        }
+    }
+
+    // Execute the request and queue its response
+    // Returns false if the connection must be closed
+    async fn handle_message(&self, id: u64, sender: &mpsc::Sender<String>, message: &str) -> bool {
+        trace!("New message from stratum miner #{}: {}", id, message);
+        let request: StratumRequest = match serde_json::from_str(message) {
+            Ok(request) => request,
+            Err(e) => {
+                debug!("Error while decoding message from stratum miner #{}: {}", id, e);
+                let error = RpcResponseError::new(None, InternalRpcError::ParseBodyError);
+                let _ = sender.send(format!("{}\n", error.to_json())).await;
+                return false
+            }
+        };
+
+        // authorized miners and the ones changing their difficulty receive a new job
+        let (result, send_job) = match request.method.as_str() {
+            "mining.subscribe" => (Ok(json!({ "id": id })), false),
+            "mining.authorize" => (self.authorize(id, request.params).await, true),
+            "mining.suggest_difficulty" => (self.suggest_difficulty(id, request.params).await, true),
+            "mining.submit" => (self.submit_share(id, request.params).await, false),
+            _ => (Err(InternalRpcError::MethodNotFound(request.method.clone())), false)
+        };
+
+        let response = match result {
+            Ok(value) => {
+                if send_job {
+                    // response must be received before the first job
+                    let response = json!(RpcResponse::new(Cow::Borrowed(&request.id), Cow::Owned(value)));
+                    if sender.send(format!("{}\n", response)).await.is_err() {
+                        return false
+                    }
+
+                    if let Err(e) = self.send_new_job(id).await {
+                        error!("Error while sending new job to stratum miner #{}: {}", id, e);
+                    }
+                    return true
+                }
+                json!(RpcResponse::new(Cow::Borrowed(&request.id), Cow::Owned(value)))
+            },
+            Err(e) => RpcResponseError::new(request.id, e).to_json()
+        };
+
+        sender.send(format!("{}\n", response)).await.is_ok()
+    }
+
+    // params: [address, worker]
+    async fn authorize(&self, id: u64, params: Value) -> Result<Value, InternalRpcError> {
+        let params: Vec<String> = serde_json::from_value(params)?;
+        let (address, worker) = match params.as_slice() {
+            [address] => (address, String::new()),
+            [address, worker] => (address, worker.clone()),
+            _ => return Err(InternalRpcError::InvalidParams("Expected miner address and worker name"))
+        };
+
+        if worker.len() > MAX_WORKER_NAME_SIZE {
+            return Err(InternalRpcError::InvalidParams("Worker name must be less or equal to 32 chars"))
+        }
+
+        let address = Address::from_string(address).map_err(|_| StratumError::InvalidAddress)?;
+        if !address.is_normal() || address.is_mainnet() != self.blockchain.get_network().is_mainnet() {
+            return Err(StratumError::InvalidAddress.into())
+        }
+
+        let mut miners = self.miners.lock().await;
+        let miner = miners.get_mut(&id).ok_or(InternalRpcError::ClientNotFound)?;
+        if miner.key.is_some() {
+            return Err(StratumError::AlreadyAuthorized.into())
+        }
+
+        miner.key = Some(address.to_public_key());
+        miner.name = worker;
+        debug!("Stratum miner authorized: {}", miner);
+        Ok(json!(true))
+    }
+
+    // params: [difficulty]
+    // The difficulty can't be below the one configured
+    async fn suggest_difficulty(&self, id: u64, params: Value) -> Result<Value, InternalRpcError> {
+        let params: Vec<u64> = serde_json::from_value(params)?;
+        let difficulty = match params.as_slice() {
+            [difficulty] => Difficulty::from_u64(*difficulty).max(self.share_difficulty),
+            _ => return Err(InternalRpcError::InvalidParams("Expected difficulty"))
+        };
+
+        let mut miners = self.miners.lock().await;
+        let miner = miners.get_mut(&id).ok_or(InternalRpcError::ClientNotFound)?;
+        if miner.key.is_none() {
+            return Err(StratumError::Unauthorized.into())
+        }
+
+        debug!("Stratum miner {} set its difficulty to {}", miner, difficulty);
+        miner.difficulty = difficulty;
+        Ok(json!(true))
+    }
+
+    // params: [worker, job id, miner work]
+    async fn submit_share(&self, id: u64, params: Value) -> Result<Value, InternalRpcError> {
+        let params: Vec<String> = serde_json::from_value(params)?;
+        let (job_id, work) = match params.as_slice() {
+            [_, job_id, work] => (job_id, work),
+            _ => return Err(InternalRpcError::InvalidParams("Expected worker name, job id and miner work"))
+        };
+
+        let res = self.verify_share(id, job_id, work).await;
+
+        // update miner stats
+        let mut miners = self.miners.lock().await;
+        if let Some(miner) = miners.get_mut(&id) {
+            match &res {
+                Ok(Some(hash)) => {
+                    debug!("Stratum miner {} found block {}!", miner, hash);
+                    miner.shares_accepted += 1;
+                    miner.blocks_accepted.insert(hash.clone());
+                },
+                Ok(None) => {
+                    miner.shares_accepted += 1;
+                },
+                Err(e) => {
+                    debug!("Stratum miner {} sent an invalid share: {}", miner, e);
+                    miner.shares_rejected += 1;
+                }
+            }
+        }
+
+        res.map(|_| json!(true))
+    }
+
+    // Verify the share against the miner difficulty
+    // If it is also valid for the block difficulty, the block is submitted to the chain
+    // Returns the block hash if it was accepted
+    async fn verify_share(&self, id: u64, job_id: &str, work: &str) -> Result<Option<Hash>, InternalRpcError> {
+        let job_id = Hash::from_hex(job_id.to_owned())?;
+        let work = MinerWork::from_hex(work.to_owned())?;
+        if *work.get_header_work_hash() != job_id {
+            return Err(StratumError::JobNotFound.into())
+        }
+
+        let difficulty = {
+            let miners = self.miners.lock().await;
+            let miner = miners.get(&id).ok_or(InternalRpcError::ClientNotFound)?;
+            let key = miner.key.as_ref().ok_or(StratumError::Unauthorized)?;
+            if work.get_miner() != Some(key) {
+                return Err(StratumError::InvalidMiner.into())
+            }
+            miner.difficulty
+        };
+
+        let (mut header, block_difficulty) = {
+            let mining_jobs = self.mining_jobs.lock().await;
+            mining_jobs.peek(&job_id).cloned().ok_or(StratumError::JobNotFound)?
+        };
+        header.apply_miner_work(work);
+
+        // PoW hash is CPU heavy, don't block the runtime
+        let (header, pow_hash) = spawn_blocking(move || {
+            let pow_hash = header.get_pow_hash();
+            (header, pow_hash)
+        }).await.context("Error while waiting on PoW hash")?;
+        let pow_hash = pow_hash.context("Error while computing PoW hash")?;
+
+        {
+            let mut miners = self.miners.lock().await;
+            let miner = miners.get_mut(&id).ok_or(InternalRpcError::ClientNotFound)?;
+            if miner.submitted_shares.put(pow_hash.clone(), ()).is_some() {
+                return Err(StratumError::DuplicateShare.into())
+            }
+        }
+
+        if check_difficulty(&pow_hash, &block_difficulty).context("Error while checking block difficulty")? {
+            let block = self.blockchain.build_block_from_header(Immutable::Owned(header)).await.context("Error while building block from header")?;
+            let block_hash = block.hash();
+            return match self.blockchain.add_new_block(block, true, true).await {
+                Ok(_) => Ok(Some(block_hash)),
+                Err(e) => {
+                    // the share is still valid for the miner
+                    warn!("Error while adding block {} from stratum miner: {}", block_hash, e);
+                    Ok(None)
+                }
+            }
+        }
+
+        if !check_difficulty(&pow_hash, &difficulty).context("Error while checking share difficulty")? {
+            return Err(StratumError::LowDifficultyShare.into())
+        }
+
+        Ok(None)
+    }
+
+    // Build a new job from the block template and save it in cache
+    async fn build_job(&self) -> Result<(Hash, u64, Difficulty), InternalRpcError> {
+        let (header, difficulty) = {
+            let storage = self.blockchain.get_storage().read().await;
+            let header = self.blockchain.get_block_template_for_storage(&storage, DEV_PUBLIC_KEY.clone()).await.context("Error while retrieving block template")?;
+            let (difficulty, _) = self.blockchain.get_difficulty_at_tips(&*storage, header.get_tips().iter()).await.context("Error while retrieving difficulty at tips")?;
+            (header, difficulty)
+        };
+
+        let header_work_hash = header.get_work_hash();
+        let height = header.height;
+        {
+            let mut last_header_hash = self.last_header_hash.lock().await;
+            *last_header_hash = Some(header_work_hash.clone());
+            let mut mining_jobs = self.mining_jobs.lock().await;
+            mining_jobs.put(header_work_hash.clone(), (header, difficulty));
+        }
+
+        Ok((header_work_hash, height, difficulty))
+    }
+
+    // Returns the last job built, or a new one if none is available
+    async fn get_current_job(&self) -> Result<(Hash, u64, Difficulty), InternalRpcError> {
+        let last_header_hash = self.last_header_hash.lock().await.clone();
+        if let Some(hash) = last_header_hash {
+            let mining_jobs = self.mining_jobs.lock().await;
+            if let Some((header, difficulty)) = mining_jobs.peek(&hash) {
+                return Ok((hash, header.height, *difficulty))
+            }
+        }
+
+        self.build_job().await
+    }
+
+    // Build the job notification for a miner
+    // Miner key and a random extra nonce are set in the miner work
+    // params: [job id, miner work, height, topoheight, block difficulty, clean jobs]
+    fn build_job_notification(header_work_hash: &Hash, key: &PublicKey, height: u64, topoheight: u64, difficulty: &Difficulty) -> String {
+        let mut job = MinerWork::new(header_work_hash.clone(), get_current_time_in_millis());
+        job.set_miner(Cow::Borrowed(key));
+        OsRng.fill_bytes(job.get_extra_nonce());
+
+        build_notification("mining.notify", json!([header_work_hash, job.to_hex(), height, topoheight, difficulty, true]))
+    }
+
+    // Send its share difficulty and the current job to an authorized miner
+    async fn send_new_job(&self, id: u64) -> Result<(), InternalRpcError> {
+        debug!("Sending new job to stratum miner #{}", id);
+        let (header_work_hash, height, difficulty) = self.get_current_job().await?;
+        let topoheight = self.blockchain.get_topo_height();
+
+        let miners = self.miners.lock().await;
+        if let Some(miner) = miners.get(&id) {
+            if let Some(key) = &miner.key {
+                miner.send(build_notification("mining.set_difficulty", json!([miner.difficulty])));
+                miner.send(Self::build_job_notification(&header_work_hash, key, height, topoheight, &difficulty));
+            }
+        }
+        Ok(())
+    }
+
+    // check if the last notify is older than the rate limit
+    // if it's the case, we can notify miners
+    // Returns a tuple with a boolean indicating if the rate limit is reached, and the current timestamp
+    fn is_rate_limited(&self) -> (bool, TimestampMillis) {
+        let now = get_current_time_in_millis();
+        let last_notify = self.last_notify.load(Ordering::SeqCst);
+        (now - last_notify < self.notify_rate_limit_ms, now)
+    }
+
+    // notify every miners connected to the stratum server
+    pub async fn notify_new_job_rate_limited(&self) -> Result<(), InternalRpcError> {
+        let (rate_limit_reached, now) = self.is_rate_limited();
+        if rate_limit_reached {
+            debug!("Rate limit reached, no need to notify stratum miners");
+            return Ok(());
+        }
+        self.last_notify.store(now, Ordering::SeqCst);
+
+        self.notify_new_job().await
+    }
+
+    // notify every authorized miners connected to the stratum server
+    // messages are queued so nobody wait on other
+    pub async fn notify_new_job(&self) -> Result<(), InternalRpcError> {
+        trace!("notify new stratum job");
+        // Check that there is at least one miner authorized
+        // otherwise, no need to build a new job
+        if self.count_miners().await == 0 {
+            debug!("No stratum miners connected, no need to notify them");
+            return Ok(());
+        }
+
+        debug!("Notify all stratum miners for a new job");
+        let (header_work_hash, height, difficulty) = self.build_job().await?;
+        let topoheight = self.blockchain.get_topo_height();
+
+        let miners = self.miners.lock().await;
+        for miner in miners.values() {
+            if let Some(key) = &miner.key {
+                debug!("Notifying {} for new job", miner);
+                miner.send(Self::build_job_notification(&header_work_hash, key, height, topoheight, &difficulty));
+            }
+        }
+
+        Ok(())
+    }
+}
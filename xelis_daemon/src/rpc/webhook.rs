@@ -0,0 +1,101 @@
+use std::time::Duration;
+use anyhow::Context;
+use log::{debug, error, trace, warn};
+use reqwest::{Client, Url};
+use serde_json::Value;
+use tokio::{
+    sync::mpsc::{self, error::TrySendError},
+    time::sleep
+};
+use xelis_common::{
+    api::daemon::NotifyEvent,
+    utils::spawn_task
+};
+use crate::config::{
+    WEBHOOK_MAX_ATTEMPTS,
+    WEBHOOK_QUEUE_SIZE,
+    WEBHOOK_REQUEST_TIMEOUT_MILLIS,
+    WEBHOOK_RETRY_BASE_DELAY_MILLIS
+};
+
+// Events pushed to the webhooks
+pub const WEBHOOK_EVENTS: [NotifyEvent; 4] = [
+    NotifyEvent::NewBlock,
+    NotifyEvent::BlockOrdered,
+    NotifyEvent::TransactionExecuted,
+    NotifyEvent::TransactionConflictDetected
+];
+
+// Push the events to HTTP webhooks using POST requests
+// Each webhook has its own queue and task, so events are delivered in order
+// and a slow endpoint doesn't delay the others
+pub struct WebhookNotifier {
+    webhooks: Vec<(Url, mpsc::Sender<Value>)>
+}
+
+impl WebhookNotifier {
+    pub fn new(urls: Vec<String>) -> Result<Self, anyhow::Error> {
+        let client = Client::builder()
+            .timeout(Duration::from_millis(WEBHOOK_REQUEST_TIMEOUT_MILLIS))
+            .build()?;
+
+        let mut webhooks = Vec::with_capacity(urls.len());
+        for url in urls {
+            let url = Url::parse(&url).context(format!("Invalid webhook URL '{}'", url))?;
+            let (sender, receiver) = mpsc::channel(WEBHOOK_QUEUE_SIZE);
+            spawn_task(format!("webhook-{}", url), Self::delivery_loop(client.clone(), url.clone(), receiver));
+            webhooks.push((url, sender));
+        }
+
+        Ok(Self {
+            webhooks
+        })
+    }
+
+    pub fn is_event_pushed(&self, event: &NotifyEvent) -> bool {
+        WEBHOOK_EVENTS.contains(event)
+    }
+
+    // Queue the event for each webhook
+    // It is dropped for a webhook whose queue is full
+    pub fn notify(&self, payload: &Value) {
+        for (url, sender) in self.webhooks.iter() {
+            match sender.try_send(payload.clone()) {
+                Ok(()) => {},
+                Err(TrySendError::Full(_)) => warn!("Webhook {} queue is full, dropping event", url),
+                Err(TrySendError::Closed(_)) => error!("Webhook {} task is stopped, dropping event", url)
+            }
+        }
+    }
+
+    // Deliver the events queued until the notifier is dropped
+    // A failed request is retried with an exponential backoff
+    async fn delivery_loop(client: Client, url: Url, mut receiver: mpsc::Receiver<Value>) {
+        while let Some(payload) = receiver.recv().await {
+            let mut delay = WEBHOOK_RETRY_BASE_DELAY_MILLIS;
+            for attempt in 1..=WEBHOOK_MAX_ATTEMPTS {
+                let res = client.post(url.clone())
+                    .json(&payload)
+                    .send().await
+                    .and_then(|response| response.error_for_status());
+
+                match res {
+                    Ok(_) => {
+                        trace!("Event delivered to webhook {}", url);
+                        break;
+                    },
+                    Err(e) if attempt == WEBHOOK_MAX_ATTEMPTS => {
+                        error!("Error while delivering event to webhook {} after {} attempts, dropping it: {}", url, attempt, e);
+                    },
+                    Err(e) => {
+                        debug!("Error while delivering event to webhook {} (attempt {}), retrying in {}ms: {}", url, attempt, delay, e);
+                        sleep(Duration::from_millis(delay)).await;
+                        delay *= 2;
+                    }
+                }
+            }
+        }
+
+        debug!("Webhook {} task stopped", url);
+    }
+}
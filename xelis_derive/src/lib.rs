@@ -0,0 +1,311 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input,
+    parse_quote,
+    spanned::Spanned,
+    Attribute,
+    Data,
+    DeriveInput,
+    Error,
+    Fields,
+    GenericParam,
+    Generics,
+    Ident,
+    LitInt,
+    Member,
+    Type
+};
+
+// Derive the Serializer trait of xelis_common
+// Fields are written in their declaration order using their own Serializer implementation,
+// so length prefixes and optional values follow the existing conventions
+// Enums are prefixed by their variant id as a u8, which is its index by default
+//
+// Attributes:
+// - #[serializer(id = 2)] on a variant to set its id
+// - #[serializer(skip)] on a field to not write it, it is read as its default value
+// - #[serializer(optional_string)] on an Option<String> field to write it as a string of length 0 if not set
+#[proc_macro_derive(Serializer, attributes(serializer))]
+pub fn derive_serializer(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+// xelis_common declares itself as an extern crate so this path also works inside it
+fn serializer_path() -> TokenStream2 {
+    quote!(::xelis_common::serializer)
+}
+
+#[derive(Default)]
+struct FieldOptions {
+    skip: bool,
+    optional_string: bool
+}
+
+fn parse_field_options(attrs: &[Attribute]) -> syn::Result<FieldOptions> {
+    let mut options = FieldOptions::default();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("serializer")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip") {
+                options.skip = true;
+                Ok(())
+            } else if meta.path.is_ident("optional_string") {
+                options.optional_string = true;
+                Ok(())
+            } else {
+                Err(meta.error("unknown serializer field attribute"))
+            }
+        })?;
+
+        if options.skip && options.optional_string {
+            return Err(Error::new(attr.span(), "a skipped field can't be an optional string"));
+        }
+    }
+
+    Ok(options)
+}
+
+fn parse_variant_id(attrs: &[Attribute]) -> syn::Result<Option<u8>> {
+    let mut id = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("serializer")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("id") {
+                let value: LitInt = meta.value()?.parse()?;
+                id = Some(value.base10_parse::<u8>()?);
+                Ok(())
+            } else {
+                Err(meta.error("unknown serializer variant attribute"))
+            }
+        })?;
+    }
+
+    Ok(id)
+}
+
+// Field to serialize, accessed through an expression of type &T
+struct FieldCode {
+    options: FieldOptions,
+    ty: Type,
+    access: TokenStream2
+}
+
+impl FieldCode {
+    fn write(&self) -> TokenStream2 {
+        let serializer = serializer_path();
+        let access = &self.access;
+        if self.options.skip {
+            quote!()
+        } else if self.options.optional_string {
+            quote!(writer.write_optional_string(#access);)
+        } else {
+            quote!(#serializer::Serializer::write(#access, writer);)
+        }
+    }
+
+    fn read(&self) -> TokenStream2 {
+        let serializer = serializer_path();
+        let ty = &self.ty;
+        if self.options.skip {
+            quote!(::core::default::Default::default())
+        } else if self.options.optional_string {
+            quote!(reader.read_optional_string()?)
+        } else {
+            quote!(<#ty as #serializer::Serializer>::read(reader)?)
+        }
+    }
+
+    fn size(&self) -> TokenStream2 {
+        let serializer = serializer_path();
+        let access = &self.access;
+        if self.options.skip {
+            quote!(0)
+        } else if self.options.optional_string {
+            // 1 for the string length as byte
+            quote!(1 + (#access).as_ref().map(|value| value.len()).unwrap_or(0))
+        } else {
+            quote!(#serializer::Serializer::size(#access))
+        }
+    }
+}
+
+// Build the fields of a struct or a variant
+// Each field is accessed using `access` from its member
+fn collect_fields(fields: &Fields, access: impl Fn(&Member, usize) -> TokenStream2) -> syn::Result<Vec<(Member, FieldCode)>> {
+    fields.iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let member = match &field.ident {
+                Some(ident) => Member::Named(ident.clone()),
+                None => Member::Unnamed(i.into())
+            };
+            let code = FieldCode {
+                options: parse_field_options(&field.attrs)?,
+                ty: field.ty.clone(),
+                access: access(&member, i)
+            };
+
+            Ok((member, code))
+        })
+        .collect()
+}
+
+// Constructor of the struct or variant from the fields read in order
+fn construct(path: TokenStream2, fields: &Fields, codes: &[(Member, FieldCode)]) -> TokenStream2 {
+    match fields {
+        Fields::Named(_) => {
+            let values = codes.iter().map(|(member, code)| {
+                let read = code.read();
+                quote!(#member: #read)
+            });
+            quote!(#path { #(#values),* })
+        },
+        Fields::Unnamed(_) => {
+            let values = codes.iter().map(|(_, code)| code.read());
+            quote!(#path(#(#values),*))
+        },
+        Fields::Unit => path
+    }
+}
+
+fn sum_sizes(codes: &[(Member, FieldCode)]) -> TokenStream2 {
+    let sizes = codes.iter().map(|(_, code)| code.size());
+    quote!(0 #(+ #sizes)*)
+}
+
+fn binding(i: usize) -> Ident {
+    format_ident!("__field_{}", i)
+}
+
+// Pattern matching a variant, skipped fields are not bound
+fn variant_pattern(name: &Ident, fields: &Fields, codes: &[(Member, FieldCode)]) -> TokenStream2 {
+    let bindings = codes.iter().enumerate().map(|(i, (member, code))| {
+        let value = if code.options.skip {
+            quote!(_)
+        } else {
+            let binding = binding(i);
+            quote!(#binding)
+        };
+
+        match fields {
+            Fields::Named(_) => quote!(#member: #value),
+            _ => value
+        }
+    });
+
+    match fields {
+        Fields::Named(_) => quote!(Self::#name { #(#bindings),* }),
+        Fields::Unnamed(_) => quote!(Self::#name(#(#bindings),*)),
+        Fields::Unit => quote!(Self::#name)
+    }
+}
+
+fn add_bounds(mut generics: Generics) -> Generics {
+    let serializer = serializer_path();
+    for param in generics.params.iter_mut() {
+        if let GenericParam::Type(param) = param {
+            param.bounds.push(parse_quote!(#serializer::Serializer));
+        }
+    }
+    generics
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let serializer = serializer_path();
+    let (write, read, size) = match &input.data {
+        Data::Struct(data) => {
+            let codes = collect_fields(&data.fields, |member, _| quote!(&self.#member))?;
+            let writes = codes.iter().map(|(_, code)| code.write());
+            let read = construct(quote!(Self), &data.fields, &codes);
+            let size = sum_sizes(&codes);
+
+            (quote!(#(#writes)*), quote!(Ok(#read)), size)
+        },
+        Data::Enum(data) => {
+            if data.variants.is_empty() {
+                return Err(Error::new(input.ident.span(), "Serializer can't be derived for an empty enum"));
+            }
+
+            let mut ids = Vec::with_capacity(data.variants.len());
+            let mut write_arms = Vec::with_capacity(data.variants.len());
+            let mut read_arms = Vec::with_capacity(data.variants.len());
+            let mut size_arms = Vec::with_capacity(data.variants.len());
+            for (i, variant) in data.variants.iter().enumerate() {
+                let id = match parse_variant_id(&variant.attrs)? {
+                    Some(id) => id,
+                    None => u8::try_from(i).map_err(|_| Error::new(variant.span(), "too many variants for a u8 id"))?
+                };
+
+                if ids.contains(&id) {
+                    return Err(Error::new(variant.span(), format!("variant id {} is already used", id)));
+                }
+                ids.push(id);
+
+                let name = &variant.ident;
+                let codes = collect_fields(&variant.fields, |_, i| {
+                    let binding = binding(i);
+                    quote!(#binding)
+                })?;
+                let pattern = variant_pattern(name, &variant.fields, &codes);
+                let writes = codes.iter().map(|(_, code)| code.write());
+                let read = construct(quote!(Self::#name), &variant.fields, &codes);
+                let size = sum_sizes(&codes);
+
+                write_arms.push(quote! {
+                    #pattern => {
+                        writer.write_u8(#id);
+                        #(#writes)*
+                    }
+                });
+                read_arms.push(quote!(#id => #read));
+                size_arms.push(quote!(#pattern => #size));
+            }
+
+            let write = quote! {
+                match self {
+                    #(#write_arms),*
+                }
+            };
+            let read = quote! {
+                Ok(match reader.read_u8()? {
+                    #(#read_arms,)*
+                    _ => return Err(#serializer::ReaderError::InvalidValue)
+                })
+            };
+            // 1 for the variant id
+            let size = quote! {
+                1 + match self {
+                    #(#size_arms),*
+                }
+            };
+
+            (write, read, size)
+        },
+        Data::Union(_) => return Err(Error::new(input.ident.span(), "Serializer can't be derived for a union"))
+    };
+
+    let name = &input.ident;
+    let generics = add_bounds(input.generics.clone());
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #serializer::Serializer for #name #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn write(&self, writer: &mut #serializer::Writer) {
+                #write
+            }
+
+            #[allow(unused_variables)]
+            fn read(reader: &mut #serializer::Reader) -> ::core::result::Result<Self, #serializer::ReaderError> {
+                #read
+            }
+
+            fn size(&self) -> usize {
+                #size
+            }
+        }
+    })
+}
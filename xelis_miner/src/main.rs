@@ -146,13 +146,15 @@ enum ThreadNotification<'a> {
 pub enum SocketMessage {
     NewJob(GetMinerWorkResult),
     BlockAccepted,
-    BlockRejected(String)
+    BlockRejected(String),
+    ShareAccepted
 }
 
 static WEBSOCKET_CONNECTED: AtomicBool = AtomicBool::new(false);
 static CURRENT_TOPO_HEIGHT: AtomicU64 = AtomicU64::new(0);
 static BLOCKS_FOUND: AtomicUsize = AtomicUsize::new(0);
 static BLOCKS_REJECTED: AtomicUsize = AtomicUsize::new(0);
+static SHARES_ACCEPTED: AtomicUsize = AtomicUsize::new(0);
 static HASHRATE_COUNTER: AtomicUsize = AtomicUsize::new(0);
 
 lazy_static! {
@@ -355,6 +357,10 @@ async fn handle_websocket_message(message: Result<Message, TungsteniteError>, jo
                 SocketMessage::BlockRejected(err) => {
                     BLOCKS_REJECTED.fetch_add(1, Ordering::SeqCst);
                     error!("Block submitted has been rejected by network: {}", err);
+                },
+                SocketMessage::ShareAccepted => {
+                    SHARES_ACCEPTED.fetch_add(1, Ordering::SeqCst);
+                    debug!("Share submitted has been accepted by daemon");
                 }
             }
         },
@@ -443,7 +449,7 @@ fn start_thread(id: u16, mut job_receiver: broadcast::Receiver<ThreadNotificatio
 
                     // compute the reference hash for easier finding of the block
                     let block_hash = job.hash();
-                    info!("Thread #{}: block {} found at height {} with difficulty {}", id, block_hash, height, format_difficulty(difficulty_from_hash(&hash)));
+                    info!("Thread #{}: share {} found at height {} with difficulty {}", id, block_hash, height, format_difficulty(difficulty_from_hash(&hash)));
                     if let Err(_) = block_sender.blocking_send(job) {
                         error!("Mining Thread #{}: error while sending block found with hash {}", id, block_hash);
                         continue 'main;
@@ -477,6 +483,11 @@ async fn run_prompt(prompt: ShareablePrompt) -> Result<()> {
             prompt.colorize_str(Color::Yellow, "Rejected"),
             prompt.colorize_string(Color::Green, &format!("{}", BLOCKS_REJECTED.load(Ordering::SeqCst))),
         );
+        let shares_accepted = format!(
+            "{}: {}",
+            prompt.colorize_str(Color::Yellow, "Shares"),
+            prompt.colorize_string(Color::Green, &format!("{}", SHARES_ACCEPTED.load(Ordering::SeqCst))),
+        );
         let status = if WEBSOCKET_CONNECTED.load(Ordering::SeqCst) {
             prompt.colorize_str(Color::Green, "Online")
         } else {
@@ -494,11 +505,12 @@ async fn run_prompt(prompt: ShareablePrompt) -> Result<()> {
 
         Ok(
             format!(
-                "{} | {} | {} | {} | {} | {} {} ",
+                "{} | {} | {} | {} | {} | {} | {} {} ",
                 prompt.colorize_str(Color::Blue, "XELIS Miner"),
                 topoheight_str,
                 blocks_found,
                 blocks_rejected,
+                shares_accepted,
                 hashrate,
                 status,
                 prompt.colorize_str(Color::BrightBlack, ">>")
@@ -0,0 +1,161 @@
+use std::collections::{HashSet, VecDeque};
+use indexmap::IndexMap;
+use log::debug;
+use xelis_common::{
+    api::wallet::{
+        AlertKind,
+        AlertRule,
+        AlertRuleEntry,
+        WalletAlert
+    },
+    crypto::Hash,
+    serializer::Serializer,
+    time::get_current_time_in_millis
+};
+use crate::config::MAX_ALERTS_HISTORY;
+
+// Alert rules and webhooks configured by the user
+// This is persisted in the wallet storage
+#[derive(Debug, Clone, Default, Serializer)]
+pub struct AlertsSettings {
+    // Id of the next rule added
+    next_id: u64,
+    rules: IndexMap<u64, AlertRule>,
+    // URLs receiving each alert triggered in a POST request
+    webhooks: Vec<String>
+}
+
+// Evaluate the alert rules against the changes detected during the sync
+// and keep the latest alerts triggered in memory
+pub struct AlertManager {
+    settings: AlertsSettings,
+    // BalanceBelow rules currently under their threshold
+    // Used to alert only once when the balance goes below it
+    below_threshold: HashSet<u64>,
+    // Latest alerts triggered, oldest first
+    alerts: VecDeque<WalletAlert>,
+    next_alert_id: u64
+}
+
+impl AlertManager {
+    pub fn new(settings: AlertsSettings) -> Self {
+        Self {
+            settings,
+            below_threshold: HashSet::new(),
+            alerts: VecDeque::new(),
+            next_alert_id: 0
+        }
+    }
+
+    pub fn get_settings(&self) -> &AlertsSettings {
+        &self.settings
+    }
+
+    // Add a new rule and returns its id
+    pub fn add_rule(&mut self, rule: AlertRule) -> u64 {
+        let id = self.settings.next_id;
+        self.settings.next_id += 1;
+        self.settings.rules.insert(id, rule);
+        id
+    }
+
+    // Returns true if the rule was found
+    pub fn remove_rule(&mut self, id: u64) -> bool {
+        self.below_threshold.remove(&id);
+        self.settings.rules.shift_remove(&id).is_some()
+    }
+
+    pub fn get_rules(&self) -> Vec<AlertRuleEntry> {
+        self.settings.rules.iter()
+            .map(|(id, rule)| AlertRuleEntry { id: *id, rule: rule.clone() })
+            .collect()
+    }
+
+    pub fn get_webhooks(&self) -> &Vec<String> {
+        &self.settings.webhooks
+    }
+
+    pub fn set_webhooks(&mut self, webhooks: Vec<String>) {
+        self.settings.webhooks = webhooks;
+    }
+
+    pub fn get_alerts(&self) -> &VecDeque<WalletAlert> {
+        &self.alerts
+    }
+
+    // Store the alert in the history
+    fn trigger(&mut self, rule_id: u64, kind: AlertKind) -> WalletAlert {
+        debug!("Alert rule {} triggered: {:?}", rule_id, kind);
+        let alert = WalletAlert {
+            id: self.next_alert_id,
+            rule_id,
+            timestamp: get_current_time_in_millis(),
+            alert: kind
+        };
+        self.next_alert_id += 1;
+
+        if self.alerts.len() >= MAX_ALERTS_HISTORY {
+            self.alerts.pop_front();
+        }
+        self.alerts.push_back(alert.clone());
+
+        alert
+    }
+
+    // A new balance has been synced for the asset
+    pub fn on_balance_changed(&mut self, asset: &Hash, balance: u64) -> Vec<WalletAlert> {
+        let mut triggered = Vec::new();
+        for (id, rule) in self.settings.rules.iter() {
+            if let AlertRule::BalanceBelow { asset: rule_asset, amount } = rule {
+                if rule_asset != asset {
+                    continue;
+                }
+
+                if balance >= *amount {
+                    self.below_threshold.remove(id);
+                } else if self.below_threshold.insert(*id) {
+                    triggered.push((*id, AlertKind::BalanceBelow { asset: asset.clone(), threshold: *amount, balance }));
+                }
+            }
+        }
+
+        triggered.into_iter()
+            .map(|(id, kind)| self.trigger(id, kind))
+            .collect()
+    }
+
+    // A transfer has been received in the transaction
+    pub fn on_incoming_transfer(&mut self, hash: &Hash, asset: &Hash, amount: u64) -> Vec<WalletAlert> {
+        let triggered: Vec<_> = self.settings.rules.iter()
+            .filter_map(|(id, rule)| match rule {
+                AlertRule::IncomingAbove { asset: rule_asset, amount: threshold } if rule_asset == asset && amount > *threshold => {
+                    Some((*id, AlertKind::IncomingAbove { asset: asset.clone(), threshold: *threshold, amount, hash: hash.clone() }))
+                },
+                _ => None
+            })
+            .collect();
+
+        triggered.into_iter()
+            .map(|(id, kind)| self.trigger(id, kind))
+            .collect()
+    }
+
+    // A new nonce has been synced
+    // expected is the next nonce the wallet would use to build a transaction
+    // The nonce is unexpected if it is above it (someone else used our keys)
+    // or below the previous one stored
+    pub fn on_nonce_changed(&mut self, previous: u64, expected: u64, nonce: u64) -> Vec<WalletAlert> {
+        if nonce <= expected && nonce >= previous {
+            return Vec::new()
+        }
+
+        let triggered: Vec<u64> = self.settings.rules.iter()
+            .filter(|(_, rule)| **rule == AlertRule::UnexpectedNonce)
+            .map(|(id, _)| *id)
+            .collect();
+
+        triggered.into_iter()
+            .map(|id| self.trigger(id, AlertKind::UnexpectedNonce { expected, nonce }))
+            .collect()
+    }
+}
@@ -0,0 +1,77 @@
+use std::fmt::Write;
+
+use actix_web::{
+    web::Data,
+    HttpResponse,
+    Responder
+};
+use async_trait::async_trait;
+use xelis_common::rpc_server::{
+    websocket::WebSocketServer,
+    RPCHandler,
+    RPCServerHandler
+};
+use super::{
+    xswd::XSWDWebSocketHandler,
+    WalletRpcServer,
+    XSWDNodeMethodHandler,
+    XSWDPermissionHandler
+};
+
+// All metrics of the wallet start with it
+const METRICS_PREFIX: &str = "xelis_wallet";
+
+#[async_trait]
+pub trait WalletMetricsHandler {
+    // Write the gauges of the wallet state using `write_gauge`
+    async fn write_metrics(&self, out: &mut String);
+}
+
+// Write a gauge in the Prometheus text format
+pub fn write_gauge(out: &mut String, name: &str, help: &str, value: u64) {
+    let _ = writeln!(out, "# HELP {}_{} {}", METRICS_PREFIX, name, help);
+    let _ = writeln!(out, "# TYPE {}_{} gauge", METRICS_PREFIX, name);
+    let _ = writeln!(out, "{}_{} {}", METRICS_PREFIX, name, value);
+}
+
+// Wallet state followed by the latencies of the RPC methods
+async fn write_all_metrics<W>(handler: &RPCHandler<W>, out: &mut String)
+where
+    W: Clone + Send + Sync + WalletMetricsHandler + 'static
+{
+    handler.get_data().write_metrics(out).await;
+    if let Some(metrics) = handler.get_metrics() {
+        metrics.write_prometheus(out, METRICS_PREFIX);
+    }
+}
+
+fn metrics_response(out: String) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4")
+        .body(out)
+}
+
+// GET /metrics on the RPC server
+pub async fn rpc_server_metrics<W>(server: Data<WalletRpcServer<W>>) -> impl Responder
+where
+    W: Clone + Send + Sync + WalletMetricsHandler + 'static
+{
+    let mut out = String::new();
+    write_all_metrics(server.get_rpc_handler(), &mut out).await;
+    metrics_response(out)
+}
+
+// GET /metrics on the XSWD server, with the applications connected
+pub async fn xswd_metrics<W>(server: Data<WebSocketServer<XSWDWebSocketHandler<W>>>) -> impl Responder
+where
+    W: Clone + Send + Sync + XSWDPermissionHandler + XSWDNodeMethodHandler + WalletMetricsHandler + 'static
+{
+    let handler = server.get_handler();
+    let mut out = String::new();
+    write_all_metrics(handler.get_rpc_handler(), &mut out).await;
+
+    let applications = handler.get_applications().read().await.len();
+    write_gauge(&mut out, "xswd_applications", "Applications connected through XSWD", applications as u64);
+
+    metrics_response(out)
+}
@@ -1,3 +1,5 @@
+mod metrics;
+mod rest;
 mod rpc;
 mod rpc_server;
 mod xswd;
@@ -6,6 +8,7 @@ use serde::ser::Serialize;
 use xelis_common::{api::wallet::NotifyEvent, rpc_server::WebSocketServerHandler};
 
 pub use self::{
+    metrics::{WalletMetricsHandler, write_gauge},
     rpc_server::{WalletRpcServer, WalletRpcServerShared, AuthConfig},
     xswd::{
         XSWD,
@@ -0,0 +1,86 @@
+use actix_web::{
+    http::StatusCode,
+    web::{Data, Json, Query},
+    HttpResponse,
+    Responder,
+    ResponseError
+};
+use serde_json::{json, Value};
+use thiserror::Error;
+use xelis_common::{
+    api::wallet::{
+        BuildTransactionParams,
+        GetBalanceParams,
+        ListTransactionsParams,
+        TransferParams
+    },
+    config::XELIS_ASSET,
+    rpc_server::{InternalRpcError, RPCServerHandler},
+    transaction::builder::{TransactionTypeBuilder, TransferBuilder}
+};
+use super::WalletRpcServer;
+
+// REST routes are a facade over the JSON-RPC methods
+// Only the result is returned, errors have the same code and message
+#[derive(Debug, Error)]
+#[error(transparent)]
+pub struct RestError(#[from] InternalRpcError);
+
+impl ResponseError for RestError {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status_code()).json(json!({
+            "code": self.0.get_code(),
+            "message": self.0.to_string()
+        }))
+    }
+}
+
+async fn call<W>(server: &WalletRpcServer<W>, method: &str, params: Value) -> Result<HttpResponse, RestError>
+where
+    W: Clone + Send + Sync + 'static
+{
+    let result = server.get_rpc_handler().call_method(method, params).await?;
+    Ok(HttpResponse::Ok().json(result))
+}
+
+// GET /balance?asset=<hash>
+pub async fn balance<W>(server: Data<WalletRpcServer<W>>, query: Query<GetBalanceParams>) -> Result<impl Responder, RestError>
+where
+    W: Clone + Send + Sync + 'static
+{
+    call(&server, "get_balance", json!(query.into_inner())).await
+}
+
+// GET /transactions?min_topoheight=<u64>&max_topoheight=<u64>&accept_incoming=<bool>...
+pub async fn transactions<W>(server: Data<WalletRpcServer<W>>, query: Query<ListTransactionsParams>) -> Result<impl Responder, RestError>
+where
+    W: Clone + Send + Sync + 'static
+{
+    call(&server, "list_transactions", json!(query.into_inner())).await
+}
+
+// POST /transfer
+pub async fn transfer<W>(server: Data<WalletRpcServer<W>>, body: Json<TransferParams>) -> Result<impl Responder, RestError>
+where
+    W: Clone + Send + Sync + 'static
+{
+    let params = body.into_inner();
+    let transfer = TransferBuilder {
+        destination: params.destination,
+        amount: params.amount,
+        asset: params.asset.unwrap_or(XELIS_ASSET),
+        extra_data: params.extra_data,
+        lock: None
+    };
+
+    call(&server, "build_transaction", json!(BuildTransactionParams {
+        tx_type: TransactionTypeBuilder::Transfers(vec![transfer]),
+        fee: params.fee,
+        broadcast: true,
+        tx_as_hex: false
+    })).await
+}
@@ -3,21 +3,56 @@ use anyhow::Context as AnyContext;
 use xelis_common::{
     api::{
         wallet::{
+            AddAlertRuleParams,
+            AddScheduledPaymentParams,
+            BackupToFileParams,
+            BalanceProofResult,
+            BuildOfflineTransactionParams,
             BuildTransactionParams,
+            BuildUnsignedTransactionParams,
+            BumpFeeParams,
+            ContactEntry,
+            CreateBalanceProofParams,
+            CreatePaymentRequestParams,
             DeleteParams,
             EstimateFeesParams,
+            ExportFormat,
+            ExportHistoryParams,
+            FinalizeUnsignedTransactionParams,
             GetAddressParams,
             GetAssetPrecisionParams,
             GetBalanceParams,
+            GetContactParams,
             GetMatchingKeysParams,
+            GetTransactionLabelParams,
             GetTransactionParams,
             GetValueFromKeyParams,
             HasKeyParams,
             ListTransactionsParams,
+            ParsePaymentRequestParams,
+            PayRequestParams,
+            PaymentRequestEntry,
             QueryDBParams,
+            RemoveAlertRuleParams,
+            RemoveContactParams,
+            RemoveScheduledPaymentParams,
+            RemoveTransactionLabelParams,
             RescanParams,
+            SearchTransactionsParams,
+            SelectAccountParams,
+            SetAlertWebhooksParams,
+            SetTransactionLabelParams,
+            SetWebhooksParams,
+            SignMessageParams,
+            SignOfflineTransactionParams,
+            SignUnsignedTransactionParams,
             StoreParams,
+            SubmitTransactionParams,
             TransactionResponse,
+            UnlockWalletParams,
+            UnsignedTransactionResponse,
+            VerifyBalanceProofParams,
+            VerifySignedMessageParams,
             SetOnlineModeParams,
         },
         SplitAddressParams,
@@ -28,7 +63,7 @@ use xelis_common::{
     async_handler,
     config::{VERSION, XELIS_ASSET},
     context::Context,
-    crypto::Hashable,
+    crypto::{proofs::BalanceProof, Hashable, PaymentRequest},
     rpc_server::{
         parse_params,
         websocket::WebSocketSessionShared,
@@ -36,12 +71,16 @@ use xelis_common::{
         RPCHandler
     },
     serializer::Serializer,
-    transaction::builder::FeeBuilder
+    transaction::{
+        builder::{FeeBuilder, TransactionTypeBuilder, UnsignedTransaction},
+        Transaction
+    }
 };
 use serde_json::{Value, json};
 use crate::{
     wallet::Wallet,
-    error::WalletError
+    error::WalletError,
+    export::{self, HttpPriceProvider, PriceProvider}
 };
 use super::xswd::XSWDWebSocketHandler;
 use log::{info, warn};
@@ -60,15 +99,74 @@ pub fn register_methods(handler: &mut RPCHandler<Arc<Wallet>>) {
     handler.register_method("has_balance", async_handler!(has_balance));
     handler.register_method("get_tracked_assets", async_handler!(get_tracked_assets));
     handler.register_method("get_asset_precision", async_handler!(get_asset_precision));
+    handler.register_method("get_assets", async_handler!(get_assets));
     handler.register_method("get_transaction", async_handler!(get_transaction));
     handler.register_method("build_transaction", async_handler!(build_transaction));
+    handler.register_method("build_unsigned_transaction", async_handler!(build_unsigned_transaction));
+    handler.register_method("sign_unsigned_transaction", async_handler!(sign_unsigned_transaction));
+    handler.register_method("finalize_unsigned_transaction", async_handler!(finalize_unsigned_transaction));
+    handler.register_method("build_offline_transaction", async_handler!(build_offline_transaction));
+    handler.register_method("sign_offline_transaction", async_handler!(sign_offline_transaction));
+    handler.register_method("submit_transaction", async_handler!(submit_transaction));
     handler.register_method("list_transactions", async_handler!(list_transactions));
+    handler.register_method("export_history", async_handler!(export_history));
     handler.register_method("is_online", async_handler!(is_online));
     handler.register_method("set_online_mode", async_handler!(set_online_mode));
     handler.register_method("set_offline_mode", async_handler!(set_offline_mode));
     handler.register_method("sign_data", async_handler!(sign_data));
+    handler.register_method("sign_message", async_handler!(sign_message));
+    handler.register_method("verify_signed_message", async_handler!(verify_signed_message));
+    handler.register_method("create_balance_proof", async_handler!(create_balance_proof));
+    handler.register_method("verify_balance_proof", async_handler!(verify_balance_proof));
+    handler.register_method("create_payment_request", async_handler!(create_payment_request));
+    handler.register_method("parse_payment_request", async_handler!(parse_payment_request));
+    handler.register_method("pay_request", async_handler!(pay_request));
+    handler.register_method("set_transaction_label", async_handler!(set_transaction_label));
+    handler.register_method("get_transaction_label", async_handler!(get_transaction_label));
+    handler.register_method("remove_transaction_label", async_handler!(remove_transaction_label));
+    handler.register_method("search_transactions", async_handler!(search_transactions));
+    handler.register_method("resync_nonce", async_handler!(resync_nonce));
+    handler.register_method("get_pending_transactions", async_handler!(get_pending_transactions));
+    handler.register_method("get_spendable_balance", async_handler!(get_spendable_balance));
+    handler.register_method("bump_fee", async_handler!(bump_fee));
     handler.register_method("estimate_fees", async_handler!(estimate_fees));
 
+    // Accounts derived from the seed, all other methods use the account selected
+    handler.register_method("get_accounts", async_handler!(get_accounts));
+    handler.register_method("get_selected_account", async_handler!(get_selected_account));
+    handler.register_method("add_account", async_handler!(add_account));
+    handler.register_method("select_account", async_handler!(select_account));
+
+    // Keys wiped from memory until the password is provided again
+    handler.register_method("is_wallet_locked", async_handler!(is_wallet_locked));
+    handler.register_method("lock_wallet", async_handler!(lock_wallet));
+    handler.register_method("unlock_wallet", async_handler!(unlock_wallet));
+    handler.register_method("backup_to_file", async_handler!(backup_to_file));
+
+    // Address book shared by all the accounts
+    handler.register_method("get_contacts", async_handler!(get_contacts));
+    handler.register_method("get_contact", async_handler!(get_contact));
+    handler.register_method("add_contact", async_handler!(add_contact));
+    handler.register_method("update_contact", async_handler!(update_contact));
+    handler.register_method("remove_contact", async_handler!(remove_contact));
+
+    // Alerts evaluated by the wallet during its sync
+    handler.register_method("get_alerts", async_handler!(get_alerts));
+    handler.register_method("get_alert_rules", async_handler!(get_alert_rules));
+    handler.register_method("add_alert_rule", async_handler!(add_alert_rule));
+    handler.register_method("remove_alert_rule", async_handler!(remove_alert_rule));
+    handler.register_method("get_alert_webhooks", async_handler!(get_alert_webhooks));
+    handler.register_method("set_alert_webhooks", async_handler!(set_alert_webhooks));
+
+    // Signed notifications of the new transactions and balance changes
+    handler.register_method("get_webhooks", async_handler!(get_webhooks));
+    handler.register_method("set_webhooks", async_handler!(set_webhooks));
+
+    // Payments of the account selected submitted by the wallet once due
+    handler.register_method("get_scheduled_payments", async_handler!(get_scheduled_payments));
+    handler.register_method("add_scheduled_payment", async_handler!(add_scheduled_payment));
+    handler.register_method("remove_scheduled_payment", async_handler!(remove_scheduled_payment));
+
     // These functions allow to have an encrypted DB directly in the wallet storage
     // You can retrieve keys, values, have differents trees, and store values
     // It is restricted in XSWD context (each app access to their own trees), and open to everything in RPC
@@ -174,6 +272,15 @@ async fn get_balance(context: &Context, body: Value) -> Result<Value, InternalRp
     Ok(json!(balance))
 }
 
+// Balance of an asset minus the amounts spent by the pending transactions
+async fn get_spendable_balance(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: GetBalanceParams = parse_params(body)?;
+    let asset = params.asset.unwrap_or(XELIS_ASSET);
+    let wallet: &Arc<Wallet> = context.get()?;
+    let balance = wallet.get_spendable_balance(&asset).await?;
+    Ok(json!(balance))
+}
+
 // Check if the wallet has a balance for a specific asset
 async fn has_balance(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
     let params: GetBalanceParams = parse_params(body)?;
@@ -203,11 +310,21 @@ async fn get_asset_precision(context: &Context, body: Value) -> Result<Value, In
     let params: GetAssetPrecisionParams = parse_params(body)?;
 
     let wallet: &Arc<Wallet> = context.get()?;
-    let storage = wallet.get_storage().read().await;
-    let precision = storage.get_asset_decimals(&params.asset)?;
+    let precision = wallet.get_asset_decimals(&params.asset).await?;
     Ok(json!(precision))
 }
 
+// Retrieve the metadata of all the assets known by the wallet
+async fn get_assets(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    if body != Value::Null {
+        return Err(InternalRpcError::UnexpectedParams)
+    }
+
+    let wallet: &Arc<Wallet> = context.get()?;
+    let assets = wallet.get_assets_data().await?;
+    Ok(json!(assets))
+}
+
 // Retrieve a transaction from the wallet storage using its hash
 async fn get_transaction(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
     let params: GetTransactionParams = parse_params(body)?;
@@ -223,12 +340,17 @@ async fn get_transaction(context: &Context, body: Value) -> Result<Value, Intern
 async fn build_transaction(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
     let params: BuildTransactionParams = parse_params(body)?;
     let wallet: &Arc<Wallet> = context.get()?;
+    build_and_apply_transaction(wallet, params.tx_type, params.fee, params.broadcast, params.tx_as_hex).await
+}
+
+// Create the TX, broadcast it if requested and apply it to the storage
+async fn build_and_apply_transaction(wallet: &Arc<Wallet>, tx_type: TransactionTypeBuilder, fee: Option<FeeBuilder>, broadcast: bool, tx_as_hex: bool) -> Result<Value, InternalRpcError> {
     // request ask to broadcast the TX but wallet is not connected to any daemon
-    if !wallet.is_online().await && params.broadcast {
+    if !wallet.is_online().await && broadcast {
         return Err(WalletError::NotOnlineMode)?
     }
 
-    if !params.broadcast && !params.tx_as_hex {
+    if !broadcast && !tx_as_hex {
         return Err(InternalRpcError::InvalidParams("Invalid params, should either be broadcasted, or returned in hex format"))
     }
 
@@ -236,21 +358,162 @@ async fn build_transaction(context: &Context, body: Value) -> Result<Value, Inte
     // The lock is kept until the TX is applied to the storage
     // So even if we have few requests building a TX, they wait for the previous one to be applied
     let mut storage = wallet.get_storage().write().await;
-    let (mut state, tx) = wallet.create_transaction_with_storage(&storage, params.tx_type, params.fee.unwrap_or(FeeBuilder::Multiplier(1f64))).await?;
+    let fee = fee.unwrap_or(FeeBuilder::Multiplier(1f64));
 
     // if requested, broadcast the TX ourself
+    // the nonce is repaired and the TX built again if it was desynchronized
+    let tx = if broadcast {
+        wallet.create_and_submit_transaction_with_storage(&mut storage, tx_type, fee).await?
+    } else {
+        let (mut state, tx) = wallet.create_transaction_with_storage(&storage, tx_type, fee).await?;
+        state.apply_changes(&mut storage).await
+            .context("Error while applying state changes")?;
+        tx
+    };
+
+    // returns the created TX and its hash
+    Ok(json!(TransactionResponse {
+        tx_as_hex: if tx_as_hex {
+            Some(hex::encode(tx.to_bytes()))
+        } else {
+            None
+        },
+        inner: DataHash {
+            hash: Cow::Owned(tx.hash()),
+            data: Cow::Owned(tx)
+        }
+    }))
+}
+
+// Build a transaction to be signed by the multisig participants
+// Wallet storage is not updated as the transaction may never be finalized
+async fn build_unsigned_transaction(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: BuildUnsignedTransactionParams = parse_params(body)?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let unsigned = wallet.create_unsigned_transaction(params.tx_type, params.fee.unwrap_or(FeeBuilder::Multiplier(1f64)), params.threshold).await?;
+
+    Ok(json!(UnsignedTransactionResponse {
+        hash: unsigned.get_hash_for_multisig(),
+        tx_as_hex: hex::encode(unsigned.to_bytes())
+    }))
+}
+
+// Sign the hash of an unsigned transaction as a multisig participant
+async fn sign_unsigned_transaction(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: SignUnsignedTransactionParams = parse_params(body)?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let signature = wallet.sign_multisig_hash(&params.hash, params.signer_id)?;
+
+    Ok(json!(signature))
+}
+
+// Finalize an unsigned transaction with the multisig signatures and broadcast it if requested
+async fn finalize_unsigned_transaction(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: FinalizeUnsignedTransactionParams = parse_params(body)?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    if !wallet.is_online().await && params.broadcast {
+        return Err(WalletError::NotOnlineMode)?
+    }
+
+    if !params.broadcast && !params.tx_as_hex {
+        return Err(InternalRpcError::InvalidParams("Invalid params, should either be broadcasted, or returned in hex format"))
+    }
+
+    let unsigned = UnsignedTransaction::from_hex(params.unsigned)?;
+    let tx = wallet.finalize_unsigned_transaction(unsigned, params.signatures).await?;
+
     if params.broadcast {
-        if let Err(e) = wallet.submit_transaction(&tx).await {
-            warn!("Clearing Tx cache because of broadcasting error: {}", e);
-            storage.clear_tx_cache();
-            return Err(e.into());
+        wallet.submit_transaction(&tx).await?;
+    }
+
+    Ok(json!(TransactionResponse {
+        tx_as_hex: if params.tx_as_hex {
+            Some(hex::encode(tx.to_bytes()))
+        } else {
+            None
+        },
+        inner: DataHash {
+            hash: Cow::Owned(tx.hash()),
+            data: Cow::Owned(tx)
         }
+    }))
+}
+
+// Prepare a transaction for an address whose keys are on an air-gapped wallet
+// The request returned must be signed using `sign_offline_transaction` on it
+async fn build_offline_transaction(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: BuildOfflineTransactionParams = parse_params(body)?;
+    if !params.address.is_normal() {
+        return Err(InternalRpcError::InvalidParams("Address should be in normal format (not integrated address)"))
     }
 
-    state.apply_changes(&mut storage).await
-        .context("Error while applying state changes")?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let request = wallet.build_offline_transaction_request(params.address, params.tx_type, params.fee.unwrap_or(FeeBuilder::Multiplier(1f64))).await?;
+
+    Ok(json!(request))
+}
+
+// Build and sign a transaction prepared by an online wallet
+// It is never broadcasted, the hex must be sent back using `submit_transaction`
+async fn sign_offline_transaction(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: SignOfflineTransactionParams = parse_params(body)?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let tx = wallet.sign_offline_transaction(params.request).await?;
+
+    Ok(json!(TransactionResponse {
+        tx_as_hex: Some(hex::encode(tx.to_bytes())),
+        inner: DataHash {
+            hash: Cow::Owned(tx.hash()),
+            data: Cow::Owned(tx)
+        }
+    }))
+}
+
+// Broadcast a transaction already signed
+async fn submit_transaction(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: SubmitTransactionParams = parse_params(body)?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let tx = Transaction::from_hex(params.tx_as_hex)?;
+    if let Err(e) = wallet.submit_transaction(&tx).await {
+        // A desynchronized nonce is repaired so the next transactions built are valid
+        if let Ok(Some(nonce)) = wallet.resync_nonce().await {
+            warn!("Nonce was repaired to {} after the transaction {} was rejected", nonce, tx.hash());
+        }
+        return Err(e.into())
+    }
+
+    Ok(json!(tx.hash()))
+}
+
+// List the transactions built by the wallet which are not executed yet
+async fn get_pending_transactions(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    if body != Value::Null {
+        return Err(InternalRpcError::UnexpectedParams)
+    }
+
+    let wallet: &Arc<Wallet> = context.get()?;
+    let transactions = wallet.get_pending_transactions().await?;
+    Ok(json!(transactions))
+}
+
+// Repair the nonce if it is desynchronized from the daemon
+// Returns the new nonce or null if it was already synced
+async fn resync_nonce(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    if body != Value::Null {
+        return Err(InternalRpcError::UnexpectedParams)
+    }
+
+    let wallet: &Arc<Wallet> = context.get()?;
+    let nonce = wallet.resync_nonce().await?;
+    Ok(json!(nonce))
+}
+
+// Replace the only pending transaction by the same one paying a higher fee
+async fn bump_fee(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: BumpFeeParams = parse_params(body)?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let tx = wallet.bump_fee(&params.hash, params.fee).await?;
 
-    // returns the created TX and its hash
     Ok(json!(TransactionResponse {
         tx_as_hex: if params.tx_as_hex {
             Some(hex::encode(tx.to_bytes()))
@@ -295,6 +558,20 @@ async fn list_transactions(context: &Context, body: Value) -> Result<Value, Inte
     Ok(json!(txs))
 }
 
+// Export the history of the wallet for accounting
+// CSV is returned as a string, JSON as an array of records
+async fn export_history(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: ExportHistoryParams = parse_params(body)?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let provider = params.fiat.map(|fiat| HttpPriceProvider::new(fiat.url, fiat.currency));
+    let records = wallet.export_transactions(params.range, provider.as_ref().map(|p| p as &dyn PriceProvider)).await?;
+
+    Ok(match params.format {
+        ExportFormat::Csv => json!(export::to_csv(&records, provider.as_ref().map(|p| p.get_currency()))),
+        ExportFormat::Json => json!(records)
+    })
+}
+
 // Check if the wallet is currently connected to a daemon
 async fn is_online(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
     if body != Value::Null {
@@ -341,10 +618,352 @@ async fn sign_data(context: &Context, body: Value) -> Result<Value, InternalRpcE
     let params: DataElement = parse_params(body)?;
 
     let wallet: &Arc<Wallet> = context.get()?;
-    let signature = wallet.sign_data(&params.to_bytes());
+    let signature = wallet.sign_data(&params.to_bytes())?;
     Ok(json!(signature))
 }
 
+// Sign a message to prove the ownership of the wallet address
+async fn sign_message(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: SignMessageParams = parse_params(body)?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let signed = wallet.sign_message(&params.message)?;
+    Ok(json!(signed))
+}
+
+// Verify that a message was signed by the owner of an address
+// The address can be of any account, not only of this wallet
+async fn verify_signed_message(_: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: VerifySignedMessageParams = parse_params(body)?;
+    let key = params.address.get_public_key().decompress()
+        .context("Invalid public key in address")?;
+    Ok(json!(params.signature.verify_message(params.message.as_bytes(), &key)))
+}
+
+// Prove that the account has at least a minimum amount of an asset at a topoheight
+async fn create_balance_proof(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: CreateBalanceProofParams = parse_params(body)?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let proof = wallet.create_balance_proof(params.asset, params.minimum, params.topoheight).await?;
+    Ok(json!(BalanceProofResult {
+        address: wallet.get_address(),
+        asset: proof.get_asset().clone(),
+        topoheight: proof.get_topoheight(),
+        minimum: proof.get_minimum(),
+        proof: proof.to_hex()
+    }))
+}
+
+// Verify a balance proof created by the owner of an address
+// The address can be of any account, not only of this wallet
+async fn verify_balance_proof(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: VerifyBalanceProofParams = parse_params(body)?;
+    let proof = BalanceProof::from_hex(params.proof)
+        .context("Invalid balance proof")?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let valid = wallet.verify_balance_proof(&params.address, &proof).await?;
+    Ok(json!(valid))
+}
+
+// Build the entry returned for a payment request
+fn payment_request_entry(request: PaymentRequest) -> PaymentRequestEntry {
+    PaymentRequestEntry {
+        uri: request.to_uri(),
+        integrated_address: request.to_integrated_address(),
+        amount: request.get_amount(),
+        asset: request.get_asset().cloned().unwrap_or(XELIS_ASSET),
+        message: request.get_message().cloned(),
+        address: request.get_address().clone()
+    }
+}
+
+// Create a payment request URI to the address of the account selected
+async fn create_payment_request(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: CreatePaymentRequestParams = parse_params(body)?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let request = wallet.create_payment_request(params.amount, params.asset, params.message)?;
+    Ok(json!(payment_request_entry(request)))
+}
+
+// Read a payment request URI, from a QR code for example
+async fn parse_payment_request(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: ParsePaymentRequestParams = parse_params(body)?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let request = wallet.parse_payment_request(&params.uri)?;
+    Ok(json!(payment_request_entry(request)))
+}
+
+// Pay a payment request URI with a transfer
+async fn pay_request(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: PayRequestParams = parse_params(body)?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let request = wallet.parse_payment_request(&params.uri)?;
+    let tx_type = wallet.payment_request_to_transfer(&request, params.amount)?;
+    build_and_apply_transaction(wallet, tx_type, params.fee, params.broadcast, params.tx_as_hex).await
+}
+
+// Attach a label and an optional category to a transaction of the history
+async fn set_transaction_label(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: SetTransactionLabelParams = parse_params(body)?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    wallet.set_transaction_label(&params.hash, params.label).await?;
+    Ok(json!(true))
+}
+
+async fn get_transaction_label(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: GetTransactionLabelParams = parse_params(body)?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let label = wallet.get_transaction_label(&params.hash).await?;
+    Ok(json!(label))
+}
+
+async fn remove_transaction_label(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: RemoveTransactionLabelParams = parse_params(body)?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    wallet.remove_transaction_label(&params.hash).await?;
+    Ok(json!(true))
+}
+
+// Search the history using its amounts, direction and labels
+async fn search_transactions(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: SearchTransactionsParams = parse_params(body)?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let transactions = wallet.search_transactions(&params).await?;
+    Ok(json!(transactions))
+}
+
+// List all the accounts of the wallet
+async fn get_accounts(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    if body != Value::Null {
+        return Err(InternalRpcError::UnexpectedParams)
+    }
+
+    let wallet: &Arc<Wallet> = context.get()?;
+    let accounts = wallet.get_accounts().await?;
+    Ok(json!(accounts))
+}
+
+// Retrieve the account currently used by the wallet
+async fn get_selected_account(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    if body != Value::Null {
+        return Err(InternalRpcError::UnexpectedParams)
+    }
+
+    let wallet: &Arc<Wallet> = context.get()?;
+    Ok(json!(wallet.get_selected_account()))
+}
+
+// Create the next account derived from the seed
+async fn add_account(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    if body != Value::Null {
+        return Err(InternalRpcError::UnexpectedParams)
+    }
+
+    let wallet: &Arc<Wallet> = context.get()?;
+    let account = wallet.add_account().await?;
+    Ok(json!(account))
+}
+
+// Select the account used by all the other methods
+async fn select_account(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: SelectAccountParams = parse_params(body)?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    wallet.select_account(params.index, params.auto_reconnect).await?;
+    Ok(json!(true))
+}
+
+// Check if the wallet keys are wiped from memory
+async fn is_wallet_locked(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    if body != Value::Null {
+        return Err(InternalRpcError::UnexpectedParams)
+    }
+
+    let wallet: &Arc<Wallet> = context.get()?;
+    Ok(json!(wallet.is_locked()))
+}
+
+// Wipe the wallet keys from memory, methods using them fail until it is unlocked
+async fn lock_wallet(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    if body != Value::Null {
+        return Err(InternalRpcError::UnexpectedParams)
+    }
+
+    let wallet: &Arc<Wallet> = context.get()?;
+    wallet.lock().await?;
+    Ok(json!(true))
+}
+
+// Load the wallet keys again using its password
+async fn unlock_wallet(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: UnlockWalletParams = parse_params(body)?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    wallet.unlock(params.password).await?;
+    Ok(json!(true))
+}
+
+// Write an encrypted backup of the wallet to a file
+// It contains the keys, so it is not available through XSWD
+async fn backup_to_file(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    if context.has::<&WebSocketSessionShared<XSWDWebSocketHandler<Arc<Wallet>>>>() {
+        return Err(InternalRpcError::InvalidRequestStr("Backups can't be created through XSWD"))
+    }
+
+    let params: BackupToFileParams = parse_params(body)?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    wallet.backup_to_file(&params.path, params.password).await?;
+    Ok(json!(true))
+}
+
+// List all the contacts of the address book
+async fn get_contacts(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    if body != Value::Null {
+        return Err(InternalRpcError::UnexpectedParams)
+    }
+
+    let wallet: &Arc<Wallet> = context.get()?;
+    let contacts = wallet.get_contacts().await?;
+    Ok(json!(contacts))
+}
+
+// Get a contact using its name
+async fn get_contact(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: GetContactParams = parse_params(body)?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let contact = wallet.get_contact(&params.name).await?;
+    Ok(json!(contact))
+}
+
+// Save a new contact in the address book
+async fn add_contact(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: ContactEntry = parse_params(body)?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    wallet.add_contact(params).await?;
+    Ok(json!(true))
+}
+
+// Replace an existing contact
+async fn update_contact(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: ContactEntry = parse_params(body)?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    wallet.update_contact(params).await?;
+    Ok(json!(true))
+}
+
+// Remove a contact using its name
+async fn remove_contact(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: RemoveContactParams = parse_params(body)?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    wallet.remove_contact(&params.name).await?;
+    Ok(json!(true))
+}
+
+// Retrieve the latest alerts triggered, oldest first
+async fn get_alerts(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    if body != Value::Null {
+        return Err(InternalRpcError::UnexpectedParams)
+    }
+
+    let wallet: &Arc<Wallet> = context.get()?;
+    let alerts = wallet.get_alerts().await;
+    Ok(json!(alerts))
+}
+
+// Retrieve all the alert rules configured
+async fn get_alert_rules(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    if body != Value::Null {
+        return Err(InternalRpcError::UnexpectedParams)
+    }
+
+    let wallet: &Arc<Wallet> = context.get()?;
+    let rules = wallet.get_alert_rules().await;
+    Ok(json!(rules))
+}
+
+// Add a new alert rule and returns its id
+async fn add_alert_rule(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: AddAlertRuleParams = parse_params(body)?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let id = wallet.add_alert_rule(params.rule).await?;
+    Ok(json!(id))
+}
+
+// Remove an alert rule using its id
+async fn remove_alert_rule(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: RemoveAlertRuleParams = parse_params(body)?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let removed = wallet.remove_alert_rule(params.id).await?;
+    Ok(json!(removed))
+}
+
+// Retrieve the URLs receiving the alerts
+async fn get_alert_webhooks(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    if body != Value::Null {
+        return Err(InternalRpcError::UnexpectedParams)
+    }
+
+    let wallet: &Arc<Wallet> = context.get()?;
+    let webhooks = wallet.get_alert_webhooks().await;
+    Ok(json!(webhooks))
+}
+
+// Set the URLs receiving each alert in a POST request
+async fn set_alert_webhooks(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: SetAlertWebhooksParams = parse_params(body)?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    wallet.set_alert_webhooks(params.webhooks).await?;
+    Ok(json!(true))
+}
+
+// Retrieve the URLs notified of the events and the notifications pending
+async fn get_webhooks(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    if body != Value::Null {
+        return Err(InternalRpcError::UnexpectedParams)
+    }
+
+    let wallet: &Arc<Wallet> = context.get()?;
+    let webhooks = wallet.get_webhooks().await;
+    Ok(json!(webhooks))
+}
+
+// Set the URLs notified of the events and returns the secret signing them
+async fn set_webhooks(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    // The secret authenticates the notifications, an application must not be able to replace it
+    if context.has::<&WebSocketSessionShared<XSWDWebSocketHandler<Arc<Wallet>>>>() {
+        return Err(InternalRpcError::InvalidRequestStr("Webhooks can't be set through XSWD"))
+    }
+
+    let params: SetWebhooksParams = parse_params(body)?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let secret = wallet.set_webhooks(params.urls, params.secret).await?;
+    Ok(json!(secret))
+}
+
+// Retrieve the payments scheduled for the account selected
+async fn get_scheduled_payments(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    if body != Value::Null {
+        return Err(InternalRpcError::UnexpectedParams)
+    }
+
+    let wallet: &Arc<Wallet> = context.get()?;
+    let payments = wallet.get_scheduled_payments().await?;
+    Ok(json!(payments))
+}
+
+// Schedule a new payment and returns its id
+async fn add_scheduled_payment(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: AddScheduledPaymentParams = parse_params(body)?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let id = wallet.add_scheduled_payment(params.payment).await?;
+    Ok(json!(id))
+}
+
+// Remove a scheduled payment using its id
+async fn remove_scheduled_payment(context: &Context, body: Value) -> Result<Value, InternalRpcError> {
+    let params: RemoveScheduledPaymentParams = parse_params(body)?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let removed = wallet.remove_scheduled_payment(params.id).await?;
+    Ok(json!(removed))
+}
+
 // In EncryptedStorage, custom trees are already prefixed
 async fn get_tree_name(context: &Context, tree: String) -> Result<String, InternalRpcError> {
     // If the API is not used through XSWD, we don't need to prefix the tree name with the app id
@@ -24,6 +24,10 @@ use xelis_common::{
     },
     utils::spawn_task
 };
+use super::{
+    metrics::{rpc_server_metrics, WalletMetricsHandler},
+    rest
+};
 use actix_web::{
     get,
     HttpResponse,
@@ -54,9 +58,10 @@ where
 
 impl<W> WalletRpcServer<W>
 where
-    W: Clone + Send + Sync + 'static
+    W: Clone + Send + Sync + WalletMetricsHandler + 'static
 {
     pub async fn new(bind_address: String, rpc_handler: RPCHandler<W>, auth_config: Option<AuthConfig>) -> Result<WalletRpcServerShared<W>> {
+        let metrics = rpc_handler.get_metrics().is_some();
         let server = Arc::new(Self {
             handle: Mutex::new(None),
             websocket: WebSocketServer::new(EventWebSocketHandler::new(rpc_handler)),
@@ -68,14 +73,24 @@ where
             let http_server = HttpServer::new(move || {
                 let server = Arc::clone(&clone);
                 let auth = HttpAuthentication::basic(auth::<W>);
-                App::new()
+                let app = App::new()
                     .app_data(Data::from(server))
                     .wrap(auth)
                     // WebSocket support
                     .route("/json_rpc", web::get().to(websocket::<EventWebSocketHandler<W, NotifyEvent>, Self>))
                     // HTTP support
                     .route("/json_rpc", web::post().to(json_rpc::<W, WalletRpcServer<W>>))
-                    .service(index)
+                    // REST support
+                    .route("/balance", web::get().to(rest::balance::<W>))
+                    .route("/transactions", web::get().to(rest::transactions::<W>))
+                    .route("/transfer", web::post().to(rest::transfer::<W>))
+                    .service(index);
+
+                if metrics {
+                    app.route("/metrics", web::get().to(rpc_server_metrics::<W>))
+                } else {
+                    app
+                }
             })
             .disable_signals()
             .bind(&bind_address)?
@@ -92,7 +107,12 @@ where
 
         Ok(server)
     }
+}
 
+impl<W> WalletRpcServer<W>
+where
+    W: Clone + Send + Sync + 'static
+{
     async fn authenticate(&self, credentials: BasicAuth) -> Result<(), Error> {
         if let Some(config) = &self.auth_config {
             let user = credentials.user_id();
@@ -41,12 +41,15 @@ use tokio::sync::{
 };
 use xelis_common::{
     api::{
-        wallet::NotifyEvent,
+        wallet::{NotifyEvent, PayRequestParams, XSWDLimits, XSWDLimitsSettings},
         EventResult
     },
+    config::XELIS_ASSET,
     context::Context,
     crypto::{
         elgamal::PublicKey as DecompressedPublicKey,
+        Hash,
+        PaymentRequest,
         Signature,
         SIGNATURE_SIZE
     },
@@ -69,10 +72,13 @@ use xelis_common::{
         Serializer,
         Writer
     },
+    time::{get_current_time_in_seconds, TimestampSeconds},
+    transaction::builder::TransactionTypeBuilder,
     utils::spawn_task
 };
 use serde::{Deserialize, Serialize};
 use crate::config::XSWD_BIND_ADDRESS;
+use super::metrics::{xswd_metrics, WalletMetricsHandler};
 use log::{
     debug,
     info,
@@ -129,7 +135,11 @@ pub enum XSWDError {
     #[error("Application permissions are not signed")]
     ApplicationPermissionsNotSigned,
     #[error("Invalid signature for application data")]
-    InvalidSignatureForApplicationData
+    InvalidSignatureForApplicationData,
+    #[error("Too many requests, try again later")]
+    TooManyRequests,
+    #[error("Daily spend cap reached")]
+    DailySpendCapReached
 }
 
 impl From<XSWDError> for InternalRpcError {
@@ -146,8 +156,8 @@ pub trait XSWDPermissionHandler {
     async fn request_permission(&self, app_state: &AppStateShared, request: PermissionRequest<'_>) -> Result<PermissionResult, Error>;
     // Handler function to cancel the request permission from app (app has disconnected)
     async fn cancel_request_permission(&self, app_state: &AppStateShared) -> Result<(), Error>;
-    // Public key to use to verify the signature
-    async fn get_public_key(&self) -> Result<&DecompressedPublicKey, Error>;
+    // Public key of the account selected to use to verify the signature
+    async fn get_public_key(&self) -> Result<DecompressedPublicKey, Error>;
 }
 
 #[async_trait]
@@ -302,18 +312,25 @@ impl Serializer for ApplicationData {
 
 impl<W> XSWD<W>
 where
-    W: Clone + Send + Sync + XSWDPermissionHandler + XSWDNodeMethodHandler + 'static
+    W: Clone + Send + Sync + XSWDPermissionHandler + XSWDNodeMethodHandler + WalletMetricsHandler + 'static
 {
     pub fn new(rpc_handler: RPCHandler<W>) -> Result<Self, anyhow::Error> {
         info!("Starting XSWD Server...");
+        let metrics = rpc_handler.get_metrics().is_some();
         let websocket = WebSocketServer::new(XSWDWebSocketHandler::new(rpc_handler));
         let cloned_websocket = websocket.clone();
         let http_server = HttpServer::new(move || {
             let server = Arc::clone(&cloned_websocket);
-            App::new()
+            let app = App::new()
                 .app_data(Data::from(server))
                 .service(index)
-                .route("/xswd", web::get().to(endpoint::<W>))
+                .route("/xswd", web::get().to(endpoint::<W>));
+
+            if metrics {
+                app.route("/metrics", web::get().to(xswd_metrics::<W>))
+            } else {
+                app
+            }
         })
         .disable_signals()
         .bind(&XSWD_BIND_ADDRESS)?
@@ -329,7 +346,12 @@ where
             handle
         })
     }
+}
 
+impl<W> XSWD<W>
+where
+    W: Clone + Send + Sync + XSWDPermissionHandler + XSWDNodeMethodHandler + 'static
+{
     pub fn get_handler(&self) -> &XSWDWebSocketHandler<W> {
         self.websocket.get_handler()
     }
@@ -390,12 +412,53 @@ impl PermissionResult {
     }
 }
 
+// Methods building a transaction spending the funds of the wallet
+// Their amounts are verified against the limits of the application
+const SPENDING_METHODS: [&str; 2] = ["build_transaction", "build_unsigned_transaction"];
+
+// Only the transaction type is read to compute the amounts spent
+#[derive(Deserialize)]
+struct SpendingParams {
+    #[serde(flatten)]
+    tx_type: TransactionTypeBuilder
+}
+
+// Method paying a payment request URI, its amount is read from the URI or the params
+const PAY_REQUEST_METHOD: &str = "pay_request";
+
+// Amounts spent by a payment request
+// An invalid URI spends nothing as the request is rejected by the method
+fn get_pay_request_amounts(params: &PayRequestParams) -> HashMap<Hash, u64> {
+    let mut spent = HashMap::new();
+    if let Ok(request) = PaymentRequest::from_uri(&params.uri) {
+        let amount = request.get_amount().or(params.amount).unwrap_or(0);
+        spent.insert(request.get_asset().cloned().unwrap_or(XELIS_ASSET), amount);
+    }
+    spent
+}
+
+// Requests and spending of an application
+// Kept by application ID so a reconnection doesn't reset them
+#[derive(Default)]
+struct AppUsage {
+    // Start of the current minute and the requests done since
+    window_start: TimestampSeconds,
+    requests: u32,
+    // Day of the spending, in days since UNIX epoch
+    day: u64,
+    spent: HashMap<Hash, u64>
+}
+
 pub struct XSWDWebSocketHandler<W>
 where
     W: Clone + Send + Sync + XSWDPermissionHandler + XSWDNodeMethodHandler + 'static
 {
     // RPC handler for methods
     handler: RPCHandler<W>,
+    // Limits applied to the applications
+    limits: RwLock<XSWDLimitsSettings>,
+    // Usage of each application by its ID
+    usages: Mutex<HashMap<String, AppUsage>>,
     // All applications connected to the wallet
     applications: RwLock<HashMap<WebSocketSessionShared<Self>, AppStateShared>>,
     // Applications listening for events
@@ -411,12 +474,18 @@ where
     pub fn new(handler: RPCHandler<W>) -> Self {
         Self {
             handler,
+            limits: RwLock::new(XSWDLimitsSettings::default()),
+            usages: Mutex::new(HashMap::new()),
             applications: RwLock::new(HashMap::new()),
             listeners: Mutex::new(HashMap::new()),
             permission_handler_semaphore: Semaphore::new(1)
         }
     }
 
+    pub fn get_rpc_handler(&self) -> &RPCHandler<W> {
+        &self.handler
+    }
+
     // This method is used to get the applications HashMap
     // be careful by using it, and if you delete a session, please disconnect it
     pub fn get_applications(&self) -> &RwLock<HashMap<WebSocketSessionShared<Self>, AppStateShared>> {
@@ -455,7 +524,83 @@ where
         }
     }
 
-    async fn verify_permission_for_request(&self, app: &AppStateShared, request: &RpcRequest) -> Result<(), RpcResponseError> {
+    // Replace the limits applied to the applications
+    pub async fn set_limits(&self, limits: XSWDLimitsSettings) {
+        let mut lock = self.limits.write().await;
+        *lock = limits;
+    }
+
+    async fn get_limits_for(&self, app: &AppStateShared) -> XSWDLimits {
+        let limits = self.limits.read().await;
+        limits.get_limits(app.get_id()).clone()
+    }
+
+    // Count the request in the current minute of the application
+    async fn verify_rate_limit(&self, app: &AppStateShared, limits: &XSWDLimits, id: &Option<Id>) -> Result<(), RpcResponseError> {
+        let Some(max) = limits.max_requests_per_minute else {
+            return Ok(())
+        };
+
+        let now = get_current_time_in_seconds();
+        let mut usages = self.usages.lock().await;
+        let usage = usages.entry(app.get_id().clone()).or_default();
+        if now >= usage.window_start + 60 {
+            usage.window_start = now;
+            usage.requests = 0;
+        }
+
+        if usage.requests >= max {
+            debug!("Application {} reached its limit of {} requests per minute", app.get_name(), max);
+            return Err(RpcResponseError::new(id.clone(), XSWDError::TooManyRequests))
+        }
+        usage.requests += 1;
+
+        Ok(())
+    }
+
+    // Reserve the amounts in the daily spending of the application
+    // They are released if the transaction couldn't be built
+    async fn reserve_spending(&self, app: &AppStateShared, limits: &XSWDLimits, amounts: &HashMap<Hash, u64>, id: &Option<Id>) -> Result<(), RpcResponseError> {
+        let day = get_current_time_in_seconds() / 86400;
+        let mut usages = self.usages.lock().await;
+        let usage = usages.entry(app.get_id().clone()).or_default();
+        if usage.day != day {
+            usage.day = day;
+            usage.spent.clear();
+        }
+
+        for (asset, amount) in amounts {
+            if let Some(cap) = limits.daily_spend_cap.get(asset) {
+                let spent = usage.spent.get(asset).copied().unwrap_or(0);
+                if spent.saturating_add(*amount) > *cap {
+                    debug!("Application {} reached its daily spend cap of {} for asset {}", app.get_name(), cap, asset);
+                    return Err(RpcResponseError::new(id.clone(), XSWDError::DailySpendCapReached))
+                }
+            }
+        }
+
+        for (asset, amount) in amounts {
+            let spent = usage.spent.entry(asset.clone()).or_default();
+            *spent = spent.saturating_add(*amount);
+        }
+
+        Ok(())
+    }
+
+    async fn release_spending(&self, app: &AppStateShared, amounts: &HashMap<Hash, u64>) {
+        let mut usages = self.usages.lock().await;
+        if let Some(usage) = usages.get_mut(app.get_id()) {
+            for (asset, amount) in amounts {
+                if let Some(spent) = usage.spent.get_mut(asset) {
+                    *spent = spent.saturating_sub(*amount);
+                }
+            }
+        }
+    }
+
+    // force_ask is set when the request exceeds the limits of the application
+    // The user is asked again even if the method is always allowed
+    async fn verify_permission_for_request(&self, app: &AppStateShared, request: &RpcRequest, force_ask: bool) -> Result<(), RpcResponseError> {
         let _permit = self.permission_handler_semaphore.acquire().await
             .map_err(|_| RpcResponseError::new(request.id.clone(), InternalRpcError::InternalError("Permission handler semaphore error")))?;
         let mut permissions = app.permissions.lock().await;
@@ -465,7 +610,10 @@ where
             return Err(RpcResponseError::new(request.id.clone(), XSWDError::ApplicationNotFound))
         }
 
-        let permission = permissions.get(&request.method).map(|v| *v).unwrap_or(Permission::Ask);
+        let permission = match permissions.get(&request.method).map(|v| *v).unwrap_or(Permission::Ask) {
+            Permission::AcceptAlways if force_ask => Permission::Ask,
+            permission => permission
+        };
         match permission {
             // Request permission from user
             Permission::Ask => {
@@ -558,7 +706,7 @@ where
                     RpcResponseError::new(None, InternalRpcError::InternalError("Error while retrieving wallet public key"))
                 })?;
 
-            if signature.verify(bytes, key) {
+            if signature.verify(bytes, &key) {
                 return Err(RpcResponseError::new(None, XSWDError::InvalidSignatureForApplicationData));
             }
         }
@@ -640,7 +788,7 @@ where
     }
 
     async fn on_message_internal(&self, session: &WebSocketSessionShared<Self>, message: &[u8]) -> Result<Option<Value>, RpcResponseError> {
-        let (request, is_subscribe, is_unsubscribe) = {
+        let (request, is_subscribe, is_unsubscribe, spending) = {
             let app_state = {
                 let applications = self.applications.read().await;
                 applications.get(session).cloned()
@@ -649,6 +797,9 @@ where
             // Application is already registered, verify permission and call the method
             if let Some(app) = app_state {
                 let mut request: RpcRequest = self.handler.parse_request_from_bytes(message)?;
+                let limits = self.get_limits_for(&app).await;
+                self.verify_rate_limit(&app, &limits, &request.id).await?;
+
                 // Redirect all node methods to the node method handler
                 if request.method.starts_with("node.") {
                     // Remove the 5 first chars (node.)
@@ -669,12 +820,38 @@ where
                     return Err(RpcResponseError::new(request.id, InternalRpcError::MethodNotFound(request.method)))
                 }
     
+                // Amounts spent by the transaction requested
+                let amounts = if SPENDING_METHODS.contains(&request.method.as_str()) {
+                    let params: SpendingParams = serde_json::from_value(request.params.clone().unwrap_or_default())
+                        .map_err(|e| RpcResponseError::new(request.id.clone(), InternalRpcError::InvalidJSONParams(e)))?;
+                    Some(params.tx_type.get_spent_amounts())
+                } else if request.method == PAY_REQUEST_METHOD {
+                    let params: PayRequestParams = serde_json::from_value(request.params.clone().unwrap_or_default())
+                        .map_err(|e| RpcResponseError::new(request.id.clone(), InternalRpcError::InvalidJSONParams(e)))?;
+                    Some(get_pay_request_amounts(&params))
+                } else {
+                    None
+                };
+
+                let force_ask = amounts.as_ref().map_or(false, |amounts| amounts.iter()
+                    .any(|(asset, amount)| limits.max_amount_per_transaction.get(asset).map_or(false, |max| amount > max))
+                );
+
                 // let's check the permission set by user for this method
                 app.set_requesting(true);
-                self.verify_permission_for_request(&app, &request).await?;
+                let res = self.verify_permission_for_request(&app, &request, force_ask).await;
                 app.set_requesting(false);
+                res?;
 
-                (request, is_subscribe, is_unsubscribe)
+                let spending = match amounts {
+                    Some(amounts) => {
+                        self.reserve_spending(&app, &limits, &amounts, &request.id).await?;
+                        Some((app, amounts))
+                    },
+                    None => None
+                };
+
+                (request, is_subscribe, is_unsubscribe, spending)
             } else {
                 // Application is not registered, register it
                 return match self.add_application(session, message).await {
@@ -712,7 +889,13 @@ where
             context.store(self.handler.get_data().clone());
             // Store the session
             context.store(session.clone());
-            self.handler.execute_method(&context, request).await
+            let res = self.handler.execute_method(&context, request).await;
+            if res.is_err() {
+                if let Some((app, amounts)) = spending {
+                    self.release_spending(&app, &amounts).await;
+                }
+            }
+            res
         }
     }
 }
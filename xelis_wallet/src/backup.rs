@@ -0,0 +1,166 @@
+use std::{fs, path::Path};
+use anyhow::{Context, Error};
+use chacha20poly1305::aead::OsRng;
+use rand::RngCore;
+use zeroize::Zeroize;
+use xelis_common::{
+    api::wallet::Contact,
+    crypto::PrivateKey,
+    network::Network,
+    serializer::{
+        Reader,
+        ReaderError,
+        Serializer,
+        Writer
+    }
+};
+use crate::{
+    alerts::AlertsSettings,
+    cipher::Cipher,
+    config::SALT_SIZE,
+    entry::TransactionEntry,
+    error::WalletError,
+    wallet::hash_password
+};
+
+// Bytes at the start of a backup file
+const BACKUP_MAGIC: &[u8] = b"XELISBAK";
+// Version of the backup format, increased on each change of WalletBackup
+pub const BACKUP_VERSION: u8 = 1;
+
+// Everything required to restore a wallet on another device
+// Balances are not included, they are synced again from the daemon
+pub struct WalletBackup {
+    pub network: Network,
+    pub private_key: PrivateKey,
+    // Only set when the seed is combined with a passphrase
+    pub seed_key: Option<PrivateKey>,
+    pub selected_account: u32,
+    pub alerts: AlertsSettings,
+    // Address book with the name of each contact
+    pub contacts: Vec<(String, Contact)>,
+    // History of each account, indexed by the account index
+    pub transactions: Vec<Vec<TransactionEntry>>
+}
+
+impl WalletBackup {
+    // Encrypt the backup with a key derived from the password and write it to a new file
+    pub fn write_to_file(&self, path: &str, password: String) -> Result<(), Error> {
+        if Path::new(path).exists() {
+            return Err(WalletError::BackupFileExists(path.to_owned()).into())
+        }
+
+        let mut salt = [0u8; SALT_SIZE];
+        OsRng.fill_bytes(&mut salt);
+        let cipher = Cipher::new(&hash_password(password, &salt)?, None)?;
+
+        let mut plaintext = self.to_bytes();
+        let encrypted = cipher.encrypt_value(&plaintext);
+        plaintext.zeroize();
+
+        let mut content = Vec::with_capacity(BACKUP_MAGIC.len() + 1 + SALT_SIZE);
+        content.extend_from_slice(BACKUP_MAGIC);
+        content.push(BACKUP_VERSION);
+        content.extend_from_slice(&salt);
+        content.extend(encrypted?);
+
+        fs::write(path, content).with_context(|| format!("Error while writing backup file {}", path))
+    }
+
+    // Read and decrypt a backup file
+    pub fn read_from_file(path: &str, password: String) -> Result<Self, Error> {
+        let content = fs::read(path).with_context(|| format!("Error while reading backup file {}", path))?;
+        let header_size = BACKUP_MAGIC.len() + 1 + SALT_SIZE;
+        if content.len() <= header_size || !content.starts_with(BACKUP_MAGIC) {
+            return Err(WalletError::InvalidBackupFile.into())
+        }
+
+        let version = content[BACKUP_MAGIC.len()];
+        if version != BACKUP_VERSION {
+            return Err(WalletError::UnsupportedBackupVersion(version, BACKUP_VERSION).into())
+        }
+
+        let salt = &content[BACKUP_MAGIC.len() + 1..header_size];
+        let cipher = Cipher::new(&hash_password(password, salt)?, None)?;
+        let mut plaintext = cipher.decrypt_value(&content[header_size..]).context("Invalid password provided for this backup")?;
+        let backup = Self::from_bytes(&plaintext).map_err(|_| WalletError::InvalidBackupFile);
+        plaintext.zeroize();
+
+        Ok(backup?)
+    }
+}
+
+impl Serializer for WalletBackup {
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        let network = Network::read(reader)?;
+        let private_key = PrivateKey::read(reader)?;
+        let seed_key = Option::read(reader)?;
+        let selected_account = reader.read_u32()?;
+        let alerts = AlertsSettings::read(reader)?;
+
+        // Counts are written as u32, the history can be bigger than a serialized Vec
+        let contacts_count = reader.read_u32()?;
+        let mut contacts = Vec::new();
+        for _ in 0..contacts_count {
+            let name = reader.read_string()?;
+            let contact = Contact::read(reader)?;
+            contacts.push((name, contact));
+        }
+
+        let accounts_count = reader.read_u32()?;
+        if selected_account >= accounts_count {
+            return Err(ReaderError::InvalidValue)
+        }
+
+        let mut transactions = Vec::new();
+        for _ in 0..accounts_count {
+            let count = reader.read_u32()?;
+            let mut entries = Vec::new();
+            for _ in 0..count {
+                entries.push(TransactionEntry::read(reader)?);
+            }
+            transactions.push(entries);
+        }
+
+        Ok(Self {
+            network,
+            private_key,
+            seed_key,
+            selected_account,
+            alerts,
+            contacts,
+            transactions
+        })
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        self.network.write(writer);
+        self.private_key.write(writer);
+        self.seed_key.write(writer);
+        writer.write_u32(&self.selected_account);
+        self.alerts.write(writer);
+
+        writer.write_u32(&(self.contacts.len() as u32));
+        for (name, contact) in &self.contacts {
+            writer.write_string(name);
+            contact.write(writer);
+        }
+
+        writer.write_u32(&(self.transactions.len() as u32));
+        for entries in &self.transactions {
+            writer.write_u32(&(entries.len() as u32));
+            for entry in entries {
+                entry.write(writer);
+            }
+        }
+    }
+}
+
+impl Drop for WalletBackup {
+    fn drop(&mut self) {
+        self.private_key.zeroize();
+        if let Some(seed_key) = self.seed_key.as_mut() {
+            seed_key.zeroize();
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+use xelis_common::{
+    crypto::Hash,
+    serializer::Serializer,
+    transaction::{builder::TransferBuilder, MAX_TRANSFER_COUNT}
+};
+use crate::error::WalletError;
+
+// A payment read from a batch file
+// The amount is kept as written, it is converted once the asset decimals are known
+pub struct BatchPayment {
+    // Line of the payment in the file
+    pub line: usize,
+    // Address or contact name
+    pub destination: String,
+    pub amount: String,
+    // Default asset of the contact or XELIS if not set
+    pub asset: Option<Hash>
+}
+
+// Parse a CSV batch with one payment per line: destination,amount[,asset]
+// Empty lines, comments starting with '#' and a header line starting with "destination" are ignored
+pub fn parse_csv(content: &str) -> Result<Vec<BatchPayment>, WalletError> {
+    let mut payments = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || (i == 0 && line.starts_with("destination")) {
+            continue;
+        }
+
+        // Line numbers are displayed starting from 1
+        let line_number = i + 1;
+        let invalid = |reason: &str| WalletError::InvalidBatchLine(line_number, reason.to_owned());
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        if fields.len() < 2 || fields.len() > 3 {
+            return Err(invalid("expected destination,amount[,asset]"))
+        }
+
+        if fields[0].is_empty() {
+            return Err(invalid("destination is empty"))
+        }
+
+        let asset = match fields.get(2).filter(|asset| !asset.is_empty()) {
+            Some(asset) => Some(Hash::from_hex(asset.to_string()).map_err(|_| invalid("invalid asset"))?),
+            None => None
+        };
+
+        payments.push(BatchPayment {
+            line: line_number,
+            destination: fields[0].to_owned(),
+            amount: fields[1].to_owned(),
+            asset
+        });
+    }
+
+    if payments.is_empty() {
+        return Err(WalletError::EmptyBatch)
+    }
+
+    if payments.len() > MAX_TRANSFER_COUNT {
+        return Err(WalletError::TooManyTransfers(payments.len(), MAX_TRANSFER_COUNT))
+    }
+
+    Ok(payments)
+}
+
+// Sum the amounts sent for each asset
+pub fn get_totals(transfers: &[TransferBuilder]) -> Result<HashMap<Hash, u64>, WalletError> {
+    let mut totals = HashMap::new();
+    for transfer in transfers {
+        let total: &mut u64 = totals.entry(transfer.asset.clone()).or_default();
+        *total = total.checked_add(transfer.amount)
+            .ok_or_else(|| WalletError::TransfersTotalOverflow(transfer.asset.clone()))?;
+    }
+
+    Ok(totals)
+}
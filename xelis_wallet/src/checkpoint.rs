@@ -0,0 +1,125 @@
+use xelis_common::{
+    crypto::Hash,
+    serializer::{
+        Reader,
+        ReaderError,
+        Serializer,
+        Writer
+    }
+};
+
+// Progress of a blocks sync between two topoheights
+// It is saved regularly in the wallet storage to resume an interrupted rescan where it stopped
+#[derive(Debug, Clone)]
+pub struct SyncCheckpoint {
+    // Topoheight from which the wallet is syncing
+    start_topoheight: u64,
+    // Daemon topoheight and top block hash when the sync started
+    target_topoheight: u64,
+    target_block_hash: Hash,
+    // Assets fully synced from the target down to the start
+    assets_done: Vec<Hash>,
+    // Asset being synced with the lowest topoheight processed for it
+    // Assets are synced from the highest topoheight to the lowest one
+    cursor: Option<(Hash, u64)>
+}
+
+impl SyncCheckpoint {
+    pub fn new(start_topoheight: u64, target_topoheight: u64, target_block_hash: Hash) -> Self {
+        Self {
+            start_topoheight,
+            target_topoheight,
+            target_block_hash,
+            assets_done: Vec::new(),
+            cursor: None
+        }
+    }
+
+    pub fn get_start_topoheight(&self) -> u64 {
+        self.start_topoheight
+    }
+
+    pub fn get_target_topoheight(&self) -> u64 {
+        self.target_topoheight
+    }
+
+    pub fn get_target_block_hash(&self) -> &Hash {
+        &self.target_block_hash
+    }
+
+    // Check if the asset was already synced until the start topoheight
+    pub fn is_asset_done(&self, asset: &Hash) -> bool {
+        self.assets_done.contains(asset)
+    }
+
+    // Lowest topoheight processed for the asset if its sync was interrupted
+    pub fn get_cursor(&self, asset: &Hash) -> Option<u64> {
+        self.cursor.as_ref()
+            .filter(|(cursor_asset, _)| cursor_asset == asset)
+            .map(|(_, topoheight)| *topoheight)
+    }
+
+    pub fn set_cursor(&mut self, asset: &Hash, topoheight: u64) {
+        self.cursor = Some((asset.clone(), topoheight));
+    }
+
+    pub fn mark_asset_done(&mut self, asset: &Hash) {
+        self.cursor = None;
+        if !self.is_asset_done(asset) {
+            self.assets_done.push(asset.clone());
+        }
+    }
+
+    // Progress over all the assets, counted in topoheights scanned
+    // Returns the current and the target values
+    pub fn get_progress(&self, assets: usize) -> (u64, u64) {
+        let range = self.target_topoheight.saturating_sub(self.start_topoheight);
+        let target = range.saturating_mul(assets as u64);
+
+        let mut current = range.saturating_mul(self.assets_done.len() as u64);
+        if let Some((_, topoheight)) = &self.cursor {
+            current = current.saturating_add(self.target_topoheight.saturating_sub(*topoheight).min(range));
+        }
+
+        (current.min(target), target)
+    }
+}
+
+impl Serializer for SyncCheckpoint {
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        let start_topoheight = reader.read_u64()?;
+        let target_topoheight = reader.read_u64()?;
+        let target_block_hash = reader.read_hash()?;
+        let assets_done = Vec::read(reader)?;
+        let cursor = if reader.read_bool()? {
+            Some((reader.read_hash()?, reader.read_u64()?))
+        } else {
+            None
+        };
+
+        Ok(Self {
+            start_topoheight,
+            target_topoheight,
+            target_block_hash,
+            assets_done,
+            cursor
+        })
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        writer.write_u64(&self.start_topoheight);
+        writer.write_u64(&self.target_topoheight);
+        writer.write_hash(&self.target_block_hash);
+        self.assets_done.write(writer);
+        writer.write_bool(self.cursor.is_some());
+        if let Some((asset, topoheight)) = &self.cursor {
+            writer.write_hash(asset);
+            writer.write_u64(topoheight);
+        }
+    }
+
+    fn size(&self) -> usize {
+        let cursor = self.cursor.as_ref().map(|(asset, topoheight)| asset.size() + topoheight.size()).unwrap_or(0);
+        self.start_topoheight.size() + self.target_topoheight.size() + self.target_block_hash.size() + self.assets_done.size() + 1 + cursor
+    }
+}
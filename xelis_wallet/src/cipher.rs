@@ -11,6 +11,7 @@ use xelis_common::crypto::{
     HASH_SIZE,
     hash
 };
+use zeroize::Zeroize;
 use crate::{error::WalletError, config::SALT_SIZE};
 
 
@@ -20,6 +21,13 @@ pub struct Cipher {
     salt: Option<[u8; SALT_SIZE]>
 }
 
+// The key is wiped by XChaCha20Poly1305 itself
+impl Drop for Cipher {
+    fn drop(&mut self) {
+        self.salt.zeroize();
+    }
+}
+
 impl Cipher {
     pub const NONCE_SIZE: usize = 24;
 
@@ -6,11 +6,53 @@ pub const XSWD_BIND_ADDRESS: &str = "0.0.0.0:44325";
 pub const PASSWORD_HASH_SIZE: usize = 32;
 pub const SALT_SIZE: usize = 32;
 pub const KEY_SIZE: usize = 32;
+// Version of the wallet storage layout
+// It must be increased with a new migration on each change of the layout
+pub const STORAGE_VERSION: u32 = 1;
 
 // daemon address by default when no specified
 pub const DEFAULT_DAEMON_ADDRESS: &str = "http://127.0.0.1:8080";
 // Auto reconnect interval in seconds for Network Handler
 pub const AUTO_RECONNECT_INTERVAL: u64 = 5;
+// Interval in seconds between each progress report of the vanity address search
+pub const VANITY_PROGRESS_INTERVAL: u64 = 5;
+// Maximum alerts kept in memory, oldest are deleted first
+pub const MAX_ALERTS_HISTORY: usize = 100;
+// Timeout in seconds for a webhook request sending an alert
+pub const ALERT_WEBHOOK_TIMEOUT_SECS: u64 = 10;
+// Timeout in seconds for a webhook request sending a notification
+pub const WEBHOOK_TIMEOUT_SECS: u64 = 10;
+// Failed attempts before dropping a webhook notification
+pub const MAX_WEBHOOK_ATTEMPTS: u8 = 10;
+// Delay in seconds before retrying a webhook notification, doubled on each failed attempt
+pub const WEBHOOK_RETRY_DELAY_SECS: u64 = 30;
+pub const MAX_WEBHOOK_RETRY_DELAY_SECS: u64 = 60 * 60;
+// Maximum webhook notifications queued, oldest are dropped first
+pub const MAX_WEBHOOK_QUEUE_SIZE: usize = 1000;
+// Size in bytes of a webhook secret generated by the wallet
+pub const WEBHOOK_SECRET_SIZE: usize = 32;
+// Accounts not registered in a row before stopping the recovery scan
+// of a wallet restored from its seed
+pub const ACCOUNTS_GAP_LIMIT: u32 = 20;
+// Maximum size in bytes of a contact name in the address book
+pub const MAX_CONTACT_NAME_SIZE: usize = 64;
+// Maximum size in bytes of a contact note, it is stored with a one byte length
+pub const MAX_CONTACT_NOTE_SIZE: usize = 255;
+// Maximum size in bytes of a transaction label, it is stored with a one byte length
+pub const MAX_TRANSACTION_LABEL_SIZE: usize = 255;
+// Maximum size in bytes of a transaction label category
+pub const MAX_TRANSACTION_CATEGORY_SIZE: usize = 64;
+// Topoheights without a pending transaction executed before broadcasting it again
+pub const PENDING_TX_REBROADCAST_TOPOHEIGHTS: u64 = 10;
+// Broadcasts of a pending transaction before considering it dropped
+pub const MAX_PENDING_TX_REBROADCASTS: u8 = 5;
+// Timeout in seconds for a request to the price API of a history export
+pub const PRICE_PROVIDER_TIMEOUT_SECS: u64 = 10;
+// Interval in seconds between each inactivity check of the auto lock
+pub const AUTO_LOCK_CHECK_INTERVAL_SECS: u64 = 5;
+// Save the progress of a blocks sync every N topoheights scanned
+// An interrupted rescan is resumed from the last one saved
+pub const SYNC_CHECKPOINT_INTERVAL: u64 = 1000;
 
 lazy_static! {
     pub static ref PASSWORD_ALGORITHM: Argon2<'static> = {
@@ -9,7 +9,8 @@ use xelis_common::{
         WebSocketJsonRPCClient,
         WebSocketJsonRPCClientImpl,
         JsonRPCResult,
-        EventReceiver
+        EventReceiver,
+        TlsConfig
     },
     api::daemon::{
         GetBalanceResult,
@@ -22,6 +23,10 @@ use xelis_common::{
         GetTransactionParams,
         GetNonceParams,
         GetNonceResult,
+        ReserveNonceParams,
+        RenewNonceReservationParams,
+        ReleaseNonceReservationParams,
+        NonceReservationResult,
         GetAssetsParams,
         IsTxExecutedInBlockParams,
         NotifyEvent,
@@ -36,7 +41,9 @@ use xelis_common::{
         IsAccountRegisteredParams,
         TransactionOrphanedEvent,
         GetTransactionExecutorParams,
-        GetTransactionExecutorResult
+        GetTransactionExecutorResult,
+        GetAccountHistoryParams,
+        AccountHistoryEntry
     },
     account::VersionedBalance,
     crypto::{
@@ -57,7 +64,13 @@ pub struct DaemonAPI {
 
 impl DaemonAPI {
     pub async fn new(daemon_address: String) -> Result<Self> {
-        let client = WebSocketJsonRPCClientImpl::new(daemon_address).await?;
+        Self::with_tls(daemon_address, &TlsConfig::default()).await
+    }
+
+    // Verify the daemon certificate with a custom CA bundle or a pinned fingerprint
+    // The connection is refused on mismatch
+    pub async fn with_tls(daemon_address: String, tls: &TlsConfig) -> Result<Self> {
+        let client = WebSocketJsonRPCClientImpl::new_with_tls(daemon_address, tls).await?;
         Ok(Self {
             client
         })
@@ -88,6 +101,11 @@ impl DaemonAPI {
         self.client.on_connection_lost().await
     }
 
+    // On reconnection, once the events are subscribed again
+    pub async fn on_reconnected(&self) -> broadcast::Receiver<()> {
+        self.client.on_reconnected().await
+    }
+
     pub async fn call<P: Serialize>(&self, method: &String, params: &P) -> JsonRPCResult<Value> {
         self.client.call_with(method.as_str(), params).await
     }
@@ -173,6 +191,18 @@ impl DaemonAPI {
         Ok(balance)
     }
 
+    // Retrieve the history of an account for an asset, ordered from the highest topoheight to the lowest
+    // Daemon returns a limited number of balance versions per call, use maximum topoheight to paginate
+    pub async fn get_account_history(&self, address: &Address, asset: &Hash, minimum_topoheight: Option<u64>, maximum_topoheight: Option<u64>) -> Result<Vec<AccountHistoryEntry>> {
+        let history = self.client.call_with("get_account_history", &GetAccountHistoryParams {
+            address: address.clone(),
+            asset: asset.clone(),
+            minimum_topoheight,
+            maximum_topoheight
+        }).await.context("Error while retrieving account history")?;
+        Ok(history)
+    }
+
     pub async fn get_block_at_topoheight(&self, topoheight: u64) -> Result<BlockResponse> {
         let block = self.client.call_with("get_block_at_topoheight", &GetBlockAtTopoHeightParams {
             topoheight,
@@ -217,6 +247,32 @@ impl DaemonAPI {
         Ok(nonce)
     }
 
+    // Reserve the next nonce of the address for the duration in seconds (daemon default if none)
+    pub async fn reserve_nonce(&self, address: &Address, duration: Option<u64>) -> Result<NonceReservationResult> {
+        let reservation = self.client.call_with("reserve_nonce", &ReserveNonceParams {
+            address: Cow::Borrowed(address),
+            duration
+        }).await.context(format!("Error while reserving nonce for address {}", address))?;
+        Ok(reservation)
+    }
+
+    pub async fn renew_nonce_reservation(&self, address: &Address, id: u64, duration: Option<u64>) -> Result<NonceReservationResult> {
+        let reservation = self.client.call_with("renew_nonce_reservation", &RenewNonceReservationParams {
+            address: Cow::Borrowed(address),
+            id,
+            duration
+        }).await.context(format!("Error while renewing nonce reservation {} for address {}", id, address))?;
+        Ok(reservation)
+    }
+
+    pub async fn release_nonce_reservation(&self, address: &Address, id: u64) -> Result<()> {
+        let _: bool = self.client.call_with("release_nonce_reservation", &ReleaseNonceReservationParams {
+            address: Cow::Borrowed(address),
+            id
+        }).await.context(format!("Error while releasing nonce reservation {} for address {}", id, address))?;
+        Ok(())
+    }
+
     pub async fn is_tx_executed_in_block(&self, tx_hash: &Hash, block_hash: &Hash) -> Result<bool> {
         let is_executed = self.client.call_with("is_tx_executed_in_block", &IsTxExecutedInBlockParams {
             tx_hash: Cow::Borrowed(tx_hash),
@@ -5,7 +5,8 @@ use xelis_common::{
             TransactionEntry as RPCTransactionEntry,
             EntryType as RPCEntryType,
             TransferIn as RPCTransferIn,
-            TransferOut as RPCTransferOut
+            TransferOut as RPCTransferOut,
+            TransactionDirection
         }
     },
     config::XELIS_ASSET,
@@ -27,7 +28,7 @@ use xelis_common::{
 use anyhow::Result;
 use crate::storage::EncryptedStorage;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serializer)]
 pub struct TransferOut {
     // Destination key
     destination: PublicKey,
@@ -39,7 +40,7 @@ pub struct TransferOut {
     extra_data: Option<DataElement>
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serializer)]
 pub struct TransferIn {
     // Asset used
     asset: Hash,
@@ -99,61 +100,6 @@ impl TransferIn {
     }
 }
 
-impl Serializer for TransferOut {
-    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
-        let destination = PublicKey::read(reader)?;
-        let asset = reader.read_hash()?;
-        let amount = reader.read_u64()?;
-
-        let extra_data = Option::read(reader)?;
-
-        Ok(Self {
-            destination,
-            asset,
-            amount,
-            extra_data
-        })
-    }
-
-    fn write(&self, writer: &mut Writer) {
-        self.destination.write(writer);
-        writer.write_hash(&self.asset);
-        writer.write_u64(&self.amount);
-
-        self.extra_data.write(writer);
-    }
-
-    fn size(&self) -> usize {
-        self.destination.size() + self.asset.size() + self.amount.size() + self.extra_data.size()
-    }
-}
-
-impl Serializer for TransferIn {
-    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
-        let asset = reader.read_hash()?;
-        let amount = reader.read_u64()?;
-
-        let extra_data = Option::read(reader)?;
-
-        Ok(Self {
-            asset,
-            amount,
-            extra_data
-        })
-    }
-
-    fn write(&self, writer: &mut Writer) {
-        writer.write_hash(&self.asset);
-        writer.write_u64(&self.amount);
-
-        self.extra_data.write(writer);
-    }
-
-    fn size(&self) -> usize {
-        self.asset.size() + self.amount.size() + self.extra_data.size()
-    }
-}
-
 #[derive(Debug, Clone)]
 pub enum EntryData {
     // Coinbase is only XELIS_ASSET
@@ -164,6 +110,43 @@ pub enum EntryData {
         asset: Hash,
         amount: u64
     },
+    // Asset registered by us, its hash is the transaction hash
+    AssetCreation {
+        asset: Hash,
+        name: String,
+        decimals: u8,
+        max_supply: u64
+    },
+    // Units minted by us as the asset owner
+    AssetMint {
+        asset: Hash,
+        amount: u64
+    },
+    // Ownership of the asset was transferred from/to us
+    AssetOwnershipTransfer {
+        asset: Hash,
+        from: PublicKey,
+        to: PublicKey
+    },
+    // Multisig of our account was set up or deleted
+    MultiSig {
+        participants: Vec<PublicKey>,
+        threshold: u8
+    },
+    // Locked transfer claimed or refunded by us
+    Unlock {
+        id: Hash,
+        asset: Hash
+    },
+    // Contract deployed by us
+    DeployContract {
+        contract: Hash
+    },
+    // Contract invoked by us, max gas is always paid
+    InvokeContract {
+        contract: Hash,
+        max_gas: u64
+    },
     Incoming {
         from: PublicKey,
         transfers: Vec<TransferIn>
@@ -207,7 +190,42 @@ impl Serializer for EntryData {
                 let nonce = reader.read_u64()?;
 
                 Self::Outgoing { transfers, fee, nonce }
-            }
+            },
+            4 => Self::AssetCreation {
+                asset: reader.read_hash()?,
+                name: reader.read_string()?,
+                decimals: reader.read_u8()?,
+                max_supply: reader.read_u64()?
+            },
+            5 => Self::AssetMint {
+                asset: reader.read_hash()?,
+                amount: reader.read_u64()?
+            },
+            6 => Self::AssetOwnershipTransfer {
+                asset: reader.read_hash()?,
+                from: PublicKey::read(reader)?,
+                to: PublicKey::read(reader)?
+            },
+            7 => {
+                let threshold = reader.read_u8()?;
+                let len = reader.read_u8()?;
+                let mut participants = Vec::with_capacity(len as usize);
+                for _ in 0..len {
+                    participants.push(PublicKey::read(reader)?);
+                }
+                Self::MultiSig { participants, threshold }
+            },
+            8 => Self::Unlock {
+                id: reader.read_hash()?,
+                asset: reader.read_hash()?
+            },
+            9 => Self::DeployContract {
+                contract: reader.read_hash()?
+            },
+            10 => Self::InvokeContract {
+                contract: reader.read_hash()?,
+                max_gas: reader.read_u64()?
+            },
             _ => return Err(ReaderError::InvalidValue)
         }) 
     }
@@ -239,6 +257,46 @@ impl Serializer for EntryData {
                 }
                 writer.write_u64(fee);
                 writer.write_u64(nonce);
+            },
+            Self::AssetCreation { asset, name, decimals, max_supply } => {
+                writer.write_u8(4);
+                writer.write_hash(asset);
+                writer.write_string(name);
+                writer.write_u8(*decimals);
+                writer.write_u64(max_supply);
+            },
+            Self::AssetMint { asset, amount } => {
+                writer.write_u8(5);
+                writer.write_hash(asset);
+                writer.write_u64(amount);
+            },
+            Self::AssetOwnershipTransfer { asset, from, to } => {
+                writer.write_u8(6);
+                writer.write_hash(asset);
+                from.write(writer);
+                to.write(writer);
+            },
+            Self::MultiSig { participants, threshold } => {
+                writer.write_u8(7);
+                writer.write_u8(*threshold);
+                writer.write_u8(participants.len() as u8);
+                for participant in participants {
+                    participant.write(writer);
+                }
+            },
+            Self::Unlock { id, asset } => {
+                writer.write_u8(8);
+                writer.write_hash(id);
+                writer.write_hash(asset);
+            },
+            Self::DeployContract { contract } => {
+                writer.write_u8(9);
+                writer.write_hash(contract);
+            },
+            Self::InvokeContract { contract, max_gas } => {
+                writer.write_u8(10);
+                writer.write_hash(contract);
+                writer.write_u64(max_gas);
             }
         }
     }
@@ -247,6 +305,13 @@ impl Serializer for EntryData {
         1 + match &self {
             Self::Coinbase { reward } => reward.size(),
             Self::Burn { asset, amount } => asset.size() + amount.size(),
+            Self::AssetCreation { asset, name, decimals, max_supply } => asset.size() + name.size() + decimals.size() + max_supply.size(),
+            Self::AssetMint { asset, amount } => asset.size() + amount.size(),
+            Self::AssetOwnershipTransfer { asset, from, to } => asset.size() + from.size() + to.size(),
+            Self::MultiSig { participants, threshold } => threshold.size() + 1 + participants.iter().map(|p| p.size()).sum::<usize>(),
+            Self::Unlock { id, asset } => id.size() + asset.size(),
+            Self::DeployContract { contract } => contract.size(),
+            Self::InvokeContract { contract, max_gas } => contract.size() + max_gas.size(),
             Self::Incoming { from, transfers } => {
                 from.size() + 2 + transfers.iter().map(|t| t.size()).sum::<usize>()
             },
@@ -257,7 +322,7 @@ impl Serializer for EntryData {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serializer)]
 pub struct TransactionEntry {
     hash: Hash,
     topoheight: u64,
@@ -289,6 +354,33 @@ impl TransactionEntry {
         &mut self.entry
     }
 
+    // Direction of the funds for the owner of the wallet
+    // Actions paid by us without any amount received are outgoing
+    pub fn get_direction(&self, owner: &PublicKey) -> TransactionDirection {
+        match &self.entry {
+            EntryData::Coinbase { .. }
+            | EntryData::AssetMint { .. }
+            | EntryData::Unlock { .. }
+            | EntryData::Incoming { .. } => TransactionDirection::Incoming,
+            EntryData::AssetOwnershipTransfer { to, .. } if to == owner => TransactionDirection::Incoming,
+            _ => TransactionDirection::Outgoing
+        }
+    }
+
+    // Assets moved by this entry with their amounts if known
+    pub fn get_assets_amounts(&self) -> Vec<(&Hash, Option<u64>)> {
+        match &self.entry {
+            EntryData::Coinbase { reward } => vec![(&XELIS_ASSET, Some(*reward))],
+            EntryData::Burn { asset, amount } | EntryData::AssetMint { asset, amount } => vec![(asset, Some(*amount))],
+            EntryData::AssetCreation { asset, .. }
+            | EntryData::AssetOwnershipTransfer { asset, .. }
+            | EntryData::Unlock { asset, .. } => vec![(asset, None)],
+            EntryData::Incoming { transfers, .. } => transfers.iter().map(|t| (t.get_asset(), Some(t.get_amount()))).collect(),
+            EntryData::Outgoing { transfers, .. } => transfers.iter().map(|t| (t.get_asset(), Some(t.get_amount()))).collect(),
+            EntryData::MultiSig { .. } | EntryData::DeployContract { .. } | EntryData::InvokeContract { .. } => Vec::new()
+        }
+    }
+
     // Convert to RPC Transaction Entry
     // This is a necessary step to serialize correctly the public key into an address
     pub fn serializable(self, mainnet: bool) -> RPCTransactionEntry {
@@ -298,6 +390,13 @@ impl TransactionEntry {
             entry: match self.entry {
                 EntryData::Coinbase { reward } => RPCEntryType::Coinbase { reward },
                 EntryData::Burn { asset, amount } => RPCEntryType::Burn { asset, amount },
+                EntryData::AssetCreation { asset, name, decimals, max_supply } => RPCEntryType::AssetCreation { asset, name, decimals, max_supply },
+                EntryData::AssetMint { asset, amount } => RPCEntryType::AssetMint { asset, amount },
+                EntryData::AssetOwnershipTransfer { asset, from, to } => RPCEntryType::AssetOwnershipTransfer { asset, from: from.to_address(mainnet), to: to.to_address(mainnet) },
+                EntryData::MultiSig { participants, threshold } => RPCEntryType::MultiSig { participants: participants.into_iter().map(|p| p.to_address(mainnet)).collect(), threshold },
+                EntryData::Unlock { id, asset } => RPCEntryType::Unlock { id, asset },
+                EntryData::DeployContract { contract } => RPCEntryType::DeployContract { contract },
+                EntryData::InvokeContract { contract, max_gas } => RPCEntryType::InvokeContract { contract, max_gas },
                 EntryData::Incoming { from, transfers } => {
                     let transfers = transfers.into_iter().map(|t| RPCTransferIn {
                         asset: t.asset,
@@ -326,6 +425,27 @@ impl TransactionEntry {
                 let decimals = storage.get_asset_decimals(asset)?;
                 format!("Burn {} of {}", format_coin(*amount, decimals), asset)
             },
+            EntryData::AssetCreation { asset, name, decimals, max_supply } => {
+                format!("Create asset {} ({}) with {} decimals and a maximum supply of {}", name, asset, decimals, format_coin(*max_supply, *decimals))
+            },
+            EntryData::AssetMint { asset, amount } => {
+                let decimals = storage.get_asset_decimals(asset)?;
+                format!("Mint {} of {}", format_coin(*amount, decimals), asset)
+            },
+            EntryData::AssetOwnershipTransfer { asset, from, to } => {
+                format!("Ownership of asset {} transferred from {} to {}", asset, from.as_address(mainnet), to.as_address(mainnet))
+            },
+            EntryData::MultiSig { participants, threshold } => {
+                if participants.is_empty() {
+                    "Multisig deleted".to_owned()
+                } else {
+                    let participants = participants.iter().map(|p| p.as_address(mainnet).to_string()).collect::<Vec<_>>();
+                    format!("Multisig set up with threshold {} for participants {}", threshold, participants.join(", "))
+                }
+            },
+            EntryData::Unlock { id, asset } => format!("Unlock locked transfer {} of {}", id, asset),
+            EntryData::DeployContract { contract } => format!("Deploy contract {}", contract),
+            EntryData::InvokeContract { contract, max_gas } => format!("Invoke contract {} with {} XELIS of gas", contract, format_xelis(*max_gas)),
             EntryData::Incoming { from, transfers } => {
                 let mut str = String::new();
                 for transfer in transfers {
@@ -356,30 +476,6 @@ impl TransactionEntry {
     }
 }
 
-impl Serializer for TransactionEntry {
-    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
-        let hash = reader.read_hash()?;
-        let topoheight = reader.read_u64()?;
-        let entry = EntryData::read(reader)?;
-
-        Ok(Self {
-            hash,
-            topoheight,
-            entry
-        })
-    }
-
-    fn write(&self, writer: &mut Writer) {
-        writer.write_hash(&self.hash);
-        writer.write_u64(&self.topoheight);
-        self.entry.write(writer);
-    }
-
-    fn size(&self) -> usize {
-        self.hash.size() + self.topoheight.size() + self.entry.size()
-    }
-}
-
 
 pub enum Transfer<'a> {
     In(&'a mut TransferIn),
@@ -1,8 +1,13 @@
 use thiserror::Error;
 use chacha20poly1305::Error as CryptoError;
-use super::network_handler::NetworkError;
+use super::{network_handler::NetworkError, signer::SignerError};
 use xelis_common::{
-    crypto::Hash,
+    crypto::{
+        proofs::ProofGenerationError,
+        Hash,
+        PaymentRequestError
+    },
+    network::Network,
     rpc_server::InternalRpcError,
     transaction::extra_data::CipherFormatError,
     utils::{format_coin, format_xelis}
@@ -82,6 +87,74 @@ pub enum WalletError {
     CiphertextDecode,
     #[error(transparent)]
     AEADCipherFormatError(#[from] CipherFormatError),
+    #[error("Invalid webhook URL: {}", _0)]
+    InvalidWebhookUrl(String),
+    #[error("Unsigned transaction source is not this wallet")]
+    InvalidUnsignedTransactionSource,
+    #[error("Account {} was not found in this wallet", _0)]
+    AccountNotFound(u32),
+    #[error("Signer public key doesn't match the account selected")]
+    InvalidSignerPublicKey,
+    #[error(transparent)]
+    Signer(#[from] SignerError),
+    #[error("Contact {} already exists", _0)]
+    ContactAlreadyExists(String),
+    #[error("Contact {} was not found", _0)]
+    ContactNotFound(String),
+    #[error("Invalid contact name, it must be between 1 and {} bytes and not be an address", _0)]
+    InvalidContactName(usize),
+    #[error("Contact note is too long, expected maximum {} bytes but got {} bytes", _0, _1)]
+    ContactNoteTooLong(usize, usize),
+    #[error("Contact address is not on the wallet network")]
+    InvalidContactNetwork,
+    #[error("Wallet is locked, it must be unlocked with its password")]
+    WalletLocked,
+    #[error("Wallet storage version {} is not supported, expected maximum version {}", _0, _1)]
+    UnsupportedStorageVersion(u32, u32),
+    #[error("Backup file {} already exists", _0)]
+    BackupFileExists(String),
+    #[error("Invalid backup file")]
+    InvalidBackupFile,
+    #[error("Backup version {} is not supported, expected version {}", _0, _1)]
+    UnsupportedBackupVersion(u8, u8),
+    #[error("Backup was made on network {}", _0)]
+    InvalidBackupNetwork(Network),
+    #[error("Invalid batch line {}: {}", _0, _1)]
+    InvalidBatchLine(usize, String),
+    #[error("Batch doesn't contain any payment")]
+    EmptyBatch,
+    #[error("Too many transfers: {}, maximum is {}", _0, _1)]
+    TooManyTransfers(usize, usize),
+    #[error("Total amount sent for asset {} is too high", _0)]
+    TransfersTotalOverflow(Hash),
+    #[error("Invalid scheduled payment: {}", _0)]
+    InvalidScheduledPayment(&'static str),
+    #[error("Invalid webhook secret, it must be between 1 and 255 bytes")]
+    InvalidWebhookSecret,
+    #[error("Error while generating the balance proof: {}", _0)]
+    BalanceProofGeneration(#[from] ProofGenerationError),
+    #[error("Invalid payment request: {}", _0)]
+    PaymentRequest(#[from] PaymentRequestError),
+    #[error("Invalid payment request: {}", _0)]
+    InvalidPaymentRequest(&'static str),
+    #[error("Asset {} was not found", _0)]
+    AssetNotFound(Hash),
+    #[error("Transaction {} was not found", _0)]
+    TransactionNotFound(Hash),
+    #[error("Transaction label must be between 1 and {} bytes", _0)]
+    InvalidTransactionLabel(usize),
+    #[error("Transaction category must be between 1 and {} bytes", _0)]
+    InvalidTransactionCategory(usize),
+    #[error("Transaction {} has no label", _0)]
+    TransactionLabelNotFound(Hash),
+    #[error("Nonce {} is desynchronized from the daemon which expects {}, wait for the transactions in its mempool to be executed", _0, _1)]
+    NonceDesynchronized(u64, u64),
+    #[error("Transaction {} is not pending", _0)]
+    PendingTransactionNotFound(Hash),
+    #[error("Fee of transaction {} can't be bumped while other transactions are pending", _0)]
+    FeeBumpNotPossible(Hash),
+    #[error("New fee {} must be at least {}", _0, _1)]
+    FeeTooLowForBump(u64, u64),
 }
 
 impl WalletError {
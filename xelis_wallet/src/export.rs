@@ -0,0 +1,166 @@
+use std::time::Duration;
+use async_trait::async_trait;
+use anyhow::{Context, Error};
+use xelis_common::{
+    api::wallet::{HistoryDirection, HistoryRecord},
+    config::{COIN_DECIMALS, XELIS_ASSET},
+    crypto::{Address, Hash, PublicKey},
+    time::TimestampMillis,
+    utils::format_coin
+};
+use crate::{
+    config::PRICE_PROVIDER_TIMEOUT_SECS,
+    entry::{EntryData, TransactionEntry}
+};
+
+// Provide the price of an asset at a given time for the fiat valuation of the history
+#[async_trait]
+pub trait PriceProvider: Send + Sync {
+    // Currency of the prices returned
+    fn get_currency(&self) -> &str;
+
+    // Price of one unit of the asset, None if it is unknown
+    async fn get_price(&self, asset: &Hash, timestamp: TimestampMillis) -> Result<Option<f64>, Error>;
+}
+
+// Price provider calling an HTTP API
+// The asset, timestamp and currency are sent as query parameters
+// and the API must answer with the price as a JSON number, or null if unknown
+pub struct HttpPriceProvider {
+    client: reqwest::Client,
+    url: String,
+    currency: String
+}
+
+impl HttpPriceProvider {
+    pub fn new(url: String, currency: String) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url,
+            currency
+        }
+    }
+}
+
+#[async_trait]
+impl PriceProvider for HttpPriceProvider {
+    fn get_currency(&self) -> &str {
+        &self.currency
+    }
+
+    async fn get_price(&self, asset: &Hash, timestamp: TimestampMillis) -> Result<Option<f64>, Error> {
+        let price = self.client.get(&self.url)
+            .query(&[("asset", asset.to_string()), ("timestamp", timestamp.to_string()), ("currency", self.currency.clone())])
+            .timeout(Duration::from_secs(PRICE_PROVIDER_TIMEOUT_SECS))
+            .send().await?
+            .error_for_status()?
+            .json().await
+            .context("Invalid price returned")?;
+
+        Ok(price)
+    }
+}
+
+// Split an entry in one record per asset movement
+// Decimals, timestamp and fiat values are set by the caller
+pub fn to_records(entry: TransactionEntry, owner: &PublicKey, mainnet: bool) -> Vec<HistoryRecord> {
+    let hash = entry.get_hash().clone();
+    let topoheight = entry.get_topoheight();
+    let record = |kind: &str, direction: HistoryDirection, asset: Option<Hash>, amount: u64, fee: u64, counterparty: Option<Address>| HistoryRecord {
+        hash: hash.clone(),
+        topoheight,
+        timestamp: None,
+        kind: kind.to_owned(),
+        direction,
+        asset,
+        amount,
+        decimals: 0,
+        fee,
+        counterparty,
+        fiat_price: None,
+        fiat_value: None
+    };
+
+    match entry.get_entry().clone() {
+        EntryData::Coinbase { reward } => vec![record("coinbase", HistoryDirection::Incoming, Some(XELIS_ASSET), reward, 0, None)],
+        EntryData::Burn { asset, amount } => vec![record("burn", HistoryDirection::Outgoing, Some(asset), amount, 0, None)],
+        EntryData::AssetCreation { asset, .. } => vec![record("asset_creation", HistoryDirection::Outgoing, Some(asset), 0, 0, None)],
+        EntryData::AssetMint { asset, amount } => vec![record("asset_mint", HistoryDirection::Incoming, Some(asset), amount, 0, None)],
+        EntryData::AssetOwnershipTransfer { asset, from, to } => if from == *owner {
+            vec![record("asset_ownership_transfer", HistoryDirection::Outgoing, Some(asset), 0, 0, Some(to.to_address(mainnet)))]
+        } else {
+            vec![record("asset_ownership_transfer", HistoryDirection::Incoming, Some(asset), 0, 0, Some(from.to_address(mainnet)))]
+        },
+        EntryData::MultiSig { .. } => vec![record("multisig", HistoryDirection::Outgoing, None, 0, 0, None)],
+        EntryData::Unlock { asset, .. } => vec![record("unlock", HistoryDirection::Incoming, Some(asset), 0, 0, None)],
+        EntryData::DeployContract { .. } => vec![record("deploy_contract", HistoryDirection::Outgoing, None, 0, 0, None)],
+        EntryData::InvokeContract { max_gas, .. } => vec![record("invoke_contract", HistoryDirection::Outgoing, Some(XELIS_ASSET), max_gas, 0, None)],
+        EntryData::Incoming { from, transfers } => transfers.into_iter()
+            .map(|transfer| record("incoming", HistoryDirection::Incoming, Some(transfer.get_asset().clone()), transfer.get_amount(), 0, Some(from.as_address(mainnet))))
+            .collect(),
+        EntryData::Outgoing { transfers, fee, .. } => {
+            let mut records: Vec<HistoryRecord> = transfers.into_iter()
+                .enumerate()
+                .map(|(i, transfer)| {
+                    // Fee is counted once per transaction
+                    let fee = if i == 0 { fee } else { 0 };
+                    record("outgoing", HistoryDirection::Outgoing, Some(transfer.get_asset().clone()), transfer.get_amount(), fee, Some(transfer.get_destination().as_address(mainnet)))
+                })
+                .collect();
+
+            if records.is_empty() {
+                records.push(record("outgoing", HistoryDirection::Outgoing, None, 0, fee, None));
+            }
+            records
+        }
+    }
+}
+
+// Write the records in CSV format with a header line
+// Amounts are written with the decimals of their asset
+pub fn to_csv(records: &[HistoryRecord], currency: Option<&str>) -> String {
+    let mut csv = String::from("hash,topoheight,timestamp,kind,direction,asset,amount,fee,counterparty");
+    if let Some(currency) = currency {
+        csv.push_str(&format!(",price_{},value_{}", escape_csv(currency), escape_csv(currency)));
+    }
+    csv.push('\n');
+
+    for record in records {
+        let direction = match record.direction {
+            HistoryDirection::Incoming => "incoming",
+            HistoryDirection::Outgoing => "outgoing"
+        };
+        let fields = [
+            record.hash.to_string(),
+            record.topoheight.to_string(),
+            record.timestamp.map(|t| t.to_string()).unwrap_or_default(),
+            escape_csv(&record.kind),
+            direction.to_owned(),
+            record.asset.as_ref().map(|a| a.to_string()).unwrap_or_default(),
+            format_coin(record.amount, record.decimals),
+            format_coin(record.fee, COIN_DECIMALS),
+            record.counterparty.as_ref().map(|a| a.to_string()).unwrap_or_default()
+        ];
+        csv.push_str(&fields.join(","));
+
+        if currency.is_some() {
+            csv.push_str(&format!(
+                ",{},{}",
+                record.fiat_price.map(|p| p.to_string()).unwrap_or_default(),
+                record.fiat_value.map(|v| v.to_string()).unwrap_or_default()
+            ));
+        }
+        csv.push('\n');
+    }
+
+    csv
+}
+
+// Quote a field if it contains a separator, a quote or a new line
+fn escape_csv(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
@@ -8,6 +8,15 @@ pub mod entry;
 pub mod mnemonics;
 pub mod transaction_builder;
 pub mod error;
+pub mod alerts;
+pub mod export;
+pub mod backup;
+pub mod batch;
+pub mod scheduler;
+pub mod webhooks;
+pub mod checkpoint;
+pub mod pending;
+pub mod signer;
 
 #[cfg(feature = "api_server")]
 pub mod api;
\ No newline at end of file
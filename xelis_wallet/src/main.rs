@@ -1,25 +1,29 @@
 use std::{
-    ops::ControlFlow,
+    fs,
     path::Path,
     sync::Arc,
-    time::Duration
+    thread,
+    time::{Duration, Instant}
 };
 use anyhow::{Result, Context};
 use fern::colors::Color;
 use log::{error, info};
 use clap::Parser;
 use xelis_common::{
+    api::wallet::{Contact, ContactEntry, HistoryRange, PaymentTrigger, ScheduledPayment},
     async_handler,
     config::{
-        COIN_DECIMALS,
+        ASSET_REGISTRATION_FEE,
         VERSION,
         XELIS_ASSET
     },
     crypto::{
-        ecdlp,
+        vanity::{VanityProgress, VanitySearch},
         Address,
+        Hash,
         Hashable
     },
+    json_rpc::TlsConfig,
     network::Network,
     prompt::{
         argument::{
@@ -37,12 +41,21 @@ use xelis_common::{
         Prompt,
         PromptError
     },
+    contract::{Program, MAX_CONTRACT_PARAMETERS},
     serializer::Serializer,
     transaction::{
-        builder::{FeeBuilder, TransactionTypeBuilder, TransferBuilder},
+        builder::{AssetOwnershipBuilder, FeeBuilder, MultiSigBuilder, TransactionTypeBuilder, TransferBuilder},
+        lock::{get_lock_id, Preimage, TransferLock},
+        AssetCreationPayload,
         BurnPayload,
-        Transaction
+        DeployContractPayload,
+        InvokeContractPayload,
+        MintAssetPayload,
+        Transaction,
+        UnlockPayload,
+        MAX_ASSET_DECIMALS
     },
+    time::get_current_time_in_seconds,
     utils::{
         format_coin,
         format_xelis
@@ -51,9 +64,15 @@ use xelis_common::{
 #[cfg(feature = "api_server")]
 use xelis_common::utils::spawn_task;
 use xelis_wallet::{
-    wallet::Wallet,
-    config::{DEFAULT_DAEMON_ADDRESS, DIR_PATH}
+    batch,
+    entry::{EntryData, TransactionEntry},
+    export::{self, HttpPriceProvider, PriceProvider},
+    wallet::{Wallet, LogProgressTableGenerationReportFunction},
+    mnemonics,
+    config::{DEFAULT_DAEMON_ADDRESS, DIR_PATH, VANITY_PROGRESS_INTERVAL}
 };
+#[cfg(feature = "ledger")]
+use xelis_wallet::signer::ledger::LedgerSigner;
 
 #[cfg(feature = "api_server")]
 use {
@@ -66,6 +85,7 @@ use {
         wallet::XSWDEvent,
     },
     xelis_common::{
+        api::wallet::XSWDLimits,
         rpc_server::RpcRequest,
         prompt::ShareablePrompt
     },
@@ -97,6 +117,16 @@ pub struct Config {
     /// Daemon address to use
     #[clap(long, default_value_t = String::from(DEFAULT_DAEMON_ADDRESS))]
     daemon_address: String,
+    /// PEM file of the certificate authorities trusted for a wss:// daemon
+    /// 
+    /// It replaces the default authorities.
+    #[clap(long)]
+    daemon_ca_bundle: Option<String>,
+    /// SHA-256 fingerprint in hex of the wss:// daemon certificate
+    /// 
+    /// Only this certificate is accepted, the connection is refused otherwise.
+    #[clap(long)]
+    daemon_cert_fingerprint: Option<String>,
     /// Disable online mode
     #[clap(long)]
     offline_mode: bool,
@@ -144,9 +174,25 @@ pub struct Config {
     /// Restore wallet using seed
     #[clap(long)]
     seed: Option<String>,
+    /// Passphrase combined with the seed when creating a wallet
+    /// 
+    /// Each passphrase gives an independent wallet from the same seed.
+    /// It is required with the seed to recover the wallet.
+    #[clap(long)]
+    seed_passphrase: Option<String>,
     /// Network selected for chain
     #[clap(long, value_enum, default_value_t = Network::Mainnet)]
     network: Network,
+    /// Lock the wallet after this many minutes without using its keys
+    /// 
+    /// The password is then required to use them again.
+    #[clap(long)]
+    auto_lock: Option<u64>,
+    /// Encrypt the extra data of the transfers with the authenticated format
+    /// 
+    /// Receivers must run a wallet version able to decrypt it.
+    #[clap(long)]
+    authenticated_extra_data: bool,
     /// RPC Server configuration
     #[cfg(feature = "api_server")]
     #[structopt(flatten)]
@@ -154,17 +200,13 @@ pub struct Config {
     /// XSWD Server configuration
     #[cfg(feature = "api_server")]
     #[clap(long)]
-    enable_xswd: bool
-}
-
-/// This struct is used to log the progress of the table generation
-struct LogProgressTableGenerationReportFunction;
-
-impl ecdlp::ProgressTableGenerationReportFunction for LogProgressTableGenerationReportFunction {
-    fn report(&self, progress: f64, step: ecdlp::ReportStep) -> ControlFlow<()> {
-        info!("Progress: {:.2}% on step {:?}", progress * 100.0, step);
-        ControlFlow::Continue(())
-    }
+    enable_xswd: bool,
+    /// Expose the metrics of the wallet in the Prometheus format
+    /// 
+    /// They are served on the /metrics route of the RPC Server or XSWD.
+    #[cfg(feature = "api_server")]
+    #[clap(long)]
+    enable_api_metrics: bool
 }
 
 #[tokio::main]
@@ -198,6 +240,7 @@ async fn main() -> Result<()> {
     command_manager.store_in_context(config.network)?;
 
     command_manager.register_default_commands()?;
+    command_manager.add_command(Command::with_optional_arguments("vanity", "Generate a new seed with an address matching a prefix and/or suffix", vec![Arg::new("threads", ArgType::Number)], CommandHandler::Async(async_handler!(vanity))))?;
 
     if let Some(path) = config.wallet_path {
         // read password from option or ask him
@@ -214,7 +257,7 @@ async fn main() -> Result<()> {
             Wallet::open(path, password, config.network, precomputed_tables)?
         } else {
             info!("Creating a new wallet at {}", path);
-            Wallet::create(path, password, config.seed, config.network, precomputed_tables)?
+            Wallet::create(path, password, config.seed, config.seed_passphrase, config.network, precomputed_tables)?
         };
 
         apply_config(&wallet, #[cfg(feature = "api_server")] &prompt).await;
@@ -223,6 +266,7 @@ async fn main() -> Result<()> {
         command_manager.add_command(Command::new("open", "Open a wallet", CommandHandler::Async(async_handler!(open_wallet))))?;
         command_manager.add_command(Command::new("create", "Create a new wallet", CommandHandler::Async(async_handler!(create_wallet))))?;
         command_manager.add_command(Command::new("recover", "Recover a wallet using a seed", CommandHandler::Async(async_handler!(recover_wallet))))?;
+        command_manager.add_command(Command::with_required_arguments("restore", "Restore a wallet from a backup file", vec![Arg::new("path", ArgType::String)], CommandHandler::Async(async_handler!(restore_wallet))))?;
 
         // Display available commands
         command_manager.display_commands()?;
@@ -314,6 +358,11 @@ async fn xswd_handle_request_permission(prompt: &ShareablePrompt, app_state: App
 async fn apply_config(wallet: &Arc<Wallet>, #[cfg(feature = "api_server")] prompt: &ShareablePrompt) {
     let config: Config = Config::parse();
 
+    wallet.set_daemon_tls_config(TlsConfig {
+        ca_bundle: config.daemon_ca_bundle,
+        pinned_fingerprint: config.daemon_cert_fingerprint
+    });
+
     if !config.offline_mode {
         info!("Trying to connect to daemon at '{}'", config.daemon_address);
         if let Err(e) = wallet.set_online_mode(&config.daemon_address, true).await {
@@ -324,6 +373,13 @@ async fn apply_config(wallet: &Arc<Wallet>, #[cfg(feature = "api_server")] promp
         }
     }
 
+    wallet.set_authenticated_extra_data(config.authenticated_extra_data);
+
+    if let Some(minutes) = config.auto_lock.filter(|minutes| *minutes > 0) {
+        info!("Wallet will be locked after {} minutes of inactivity", minutes);
+        wallet.set_auto_lock(Some(Duration::from_secs(minutes.saturating_mul(60)))).await;
+    }
+
     #[cfg(feature = "api_server")]
     {
         if config.enable_xswd && config.rpc.rpc_bind_address.is_some() {
@@ -331,6 +387,7 @@ async fn apply_config(wallet: &Arc<Wallet>, #[cfg(feature = "api_server")] promp
             return;
         }
 
+        wallet.set_api_metrics(config.enable_api_metrics);
         if let Some(address) = config.rpc.rpc_bind_address {
             let auth_config = if let (Some(username), Some(password)) = (config.rpc.rpc_username, config.rpc.rpc_password) {
                 Some(AuthConfig {
@@ -367,18 +424,50 @@ async fn setup_wallet_command_manager(wallet: Arc<Wallet>, command_manager: &Com
 
     // Add wallet commands
     command_manager.add_command(Command::new("change_password", "Set a new password to open your wallet", CommandHandler::Async(async_handler!(change_password))))?;
+    command_manager.add_command(Command::new("lock_wallet", "Wipe your keys from memory until your password is provided again", CommandHandler::Async(async_handler!(lock_wallet))))?;
+    command_manager.add_command(Command::new("unlock_wallet", "Load your keys again using your password", CommandHandler::Async(async_handler!(unlock_wallet))))?;
+    command_manager.add_command(Command::with_optional_arguments("auto_lock", "Show or set the minutes of inactivity before locking your wallet, 0 to disable it", vec![Arg::new("minutes", ArgType::Number)], CommandHandler::Async(async_handler!(auto_lock))))?;
     command_manager.add_command(Command::with_optional_arguments("transfer", "Send asset to a specified address", vec![Arg::new("asset", ArgType::Hash)], CommandHandler::Async(async_handler!(transfer))))?;
     command_manager.add_command(Command::with_optional_arguments("transfer_all", "Send all your asset balance to a specified address", vec![Arg::new("asset", ArgType::Hash)], CommandHandler::Async(async_handler!(transfer_all))))?;
+    command_manager.add_command(Command::with_required_arguments("transfer_batch", "Send in one transaction all the payments of a CSV file with destination,amount[,asset] lines", vec![Arg::new("path", ArgType::String)], CommandHandler::Async(async_handler!(transfer_batch))))?;
+    command_manager.add_command(Command::new("scheduled_payments", "List all payments scheduled for the selected account", CommandHandler::Async(async_handler!(scheduled_payments))))?;
+    command_manager.add_command(Command::new("schedule_payment", "Schedule a single or recurring payment sent while the wallet is online", CommandHandler::Async(async_handler!(schedule_payment))))?;
+    command_manager.add_command(Command::with_required_arguments("remove_scheduled_payment", "Remove a scheduled payment", vec![Arg::new("id", ArgType::Number)], CommandHandler::Async(async_handler!(remove_scheduled_payment))))?;
+    command_manager.add_command(Command::with_arguments("transfer_locked", "Send asset to a specified address that can only be claimed at a topoheight or before it using a secret", vec![Arg::new("topoheight", ArgType::Number)], vec![Arg::new("hash_lock", ArgType::Bool)], CommandHandler::Async(async_handler!(transfer_locked))))?;
+    command_manager.add_command(Command::with_arguments("unlock", "Claim a locked transfer sent to us or get refunded of an expired one", vec![Arg::new("id", ArgType::Hash), Arg::new("asset", ArgType::Hash)], vec![Arg::new("preimage", ArgType::String)], CommandHandler::Async(async_handler!(unlock))))?;
+    command_manager.add_command(Command::with_required_arguments("deploy_contract", "Deploy a contract program encoded in hexadecimal", vec![Arg::new("program", ArgType::String)], CommandHandler::Async(async_handler!(deploy_contract))))?;
+    command_manager.add_command(Command::with_arguments("invoke_contract", "Invoke a contract with comma separated parameters, max gas is always paid", vec![Arg::new("contract", ArgType::Hash), Arg::new("max_gas", ArgType::Number)], vec![Arg::new("parameters", ArgType::String)], CommandHandler::Async(async_handler!(invoke_contract))))?;
     command_manager.add_command(Command::with_required_arguments("burn", "Burn amount of asset", vec![Arg::new("asset", ArgType::Hash), Arg::new("amount", ArgType::Number)], CommandHandler::Async(async_handler!(burn))))?;
+    command_manager.add_command(Command::with_required_arguments("create_asset", "Register a new asset, max supply is in atomic units", vec![Arg::new("name", ArgType::String), Arg::new("decimals", ArgType::Number), Arg::new("max_supply", ArgType::Number)], CommandHandler::Async(async_handler!(create_asset))))?;
+    command_manager.add_command(Command::new("assets", "List all assets created by this wallet", CommandHandler::Async(async_handler!(assets))))?;
+    command_manager.add_command(Command::with_required_arguments("mint_asset", "Mint new units of an asset we own, amount is in atomic units", vec![Arg::new("asset", ArgType::Hash), Arg::new("amount", ArgType::Number)], CommandHandler::Async(async_handler!(mint_asset))))?;
+    command_manager.add_command(Command::with_required_arguments("transfer_asset_ownership", "Transfer the ownership of an asset we own", vec![Arg::new("asset", ArgType::Hash), Arg::new("address", ArgType::String)], CommandHandler::Async(async_handler!(transfer_asset_ownership))))?;
+    command_manager.add_command(Command::with_required_arguments("multisig_setup", "Require signatures of participants for all transactions, participants are comma separated addresses", vec![Arg::new("threshold", ArgType::Number), Arg::new("participants", ArgType::String)], CommandHandler::Async(async_handler!(multisig_setup))))?;
+    command_manager.add_command(Command::with_required_arguments("multisig_sign", "Sign the hash of an unsigned transaction as a multisig participant", vec![Arg::new("hash", ArgType::Hash), Arg::new("id", ArgType::Number)], CommandHandler::Async(async_handler!(multisig_sign))))?;
     command_manager.add_command(Command::new("display_address", "Show your wallet address", CommandHandler::Async(async_handler!(display_address))))?;
+    command_manager.add_command(Command::new("accounts", "List all accounts derived from your seed", CommandHandler::Async(async_handler!(accounts))))?;
+    command_manager.add_command(Command::new("add_account", "Create a new account derived from your seed", CommandHandler::Async(async_handler!(add_account))))?;
+    command_manager.add_command(Command::with_required_arguments("select_account", "Select the account to use", vec![Arg::new("index", ArgType::Number)], CommandHandler::Async(async_handler!(select_account))))?;
+    #[cfg(feature = "ledger")]
+    {
+        command_manager.add_command(Command::new("use_ledger", "Sign the transactions of the selected account with a Ledger device", CommandHandler::Async(async_handler!(use_ledger))))?;
+        command_manager.add_command(Command::new("use_local_key", "Sign the transactions of the selected account with the wallet key", CommandHandler::Async(async_handler!(use_local_key))))?;
+    }
+    command_manager.add_command(Command::new("contacts", "List all contacts of your address book", CommandHandler::Async(async_handler!(contacts))))?;
+    command_manager.add_command(Command::with_required_arguments("add_contact", "Save an address in your address book, its name can be used as transfer destination", vec![Arg::new("name", ArgType::String)], CommandHandler::Async(async_handler!(add_contact))))?;
+    command_manager.add_command(Command::with_required_arguments("remove_contact", "Remove a contact from your address book", vec![Arg::new("name", ArgType::String)], CommandHandler::Async(async_handler!(remove_contact))))?;
     command_manager.add_command(Command::with_optional_arguments("balance", "List all non-zero balances or show the selected one", vec![Arg::new("asset", ArgType::Hash)], CommandHandler::Async(async_handler!(balance))))?;
     command_manager.add_command(Command::with_optional_arguments("history", "Show all your transactions", vec![Arg::new("page", ArgType::Number)], CommandHandler::Async(async_handler!(history))))?;
+    command_manager.add_command(Command::with_arguments("export_history", "Export all your transactions to a CSV file, or JSON if the path ends with .json", vec![Arg::new("path", ArgType::String)], vec![Arg::new("currency", ArgType::String), Arg::new("price_url", ArgType::String)], CommandHandler::Async(async_handler!(export_history))))?;
+    command_manager.add_command(Command::with_required_arguments("backup", "Write an encrypted backup of your wallet to a file, it can be restored with the restore command", vec![Arg::new("path", ArgType::String)], CommandHandler::Async(async_handler!(backup))))?;
     command_manager.add_command(Command::with_optional_arguments("online_mode", "Set your wallet in online mode", vec![Arg::new("daemon_address", ArgType::String)], CommandHandler::Async(async_handler!(online_mode))))?;
     command_manager.add_command(Command::new("offline_mode", "Set your wallet in offline mode", CommandHandler::Async(async_handler!(offline_mode))))?;
     command_manager.add_command(Command::with_optional_arguments("rescan", "Rescan balance and transactions", vec![Arg::new("topoheight", ArgType::Number)], CommandHandler::Async(async_handler!(rescan))))?;
     command_manager.add_command(Command::with_optional_arguments("seed", "Show seed of selected language", vec![Arg::new("language", ArgType::Number)], CommandHandler::Async(async_handler!(seed))))?;
     command_manager.add_command(Command::new("nonce", "Show current nonce", CommandHandler::Async(async_handler!(nonce))))?;
     command_manager.add_command(Command::new("set_nonce", "Set new nonce", CommandHandler::Async(async_handler!(set_nonce))))?;
+    command_manager.add_command(Command::new("resync_nonce", "Repair the nonce if it is desynchronized from the daemon", CommandHandler::Async(async_handler!(resync_nonce))))?;
+    command_manager.add_command(Command::with_arguments("bump_fee", "Replace a pending transaction with a higher fee, in atomic units, or twice its fee", vec![Arg::new("hash", ArgType::Hash)], vec![Arg::new("fee", ArgType::Number)], CommandHandler::Async(async_handler!(bump_fee))))?;
 
     #[cfg(feature = "api_server")]
     {
@@ -390,6 +479,8 @@ async fn setup_wallet_command_manager(wallet: Arc<Wallet>, command_manager: &Com
         ], CommandHandler::Async(async_handler!(start_rpc_server))))?;
 
         command_manager.add_command(Command::new("start_xswd", "Start the XSWD Server",  CommandHandler::Async(async_handler!(start_xswd))))?;
+        command_manager.add_command(Command::new("xswd_limits", "Show the limits applied to the XSWD applications", CommandHandler::Async(async_handler!(xswd_limits))))?;
+        command_manager.add_command(Command::with_optional_arguments("set_xswd_limits", "Set the limits of an XSWD application, or the default ones if no ID is set", vec![Arg::new("app_id", ArgType::String)], CommandHandler::Async(async_handler!(set_xswd_limits))))?;
 
         // Stop API Server (RPC or XSWD)
         command_manager.add_command(Command::new("stop_api_server", "Stop the API (XSWD/RPC) Server", CommandHandler::Async(async_handler!(stop_api_server))))?;
@@ -525,11 +616,14 @@ async fn create_wallet(manager: &CommandManager, _: ArgumentManager) -> Result<(
         return Ok(())
     }
 
+    let passphrase = read_seed_passphrase(manager).await?;
+    let with_passphrase = passphrase.is_some();
+
     let wallet = {
         let context = manager.get_context().lock()?;
         let network = context.get::<Network>()?;
         let precomputed_tables = Wallet::read_or_generate_precomputed_tables(None, LogProgressTableGenerationReportFunction)?;
-        Wallet::create(dir, password, None, *network, precomputed_tables)?
+        Wallet::create(dir, password, None, passphrase, *network, precomputed_tables)?
     };
  
     manager.message("Wallet sucessfully created");
@@ -537,8 +631,9 @@ async fn create_wallet(manager: &CommandManager, _: ArgumentManager) -> Result<(
 
     // Display the seed in prompt
     {
-        let seed = wallet.get_seed(0)?; // TODO language index
-        prompt.read_input(format!("Seed: {}\r\nPress ENTER to continue", seed), false)
+        let seed = wallet.get_seed(0).await?; // TODO language index
+        let note = if with_passphrase { "\r\nYour passphrase is required with this seed to recover your wallet" } else { "" };
+        prompt.read_input(format!("Seed: {}{}\r\nPress ENTER to continue", seed, note), false)
             .await.context("Error while displaying seed")?;
     }
 
@@ -586,12 +681,13 @@ async fn recover_wallet(manager: &CommandManager, _: ArgumentManager) -> Result<
         return Ok(())
     }
 
+    let passphrase = read_seed_passphrase(manager).await?;
 
     let wallet = {
         let context = manager.get_context().lock()?;
         let network = context.get::<Network>()?;
         let precomputed_tables = Wallet::read_or_generate_precomputed_tables(None, LogProgressTableGenerationReportFunction)?;
-        Wallet::create(dir, password, Some(seed), *network, precomputed_tables)?
+        Wallet::create(dir, password, Some(seed), passphrase, *network, precomputed_tables)?
     };
 
     manager.message("Wallet sucessfully recovered");
@@ -602,6 +698,175 @@ async fn recover_wallet(manager: &CommandManager, _: ArgumentManager) -> Result<
     Ok(())
 }
 
+// Restore a wallet from a backup file by requesting its password, the name and password of the new wallet
+async fn restore_wallet(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let prompt = manager.get_prompt();
+    let path = arguments.get_value("path")?.to_string_value()?;
+    if !Path::new(&path).is_file() {
+        manager.error("No backup file found at this path");
+        return Ok(())
+    }
+
+    let backup_password = prompt.read_input("Backup password: ", true)
+        .await.context("Error while reading backup password")?;
+
+    let name = prompt.read_input("Wallet name: ", false)
+        .await.context("Error while reading wallet name")?;
+
+    if name.is_empty() {
+        manager.error("Wallet name cannot be empty");
+        return Ok(())
+    }
+
+    let dir = format!("{}{}", DIR_PATH, name);
+    // check if it doesn't exists yet
+    if Path::new(&dir).is_dir() {
+        manager.message("Wallet already exist with this name!");
+        return Ok(())
+    }
+
+    // ask and verify password
+    let password = prompt.read_input("Password: ", true)
+        .await.context("Error while reading password")?;
+    let confirm_password = prompt.read_input("Confirm Password: ", true)
+        .await.context("Error while reading password")?;
+
+    if password != confirm_password {
+        manager.message("Confirm password doesn't match password");        
+        return Ok(())
+    }
+
+    let wallet = {
+        let context = manager.get_context().lock()?;
+        let network = context.get::<Network>()?;
+        let precomputed_tables = Wallet::read_or_generate_precomputed_tables(None, LogProgressTableGenerationReportFunction)?;
+        Wallet::restore_from_file(dir, password, &path, backup_password, *network, precomputed_tables)?
+    };
+
+    manager.message("Wallet sucessfully restored");
+    apply_config(&wallet, #[cfg(feature = "api_server")] prompt).await;
+
+    setup_wallet_command_manager(wallet, manager).await?;
+
+    Ok(())
+}
+
+// Ask the optional passphrase combined with the seed
+async fn read_seed_passphrase(manager: &CommandManager) -> Result<Option<String>, CommandError> {
+    let prompt = manager.get_prompt();
+    let passphrase = prompt.read_input("Seed passphrase (optional, press ENTER to skip): ", true)
+        .await.context("Error while reading seed passphrase")?;
+    if passphrase.is_empty() {
+        return Ok(None)
+    }
+
+    let confirm_passphrase = prompt.read_input("Confirm seed passphrase: ", true)
+        .await.context("Error while reading seed passphrase")?;
+    if passphrase != confirm_passphrase {
+        return Err(CommandError::InvalidArgument("Confirm seed passphrase doesn't match seed passphrase".to_owned()))
+    }
+
+    Ok(Some(passphrase))
+}
+
+// Stop the vanity search threads once dropped
+struct VanityStopGuard(Arc<VanityProgress>);
+
+impl Drop for VanityStopGuard {
+    fn drop(&mut self) {
+        self.0.stop();
+    }
+}
+
+// Search a new key pair with an address matching the requested prefix and/or suffix
+// Search is done on several threads and its progress is reported periodically
+async fn vanity(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let prompt = manager.get_prompt();
+    let threads = if arguments.has_argument("threads") {
+        arguments.get_value("threads")?.to_number()? as usize
+    } else {
+        thread::available_parallelism().map(|v| v.get()).unwrap_or(1)
+    };
+
+    let prefix = prompt.read_input("Prefix (empty for none): ", false)
+        .await.context("Error while reading prefix")?;
+    let suffix = prompt.read_input("Suffix (empty for none): ", false)
+        .await.context("Error while reading suffix")?;
+
+    let mainnet = {
+        let context = manager.get_context().lock()?;
+        context.get::<Network>()?.is_mainnet()
+    };
+
+    let search = match VanitySearch::new(prefix, suffix, mainnet) {
+        Ok(search) => Arc::new(search),
+        Err(e) => {
+            manager.error(format!("Invalid pattern: {}", e));
+            return Ok(())
+        }
+    };
+
+    manager.message(format!("Searching using {} threads, expected attempts: {}", threads, search.expected_attempts()));
+
+    let progress = Arc::new(VanityProgress::new());
+    // Threads are stopped even if this command is dropped before the end of the search
+    let _guard = VanityStopGuard(Arc::clone(&progress));
+    let mut handle = {
+        let search = Arc::clone(&search);
+        let progress = Arc::clone(&progress);
+        tokio::task::spawn_blocking(move || search.search(threads, &progress))
+    };
+
+    // Input also returns if the prompt is exited
+    let mut stop_input = Box::pin(prompt.read_input("Press ENTER to stop the search", false));
+    let mut waiting_input = true;
+
+    let start = Instant::now();
+    let keypair = loop {
+        tokio::select! {
+            res = &mut handle => {
+                break res.context("Error while waiting on vanity search")?;
+            },
+            _ = &mut stop_input, if waiting_input => {
+                waiting_input = false;
+                progress.stop();
+                manager.message("Stopping the search...");
+            },
+            _ = tokio::time::sleep(Duration::from_secs(VANITY_PROGRESS_INTERVAL)) => {
+                let attempts = progress.get_attempts();
+                let rate = attempts as f64 / start.elapsed().as_secs_f64();
+                let estimated = search.estimated_time(rate)
+                    .map(|v| format!("{}s", v.as_secs()))
+                    .unwrap_or_else(|| "unknown".to_owned());
+                manager.message(format!("Attempts: {} ({:.2} keys/s), estimated time: {}", attempts, rate, estimated));
+            }
+        }
+    };
+
+    if waiting_input {
+        prompt.cancel_read_input().await.context("Error while canceling stop input")?;
+        let _ = stop_input.await;
+    }
+
+    let keypair = match keypair {
+        Some(keypair) => keypair,
+        None => {
+            manager.error("No matching address found");
+            return Ok(())
+        }
+    };
+
+    let address = keypair.get_public_key().to_address(mainnet);
+    let seed = mnemonics::key_to_words(keypair.get_private_key(), 0)?.join(" ");
+    manager.message(format!("Found after {} attempts in {}s", progress.get_attempts(), start.elapsed().as_secs()));
+    prompt.read_input(
+        prompt.colorize_string(Color::Green, &format!("Address: {}\r\nSeed: {}\r\nPress ENTER to continue", address, seed)),
+        false
+    ).await.context("Error while printing seed")?;
+
+    Ok(())
+}
+
 // Change wallet password
 async fn change_password(manager: &CommandManager, _: ArgumentManager) -> Result<(), CommandError> {
     let context = manager.get_context().lock()?;
@@ -623,31 +888,82 @@ async fn change_password(manager: &CommandManager, _: ArgumentManager) -> Result
     Ok(())
 }
 
+// Wipe the keys from memory
+async fn lock_wallet(manager: &CommandManager, _: ArgumentManager) -> Result<(), CommandError> {
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    wallet.lock().await.context("Error while locking wallet")?;
+    manager.message("Wallet is locked, use 'unlock_wallet' to use your keys again");
+    Ok(())
+}
+
+// Load the keys again after asking the password
+async fn unlock_wallet(manager: &CommandManager, _: ArgumentManager) -> Result<(), CommandError> {
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+
+    let prompt = manager.get_prompt();
+    let password = prompt.read_input(prompt.colorize_str(Color::BrightRed, "Password: "), true)
+        .await
+        .context("Error while asking password")?;
+
+    wallet.unlock(password).await?;
+    manager.message("Wallet is unlocked");
+    Ok(())
+}
+
+// Show or set the inactivity duration before locking the wallet
+async fn auto_lock(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+
+    if !arguments.has_argument("minutes") {
+        match wallet.get_auto_lock().await {
+            Some(duration) => manager.message(format!("Wallet is locked after {} minutes of inactivity", duration.as_secs() / 60)),
+            None => manager.message("Auto lock is disabled")
+        };
+        return Ok(())
+    }
+
+    let minutes = arguments.get_value("minutes")?.to_number()?;
+    if minutes == 0 {
+        wallet.set_auto_lock(None).await;
+        manager.message("Auto lock is disabled");
+    } else {
+        wallet.set_auto_lock(Some(Duration::from_secs(minutes.saturating_mul(60)))).await;
+        manager.message(format!("Wallet will be locked after {} minutes of inactivity", minutes));
+    }
+    Ok(())
+}
+
 // Create a new transfer to a specified address
 async fn transfer(manager: &CommandManager, _: ArgumentManager) -> Result<(), CommandError> {
     let prompt = manager.get_prompt();
     let context = manager.get_context().lock()?;
     let wallet: &Arc<Wallet> = context.get()?;
 
-    // read address
-    let str_address = prompt.read_input(
-        prompt.colorize_str(Color::Green, "Address: "),
+    // read address or contact name
+    let destination = prompt.read_input(
+        prompt.colorize_str(Color::Green, "Address or contact name: "),
         false
     ).await.context("Error while reading address")?;
-    let address = Address::from_string(&str_address).context("Invalid address")?;
-
-    let asset = prompt.read_hash(
-        prompt.colorize_str(Color::Green, "Asset (default XELIS): ")
-    ).await.ok();
-
-    let asset = asset.unwrap_or(XELIS_ASSET);
+    let (address, default_asset) = wallet.resolve_destination(&destination).await.context("Invalid destination")?;
+
+    // The contact may have its own default asset
+    let asset = match default_asset {
+        Some(default_asset) => prompt.read_hash(
+            prompt.colorize_string(Color::Green, &format!("Asset (default {}): ", default_asset))
+        ).await.unwrap_or(default_asset),
+        None => prompt.read_hash(
+            prompt.colorize_str(Color::Green, "Asset (default XELIS): ")
+        ).await.unwrap_or(XELIS_ASSET)
+    };
 
-    let (max_balance, decimals) = {
+    let max_balance = {
         let storage = wallet.get_storage().read().await;
-        let balance = storage.get_plaintext_balance_for(&asset).await.unwrap_or(0);
-        let decimals = storage.get_asset_decimals(&asset).unwrap_or(COIN_DECIMALS);
-        (balance, decimals)
+        storage.get_plaintext_balance_for(&asset).await.unwrap_or(0)
     };
+    let decimals = wallet.get_asset_decimals(&asset).await.context("Error while retrieving asset decimals")?;
 
     // read amount
     let float_amount: f64 = prompt.read(
@@ -668,7 +984,8 @@ async fn transfer(manager: &CommandManager, _: ArgumentManager) -> Result<(), Co
         destination: address,
         amount,
         asset,
-        extra_data: None
+        extra_data: None,
+        lock: None
     };
     let tx = wallet.create_transaction(TransactionTypeBuilder::Transfers(vec![transfer]), FeeBuilder::default()).await
         .context("Error while creating transaction")?;
@@ -677,130 +994,759 @@ async fn transfer(manager: &CommandManager, _: ArgumentManager) -> Result<(), Co
     Ok(())
 }
 
+// Send all the payments of a CSV file in a single transaction
+// Destinations can be addresses or contact names
+async fn transfer_batch(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let path = arguments.get_value("path")?.to_string_value()?;
+    let content = fs::read_to_string(&path).context("Error while reading batch file")?;
+    let payments = batch::parse_csv(&content).context("Invalid batch file")?;
 
-// Send the whole balance to a specified address
-async fn transfer_all(manager: &CommandManager, mut args: ArgumentManager) -> Result<(), CommandError> {
     let prompt = manager.get_prompt();
     let context = manager.get_context().lock()?;
     let wallet: &Arc<Wallet> = context.get()?;
 
-    // read address
-    let str_address = prompt.read_input(
-        prompt.colorize_str(Color::Green, "Address: "),
-        false
-    ).await.context("Error while reading address")?;
-    let address = Address::from_string(&str_address).context("Invalid address")?;
+    let transfers = wallet.build_batch_transfers(payments).await.context("Invalid batch file")?;
+    let totals = batch::get_totals(&transfers).context("Invalid batch file")?;
 
-    let mut asset = args.get_value("asset").and_then(|v| v.to_hash()).ok();
-    if asset.is_none() {
-        asset = prompt.read_hash(
-           prompt.colorize_str(Color::Green, "Asset (default XELIS): ")
-       ).await.ok();
+    manager.message(format!("Sending {} transfers:", transfers.len()));
+    for (asset, total) in totals {
+        let decimals = wallet.get_asset_decimals(&asset).await.context("Error while retrieving asset decimals")?;
+        manager.message(format!("- {} of {}", format_coin(total, decimals), asset));
     }
 
-    let asset = asset.unwrap_or(XELIS_ASSET);
-    let (mut amount, decimals) = {
-        let storage = wallet.get_storage().read().await;
-        let amount = storage.get_plaintext_balance_for(&asset).await.unwrap_or(0);
-        let decimals = storage.get_asset_decimals(&asset).unwrap_or(COIN_DECIMALS);
-        (amount, decimals)
-    };
-
-    let transfer = TransferBuilder {
-        destination: address.clone(),
-        amount,
-        asset: asset.clone(),
-        extra_data: None
-    };
-    let tx_type = TransactionTypeBuilder::Transfers(vec![transfer]);
-    let estimated_fees = wallet.estimate_fees(tx_type.clone()).await.context("Error while estimating fees")?;
-
-    if asset == XELIS_ASSET {
-        amount -= estimated_fees;
-    }
+    let transaction_type = TransactionTypeBuilder::Transfers(transfers);
+    let fee = wallet.estimate_fees(transaction_type.clone()).await.context("Error while estimating fees")?;
+    manager.message(format!("Estimated fees: {}", format_xelis(fee)));
 
-    manager.message(format!("Sending {} of {} to {} (fees: {})", format_coin(amount, decimals), asset, address.to_string(), format_xelis(estimated_fees)));
-    
     if !prompt.ask_confirmation().await.context("Error while confirming action")? {
         manager.message("Transaction has been aborted");
         return Ok(())
     }
 
-    let transfer = TransferBuilder {
-        destination: address,
-        amount,
-        asset,
-        extra_data: None
-    };
-    let tx_type = TransactionTypeBuilder::Transfers(vec![transfer]);
-
     manager.message("Building transaction...");
-
-    let tx = wallet.create_transaction(tx_type, FeeBuilder::default()).await
+    let tx = wallet.create_transaction(transaction_type, FeeBuilder::default()).await
         .context("Error while creating transaction")?;
 
     broadcast_tx(wallet, manager, tx).await;
     Ok(())
 }
 
-async fn burn(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
-    let amount = arguments.get_value("amount")?.to_number()?;
-    let asset = arguments.get_value("asset")?.to_hash()?;
+// List the scheduled payments of the selected account
+async fn scheduled_payments(manager: &CommandManager, _: ArgumentManager) -> Result<(), CommandError> {
     let context = manager.get_context().lock()?;
     let wallet: &Arc<Wallet> = context.get()?;
-    {
-        let storage = wallet.get_storage().read().await;
-        let decimals = storage.get_asset_decimals(&asset).unwrap_or(COIN_DECIMALS);
-
-        manager.message(format!("Burning {} of {}", format_coin(amount, decimals), asset));
+    let payments = wallet.get_scheduled_payments().await.context("Error while retrieving scheduled payments")?;
+    if payments.is_empty() {
+        manager.message("No scheduled payment");
+        return Ok(())
     }
-    let payload = BurnPayload {
-        amount,
-        asset
-    };
-    let tx = wallet.create_transaction(TransactionTypeBuilder::Burn(payload), FeeBuilder::Multiplier(1f64)).await
-        .context("Error while creating transaction")?;
 
-    broadcast_tx(wallet, manager, tx).await;
+    for entry in payments {
+        let payment = entry.payment;
+        let decimals = wallet.get_asset_decimals(&payment.asset).await.context("Error while retrieving asset decimals")?;
+        let (next, unit) = match payment.next_execution {
+            PaymentTrigger::Topoheight(topoheight) => (format!("at topoheight {}", topoheight), "blocks"),
+            PaymentTrigger::Timestamp(timestamp) => (format!("at timestamp {}", timestamp), "seconds")
+        };
+        let mut line = format!("#{}: {} of {} to {} {}", entry.id, format_coin(payment.amount, decimals), payment.asset, payment.destination, next);
+        if let Some(interval) = payment.interval {
+            line.push_str(&format!(", every {} {}", interval, unit));
+        }
+        manager.message(line);
+    }
     Ok(())
 }
 
-// Show current wallet address
-async fn display_address(manager: &CommandManager, _: ArgumentManager) -> Result<(), CommandError> {
+// Schedule a payment executed after a delay, and optionally repeated
+async fn schedule_payment(manager: &CommandManager, _: ArgumentManager) -> Result<(), CommandError> {
+    let prompt = manager.get_prompt();
     let context = manager.get_context().lock()?;
     let wallet: &Arc<Wallet> = context.get()?;
-    manager.message(format!("Wallet address: {}", wallet.get_address()));
+
+    let destination = prompt.read_input(
+        prompt.colorize_str(Color::Green, "Address or contact name: "),
+        false
+    ).await.context("Error while reading address")?;
+    let (address, default_asset) = wallet.resolve_destination(&destination).await.context("Invalid destination")?;
+
+    let default_asset = default_asset.unwrap_or(XELIS_ASSET);
+    let asset = prompt.read_hash(
+        prompt.colorize_string(Color::Green, &format!("Asset (default {}): ", default_asset))
+    ).await.unwrap_or(default_asset);
+
+    let decimals = wallet.get_asset_decimals(&asset).await.context("Error while retrieving asset decimals")?;
+
+    let float_amount: f64 = prompt.read(
+        prompt.colorize_str(Color::Green, "Amount: ")
+    ).await.context("Error while reading amount")?;
+    let amount = (float_amount * 10u32.pow(decimals as u32) as f64) as u64;
+
+    let delay: u64 = prompt.read(
+        prompt.colorize_str(Color::Green, "Minutes before the first payment (default 0): ")
+    ).await.unwrap_or(0);
+
+    let interval: Option<u64> = prompt.read(
+        prompt.colorize_str(Color::Green, "Minutes between payments (optional): ")
+    ).await.ok();
+
+    let next_execution = get_current_time_in_seconds().saturating_add(delay.saturating_mul(60));
+    let mut summary = format!("Scheduling {} of {} to {} in {} minutes", format_coin(amount, decimals), asset, address, delay);
+    if let Some(interval) = interval {
+        summary.push_str(&format!(", then every {} minutes", interval));
+    }
+    manager.message(summary);
+
+    if !prompt.ask_confirmation().await.context("Error while confirming action")? {
+        manager.message("Scheduled payment has been aborted");
+        return Ok(())
+    }
+
+    let payment = ScheduledPayment {
+        destination: address,
+        asset,
+        amount,
+        next_execution: PaymentTrigger::Timestamp(next_execution),
+        interval: interval.map(|interval| interval.saturating_mul(60))
+    };
+    let id = wallet.add_scheduled_payment(payment).await.context("Error while scheduling payment")?;
+    manager.message(format!("Payment scheduled with id {}", id));
     Ok(())
 }
 
-// Show current balance for specified asset or list all non-zero balances
-async fn balance(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+// Remove a scheduled payment using its id
+async fn remove_scheduled_payment(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let id = arguments.get_value("id")?.to_number()?;
     let context = manager.get_context().lock()?;
     let wallet: &Arc<Wallet> = context.get()?;
-    let storage = wallet.get_storage().read().await;
-
-    if arguments.has_argument("asset") {
-        let asset = arguments.get_value("asset")?.to_hash()?;
-        let balance = storage.get_plaintext_balance_for(&asset).await.unwrap_or(0);
-        let decimals = storage.get_asset_decimals(&asset).unwrap_or(0);
-        manager.message(format!("Balance for asset {}: {}", asset, format_coin(balance, decimals)));
+    if wallet.remove_scheduled_payment(id).await.context("Error while removing scheduled payment")? {
+        manager.message(format!("Scheduled payment #{} removed", id));
     } else {
-        for (asset, decimals) in storage.get_assets_with_decimals().await? {
-            let balance = storage.get_plaintext_balance_for(&asset).await.unwrap_or(0);
-            if balance > 0 {
-                manager.message(format!("Balance for asset {}: {}", asset, format_coin(balance, decimals)));
-            }
-        }
+        manager.message(format!("No scheduled payment with id {}", id));
     }
-
     Ok(())
 }
 
-// Show all transactions
-const TXS_PER_PAGE: usize = 10;
-async fn history(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
-    let page = if arguments.has_argument("page") {
-        arguments.get_value("page")?.to_number()? as usize
+// Create a new transfer locked until a topoheight or by a hash
+async fn transfer_locked(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let topoheight = arguments.get_value("topoheight")?.to_number()?;
+    let hash_lock = if arguments.has_argument("hash_lock") {
+        arguments.get_value("hash_lock")?.to_bool()?
+    } else {
+        false
+    };
+
+    let prompt = manager.get_prompt();
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+
+    // read address
+    let str_address = prompt.read_input(
+        prompt.colorize_str(Color::Green, "Address: "),
+        false
+    ).await.context("Error while reading address")?;
+    let address = Address::from_string(&str_address).context("Invalid address")?;
+
+    let asset = prompt.read_hash(
+        prompt.colorize_str(Color::Green, "Asset (default XELIS): ")
+    ).await.ok();
+
+    let asset = asset.unwrap_or(XELIS_ASSET);
+
+    let max_balance = {
+        let storage = wallet.get_storage().read().await;
+        storage.get_plaintext_balance_for(&asset).await.unwrap_or(0)
+    };
+    let decimals = wallet.get_asset_decimals(&asset).await.context("Error while retrieving asset decimals")?;
+
+    // read amount
+    let float_amount: f64 = prompt.read(
+        prompt.colorize_string(Color::Green, &format!("Amount (max: {}): ", format_coin(max_balance, decimals)))
+    ).await.context("Error while reading amount")?;
+
+    let amount = (float_amount * 10u32.pow(decimals as u32) as f64) as u64;
+
+    let hash = if hash_lock {
+        // Hash can be provided by the receiver for an atomic swap
+        let hash = match prompt.read_hash(prompt.colorize_str(Color::Green, "Hash (default new secret): ")).await {
+            Ok(hash) => hash,
+            Err(_) => {
+                let preimage = Preimage::random();
+                manager.message(format!("Secret to reveal to the receiver: {}", preimage.to_hex()));
+                preimage.get_hash()
+            }
+        };
+        manager.message(format!("Sending {} of {} to {} claimable until topoheight {} with the secret of {}", format_coin(amount, decimals), asset, address, topoheight, hash));
+        Some(hash)
+    } else {
+        manager.message(format!("Sending {} of {} to {} claimable from topoheight {}", format_coin(amount, decimals), asset, address, topoheight));
+        None
+    };
+
+    if !prompt.ask_confirmation().await.context("Error while confirming action")? {
+        manager.message("Transaction has been aborted");
+        return Ok(())
+    }
+
+    manager.message("Building transaction...");
+
+    let transfer = TransferBuilder {
+        destination: address,
+        amount,
+        asset,
+        extra_data: None,
+        lock: Some(TransferLock {
+            topoheight,
+            hash
+        })
+    };
+    let tx = wallet.create_transaction(TransactionTypeBuilder::Transfers(vec![transfer]), FeeBuilder::default()).await
+        .context("Error while creating transaction")?;
+
+    manager.message(format!("Locked transfer id: {}", get_lock_id(&tx.hash(), 0)));
+    broadcast_tx(wallet, manager, tx).await;
+    Ok(())
+}
+
+// Claim or get refunded of a locked transfer
+async fn unlock(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let id = arguments.get_value("id")?.to_hash()?;
+    let asset = arguments.get_value("asset")?.to_hash()?;
+    let preimage = if arguments.has_argument("preimage") {
+        let hex = arguments.get_value("preimage")?.to_string_value()?;
+        Some(Preimage::from_hex(hex).context("Invalid preimage")?)
+    } else {
+        None
+    };
+
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+
+    let payload = UnlockPayload {
+        id,
+        asset,
+        preimage
+    };
+    let tx = wallet.create_transaction(TransactionTypeBuilder::Unlock(payload), FeeBuilder::default()).await
+        .context("Error while creating transaction")?;
+
+    broadcast_tx(wallet, manager, tx).await;
+    Ok(())
+}
+
+// Deploy a new contract
+async fn deploy_contract(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let hex = arguments.get_value("program")?.to_string_value()?;
+    let program = Program::from_hex(hex).context("Invalid program")?;
+
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+
+    let payload = DeployContractPayload {
+        program
+    };
+    let tx = wallet.create_transaction(TransactionTypeBuilder::DeployContract(payload), FeeBuilder::default()).await
+        .context("Error while creating transaction")?;
+
+    // Contract is identified by the hash of its deploy TX
+    manager.message(format!("Contract: {}", tx.hash()));
+    broadcast_tx(wallet, manager, tx).await;
+    Ok(())
+}
+
+// Invoke a deployed contract
+async fn invoke_contract(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let contract = arguments.get_value("contract")?.to_hash()?;
+    let max_gas = arguments.get_value("max_gas")?.to_number()?;
+    let parameters = if arguments.has_argument("parameters") {
+        arguments.get_value("parameters")?.to_string_value()?
+            .split(',')
+            .map(|param| param.trim().parse::<u64>())
+            .collect::<Result<Vec<u64>, _>>()
+            .context("Invalid parameters")?
+    } else {
+        Vec::new()
+    };
+
+    if parameters.len() > MAX_CONTRACT_PARAMETERS {
+        return Err(CommandError::InvalidArgument(format!("Parameters count must be less than or equal to {}", MAX_CONTRACT_PARAMETERS)));
+    }
+
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+
+    let payload = InvokeContractPayload {
+        contract,
+        parameters,
+        max_gas
+    };
+    let tx = wallet.create_transaction(TransactionTypeBuilder::InvokeContract(payload), FeeBuilder::default()).await
+        .context("Error while creating transaction")?;
+
+    broadcast_tx(wallet, manager, tx).await;
+    Ok(())
+}
+
+// Send the whole balance to a specified address
+async fn transfer_all(manager: &CommandManager, mut args: ArgumentManager) -> Result<(), CommandError> {
+    let prompt = manager.get_prompt();
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+
+    // read address
+    let str_address = prompt.read_input(
+        prompt.colorize_str(Color::Green, "Address: "),
+        false
+    ).await.context("Error while reading address")?;
+    let address = Address::from_string(&str_address).context("Invalid address")?;
+
+    let mut asset = args.get_value("asset").and_then(|v| v.to_hash()).ok();
+    if asset.is_none() {
+        asset = prompt.read_hash(
+           prompt.colorize_str(Color::Green, "Asset (default XELIS): ")
+       ).await.ok();
+    }
+
+    let asset = asset.unwrap_or(XELIS_ASSET);
+    let mut amount = {
+        let storage = wallet.get_storage().read().await;
+        storage.get_plaintext_balance_for(&asset).await.unwrap_or(0)
+    };
+    let decimals = wallet.get_asset_decimals(&asset).await.context("Error while retrieving asset decimals")?;
+
+    let transfer = TransferBuilder {
+        destination: address.clone(),
+        amount,
+        asset: asset.clone(),
+        extra_data: None,
+        lock: None
+    };
+    let tx_type = TransactionTypeBuilder::Transfers(vec![transfer]);
+    let estimated_fees = wallet.estimate_fees(tx_type.clone()).await.context("Error while estimating fees")?;
+
+    if asset == XELIS_ASSET {
+        amount -= estimated_fees;
+    }
+
+    manager.message(format!("Sending {} of {} to {} (fees: {})", format_coin(amount, decimals), asset, address.to_string(), format_xelis(estimated_fees)));
+    
+    if !prompt.ask_confirmation().await.context("Error while confirming action")? {
+        manager.message("Transaction has been aborted");
+        return Ok(())
+    }
+
+    let transfer = TransferBuilder {
+        destination: address,
+        amount,
+        asset,
+        extra_data: None,
+        lock: None
+    };
+    let tx_type = TransactionTypeBuilder::Transfers(vec![transfer]);
+
+    manager.message("Building transaction...");
+
+    let tx = wallet.create_transaction(tx_type, FeeBuilder::default()).await
+        .context("Error while creating transaction")?;
+
+    broadcast_tx(wallet, manager, tx).await;
+    Ok(())
+}
+
+async fn burn(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let amount = arguments.get_value("amount")?.to_number()?;
+    let asset = arguments.get_value("asset")?.to_hash()?;
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    {
+        let decimals = wallet.get_asset_decimals(&asset).await.context("Error while retrieving asset decimals")?;
+        manager.message(format!("Burning {} of {}", format_coin(amount, decimals), asset));
+    }
+    let payload = BurnPayload {
+        amount,
+        asset
+    };
+    let tx = wallet.create_transaction(TransactionTypeBuilder::Burn(payload), FeeBuilder::Multiplier(1f64)).await
+        .context("Error while creating transaction")?;
+
+    broadcast_tx(wallet, manager, tx).await;
+    Ok(())
+}
+
+async fn create_asset(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let name = arguments.get_value("name")?.to_string_value()?;
+    let decimals = arguments.get_value("decimals")?.to_number()?;
+    let max_supply = arguments.get_value("max_supply")?.to_number()?;
+    if decimals > MAX_ASSET_DECIMALS as u64 {
+        return Err(CommandError::InvalidArgument(format!("Decimals must be less than or equal to {}", MAX_ASSET_DECIMALS)));
+    }
+    let decimals = decimals as u8;
+
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    manager.message(format!("Creating asset {} with {} decimals and a maximum supply of {} for {} XELIS", name, decimals, format_coin(max_supply, decimals), format_xelis(ASSET_REGISTRATION_FEE)));
+
+    let payload = AssetCreationPayload {
+        name,
+        decimals,
+        max_supply
+    };
+    let tx = wallet.create_transaction(TransactionTypeBuilder::CreateAsset(payload), FeeBuilder::Multiplier(1f64)).await
+        .context("Error while creating transaction")?;
+
+    // The asset hash is the transaction hash
+    manager.message(format!("Asset: {}", tx.hash()));
+    broadcast_tx(wallet, manager, tx).await;
+    Ok(())
+}
+
+// List all assets created by this wallet
+async fn assets(manager: &CommandManager, _: ArgumentManager) -> Result<(), CommandError> {
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let storage = wallet.get_storage().read().await;
+
+    let mut count = 0;
+    for tx in storage.get_transactions()? {
+        if let EntryData::AssetCreation { asset, name, decimals, max_supply } = tx.get_entry() {
+            manager.message(format!("- {} ({}) created at topoheight {}: {} decimals, max supply {}", name, asset, tx.get_topoheight(), decimals, format_coin(*max_supply, *decimals)));
+            count += 1;
+        }
+    }
+
+    if count == 0 {
+        manager.message("No assets created");
+    }
+
+    Ok(())
+}
+
+async fn mint_asset(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let asset = arguments.get_value("asset")?.to_hash()?;
+    let amount = arguments.get_value("amount")?.to_number()?;
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    {
+        let decimals = wallet.get_asset_decimals(&asset).await.context("Error while retrieving asset decimals")?;
+        manager.message(format!("Minting {} of {}", format_coin(amount, decimals), asset));
+    }
+
+    let payload = MintAssetPayload {
+        asset,
+        amount
+    };
+    let tx = wallet.create_transaction(TransactionTypeBuilder::MintAsset(payload), FeeBuilder::Multiplier(1f64)).await
+        .context("Error while creating transaction")?;
+
+    broadcast_tx(wallet, manager, tx).await;
+    Ok(())
+}
+
+async fn transfer_asset_ownership(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let asset = arguments.get_value("asset")?.to_hash()?;
+    let address = arguments.get_value("address")?.to_string_value()?;
+    let new_owner = Address::from_string(&address).context("Invalid address")?;
+
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let prompt = manager.get_prompt();
+    manager.message(format!("Transferring ownership of {} to {}", asset, new_owner));
+    if !prompt.ask_confirmation().await.context("Error while confirming action")? {
+        manager.message("Transaction has been aborted");
+        return Ok(())
+    }
+
+    let payload = AssetOwnershipBuilder {
+        asset,
+        new_owner
+    };
+    let tx = wallet.create_transaction(TransactionTypeBuilder::TransferAssetOwnership(payload), FeeBuilder::Multiplier(1f64)).await
+        .context("Error while creating transaction")?;
+
+    broadcast_tx(wallet, manager, tx).await;
+    Ok(())
+}
+
+async fn multisig_setup(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let threshold = arguments.get_value("threshold")?.to_number()?;
+    let participants = arguments.get_value("participants")?.to_string_value()?;
+    let participants = participants.split(',')
+        .map(|addr| Address::from_string(&addr.trim().to_owned()).context("Invalid participant address"))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if threshold == 0 || threshold > participants.len() as u64 {
+        return Err(CommandError::InvalidArgument(format!("Threshold must be in range [1-{}]", participants.len())));
+    }
+
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let prompt = manager.get_prompt();
+    manager.message(format!("Each transaction will require {} signatures of {} participants", threshold, participants.len()));
+    for (id, participant) in participants.iter().enumerate() {
+        manager.message(format!("- {}: {}", id, participant));
+    }
+
+    if !prompt.ask_confirmation().await.context("Error while confirming action")? {
+        manager.message("Transaction has been aborted");
+        return Ok(())
+    }
+
+    let payload = MultiSigBuilder {
+        threshold: threshold as u8,
+        participants
+    };
+    let tx = wallet.create_transaction(TransactionTypeBuilder::MultiSig(payload), FeeBuilder::Multiplier(1f64)).await
+        .context("Error while creating transaction")?;
+
+    broadcast_tx(wallet, manager, tx).await;
+    Ok(())
+}
+
+async fn multisig_sign(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let hash = arguments.get_value("hash")?.to_hash()?;
+    let id = arguments.get_value("id")?.to_number()?;
+    if id > u8::MAX as u64 {
+        return Err(CommandError::InvalidArgument(format!("Participant id must be less than or equal to {}", u8::MAX)));
+    }
+
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let signature = wallet.sign_multisig_hash(&hash, id as u8).context("Error while signing hash")?;
+    manager.message(format!("Signature for participant {}: {}", id, signature.signature.to_hex()));
+    Ok(())
+}
+
+// Show current wallet address
+async fn display_address(manager: &CommandManager, _: ArgumentManager) -> Result<(), CommandError> {
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    manager.message(format!("Wallet address: {}", wallet.get_address()));
+    Ok(())
+}
+
+// List all accounts of the wallet
+async fn accounts(manager: &CommandManager, _: ArgumentManager) -> Result<(), CommandError> {
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let selected = wallet.get_account().get_index();
+    let accounts = wallet.get_accounts().await.context("Error while retrieving accounts")?;
+
+    manager.message(format!("Accounts ({}):", accounts.len()));
+    for account in accounts {
+        let marker = if account.index == selected { " (selected)" } else { "" };
+        manager.message(format!("- #{}: {}{}", account.index, account.address, marker));
+    }
+
+    Ok(())
+}
+
+// Create a new account derived from the seed
+async fn add_account(manager: &CommandManager, _: ArgumentManager) -> Result<(), CommandError> {
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let account = wallet.add_account().await.context("Error while creating account")?;
+    manager.message(format!("Account #{} created: {}", account.index, account.address));
+    Ok(())
+}
+
+// Select the account to use
+async fn select_account(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let index = arguments.get_value("index")?.to_number()?;
+    if index > u32::MAX as u64 {
+        return Err(CommandError::InvalidArgument(format!("Account index must be less than or equal to {}", u32::MAX)));
+    }
+
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    wallet.select_account(index as u32, true).await.context("Error while selecting account")?;
+    manager.message(format!("Account #{} selected: {}", index, wallet.get_address()));
+    Ok(())
+}
+
+// Connect to a Ledger device to sign the transactions of the selected account
+#[cfg(feature = "ledger")]
+async fn use_ledger(manager: &CommandManager, _: ArgumentManager) -> Result<(), CommandError> {
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let account = wallet.get_account().get_index();
+    manager.message(format!("Connecting to Ledger device for account #{}...", account));
+    let signer = LedgerSigner::connect(account).context("Error while connecting to Ledger device")?;
+    wallet.set_signer(Some(Arc::new(signer))).context("Error while setting Ledger signer")?;
+    manager.message("Transactions will be confirmed on the Ledger device");
+    Ok(())
+}
+
+// Remove the external signer of the selected account
+#[cfg(feature = "ledger")]
+async fn use_local_key(manager: &CommandManager, _: ArgumentManager) -> Result<(), CommandError> {
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    wallet.set_signer(None).context("Error while removing signer")?;
+    manager.message("Transactions will be signed with the wallet key");
+    Ok(())
+}
+
+// List all the contacts of the address book
+async fn contacts(manager: &CommandManager, _: ArgumentManager) -> Result<(), CommandError> {
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let contacts = wallet.get_contacts().await.context("Error while retrieving contacts")?;
+    if contacts.is_empty() {
+        manager.message("No contact in your address book");
+        return Ok(())
+    }
+
+    for entry in contacts {
+        let mut line = format!("{}: {}", entry.name, entry.contact.address);
+        if let Some(asset) = entry.contact.default_asset {
+            line.push_str(&format!(" (asset {})", asset));
+        }
+        if let Some(note) = entry.contact.note {
+            line.push_str(&format!(" - {}", note));
+        }
+        manager.message(line);
+    }
+    Ok(())
+}
+
+// Save a new contact in the address book
+async fn add_contact(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let name = arguments.get_value("name")?.to_string_value()?;
+    let prompt = manager.get_prompt();
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+
+    let str_address = prompt.read_input(
+        prompt.colorize_str(Color::Green, "Address: "),
+        false
+    ).await.context("Error while reading address")?;
+    let address = Address::from_string(&str_address).context("Invalid address")?;
+
+    let note = prompt.read_input(
+        prompt.colorize_str(Color::Green, "Note (optional): "),
+        false
+    ).await.context("Error while reading note")?;
+
+    let default_asset = prompt.read_hash(
+        prompt.colorize_str(Color::Green, "Default asset (optional): ")
+    ).await.ok();
+
+    let entry = ContactEntry {
+        name,
+        contact: Contact {
+            address,
+            note: Some(note),
+            default_asset
+        }
+    };
+    wallet.add_contact(entry).await.context("Error while adding contact")?;
+    manager.message("Contact saved");
+    Ok(())
+}
+
+// Remove a contact from the address book
+async fn remove_contact(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let name = arguments.get_value("name")?.to_string_value()?;
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    wallet.remove_contact(&name).await.context("Error while removing contact")?;
+    manager.message(format!("Contact {} removed", name));
+    Ok(())
+}
+
+// Export the history to a file, with fiat values if a price API is set
+async fn export_history(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let path = arguments.get_value("path")?.to_string_value()?;
+    let provider = match (arguments.has_argument("currency"), arguments.has_argument("price_url")) {
+        (true, true) => {
+            let currency = arguments.get_value("currency")?.to_string_value()?;
+            let url = arguments.get_value("price_url")?.to_string_value()?;
+            Some(HttpPriceProvider::new(url, currency))
+        },
+        (false, false) => None,
+        _ => return Err(CommandError::InvalidArgument("Both currency and price_url must be set for fiat values".to_owned()))
+    };
+
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    manager.message("Exporting history...");
+    let records = wallet.export_transactions(HistoryRange::default(), provider.as_ref().map(|p| p as &dyn PriceProvider)).await
+        .context("Error while exporting history")?;
+
+    let content = if path.ends_with(".json") {
+        serde_json::to_string_pretty(&records).context("Error while serializing history")?
+    } else {
+        export::to_csv(&records, provider.as_ref().map(|p| p.get_currency()))
+    };
+    fs::write(&path, content).context("Error while writing history file")?;
+
+    manager.message(format!("{} records exported to {}", records.len(), path));
+    Ok(())
+}
+
+// Write an encrypted backup of the wallet protected by its own password
+async fn backup(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let prompt = manager.get_prompt();
+    let path = arguments.get_value("path")?.to_string_value()?;
+    if Path::new(&path).exists() {
+        return Err(CommandError::InvalidArgument(format!("File {} already exists", path)))
+    }
+
+    let password = prompt.read_input("Backup password: ", true)
+        .await.context("Error while reading backup password")?;
+    let confirm_password = prompt.read_input("Confirm backup password: ", true)
+        .await.context("Error while reading backup password")?;
+    if password != confirm_password {
+        return Err(CommandError::InvalidArgument("Confirm backup password doesn't match backup password".to_owned()))
+    }
+
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    wallet.backup_to_file(&path, password).await
+        .context("Error while writing backup")?;
+
+    manager.message(format!("Backup written to {}", path));
+    Ok(())
+}
+
+// Show current balance for specified asset or list all non-zero balances
+async fn balance(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    if arguments.has_argument("asset") {
+        let asset = arguments.get_value("asset")?.to_hash()?;
+        let decimals = wallet.get_asset_decimals(&asset).await.context("Error while retrieving asset decimals")?;
+        let storage = wallet.get_storage().read().await;
+        let balance = storage.get_plaintext_balance_for(&asset).await.unwrap_or(0);
+        let pending = storage.get_pending_cost_for(&asset)?;
+        manager.message(format_balance(&asset, balance, pending, decimals));
+    } else {
+        let storage = wallet.get_storage().read().await;
+        for (asset, decimals) in storage.get_assets_with_decimals().await? {
+            let balance = storage.get_plaintext_balance_for(&asset).await.unwrap_or(0);
+            if balance > 0 {
+                let pending = storage.get_pending_cost_for(&asset)?;
+                manager.message(format_balance(&asset, balance, pending, decimals));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Amounts spent by the pending transactions are shown until they are executed
+fn format_balance(asset: &Hash, balance: u64, pending: u64, decimals: u8) -> String {
+    if pending == 0 {
+        format!("Balance for asset {}: {}", asset, format_coin(balance, decimals))
+    } else {
+        format!("Balance for asset {}: {} ({} spendable, {} pending)", asset, format_coin(balance, decimals), format_coin(balance.saturating_sub(pending), decimals), format_coin(pending, decimals))
+    }
+}
+
+// Show all transactions
+const TXS_PER_PAGE: usize = 10;
+async fn history(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let page = if arguments.has_argument("page") {
+        arguments.get_value("page")?.to_number()? as usize
     } else {
         1
     };
@@ -812,6 +1758,18 @@ async fn history(manager: &CommandManager, mut arguments: ArgumentManager) -> Re
     let context = manager.get_context().lock()?;
     let wallet: &Arc<Wallet> = context.get()?;
     let storage = wallet.get_storage().read().await;
+    let mainnet = wallet.get_network().is_mainnet();
+
+    // Transactions not executed yet are always shown first
+    let pending = storage.get_pending_transactions()?;
+    if !pending.is_empty() && page == 1 {
+        manager.message(format!("Pending transactions ({}):", pending.len()));
+        for (hash, pending) in pending {
+            let entry = TransactionEntry::new(hash, pending.get_topoheight(), pending.get_entry().clone());
+            manager.message(format!("- {} (pending, broadcasted again {} times)", entry.summary(mainnet, &*storage)?, pending.get_rebroadcasts()));
+        }
+    }
+
     let mut transactions = storage.get_transactions()?;
 
     // if we don't have any txs, no need proceed further
@@ -833,7 +1791,7 @@ async fn history(manager: &CommandManager, mut arguments: ArgumentManager) -> Re
 
     manager.message(format!("Transactions (total {}) page {}/{}:", transactions.len(), page, max_pages));
     for tx in transactions.iter().skip((page - 1) * TXS_PER_PAGE).take(TXS_PER_PAGE) {
-        manager.message(format!("- {}", tx.summary(wallet.get_network().is_mainnet(), &*storage)?));
+        manager.message(format!("- {}", tx.summary(mainnet, &*storage)?));
     }
 
     Ok(())
@@ -902,9 +1860,14 @@ async fn seed(manager: &CommandManager, mut arguments: ArgumentManager) -> Resul
         0
     };
 
-    let seed = wallet.get_seed(language as usize)?;
+    let seed = wallet.get_seed(language as usize).await?;
+    let note = if wallet.has_seed_passphrase().await.context("Error while checking seed passphrase")? {
+        "\r\nYour passphrase is required with this seed to recover your wallet"
+    } else {
+        ""
+    };
     prompt.read_input(
-        prompt.colorize_string(Color::Green, &format!("Seed: {}\r\nPress ENTER to continue", seed)),
+        prompt.colorize_string(Color::Green, &format!("Seed: {}{}\r\nPress ENTER to continue", seed, note)),
         false
     ).await.context("Error while printing seed")?;
     Ok(())
@@ -938,6 +1901,31 @@ async fn set_nonce(manager: &CommandManager, _: ArgumentManager) -> Result<(), C
     Ok(())
 }
 
+async fn resync_nonce(manager: &CommandManager, _: ArgumentManager) -> Result<(), CommandError> {
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    match wallet.resync_nonce().await.context("Error while checking the nonce")? {
+        Some(nonce) => manager.message(format!("Nonce has been repaired, new nonce is: {}", nonce)),
+        None => manager.message("Nonce is already synced with the daemon")
+    }
+    Ok(())
+}
+
+async fn bump_fee(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let hash = arguments.get_value("hash")?.to_hash()?;
+    let fee = if arguments.has_argument("fee") {
+        Some(FeeBuilder::Value(arguments.get_value("fee")?.to_number()?))
+    } else {
+        None
+    };
+
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let tx = wallet.bump_fee(&hash, fee).await.context("Error while bumping the fee")?;
+    manager.message(format!("Transaction {} has been replaced by {} with a fee of {} XELIS", hash, tx.hash(), format_xelis(tx.get_fee())));
+    Ok(())
+}
+
 #[cfg(feature = "api_server")]
 async fn stop_api_server(manager: &CommandManager, _: ArgumentManager) -> Result<(), CommandError> {
     let context = manager.get_context().lock()?;
@@ -981,6 +1969,92 @@ async fn start_xswd(manager: &CommandManager, _: ArgumentManager) -> Result<(),
     Ok(())
 }
 
+#[cfg(feature = "api_server")]
+async fn xswd_limits(manager: &CommandManager, _: ArgumentManager) -> Result<(), CommandError> {
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let settings = wallet.get_xswd_limits().await.context("Error while retrieving XSWD limits")?;
+
+    // Resolve the decimals first as the closure can't fetch them
+    let mut decimals = std::collections::HashMap::new();
+    let all_limits = std::iter::once(&settings.default).chain(settings.applications.values());
+    for limits in all_limits {
+        for asset in limits.max_amount_per_transaction.keys().chain(limits.daily_spend_cap.keys()) {
+            if !decimals.contains_key(asset) {
+                let value = wallet.get_asset_decimals(asset).await.context("Error while retrieving asset decimals")?;
+                decimals.insert(asset.clone(), value);
+            }
+        }
+    }
+
+    let display = |name: &str, limits: &XSWDLimits| {
+        let requests = limits.max_requests_per_minute.map(|max| max.to_string()).unwrap_or_else(|| "unlimited".to_owned());
+        manager.message(format!("{}: {} requests per minute", name, requests));
+        for (asset, max) in limits.max_amount_per_transaction.iter() {
+            let decimals = decimals[asset];
+            manager.message(format!("- {} of {} per transaction without confirmation", format_coin(*max, decimals), asset));
+        }
+        for (asset, cap) in limits.daily_spend_cap.iter() {
+            let decimals = decimals[asset];
+            manager.message(format!("- {} of {} per day", format_coin(*cap, decimals), asset));
+        }
+    };
+
+    display("Default", &settings.default);
+    for (id, limits) in settings.applications.iter() {
+        display(id, limits);
+    }
+    Ok(())
+}
+
+// Limits are updated for one asset at a time, the others are kept
+#[cfg(feature = "api_server")]
+async fn set_xswd_limits(manager: &CommandManager, mut arguments: ArgumentManager) -> Result<(), CommandError> {
+    let app_id = if arguments.has_argument("app_id") {
+        Some(arguments.get_value("app_id")?.to_string_value()?)
+    } else {
+        None
+    };
+
+    let prompt = manager.get_prompt();
+    let context = manager.get_context().lock()?;
+    let wallet: &Arc<Wallet> = context.get()?;
+    let settings = wallet.get_xswd_limits().await.context("Error while retrieving XSWD limits")?;
+    let mut limits = match app_id.as_ref() {
+        Some(id) => settings.get_limits(id).clone(),
+        None => settings.default
+    };
+
+    limits.max_requests_per_minute = prompt.read(
+        prompt.colorize_str(Color::Green, "Requests per minute (optional): ")
+    ).await.ok();
+
+    let asset = prompt.read_hash(
+        prompt.colorize_str(Color::Green, "Asset (default XELIS): ")
+    ).await.unwrap_or(XELIS_ASSET);
+    let decimals = wallet.get_asset_decimals(&asset).await.context("Error while retrieving asset decimals")?;
+
+    let max_amount: Option<f64> = prompt.read(
+        prompt.colorize_str(Color::Green, "Amount per transaction without confirmation (optional): ")
+    ).await.ok();
+    match max_amount {
+        Some(amount) => limits.max_amount_per_transaction.insert(asset.clone(), (amount * 10u32.pow(decimals as u32) as f64) as u64),
+        None => limits.max_amount_per_transaction.remove(&asset)
+    };
+
+    let daily_cap: Option<f64> = prompt.read(
+        prompt.colorize_str(Color::Green, "Amount per day (optional): ")
+    ).await.ok();
+    match daily_cap {
+        Some(amount) => limits.daily_spend_cap.insert(asset, (amount * 10u32.pow(decimals as u32) as f64) as u64),
+        None => limits.daily_spend_cap.remove(&asset)
+    };
+
+    wallet.set_xswd_limits(app_id, limits).await.context("Error while saving XSWD limits")?;
+    manager.message("XSWD limits saved");
+    Ok(())
+}
+
 // broadcast tx if possible
 // submit_transaction increase the local nonce in storage in case of success
 async fn broadcast_tx(wallet: &Wallet, manager: &CommandManager, tx: Transaction) {
@@ -990,7 +2064,10 @@ async fn broadcast_tx(wallet: &Wallet, manager: &CommandManager, tx: Transaction
     if wallet.is_online().await {
         if let Err(e) = wallet.submit_transaction(&tx).await {
             manager.error(format!("Couldn't submit transaction: {}", e));
-            manager.error("You can try to rescan your balance with the command 'rescan'");
+            match wallet.resync_nonce().await {
+                Ok(Some(nonce)) => manager.warn(format!("Nonce was desynchronized from the daemon and has been repaired to {}, you can create the transaction again", nonce)),
+                _ => manager.error("You can try to rescan your balance with the command 'rescan'")
+            }
         } else {
             manager.message("Transaction submitted successfully!");
         }
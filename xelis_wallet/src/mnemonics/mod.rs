@@ -9,11 +9,14 @@ use xelis_common::{
     serializer::Serializer
 };
 use languages::*;
+use crate::config::{PASSWORD_ALGORITHM, PASSWORD_HASH_SIZE};
 
 const KEY_SIZE: usize = 32;
 const SEED_LENGTH: usize = 24;
 const WORDS_LIST: usize = 1626;
 const WORDS_LIST_U32: u32 = WORDS_LIST as u32;
+// Salt prefix used to stretch the passphrase of a seed
+const PASSPHRASE_SALT: &[u8] = b"xelis-mnemonic";
 
 lazy_static! {
     pub static ref LANGUAGES: Vec<Language<'static>> = vec![
@@ -120,6 +123,24 @@ pub fn words_to_key(words: &Vec<String>) -> Result<PrivateKey> {
     Ok(PrivateKey::from_bytes(&dest)?)
 }
 
+// Combine the key of a seed with a passphrase, like a BIP39 passphrase
+// Each passphrase gives an independent key, an empty one keeps the key of the seed
+pub fn apply_passphrase(key: &PrivateKey, passphrase: &str) -> Result<PrivateKey> {
+    if passphrase.is_empty() {
+        return Ok(key.clone())
+    }
+
+    // The key of the seed is used as salt so the same passphrase is stretched differently for each seed
+    let mut salt = PASSPHRASE_SALT.to_vec();
+    salt.extend_from_slice(&key.to_bytes());
+
+    let mut stretched = [0; PASSWORD_HASH_SIZE];
+    PASSWORD_ALGORITHM.hash_password_into(passphrase.as_bytes(), &salt, &mut stretched)
+        .map_err(|e| anyhow!("Error while stretching passphrase: {}", e))?;
+
+    Ok(key.derive_passphrase(&stretched))
+}
+
 pub fn key_to_words(key: &PrivateKey, language_index: usize) -> Result<Vec<String>> {
     let language = LANGUAGES.get(language_index).context("Invalid language index")?;
     key_to_words_with_language(key, language)
@@ -169,4 +190,16 @@ mod tests {
             assert_eq!(words, words2);
         }
     }
+
+    #[test]
+    fn test_passphrase() {
+        let (_, key) = KeyPair::new().split();
+        let empty = super::apply_passphrase(&key, "").unwrap();
+        assert_eq!(key.as_scalar(), empty.as_scalar());
+
+        let first = super::apply_passphrase(&key, "hello").unwrap();
+        assert_eq!(first.as_scalar(), super::apply_passphrase(&key, "hello").unwrap().as_scalar());
+        assert_ne!(first.as_scalar(), key.as_scalar());
+        assert_ne!(first.as_scalar(), super::apply_passphrase(&key, "world").unwrap().as_scalar());
+    }
 }
\ No newline at end of file
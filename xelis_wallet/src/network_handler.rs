@@ -3,12 +3,15 @@ use std::{
         HashMap,
         HashSet
     },
-    sync::Arc,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc
+    },
     time::Duration
 };
 use thiserror::Error;
 use anyhow::Error;
-use log::{debug, error, trace, warn};
+use log::{debug, error, info, trace, warn};
 use tokio::{sync::Mutex, task::JoinHandle, time::sleep};
 use xelis_common::{
     account::CiphertextCache,
@@ -28,12 +31,15 @@ use xelis_common::{
         Hash
     },
     serializer::Serializer,
-    transaction::Role,
+    transaction::{lock::get_lock_id, Role},
+    json_rpc::{EventMessage, TlsConfig},
     utils::{sanitize_daemon_address, spawn_task}
 };
 use crate::{
-    config::AUTO_RECONNECT_INTERVAL,
+    checkpoint::SyncCheckpoint,
+    config::{ACCOUNTS_GAP_LIMIT, AUTO_RECONNECT_INTERVAL, SYNC_CHECKPOINT_INTERVAL},
     daemon_api::DaemonAPI,
+    error::WalletError,
     entry::{
         EntryData,
         TransactionEntry,
@@ -63,6 +69,36 @@ pub enum NetworkError {
     NetworkMismatch
 }
 
+// Progress of the blocks sync shared by all the assets
+struct SyncState {
+    checkpoint: SyncCheckpoint,
+    // Number of assets to sync
+    assets: usize,
+    // Cursor topoheight when the checkpoint was saved for the current asset
+    saved_topoheight: u64,
+    // A checkpoint is in the storage and must be deleted once the sync is done
+    stored: bool,
+    // cache for all topoheight we already processed
+    // a same block may contains changes for several assets
+    topoheight_processed: HashSet<u64>,
+    // Highest nonce stored during the sync
+    highest_nonce: Option<u64>
+}
+
+impl SyncState {
+    fn new(checkpoint: SyncCheckpoint, stored: bool) -> Self {
+        let saved_topoheight = checkpoint.get_target_topoheight();
+        Self {
+            checkpoint,
+            assets: 0,
+            saved_topoheight,
+            stored,
+            topoheight_processed: HashSet::new(),
+            highest_nonce: None
+        }
+    }
+}
+
 pub struct NetworkHandler {
     // tokio task
     task: Mutex<Option<JoinHandle<Result<(), Error>>>>,
@@ -71,15 +107,17 @@ pub struct NetworkHandler {
     // api to communicate with daemon
     // It is behind a Arc to be shared across several wallets
     // in case someone make a custom service and don't want to create a new connection
-    api: Arc<DaemonAPI>
+    api: Arc<DaemonAPI>,
+    // Last topoheight of the daemon seen while syncing
+    daemon_topoheight: AtomicU64
 }
 
 impl NetworkHandler {
     // Create a new network handler with a wallet and a daemon address
     // This will create itself a DaemonAPI and verify if connection is possible
-    pub async fn new<S: ToString>(wallet: Arc<Wallet>, daemon_address: S) -> Result<SharedNetworkHandler, Error> {
+    pub async fn new<S: ToString>(wallet: Arc<Wallet>, daemon_address: S, tls: &TlsConfig) -> Result<SharedNetworkHandler, Error> {
         let s = daemon_address.to_string();
-        let api = DaemonAPI::new(format!("{}/json_rpc", sanitize_daemon_address(s.as_str()))).await?;
+        let api = DaemonAPI::with_tls(format!("{}/json_rpc", sanitize_daemon_address(s.as_str())), tls).await?;
         Self::with_api(wallet, Arc::new(api)).await
     }
 
@@ -92,7 +130,8 @@ impl NetworkHandler {
         Ok(Arc::new(Self {
             task: Mutex::new(None),
             wallet,
-            api
+            api,
+            daemon_topoheight: AtomicU64::new(0)
         }))
     }
 
@@ -185,6 +224,10 @@ impl NetworkHandler {
         &self.api
     }
 
+    pub fn get_daemon_topoheight(&self) -> u64 {
+        self.daemon_topoheight.load(Ordering::SeqCst)
+    }
+
     // check if the network handler is running (that we have a task and its not finished)
     pub async fn is_running(&self) -> bool {
         let task = self.task.lock().await;
@@ -263,12 +306,89 @@ impl NetworkHandler {
                         None
                     }
                 },
+                RPCTransactionType::CreateAsset(payload) => {
+                    let payload = payload.into_owned();
+                    if is_owner {
+                        // Registration fee is paid in XELIS
+                        assets_changed.insert(XELIS_ASSET);
+                        Some(EntryData::AssetCreation { asset: tx.hash.as_ref().clone(), name: payload.name, decimals: payload.decimals, max_supply: payload.max_supply })
+                    } else {
+                        None
+                    }
+                },
+                RPCTransactionType::MintAsset(payload) => {
+                    let payload = payload.into_owned();
+                    if is_owner {
+                        // Minted units are credited to us and fee is paid in XELIS
+                        assets_changed.insert(XELIS_ASSET);
+                        assets_changed.insert(payload.asset.clone());
+                        Some(EntryData::AssetMint { asset: payload.asset, amount: payload.amount })
+                    } else {
+                        None
+                    }
+                },
+                RPCTransactionType::TransferAssetOwnership(payload) => {
+                    let new_owner = payload.new_owner.to_public_key();
+                    if is_owner || new_owner == *address.get_public_key() {
+                        if is_owner {
+                            assets_changed.insert(XELIS_ASSET);
+                        }
+                        Some(EntryData::AssetOwnershipTransfer { asset: payload.asset.into_owned(), from: tx.source.to_public_key(), to: new_owner })
+                    } else {
+                        None
+                    }
+                },
+                RPCTransactionType::MultiSig(payload) => {
+                    if is_owner {
+                        assets_changed.insert(XELIS_ASSET);
+                        Some(EntryData::MultiSig { participants: payload.participants.iter().map(|p| p.get_public_key().clone()).collect(), threshold: payload.threshold })
+                    } else {
+                        None
+                    }
+                },
+                RPCTransactionType::Unlock(payload) => {
+                    if is_owner {
+                        // Unlocked funds are credited to us and fee is paid in XELIS
+                        assets_changed.insert(XELIS_ASSET);
+                        assets_changed.insert(payload.asset.clone());
+                        let payload = payload.into_owned();
+                        Some(EntryData::Unlock { id: payload.id, asset: payload.asset })
+                    } else {
+                        None
+                    }
+                },
+                RPCTransactionType::DeployContract(_) => {
+                    if is_owner {
+                        assets_changed.insert(XELIS_ASSET);
+                        // Contract is identified by the hash of its deploy TX
+                        Some(EntryData::DeployContract { contract: tx.hash.as_ref().clone() })
+                    } else {
+                        None
+                    }
+                },
+                RPCTransactionType::InvokeContract(payload) => {
+                    if is_owner {
+                        // Gas and fee are paid in XELIS
+                        assets_changed.insert(XELIS_ASSET);
+                        let payload = payload.into_owned();
+                        Some(EntryData::InvokeContract { contract: payload.contract, max_gas: payload.max_gas })
+                    } else {
+                        None
+                    }
+                },
                 RPCTransactionType::Transfers(txs) => {
-                    let mut transfers_in: Vec<TransferIn> = Vec::new();
-                    let mut transfers_out: Vec<TransferOut> = Vec::new();
-                    for transfer in txs {
+                    // Amounts of all the transfers are decrypted at once
+                    let mut pending = Vec::new();
+                    let mut ciphertexts = Vec::new();
+                    for (i, transfer) in txs.into_iter().enumerate() {
                         let destination = transfer.destination.to_public_key();
                         if is_owner || destination == *address.get_public_key() {
+                            // Locked funds are only credited once unlocked
+                            if !is_owner && transfer.lock.is_some() {
+                                info!("Locked transfer {} received in TX {}, it can be claimed using its id", get_lock_id(&tx.hash, i as u8), tx.hash);
+                                continue;
+                            }
+
                             // Get the right handle
                             let (role, handle) = if is_owner {
                                 (Role::Sender, transfer.sender_handle)
@@ -300,20 +420,26 @@ impl NetworkHandler {
                                 None
                             };
 
-                            debug!("Decrypting amount from TX {}", tx.hash);
-                            let ciphertext = Ciphertext::new(commitment, handle);
-                            let amount = Arc::clone(&self.wallet).decrypt_ciphertext(ciphertext).await?;
-
                             let asset = transfer.asset.into_owned();
                             assets_changed.insert(asset.clone());
 
-                            if is_owner {
-                                let transfer = TransferOut::new(destination, asset, amount, extra_data);
-                                transfers_out.push(transfer);
-                            } else {
-                                let transfer = TransferIn::new(asset, amount, extra_data);
-                                transfers_in.push(transfer);
-                            }
+                            ciphertexts.push(Ciphertext::new(commitment, handle));
+                            pending.push((destination, asset, extra_data));
+                        }
+                    }
+
+                    debug!("Decrypting {} amounts from TX {}", ciphertexts.len(), tx.hash);
+                    let amounts = Arc::clone(&self.wallet).decrypt_ciphertexts(ciphertexts).await?;
+
+                    let mut transfers_in: Vec<TransferIn> = Vec::new();
+                    let mut transfers_out: Vec<TransferOut> = Vec::new();
+                    for ((destination, asset, extra_data), amount) in pending.into_iter().zip(amounts) {
+                        if is_owner {
+                            let transfer = TransferOut::new(destination, asset, amount, extra_data);
+                            transfers_out.push(transfer);
+                        } else {
+                            let transfer = TransferIn::new(asset, amount, extra_data);
+                            transfers_in.push(transfer);
                         }
                     }
 
@@ -392,9 +518,27 @@ impl NetworkHandler {
 
     // Scan the chain using a specific balance asset, this helps us to get a list of version to only requests blocks where changes happened
     // When the block is requested, we don't limit the syncing to asset in parameter
-    async fn get_balance_and_transactions(&self, topoheight_processed: &mut HashSet<u64>, address: &Address, asset: &Hash, min_topoheight: u64, balances: bool, highest_nonce: &mut Option<u64>) -> Result<(), Error> {
+    // If from is set, the scan is resumed below this topoheight already processed
+    async fn get_balance_and_transactions(&self, address: &Address, asset: &Hash, min_topoheight: u64, from: Option<u64>, balances: bool, state: &mut SyncState) -> Result<(), Error> {
+        let resumed = match from {
+            Some(from) => match self.api.get_balance_at_topoheight(address, asset, from).await {
+                Ok(version) => {
+                    state.topoheight_processed.insert(from);
+                    Some((from, version))
+                },
+                Err(e) => {
+                    debug!("No balance version at topoheight {} for asset {}, scanning it from the top: {}", from, asset, e);
+                    None
+                }
+            },
+            None => None
+        };
+
         // Retrieve the highest version
-        let (mut topoheight, mut version) = self.api.get_balance(address, asset).await.map(|res| (res.topoheight, res.version))?;
+        let (mut topoheight, mut version) = match resumed {
+            Some(resumed) => resumed,
+            None => self.api.get_balance(address, asset).await.map(|res| (res.topoheight, res.version))?
+        };
         // don't sync already synced blocks
         if min_topoheight >= topoheight {
             return Ok(())
@@ -402,12 +546,13 @@ impl NetworkHandler {
 
         // Determine if its the highest version of balance or not
         // This is used to save the latest balance
-        let mut highest_version = true;
+        // A resumed scan already stored it
+        let mut highest_version = from.is_none();
         loop {
             let (mut balance, _, _, previous_topoheight) = version.consume();
             // add this topoheight in cache to not re-process it (blocks are independant of asset to have faster sync)
             // if its not already processed, do it
-            if topoheight_processed.insert(topoheight) {
+            if state.topoheight_processed.insert(topoheight) {
                 let response = self.api.get_block_with_txs_at_topoheight(topoheight).await?;
                 let changes = self.process_block(address, response, topoheight).await?;
 
@@ -415,18 +560,24 @@ impl NetworkHandler {
                 if let Some((_, nonce)) = changes.filter(|_| balances && highest_version) {
                     let mut storage = self.wallet.get_storage().write().await;
 
-                    if highest_nonce.is_none() {
+                    if state.highest_nonce.is_none() {
                         // Get the highest nonce from storage
-                        *highest_nonce = Some(storage.get_nonce()?);
+                        state.highest_nonce = Some(storage.get_nonce()?);
                     }
 
                     // Store only the highest nonce
                     // Because if we are building queued transactions, it may break our queue
                     // Our we couldn't submit new txs before they get removed from mempool
-                    if let Some(nonce) = nonce.filter(|n| highest_nonce.as_ref().map(|h| *h < *n).unwrap_or(true)) {
+                    if let Some(nonce) = nonce.filter(|n| state.highest_nonce.as_ref().map(|h| *h < *n).unwrap_or(true)) {
                         debug!("Storing new highest nonce {}", nonce);
+                        let previous = storage.get_nonce().ok();
+                        let expected = storage.get_unconfirmed_nonce();
                         storage.set_nonce(nonce)?;
-                        *highest_nonce = Some(nonce);
+                        state.highest_nonce = Some(nonce);
+
+                        if let Some(previous) = previous {
+                            self.wallet.on_nonce_synced(previous, expected, nonce).await;
+                        }
                     }
 
                     // If we have no balance in storage OR the stored ciphertext isn't the same, we should store it
@@ -452,6 +603,7 @@ impl NetworkHandler {
                     }
                 }
             }
+            self.on_block_synced(state, asset, topoheight).await?;
 
             // Prepare a new iteration
             if let Some(previous) = previous_topoheight {
@@ -471,6 +623,42 @@ impl NetworkHandler {
         }
     }
 
+    // Fast sync strategy using the daemon account history API
+    // Daemon returns us only the topoheights where our address was involved for this asset,
+    // so we don't have to walk the balance versions one by one
+    // Balances and nonce are not updated here, they must be synced by the head state
+    // If maximum topoheight is set, the blocks above it are not requested
+    async fn get_transactions_from_history(&self, address: &Address, asset: &Hash, min_topoheight: u64, mut maximum_topoheight: Option<u64>, state: &mut SyncState) -> Result<(), Error> {
+        loop {
+            let history = self.api.get_account_history(address, asset, Some(min_topoheight), maximum_topoheight).await?;
+            // History is ordered from highest to lowest topoheight
+            let lowest_topoheight = match history.last() {
+                Some(entry) => entry.topoheight,
+                None => return Ok(())
+            };
+
+            for entry in history {
+                // don't sync already synced blocks
+                // and don't request the same block several times
+                if entry.topoheight > min_topoheight && state.topoheight_processed.insert(entry.topoheight) {
+                    let response = self.api.get_block_with_txs_at_topoheight(entry.topoheight).await?;
+                    self.process_block(address, response, entry.topoheight).await?;
+                }
+
+                if entry.topoheight > min_topoheight {
+                    self.on_block_synced(state, asset, entry.topoheight).await?;
+                }
+            }
+
+            if lowest_topoheight <= min_topoheight {
+                return Ok(())
+            }
+
+            // Request the next page below the lowest topoheight received
+            maximum_topoheight = Some(lowest_topoheight - 1);
+        }
+    }
+
     // Locate the last topoheight valid for syncing, this support soft forks, DAG reorgs, etc...
     // Balances and nonce may be outdated, but we will sync them later
     // All transactions / changes above the last valid topoheight will be deleted
@@ -642,20 +830,28 @@ impl NetworkHandler {
         for asset in &assets {
             trace!("asset: {}", asset);
             // check if we have this asset locally
-            if !{
+            // Assets tracked before the metadata registry existed are fetched again for it
+            let (tracked, has_data) = {
                 let storage = self.wallet.get_storage().read().await;
-                storage.contains_asset(&asset).await?
-            } {
+                (storage.contains_asset(&asset).await?, storage.has_asset_data(&asset)?)
+            };
+
+            if !tracked || !has_data {
                 let data = self.api.get_asset(&asset).await?;
-                
+
                 // Add the asset to the storage
                 {
                     let mut storage = self.wallet.get_storage().write().await;
-                    storage.add_asset(&asset, data.get_decimals()).await?;
+                    storage.set_asset_data(&asset, &data)?;
+                    if !tracked {
+                        storage.add_asset(&asset, data.get_decimals()).await?;
+                    }
                 }
 
                 // New asset added to the wallet, inform listeners
-                self.wallet.propagate_event(Event::NewAsset(AssetWithData::new(asset.clone(), data))).await;
+                if !tracked {
+                    self.wallet.propagate_event(Event::NewAsset(AssetWithData::new(asset.clone(), data))).await;
+                }
             }
 
             // get the balance for this asset
@@ -669,14 +865,22 @@ impl NetworkHandler {
         {
             if let Some(new_nonce) = new_nonce {
                 let mut storage = self.wallet.get_storage().write().await;
-                if storage.get_nonce().map(|n| n != new_nonce).unwrap_or(true) {
+                let previous = storage.get_nonce().ok();
+                if previous.map(|n| n != new_nonce).unwrap_or(true) {
                     // Store the new nonce
                     debug!("Storing new nonce {}", new_nonce);
+                    let expected = storage.get_unconfirmed_nonce();
                     storage.set_nonce(new_nonce)?;
                     should_sync_blocks = true;
+
+                    if let Some(previous) = previous {
+                        self.wallet.on_nonce_synced(previous, expected, new_nonce).await;
+                    }
                 }
             }
 
+            // Balances to update with their plaintext value if it is already known
+            let mut updates = Vec::new();
             for (asset, mut ciphertext) in balances {
                 let (must_update, balance_cache) = {
                     let storage = self.wallet.get_storage().read().await;
@@ -698,26 +902,38 @@ impl NetworkHandler {
 
                 if must_update {
                     trace!("must update balance for asset: {}, ct: {:?}", asset, ciphertext.to_bytes());
-                    let value = if let Some(cache) = balance_cache {
-                        cache
-                    } else {
-                        trace!("Decrypting balance for asset {}", asset);
-                        Arc::clone(&self.wallet).decrypt_ciphertext(ciphertext.decompressed()?.clone()).await?
-                    };
+                    updates.push((asset, ciphertext, balance_cache));
+                }
+            }
+
+            // Balances not in cache are decrypted at once
+            let mut ciphertexts = Vec::new();
+            for (asset, ciphertext, balance_cache) in updates.iter_mut() {
+                if balance_cache.is_none() {
+                    trace!("Decrypting balance for asset {}", asset);
+                    ciphertexts.push(ciphertext.decompressed()?.clone());
+                }
+            }
+            let mut decrypted = Arc::clone(&self.wallet).decrypt_ciphertexts(ciphertexts).await?.into_iter();
 
-                    // Inform the change of the balance
-                    self.wallet.propagate_event(Event::BalanceChanged(BalanceChanged {
-                        asset: asset.clone(),
-                        balance: value
-                    })).await;
+            for (asset, ciphertext, balance_cache) in updates {
+                let value = match balance_cache.or_else(|| decrypted.next()) {
+                    Some(value) => value,
+                    None => return Err(WalletError::CiphertextDecode.into())
+                };
 
-                    // Update the balance
-                    let mut storage = self.wallet.get_storage().write().await;
-                    storage.set_balance_for(asset, Balance::new(value, ciphertext)).await?;
+                // Inform the change of the balance
+                self.wallet.propagate_event(Event::BalanceChanged(BalanceChanged {
+                    asset: asset.clone(),
+                    balance: value
+                })).await;
 
-                    // We should sync new blocks to get the TXs
-                    should_sync_blocks = true;
-                }
+                // Update the balance
+                let mut storage = self.wallet.get_storage().write().await;
+                storage.set_balance_for(asset, Balance::new(value, ciphertext)).await?;
+
+                // We should sync new blocks to get the TXs
+                should_sync_blocks = true;
             }
         }
 
@@ -731,6 +947,13 @@ impl NetworkHandler {
         // First, locate the last topoheight valid for syncing
         let (daemon_topoheight, daemon_block_hash, wallet_topoheight, sync_back) = self.locate_sync_topoheight_and_clean().await?;
         debug!("Daemon topoheight: {}, wallet topoheight: {}, sync back: {}", daemon_topoheight, wallet_topoheight, sync_back);
+        self.daemon_topoheight.store(daemon_topoheight, Ordering::SeqCst);
+
+        // An interrupted sync is resumed where it stopped
+        let checkpoint = self.get_resumable_checkpoint(wallet_topoheight).await?;
+        let resume = checkpoint.is_some();
+        // The head state may not have been fully synced before the interruption
+        let sync_back = sync_back || resume;
 
         let mut sync_new_blocks = false;
         // Sync back is requested, sync the head state again
@@ -768,9 +991,21 @@ impl NetworkHandler {
         }
 
         // we have something that changed, sync transactions
-        if sync_new_blocks {
+        let mut checkpoint_stored = resume;
+        if sync_new_blocks || resume {
             debug!("Syncing new blocks");
-            self.sync_new_blocks(address, wallet_topoheight, true).await?;
+            let checkpoint = checkpoint.unwrap_or_else(|| SyncCheckpoint::new(wallet_topoheight, daemon_topoheight, daemon_block_hash.clone()));
+            let mut state = SyncState::new(checkpoint, resume);
+            if sync_back {
+                // Head state is already synced, we only need the blocks where we were involved
+                if let Err(e) = self.sync_new_blocks_from_history(address, &mut state).await {
+                    warn!("Error while syncing using account history, fallback to balance versions: {}", e);
+                    self.sync_new_blocks(address, true, &mut state).await?;
+                }
+            } else {
+                self.sync_new_blocks(address, true, &mut state).await?;
+            }
+            checkpoint_stored = state.stored;
         }
 
         // Update the topoheight and block hash for wallet
@@ -778,11 +1013,48 @@ impl NetworkHandler {
             let mut storage = self.wallet.get_storage().write().await;
             storage.set_synced_topoheight(daemon_topoheight)?;
             storage.set_top_block_hash(&daemon_block_hash)?;
+            if checkpoint_stored {
+                storage.delete_sync_checkpoint()?;
+            }
         }
 
         // Propagate the event
         self.wallet.propagate_event(Event::NewTopoHeight { topoheight: daemon_topoheight }).await;
         debug!("Synced to topoheight {}", daemon_topoheight);
+
+        // Transactions built by the wallet are confirmed or broadcasted again
+        if let Err(e) = self.wallet.process_pending_transactions(daemon_topoheight).await {
+            warn!("Error while processing pending transactions: {}", e);
+        }
+
+        // Payments due are executed once the balances are synced
+        if let Err(e) = self.wallet.execute_scheduled_payments(daemon_topoheight).await {
+            warn!("Error while executing scheduled payments: {}", e);
+        }
+
+        // Notifications queued during the sync are sent to the webhooks
+        self.wallet.deliver_webhooks().await;
+        Ok(())
+    }
+
+    // Search the accounts derived from the seed which are registered on chain
+    // It stops once ACCOUNTS_GAP_LIMIT accounts in a row are not registered
+    async fn recover_accounts(&self) -> Result<(), Error> {
+        info!("Searching the accounts used by this wallet");
+        let mut last_used = 0;
+        let mut index = 1;
+        while index - last_used <= ACCOUNTS_GAP_LIMIT {
+            let address = self.wallet.get_account_address(index)?;
+            if self.api.is_account_registered(&address, false).await? {
+                debug!("Account {} is registered", index);
+                last_used = index;
+            }
+            index += 1;
+        }
+
+        info!("{} accounts found", last_used + 1);
+        self.wallet.set_recovered_accounts(last_used + 1).await?;
+
         Ok(())
     }
 
@@ -790,6 +1062,11 @@ impl NetworkHandler {
     // Because of potential forks and DAG reorg during attacks,
     // we verify the last valid topoheight where changes happened
     async fn start_syncing(self: &Arc<Self>) -> Result<(), Error> {
+        // Wallet restored from its seed, search the accounts it was using
+        if self.wallet.is_accounts_recovery_pending().await? {
+            self.recover_accounts().await?;
+        }
+
         // Generate only one time the address
         let address = self.wallet.get_address();
         // Do a first sync to be up-to-date with the daemon
@@ -815,10 +1092,16 @@ impl NetworkHandler {
             tokio::select! {
                 // Wait on a new block, we don't parse the block directly as it may
                 // have reorg the chain
-                res = on_new_block.next() => {
+                res = on_new_block.recv() => {
                     trace!("on_new_block_event");
-                    let event = res?;
-                    self.sync(&address, Some(event)).await?;
+                    match res? {
+                        EventMessage::Event(event) => self.sync(&address, Some(event)).await?,
+                        // Blocks may have been missed while offline, make sure we are still up-to-date with node
+                        EventMessage::ResyncRequired => {
+                            debug!("Resync required by the daemon connection");
+                            self.sync(&address, None).await?;
+                        }
+                    };
                 },
                 res = on_block_ordered.next() => {
                     trace!("on_block_ordered_event");
@@ -866,12 +1149,10 @@ impl NetworkHandler {
                     }
                 },
                 // Detect network events
+                // Syncing again is done on the resync marker, once the events are subscribed again
                 res = on_connection.recv() => {
                     trace!("on_connection");
                     res?;
-                    // We are connected again, make sure we are still up-to-date with node 
-                    self.sync(&address, None).await?;
-
                     self.wallet.propagate_event(Event::Online).await;
                 },
                 res = on_connection_lost.recv() => {
@@ -883,25 +1164,149 @@ impl NetworkHandler {
         }
     }
 
+    // Load the checkpoint of an interrupted sync if it can be resumed from the wallet topoheight
+    // The checkpoint is deleted if its target block is not in the chain anymore
+    async fn get_resumable_checkpoint(&self, wallet_topoheight: u64) -> Result<Option<SyncCheckpoint>, Error> {
+        let checkpoint = {
+            let storage = self.wallet.get_storage().read().await;
+            storage.get_sync_checkpoint()?
+        };
+
+        let Some(checkpoint) = checkpoint else {
+            return Ok(None)
+        };
+
+        if checkpoint.get_start_topoheight() == wallet_topoheight {
+            let valid = match self.api.get_block_at_topoheight(checkpoint.get_target_topoheight()).await {
+                Ok(header) => *header.hash == *checkpoint.get_target_block_hash(),
+                Err(e) => {
+                    debug!("Error while retrieving the block at topoheight {}: {}", checkpoint.get_target_topoheight(), e);
+                    false
+                }
+            };
+
+            if valid {
+                info!("Resuming the sync from topoheight {} to {}", checkpoint.get_start_topoheight(), checkpoint.get_target_topoheight());
+                return Ok(Some(checkpoint))
+            }
+        }
+
+        debug!("Sync checkpoint can't be resumed, deleting it");
+        let mut storage = self.wallet.get_storage().write().await;
+        storage.delete_sync_checkpoint()?;
+        Ok(None)
+    }
+
+    // A block was scanned for the asset, notify the progress
+    // The checkpoint is saved every SYNC_CHECKPOINT_INTERVAL topoheights
+    async fn on_block_synced(&self, state: &mut SyncState, asset: &Hash, topoheight: u64) -> Result<(), Error> {
+        // Blocks above the target are not part of the checkpoint
+        if topoheight > state.checkpoint.get_target_topoheight() {
+            return Ok(())
+        }
+
+        state.checkpoint.set_cursor(asset, topoheight);
+        if state.saved_topoheight.saturating_sub(topoheight) >= SYNC_CHECKPOINT_INTERVAL {
+            trace!("saving sync checkpoint at topoheight {} for asset {}", topoheight, asset);
+            let mut storage = self.wallet.get_storage().write().await;
+            storage.set_sync_checkpoint(&state.checkpoint)?;
+            state.saved_topoheight = topoheight;
+            state.stored = true;
+        }
+
+        let (current, target) = state.checkpoint.get_progress(state.assets);
+        self.wallet.propagate_event(Event::SyncProgress { current, target }).await;
+        Ok(())
+    }
+
+    // The asset is synced until the start topoheight
+    async fn on_asset_synced(&self, state: &mut SyncState, asset: &Hash) -> Result<(), Error> {
+        state.checkpoint.mark_asset_done(asset);
+        state.saved_topoheight = state.checkpoint.get_target_topoheight();
+
+        // Small syncs are not saved
+        if state.stored {
+            let mut storage = self.wallet.get_storage().write().await;
+            storage.set_sync_checkpoint(&state.checkpoint)?;
+        }
+
+        let (current, target) = state.checkpoint.get_progress(state.assets);
+        self.wallet.propagate_event(Event::SyncProgress { current, target }).await;
+        Ok(())
+    }
+
+    // Sync all new blocks until the current topoheight using the account history API
+    // This is much faster for old accounts as only the blocks where we were involved are requested
+    async fn sync_new_blocks_from_history(&self, address: &Address, state: &mut SyncState) -> Result<(), Error> {
+        let assets = {
+            let storage = self.wallet.get_storage().read().await;
+            storage.get_assets().await?
+        };
+        state.assets = assets.len();
+
+        // Daemon can't give us any history below its pruned topoheight
+        let pruned_topoheight = self.api.get_info().await?.pruned_topoheight.unwrap_or(0);
+        let min_topoheight = state.checkpoint.get_start_topoheight().max(pruned_topoheight);
+        let target_topoheight = state.checkpoint.get_target_topoheight().max(pruned_topoheight);
+
+        for asset in assets {
+            if state.checkpoint.is_asset_done(&asset) {
+                // Only the blocks added since the checkpoint are left
+                debug!("syncing transactions from history for asset {} above topoheight {}", asset, target_topoheight);
+                self.get_transactions_from_history(address, &asset, target_topoheight, None, state).await?;
+                continue;
+            }
+
+            if let Some(cursor) = state.checkpoint.get_cursor(&asset) {
+                debug!("resuming transactions from history for asset {} below topoheight {}", asset, cursor);
+                self.get_transactions_from_history(address, &asset, target_topoheight, None, state).await?;
+                self.get_transactions_from_history(address, &asset, min_topoheight, cursor.checked_sub(1), state).await?;
+            } else {
+                debug!("syncing transactions from history for asset {} above topoheight {}", asset, min_topoheight);
+                self.get_transactions_from_history(address, &asset, min_topoheight, None, state).await?;
+            }
+            self.on_asset_synced(state, &asset).await?;
+        }
+
+        Ok(())
+    }
+
     // Sync all new blocks until the current topoheight
-    async fn sync_new_blocks(&self, address: &Address, current_topoheight: u64, balances: bool) -> Result<(), Error> {
+    async fn sync_new_blocks(&self, address: &Address, balances: bool, state: &mut SyncState) -> Result<(), Error> {
         let assets = {
             let storage = self.wallet.get_storage().read().await;
             storage.get_assets().await?
         };
+        state.assets = assets.len();
 
-        // cache for all topoheight we already processed
-        // this will prevent us to request more than one time the same topoheight
-        let mut topoheight_processed = HashSet::new();
+        let min_topoheight = state.checkpoint.get_start_topoheight();
+        let target_topoheight = state.checkpoint.get_target_topoheight();
 
         // get balance and transactions for each asset
-        let mut highest_nonce = None;
         for asset in assets {
-            debug!("calling get balances and transactions {}", current_topoheight);
-            if let Err(e) = self.get_balance_and_transactions(&mut topoheight_processed, &address, &asset, current_topoheight, balances, &mut highest_nonce).await {
+            if state.checkpoint.is_asset_done(&asset) {
+                // Only the blocks added since the checkpoint are left
+                debug!("calling get balances and transactions {}", target_topoheight);
+                if let Err(e) = self.get_balance_and_transactions(&address, &asset, target_topoheight, None, balances, state).await {
+                    error!("Error while syncing balance for asset {}: {}", asset, e);
+                }
+                continue;
+            }
+
+            let cursor = state.checkpoint.get_cursor(&asset);
+            if cursor.is_some() {
+                debug!("calling get balances and transactions {}", target_topoheight);
+                if let Err(e) = self.get_balance_and_transactions(&address, &asset, target_topoheight, None, balances, state).await {
+                    error!("Error while syncing balance for asset {}: {}", asset, e);
+                }
+            }
+
+            debug!("calling get balances and transactions {}", min_topoheight);
+            if let Err(e) = self.get_balance_and_transactions(&address, &asset, min_topoheight, cursor, balances, state).await {
                 error!("Error while syncing balance for asset {}: {}", asset, e);
             }
+            self.on_asset_synced(state, &asset).await?;
         }
         Ok(())
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,128 @@
+use indexmap::IndexMap;
+use xelis_common::{
+    crypto::{Hash, PublicKey},
+    serializer::Serializer,
+    transaction::{
+        builder::{
+            AssetOwnershipBuilder,
+            MultiSigBuilder,
+            TransactionTypeBuilder,
+            TransferBuilder
+        },
+        Transaction,
+        TransactionType
+    }
+};
+use crate::entry::{EntryData, TransferOut};
+
+// Transaction built by the wallet which is not executed in a block yet
+// It is persisted in the wallet storage until the sync finds it in a block
+#[derive(Debug, Clone, Serializer)]
+pub struct PendingTransaction {
+    transaction: Transaction,
+    // Entry saved in the history once executed, amounts are known from the builder
+    entry: EntryData,
+    // Amount spent for each asset, fee included
+    costs: IndexMap<Hash, u64>,
+    // Topoheight at which it was broadcasted the last time
+    topoheight: u64,
+    // How many times it was broadcasted again
+    rebroadcasts: u8
+}
+
+impl PendingTransaction {
+    pub fn new(transaction: Transaction, entry: EntryData, costs: IndexMap<Hash, u64>, topoheight: u64) -> Self {
+        Self {
+            transaction,
+            entry,
+            costs,
+            topoheight,
+            rebroadcasts: 0
+        }
+    }
+
+    pub fn get_transaction(&self) -> &Transaction {
+        &self.transaction
+    }
+
+    pub fn get_entry(&self) -> &EntryData {
+        &self.entry
+    }
+
+    pub fn get_cost(&self, asset: &Hash) -> u64 {
+        self.costs.get(asset).copied().unwrap_or(0)
+    }
+
+    pub fn get_topoheight(&self) -> u64 {
+        self.topoheight
+    }
+
+    pub fn get_rebroadcasts(&self) -> u8 {
+        self.rebroadcasts
+    }
+
+    // Mark it as broadcasted again at this topoheight
+    pub fn set_rebroadcasted(&mut self, topoheight: u64) {
+        self.topoheight = topoheight;
+        self.rebroadcasts = self.rebroadcasts.saturating_add(1);
+    }
+
+    // Rebuild the transaction type to build it again with another fee
+    // Amounts and extra data of the transfers are only known from the entry
+    pub fn get_transaction_type(&self, mainnet: bool) -> Option<TransactionTypeBuilder> {
+        let tx_type = match (self.transaction.get_data(), &self.entry) {
+            (TransactionType::Transfers(payloads), EntryData::Outgoing { transfers, .. }) => TransactionTypeBuilder::Transfers(
+                payloads.iter().zip(transfers).map(|(payload, transfer)| TransferBuilder {
+                    asset: transfer.get_asset().clone(),
+                    amount: transfer.get_amount(),
+                    destination: transfer.get_destination().clone().to_address(mainnet),
+                    extra_data: transfer.get_extra_data().clone(),
+                    lock: payload.get_lock().clone()
+                }).collect()
+            ),
+            (TransactionType::Burn(payload), _) => TransactionTypeBuilder::Burn(payload.clone()),
+            (TransactionType::CreateAsset(payload), _) => TransactionTypeBuilder::CreateAsset(payload.clone()),
+            (TransactionType::MintAsset(payload), _) => TransactionTypeBuilder::MintAsset(payload.clone()),
+            (TransactionType::TransferAssetOwnership(payload), _) => TransactionTypeBuilder::TransferAssetOwnership(AssetOwnershipBuilder {
+                asset: payload.asset.clone(),
+                new_owner: payload.new_owner.clone().to_address(mainnet)
+            }),
+            (TransactionType::MultiSig(payload), _) => TransactionTypeBuilder::MultiSig(MultiSigBuilder {
+                threshold: payload.threshold,
+                participants: payload.participants.iter().map(|key| key.clone().to_address(mainnet)).collect()
+            }),
+            (TransactionType::Unlock(payload), _) => TransactionTypeBuilder::Unlock(payload.clone()),
+            (TransactionType::DeployContract(payload), _) => TransactionTypeBuilder::DeployContract(payload.clone()),
+            (TransactionType::InvokeContract(payload), _) => TransactionTypeBuilder::InvokeContract(payload.clone()),
+            _ => return None
+        };
+
+        Some(tx_type)
+    }
+}
+
+// Build the history entry of a transaction created by us
+// Assets and contracts created are identified by the TX hash
+pub fn entry_from_builder(tx_type: TransactionTypeBuilder, hash: &Hash, source: &PublicKey, fee: u64, nonce: u64) -> EntryData {
+    match tx_type {
+        TransactionTypeBuilder::Transfers(transfers) => EntryData::Outgoing {
+            transfers: transfers.into_iter()
+                .map(|mut t| {
+                    // Data integrated in the address is sent as extra data
+                    let extra_data = t.extra_data.or_else(|| t.destination.extract_data_only());
+                    TransferOut::new(t.destination.to_public_key(), t.asset, t.amount, extra_data)
+                })
+                .collect(),
+            fee,
+            nonce
+        },
+        TransactionTypeBuilder::Burn(payload) => EntryData::Burn { asset: payload.asset, amount: payload.amount },
+        TransactionTypeBuilder::CreateAsset(payload) => EntryData::AssetCreation { asset: hash.clone(), name: payload.name, decimals: payload.decimals, max_supply: payload.max_supply },
+        TransactionTypeBuilder::MintAsset(payload) => EntryData::AssetMint { asset: payload.asset, amount: payload.amount },
+        TransactionTypeBuilder::TransferAssetOwnership(builder) => EntryData::AssetOwnershipTransfer { asset: builder.asset, from: source.clone(), to: builder.new_owner.to_public_key() },
+        TransactionTypeBuilder::MultiSig(builder) => EntryData::MultiSig { participants: builder.participants.into_iter().map(|p| p.to_public_key()).collect(), threshold: builder.threshold },
+        TransactionTypeBuilder::Unlock(payload) => EntryData::Unlock { id: payload.id, asset: payload.asset },
+        TransactionTypeBuilder::DeployContract(_) => EntryData::DeployContract { contract: hash.clone() },
+        TransactionTypeBuilder::InvokeContract(payload) => EntryData::InvokeContract { contract: payload.contract, max_gas: payload.max_gas }
+    }
+}
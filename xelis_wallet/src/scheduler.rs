@@ -0,0 +1,89 @@
+use indexmap::IndexMap;
+use xelis_common::{
+    api::wallet::{
+        PaymentTrigger,
+        ScheduledPayment,
+        ScheduledPaymentEntry
+    },
+    serializer::Serializer,
+    time::TimestampSeconds
+};
+
+// Scheduled payments of an account
+// This is persisted in the wallet storage
+#[derive(Debug, Clone, Default, Serializer)]
+pub struct ScheduledPayments {
+    // Id of the next payment added
+    next_id: u64,
+    payments: IndexMap<u64, ScheduledPayment>
+}
+
+impl ScheduledPayments {
+    // Add a new payment and returns its id
+    pub fn add(&mut self, payment: ScheduledPayment) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.payments.insert(id, payment);
+        id
+    }
+
+    // Returns true if the payment was found
+    pub fn remove(&mut self, id: u64) -> bool {
+        self.payments.shift_remove(&id).is_some()
+    }
+
+    pub fn get_entries(&self) -> Vec<ScheduledPaymentEntry> {
+        self.payments.iter()
+            .map(|(id, payment)| ScheduledPaymentEntry { id: *id, payment: payment.clone() })
+            .collect()
+    }
+
+    // Payments due at this topoheight or time
+    pub fn get_due(&self, topoheight: u64, timestamp: TimestampSeconds) -> Vec<ScheduledPaymentEntry> {
+        self.payments.iter()
+            .filter(|(_, payment)| is_due(&payment.next_execution, topoheight, timestamp))
+            .map(|(id, payment)| ScheduledPaymentEntry { id: *id, payment: payment.clone() })
+            .collect()
+    }
+
+    // Move a recurring payment to its first execution after this topoheight or time
+    // Executions missed while offline are skipped, a single payment is deleted
+    pub fn reschedule(&mut self, id: u64, topoheight: u64, timestamp: TimestampSeconds) {
+        let interval = match self.payments.get(&id) {
+            Some(payment) => payment.interval,
+            None => return
+        };
+
+        let interval = match interval {
+            Some(interval) if interval > 0 => interval,
+            _ => {
+                self.payments.shift_remove(&id);
+                return
+            }
+        };
+
+        if let Some(payment) = self.payments.get_mut(&id) {
+            payment.next_execution = match payment.next_execution {
+                PaymentTrigger::Topoheight(next) => PaymentTrigger::Topoheight(next_after(next, interval, topoheight)),
+                PaymentTrigger::Timestamp(next) => PaymentTrigger::Timestamp(next_after(next, interval, timestamp))
+            };
+        }
+    }
+}
+
+fn is_due(trigger: &PaymentTrigger, topoheight: u64, timestamp: TimestampSeconds) -> bool {
+    match trigger {
+        PaymentTrigger::Topoheight(next) => *next <= topoheight,
+        PaymentTrigger::Timestamp(next) => *next <= timestamp
+    }
+}
+
+// First execution strictly above the current value
+fn next_after(next: u64, interval: u64, current: u64) -> u64 {
+    if next > current {
+        return next
+    }
+
+    let skipped = (current - next) / interval + 1;
+    next.saturating_add(skipped.saturating_mul(interval))
+}
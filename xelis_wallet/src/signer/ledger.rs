@@ -0,0 +1,190 @@
+use std::sync::{Arc, Mutex};
+use async_trait::async_trait;
+use hidapi::{HidApi, HidDevice, HidError};
+use log::{debug, trace};
+use thiserror::Error;
+use xelis_common::{
+    crypto::{
+        elgamal::CompressedPublicKey,
+        Signature
+    },
+    serializer::Serializer
+};
+use super::{Signer, SignerError};
+
+const LEDGER_VENDOR_ID: u16 = 0x2c97;
+// Usage page of the interface used by the apps
+const LEDGER_USAGE_PAGE: u16 = 0xffa0;
+
+// HID framing of the APDUs
+const HID_PACKET_SIZE: usize = 64;
+const HID_CHANNEL: u16 = 0x0101;
+const HID_TAG_APDU: u8 = 0x05;
+// channel, tag and sequence
+const HID_HEADER_SIZE: usize = 5;
+
+// APDUs handled by the XELIS app
+const CLA: u8 = 0xe0;
+const INS_GET_PUBLIC_KEY: u8 = 0x02;
+const INS_SIGN_TX: u8 = 0x03;
+// P1 of a sign chunk
+const P1_FIRST_CHUNK: u8 = 0x00;
+const P1_NEXT_CHUNK: u8 = 0x01;
+// P2 of a sign chunk
+const P2_MORE_CHUNKS: u8 = 0x80;
+const P2_LAST_CHUNK: u8 = 0x00;
+const MAX_APDU_DATA_SIZE: usize = 255;
+
+const SW_OK: u16 = 0x9000;
+const SW_USER_REJECTED: u16 = 0x6985;
+
+#[derive(Error, Debug)]
+pub enum LedgerError {
+    #[error(transparent)]
+    Hid(#[from] HidError),
+    #[error("No Ledger device found")]
+    DeviceNotFound,
+    #[error("Invalid HID frame received from the device")]
+    InvalidFrame,
+    #[error("Device returned status {:#06x}, is the XELIS app open?", _0)]
+    Status(u16)
+}
+
+// Ledger device running the XELIS app
+// Keys are derived on the device from its seed using the same account index as the wallet
+pub struct LedgerSigner {
+    device: Arc<Mutex<HidDevice>>,
+    account: u32,
+    public_key: CompressedPublicKey
+}
+
+impl LedgerSigner {
+    // Connect to the first Ledger device found and retrieve the public key of the account
+    pub fn connect(account: u32) -> Result<Self, SignerError> {
+        let api = HidApi::new().map_err(LedgerError::from)?;
+        let info = api.device_list()
+            .find(|info| info.vendor_id() == LEDGER_VENDOR_ID && info.usage_page() == LEDGER_USAGE_PAGE)
+            .ok_or(LedgerError::DeviceNotFound)?;
+
+        debug!("Connecting to Ledger device {:?}", info.product_string());
+        let device = info.open_device(&api).map_err(LedgerError::from)?;
+
+        let response = exchange(&device, INS_GET_PUBLIC_KEY, 0, 0, &account.to_be_bytes())?;
+        let public_key = CompressedPublicKey::from_bytes(&response)
+            .map_err(|e| SignerError::InvalidResponse(e.to_string()))?;
+
+        Ok(Self {
+            device: Arc::new(Mutex::new(device)),
+            account,
+            public_key
+        })
+    }
+
+    pub fn get_account(&self) -> u32 {
+        self.account
+    }
+}
+
+#[async_trait]
+impl Signer for LedgerSigner {
+    fn get_public_key(&self) -> &CompressedPublicKey {
+        &self.public_key
+    }
+
+    async fn sign_transaction(&self, data: &[u8]) -> Result<Signature, SignerError> {
+        // First chunk starts with the account index
+        let mut payload = self.account.to_be_bytes().to_vec();
+        payload.extend_from_slice(data);
+
+        let device = Arc::clone(&self.device);
+        // The device waits for the user confirmation, don't block the runtime
+        let response = tokio::task::spawn_blocking(move || {
+            let device = device.lock().map_err(|_| SignerError::Unavailable("device lock is poisoned".to_owned()))?;
+            let chunks: Vec<&[u8]> = payload.chunks(MAX_APDU_DATA_SIZE).collect();
+            let mut response = Vec::new();
+            for (i, chunk) in chunks.iter().enumerate() {
+                let p1 = if i == 0 { P1_FIRST_CHUNK } else { P1_NEXT_CHUNK };
+                let p2 = if i + 1 == chunks.len() { P2_LAST_CHUNK } else { P2_MORE_CHUNKS };
+                response = exchange(&device, INS_SIGN_TX, p1, p2, chunk)?;
+            }
+            Ok::<_, SignerError>(response)
+        }).await.map_err(|e| SignerError::Unavailable(e.to_string()))??;
+
+        Signature::from_bytes(&response)
+            .map_err(|e| SignerError::InvalidResponse(e.to_string()))
+    }
+}
+
+// Send an APDU to the device and wait for its response
+fn exchange(device: &HidDevice, ins: u8, p1: u8, p2: u8, data: &[u8]) -> Result<Vec<u8>, SignerError> {
+    trace!("APDU ins {:#04x} p1 {} p2 {} with {} bytes", ins, p1, p2, data.len());
+    let mut apdu = vec![CLA, ins, p1, p2, data.len() as u8];
+    apdu.extend_from_slice(data);
+
+    write_apdu(device, &apdu)?;
+    let mut response = read_apdu(device)?;
+    if response.len() < 2 {
+        return Err(LedgerError::InvalidFrame.into())
+    }
+
+    let sw_bytes = response.split_off(response.len() - 2);
+    match u16::from_be_bytes([sw_bytes[0], sw_bytes[1]]) {
+        SW_OK => Ok(response),
+        SW_USER_REJECTED => Err(SignerError::Rejected),
+        sw => Err(LedgerError::Status(sw).into())
+    }
+}
+
+// Split the APDU in HID packets, the first one is prefixed by the APDU length
+fn write_apdu(device: &HidDevice, apdu: &[u8]) -> Result<(), LedgerError> {
+    let mut data = (apdu.len() as u16).to_be_bytes().to_vec();
+    data.extend_from_slice(apdu);
+
+    for (sequence, chunk) in data.chunks(HID_PACKET_SIZE - HID_HEADER_SIZE).enumerate() {
+        // Report ID is required by hidapi
+        let mut packet = vec![0x00];
+        packet.extend_from_slice(&HID_CHANNEL.to_be_bytes());
+        packet.push(HID_TAG_APDU);
+        packet.extend_from_slice(&(sequence as u16).to_be_bytes());
+        packet.extend_from_slice(chunk);
+        packet.resize(HID_PACKET_SIZE + 1, 0);
+        device.write(&packet)?;
+    }
+
+    Ok(())
+}
+
+fn read_apdu(device: &HidDevice) -> Result<Vec<u8>, LedgerError> {
+    let mut response = Vec::new();
+    let mut expected = None;
+    let mut sequence: u16 = 0;
+    loop {
+        let mut packet = [0u8; HID_PACKET_SIZE];
+        // Block until the user confirms on the device
+        let read = device.read(&mut packet)?;
+        if read < HID_HEADER_SIZE
+            || u16::from_be_bytes([packet[0], packet[1]]) != HID_CHANNEL
+            || packet[2] != HID_TAG_APDU
+            || u16::from_be_bytes([packet[3], packet[4]]) != sequence {
+            return Err(LedgerError::InvalidFrame)
+        }
+
+        let mut data = &packet[HID_HEADER_SIZE..read];
+        if expected.is_none() {
+            if data.len() < 2 {
+                return Err(LedgerError::InvalidFrame)
+            }
+            expected = Some(u16::from_be_bytes([data[0], data[1]]) as usize);
+            data = &data[2..];
+        }
+
+        let expected = expected.unwrap_or(0);
+        let remaining = expected - response.len();
+        response.extend_from_slice(&data[..data.len().min(remaining)]);
+        if response.len() >= expected {
+            return Ok(response)
+        }
+
+        sequence = sequence.wrapping_add(1);
+    }
+}
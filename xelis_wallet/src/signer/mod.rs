@@ -0,0 +1,34 @@
+#[cfg(feature = "ledger")]
+pub mod ledger;
+
+use async_trait::async_trait;
+use thiserror::Error;
+use xelis_common::crypto::{
+    elgamal::CompressedPublicKey,
+    Signature
+};
+
+#[derive(Error, Debug)]
+pub enum SignerError {
+    #[error("Signer is not available: {}", _0)]
+    Unavailable(String),
+    #[error("Signature was rejected on the device")]
+    Rejected,
+    #[error("Invalid response from the signer: {}", _0)]
+    InvalidResponse(String),
+    #[cfg(feature = "ledger")]
+    #[error(transparent)]
+    Ledger(#[from] ledger::LedgerError)
+}
+
+// Sign the transactions of an account without exposing its key to the wallet
+// The proofs of a transaction are still generated by the wallet,
+// only the final signature over the unsigned transaction bytes is delegated
+#[async_trait]
+pub trait Signer: Send + Sync {
+    // Public key of the account signed for
+    fn get_public_key(&self) -> &CompressedPublicKey;
+
+    // Sign the bytes of an unsigned transaction
+    async fn sign_transaction(&self, data: &[u8]) -> Result<Signature, SignerError>;
+}
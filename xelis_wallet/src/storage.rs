@@ -1,6 +1,7 @@
 use std::{
     collections::{HashMap, HashSet, VecDeque},
-    num::NonZeroUsize
+    num::NonZeroUsize,
+    path::Path
 };
 use indexmap::IndexMap;
 use lru::LruCache;
@@ -11,11 +12,13 @@ use sled::{
 use tokio::sync::Mutex;
 use xelis_common::{
     account::CiphertextCache,
+    asset::{AssetData, AssetWithData},
     api::{
         query::{
             Query,
             QueryResult
         },
+        wallet::{Contact, ContactEntry, SearchTransactionsParams, TransactionLabel, XSWDLimitsSettings},
         DataElement,
         DataValue
     },
@@ -28,9 +31,7 @@ use xelis_common::{
     network::Network,
     serializer::{
         Reader,
-        ReaderError,
-        Serializer,
-        Writer
+        Serializer
     },
     transaction::Reference
 };
@@ -40,16 +41,21 @@ use anyhow::{
     anyhow
 };
 use crate::{
+    alerts::AlertsSettings,
     cipher::Cipher,
-    config::SALT_SIZE,
+    config::{SALT_SIZE, STORAGE_VERSION},
     entry::{
         EntryData,
         TransactionEntry,
         Transfer
     },
-    error::WalletError
+    error::WalletError,
+    pending::PendingTransaction,
+    scheduler::ScheduledPayments,
+    checkpoint::SyncCheckpoint,
+    webhooks::{WebhookQueue, WebhooksSettings}
 };
-use log::{trace, debug, error};
+use log::{trace, debug, error, info};
 
 // keys used to retrieve from storage
 const NONCE_KEY: &[u8] = b"NONCE";
@@ -59,6 +65,8 @@ const PASSWORD_SALT_KEY: &[u8] = b"PSALT";
 // Master key to encrypt/decrypt while interacting with the storage 
 const MASTER_KEY: &[u8] = b"MKEY";
 const PRIVATE_KEY: &[u8] = b"PKEY";
+// key encoded in the seed, only set when the private key is derived from it with a passphrase
+const SEED_KEY: &[u8] = b"SKEY";
 
 // const used for online mode
 // represent the daemon topoheight
@@ -66,11 +74,38 @@ const TOPOHEIGHT_KEY: &[u8] = b"TOPH";
 // represent the daemon top block hash
 const TOP_BLOCK_HASH_KEY: &[u8] = b"TOPBH";
 const NETWORK: &[u8] = b"NET";
+// alert rules and webhooks configured
+const ALERTS_KEY: &[u8] = b"ALERTS";
+// number of accounts derived from the private key
+const ACCOUNTS_COUNT_KEY: &[u8] = b"ACCOUNTS";
+// account selected when opening the wallet
+const SELECTED_ACCOUNT_KEY: &[u8] = b"SACCOUNT";
+// set when the accounts used must be searched on chain
+const ACCOUNTS_RECOVERY_KEY: &[u8] = b"RACCOUNTS";
+// version of the storage layout, not set by wallets created before it existed
+const STORAGE_VERSION_KEY: &[u8] = b"VERSION";
+// scheduled payments of the account
+const SCHEDULED_PAYMENTS_KEY: &[u8] = b"SCHEDULED";
+// webhooks notified of the wallet events
+const WEBHOOKS_KEY: &[u8] = b"WEBHOOKS";
+// webhook notifications not delivered yet
+const WEBHOOKS_QUEUE_KEY: &[u8] = b"WHQUEUE";
+// limits applied to the XSWD applications
+const XSWD_LIMITS_KEY: &[u8] = b"XSWDLIMITS";
+// progress of the blocks sync not finished yet
+const SYNC_CHECKPOINT_KEY: &[u8] = b"SYNCCHECK";
+
+// Upgrade the storage layout to the next version
+type Migration = fn(&mut EncryptedStorage) -> Result<()>;
+
+// The migration at index i upgrades the storage from version i + 1 to i + 2
+// Migrations can re-encrypt or re-key trees as they have access to the whole storage
+const MIGRATIONS: [Migration; STORAGE_VERSION as usize - 1] = [];
 
 // Default cache size
 const DEFAULT_CACHE_SIZE: usize = 100;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serializer)]
 pub struct Balance {
     pub amount: u64,
     pub ciphertext: CiphertextCache
@@ -85,22 +120,6 @@ impl Balance {
     }
 }
 
-impl Serializer for Balance {
-    fn write(&self, writer: &mut Writer) {
-        self.amount.write(writer);
-        self.ciphertext.write(writer);
-    }
-
-    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
-        let amount = u64::read(reader)?;
-        let ciphertext = CiphertextCache::read(reader)?;
-        Ok(Self {
-            amount,
-            ciphertext
-        })
-    }
-}
-
 // Use this struct to get access to non-encrypted keys (such as salt for KDF and encrypted master key)
 pub struct Storage {
     db: Db
@@ -118,11 +137,21 @@ pub struct TxCache {
 }
 
 // Implement an encrypted storage system 
+// Transactions, balances, assets, nonce and synced topoheight are stored per account
+// Only the selected account is accessible at a time
 pub struct EncryptedStorage {
     // cipher used to encrypt/decrypt/hash data
-    cipher: Cipher,
+    // None when the wallet is locked, it is built again from the password
+    cipher: Option<Cipher>,
+    // Account selected
+    account: u32,
     // All transactions where this wallet is part of
     transactions: Tree,
+    // Labels set by the user on the transactions
+    // They are kept on a rescan as the transactions are found again with the same hash
+    transactions_labels: Tree,
+    // Transactions built by the wallet and not executed yet
+    pending_transactions: Tree,
     // balances for each asset
     balances: Tree,
     // extra data (network, topoheight, etc)
@@ -131,6 +160,10 @@ pub struct EncryptedStorage {
     assets: Tree,
     // This tree is used to store all topoheight where a change in the wallet occured
     changes_topoheight: Tree,
+    // Address book, shared by all the accounts
+    contacts: Tree,
+    // Metadata of the assets fetched from the daemon, shared by all the accounts
+    assets_data: Tree,
     // The inner storage
     inner: Storage,
     // Caches
@@ -151,12 +184,17 @@ impl EncryptedStorage {
     pub fn new(inner: Storage, key: &[u8], salt: [u8; SALT_SIZE], network: Network) -> Result<Self> {
         let cipher = Cipher::new(key, Some(salt))?;
         let mut storage = Self {
-            transactions: inner.db.open_tree(&cipher.hash_key("transactions"))?,
-            balances: inner.db.open_tree(&cipher.hash_key("balances"))?,
+            account: 0,
+            transactions: Self::open_account_tree(&inner, &cipher, "transactions", 0)?,
+            transactions_labels: Self::open_account_tree(&inner, &cipher, "transactions_labels", 0)?,
+            pending_transactions: Self::open_account_tree(&inner, &cipher, "pending_transactions", 0)?,
+            balances: Self::open_account_tree(&inner, &cipher, "balances", 0)?,
             extra: inner.db.open_tree(&cipher.hash_key("extra"))?,
-            assets: inner.db.open_tree(&cipher.hash_key("assets"))?,
-            changes_topoheight: inner.db.open_tree(&cipher.hash_key("changes_topoheight"))?,
-            cipher,
+            assets: Self::open_account_tree(&inner, &cipher, "assets", 0)?,
+            changes_topoheight: Self::open_account_tree(&inner, &cipher, "changes_topoheight", 0)?,
+            contacts: inner.db.open_tree(&cipher.hash_key("contacts"))?,
+            assets_data: inner.db.open_tree(&cipher.hash_key("assets_data"))?,
+            cipher: Some(cipher),
             inner,
             balances_cache: Mutex::new(LruCache::new(NonZeroUsize::new(DEFAULT_CACHE_SIZE).unwrap())),
             unconfirmed_balances_cache: Mutex::new(HashMap::new()),
@@ -177,6 +215,177 @@ impl EncryptedStorage {
         Ok(storage)
     }
 
+    // Cipher of the storage, the wallet must be unlocked
+    fn get_cipher(&self) -> Result<&Cipher, WalletError> {
+        self.cipher.as_ref().ok_or(WalletError::WalletLocked)
+    }
+
+    // Drop the cipher so no data can be decrypted anymore, its key is wiped from memory
+    pub fn lock(&mut self) {
+        trace!("lock storage");
+        self.cipher = None;
+    }
+
+    // Build again the cipher from the master key and the storage salt
+    pub fn unlock(&mut self, key: &[u8], salt: [u8; SALT_SIZE]) -> Result<()> {
+        trace!("unlock storage");
+        self.cipher = Some(Cipher::new(key, Some(salt))?);
+        Ok(())
+    }
+
+    // Open the tree of an account
+    // The first account keeps the tree names used before accounts existed
+    fn open_account_tree(inner: &Storage, cipher: &Cipher, name: &str, account: u32) -> Result<Tree> {
+        let name = if account == 0 {
+            name.to_owned()
+        } else {
+            format!("{}_{}", name, account)
+        };
+
+        Ok(inner.db.open_tree(&cipher.hash_key(name))?)
+    }
+
+    // Build the key of a value stored per account in the extra tree
+    fn get_account_key(&self, key: &[u8]) -> Vec<u8> {
+        let mut account_key = key.to_vec();
+        if self.account != 0 {
+            account_key.extend_from_slice(&self.account.to_be_bytes());
+        }
+        account_key
+    }
+
+    // Account currently selected
+    pub fn get_account(&self) -> u32 {
+        self.account
+    }
+
+    // Select the account to use, all the caches are cleared
+    pub fn set_account(&mut self, account: u32) -> Result<()> {
+        trace!("set account to {}", account);
+        self.transactions = Self::open_account_tree(&self.inner, self.get_cipher()?, "transactions", account)?;
+        self.transactions_labels = Self::open_account_tree(&self.inner, self.get_cipher()?, "transactions_labels", account)?;
+        self.pending_transactions = Self::open_account_tree(&self.inner, self.get_cipher()?, "pending_transactions", account)?;
+        self.balances = Self::open_account_tree(&self.inner, self.get_cipher()?, "balances", account)?;
+        self.assets = Self::open_account_tree(&self.inner, self.get_cipher()?, "assets", account)?;
+        self.changes_topoheight = Self::open_account_tree(&self.inner, self.get_cipher()?, "changes_topoheight", account)?;
+        self.account = account;
+
+        self.balances_cache.get_mut().clear();
+        self.unconfirmed_balances_cache.get_mut().clear();
+        self.assets_cache.get_mut().clear();
+        self.tx_cache = None;
+        self.synced_topoheight = None;
+
+        Ok(())
+    }
+
+    // Number of accounts created in this wallet
+    // A wallet has at least one account
+    pub fn get_accounts_count(&self) -> Result<u32> {
+        trace!("get accounts count");
+        if !self.contains_data(&self.extra, ACCOUNTS_COUNT_KEY)? {
+            return Ok(1)
+        }
+
+        self.load_from_disk(&self.extra, ACCOUNTS_COUNT_KEY)
+    }
+
+    // Set the number of accounts created in this wallet
+    pub fn set_accounts_count(&mut self, count: u32) -> Result<()> {
+        trace!("set accounts count to {}", count);
+        self.save_to_disk(&self.extra, ACCOUNTS_COUNT_KEY, &count.to_be_bytes())
+    }
+
+    // Account to select when opening the wallet
+    pub fn get_selected_account(&self) -> Result<u32> {
+        trace!("get selected account");
+        if !self.contains_data(&self.extra, SELECTED_ACCOUNT_KEY)? {
+            return Ok(0)
+        }
+
+        self.load_from_disk(&self.extra, SELECTED_ACCOUNT_KEY)
+    }
+
+    // Save the account to select when opening the wallet
+    pub fn set_selected_account(&mut self, account: u32) -> Result<()> {
+        trace!("set selected account to {}", account);
+        self.save_to_disk(&self.extra, SELECTED_ACCOUNT_KEY, &account.to_be_bytes())
+    }
+
+    // Check if the accounts used must be searched on chain
+    pub fn is_accounts_recovery_pending(&self) -> Result<bool> {
+        trace!("is accounts recovery pending");
+        self.contains_data(&self.extra, ACCOUNTS_RECOVERY_KEY)
+    }
+
+    // Set if the accounts used must be searched on chain
+    pub fn set_accounts_recovery_pending(&mut self, pending: bool) -> Result<()> {
+        trace!("set accounts recovery pending to {}", pending);
+        if pending {
+            self.save_to_disk(&self.extra, ACCOUNTS_RECOVERY_KEY, &[])
+        } else {
+            self.delete_from_disk(&self.extra, ACCOUNTS_RECOVERY_KEY)
+        }
+    }
+
+    // Version of the storage layout
+    // Wallets created before it was stored are using the first version
+    pub fn get_storage_version(&self) -> Result<u32> {
+        trace!("get storage version");
+        if !self.contains_data(&self.extra, STORAGE_VERSION_KEY)? {
+            return Ok(1)
+        }
+
+        self.load_from_disk(&self.extra, STORAGE_VERSION_KEY)
+    }
+
+    // Set the version of the storage layout
+    pub fn set_storage_version(&mut self, version: u32) -> Result<()> {
+        trace!("set storage version to {}", version);
+        self.save_to_disk(&self.extra, STORAGE_VERSION_KEY, &version.to_be_bytes())
+    }
+
+    // Check if the storage must be migrated to the current layout
+    // A storage written by a newer version can't be opened
+    pub fn needs_migration(&self) -> Result<bool> {
+        let version = self.get_storage_version()?;
+        if version > STORAGE_VERSION {
+            return Err(WalletError::UnsupportedStorageVersion(version, STORAGE_VERSION).into())
+        }
+
+        Ok(version < STORAGE_VERSION)
+    }
+
+    // Run the migrations from the stored version to the current one
+    // The version is saved after each of them so an interrupted migration is resumed
+    pub fn migrate(&mut self) -> Result<()> {
+        let from = self.get_storage_version()?;
+        for version in from..STORAGE_VERSION {
+            info!("Migrating wallet storage from version {} to {}", version, version + 1);
+            let migration = MIGRATIONS[version as usize - 1];
+            migration(self).with_context(|| format!("Error while migrating wallet storage to version {}", version + 1))?;
+
+            self.set_storage_version(version + 1)?;
+            self.flush()?;
+        }
+
+        Ok(())
+    }
+
+    // Copy all the trees in a new database at this path
+    // Values are copied as is, so the backup is opened with the same password
+    pub fn backup(&self, path: &str) -> Result<()> {
+        trace!("backup storage to {}", path);
+        if Path::new(path).exists() {
+            return Err(anyhow!("Backup path {} already exists", path))
+        }
+
+        let backup = sled::open(path)?;
+        backup.import(self.inner.db.export());
+        backup.flush()?;
+        Ok(())
+    }
+
     // Flush on disk to make sure it is saved
     pub fn flush(&mut self) -> Result<()> {
         trace!("Flushing storage");
@@ -195,27 +404,27 @@ impl EncryptedStorage {
     // Key must be hashed or encrypted before calling this function
     fn internal_load<V: Serializer>(&self, tree: &Tree, key: &[u8]) -> Result<V> {
         let data = tree.get(key)?.context(format!("load from disk: tree = {:?}, key = {}", tree.name(), String::from_utf8_lossy(key)))?;
-        let bytes = self.cipher.decrypt_value(&data).context("Error while decrypting value from disk")?;
+        let bytes = self.get_cipher()?.decrypt_value(&data).context("Error while decrypting value from disk")?;
         let mut reader = Reader::new(&bytes);
         Ok(V::read(&mut reader).context("Error while de-serializing value from disk")?)
     }
 
     // load from disk using a hashed key, decrypt the value and deserialize it
     fn load_from_disk<V: Serializer>(&self, tree: &Tree, key: &[u8]) -> Result<V> {
-        let hashed_key = self.cipher.hash_key(key);
+        let hashed_key = self.get_cipher()?.hash_key(key);
         self.internal_load(tree, &hashed_key)
     }
 
     // Because we can't predict the nonce used for encryption, we make it determistic
     fn create_encrypted_key(&self, key: &[u8]) -> Result<Vec<u8>> {
         // the hashed key is salted so its unique and can't be recover/bruteforced
-        let hashed_key = self.cipher.hash_key(key);
+        let hashed_key = self.get_cipher()?.hash_key(key);
 
         // Use only the first 24 bytes as nonce
         let mut nonce = [0u8; Cipher::NONCE_SIZE];
         nonce.copy_from_slice(&hashed_key[0..Cipher::NONCE_SIZE]);
 
-        let key = self.cipher.encrypt_value_with_nonce(key, &nonce)?;
+        let key = self.get_cipher()?.encrypt_value_with_nonce(key, &nonce)?;
         Ok(key)
     }
 
@@ -229,21 +438,21 @@ impl EncryptedStorage {
     // We encrypt instead of hashing to be able to retrieve the key
     fn save_to_disk_with_encrypted_key(&self, tree: &Tree, key: &[u8], value: &[u8]) -> Result<()> {
         let encrypted_key = self.create_encrypted_key(key)?;
-        let encrypted_value = self.cipher.encrypt_value(value)?;
+        let encrypted_value = self.get_cipher()?.encrypt_value(value)?;
         tree.insert(encrypted_key, encrypted_value)?;
         Ok(())
     }
 
     // hash key, encrypt data and then save to disk 
     fn save_to_disk(&self, tree: &Tree, key: &[u8], value: &[u8]) -> Result<()> {
-        let hashed_key = self.cipher.hash_key(key);
-        tree.insert(hashed_key, self.cipher.encrypt_value(value)?)?;
+        let hashed_key = self.get_cipher()?.hash_key(key);
+        tree.insert(hashed_key, self.get_cipher()?.encrypt_value(value)?)?;
         Ok(())
     }
 
     // hash key, encrypt data and then save to disk 
     fn delete_from_disk(&self, tree: &Tree, key: &[u8]) -> Result<()> {
-        let hashed_key = self.cipher.hash_key(key);
+        let hashed_key = self.get_cipher()?.hash_key(key);
         tree.remove(hashed_key)?;
         Ok(())
     }
@@ -257,7 +466,7 @@ impl EncryptedStorage {
 
     // Search if the data is present in the tree using hashed key
     fn contains_data(&self, tree: &Tree, key: &[u8]) -> Result<bool> {
-        let hashed_key = self.cipher.hash_key(key);
+        let hashed_key = self.get_cipher()?.hash_key(key);
         Ok(tree.contains_key(hashed_key)?)
     }
 
@@ -269,7 +478,7 @@ impl EncryptedStorage {
 
     // Open the named tree
     fn get_custom_tree(&self, name: impl Into<String>) -> Result<Tree> {
-        let hash = self.cipher.hash_key(format!("custom_{}", name.into()));
+        let hash = self.get_cipher()?.hash_key(format!("custom_{}", name.into()));
         let tree = self.inner.db.open_tree(&hash)?;
         Ok(tree)
     }
@@ -310,7 +519,7 @@ impl EncryptedStorage {
             let mut key = None;
             let mut value = None;
             if let Some(query) = query_key.as_ref() {
-                let decrypted = self.cipher.decrypt_value(&k)?;
+                let decrypted = self.get_cipher()?.decrypt_value(&k)?;
                 let k = DataValue::from_bytes(&decrypted)?;
                 if !query.verify_value(&k) {
                     continue;
@@ -319,7 +528,7 @@ impl EncryptedStorage {
             }
 
             if let Some(query) = query_value.as_ref() {
-                let decrypted = self.cipher.decrypt_value(&k)?;
+                let decrypted = self.get_cipher()?.decrypt_value(&k)?;
                 let v = DataElement::from_bytes(&decrypted)?;
                 if !query.verify_element(&v) {
                     continue;
@@ -331,14 +540,14 @@ impl EncryptedStorage {
             let key = if let Some(key) = key {
                 key
             } else {
-                let decrypted = self.cipher.decrypt_value(&k)?;
+                let decrypted = self.get_cipher()?.decrypt_value(&k)?;
                 DataValue::from_bytes(&decrypted)?
             };
 
             let value = if let Some(value) = value {
                 value
             } else {
-                let decrypted = self.cipher.decrypt_value(&v)?;
+                let decrypted = self.get_cipher()?.decrypt_value(&v)?;
                 DataElement::from_bytes(&decrypted)?
             };
 
@@ -360,7 +569,7 @@ impl EncryptedStorage {
         let mut keys = Vec::new();
         for e in tree.iter() {
             let (key, _) = e?;
-            let decrypted = self.cipher.decrypt_value(&key)?;
+            let decrypted = self.get_cipher()?.decrypt_value(&key)?;
             let k = DataValue::from_bytes(&decrypted)?;
             if let Some(query) = query {
                 if !query.verify_value(&k) {
@@ -385,14 +594,14 @@ impl EncryptedStorage {
         let mut assets = HashSet::new();
         for res in self.assets.iter() {
             let (key, value) = res?;
-            let raw_key = &self.cipher.decrypt_value(&key)?;
+            let raw_key = &self.get_cipher()?.decrypt_value(&key)?;
             let mut reader = Reader::new(raw_key);
             let asset = Hash::read(&mut reader)?;
 
             let decimals = if let Some(decimals) = cache.get(&asset) {
                 *decimals
             } else {
-                let raw_value = &self.cipher.decrypt_value(&value)?;
+                let raw_value = &self.get_cipher()?.decrypt_value(&value)?;
                 let mut reader = Reader::new(raw_value);
                 u8::read(&mut reader)?
             };
@@ -414,11 +623,11 @@ impl EncryptedStorage {
         let mut assets = Vec::new();
         for res in self.assets.iter() {
             let (key, value) = res?;
-            let asset = Hash::from_bytes(&self.cipher.decrypt_value(&key)?)?;
+            let asset = Hash::from_bytes(&self.get_cipher()?.decrypt_value(&key)?)?;
             let decimals = if let Some(decimals) = cache.get(&asset) {
                 *decimals
             } else {
-                let raw_value = &self.cipher.decrypt_value(&value)?;
+                let raw_value = &self.get_cipher()?.decrypt_value(&value)?;
                 let mut reader = Reader::new(raw_value);
                 u8::read(&mut reader)?
             };
@@ -456,10 +665,46 @@ impl EncryptedStorage {
     }
 
     // Retrieve the stored decimals for this asset for better display
+    // The metadata registry is used first as it also contains the assets not tracked by the account
     pub fn get_asset_decimals(&self, asset: &Hash) -> Result<u8> {
+        if self.has_asset_data(asset)? {
+            return Ok(self.get_asset_data(asset)?.get_decimals())
+        }
+
         self.load_from_disk_with_encrypted_key(&self.assets, asset.as_bytes())
     }
 
+    // Save the metadata of an asset fetched from the daemon
+    pub fn set_asset_data(&mut self, asset: &Hash, data: &AssetData) -> Result<()> {
+        trace!("set asset data for {}", asset);
+        self.save_to_disk_with_encrypted_key(&self.assets_data, asset.as_bytes(), &data.to_bytes())
+    }
+
+    // Get the metadata of an asset
+    pub fn get_asset_data(&self, asset: &Hash) -> Result<AssetData> {
+        trace!("get asset data for {}", asset);
+        self.load_from_disk_with_encrypted_key(&self.assets_data, asset.as_bytes())
+    }
+
+    pub fn has_asset_data(&self, asset: &Hash) -> Result<bool> {
+        trace!("has asset data for {}", asset);
+        self.contains_encrypted_data(&self.assets_data, asset.as_bytes())
+    }
+
+    // Get the metadata of all the assets known by the wallet
+    pub fn get_assets_data(&self) -> Result<Vec<AssetWithData>> {
+        trace!("get assets data");
+        let mut assets = Vec::new();
+        for res in self.assets_data.iter() {
+            let (key, value) = res?;
+            let asset = Hash::from_bytes(&self.get_cipher()?.decrypt_value(&key)?)?;
+            let data = AssetData::from_bytes(&self.get_cipher()?.decrypt_value(&value)?)?;
+            assets.push(AssetWithData::new(asset, data));
+        }
+
+        Ok(assets)
+    }
+
     // Retrieve the plaintext balance for this asset
     pub async fn get_plaintext_balance_for(&self, asset: &Hash) -> Result<u64> {
         let mut cache = self.balances_cache.lock().await;
@@ -605,12 +850,32 @@ impl EncryptedStorage {
         self.get_filtered_transactions(None, None, None, true, true, true, true, None)
     }
 
+    // Read all the transactions of an account, even if it's not the one selected
+    pub fn get_account_transactions(&self, account: u32) -> Result<Vec<TransactionEntry>> {
+        trace!("get transactions of account {}", account);
+        let tree = Self::open_account_tree(&self.inner, self.get_cipher()?, "transactions", account)?;
+        let mut transactions = Vec::new();
+        for el in tree.iter().values() {
+            let value = el?;
+            transactions.push(TransactionEntry::from_bytes(&self.get_cipher()?.decrypt_value(&value)?)?);
+        }
+
+        Ok(transactions)
+    }
+
+    // Save a transaction of an account, even if it's not the one selected
+    pub fn save_account_transaction(&mut self, account: u32, transaction: &TransactionEntry) -> Result<()> {
+        trace!("save transaction {} of account {}", transaction.get_hash(), account);
+        let tree = Self::open_account_tree(&self.inner, self.get_cipher()?, "transactions", account)?;
+        self.save_to_disk(&tree, transaction.get_hash().as_bytes(), &transaction.to_bytes())
+    }
+
     // delete all transactions above the specified topoheight
     // This will go through each transaction, deserialize it, check topoheight, and delete it if required
     pub fn delete_transactions_above_topoheight(&mut self, topoheight: u64) -> Result<()> {
         for el in self.transactions.iter().values() {
             let value = el?;
-            let entry = TransactionEntry::from_bytes(&self.cipher.decrypt_value(&value)?)?;
+            let entry = TransactionEntry::from_bytes(&self.get_cipher()?.decrypt_value(&value)?)?;
             if entry.get_topoheight() > topoheight {
                 self.delete_transaction(entry.get_hash())?;
             }
@@ -624,7 +889,7 @@ impl EncryptedStorage {
         let mut transactions = Vec::new();
         for el in self.transactions.iter().values() {
             let value = el?;
-            let mut entry = TransactionEntry::from_bytes(&self.cipher.decrypt_value(&value)?)?;
+            let mut entry = TransactionEntry::from_bytes(&self.get_cipher()?.decrypt_value(&value)?)?;
             if let Some(topoheight) = min_topoheight {
                 if entry.get_topoheight() < topoheight {
                     continue;
@@ -640,6 +905,12 @@ impl EncryptedStorage {
             let (save, mut transfers) = match entry.get_mut_entry() {
                 EntryData::Coinbase { .. } if accept_coinbase => (true, None),
                 EntryData::Burn { .. } if accept_burn => (true, None),
+                // Outgoing transaction without any destination
+                EntryData::AssetCreation { .. } | EntryData::AssetMint { .. } | EntryData::MultiSig { .. } | EntryData::Unlock { .. } | EntryData::DeployContract { .. } | EntryData::InvokeContract { .. } if accept_outgoing && address.is_none() => (true, None),
+                EntryData::AssetOwnershipTransfer { from, to, .. } if accept_incoming || accept_outgoing => match address {
+                    Some(key) => (*key == *from || *key == *to, None),
+                    None => (true, None)
+                },
                 EntryData::Incoming { from, transfers } if accept_incoming => match address {
                     Some(key) => (*key == *from, Some(transfers.into_iter().map(|t| Transfer::In(t)).collect::<Vec<_>>())),
                     None => (true, None)
@@ -689,9 +960,123 @@ impl EncryptedStorage {
         Ok(transactions)
     }
 
+    // Search the transactions matching all the filters set, with their labels
+    pub fn search_transactions(&self, owner: &PublicKey, params: &SearchTransactionsParams) -> Result<Vec<(TransactionEntry, Option<TransactionLabel>)>> {
+        trace!("search transactions");
+        let text = params.label.as_ref().map(|label| label.to_lowercase());
+        let filter_amounts = params.asset.is_some() || params.min_amount.is_some() || params.max_amount.is_some();
+
+        let mut transactions = Vec::new();
+        for entry in self.get_filtered_transactions(None, params.min_topoheight, params.max_topoheight, true, true, true, true, None)? {
+            if params.direction.is_some_and(|direction| entry.get_direction(owner) != direction) {
+                continue;
+            }
+
+            // At least one asset moved must match the asset and the amounts bounds
+            if filter_amounts {
+                let found = entry.get_assets_amounts().into_iter().any(|(asset, amount)| {
+                    if params.asset.as_ref().is_some_and(|expected| expected != asset) {
+                        return false
+                    }
+
+                    match amount {
+                        Some(amount) => params.min_amount.map_or(true, |min| amount >= min) && params.max_amount.map_or(true, |max| amount <= max),
+                        None => params.min_amount.is_none() && params.max_amount.is_none()
+                    }
+                });
+
+                if !found {
+                    continue;
+                }
+            }
+
+            let label = if self.has_transaction_label(entry.get_hash())? {
+                Some(self.get_transaction_label(entry.get_hash())?)
+            } else {
+                None
+            };
+
+            if let Some(text) = text.as_ref() {
+                let matches = label.as_ref().is_some_and(|label| {
+                    label.label.to_lowercase().contains(text)
+                        || label.category.as_ref().is_some_and(|category| category.to_lowercase().contains(text))
+                });
+
+                if !matches {
+                    continue;
+                }
+            }
+
+            transactions.push((entry, label));
+        }
+
+        Ok(transactions)
+    }
+
+    // Save the label of a transaction, it replaces the previous one
+    pub fn set_transaction_label(&mut self, hash: &Hash, label: &TransactionLabel) -> Result<()> {
+        trace!("set transaction label for {}", hash);
+        self.save_to_disk_with_encrypted_key(&self.transactions_labels, hash.as_bytes(), &label.to_bytes())
+    }
+
+    pub fn get_transaction_label(&self, hash: &Hash) -> Result<TransactionLabel> {
+        trace!("get transaction label for {}", hash);
+        self.load_from_disk_with_encrypted_key(&self.transactions_labels, hash.as_bytes())
+    }
+
+    pub fn has_transaction_label(&self, hash: &Hash) -> Result<bool> {
+        trace!("has transaction label for {}", hash);
+        self.contains_encrypted_data(&self.transactions_labels, hash.as_bytes())
+    }
+
+    pub fn delete_transaction_label(&mut self, hash: &Hash) -> Result<()> {
+        trace!("delete transaction label for {}", hash);
+        self.delete_from_disk_with_encrypted_key(&self.transactions_labels, hash.as_bytes())
+    }
+
+    // Track a transaction built by the wallet until it is executed
+    pub fn set_pending_transaction(&mut self, hash: &Hash, pending: &PendingTransaction) -> Result<()> {
+        trace!("set pending transaction {}", hash);
+        self.save_to_disk_with_encrypted_key(&self.pending_transactions, hash.as_bytes(), &pending.to_bytes())
+    }
+
+    pub fn has_pending_transaction(&self, hash: &Hash) -> Result<bool> {
+        trace!("has pending transaction {}", hash);
+        self.contains_encrypted_data(&self.pending_transactions, hash.as_bytes())
+    }
+
+    pub fn delete_pending_transaction(&mut self, hash: &Hash) -> Result<()> {
+        trace!("delete pending transaction {}", hash);
+        self.delete_from_disk_with_encrypted_key(&self.pending_transactions, hash.as_bytes())
+    }
+
+    // Get all the transactions not executed yet, ordered by nonce
+    pub fn get_pending_transactions(&self) -> Result<Vec<(Hash, PendingTransaction)>> {
+        trace!("get pending transactions");
+        let mut transactions = Vec::new();
+        for res in self.pending_transactions.iter() {
+            let (key, value) = res?;
+            let hash = Hash::from_bytes(&self.get_cipher()?.decrypt_value(&key)?)?;
+            let pending = PendingTransaction::from_bytes(&self.get_cipher()?.decrypt_value(&value)?)?;
+            transactions.push((hash, pending));
+        }
+
+        transactions.sort_by_key(|(_, pending)| pending.get_transaction().get_nonce());
+        Ok(transactions)
+    }
+
+    // Amount of the asset spent by the pending transactions
+    pub fn get_pending_cost_for(&self, asset: &Hash) -> Result<u64> {
+        let mut cost = 0u64;
+        for (_, pending) in self.get_pending_transactions()? {
+            cost = cost.saturating_add(pending.get_cost(asset));
+        }
+        Ok(cost)
+    }
+
     // Delete a transaction saved in wallet using its hash
     pub fn delete_transaction(&mut self, hash: &Hash) -> Result<()> {
-        self.transactions.remove(self.cipher.hash_key(hash.as_bytes()))?;
+        self.transactions.remove(self.get_cipher()?.hash_key(hash.as_bytes()))?;
         Ok(())
     }
 
@@ -754,7 +1139,7 @@ impl EncryptedStorage {
     // Retrieve the nonce used to create new transactions
     pub fn get_nonce(&self) -> Result<u64> {
         trace!("get nonce");
-        self.load_from_disk(&self.extra, NONCE_KEY)
+        self.load_from_disk(&self.extra, &self.get_account_key(NONCE_KEY))
     }
 
     // Get the unconfirmed nonce to use to build ordered TXs
@@ -780,26 +1165,43 @@ impl EncryptedStorage {
     // If the unconfirmed nonce is lower than the new nonce, we reset it
     pub fn set_nonce(&mut self, nonce: u64) -> Result<()> {
         trace!("set nonce to {}", nonce);
-        self.save_to_disk(&self.extra, NONCE_KEY, &nonce.to_be_bytes())
+        self.save_to_disk(&self.extra, &self.get_account_key(NONCE_KEY), &nonce.to_be_bytes())
     }
 
-    // Store the private key
+    // Store the private key of the seed
+    // The keys of all the accounts are derived from it
     pub fn set_private_key(&mut self, private_key: &PrivateKey) -> Result<()> {
         trace!("set private key");
         self.save_to_disk(&self.extra, PRIVATE_KEY, &private_key.to_bytes())
     }
 
-    // Retrieve the keypair of this wallet
+    // Retrieve the private key of the seed
     pub fn get_private_key(&self) -> Result<PrivateKey> {
         trace!("get private key");
         self.load_from_disk(&self.extra, PRIVATE_KEY)
     }
 
+    // Store the key encoded in the seed when a passphrase is used
+    pub fn set_seed_key(&mut self, seed_key: &PrivateKey) -> Result<()> {
+        trace!("set seed key");
+        self.save_to_disk(&self.extra, SEED_KEY, &seed_key.to_bytes())
+    }
+
+    // Retrieve the key encoded in the seed, None if no passphrase is used
+    pub fn get_seed_key(&self) -> Result<Option<PrivateKey>> {
+        trace!("get seed key");
+        if !self.contains_data(&self.extra, SEED_KEY)? {
+            return Ok(None)
+        }
+
+        self.load_from_disk(&self.extra, SEED_KEY).map(Some)
+    }
+
     // Set the topoheight until which the wallet is synchronized
     pub fn set_synced_topoheight(&mut self, topoheight: u64) -> Result<()> {
         trace!("set synced topoheight to {}", topoheight);
         self.synced_topoheight = Some(topoheight);
-        self.save_to_disk(&self.extra, TOPOHEIGHT_KEY, &topoheight.to_be_bytes())
+        self.save_to_disk(&self.extra, &self.get_account_key(TOPOHEIGHT_KEY), &topoheight.to_be_bytes())
     }
 
     // Get the topoheight until which the wallet is synchronized
@@ -811,32 +1213,177 @@ impl EncryptedStorage {
             return Ok(topoheight);
         }
 
-        let synced_topoheight = self.load_from_disk(&self.extra, TOPOHEIGHT_KEY)?;
+        let synced_topoheight = self.load_from_disk(&self.extra, &self.get_account_key(TOPOHEIGHT_KEY))?;
         Ok(synced_topoheight)
     }
 
+    // Get the progress of the blocks sync if it was interrupted
+    pub fn get_sync_checkpoint(&self) -> Result<Option<SyncCheckpoint>> {
+        trace!("get sync checkpoint");
+        let key = self.get_account_key(SYNC_CHECKPOINT_KEY);
+        if !self.contains_data(&self.extra, &key)? {
+            return Ok(None)
+        }
+
+        self.load_from_disk(&self.extra, &key).map(Some)
+    }
+
+    // Save the progress of the blocks sync
+    pub fn set_sync_checkpoint(&mut self, checkpoint: &SyncCheckpoint) -> Result<()> {
+        trace!("set sync checkpoint");
+        self.save_to_disk(&self.extra, &self.get_account_key(SYNC_CHECKPOINT_KEY), &checkpoint.to_bytes())
+    }
+
+    // Delete the progress of the blocks sync once it is done
+    pub fn delete_sync_checkpoint(&mut self) -> Result<()> {
+        trace!("delete sync checkpoint");
+        self.delete_from_disk(&self.extra, &self.get_account_key(SYNC_CHECKPOINT_KEY))
+    }
+
     // Delete the top block hash
     pub fn delete_top_block_hash(&mut self) -> Result<()> {
         trace!("delete top block hash");
-        self.delete_from_disk(&self.extra, TOP_BLOCK_HASH_KEY)
+        self.delete_from_disk(&self.extra, &self.get_account_key(TOP_BLOCK_HASH_KEY))
     }
 
     // Set the top block hash until which the wallet is synchronized
     pub fn set_top_block_hash(&mut self, hash: &Hash) -> Result<()> {
         trace!("set top block hash to {}", hash);
-        self.save_to_disk(&self.extra, TOP_BLOCK_HASH_KEY, hash.as_bytes())
+        self.save_to_disk(&self.extra, &self.get_account_key(TOP_BLOCK_HASH_KEY), hash.as_bytes())
     }
 
     // Check if a top block hash is set 
     pub fn has_top_block_hash(&self) -> Result<bool> {
         trace!("has top block hash");
-        self.contains_data(&self.extra, TOP_BLOCK_HASH_KEY)
+        self.contains_data(&self.extra, &self.get_account_key(TOP_BLOCK_HASH_KEY))
     }
 
     // Top block hash until which the wallet is synchronized 
     pub fn get_top_block_hash(&self) -> Result<Hash> {
         trace!("get top block hash");
-        self.load_from_disk(&self.extra, TOP_BLOCK_HASH_KEY)
+        self.load_from_disk(&self.extra, &self.get_account_key(TOP_BLOCK_HASH_KEY))
+    }
+
+    // Retrieve the alert rules and webhooks configured
+    // Returns the default settings if none were saved
+    pub fn get_alerts_settings(&self) -> Result<AlertsSettings> {
+        trace!("get alerts settings");
+        if !self.contains_data(&self.extra, ALERTS_KEY)? {
+            return Ok(AlertsSettings::default())
+        }
+
+        self.load_from_disk(&self.extra, ALERTS_KEY)
+    }
+
+    // Save the alert rules and webhooks configured
+    pub fn set_alerts_settings(&mut self, settings: &AlertsSettings) -> Result<()> {
+        trace!("set alerts settings");
+        self.save_to_disk(&self.extra, ALERTS_KEY, &settings.to_bytes())
+    }
+
+    // Retrieve the webhooks configured
+    // Returns the default settings if none were saved
+    pub fn get_webhooks_settings(&self) -> Result<WebhooksSettings> {
+        trace!("get webhooks settings");
+        if !self.contains_data(&self.extra, WEBHOOKS_KEY)? {
+            return Ok(WebhooksSettings::default())
+        }
+
+        self.load_from_disk(&self.extra, WEBHOOKS_KEY)
+    }
+
+    // Save the webhooks configured
+    pub fn set_webhooks_settings(&mut self, settings: &WebhooksSettings) -> Result<()> {
+        trace!("set webhooks settings");
+        self.save_to_disk(&self.extra, WEBHOOKS_KEY, &settings.to_bytes())
+    }
+
+    // Retrieve the webhook notifications not delivered yet
+    pub fn get_webhooks_queue(&self) -> Result<WebhookQueue> {
+        trace!("get webhooks queue");
+        if !self.contains_data(&self.extra, WEBHOOKS_QUEUE_KEY)? {
+            return Ok(WebhookQueue::default())
+        }
+
+        self.load_from_disk(&self.extra, WEBHOOKS_QUEUE_KEY)
+    }
+
+    // Save the webhook notifications not delivered yet
+    pub fn set_webhooks_queue(&mut self, queue: &WebhookQueue) -> Result<()> {
+        trace!("set webhooks queue");
+        self.save_to_disk(&self.extra, WEBHOOKS_QUEUE_KEY, &queue.to_bytes())
+    }
+
+    // Retrieve the limits applied to the XSWD applications
+    // Returns no limits if none were saved
+    pub fn get_xswd_limits(&self) -> Result<XSWDLimitsSettings> {
+        trace!("get xswd limits");
+        if !self.contains_data(&self.extra, XSWD_LIMITS_KEY)? {
+            return Ok(XSWDLimitsSettings::default())
+        }
+
+        self.load_from_disk(&self.extra, XSWD_LIMITS_KEY)
+    }
+
+    // Save the limits applied to the XSWD applications
+    pub fn set_xswd_limits(&mut self, limits: &XSWDLimitsSettings) -> Result<()> {
+        trace!("set xswd limits");
+        self.save_to_disk(&self.extra, XSWD_LIMITS_KEY, &limits.to_bytes())
+    }
+
+    // Retrieve the scheduled payments of the account selected
+    pub fn get_scheduled_payments(&self) -> Result<ScheduledPayments> {
+        trace!("get scheduled payments");
+        let key = self.get_account_key(SCHEDULED_PAYMENTS_KEY);
+        if !self.contains_data(&self.extra, &key)? {
+            return Ok(ScheduledPayments::default())
+        }
+
+        self.load_from_disk(&self.extra, &key)
+    }
+
+    // Save the scheduled payments of the account selected
+    pub fn set_scheduled_payments(&mut self, payments: &ScheduledPayments) -> Result<()> {
+        trace!("set scheduled payments");
+        self.save_to_disk(&self.extra, &self.get_account_key(SCHEDULED_PAYMENTS_KEY), &payments.to_bytes())
+    }
+
+    // Get all the contacts of the address book
+    pub fn get_contacts(&self) -> Result<Vec<ContactEntry>> {
+        trace!("get contacts");
+        let mut contacts = Vec::new();
+        for res in self.contacts.iter() {
+            let (key, value) = res?;
+            let name = String::from_utf8(self.get_cipher()?.decrypt_value(&key)?)
+                .context("Invalid contact name")?;
+            let raw_value = &self.get_cipher()?.decrypt_value(&value)?;
+            let contact = Contact::from_bytes(raw_value)?;
+            contacts.push(ContactEntry { name, contact });
+        }
+
+        Ok(contacts)
+    }
+
+    // Get a contact using its name
+    pub fn get_contact(&self, name: &str) -> Result<Contact> {
+        trace!("get contact {}", name);
+        self.load_from_disk_with_encrypted_key(&self.contacts, name.as_bytes())
+    }
+
+    pub fn has_contact(&self, name: &str) -> Result<bool> {
+        trace!("has contact {}", name);
+        self.contains_encrypted_data(&self.contacts, name.as_bytes())
+    }
+
+    // Save a contact, the previous one with the same name is overwritten
+    pub fn set_contact(&mut self, name: &str, contact: &Contact) -> Result<()> {
+        trace!("set contact {}", name);
+        self.save_to_disk_with_encrypted_key(&self.contacts, name.as_bytes(), &contact.to_bytes())
+    }
+
+    pub fn delete_contact(&mut self, name: &str) -> Result<()> {
+        trace!("delete contact {}", name);
+        self.delete_from_disk_with_encrypted_key(&self.contacts, name.as_bytes())
     }
 
     pub fn get_public_storage(&self) -> &Storage {
@@ -892,7 +1439,7 @@ impl EncryptedStorage {
         let mut deleted = false;
         for res in self.changes_topoheight.iter().keys() {
             let key = res?;
-            let raw = self.cipher.decrypt_value(&key).context("Error while decrypting key from disk")?;
+            let raw = self.get_cipher()?.decrypt_value(&key).context("Error while decrypting key from disk")?;
             let topo = u64::from_bytes(&raw)?;
             if topo > topoheight {
                 trace!("deleting topoheight changes at {}", topo);
@@ -909,8 +1456,8 @@ impl EncryptedStorage {
         trace!("get topoheight changes");
         self.changes_topoheight.iter().rev().map(|res| {
             let (key, value) = res?;
-            let topo = u64::from_bytes(&self.cipher.decrypt_value(&key)?)?;
-            let hash = Hash::from_bytes(&self.cipher.decrypt_value(&value)?)?;
+            let topo = u64::from_bytes(&self.get_cipher()?.decrypt_value(&key)?)?;
+            let hash = Hash::from_bytes(&self.get_cipher()?.decrypt_value(&value)?)?;
             Ok((topo, hash))
         })
     }
@@ -921,7 +1468,7 @@ impl EncryptedStorage {
         let mut highest = 0;
         for res in self.changes_topoheight.iter().keys() {
             let key = res?;
-            let raw = self.cipher.decrypt_value(&key).context("Error while decrypting key from disk")?;
+            let raw = self.get_cipher()?.decrypt_value(&key).context("Error while decrypting key from disk")?;
             let topo = u64::from_bytes(&raw)?;
             if topo > highest && topo < max {
                 highest = topo;
@@ -4,7 +4,7 @@ use xelis_common::{
     crypto::{elgamal::Ciphertext, Hash, PublicKey},
     transaction::{builder::{AccountState, FeeHelper}, Reference}
 };
-use crate::{error::WalletError, storage::{Balance, EncryptedStorage, TxCache}};
+use crate::{error::WalletError, pending::PendingTransaction, storage::{Balance, EncryptedStorage, TxCache}};
 
 // State used to estimate fees for a transaction
 // Because fees can be higher if a destination account is not registered
@@ -28,6 +28,10 @@ impl EstimateFeesState {
     pub fn add_registered_key(&mut self, key: PublicKey) {
         self.registered_keys.insert(key);
     }
+
+    pub fn get_registered_keys(&self) -> &HashSet<PublicKey> {
+        &self.registered_keys
+    }
 }
 
 impl FeeHelper for EstimateFeesState {
@@ -46,7 +50,9 @@ pub struct TransactionBuilderState {
     balances: HashMap<Hash, Balance>,
     reference: Reference,
     nonce: u64,
-    tx_hash_built: Option<Hash>
+    tx_hash_built: Option<Hash>,
+    // Tracked until the TX is executed in a block
+    pending: Option<PendingTransaction>
 }
 
 impl TransactionBuilderState {
@@ -59,7 +65,8 @@ impl TransactionBuilderState {
             balances: HashMap::new(),
             reference,
             nonce,
-            tx_hash_built: None
+            tx_hash_built: None,
+            pending: None
         }
     }
 
@@ -84,12 +91,20 @@ impl TransactionBuilderState {
         self.tx_hash_built = Some(tx_hash);
     }
 
+    pub fn set_pending_transaction(&mut self, pending: PendingTransaction) {
+        self.pending = Some(pending);
+    }
+
     pub async fn apply_changes(&mut self, storage: &mut EncryptedStorage) -> Result<(), WalletError> {
         let last_tx_hash_created = self.tx_hash_built.take().ok_or(WalletError::TxNotBuilt)?;
         for (asset, balance) in self.balances.drain() {
             storage.set_unconfirmed_balance_for(asset, balance).await?;
         }
 
+        if let Some(pending) = self.pending.take() {
+            storage.set_pending_transaction(&last_tx_hash_created, &pending)?;
+        }
+
         storage.set_tx_cache(TxCache {
             reference: self.reference.clone(),
             nonce: self.nonce,
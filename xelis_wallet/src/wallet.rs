@@ -1,9 +1,12 @@
 use std::{
-    collections::HashSet,
-    fs::{create_dir_all, File},
-    io::{Read, Write},
-    path::Path,
-    sync::Arc
+    collections::{HashMap, HashSet},
+    ops::ControlFlow,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+        RwLock as StdRwLock
+    },
+    time::Duration
 };
 use anyhow::{Error, Context};
 use serde::Serialize;
@@ -15,60 +18,123 @@ use tokio::sync::{
     Mutex,
     RwLock
 };
+use tokio::task::JoinHandle;
+use zeroize::{Zeroize, Zeroizing};
 use xelis_common::{
     api::{
         wallet::{
+            AccountEntry,
+            AlertRule,
+            AlertRuleEntry,
             BalanceChanged,
+            Contact,
+            ContactEntry,
+            EntryType,
+            GetWebhooksResult,
+            HistoryRange,
+            HistoryRecord,
+            LabeledTransactionEntry,
             NotifyEvent,
-            TransactionEntry
+            PendingTransactionEntry,
+            OfflineTransactionRequest,
+            ScheduledPayment,
+            ScheduledPaymentEntry,
+            ScheduledPaymentResult,
+            SearchTransactionsParams,
+            SignedMessage,
+            TransactionEntry,
+            TransactionLabel,
+            WalletAlert,
+            XSWDLimitsSettings
         },
-        DataElement
+        DataElement,
+        DataValue
     },
-    asset::AssetWithData,
+    asset::{AssetData, AssetWithData},
+    config::{COIN_DECIMALS, XELIS_ASSET},
     crypto::{
         ecdlp::{self, ECDLPTablesFileView},
-        elgamal::{Ciphertext, DecryptHandle, PublicKey as DecompressedPublicKey},
+        elgamal::{
+            Ciphertext,
+            DecryptHandle,
+            PrecomputedTables,
+            PublicKey as DecompressedPublicKey,
+            PRECOMPUTED_TABLES_L1
+        },
+        proofs::{verify_balance_proof, BalanceProof},
         Address,
+        AddressType,
+        Hash,
+        PaymentRequest,
         Hashable,
         KeyPair,
+        PrivateKey,
         PublicKey,
         Signature
     },
     network::Network,
+    time::get_current_time_in_seconds,
     transaction::{
         builder::{
+            AccountState,
             FeeBuilder,
             TransactionBuilder,
-            TransactionTypeBuilder
+            TransactionTypeBuilder,
+            TransferBuilder,
+            UnsignedTransaction
         },
         extra_data::UnknownExtraDataFormat,
+        multisig::SignatureId,
         Reference,
         Role,
-        Transaction
-    }
+        Transaction,
+        TX_VERSION_MULTISIG
+    },
+    json_rpc::TlsConfig,
+    utils::{from_coin, get_replacement_min_fee, spawn_task}
 };
 use crate::{
+    alerts::AlertManager,
+    backup::WalletBackup,
+    batch::BatchPayment,
     cipher::Cipher,
     config::{
+        ALERT_WEBHOOK_TIMEOUT_SECS,
+        AUTO_LOCK_CHECK_INTERVAL_SECS,
+        MAX_CONTACT_NAME_SIZE,
+        MAX_CONTACT_NOTE_SIZE,
+        MAX_TRANSACTION_CATEGORY_SIZE,
+        MAX_TRANSACTION_LABEL_SIZE,
+        MAX_PENDING_TX_REBROADCASTS,
+        PENDING_TX_REBROADCAST_TOPOHEIGHTS,
         PASSWORD_ALGORITHM,
         PASSWORD_HASH_SIZE,
-        SALT_SIZE
+        SALT_SIZE,
+        STORAGE_VERSION,
+        WEBHOOK_SECRET_SIZE,
+        WEBHOOK_TIMEOUT_SECS
     },
     daemon_api::DaemonAPI,
+    entry::TransactionEntry as StoredTransactionEntry,
     error::WalletError,
+    export::{self, PriceProvider},
     mnemonics,
     network_handler::{
         NetworkHandler,
         SharedNetworkHandler
     },
+    pending::{self, PendingTransaction},
+    signer::Signer,
     storage::{
+        Balance,
         EncryptedStorage,
         Storage
     },
     transaction_builder::{
         EstimateFeesState,
         TransactionBuilderState
-    }
+    },
+    webhooks::{self, WebhookManager}
 };
 use chacha20poly1305::aead::OsRng;
 use rand::RngCore;
@@ -76,7 +142,8 @@ use log::{
     trace,
     debug,
     error,
-    info
+    info,
+    warn
 };
 
 #[cfg(feature = "api_server")]
@@ -84,7 +151,9 @@ use {
     serde_json::{json, Value},
     async_trait::async_trait,
     crate::api::{
+        WalletMetricsHandler,
         XSWDNodeMethodHandler,
+        write_gauge,
         register_rpc_methods,
         XSWD,
         WalletRpcServer,
@@ -95,12 +164,15 @@ use {
         PermissionRequest,
         XSWDPermissionHandler
     },
-    xelis_common::rpc_server::{
-        RPCHandler,
-        RpcRequest,
-        InternalRpcError,
-        RpcResponseError,
-        JSON_RPC_VERSION
+    xelis_common::{
+        api::wallet::XSWDLimits,
+        rpc_server::{
+            RPCHandler,
+            RpcRequest,
+            InternalRpcError,
+            RpcResponseError,
+            JSON_RPC_VERSION
+        }
     },
     tokio::sync::{
         mpsc::{UnboundedSender, UnboundedReceiver, unbounded_channel},
@@ -131,10 +203,34 @@ pub enum Event {
     Rescan {
         start_topoheight: u64   
     },
+    // Progress of the blocks being synced
+    // Values are counted in topoheights scanned over all the assets
+    SyncProgress {
+        current: u64,
+        target: u64
+    },
     // Wallet is now in online mode
     Online,
     // Wallet is now in offline mode
-    Offline
+    Offline,
+    // When an alert rule is triggered
+    Alert(WalletAlert),
+    // When another account is selected
+    AccountSelected(AccountEntry),
+    // When a scheduled payment is submitted
+    ScheduledPaymentExecuted(ScheduledPaymentResult),
+    // When a scheduled payment couldn't be built or submitted
+    ScheduledPaymentFailed(ScheduledPaymentResult),
+    // When a transaction built by the wallet is executed in a block
+    TransactionConfirmed {
+        hash: Hash,
+        topoheight: u64
+    },
+    // When a transaction built by the wallet will never be executed
+    // It was replaced by another one using its nonce, or the daemon kept ignoring it
+    TransactionDropped {
+        hash: Hash
+    }
 }
 
 impl Event {
@@ -145,74 +241,106 @@ impl Event {
             Event::BalanceChanged(_) => NotifyEvent::BalanceChanged,
             Event::NewAsset(_) => NotifyEvent::NewAsset,
             Event::Rescan { .. } => NotifyEvent::Rescan,
+            Event::SyncProgress { .. } => NotifyEvent::SyncProgress,
             Event::Online => NotifyEvent::Online,
-            Event::Offline => NotifyEvent::Offline
+            Event::Offline => NotifyEvent::Offline,
+            Event::Alert(_) => NotifyEvent::Alert,
+            Event::AccountSelected(_) => NotifyEvent::AccountSelected,
+            Event::ScheduledPaymentExecuted(_) => NotifyEvent::ScheduledPaymentExecuted,
+            Event::ScheduledPaymentFailed(_) => NotifyEvent::ScheduledPaymentFailed,
+            Event::TransactionConfirmed { .. } => NotifyEvent::TransactionConfirmed,
+            Event::TransactionDropped { .. } => NotifyEvent::TransactionDropped
         }
     }
 
 }
 
-// This is a 32 bytes aligned struct
-// It is necessary for the precomputed tables points
-#[derive(bytemuck::Pod, bytemuck::Zeroable, Copy, Clone)]
-#[repr(C, align(32))]
-struct Bytes32Alignment([u8; 32]);
-
-pub struct PrecomputedTables {
-    bytes: Vec<Bytes32Alignment>,
-    l1: usize,
-    bytes_count: usize,
-}
-
 // Allows to be used in several wallets at the same time
 pub type PrecomputedTablesShared = Arc<PrecomputedTables>;
 
-impl PrecomputedTables {
-    pub fn new(l1: usize) -> Self {
-        let bytes_count = ecdlp::table_generation::table_file_len(l1);
-        debug!("Precomputed tables size: {} bytes", bytes_count);
-        let mut n = bytes_count / 32;
-        if bytes_count % 32 != 0 {
-            n += 1;
-        }
+/// This struct is used to log the progress of the table generation
+pub struct LogProgressTableGenerationReportFunction;
+
+impl ecdlp::ProgressTableGenerationReportFunction for LogProgressTableGenerationReportFunction {
+    fn report(&self, progress: f64, step: ecdlp::ReportStep) -> ControlFlow<()> {
+        info!("Progress: {:.2}% on step {:?}", progress * 100.0, step);
+        ControlFlow::Continue(())
+    }
+}
+
+// Account derived from the private key of the seed
+pub struct Account {
+    index: u32,
+    // Private & Public key linked for this account
+    // None when the wallet is locked
+    keypair: Option<KeyPair>,
+    // Compressed public key
+    public_key: PublicKey
+}
 
-        let bytes = vec![Bytes32Alignment([0; 32]); n];
+impl Account {
+    fn new(master_key: &PrivateKey, index: u32) -> Self {
+        // The first account uses the key of the seed
+        let private_key = if index == 0 {
+            master_key.clone()
+        } else {
+            master_key.derive_account(index)
+        };
 
+        let keypair = KeyPair::from_private_key(private_key);
         Self {
-            bytes,
-            l1,
-            bytes_count
+            index,
+            public_key: keypair.get_public_key().compress(),
+            keypair: Some(keypair)
         }
     }
 
-    pub fn get<'a>(&'a self) -> &'a [u8] {
-       &bytemuck::cast_slice(self.bytes.as_slice())[..self.bytes_count]
+    // Same account without its keys
+    fn without_keys(&self) -> Self {
+        Self {
+            index: self.index,
+            keypair: None,
+            public_key: self.public_key.clone()
+        }
     }
 
-    pub fn get_mut<'a>(&'a mut self) -> &'a mut [u8] {
-        &mut bytemuck::cast_slice_mut(self.bytes.as_mut_slice())[..self.bytes_count]
+    pub fn get_index(&self) -> u32 {
+        self.index
     }
 
-    pub fn l1(&self) -> usize {
-        self.l1
+    // Get the keys of the account, the wallet must be unlocked
+    pub fn get_keypair(&self) -> Result<&KeyPair, WalletError> {
+        self.keypair.as_ref().ok_or(WalletError::WalletLocked)
     }
 
-    pub fn bytes_count(&self) -> usize {
-        self.bytes_count
+    pub fn get_public_key(&self) -> &PublicKey {
+        &self.public_key
     }
 }
 
-pub const PRECOMPUTED_TABLES_L1: usize = 26;
+// Wipe the private key from memory once the account is no longer used
+impl Drop for Account {
+    fn drop(&mut self) {
+        if let Some(keypair) = self.keypair.as_mut() {
+            keypair.zeroize();
+        }
+    }
+}
 
 pub struct Wallet {
     // Encrypted Wallet Storage
     storage: RwLock<EncryptedStorage>,
-    // Private & Public key linked for this wallet
-    keypair: KeyPair,
-    // Compressed public key
-    public_key: PublicKey,
+    // Private key of the seed, all accounts are derived from it
+    // None when the wallet is locked
+    master_key: StdRwLock<Option<PrivateKey>>,
+    // Account selected, the storage is using the same one
+    account: StdRwLock<Arc<Account>>,
+    // External signer of the account selected, its own key is used if not set
+    signer: StdRwLock<Option<Arc<dyn Signer>>>,
     // network handler for online mode to keep wallet synced
     network_handler: Mutex<Option<SharedNetworkHandler>>,
+    // TLS verification of the daemon when connecting to a wss:// address
+    daemon_tls: StdRwLock<TlsConfig>,
     // network on which we are connected
     network: Network,
     // RPC Server
@@ -221,10 +349,25 @@ pub struct Wallet {
     // All XSWD requests are routed through this channel
     #[cfg(feature = "api_server")]
     xswd_channel: RwLock<Option<UnboundedSender<XSWDEvent>>>,
+    // Metrics are exposed by the API server
+    #[cfg(feature = "api_server")]
+    api_metrics: AtomicBool,
     // Event broadcaster
     event_broadcaster: Mutex<Option<BroadcastSender<Event>>>,
+    // Alert rules evaluated during the sync
+    alerts: Mutex<AlertManager>,
+    // Notifications of the events queued for the webhooks
+    webhooks: Mutex<WebhookManager>,
     // Precomputed tables byte array
-    precomputed_tables: PrecomputedTablesShared
+    precomputed_tables: PrecomputedTablesShared,
+    // Last time in seconds the keys were used by the user
+    last_activity: AtomicU64,
+    // Inactivity duration before locking the wallet and its task
+    auto_lock: Mutex<Option<(Duration, JoinHandle<()>)>>,
+    // Syncing was stopped by the lock and must be restarted on unlock
+    resume_sync: AtomicBool,
+    // Encrypt the extra data of the transfers with the authenticated format
+    authenticated_extra_data: AtomicBool
 }
 
 pub fn hash_password(password: String, salt: &[u8]) -> Result<[u8; PASSWORD_HASH_SIZE], WalletError> {
@@ -233,71 +376,71 @@ pub fn hash_password(password: String, salt: &[u8]) -> Result<[u8; PASSWORD_HASH
     Ok(output)
 }
 
+// Get the lowest transaction version supporting the transaction type
+// Older versions are used when possible so the transaction is accepted before the hard forks
+fn get_tx_version(transaction_type: &TransactionTypeBuilder, multisig: bool) -> u8 {
+    let version = transaction_type.get_min_tx_version();
+    if multisig {
+        version.max(TX_VERSION_MULTISIG)
+    } else {
+        version
+    }
+}
+
 impl Wallet {
     // This will read from file if exists, or generate and store it in file
     // This must be call only one time, and can be cloned to be shared through differents wallets
     pub fn read_or_generate_precomputed_tables<P: ecdlp::ProgressTableGenerationReportFunction>(path: Option<String>, progress_report: P) -> Result<PrecomputedTablesShared, Error> {
-        const N: usize = PRECOMPUTED_TABLES_L1;
-        let mut precomputed_tables = PrecomputedTables::new(N);
-
-        if let Some(path) = path.as_ref() {
-            let path = Path::new(&path);
-            if !path.exists() {
-                create_dir_all(path)?;
-            }
-        }
-        let path = path.unwrap_or_default();
-
-        // Try to read from file
-        if let Ok(mut file) = File::open(format!("{path}precomputed_tables_{N}.bin")) {
-            info!("Reading precomputed tables from file");
-            file.read_exact(precomputed_tables.get_mut())?;
-        } else {
-            // File does not exists, generate and store it
-            info!("Generating precomputed tables");
-            ecdlp::table_generation::create_table_file_with_progress_report(N, precomputed_tables.get_mut(), progress_report)?;
-            File::create(format!("{path}precomputed_tables_{N}.bin"))?.write_all(precomputed_tables.get())?;
-        }
-
+        let precomputed_tables = PrecomputedTables::load_or_generate(&path.unwrap_or_default(), progress_report)?;
         Ok(Arc::new(precomputed_tables))
     }
 
-    // Create a new wallet with the specificed storage, keypair and its network
-    fn new(storage: EncryptedStorage, keypair: KeyPair, network: Network, precomputed_tables: PrecomputedTablesShared) -> Arc<Self> {
+    // Create a new wallet with the specificed storage, master key and its network
+    // The storage must have the account selected already
+    fn new(storage: EncryptedStorage, master_key: PrivateKey, network: Network, precomputed_tables: PrecomputedTablesShared) -> Arc<Self> {
+        let alerts_settings = storage.get_alerts_settings().unwrap_or_else(|e| {
+            error!("Error while loading alerts settings, using default: {}", e);
+            Default::default()
+        });
+        let webhooks_settings = storage.get_webhooks_settings().unwrap_or_else(|e| {
+            error!("Error while loading webhooks settings, using default: {}", e);
+            Default::default()
+        });
+        let webhooks_queue = storage.get_webhooks_queue().unwrap_or_else(|e| {
+            error!("Error while loading webhooks queue, using an empty one: {}", e);
+            Default::default()
+        });
+
+        let account = Account::new(&master_key, storage.get_account());
         let zelf = Self {
             storage: RwLock::new(storage),
-            public_key: keypair.get_public_key().compress(),
-            keypair,
+            master_key: StdRwLock::new(Some(master_key)),
+            account: StdRwLock::new(Arc::new(account)),
+            signer: StdRwLock::new(None),
             network_handler: Mutex::new(None),
+            daemon_tls: StdRwLock::new(TlsConfig::default()),
             network,
             #[cfg(feature = "api_server")]
             api_server: Mutex::new(None),
             #[cfg(feature = "api_server")]
             xswd_channel: RwLock::new(None),
+            #[cfg(feature = "api_server")]
+            api_metrics: AtomicBool::new(false),
             event_broadcaster: Mutex::new(None),
-            precomputed_tables
+            alerts: Mutex::new(AlertManager::new(alerts_settings)),
+            webhooks: Mutex::new(WebhookManager::new(webhooks_settings, webhooks_queue)),
+            precomputed_tables,
+            last_activity: AtomicU64::new(get_current_time_in_seconds()),
+            auto_lock: Mutex::new(None),
+            resume_sync: AtomicBool::new(false),
+            authenticated_extra_data: AtomicBool::new(false)
         };
 
         Arc::new(zelf)
     }
 
-    // Create a new wallet on disk
-    pub fn create(name: String, password: String, seed: Option<String>, network: Network, precomputed_tables: PrecomputedTablesShared) -> Result<Arc<Self>, Error> {
-        if name.is_empty() {
-            return Err(WalletError::EmptyName.into())
-        }
-
-        // generate random keypair or recover it from seed
-        let keypair = if let Some(seed) = seed {
-        debug!("Retrieving keypair from seed...");
-        let words: Vec<String> = seed.split_whitespace().map(str::to_string).collect();
-        let key = mnemonics::words_to_key(&words)?;
-            KeyPair::from_private_key(key)
-        } else {
-            debug!("Generating a new keypair...");
-            KeyPair::new()
-        };
-
+    // Create the storage of a new wallet, encrypted by a master key protected by the password
+    fn create_storage(name: String, password: String, network: Network) -> Result<EncryptedStorage, Error> {
         // generate random salt for hashed password
         let mut salt: [u8; SALT_SIZE] = [0; SALT_SIZE];
         OsRng.fill_bytes(&mut salt);
@@ -330,15 +473,98 @@ impl Wallet {
         inner.set_encrypted_storage_salt(&encrypted_storage_salt)?;
 
         debug!("Creating encrypted storage");
-        let mut storage = EncryptedStorage::new(inner, &master_key, storage_salt, network)?;
+        let storage = EncryptedStorage::new(inner, &master_key, storage_salt, network);
+        master_key.zeroize();
+        let mut storage = storage?;
+        storage.set_storage_version(STORAGE_VERSION)?;
+
+        Ok(storage)
+    }
+
+    // Create a new wallet on disk
+    // The passphrase is combined with the seed, each passphrase gives an independent wallet
+    pub fn create(name: String, password: String, seed: Option<String>, passphrase: Option<String>, network: Network, precomputed_tables: PrecomputedTablesShared) -> Result<Arc<Self>, Error> {
+        if name.is_empty() {
+            return Err(WalletError::EmptyName.into())
+        }
+
+        // generate random keypair or recover it from seed
+        let recover = seed.is_some();
+        let keypair = if let Some(seed) = seed {
+        debug!("Retrieving keypair from seed...");
+        let words: Vec<String> = seed.split_whitespace().map(str::to_string).collect();
+        let key = mnemonics::words_to_key(&words)?;
+            KeyPair::from_private_key(key)
+        } else {
+            debug!("Generating a new keypair...");
+            KeyPair::new()
+        };
+
+        let mut storage = Self::create_storage(name, password, network)?;
 
         // Store the private key
-        storage.set_private_key(&keypair.get_private_key())?;
+        // With a passphrase, it is derived from the key of the seed which is kept to display the seed
+        let (_, seed_key) = keypair.split();
+        let private_key = match passphrase.filter(|passphrase| !passphrase.is_empty()) {
+            Some(passphrase) => {
+                debug!("Deriving private key using the passphrase");
+                storage.set_seed_key(&seed_key)?;
+                mnemonics::apply_passphrase(&seed_key, &passphrase)?
+            },
+            None => seed_key
+        };
+        storage.set_private_key(&private_key)?;
+
+        // Accounts derived from the seed will be searched once online
+        if recover {
+            storage.set_accounts_recovery_pending(true)?;
+        }
+
+        // Flush the storage to be sure its written on disk
+        storage.flush()?;
+
+        Ok(Self::new(storage, private_key, network, precomputed_tables))
+    }
+
+    // Restore a wallet from a backup file in a new storage
+    // The history is restored but the balances are synced again once online
+    pub fn restore_from_file(name: String, password: String, path: &str, backup_password: String, network: Network, precomputed_tables: PrecomputedTablesShared) -> Result<Arc<Self>, Error> {
+        if name.is_empty() {
+            return Err(WalletError::EmptyName.into())
+        }
+
+        debug!("Reading backup file {}", path);
+        let backup = WalletBackup::read_from_file(path, backup_password)?;
+        if backup.network != network {
+            return Err(WalletError::InvalidBackupNetwork(backup.network).into())
+        }
+
+        let mut storage = Self::create_storage(name, password, network)?;
+        storage.set_private_key(&backup.private_key)?;
+        if let Some(seed_key) = backup.seed_key.as_ref() {
+            storage.set_seed_key(seed_key)?;
+        }
+
+        storage.set_alerts_settings(&backup.alerts)?;
+        for (name, contact) in backup.contacts.iter() {
+            storage.set_contact(name, contact)?;
+        }
+
+        debug!("Restoring history of {} accounts", backup.transactions.len());
+        storage.set_accounts_count(backup.transactions.len() as u32)?;
+        for (account, entries) in backup.transactions.iter().enumerate() {
+            for entry in entries {
+                storage.save_account_transaction(account as u32, entry)?;
+            }
+        }
+
+        storage.set_selected_account(backup.selected_account)?;
+        storage.set_account(backup.selected_account)?;
 
         // Flush the storage to be sure its written on disk
         storage.flush()?;
 
-        Ok(Self::new(storage, keypair, network, precomputed_tables))
+        Ok(Self::new(storage, backup.private_key.clone(), network, precomputed_tables))
     }
 
     // Open an existing wallet on disk
@@ -348,8 +574,34 @@ impl Wallet {
         }
 
         debug!("Creating storage for {}", name);
-        let storage = Storage::new(name)?;
-        
+        let storage = Storage::new(name.clone())?;
+        let (master_key, salt) = Self::decrypt_master_key(&storage, password)?;
+
+        debug!("Creating encrypted storage");
+        let mut storage = EncryptedStorage::new(storage, &master_key, salt, network)?;
+
+        // Upgrade a storage written by an older version, a backup is made first
+        if storage.needs_migration()? {
+            let backup_path = format!("{}_backup_v{}_{}", name, storage.get_storage_version()?, get_current_time_in_seconds());
+            info!("Wallet storage must be migrated, saving a backup at {}", backup_path);
+            storage.backup(&backup_path)?;
+            storage.migrate()?;
+            info!("Wallet storage migrated");
+        }
+
+        debug!("Retrieving private key from encrypted storage");
+        let private_key =  storage.get_private_key()?;
+
+        let account = storage.get_selected_account()?;
+        debug!("Selecting account {}", account);
+        storage.set_account(account)?;
+
+        Ok(Self::new(storage, private_key, network, precomputed_tables))
+    }
+
+    // Decrypt the master key of the storage and its salt using the password
+    // The master key is wiped from memory once dropped
+    fn decrypt_master_key(storage: &Storage, password: String) -> Result<(Zeroizing<Vec<u8>>, [u8; SALT_SIZE]), Error> {
         // get password salt for KDF
         debug!("Retrieving password salt from public storage");
         let salt = storage.get_password_salt()?;
@@ -358,15 +610,15 @@ impl Wallet {
         debug!("Retrieving encrypted master key from public storage");
         let encrypted_master_key = storage.get_encrypted_master_key()?;
 
-        let hashed_password = hash_password(password, &salt)?;
+        let hashed_password = Zeroizing::new(hash_password(password, &salt)?);
 
         // decrypt the encrypted master key using the hashed password (used as key)
-        let cipher = Cipher::new(&hashed_password, None)?;
-        let master_key = cipher.decrypt_value(&encrypted_master_key).context("Invalid password provided for this wallet")?;
+        let cipher = Cipher::new(&*hashed_password, None)?;
+        let master_key = Zeroizing::new(cipher.decrypt_value(&encrypted_master_key).context("Invalid password provided for this wallet")?);
 
         // Retrieve the encrypted storage salt
         let encrypted_storage_salt = storage.get_encrypted_storage_salt()?;
-        let storage_salt = cipher.decrypt_value(&encrypted_storage_salt).context("Invalid encrypted storage salt for this wallet")?;
+        let storage_salt = Zeroizing::new(cipher.decrypt_value(&encrypted_storage_salt).context("Invalid encrypted storage salt for this wallet")?);
         if storage_salt.len() != SALT_SIZE {
             error!("Invalid size received after decrypting storage salt: {} bytes", storage_salt.len());
             return Err(WalletError::InvalidSaltSize.into());
@@ -375,13 +627,7 @@ impl Wallet {
         let mut salt: [u8; SALT_SIZE] = [0; SALT_SIZE];
         salt.copy_from_slice(&storage_salt);
 
-        debug!("Creating encrypted storage");
-        let storage = EncryptedStorage::new(storage, &master_key, salt, network)?;
-        debug!("Retrieving private key from encrypted storage");
-        let private_key =  storage.get_private_key()?;
-        let keypair = KeyPair::from_private_key(private_key);
-
-        Ok(Self::new(storage, keypair, network, precomputed_tables))
+        Ok((master_key, salt))
     }
 
     // Close the wallet
@@ -425,13 +671,37 @@ impl Wallet {
             storage.stop().await;
         }
 
+        // Stop the auto lock task
+        {
+            let mut lock = self.auto_lock.lock().await;
+            if let Some((_, task)) = lock.take() {
+                task.abort();
+            }
+        }
+
         // Close the event broadcaster
         // So all subscribers will be notified
         self.close_events_channel().await;
     }
 
     // Propagate a new event to registered listeners
+    // Alert rules are evaluated against it and the triggered alerts are propagated too
     pub async fn propagate_event(&self, event: Event) {
+        let alerts = self.evaluate_alerts(&event).await;
+        if matches!(event, Event::NewTransaction(_) | Event::BalanceChanged(_)) {
+            let mut webhooks = self.webhooks.lock().await;
+            webhooks.enqueue(event.kind(), &event);
+        }
+        self.broadcast_event(event).await;
+
+        for alert in alerts {
+            self.send_alert_to_webhooks(&alert).await;
+            self.broadcast_event(Event::Alert(alert)).await;
+        }
+    }
+
+    // Send the event to the API Server and the event broadcaster
+    async fn broadcast_event(&self, event: Event) {
         // Broadcast it to the API Server
         #[cfg(feature = "api_server")]
         {
@@ -454,144 +724,825 @@ impl Wallet {
         }
     }
 
-    // Subscribe to events
-    pub async fn subscribe_events(&self) -> BroadcastReceiver<Event> {
-        let mut broadcaster = self.event_broadcaster.lock().await;
-        match broadcaster.as_ref() {
-            Some(broadcaster) => broadcaster.subscribe(),
-            None => {
-                let (sender, receiver) = tokio::sync::broadcast::channel(10);
-                *broadcaster = Some(sender);
-                receiver
-            }
+    // Evaluate the alert rules against the event
+    async fn evaluate_alerts(&self, event: &Event) -> Vec<WalletAlert> {
+        match event {
+            Event::BalanceChanged(changed) => {
+                let mut alerts = self.alerts.lock().await;
+                alerts.on_balance_changed(&changed.asset, changed.balance)
+            },
+            Event::NewTransaction(TransactionEntry { hash, entry: EntryType::Incoming { transfers, .. }, .. }) => {
+                let mut alerts = self.alerts.lock().await;
+                transfers.iter()
+                    .flat_map(|transfer| alerts.on_incoming_transfer(hash, &transfer.asset, transfer.amount))
+                    .collect()
+            },
+            _ => Vec::new()
         }
     }
 
-    // Close events channel
-    // This will disconnect all subscribers
-    pub async fn close_events_channel(&self) -> bool {
-        trace!("Closing events channel");
-        let mut broadcaster = self.event_broadcaster.lock().await;
-        broadcaster.take().is_some()
-    }
+    // A new nonce has been synced from the daemon
+    // previous is the nonce stored and expected the next nonce we would use
+    pub async fn on_nonce_synced(&self, previous: u64, expected: u64, nonce: u64) {
+        let alerts = {
+            let mut alerts = self.alerts.lock().await;
+            alerts.on_nonce_changed(previous, expected, nonce)
+        };
 
-    // Enable RPC Server with requested authentication and bind address
-    #[cfg(feature = "api_server")]
-    pub async fn enable_rpc_server(self: &Arc<Self>, bind_address: String, config: Option<AuthConfig>) -> Result<(), Error> {
-        let mut lock = self.api_server.lock().await;
-        if lock.is_some() {
-            return Err(WalletError::RPCServerAlreadyRunning.into())
+        for alert in alerts {
+            self.send_alert_to_webhooks(&alert).await;
+            self.broadcast_event(Event::Alert(alert)).await;
         }
-        let mut rpc_handler = RPCHandler::new(self.clone());
-        register_rpc_methods(&mut rpc_handler);
-
-        let rpc_server = WalletRpcServer::new(bind_address, rpc_handler, config).await?;
-        *lock = Some(APIServer::RPCServer(rpc_server));
-        Ok(())
     }
 
-    // Enable XSWD Protocol
-    #[cfg(feature = "api_server")]
-    pub async fn enable_xswd(self: &Arc<Self>) -> Result<UnboundedReceiver<XSWDEvent>, Error> {
-        let receiver = {
-            let (sender, receiver) = unbounded_channel();
-            let mut channel = self.xswd_channel.write().await;
-            *channel = Some(sender);
-            receiver
+    // POST the alert to all configured webhooks
+    // Requests are done in background to not slow down the sync
+    async fn send_alert_to_webhooks(&self, alert: &WalletAlert) {
+        let webhooks = {
+            let alerts = self.alerts.lock().await;
+            alerts.get_webhooks().clone()
         };
 
-        let mut lock = self.api_server.lock().await;
-        if lock.is_some() {
-            return Err(WalletError::RPCServerAlreadyRunning.into())
+        if webhooks.is_empty() {
+            return
         }
-        let mut rpc_handler = RPCHandler::new(self.clone());
-        register_rpc_methods(&mut rpc_handler);
 
-        *lock = Some(APIServer::XSWD(XSWD::new(rpc_handler)?));
-        Ok(receiver)
+        let alert = alert.clone();
+        tokio::spawn(async move {
+            let client = reqwest::Client::new();
+            for url in webhooks {
+                let res = client.post(&url)
+                    .timeout(Duration::from_secs(ALERT_WEBHOOK_TIMEOUT_SECS))
+                    .json(&alert)
+                    .send().await;
+
+                match res {
+                    Ok(response) if !response.status().is_success() => warn!("Webhook {} answered with status {} for alert {}", url, response.status(), alert.id),
+                    Err(e) => warn!("Error while sending alert {} to webhook {}: {}", alert.id, url, e),
+                    _ => trace!("Alert {} sent to webhook {}", alert.id, url)
+                }
+            }
+        });
     }
 
-    #[cfg(feature = "api_server")]
-    pub async fn stop_api_server(&self) -> Result<(), Error> {
-        let mut lock = self.api_server.lock().await;
-        let rpc_server = lock.take().ok_or(WalletError::RPCServerNotRunning)?;
-        rpc_server.stop().await;
-        Ok(())
-    }
+    // Add a new alert rule and returns its id
+    pub async fn add_alert_rule(&self, rule: AlertRule) -> Result<u64, Error> {
+        // Storage lock must not be taken while holding the alerts lock
+        // because events are propagated while holding the storage lock
+        let (id, settings) = {
+            let mut alerts = self.alerts.lock().await;
+            let id = alerts.add_rule(rule);
+            (id, alerts.get_settings().clone())
+        };
 
-    #[cfg(feature = "api_server")]
-    pub fn get_api_server<'a>(&'a self) -> &Mutex<Option<APIServer<Arc<Self>>>> {
-        &self.api_server
+        let mut storage = self.storage.write().await;
+        storage.set_alerts_settings(&settings)?;
+        Ok(id)
     }
 
-    // Verify if a password is valid or not
-    pub async fn is_valid_password(&self, password: String) -> Result<(), Error> {
-        let mut encrypted_storage = self.storage.write().await;
-        let storage = encrypted_storage.get_mutable_public_storage();
-        let salt = storage.get_password_salt()?;
-        let hashed_password = hash_password(password, &salt)?;
-        let cipher = Cipher::new(&hashed_password, None)?;
-        let encrypted_master_key = storage.get_encrypted_master_key()?;
-        let _ = cipher.decrypt_value(&encrypted_master_key).context("Invalid password provided")?;
-        Ok(())
+    // Remove an alert rule, returns false if it was not found
+    pub async fn remove_alert_rule(&self, id: u64) -> Result<bool, Error> {
+        let settings = {
+            let mut alerts = self.alerts.lock().await;
+            if !alerts.remove_rule(id) {
+                return Ok(false)
+            }
+            alerts.get_settings().clone()
+        };
+
+        let mut storage = self.storage.write().await;
+        storage.set_alerts_settings(&settings)?;
+        Ok(true)
     }
 
-    // change the current password wallet to a new one
-    pub async fn set_password(&self, old_password: String, password: String) -> Result<(), Error> {
-        let mut encrypted_storage = self.storage.write().await;
-        let storage = encrypted_storage.get_mutable_public_storage();
-        let (master_key, storage_salt) = {
-            // retrieve old salt to build key from current password
-            let salt = storage.get_password_salt()?;
-            let hashed_password = hash_password(old_password, &salt)?;
+    // All alert rules configured
+    pub async fn get_alert_rules(&self) -> Vec<AlertRuleEntry> {
+        let alerts = self.alerts.lock().await;
+        alerts.get_rules()
+    }
 
-            let encrypted_master_key = storage.get_encrypted_master_key()?;
-            let encrypted_storage_salt = storage.get_encrypted_storage_salt()?;
+    // Set the URLs receiving the alerts
+    pub async fn set_alert_webhooks(&self, webhooks: Vec<String>) -> Result<(), WalletError> {
+        if let Some(url) = webhooks.iter().find(|url| url.len() > u8::MAX as usize || !(url.starts_with("http://") || url.starts_with("https://"))) {
+            return Err(WalletError::InvalidWebhookUrl(url.clone()))
+        }
 
-            // decrypt the encrypted master key using the provided password
-            let cipher = Cipher::new(&hashed_password, None)?;
-            let master_key = cipher.decrypt_value(&encrypted_master_key).context("Invalid password provided")?;
-            let storage_salt = cipher.decrypt_value(&encrypted_storage_salt)?;
-            (master_key, storage_salt)
+        let settings = {
+            let mut alerts = self.alerts.lock().await;
+            alerts.set_webhooks(webhooks);
+            alerts.get_settings().clone()
         };
 
-        // generate a new salt for password
-        let mut salt: [u8; SALT_SIZE] = [0; SALT_SIZE];
-        OsRng.fill_bytes(&mut salt);
+        let mut storage = self.storage.write().await;
+        storage.set_alerts_settings(&settings)?;
+        Ok(())
+    }
 
-        // generate the password-based derivated key to encrypt the master key
-        let hashed_password = hash_password(password, &salt)?;
-        let cipher = Cipher::new(&hashed_password, None)?;
+    // URLs receiving the alerts
+    pub async fn get_alert_webhooks(&self) -> Vec<String> {
+        let alerts = self.alerts.lock().await;
+        alerts.get_webhooks().clone()
+    }
 
-        // encrypt the master key using the new password
-        let encrypted_key = cipher.encrypt_value(&master_key)?;
+    // Set the URLs notified of the new transactions and balance changes
+    // Returns the secret used to sign the notifications, a new one is generated if not set
+    pub async fn set_webhooks(&self, urls: Vec<String>, secret: Option<String>) -> Result<String, WalletError> {
+        if let Some(url) = urls.iter().find(|url| url.len() > u8::MAX as usize || !(url.starts_with("http://") || url.starts_with("https://"))) {
+            return Err(WalletError::InvalidWebhookUrl(url.clone()))
+        }
 
-        // encrypt the salt with the new password
-        let encrypted_storage_salt = cipher.encrypt_value(&storage_salt)?;
+        let secret = match secret {
+            Some(secret) if secret.is_empty() || secret.len() > u8::MAX as usize => return Err(WalletError::InvalidWebhookSecret),
+            Some(secret) => secret,
+            None => {
+                let mut bytes = [0u8; WEBHOOK_SECRET_SIZE];
+                OsRng.fill_bytes(&mut bytes);
+                hex::encode(bytes)
+            }
+        };
 
-        // save on disk
-        storage.set_password_salt(&salt)?;
-        storage.set_encrypted_master_key(&encrypted_key)?;
-        storage.set_encrypted_storage_salt(&encrypted_storage_salt)?;
+        // Storage lock must not be taken while holding the webhooks lock
+        let (settings, queue) = {
+            let mut webhooks = self.webhooks.lock().await;
+            webhooks.set_settings(urls, secret.clone());
+            (webhooks.get_settings().clone(), webhooks.get_queue().clone())
+        };
 
-        Ok(())
+        let mut storage = self.storage.write().await;
+        storage.set_webhooks_settings(&settings)?;
+        storage.set_webhooks_queue(&queue)?;
+        Ok(secret)
     }
 
-    // Wallet has to be under a Arc to be shared to the spawn_blocking function
-    pub async fn decrypt_ciphertext(self: Arc<Self>, ciphertext: Ciphertext) -> Result<u64, WalletError> {
-        trace!("decrypt ciphertext");
-        tokio::task::spawn_blocking(move || {
-            let view = ECDLPTablesFileView::<PRECOMPUTED_TABLES_L1>::from_bytes(self.precomputed_tables.get());
-            self.keypair.get_private_key()
-                .decrypt(&view, &ciphertext)
-                .ok_or(WalletError::CiphertextDecode)
-        }).await.context("Error while decrypting ciphertext")?
+    // URLs notified and the count of notifications not delivered yet
+    pub async fn get_webhooks(&self) -> GetWebhooksResult {
+        let webhooks = self.webhooks.lock().await;
+        GetWebhooksResult {
+            urls: webhooks.get_urls().clone(),
+            pending: webhooks.get_pending()
+        }
     }
 
-    // Decrypt the extra data from a transfer
-    pub fn decrypt_extra_data(&self, cipher: UnknownExtraDataFormat, handle: &DecryptHandle, role: Role) -> Result<DataElement, WalletError> {
-        trace!("decrypt extra data");
-        cipher.decrypt(&self.keypair.get_private_key(), handle, role).map_err(|_| WalletError::CiphertextDecode)
+    // POST the webhook notifications due, each body is signed with the secret
+    // Requests are done in background to not slow down the sync
+    // Failed ones stay in the queue and are retried on a next call
+    pub async fn deliver_webhooks(self: &Arc<Self>) {
+        let (secret, due) = {
+            let mut webhooks = self.webhooks.lock().await;
+            match webhooks.start_delivery(get_current_time_in_seconds()) {
+                Some(delivery) => delivery,
+                None => return
+            }
+        };
+
+        let zelf = Arc::clone(self);
+        tokio::spawn(async move {
+            // Save the new notifications before sending them
+            if let Err(e) = zelf.save_webhooks_queue().await {
+                warn!("Error while saving webhooks queue: {}", e);
+            }
+
+            let client = reqwest::Client::new();
+            let mut results = Vec::with_capacity(due.len());
+            for (id, delivery) in due {
+                let res = client.post(&delivery.url)
+                    .timeout(Duration::from_secs(WEBHOOK_TIMEOUT_SECS))
+                    .header("Content-Type", "application/json")
+                    .header("X-Xelis-Signature", webhooks::sign(&secret, &delivery.body))
+                    .body(delivery.body)
+                    .send().await;
+
+                let success = match res {
+                    Ok(response) if !response.status().is_success() => {
+                        warn!("Webhook {} answered with status {} for delivery {}", delivery.url, response.status(), id);
+                        false
+                    },
+                    Err(e) => {
+                        warn!("Error while sending delivery {} to webhook {}: {}", id, delivery.url, e);
+                        false
+                    },
+                    _ => {
+                        trace!("Delivery {} sent to webhook {}", id, delivery.url);
+                        true
+                    }
+                };
+                results.push((id, success));
+            }
+
+            {
+                let mut webhooks = zelf.webhooks.lock().await;
+                webhooks.end_delivery(results, get_current_time_in_seconds());
+            }
+
+            if let Err(e) = zelf.save_webhooks_queue().await {
+                warn!("Error while saving webhooks queue: {}", e);
+            }
+        });
+    }
+
+    // Persist the webhook notifications not delivered yet
+    async fn save_webhooks_queue(&self) -> Result<(), Error> {
+        let queue = {
+            let webhooks = self.webhooks.lock().await;
+            webhooks.get_queue().clone()
+        };
+
+        let mut storage = self.storage.write().await;
+        storage.set_webhooks_queue(&queue)
+    }
+
+    // Latest alerts triggered, oldest first
+    pub async fn get_alerts(&self) -> Vec<WalletAlert> {
+        let alerts = self.alerts.lock().await;
+        alerts.get_alerts().iter().cloned().collect()
+    }
+
+    // Schedule a payment of the account selected and returns its id
+    pub async fn add_scheduled_payment(&self, payment: ScheduledPayment) -> Result<u64, WalletError> {
+        trace!("add scheduled payment");
+        if payment.amount == 0 {
+            return Err(WalletError::InvalidScheduledPayment("amount can't be zero"))
+        }
+
+        if payment.interval == Some(0) {
+            return Err(WalletError::InvalidScheduledPayment("interval can't be zero"))
+        }
+
+        if payment.destination.is_mainnet() != self.network.is_mainnet() {
+            return Err(WalletError::InvalidScheduledPayment("destination is not on the wallet network"))
+        }
+
+        if *payment.destination.get_public_key() == self.get_public_key() {
+            return Err(WalletError::InvalidScheduledPayment("destination can't be the wallet itself"))
+        }
+
+        let mut storage = self.storage.write().await;
+        let mut payments = storage.get_scheduled_payments()?;
+        let id = payments.add(payment);
+        storage.set_scheduled_payments(&payments)?;
+
+        Ok(id)
+    }
+
+    // Remove a scheduled payment, returns false if it was not found
+    pub async fn remove_scheduled_payment(&self, id: u64) -> Result<bool, WalletError> {
+        trace!("remove scheduled payment {}", id);
+        let mut storage = self.storage.write().await;
+        let mut payments = storage.get_scheduled_payments()?;
+        if !payments.remove(id) {
+            return Ok(false)
+        }
+        storage.set_scheduled_payments(&payments)?;
+
+        Ok(true)
+    }
+
+    // All the payments scheduled for the account selected
+    pub async fn get_scheduled_payments(&self) -> Result<Vec<ScheduledPaymentEntry>, WalletError> {
+        let storage = self.storage.read().await;
+        Ok(storage.get_scheduled_payments()?.get_entries())
+    }
+
+    // Build and submit the scheduled payments due at this topoheight or time
+    // Payments are rescheduled before being submitted so they are never sent twice,
+    // a failed execution is not retried
+    pub async fn execute_scheduled_payments(&self, topoheight: u64) -> Result<(), WalletError> {
+        trace!("execute scheduled payments at topoheight {}", topoheight);
+        let timestamp = get_current_time_in_seconds();
+        let due = {
+            let mut storage = self.storage.write().await;
+            let mut payments = storage.get_scheduled_payments()?;
+            let due = payments.get_due(topoheight, timestamp);
+            if due.is_empty() {
+                return Ok(())
+            }
+
+            for entry in due.iter() {
+                payments.reschedule(entry.id, topoheight, timestamp);
+            }
+            storage.set_scheduled_payments(&payments)?;
+            due
+        };
+
+        for entry in due {
+            debug!("Executing scheduled payment {}", entry.id);
+            match self.submit_scheduled_payment(entry.payment).await {
+                Ok(hash) => {
+                    info!("Scheduled payment {} submitted in transaction {}", entry.id, hash);
+                    self.propagate_event(Event::ScheduledPaymentExecuted(ScheduledPaymentResult { id: entry.id, hash: Some(hash), error: None })).await;
+                },
+                Err(e) => {
+                    warn!("Error while executing scheduled payment {}: {}", entry.id, e);
+                    self.propagate_event(Event::ScheduledPaymentFailed(ScheduledPaymentResult { id: entry.id, hash: None, error: Some(e.to_string()) })).await;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Follow the transactions built by the wallet until they are executed
+    // The ones still missing are broadcasted again every PENDING_TX_REBROADCAST_TOPOHEIGHTS
+    pub async fn process_pending_transactions(&self, topoheight: u64) -> Result<(), WalletError> {
+        trace!("process pending transactions at topoheight {}", topoheight);
+        let (transactions, nonce) = {
+            let storage = self.storage.read().await;
+            (storage.get_pending_transactions()?, storage.get_nonce().unwrap_or(0))
+        };
+
+        for (hash, mut pending) in transactions {
+            let executed_topoheight = {
+                let storage = self.storage.read().await;
+                if storage.has_transaction(&hash)? {
+                    Some(storage.get_transaction(&hash)?.get_topoheight())
+                } else {
+                    None
+                }
+            };
+
+            if let Some(executed_topoheight) = executed_topoheight {
+                debug!("Pending transaction {} was executed at topoheight {}", hash, executed_topoheight);
+                self.storage.write().await.delete_pending_transaction(&hash)?;
+                self.propagate_event(Event::TransactionConfirmed { hash, topoheight: executed_topoheight }).await;
+                continue;
+            }
+
+            // Its nonce was used by another transaction executed
+            let replaced = pending.get_transaction().get_nonce() < nonce;
+            if replaced || pending.get_rebroadcasts() >= MAX_PENDING_TX_REBROADCASTS {
+                warn!("Pending transaction {} was dropped (replaced: {})", hash, replaced);
+                self.storage.write().await.delete_pending_transaction(&hash)?;
+                self.propagate_event(Event::TransactionDropped { hash }).await;
+                continue;
+            }
+
+            if topoheight < pending.get_topoheight() + PENDING_TX_REBROADCAST_TOPOHEIGHTS {
+                continue;
+            }
+
+            // The daemon may have lost it, an error is expected if it is still in its mempool
+            match self.submit_transaction(pending.get_transaction()).await {
+                Ok(()) => info!("Pending transaction {} was broadcasted again", hash),
+                Err(e) => debug!("Error while broadcasting again pending transaction {}: {}", hash, e)
+            };
+
+            pending.set_rebroadcasted(topoheight);
+            let mut storage = self.storage.write().await;
+            if storage.has_pending_transaction(&hash)? {
+                storage.set_pending_transaction(&hash, &pending)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Get the transactions built by the wallet and not executed yet
+    pub async fn get_pending_transactions(&self) -> Result<Vec<PendingTransactionEntry>, WalletError> {
+        let mainnet = self.network.is_mainnet();
+        let storage = self.storage.read().await;
+        let transactions = storage.get_pending_transactions()?
+            .into_iter()
+            .map(|(hash, pending)| PendingTransactionEntry {
+                transaction: StoredTransactionEntry::new(hash, pending.get_topoheight(), pending.get_entry().clone()).serializable(mainnet),
+                rebroadcasts: pending.get_rebroadcasts()
+            })
+            .collect();
+
+        Ok(transactions)
+    }
+
+    // Balance which can be spent once the pending transactions are executed
+    // It is 0 if the wallet has no balance for this asset
+    pub async fn get_spendable_balance(&self, asset: &Hash) -> Result<u64, WalletError> {
+        let storage = self.storage.read().await;
+        let balance = storage.get_plaintext_balance_for(asset).await.unwrap_or(0);
+        let cost = storage.get_pending_cost_for(asset)?;
+        Ok(balance.saturating_sub(cost))
+    }
+
+    // Build again the only pending transaction with the same nonce and a higher fee
+    // The daemon replaces it in its mempool as no other transaction depends on it
+    // By default, the previous fee is doubled
+    pub async fn bump_fee(&self, hash: &Hash, fee: Option<FeeBuilder>) -> Result<Transaction, WalletError> {
+        trace!("bump fee of {}", hash);
+        let mainnet = self.network.is_mainnet();
+        let mut storage = self.storage.write().await;
+        let transactions = storage.get_pending_transactions()?;
+        let pending = transactions.iter()
+            .find(|(pending_hash, _)| pending_hash == hash)
+            .map(|(_, pending)| pending)
+            .ok_or_else(|| WalletError::PendingTransactionNotFound(hash.clone()))?;
+
+        let nonce = pending.get_transaction().get_nonce();
+        if transactions.len() > 1 || storage.get_nonce().unwrap_or(0) != nonce {
+            return Err(WalletError::FeeBumpNotPossible(hash.clone()))
+        }
+
+        let transaction_type = pending.get_transaction_type(mainnet)
+            .ok_or_else(|| WalletError::FeeBumpNotPossible(hash.clone()))?;
+        let previous_fee = pending.get_transaction().get_fee();
+        let required_fee = get_replacement_min_fee(previous_fee);
+        let fee = fee.unwrap_or(FeeBuilder::Value(previous_fee.saturating_mul(2).max(required_fee)));
+
+        // Built on top of the confirmed balances as it is the only pending transaction
+        let reference = Reference {
+            topoheight: storage.get_synced_topoheight()?,
+            hash: storage.get_top_block_hash()?
+        };
+        let mut state = TransactionBuilderState::new(mainnet, reference, nonce);
+        for asset in transaction_type.used_assets() {
+            let balance = storage.get_balance_for(&asset).await?;
+            state.add_balance(asset, balance);
+        }
+        self.add_registered_keys_for_fees_estimation(state.as_mut(), &fee, &transaction_type).await?;
+
+        let (mut state, transaction) = self.create_transaction_with_state(&storage, state, transaction_type, fee).await?;
+        // Daemon refuses a replacement which doesn't increase enough the fee
+        if transaction.get_fee() < required_fee {
+            return Err(WalletError::FeeTooLowForBump(transaction.get_fee(), required_fee))
+        }
+        self.submit_transaction(&transaction).await?;
+
+        // Unconfirmed balances were built on top of the replaced transaction
+        storage.delete_pending_transaction(hash)?;
+        storage.delete_unconfirmed_balances().await?;
+        storage.clear_tx_cache();
+        state.apply_changes(&mut storage).await?;
+
+        let new_hash = transaction.hash();
+        if storage.has_transaction_label(hash)? {
+            let label = storage.get_transaction_label(hash)?;
+            storage.set_transaction_label(&new_hash, &label)?;
+            storage.delete_transaction_label(hash)?;
+        }
+        drop(storage);
+
+        info!("Transaction {} replaced by {} with fee {} instead of {}", hash, new_hash, transaction.get_fee(), previous_fee);
+        self.propagate_event(Event::TransactionDropped { hash: hash.clone() }).await;
+
+        Ok(transaction)
+    }
+
+    // Build the transfer of a scheduled payment and submit it
+    async fn submit_scheduled_payment(&self, payment: ScheduledPayment) -> Result<Hash, WalletError> {
+        let transfer = TransferBuilder {
+            destination: payment.destination,
+            amount: payment.amount,
+            asset: payment.asset,
+            extra_data: None,
+            lock: None
+        };
+
+        // The lock is kept until the TX is applied to the storage
+        let mut storage = self.storage.write().await;
+        let tx = self.create_and_submit_transaction_with_storage(&mut storage, TransactionTypeBuilder::Transfers(vec![transfer]), FeeBuilder::default()).await?;
+
+        Ok(tx.hash())
+    }
+
+    // Subscribe to events
+    pub async fn subscribe_events(&self) -> BroadcastReceiver<Event> {
+        let mut broadcaster = self.event_broadcaster.lock().await;
+        match broadcaster.as_ref() {
+            Some(broadcaster) => broadcaster.subscribe(),
+            None => {
+                let (sender, receiver) = tokio::sync::broadcast::channel(10);
+                *broadcaster = Some(sender);
+                receiver
+            }
+        }
+    }
+
+    // Close events channel
+    // This will disconnect all subscribers
+    pub async fn close_events_channel(&self) -> bool {
+        trace!("Closing events channel");
+        let mut broadcaster = self.event_broadcaster.lock().await;
+        broadcaster.take().is_some()
+    }
+
+    // Expose the metrics on the /metrics route of the next API server started
+    #[cfg(feature = "api_server")]
+    pub fn set_api_metrics(&self, enabled: bool) {
+        self.api_metrics.store(enabled, Ordering::SeqCst);
+    }
+
+    // Encrypt the extra data of the next transfers built with the authenticated format
+    pub fn set_authenticated_extra_data(&self, enabled: bool) {
+        self.authenticated_extra_data.store(enabled, Ordering::SeqCst);
+    }
+
+    pub fn is_authenticated_extra_data(&self) -> bool {
+        self.authenticated_extra_data.load(Ordering::SeqCst)
+    }
+
+    // RPC handler with all the wallet methods, shared by the RPC Server and XSWD
+    #[cfg(feature = "api_server")]
+    fn create_rpc_handler(self: &Arc<Self>) -> RPCHandler<Arc<Self>> {
+        let mut rpc_handler = RPCHandler::new(self.clone());
+        register_rpc_methods(&mut rpc_handler);
+        if self.api_metrics.load(Ordering::SeqCst) {
+            rpc_handler.enable_metrics();
+        }
+
+        rpc_handler
+    }
+
+    // Enable RPC Server with requested authentication and bind address
+    #[cfg(feature = "api_server")]
+    pub async fn enable_rpc_server(self: &Arc<Self>, bind_address: String, config: Option<AuthConfig>) -> Result<(), Error> {
+        let mut lock = self.api_server.lock().await;
+        if lock.is_some() {
+            return Err(WalletError::RPCServerAlreadyRunning.into())
+        }
+        let rpc_handler = self.create_rpc_handler();
+        let rpc_server = WalletRpcServer::new(bind_address, rpc_handler, config).await?;
+        *lock = Some(APIServer::RPCServer(rpc_server));
+        Ok(())
+    }
+
+    // Enable XSWD Protocol
+    #[cfg(feature = "api_server")]
+    pub async fn enable_xswd(self: &Arc<Self>) -> Result<UnboundedReceiver<XSWDEvent>, Error> {
+        let receiver = {
+            let (sender, receiver) = unbounded_channel();
+            let mut channel = self.xswd_channel.write().await;
+            *channel = Some(sender);
+            receiver
+        };
+
+        let mut lock = self.api_server.lock().await;
+        if lock.is_some() {
+            return Err(WalletError::RPCServerAlreadyRunning.into())
+        }
+        let rpc_handler = self.create_rpc_handler();
+        let limits = {
+            let storage = self.storage.read().await;
+            storage.get_xswd_limits()?
+        };
+        let xswd = XSWD::new(rpc_handler)?;
+        xswd.get_handler().set_limits(limits).await;
+
+        *lock = Some(APIServer::XSWD(xswd));
+        Ok(receiver)
+    }
+
+    // Limits applied to the XSWD applications
+    pub async fn get_xswd_limits(&self) -> Result<XSWDLimitsSettings, Error> {
+        let storage = self.storage.read().await;
+        storage.get_xswd_limits()
+    }
+
+    // Set the limits of an application, or the default ones if no ID is set
+    // The limits of an application are removed if they are empty
+    #[cfg(feature = "api_server")]
+    pub async fn set_xswd_limits(&self, app_id: Option<String>, limits: XSWDLimits) -> Result<(), Error> {
+        let settings = {
+            let mut storage = self.storage.write().await;
+            let mut settings = storage.get_xswd_limits()?;
+            match app_id {
+                Some(id) if limits == XSWDLimits::default() => {
+                    settings.applications.remove(&id);
+                },
+                Some(id) => {
+                    settings.applications.insert(id, limits);
+                },
+                None => settings.default = limits
+            };
+            storage.set_xswd_limits(&settings)?;
+            settings
+        };
+
+        // Apply them directly if XSWD is running
+        let lock = self.api_server.lock().await;
+        if let Some(APIServer::XSWD(xswd)) = lock.as_ref() {
+            xswd.get_handler().set_limits(settings).await;
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "api_server")]
+    pub async fn stop_api_server(&self) -> Result<(), Error> {
+        let mut lock = self.api_server.lock().await;
+        let rpc_server = lock.take().ok_or(WalletError::RPCServerNotRunning)?;
+        rpc_server.stop().await;
+        Ok(())
+    }
+
+    #[cfg(feature = "api_server")]
+    pub fn get_api_server<'a>(&'a self) -> &Mutex<Option<APIServer<Arc<Self>>>> {
+        &self.api_server
+    }
+
+    // Verify if a password is valid or not
+    pub async fn is_valid_password(&self, password: String) -> Result<(), Error> {
+        let mut encrypted_storage = self.storage.write().await;
+        let storage = encrypted_storage.get_mutable_public_storage();
+        let salt = storage.get_password_salt()?;
+        let hashed_password = hash_password(password, &salt)?;
+        let cipher = Cipher::new(&hashed_password, None)?;
+        let encrypted_master_key = storage.get_encrypted_master_key()?;
+        let _ = cipher.decrypt_value(&encrypted_master_key).context("Invalid password provided")?;
+        Ok(())
+    }
+
+    // change the current password wallet to a new one
+    pub async fn set_password(&self, old_password: String, password: String) -> Result<(), Error> {
+        let mut encrypted_storage = self.storage.write().await;
+        let storage = encrypted_storage.get_mutable_public_storage();
+        let (master_key, storage_salt) = {
+            // retrieve old salt to build key from current password
+            let salt = storage.get_password_salt()?;
+            let hashed_password = hash_password(old_password, &salt)?;
+
+            let encrypted_master_key = storage.get_encrypted_master_key()?;
+            let encrypted_storage_salt = storage.get_encrypted_storage_salt()?;
+
+            // decrypt the encrypted master key using the provided password
+            let cipher = Cipher::new(&hashed_password, None)?;
+            let master_key = cipher.decrypt_value(&encrypted_master_key).context("Invalid password provided")?;
+            let storage_salt = cipher.decrypt_value(&encrypted_storage_salt)?;
+            (master_key, storage_salt)
+        };
+
+        // generate a new salt for password
+        let mut salt: [u8; SALT_SIZE] = [0; SALT_SIZE];
+        OsRng.fill_bytes(&mut salt);
+
+        // generate the password-based derivated key to encrypt the master key
+        let hashed_password = hash_password(password, &salt)?;
+        let cipher = Cipher::new(&hashed_password, None)?;
+
+        // encrypt the master key using the new password
+        let encrypted_key = cipher.encrypt_value(&master_key)?;
+
+        // encrypt the salt with the new password
+        let encrypted_storage_salt = cipher.encrypt_value(&storage_salt)?;
+
+        // save on disk
+        storage.set_password_salt(&salt)?;
+        storage.set_encrypted_master_key(&encrypted_key)?;
+        storage.set_encrypted_storage_salt(&encrypted_storage_salt)?;
+
+        Ok(())
+    }
+
+    // Check if the keys were wiped from memory
+    pub fn is_locked(&self) -> bool {
+        self.master_key.read().expect("master key lock is poisoned").is_none()
+    }
+
+    // Wipe the keys from memory, the password is required to use them again
+    // Syncing is stopped as the balances can't be decrypted without the keys
+    pub async fn lock(&self) -> Result<(), WalletError> {
+        trace!("lock wallet");
+        {
+            let handler = self.network_handler.lock().await;
+            if let Some(network_handler) = handler.as_ref() {
+                if network_handler.is_running().await {
+                    debug!("Stopping network handler until the wallet is unlocked");
+                    network_handler.stop().await?;
+                    self.resume_sync.store(true, Ordering::SeqCst);
+                }
+            }
+        }
+
+        if let Some(mut master_key) = self.master_key.write().expect("master key lock is poisoned").take() {
+            master_key.zeroize();
+        }
+
+        // The storage cipher could decrypt the private key, it is rebuilt from the password on unlock
+        self.storage.write().await.lock();
+
+        // Keys of the previous account are wiped once it's not used anymore
+        let mut account = self.account.write().expect("account lock is poisoned");
+        let locked = Arc::new(account.without_keys());
+        *account = locked;
+
+        info!("Wallet is now locked");
+        Ok(())
+    }
+
+    // Load the keys again after verifying the password
+    pub async fn unlock(&self, password: String) -> Result<(), Error> {
+        trace!("unlock wallet");
+        let private_key = {
+            let mut storage = self.storage.write().await;
+            let (master_key, salt) = Self::decrypt_master_key(storage.get_public_storage(), password)?;
+            storage.unlock(&master_key, salt)?;
+            storage.get_private_key()?
+        };
+
+        {
+            let mut master_key = self.master_key.write().expect("master key lock is poisoned");
+            let mut account = self.account.write().expect("account lock is poisoned");
+            let unlocked = Arc::new(Account::new(&private_key, account.index));
+            *account = unlocked;
+            *master_key = Some(private_key);
+        }
+        self.record_activity();
+
+        if self.resume_sync.swap(false, Ordering::SeqCst) {
+            let handler = self.network_handler.lock().await;
+            if let Some(network_handler) = handler.as_ref() {
+                debug!("Starting again network handler");
+                network_handler.start(true).await.context("Error while restarting network handler")?;
+            }
+        }
+
+        info!("Wallet is now unlocked");
+        Ok(())
+    }
+
+    // Lock the wallet when its keys are not used during this duration, None to disable it
+    pub async fn set_auto_lock(self: &Arc<Self>, duration: Option<Duration>) {
+        trace!("set auto lock {:?}", duration);
+        let mut auto_lock = self.auto_lock.lock().await;
+        if let Some((_, task)) = auto_lock.take() {
+            task.abort();
+        }
+
+        if let Some(duration) = duration {
+            self.record_activity();
+            // The task must not keep the wallet alive
+            let zelf = Arc::downgrade(self);
+            let task = spawn_task("wallet-auto-lock", async move {
+                let mut interval = tokio::time::interval(Duration::from_secs(AUTO_LOCK_CHECK_INTERVAL_SECS));
+                loop {
+                    interval.tick().await;
+                    let wallet = match zelf.upgrade() {
+                        Some(wallet) => wallet,
+                        None => break
+                    };
+
+                    let inactivity = get_current_time_in_seconds().saturating_sub(wallet.last_activity.load(Ordering::SeqCst));
+                    if !wallet.is_locked() && inactivity >= duration.as_secs() {
+                        info!("Locking wallet after {} seconds of inactivity", inactivity);
+                        if let Err(e) = wallet.lock().await {
+                            error!("Error while locking wallet: {}", e);
+                        }
+                    }
+                }
+            });
+            *auto_lock = Some((duration, task));
+        }
+    }
+
+    // Get the inactivity duration before locking the wallet
+    pub async fn get_auto_lock(&self) -> Option<Duration> {
+        self.auto_lock.lock().await.as_ref().map(|(duration, _)| *duration)
+    }
+
+    // The keys were used, delay the auto lock
+    fn record_activity(&self) {
+        self.last_activity.store(get_current_time_in_seconds(), Ordering::SeqCst);
+    }
+
+    // Call a function with the master key, the wallet must be unlocked
+    fn with_master_key<T>(&self, f: impl FnOnce(&PrivateKey) -> T) -> Result<T, WalletError> {
+        let master_key = self.master_key.read().expect("master key lock is poisoned");
+        let master_key = master_key.as_ref().ok_or(WalletError::WalletLocked)?;
+        self.record_activity();
+        Ok(f(master_key))
+    }
+
+    // Wallet has to be under a Arc to be shared to the spawn_blocking function
+    pub async fn decrypt_ciphertext(self: Arc<Self>, ciphertext: Ciphertext) -> Result<u64, WalletError> {
+        trace!("decrypt ciphertext");
+        let account = self.get_account();
+        tokio::task::spawn_blocking(move || {
+            let view = ECDLPTablesFileView::<PRECOMPUTED_TABLES_L1>::from_bytes(self.precomputed_tables.get());
+            account.get_keypair()?.get_private_key()
+                .decrypt(&view, &ciphertext)
+                .ok_or(WalletError::CiphertextDecode)
+        }).await.context("Error while decrypting ciphertext")?
+    }
+
+    // Decrypt several ciphertexts at once, they are decoded in parallel
+    // Amounts are returned in the same order as the ciphertexts
+    pub async fn decrypt_ciphertexts(self: Arc<Self>, ciphertexts: Vec<Ciphertext>) -> Result<Vec<u64>, WalletError> {
+        trace!("decrypt {} ciphertexts", ciphertexts.len());
+        if ciphertexts.is_empty() {
+            return Ok(Vec::new())
+        }
+
+        let account = self.get_account();
+        tokio::task::spawn_blocking(move || {
+            let view = ECDLPTablesFileView::<PRECOMPUTED_TABLES_L1>::from_bytes(self.precomputed_tables.get());
+            account.get_keypair()?.get_private_key()
+                .decrypt_batch(&view, &ciphertexts)
+                .into_iter()
+                .map(|amount| amount.ok_or(WalletError::CiphertextDecode))
+                .collect()
+        }).await.context("Error while decrypting ciphertexts")?
+    }
+
+    // Decrypt the extra data from a transfer
+    pub fn decrypt_extra_data(&self, cipher: UnknownExtraDataFormat, handle: &DecryptHandle, role: Role) -> Result<DataElement, WalletError> {
+        trace!("decrypt extra data");
+        let account = self.get_account();
+        cipher.decrypt(account.get_keypair()?.get_private_key(), handle, role).map_err(|_| WalletError::CiphertextDecode)
     }
 
     // Create a transaction with the given transaction type and fee
@@ -612,6 +1563,209 @@ impl Wallet {
     // You must handle "apply changes" to the storage
     pub async fn create_transaction_with_storage(&self, storage: &EncryptedStorage, transaction_type: TransactionTypeBuilder, fee: FeeBuilder) -> Result<(TransactionBuilderState, Transaction), WalletError> {
         trace!("create transaction with storage");
+        let state = self.create_transaction_builder_state(storage, &transaction_type, &fee).await?;
+        self.create_transaction_with_state(storage, state, transaction_type, fee).await
+    }
+
+    // Build the transaction on top of the given state
+    async fn create_transaction_with_state(&self, storage: &EncryptedStorage, mut state: TransactionBuilderState, transaction_type: TransactionTypeBuilder, fee: FeeBuilder) -> Result<(TransactionBuilderState, Transaction), WalletError> {
+        // Create the transaction builder
+        let account = self.get_account();
+        let version = get_tx_version(&transaction_type, false);
+        let used_assets = transaction_type.used_assets();
+        let builder = TransactionBuilder::new(version, account.public_key.clone(), transaction_type.clone(), fee)
+            .with_authenticated_extra_data(self.is_authenticated_extra_data());
+        let estimated_fee = self.verify_funds(storage, &builder, &mut state, used_assets.clone())?;
+        let costs = used_assets.iter()
+            .map(|asset| (asset.clone(), builder.get_transaction_cost(estimated_fee, asset)))
+            .collect();
+
+        // Build the final transaction
+        let unsigned = builder.build_unsigned(&mut state, account.get_keypair()?)
+            .map_err(|e| WalletError::Any(e.into()))?;
+        let transaction = self.sign_transaction(&account, unsigned).await?;
+
+        let tx_hash = transaction.hash();
+        debug!("Transaction created: {} with nonce {} and reference {}", tx_hash, transaction.get_nonce(), transaction.get_reference());
+
+        // Tracked as pending once the changes are applied
+        let entry = pending::entry_from_builder(transaction_type, &tx_hash, &account.public_key, transaction.get_fee(), transaction.get_nonce());
+        let topoheight = transaction.get_reference().topoheight;
+        state.set_pending_transaction(PendingTransaction::new(transaction.clone(), entry, costs, topoheight));
+        state.set_tx_hash_built(tx_hash);
+
+        Ok((state, transaction))
+    }
+
+    // Create a transaction for a wallet having a multisig configured
+    // It must be signed by `threshold` participants before being finalized
+    // No changes are applied to the storage as the transaction may never be finalized
+    pub async fn create_unsigned_transaction(&self, transaction_type: TransactionTypeBuilder, fee: FeeBuilder, threshold: u8) -> Result<UnsignedTransaction, WalletError> {
+        trace!("create unsigned transaction");
+        let storage = self.storage.read().await;
+        let mut state = self.create_transaction_builder_state(&storage, &transaction_type, &fee).await?;
+
+        let account = self.get_account();
+        let version = get_tx_version(&transaction_type, true);
+        let used_assets = transaction_type.used_assets();
+        let builder = TransactionBuilder::new(version, account.public_key.clone(), transaction_type, fee)
+            .with_authenticated_extra_data(self.is_authenticated_extra_data())
+            .with_multisig_threshold(threshold);
+        self.verify_funds(&storage, &builder, &mut state, used_assets)?;
+
+        let unsigned = builder.build_unsigned(&mut state, account.get_keypair()?)
+            .map_err(|e| WalletError::Any(e.into()))?;
+        self.record_activity();
+
+        debug!("Unsigned transaction created with nonce {} and multisig hash {}", unsigned.get_nonce(), unsigned.get_hash_for_multisig());
+        Ok(unsigned)
+    }
+
+    // Sign the multisig hash of an unsigned transaction as the participant `id`
+    pub fn sign_multisig_hash(&self, hash: &Hash, id: u8) -> Result<SignatureId, WalletError> {
+        let account = self.get_account();
+        let signature = account.get_keypair()?.sign(hash.as_bytes());
+        self.record_activity();
+
+        Ok(SignatureId {
+            id,
+            signature
+        })
+    }
+
+    // Add the signatures of the multisig participants and sign the transaction
+    pub async fn finalize_unsigned_transaction(&self, mut unsigned: UnsignedTransaction, signatures: Vec<SignatureId>) -> Result<Transaction, WalletError> {
+        trace!("finalize unsigned transaction");
+        let account = self.get_account();
+        if *unsigned.get_source() != account.public_key {
+            return Err(WalletError::InvalidUnsignedTransactionSource);
+        }
+
+        for signature in signatures {
+            unsigned.add_multisig_signature(signature)
+                .map_err(|e| WalletError::Any(e.into()))?;
+        }
+
+        let transaction = self.sign_transaction(&account, unsigned).await?;
+        debug!("Unsigned transaction finalized: {}", transaction.hash());
+        Ok(transaction)
+    }
+
+    // Set the signer used for the transactions of the account selected
+    // It is removed when another account is selected
+    pub fn set_signer(&self, signer: Option<Arc<dyn Signer>>) -> Result<(), WalletError> {
+        if let Some(signer) = signer.as_ref() {
+            if *signer.get_public_key() != self.get_account().public_key {
+                return Err(WalletError::InvalidSignerPublicKey);
+            }
+        }
+
+        *self.signer.write().expect("signer lock is poisoned") = signer;
+        Ok(())
+    }
+
+    // Check if the transactions are signed by an external signer
+    pub fn has_external_signer(&self) -> bool {
+        self.signer.read().expect("signer lock is poisoned").is_some()
+    }
+
+    // Sign the transaction using the external signer if set, otherwise the account key
+    async fn sign_transaction(&self, account: &Account, unsigned: UnsignedTransaction) -> Result<Transaction, WalletError> {
+        self.record_activity();
+        let signer = self.signer.read().expect("signer lock is poisoned").clone();
+        match signer {
+            Some(signer) => {
+                debug!("Waiting for the signature of the external signer");
+                let signature = signer.sign_transaction(&unsigned.to_bytes()).await?;
+                Ok(unsigned.finalize_with_signature(signature))
+            },
+            None => Ok(unsigned.finalize(account.get_keypair()?))
+        }
+    }
+
+    // Prepare a transaction for an account whose keys are kept on an air-gapped wallet
+    // Only public data from the daemon is used, so it can be done for any address
+    pub async fn build_offline_transaction_request(&self, address: Address, transaction_type: TransactionTypeBuilder, fee: FeeBuilder) -> Result<OfflineTransactionRequest, WalletError> {
+        trace!("build offline transaction request for {}", address);
+        let mut state = EstimateFeesState::new();
+        self.add_registered_keys_for_fees_estimation(&mut state, &fee, &transaction_type).await?;
+
+        let network_handler = self.network_handler.lock().await;
+        let api = match network_handler.as_ref() {
+            Some(network_handler) if network_handler.is_running().await => network_handler.get_api(),
+            _ => return Err(WalletError::NotOnlineMode)
+        };
+
+        let info = api.get_info().await?;
+        let nonce = api.get_nonce(&address).await
+            // Account has no transactions yet
+            .map(|v| v.version.get_nonce()).unwrap_or(0);
+
+        let mut balances = HashMap::new();
+        for asset in transaction_type.used_assets() {
+            let result = api.get_balance(&address, &asset).await
+                .map_err(|_| WalletError::BalanceNotFound(asset.clone()))?;
+            balances.insert(asset, result.version.take_balance());
+        }
+
+        let mainnet = self.network.is_mainnet();
+        Ok(OfflineTransactionRequest {
+            source: address,
+            tx_type: transaction_type,
+            fee,
+            nonce,
+            reference: Reference {
+                topoheight: info.topoheight,
+                hash: info.top_block_hash
+            },
+            balances,
+            registered_keys: state.get_registered_keys()
+                .iter()
+                .map(|key| key.as_address(mainnet))
+                .collect()
+        })
+    }
+
+    // Build and sign a transaction prepared by an online wallet
+    // Balances are decrypted with the key of the account selected, the storage is not used
+    pub async fn sign_offline_transaction(self: &Arc<Self>, request: OfflineTransactionRequest) -> Result<Transaction, WalletError> {
+        trace!("sign offline transaction");
+        let account = self.get_account();
+        if *request.source.get_public_key() != account.public_key || request.source.is_mainnet() != self.network.is_mainnet() {
+            return Err(WalletError::InvalidUnsignedTransactionSource);
+        }
+
+        let mut state = TransactionBuilderState::new(
+            self.network.is_mainnet(),
+            request.reference,
+            request.nonce
+        );
+
+        for (asset, mut ciphertext) in request.balances {
+            let decompressed = ciphertext.decompressed()
+                .map_err(|e| WalletError::Any(e.into()))?
+                .clone();
+            let amount = Arc::clone(self).decrypt_ciphertext(decompressed).await?;
+            state.add_balance(asset, Balance::new(amount, ciphertext));
+        }
+
+        for address in request.registered_keys {
+            state.add_registered_key(address.to_public_key());
+        }
+
+        let version = get_tx_version(&request.tx_type, false);
+        let builder = TransactionBuilder::new(version, account.public_key.clone(), request.tx_type, request.fee)
+            .with_authenticated_extra_data(self.is_authenticated_extra_data());
+        let unsigned = builder.build_unsigned(&mut state, account.get_keypair()?)
+            .map_err(|e| WalletError::Any(e.into()))?;
+        let transaction = self.sign_transaction(&account, unsigned).await?;
+
+        debug!("Offline transaction signed: {} with nonce {}", transaction.hash(), transaction.get_nonce());
+        Ok(transaction)
+    }
+
+    // Build the state used by the transaction builder from the wallet storage
+    async fn create_transaction_builder_state(&self, storage: &EncryptedStorage, transaction_type: &TransactionTypeBuilder, fee: &FeeBuilder) -> Result<TransactionBuilderState, WalletError> {
         let nonce = storage.get_unconfirmed_nonce();
 
         // Build the state for the builder
@@ -644,20 +1798,36 @@ impl Wallet {
             state.add_balance(asset, balance);
         }
 
-        self.add_registered_keys_for_fees_estimation(state.as_mut(), &fee, &transaction_type).await?;
+        self.add_registered_keys_for_fees_estimation(state.as_mut(), fee, transaction_type).await?;
 
-        // Create the transaction builder
-        let builder = TransactionBuilder::new(0, self.public_key.clone(), transaction_type, fee);
+        Ok(state)
+    }
 
-        // Build the final transaction
-        let transaction = builder.build(&mut state, &self.keypair)
+    // Verify the balances of the state cover the amounts spent for each asset and the fee
+    // so the missing asset is reported before generating the proofs
+    // Returns the fee estimated for the transaction
+    fn verify_funds(&self, storage: &EncryptedStorage, builder: &TransactionBuilder, state: &mut TransactionBuilderState, used_assets: HashSet<Hash>) -> Result<u64, WalletError> {
+        let fee = builder.estimate_fees(state)
             .map_err(|e| WalletError::Any(e.into()))?;
 
-        let tx_hash = transaction.hash();
-        debug!("Transaction created: {} with nonce {} and reference {}", tx_hash, transaction.get_nonce(), transaction.get_reference());
-        state.set_tx_hash_built(tx_hash);
+        for asset in used_assets {
+            let balance = state.get_account_balance(&asset)?;
+            let cost = builder.get_transaction_cost(fee, &asset);
+            if balance >= cost {
+                continue;
+            }
+
+            // Amounts sent are covered but not the fee
+            if asset == XELIS_ASSET && balance >= cost - fee {
+                return Err(WalletError::NotEnoughFundsForFee(fee, balance - (cost - fee)))
+            }
 
-        Ok((state, transaction))
+            // Only used to display the amounts
+            let decimals = storage.get_asset_decimals(&asset).unwrap_or(COIN_DECIMALS);
+            return Err(WalletError::NotEnoughFunds(balance, cost, decimals, asset))
+        }
+
+        Ok(fee)
     }
 
     // submit a transaction to the network through the connection to daemon
@@ -674,6 +1844,87 @@ impl Wallet {
         }
     }
 
+    // Build a transaction and submit it, its changes are applied to the storage once accepted by the daemon
+    // If it is rejected while the nonce was desynchronized, the nonce is repaired and the transaction is built again once
+    pub async fn create_and_submit_transaction_with_storage(&self, storage: &mut EncryptedStorage, transaction_type: TransactionTypeBuilder, fee: FeeBuilder) -> Result<Transaction, WalletError> {
+        trace!("create and submit transaction");
+        let (mut state, mut transaction) = self.create_transaction_with_storage(storage, transaction_type.clone(), fee.clone()).await?;
+        if let Err(e) = self.submit_transaction(&transaction).await {
+            warn!("Clearing Tx cache because of broadcasting error: {}", e);
+            storage.clear_tx_cache();
+
+            // Only a repaired nonce can make the same transaction valid
+            match self.resync_nonce_with_storage(storage).await {
+                Ok(Some(nonce)) => info!("Building the transaction again with the repaired nonce {}", nonce),
+                Ok(None) => return Err(e),
+                Err(resync_error) => {
+                    debug!("Error while checking the nonce after a rejected transaction: {}", resync_error);
+                    return Err(e)
+                }
+            };
+
+            (state, transaction) = self.create_transaction_with_storage(storage, transaction_type, fee).await?;
+            if let Err(e) = self.submit_transaction(&transaction).await {
+                storage.clear_tx_cache();
+                return Err(e)
+            }
+        }
+
+        state.apply_changes(storage).await?;
+        Ok(transaction)
+    }
+
+    // Compare the nonce used to build transactions with the one expected by the daemon
+    // They diverge when a transaction was dropped from the mempool or was sent by another instance of the wallet
+    // Returns the new nonce if it was repaired
+    pub async fn resync_nonce(&self) -> Result<Option<u64>, WalletError> {
+        let mut storage = self.storage.write().await;
+        self.resync_nonce_with_storage(&mut storage).await
+    }
+
+    pub async fn resync_nonce_with_storage(&self, storage: &mut EncryptedStorage) -> Result<Option<u64>, WalletError> {
+        trace!("resync nonce");
+        let network_handler = self.network_handler.lock().await;
+        let api = match network_handler.as_ref() {
+            Some(network_handler) if network_handler.is_running().await => network_handler.get_api(),
+            _ => return Err(WalletError::NotOnlineMode)
+        };
+
+        let address = self.get_address();
+        // User has no transactions/balances yet, its nonce is 0
+        let nonce = api.get_nonce(&address).await
+            .map(|v| v.version.get_nonce())
+            .unwrap_or(0);
+        // The daemon has no cache for us if none of our transactions are in its mempool
+        let pending = api.get_mempool_cache(&address).await.ok();
+
+        let local = storage.get_unconfirmed_nonce();
+        let expected = pending.as_ref().map(|cache| cache.get_max() + 1).unwrap_or(nonce);
+        if local == expected {
+            trace!("nonce {} is synced with the daemon", local);
+            return Ok(None)
+        }
+
+        // Our unconfirmed balances can't be rebuilt from the transactions in the mempool
+        if pending.is_some() {
+            return Err(WalletError::NonceDesynchronized(local, expected))
+        }
+
+        warn!("Nonce {} is desynchronized from the daemon, repairing it to {}", local, nonce);
+        storage.set_nonce(nonce)?;
+        // Unconfirmed balances were built on top of the dropped transactions
+        storage.delete_unconfirmed_balances().await?;
+        // The daemon doesn't know the transactions pending with the next nonces
+        for (hash, pending) in storage.get_pending_transactions()? {
+            if pending.get_transaction().get_nonce() >= nonce {
+                warn!("Pending transaction {} was dropped by the daemon", hash);
+                storage.delete_pending_transaction(&hash)?;
+            }
+        }
+
+        Ok(Some(nonce))
+    }
+
     // Search if possible all registered keys for the transaction type
     pub async fn add_registered_keys_for_fees_estimation(&self, state: &mut EstimateFeesState, fee: &FeeBuilder, transaction_type: &TransactionTypeBuilder) -> Result<(), WalletError> {
         trace!("add registered keys for fees estimation");
@@ -717,7 +1968,9 @@ impl Wallet {
 
         self.add_registered_keys_for_fees_estimation(&mut state, &FeeBuilder::default(), &tx_type).await?;
 
-        let builder = TransactionBuilder::new(0, self.public_key.clone(), tx_type, FeeBuilder::default());
+        let version = get_tx_version(&tx_type, false);
+        let builder = TransactionBuilder::new(version, self.get_public_key(), tx_type, FeeBuilder::default())
+            .with_authenticated_extra_data(self.is_authenticated_extra_data());
         let estimated_fees = builder.estimate_fees(&mut state)
             .map_err(|e| WalletError::Any(e.into()))?;
 
@@ -732,8 +1985,14 @@ impl Wallet {
             return Err(WalletError::AlreadyOnlineMode)
         }
 
+        // balances can't be decrypted during the sync
+        if self.is_locked() {
+            return Err(WalletError::WalletLocked)
+        }
+
         // create the network handler
-        let network_handler = NetworkHandler::new(Arc::clone(&self), daemon_address).await?;
+        let tls = self.get_daemon_tls_config();
+        let network_handler = NetworkHandler::new(Arc::clone(&self), daemon_address, &tls).await?;
         // start the task
         network_handler.start(auto_reconnect).await?;
         *self.network_handler.lock().await = Some(network_handler);
@@ -741,6 +2000,16 @@ impl Wallet {
         Ok(())
     }
 
+    // Set the CA bundle or the pinned certificate used to verify the daemon
+    // It is applied on the next connection
+    pub fn set_daemon_tls_config(&self, tls: TlsConfig) {
+        *self.daemon_tls.write().expect("daemon tls lock is poisoned") = tls;
+    }
+
+    pub fn get_daemon_tls_config(&self) -> TlsConfig {
+        self.daemon_tls.read().expect("daemon tls lock is poisoned").clone()
+    }
+
     // set the wallet in online mode using a shared daemon API
     // this allows to share the same connection/Daemon API across several wallets to save resources
     pub async fn set_online_mode_with_api(self: &Arc<Self>, daemon_api: Arc<DaemonAPI>, auto_reconnect: bool) -> Result<(), WalletError> {
@@ -750,6 +2019,11 @@ impl Wallet {
             return Err(WalletError::AlreadyOnlineMode)
         }
 
+        // balances can't be decrypted during the sync
+        if self.is_locked() {
+            return Err(WalletError::WalletLocked)
+        }
+
         // create the network handler
         let network_handler = NetworkHandler::with_api(Arc::clone(&self), daemon_api).await?;
         // start the task
@@ -795,6 +2069,8 @@ impl Wallet {
                 debug!("set synced topoheight to {}", topoheight);
                 storage.set_synced_topoheight(topoheight)?;
                 storage.delete_top_block_hash()?;
+                // a previous sync must not be resumed
+                storage.delete_sync_checkpoint()?;
                 // balances will be re-fetched from daemon
                 storage.delete_balances().await?;
                 storage.delete_assets().await?;
@@ -843,31 +2119,618 @@ impl Wallet {
     }
 
     // Create a signature of the given data
-    pub fn sign_data(&self, data: &[u8]) -> Signature {
-        self.keypair.sign(data)
+    pub fn sign_data(&self, data: &[u8]) -> Result<Signature, WalletError> {
+        let account = self.get_account();
+        let signature = account.get_keypair()?.sign(data);
+        self.record_activity();
+
+        Ok(signature)
     }
 
-    // Get the public key of the wallet
-    pub fn get_public_key(&self) -> &PublicKey {
-        &self.public_key
+    // Sign a message to prove the ownership of the address of the account selected
+    // It is domain separated so it can't be used as a transaction signature
+    pub fn sign_message(&self, message: &str) -> Result<SignedMessage, WalletError> {
+        let account = self.get_account();
+        let signature = account.get_keypair()?.sign_message(message.as_bytes());
+        self.record_activity();
+
+        Ok(SignedMessage {
+            address: account.public_key.as_address(self.get_network().is_mainnet()),
+            signature
+        })
+    }
+
+    // Prove that the account selected has at least minimum of the asset at a topoheight
+    // The daemon top topoheight is used if none is provided
+    pub async fn create_balance_proof(self: &Arc<Self>, asset: Hash, minimum: u64, topoheight: Option<u64>) -> Result<BalanceProof, WalletError> {
+        trace!("create balance proof for {} with minimum {}", asset, minimum);
+        let address = self.get_address();
+        let (topoheight, mut balance) = {
+            let network_handler = self.network_handler.lock().await;
+            let api = match network_handler.as_ref() {
+                Some(network_handler) if network_handler.is_running().await => network_handler.get_api(),
+                _ => return Err(WalletError::NotOnlineMode)
+            };
+
+            match topoheight {
+                Some(topoheight) => {
+                    let version = api.get_balance_at_topoheight(&address, &asset, topoheight).await
+                        .map_err(|_| WalletError::BalanceNotFound(asset.clone()))?;
+                    (topoheight, version.take_balance())
+                },
+                None => {
+                    let result = api.get_balance(&address, &asset).await
+                        .map_err(|_| WalletError::BalanceNotFound(asset.clone()))?;
+                    (result.topoheight, result.version.take_balance())
+                }
+            }
+        };
+
+        let ciphertext = balance.decompressed()
+            .context("Invalid balance ciphertext")?
+            .clone();
+        let amount = Arc::clone(self).decrypt_ciphertext(ciphertext.clone()).await?;
+
+        let account = self.get_account();
+        let proof = BalanceProof::new(account.get_keypair()?, asset, topoheight, &ciphertext, amount, minimum)?;
+        self.record_activity();
+
+        Ok(proof)
+    }
+
+    // Verify a balance proof made by the owner of an address
+    // The balance ciphertext at the proof topoheight is fetched from the daemon
+    pub async fn verify_balance_proof(&self, address: &Address, proof: &BalanceProof) -> Result<bool, WalletError> {
+        trace!("verify balance proof of {}", address);
+        let network_handler = self.network_handler.lock().await;
+        let api = match network_handler.as_ref() {
+            Some(network_handler) if network_handler.is_running().await => network_handler.get_api(),
+            _ => return Err(WalletError::NotOnlineMode)
+        };
+
+        let version = api.get_balance_at_topoheight(address, proof.get_asset(), proof.get_topoheight()).await
+            .map_err(|_| WalletError::BalanceNotFound(proof.get_asset().clone()))?;
+        let mut balance = version.take_balance();
+        let ciphertext = balance.compressed();
+
+        Ok(verify_balance_proof(proof, address.get_public_key(), ciphertext).is_ok())
+    }
+
+    // Request a payment to the address of the account selected
+    pub fn create_payment_request(&self, amount: Option<u64>, asset: Option<Hash>, message: Option<String>) -> Result<PaymentRequest, WalletError> {
+        Ok(PaymentRequest::new(self.get_address(), amount, asset, message)?)
+    }
+
+    // Parse a payment request URI, it must be for the wallet network
+    pub fn parse_payment_request(&self, uri: &str) -> Result<PaymentRequest, WalletError> {
+        let request = PaymentRequest::from_uri(uri)?;
+        if request.get_address().is_mainnet() != self.network.is_mainnet() {
+            return Err(WalletError::InvalidPaymentRequest("address is not on the wallet network"))
+        }
+
+        Ok(request)
+    }
+
+    // Build the transfer paying the request
+    // The amount is only used if the request doesn't set it
+    pub fn payment_request_to_transfer(&self, request: &PaymentRequest, amount: Option<u64>) -> Result<TransactionTypeBuilder, WalletError> {
+        let amount = match (request.get_amount(), amount) {
+            (Some(requested), Some(amount)) if requested != amount => return Err(WalletError::InvalidPaymentRequest("amount is already set by the request")),
+            (Some(amount), _) | (None, Some(amount)) => amount,
+            (None, None) => return Err(WalletError::InvalidPaymentRequest("no amount set"))
+        };
+
+        if amount == 0 {
+            return Err(WalletError::InvalidPaymentRequest("amount can't be zero"))
+        }
+
+        if *request.get_address().get_public_key() == self.get_public_key() {
+            return Err(WalletError::InvalidPaymentRequest("destination can't be the wallet itself"))
+        }
+
+        // Data of an integrated address is extracted by the builder
+        let extra_data = request.get_message()
+            .map(|message| DataElement::Value(DataValue::String(message.clone())));
+
+        Ok(TransactionTypeBuilder::Transfers(vec![TransferBuilder {
+            asset: request.get_asset().cloned().unwrap_or(XELIS_ASSET),
+            amount,
+            destination: request.get_address().clone(),
+            extra_data,
+            lock: None
+        }]))
+    }
+
+    // Get the metadata of an asset, it is fetched from the daemon and saved if not known yet
+    pub async fn get_asset_data(&self, asset: &Hash) -> Result<AssetData, WalletError> {
+        trace!("get asset data for {}", asset);
+        {
+            let storage = self.storage.read().await;
+            if storage.has_asset_data(asset)? {
+                return Ok(storage.get_asset_data(asset)?)
+            }
+        }
+
+        let data = {
+            let network_handler = self.network_handler.lock().await;
+            match network_handler.as_ref() {
+                Some(network_handler) if network_handler.is_running().await => network_handler.get_api().get_asset(asset).await
+                    .map_err(|_| WalletError::AssetNotFound(asset.clone()))?,
+                // XELIS is known even before the first sync
+                _ if *asset == XELIS_ASSET => return Ok(AssetData::new(0, COIN_DECIMALS)),
+                _ => return Err(WalletError::NotOnlineMode)
+            }
+        };
+
+        let mut storage = self.storage.write().await;
+        storage.set_asset_data(asset, &data)?;
+        Ok(data)
+    }
+
+    // Decimals of an asset to format its amounts
+    // The storage lock must not be held by the caller
+    pub async fn get_asset_decimals(&self, asset: &Hash) -> Result<u8, WalletError> {
+        {
+            let storage = self.storage.read().await;
+            if let Ok(decimals) = storage.get_asset_decimals(asset) {
+                return Ok(decimals)
+            }
+        }
+
+        Ok(self.get_asset_data(asset).await?.get_decimals())
+    }
+
+    // Get the metadata of all the assets known by the wallet
+    pub async fn get_assets_data(&self) -> Result<Vec<AssetWithData>, WalletError> {
+        let storage = self.storage.read().await;
+        Ok(storage.get_assets_data()?)
+    }
+
+    // Get the account selected
+    pub fn get_account(&self) -> Arc<Account> {
+        Arc::clone(&self.account.read().expect("account lock is poisoned"))
+    }
+
+    // Get the public key of the account selected
+    pub fn get_public_key(&self) -> PublicKey {
+        self.get_account().public_key.clone()
     }
 
-    // Get the address of the wallet using its network used
+    // Get the address of the account selected using its network used
     pub fn get_address(&self) -> Address {
-        self.keypair.get_public_key().to_address(self.get_network().is_mainnet())
+        self.get_public_key().as_address(self.get_network().is_mainnet())
     }
 
     // Get the address with integrated data and using its network used
     pub fn get_address_with(&self, data: DataElement) -> Address {
-        self.keypair.get_public_key().to_address_with(self.get_network().is_mainnet(), data)
+        Address::new(self.get_network().is_mainnet(), AddressType::Data(data), self.get_public_key())
     }
 
     // Returns the seed using the language index provided
-    pub fn get_seed(&self, language_index: usize) -> Result<String, Error> {
-        let words = mnemonics::key_to_words(self.keypair.get_private_key(), language_index)?;
+    // All the accounts can be recovered from it
+    // If a passphrase was used, it is also required to recover the wallet
+    pub async fn get_seed(&self, language_index: usize) -> Result<String, Error> {
+        let seed_key = {
+            let storage = self.storage.read().await;
+            storage.get_seed_key()?
+        };
+
+        let words = match seed_key {
+            Some(mut seed_key) => {
+                // Keys must be unlocked to display the seed
+                let words = self.with_master_key(|_| mnemonics::key_to_words(&seed_key, language_index))?;
+                seed_key.zeroize();
+                words?
+            },
+            None => self.with_master_key(|master_key| mnemonics::key_to_words(master_key, language_index))??
+        };
         Ok(words.join(" "))
     }
 
+    // Check if the private key is derived from the seed with a passphrase
+    pub async fn has_seed_passphrase(&self) -> Result<bool, WalletError> {
+        let storage = self.storage.read().await;
+        Ok(storage.get_seed_key()?.is_some())
+    }
+
+    // Get the address of the account at this index, even if not created yet
+    pub fn get_account_address(&self, index: u32) -> Result<Address, WalletError> {
+        let account = self.with_master_key(|master_key| Account::new(master_key, index))?;
+        Ok(account.public_key.as_address(self.get_network().is_mainnet()))
+    }
+
+    // Check if the accounts derived from the seed must be searched on chain
+    pub async fn is_accounts_recovery_pending(&self) -> Result<bool, WalletError> {
+        let storage = self.storage.read().await;
+        Ok(storage.is_accounts_recovery_pending()?)
+    }
+
+    // Save the accounts found on chain by the recovery scan
+    // Accounts already created are kept
+    pub async fn set_recovered_accounts(&self, count: u32) -> Result<(), WalletError> {
+        trace!("set recovered accounts {}", count);
+        let mut storage = self.storage.write().await;
+        if count > storage.get_accounts_count()? {
+            storage.set_accounts_count(count)?;
+        }
+        storage.set_accounts_recovery_pending(false)?;
+
+        Ok(())
+    }
+
+    // Get the account at this index with its address
+    fn get_account_entry(&self, account: &Account) -> AccountEntry {
+        AccountEntry {
+            index: account.index,
+            address: account.public_key.as_address(self.get_network().is_mainnet())
+        }
+    }
+
+    // List all the accounts of the wallet
+    pub async fn get_accounts(&self) -> Result<Vec<AccountEntry>, WalletError> {
+        let count = {
+            let storage = self.storage.read().await;
+            storage.get_accounts_count()?
+        };
+
+        self.with_master_key(|master_key| {
+            (0..count).map(|index| self.get_account_entry(&Account::new(master_key, index))).collect()
+        })
+    }
+
+    // Get the account selected with its address
+    pub fn get_selected_account(&self) -> AccountEntry {
+        self.get_account_entry(&self.get_account())
+    }
+
+    // Create the next account derived from the seed
+    pub async fn add_account(&self) -> Result<AccountEntry, WalletError> {
+        trace!("add account");
+        let mut storage = self.storage.write().await;
+        let index = storage.get_accounts_count()?;
+        let account = self.with_master_key(|master_key| Account::new(master_key, index))?;
+        storage.set_accounts_count(index + 1)?;
+
+        Ok(self.get_account_entry(&account))
+    }
+
+    // Select the account to use
+    // If the wallet is online, the network handler is restarted to sync the selected account
+    pub async fn select_account(&self, index: u32, auto_reconnect: bool) -> Result<(), WalletError> {
+        trace!("select account {}", index);
+        if self.get_account().index == index {
+            return Ok(())
+        }
+
+        let entry = {
+            let mut storage = self.storage.write().await;
+            let handler = self.network_handler.lock().await;
+            if index >= storage.get_accounts_count()? {
+                return Err(WalletError::AccountNotFound(index))
+            }
+            let account = self.with_master_key(|master_key| Account::new(master_key, index))?;
+
+            let network_handler = match handler.as_ref() {
+                Some(network_handler) if network_handler.is_running().await => {
+                    debug!("Stopping network handler!");
+                    network_handler.stop().await?;
+                    Some(network_handler)
+                },
+                _ => None
+            };
+
+            storage.set_account(index)?;
+            storage.set_selected_account(index)?;
+
+            let account = Arc::new(account);
+            let entry = self.get_account_entry(&account);
+            *self.account.write().expect("account lock is poisoned") = account;
+            // The signer is linked to the previous account
+            if self.signer.write().expect("signer lock is poisoned").take().is_some() {
+                warn!("External signer removed, it must be set again for account #{}", index);
+            }
+
+            if let Some(network_handler) = network_handler {
+                debug!("Starting again network handler");
+                network_handler.start(auto_reconnect).await.context("Error while restarting network handler")?;
+            }
+
+            entry
+        };
+
+        self.propagate_event(Event::AccountSelected(entry)).await;
+        Ok(())
+    }
+
+    // Verify a contact before saving it in the address book
+    // A name can't be an address so both can be used as a destination
+    fn verify_contact(&self, entry: &mut ContactEntry) -> Result<(), WalletError> {
+        entry.name = entry.name.trim().to_owned();
+        if entry.name.is_empty() || entry.name.len() > MAX_CONTACT_NAME_SIZE || Address::from_string(&entry.name).is_ok() {
+            return Err(WalletError::InvalidContactName(MAX_CONTACT_NAME_SIZE))
+        }
+
+        if entry.contact.address.is_mainnet() != self.network.is_mainnet() {
+            return Err(WalletError::InvalidContactNetwork)
+        }
+
+        // An empty note is stored as no note
+        if entry.contact.note.as_ref().is_some_and(|note| note.is_empty()) {
+            entry.contact.note = None;
+        }
+
+        if let Some(note) = entry.contact.note.as_ref() {
+            if note.len() > MAX_CONTACT_NOTE_SIZE {
+                return Err(WalletError::ContactNoteTooLong(MAX_CONTACT_NOTE_SIZE, note.len()))
+            }
+        }
+
+        Ok(())
+    }
+
+    // Get all the contacts of the address book
+    pub async fn get_contacts(&self) -> Result<Vec<ContactEntry>, WalletError> {
+        let storage = self.storage.read().await;
+        Ok(storage.get_contacts()?)
+    }
+
+    pub async fn get_contact(&self, name: &str) -> Result<Contact, WalletError> {
+        let storage = self.storage.read().await;
+        if !storage.has_contact(name)? {
+            return Err(WalletError::ContactNotFound(name.to_owned()))
+        }
+
+        Ok(storage.get_contact(name)?)
+    }
+
+    // Save a new contact in the address book
+    pub async fn add_contact(&self, mut entry: ContactEntry) -> Result<(), WalletError> {
+        trace!("add contact {}", entry.name);
+        self.verify_contact(&mut entry)?;
+        let mut storage = self.storage.write().await;
+        if storage.has_contact(&entry.name)? {
+            return Err(WalletError::ContactAlreadyExists(entry.name))
+        }
+
+        storage.set_contact(&entry.name, &entry.contact)?;
+        Ok(())
+    }
+
+    // Replace the address, note and default asset of a contact
+    pub async fn update_contact(&self, mut entry: ContactEntry) -> Result<(), WalletError> {
+        trace!("update contact {}", entry.name);
+        self.verify_contact(&mut entry)?;
+        let mut storage = self.storage.write().await;
+        if !storage.has_contact(&entry.name)? {
+            return Err(WalletError::ContactNotFound(entry.name))
+        }
+
+        storage.set_contact(&entry.name, &entry.contact)?;
+        Ok(())
+    }
+
+    pub async fn remove_contact(&self, name: &str) -> Result<(), WalletError> {
+        trace!("remove contact {}", name);
+        let mut storage = self.storage.write().await;
+        if !storage.has_contact(name)? {
+            return Err(WalletError::ContactNotFound(name.to_owned()))
+        }
+
+        storage.delete_contact(name)?;
+        Ok(())
+    }
+
+    // Attach a label to a transaction of the history, it replaces the previous one
+    pub async fn set_transaction_label(&self, hash: &Hash, mut label: TransactionLabel) -> Result<(), WalletError> {
+        trace!("set transaction label for {}", hash);
+        label.label = label.label.trim().to_owned();
+        if label.label.is_empty() || label.label.len() > MAX_TRANSACTION_LABEL_SIZE {
+            return Err(WalletError::InvalidTransactionLabel(MAX_TRANSACTION_LABEL_SIZE))
+        }
+
+        // An empty category is stored as no category
+        label.category = label.category.map(|category| category.trim().to_owned()).filter(|category| !category.is_empty());
+        if label.category.as_ref().is_some_and(|category| category.len() > MAX_TRANSACTION_CATEGORY_SIZE) {
+            return Err(WalletError::InvalidTransactionCategory(MAX_TRANSACTION_CATEGORY_SIZE))
+        }
+
+        let mut storage = self.storage.write().await;
+        if !storage.has_transaction(hash)? {
+            return Err(WalletError::TransactionNotFound(hash.clone()))
+        }
+
+        storage.set_transaction_label(hash, &label)?;
+        Ok(())
+    }
+
+    pub async fn get_transaction_label(&self, hash: &Hash) -> Result<TransactionLabel, WalletError> {
+        let storage = self.storage.read().await;
+        if !storage.has_transaction_label(hash)? {
+            return Err(WalletError::TransactionLabelNotFound(hash.clone()))
+        }
+
+        Ok(storage.get_transaction_label(hash)?)
+    }
+
+    pub async fn remove_transaction_label(&self, hash: &Hash) -> Result<(), WalletError> {
+        trace!("remove transaction label for {}", hash);
+        let mut storage = self.storage.write().await;
+        if !storage.has_transaction_label(hash)? {
+            return Err(WalletError::TransactionLabelNotFound(hash.clone()))
+        }
+
+        storage.delete_transaction_label(hash)?;
+        Ok(())
+    }
+
+    // Search the transactions of the selected account with their labels
+    pub async fn search_transactions(&self, params: &SearchTransactionsParams) -> Result<Vec<LabeledTransactionEntry>, WalletError> {
+        trace!("search transactions");
+        let owner = self.get_public_key();
+        let mainnet = self.network.is_mainnet();
+        let storage = self.storage.read().await;
+        let transactions = storage.search_transactions(&owner, params)?
+            .into_iter()
+            .map(|(entry, label)| LabeledTransactionEntry {
+                transaction: entry.serializable(mainnet),
+                label
+            })
+            .collect();
+
+        Ok(transactions)
+    }
+
+    // Resolve a destination which is either an address or the name of a contact
+    // The default asset of the contact is returned if set
+    pub async fn resolve_destination(&self, destination: &str) -> Result<(Address, Option<Hash>), WalletError> {
+        let destination = destination.trim();
+        if let Ok(address) = Address::from_string(&destination.to_owned()) {
+            return Ok((address, None))
+        }
+
+        let contact = self.get_contact(destination).await?;
+        Ok((contact.address, contact.default_asset))
+    }
+
+    // Build the transfers of a batch payment
+    // Destinations can be contact names, the asset of a payment defaults to the contact one or XELIS
+    pub async fn build_batch_transfers(&self, payments: Vec<BatchPayment>) -> Result<Vec<TransferBuilder>, WalletError> {
+        trace!("build batch transfers");
+        let mut transfers = Vec::with_capacity(payments.len());
+        for payment in payments {
+            let (destination, default_asset) = self.resolve_destination(&payment.destination).await?;
+            let asset = payment.asset.or(default_asset).unwrap_or(XELIS_ASSET);
+            let decimals = self.get_asset_decimals(&asset).await?;
+
+            let amount = from_coin(payment.amount.as_str(), decimals)
+                .filter(|amount| *amount > 0)
+                .ok_or_else(|| WalletError::InvalidBatchLine(payment.line, format!("invalid amount {}", payment.amount)))?;
+
+            transfers.push(TransferBuilder {
+                destination,
+                amount,
+                asset,
+                extra_data: None,
+                lock: None
+            });
+        }
+
+        Ok(transfers)
+    }
+
+    // Export the history of the account selected, one record per asset movement
+    // Timestamps are retrieved from the daemon when online, they are required to filter by time
+    // If a price provider is given, the fiat value of each record is added
+    pub async fn export_transactions(&self, range: HistoryRange, price_provider: Option<&dyn PriceProvider>) -> Result<Vec<HistoryRecord>, WalletError> {
+        trace!("export transactions");
+        let mainnet = self.network.is_mainnet();
+        let owner = self.get_public_key();
+        let mut records = Vec::new();
+        {
+            let storage = self.storage.read().await;
+            let entries = storage.get_filtered_transactions(None, range.min_topoheight, range.max_topoheight, true, true, true, true, None)?;
+            for entry in entries {
+                records.extend(export::to_records(entry, &owner, mainnet));
+            }
+        }
+
+        // Decimals are resolved once the storage lock is released as unknown assets are fetched
+        let mut decimals = HashMap::new();
+        for record in records.iter_mut() {
+            if let Some(asset) = record.asset.as_ref() {
+                if !decimals.contains_key(asset) {
+                    decimals.insert(asset.clone(), self.get_asset_decimals(asset).await?);
+                }
+                record.decimals = decimals[asset];
+            }
+        }
+
+        let filter_by_time = range.min_timestamp.is_some() || range.max_timestamp.is_some();
+        {
+            let network_handler = self.network_handler.lock().await;
+            match network_handler.as_ref() {
+                Some(network_handler) if network_handler.is_running().await => {
+                    let api = network_handler.get_api();
+                    let mut timestamps = HashMap::new();
+                    for record in records.iter_mut() {
+                        if !timestamps.contains_key(&record.topoheight) {
+                            let block = api.get_block_at_topoheight(record.topoheight).await?;
+                            timestamps.insert(record.topoheight, block.timestamp);
+                        }
+                        record.timestamp = timestamps.get(&record.topoheight).copied();
+                    }
+                },
+                _ if filter_by_time => return Err(WalletError::NotOnlineMode),
+                _ => debug!("Wallet is offline, history is exported without timestamps")
+            };
+        }
+
+        if filter_by_time {
+            records.retain(|record| record.timestamp.is_some_and(|timestamp| {
+                range.min_timestamp.map_or(true, |min| timestamp >= min) && range.max_timestamp.map_or(true, |max| timestamp <= max)
+            }));
+        }
+
+        records.sort_by(|a, b| a.topoheight.cmp(&b.topoheight).then_with(|| a.hash.cmp(&b.hash)));
+
+        if let Some(provider) = price_provider {
+            let mut prices = HashMap::new();
+            for record in records.iter_mut() {
+                let (Some(asset), Some(timestamp)) = (record.asset.as_ref(), record.timestamp) else {
+                    continue;
+                };
+                if record.amount == 0 {
+                    continue;
+                }
+
+                let key = (asset.clone(), timestamp);
+                let price = match prices.get(&key) {
+                    Some(price) => *price,
+                    None => {
+                        let price = provider.get_price(asset, timestamp).await?;
+                        prices.insert(key, price);
+                        price
+                    }
+                };
+
+                if let Some(price) = price {
+                    record.fiat_price = Some(price);
+                    record.fiat_value = Some(record.amount as f64 / 10u64.pow(record.decimals as u32) as f64 * price);
+                }
+            }
+        }
+
+        Ok(records)
+    }
+
+    // Write an encrypted backup file with the keys, the address book, the history of all the accounts and the settings
+    // The backup password can be different from the wallet password
+    pub async fn backup_to_file(&self, path: &str, password: String) -> Result<(), Error> {
+        trace!("backup to file {}", path);
+        let private_key = self.with_master_key(|master_key| master_key.clone())?;
+        let backup = {
+            let storage = self.storage.read().await;
+            let mut transactions = Vec::new();
+            for account in 0..storage.get_accounts_count()? {
+                transactions.push(storage.get_account_transactions(account)?);
+            }
+
+            WalletBackup {
+                network: self.network,
+                private_key,
+                seed_key: storage.get_seed_key()?,
+                selected_account: storage.get_account(),
+                alerts: storage.get_alerts_settings()?,
+                contacts: storage.get_contacts()?.into_iter().map(|entry| (entry.name, entry.contact)).collect(),
+                transactions
+            }
+        };
+
+        backup.write_to_file(path, password)?;
+        info!("Wallet backup written to {}", path);
+        Ok(())
+    }
+
     // Current account nonce for transactions
     // Nonce is used against replay attacks on-chain
     pub async fn get_nonce(&self) -> u64 {
@@ -933,8 +2796,31 @@ impl XSWDPermissionHandler for Arc<Wallet> {
         Err(WalletError::NoHandlerAvailable.into())
     }
 
-    async fn get_public_key(&self) -> Result<&DecompressedPublicKey, Error> {
-        Ok(self.keypair.get_public_key())
+    async fn get_public_key(&self) -> Result<DecompressedPublicKey, Error> {
+        Ok(self.get_account().get_public_key().decompress()?)
+    }
+}
+
+#[cfg(feature = "api_server")]
+#[async_trait]
+impl WalletMetricsHandler for Arc<Wallet> {
+    async fn write_metrics(&self, out: &mut String) {
+        let (synced_topoheight, pending_transactions) = {
+            let storage = self.storage.read().await;
+            let pending_transactions = storage.get_pending_transactions().map(|transactions| transactions.len()).unwrap_or(0);
+            (storage.get_synced_topoheight().unwrap_or(0), pending_transactions)
+        };
+
+        // Daemon topoheight is the last one seen while syncing
+        let (online, daemon_topoheight) = match self.network_handler.lock().await.as_ref() {
+            Some(network_handler) if network_handler.is_running().await => (true, network_handler.get_daemon_topoheight()),
+            _ => (false, 0)
+        };
+
+        write_gauge(out, "online", "Whether the wallet is connected to a daemon", online as u64);
+        write_gauge(out, "synced_topoheight", "Topoheight synced by the wallet", synced_topoheight);
+        write_gauge(out, "daemon_topoheight", "Topoheight of the daemon", daemon_topoheight);
+        write_gauge(out, "pending_transactions", "Transactions built by the wallet and not executed yet", pending_transactions as u64);
     }
 }
 
@@ -0,0 +1,226 @@
+use hmac::{Hmac, Mac};
+use indexmap::IndexMap;
+use log::{debug, error, warn};
+use serde::Serialize;
+use sha2::Sha256;
+use xelis_common::{
+    api::wallet::{NotifyEvent, WebhookNotification},
+    serializer::{
+        Reader,
+        ReaderError,
+        Serializer,
+        Writer
+    },
+    time::{
+        get_current_time_in_millis,
+        TimestampSeconds
+    }
+};
+use crate::config::{
+    MAX_WEBHOOK_ATTEMPTS,
+    MAX_WEBHOOK_QUEUE_SIZE,
+    MAX_WEBHOOK_RETRY_DELAY_SECS,
+    WEBHOOK_RETRY_DELAY_SECS
+};
+
+// URLs notified of the wallet events and the secret used to sign them
+// This is persisted in the wallet storage
+#[derive(Debug, Clone, Default, Serializer)]
+pub struct WebhooksSettings {
+    urls: Vec<String>,
+    secret: String
+}
+
+// A notification waiting to be delivered to an URL
+#[derive(Debug, Clone)]
+pub struct WebhookDelivery {
+    pub url: String,
+    // JSON body sent, it is signed at each attempt
+    pub body: String,
+    // Failed attempts until now
+    pub attempts: u8,
+    // Time in seconds before the next attempt
+    pub next_attempt: TimestampSeconds
+}
+
+impl Serializer for WebhookDelivery {
+    fn read(reader: &mut Reader) -> Result<Self, ReaderError> {
+        let url = reader.read_string()?;
+        // The body can be bigger than a serialized String
+        let len = reader.read_u32()?;
+        let body = reader.read_string_with_size(len as usize)?;
+        let attempts = reader.read_u8()?;
+        let next_attempt = reader.read_u64()?;
+        Ok(Self {
+            url,
+            body,
+            attempts,
+            next_attempt
+        })
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        writer.write_string(&self.url);
+        writer.write_u32(&(self.body.len() as u32));
+        writer.write_bytes(self.body.as_bytes());
+        writer.write_u8(self.attempts);
+        writer.write_u64(&self.next_attempt);
+    }
+
+    fn size(&self) -> usize {
+        self.url.size() + 4 + self.body.len() + 1 + 8
+    }
+}
+
+// Notifications not delivered yet, they are retried until MAX_WEBHOOK_ATTEMPTS
+// This is persisted in the wallet storage
+#[derive(Debug, Clone, Default, Serializer)]
+pub struct WebhookQueue {
+    // Id of the next notification created
+    next_notification_id: u64,
+    // Id of the next delivery queued
+    next_delivery_id: u64,
+    deliveries: IndexMap<u64, WebhookDelivery>
+}
+
+// Queue the notifications of the wallet events for each webhook
+// Deliveries are done by the wallet outside of the sync
+pub struct WebhookManager {
+    settings: WebhooksSettings,
+    queue: WebhookQueue,
+    // A delivery task is currently running
+    delivering: bool
+}
+
+impl WebhookManager {
+    pub fn new(settings: WebhooksSettings, queue: WebhookQueue) -> Self {
+        Self {
+            settings,
+            queue,
+            delivering: false
+        }
+    }
+
+    pub fn get_settings(&self) -> &WebhooksSettings {
+        &self.settings
+    }
+
+    pub fn get_queue(&self) -> &WebhookQueue {
+        &self.queue
+    }
+
+    pub fn get_urls(&self) -> &Vec<String> {
+        &self.settings.urls
+    }
+
+    // Notifications waiting to be delivered
+    pub fn get_pending(&self) -> usize {
+        self.queue.deliveries.len()
+    }
+
+    // Replace the URLs and the secret
+    // Pending notifications of the URLs removed are dropped
+    pub fn set_settings(&mut self, urls: Vec<String>, secret: String) {
+        self.queue.deliveries.retain(|_, delivery| urls.contains(&delivery.url));
+        self.settings = WebhooksSettings {
+            urls,
+            secret
+        };
+    }
+
+    // Queue a notification of the event for each URL
+    pub fn enqueue<T: Serialize>(&mut self, event: NotifyEvent, data: &T) {
+        if self.settings.urls.is_empty() {
+            return
+        }
+
+        let notification = WebhookNotification {
+            id: self.queue.next_notification_id,
+            event,
+            timestamp: get_current_time_in_millis(),
+            data
+        };
+
+        let body = match serde_json::to_string(&notification) {
+            Ok(body) => body,
+            Err(e) => {
+                error!("Error while serializing webhook notification: {}", e);
+                return
+            }
+        };
+        self.queue.next_notification_id += 1;
+
+        for url in self.settings.urls.iter() {
+            if self.queue.deliveries.len() >= MAX_WEBHOOK_QUEUE_SIZE {
+                if let Some((id, delivery)) = self.queue.deliveries.shift_remove_index(0) {
+                    warn!("Webhook queue is full, dropping delivery {} to {}", id, delivery.url);
+                }
+            }
+
+            let id = self.queue.next_delivery_id;
+            self.queue.next_delivery_id += 1;
+            self.queue.deliveries.insert(id, WebhookDelivery {
+                url: url.clone(),
+                body: body.clone(),
+                attempts: 0,
+                next_attempt: 0
+            });
+        }
+    }
+
+    // Start a delivery of the notifications due with the secret to sign them
+    // Returns None if a delivery is already running or nothing is due
+    pub fn start_delivery(&mut self, timestamp: TimestampSeconds) -> Option<(String, Vec<(u64, WebhookDelivery)>)> {
+        if self.delivering {
+            return None
+        }
+
+        let due: Vec<_> = self.queue.deliveries.iter()
+            .filter(|(_, delivery)| delivery.next_attempt <= timestamp)
+            .map(|(id, delivery)| (*id, delivery.clone()))
+            .collect();
+
+        if due.is_empty() {
+            return None
+        }
+
+        self.delivering = true;
+        Some((self.settings.secret.clone(), due))
+    }
+
+    // Remove the deliveries done and schedule the failed ones again
+    // The delay is doubled on each failed attempt
+    pub fn end_delivery(&mut self, results: Vec<(u64, bool)>, timestamp: TimestampSeconds) {
+        self.delivering = false;
+        for (id, success) in results {
+            if success {
+                self.queue.deliveries.shift_remove(&id);
+                continue;
+            }
+
+            let Some(delivery) = self.queue.deliveries.get_mut(&id) else {
+                continue;
+            };
+
+            delivery.attempts += 1;
+            if delivery.attempts >= MAX_WEBHOOK_ATTEMPTS {
+                warn!("Delivery {} to {} failed {} times, dropping it", id, delivery.url, delivery.attempts);
+                self.queue.deliveries.shift_remove(&id);
+                continue;
+            }
+
+            let delay = WEBHOOK_RETRY_DELAY_SECS.saturating_mul(1u64 << (delivery.attempts - 1)).min(MAX_WEBHOOK_RETRY_DELAY_SECS);
+            debug!("Delivery {} to {} will be retried in {}s", id, delivery.url, delay);
+            delivery.next_attempt = timestamp + delay;
+        }
+    }
+}
+
+// HMAC-SHA256 of the body with the secret, in hex
+// Receivers must compute it with the same secret to authenticate the notification
+pub fn sign(secret: &str, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC can take a key of any size");
+    mac.update(body.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}